@@ -0,0 +1,70 @@
+//! The cviz parser and renderers as a WebAssembly component.
+//!
+//! Built for `wasm32-wasip2`, this exports the `cosmonic:cviz/graphs`
+//! interface (see `wit/cviz.wit`) so web UIs and other hosts can parse and
+//! draw compositions without shelling out to the CLI:
+//!
+//! ```text
+//! cargo build -p cviz-component --target wasm32-wasip2 --release
+//! ```
+
+use cviz::output::{self, json, DetailLevel, Direction, OutputFormat, RenderOptions};
+use cviz::parse::component::parse_component;
+use cviz::parse::json::parse_json_str;
+use exports::cosmonic::cviz::graphs::{Detail, Format, Guest};
+
+wit_bindgen::generate!({
+    world: "cviz",
+    path: "wit",
+});
+
+struct Cviz;
+
+impl Guest for Cviz {
+    fn parse(component: Vec<u8>) -> Result<String, String> {
+        let graph = parse_component(&component).map_err(|e| e.to_string())?;
+        json::generate_json(&graph, false).map_err(|e| e.to_string())
+    }
+
+    fn render(graph_json: String, format: Format, detail: Detail) -> Result<String, String> {
+        let graph = parse_json_str(&graph_json).map_err(|e| e.to_string())?;
+        let detail = match detail {
+            Detail::HandlerChain => DetailLevel::HandlerChain,
+            Detail::Chains => DetailLevel::Chains,
+            Detail::AllInterfaces => DetailLevel::AllInterfaces,
+            Detail::Full => DetailLevel::Full,
+        };
+        let format = match format {
+            Format::Ascii => OutputFormat::Ascii,
+            Format::Mermaid => OutputFormat::Mermaid,
+            Format::Json => OutputFormat::Json,
+            Format::JsonPretty => OutputFormat::JsonPretty,
+        };
+        let opts = RenderOptions::new(true);
+        output::render(&graph, format, detail, Direction::LeftToRight, &opts)
+            .map_err(|e| e.to_string())
+    }
+}
+
+// The exported symbol names only link on wasm targets.
+#[cfg(target_arch = "wasm32")]
+export!(Cviz);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &[u8] = include_bytes!("../../tests/fixtures/composed-multiple.wasm");
+
+    #[test]
+    fn test_parse_then_render() {
+        let graph = Cviz::parse(FIXTURE.to_vec()).unwrap();
+        assert!(graph.contains("\"nodes\""));
+        let diagram = Cviz::render(graph, Format::Mermaid, Detail::AllInterfaces).unwrap();
+        assert!(diagram.contains("graph LR"), "{}", diagram);
+
+        let err = Cviz::parse(b"\0asm\x0d\0\x01\0\x01".to_vec()).unwrap_err();
+        assert!(err.contains("offset"), "{}", err);
+        assert!(Cviz::render("{}".to_string(), Format::Ascii, Detail::Full).is_err());
+    }
+}
@@ -0,0 +1,53 @@
+/*
+ * C interface to the cviz parser and renderers.
+ *
+ * Every function returns CVIZ_OK and stores its result in *out, or returns
+ * one of the CVIZ_ERROR_* codes and stores an error message there instead.
+ * Either way *out is a NUL-terminated UTF-8 string owned by the caller, to
+ * be released with cviz_free.  The functions keep no state between calls
+ * and may be called from several threads at once.
+ */
+
+#ifndef CVIZ_H
+#define CVIZ_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define CVIZ_OK 0
+/* A NULL pointer, or a string that is not UTF-8 or names no format or detail level. */
+#define CVIZ_ERROR_INVALID_ARGUMENT 1
+/* The component binary or JSON graph could not be read. */
+#define CVIZ_ERROR_PARSE 2
+/* The graph could not be rendered. */
+#define CVIZ_ERROR_RENDER 3
+/* cviz hit a bug; the message says where. */
+#define CVIZ_ERROR_INTERNAL 4
+
+/*
+ * Parse the `len` bytes of a .wasm component at `data` into a JSON graph,
+ * as written by `cviz --format json`.
+ */
+int32_t cviz_parse(const uint8_t *data, size_t len, char **out);
+
+/*
+ * Draw a JSON graph returned by cviz_parse (or saved by `cviz`).
+ *
+ * `format` is "ascii", "mermaid", "json" or "json-pretty" and `detail` is
+ * "handler-chain", "chains", "all-interfaces" or "full", as on the command
+ * line.  NULL picks "mermaid" and "handler-chain".
+ */
+int32_t cviz_render(const char *graph_json, const char *format, const char *detail, char **out);
+
+/* Release a string returned through `out`.  NULL is ignored. */
+void cviz_free(char *string);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* CVIZ_H */
@@ -0,0 +1,217 @@
+//! A C ABI for the cviz parser and renderers.
+//!
+//! Builds as `libcviz_ffi` (shared and static) for tools outside Rust, such
+//! as Go programs and IDE plugins, to parse components and draw diagrams
+//! through `include/cviz.h` rather than by running the CLI.  Results and
+//! error messages are handed over as C strings the caller frees with
+//! [`cviz_free`]; panics are caught at the boundary and reported as
+//! [`CVIZ_ERROR_INTERNAL`].
+
+use cviz::output::{self, json, DetailLevel, Direction, OutputFormat, RenderOptions};
+use cviz::parse::component::parse_component;
+use cviz::parse::json::parse_json_str;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+
+pub const CVIZ_OK: i32 = 0;
+pub const CVIZ_ERROR_INVALID_ARGUMENT: i32 = 1;
+pub const CVIZ_ERROR_PARSE: i32 = 2;
+pub const CVIZ_ERROR_RENDER: i32 = 3;
+pub const CVIZ_ERROR_INTERNAL: i32 = 4;
+
+/// A failed call: the status code returned and the message handed back.
+struct Failure(i32, String);
+
+fn invalid(message: impl Into<String>) -> Failure {
+    Failure(CVIZ_ERROR_INVALID_ARGUMENT, message.into())
+}
+
+/// Run `f`, store its result or error message in `*out` and return the
+/// status code.
+///
+/// # Safety
+///
+/// `out` must be null or valid for a pointer-sized write.
+unsafe fn call(out: *mut *mut c_char, f: impl FnOnce() -> Result<String, Failure>) -> i32 {
+    if out.is_null() {
+        return CVIZ_ERROR_INVALID_ARGUMENT;
+    }
+    let (status, message) = match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(result)) => (CVIZ_OK, result),
+        Ok(Err(Failure(status, message))) => (status, message),
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            (CVIZ_ERROR_INTERNAL, format!("cviz panicked: {}", message))
+        }
+    };
+    // Names come from the component and may, in theory, hold a NUL
+    let string = CString::new(message).unwrap_or_else(|e| {
+        let end = e.nul_position();
+        let mut bytes = e.into_vec();
+        bytes.truncate(end);
+        CString::new(bytes).expect("truncated at the first NUL")
+    });
+    *out = string.into_raw();
+    status
+}
+
+/// # Safety
+///
+/// `s`, when not null, must point to a NUL-terminated string.
+unsafe fn optional_str<'a>(s: *const c_char, what: &str) -> Result<Option<&'a str>, Failure> {
+    if s.is_null() {
+        return Ok(None);
+    }
+    CStr::from_ptr(s)
+        .to_str()
+        .map(Some)
+        .map_err(|_| invalid(format!("{} is not valid UTF-8", what)))
+}
+
+/// Parse the `len` bytes of a component at `data` into a JSON graph.
+///
+/// # Safety
+///
+/// `data` must be valid for reads of `len` bytes (or may be null when
+/// `len` is 0) and `out` must be valid for a pointer-sized write.
+#[no_mangle]
+pub unsafe extern "C" fn cviz_parse(data: *const u8, len: usize, out: *mut *mut c_char) -> i32 {
+    call(out, || {
+        let bytes = match (data.is_null(), len) {
+            (true, 0) => &[][..],
+            (true, _) => return Err(invalid("data is null")),
+            (false, _) => std::slice::from_raw_parts(data, len),
+        };
+        let graph = parse_component(bytes).map_err(|e| Failure(CVIZ_ERROR_PARSE, e.to_string()))?;
+        json::generate_json(&graph, false).map_err(|e| Failure(CVIZ_ERROR_RENDER, e.to_string()))
+    })
+}
+
+/// Draw a JSON graph in `format` at `detail` (`mermaid` and
+/// `handler-chain` when null).
+///
+/// # Safety
+///
+/// `graph_json`, and `format` and `detail` when not null, must point to
+/// NUL-terminated strings, and `out` must be valid for a pointer-sized
+/// write.
+#[no_mangle]
+pub unsafe extern "C" fn cviz_render(
+    graph_json: *const c_char,
+    format: *const c_char,
+    detail: *const c_char,
+    out: *mut *mut c_char,
+) -> i32 {
+    call(out, || {
+        let graph_json =
+            optional_str(graph_json, "graph_json")?.ok_or_else(|| invalid("graph_json is null"))?;
+        let format: OutputFormat = optional_str(format, "format")?
+            .unwrap_or("mermaid")
+            .parse()
+            .map_err(invalid)?;
+        let detail: DetailLevel = optional_str(detail, "detail")?
+            .unwrap_or("handler-chain")
+            .parse()
+            .map_err(invalid)?;
+
+        let graph =
+            parse_json_str(graph_json).map_err(|e| Failure(CVIZ_ERROR_PARSE, e.to_string()))?;
+        let opts = RenderOptions::new(true);
+        output::render(&graph, format, detail, Direction::LeftToRight, &opts)
+            .map_err(|e| Failure(CVIZ_ERROR_RENDER, e.to_string()))
+    })
+}
+
+/// Release a string returned by [`cviz_parse`] or [`cviz_render`].
+///
+/// # Safety
+///
+/// `string` must be null or a string returned through `out` by this
+/// library that has not been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn cviz_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ptr;
+
+    const FIXTURE: &[u8] = include_bytes!("../../tests/fixtures/composed-multiple.wasm");
+
+    /// Take ownership of a string handed back by the library.
+    unsafe fn take(out: *mut c_char) -> String {
+        let s = CStr::from_ptr(out).to_str().unwrap().to_string();
+        cviz_free(out);
+        s
+    }
+
+    #[test]
+    fn test_parse_then_render() {
+        unsafe {
+            let mut out = ptr::null_mut();
+            assert_eq!(
+                cviz_parse(FIXTURE.as_ptr(), FIXTURE.len(), &mut out),
+                CVIZ_OK
+            );
+            let graph = CString::new(take(out)).unwrap();
+
+            let mut out = ptr::null_mut();
+            let status = cviz_render(graph.as_ptr(), ptr::null(), ptr::null(), &mut out);
+            assert_eq!(status, CVIZ_OK);
+            assert!(take(out).contains("graph LR"));
+
+            let format = CString::new("ascii").unwrap();
+            let detail = CString::new("full").unwrap();
+            let mut out = ptr::null_mut();
+            let status = cviz_render(graph.as_ptr(), format.as_ptr(), detail.as_ptr(), &mut out);
+            assert_eq!(status, CVIZ_OK);
+            assert!(!take(out).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_errors_are_reported() {
+        unsafe {
+            let mut out = ptr::null_mut();
+            let bytes = b"\0asm\x0d\0\x01\0\x01";
+            assert_eq!(
+                cviz_parse(bytes.as_ptr(), bytes.len(), &mut out),
+                CVIZ_ERROR_PARSE
+            );
+            assert!(take(out).contains("offset"));
+
+            let mut out = ptr::null_mut();
+            assert_eq!(
+                cviz_parse(ptr::null(), 4, &mut out),
+                CVIZ_ERROR_INVALID_ARGUMENT
+            );
+            assert_eq!(take(out), "data is null");
+
+            let graph = CString::new("{}").unwrap();
+            let format = CString::new("svg").unwrap();
+            let mut out = ptr::null_mut();
+            let status = cviz_render(graph.as_ptr(), format.as_ptr(), ptr::null(), &mut out);
+            assert_eq!(status, CVIZ_ERROR_INVALID_ARGUMENT);
+            assert!(take(out).contains("svg"));
+
+            let mut out = ptr::null_mut();
+            let status = cviz_render(graph.as_ptr(), ptr::null(), ptr::null(), &mut out);
+            assert_eq!(status, CVIZ_ERROR_PARSE);
+            take(out);
+
+            assert_eq!(
+                cviz_parse(FIXTURE.as_ptr(), FIXTURE.len(), ptr::null_mut()),
+                CVIZ_ERROR_INVALID_ARGUMENT
+            );
+            cviz_free(ptr::null_mut());
+        }
+    }
+}
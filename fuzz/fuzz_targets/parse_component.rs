@@ -0,0 +1,16 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(
+    init: {
+        // libfuzzer-sys aborts on any panic, even one cviz catches and
+        // reports as a parse error.  Put the default hook back, so only a
+        // panic escaping the parser counts as a crash.
+        drop(std::panic::take_hook());
+    },
+    |data: &[u8]| {
+        let _ = cviz::parse::component::parse_component(data);
+        let _ = cviz::parse::component::parse_component_imports(data);
+    }
+);
@@ -0,0 +1,13 @@
+// Build the addon with cargo and copy it next to index.js as cviz.node.
+const { execFileSync } = require('node:child_process')
+const fs = require('node:fs')
+const path = require('node:path')
+
+execFileSync('cargo', ['build', '-p', 'cviz-node', '--release'], { stdio: 'inherit' })
+
+const library = {
+  win32: 'cviz_node.dll',
+  darwin: 'libcviz_node.dylib',
+}[process.platform] ?? 'libcviz_node.so'
+const target = process.env.CARGO_TARGET_DIR ?? path.join(__dirname, '..', 'target')
+fs.copyFileSync(path.join(target, 'release', library), path.join(__dirname, 'cviz.node'))
@@ -0,0 +1,14 @@
+/** Parse a `.wasm` component into its JSON graph, as written by `cviz --format json`. */
+export declare function parse(component: Buffer): string
+
+/** `parse` on the libuv thread pool, for large components that would otherwise hold up the event loop. */
+export declare function parseAsync(component: Buffer): Promise<string>
+
+/**
+ * Draw a JSON graph returned by `parse` (or saved by `cviz`).
+ *
+ * `format` is `ascii`, `mermaid`, `json` or `json-pretty` and `detail` is `handler-chain`,
+ * `chains`, `all-interfaces` or `full`, as on the command line; `mermaid` and `handler-chain`
+ * when left out.
+ */
+export declare function render(graphJson: string, format?: string, detail?: string): string
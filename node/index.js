@@ -0,0 +1,2 @@
+// The addon built by `npm run build`.
+module.exports = require('./cviz.node')
@@ -0,0 +1,79 @@
+//! Node.js bindings for the cviz parser and renderers.
+//!
+//! Built with napi-rs into a native addon, so dashboards and editor
+//! extensions can draw compositions in-process rather than by spawning the
+//! CLI.  `index.d.ts` declares what JavaScript sees; failures are thrown as
+//! `Error`s carrying cviz's message.
+
+use cviz::output::{self, DetailLevel, Direction, OutputFormat, RenderOptions};
+use cviz::parse::component::parse_component;
+use cviz::parse::json::parse_json_str;
+use napi::bindgen_prelude::{AsyncTask, Buffer};
+use napi::{Env, Error, Status, Task};
+use napi_derive::napi;
+
+fn failure(e: cviz::CvizError) -> Error {
+    Error::new(Status::GenericFailure, e.to_string())
+}
+
+fn option<T: std::str::FromStr<Err = String>>(
+    value: Option<String>,
+    default: &str,
+) -> napi::Result<T> {
+    value
+        .as_deref()
+        .unwrap_or(default)
+        .parse()
+        .map_err(|e| Error::new(Status::InvalidArg, e))
+}
+
+fn parse_to_json(component: &[u8]) -> napi::Result<String> {
+    let graph = parse_component(component).map_err(failure)?;
+    output::json::generate_json(&graph, false).map_err(failure)
+}
+
+/// Parse a `.wasm` component into its JSON graph, as written by
+/// `cviz --format json`.
+#[napi]
+pub fn parse(component: Buffer) -> napi::Result<String> {
+    parse_to_json(&component)
+}
+
+/// [`parse`] on the libuv thread pool, for large components that would
+/// otherwise hold up the event loop.  Resolves to the JSON graph.
+#[napi]
+pub fn parse_async(component: Buffer) -> AsyncTask<ParseTask> {
+    AsyncTask::new(ParseTask(component.to_vec()))
+}
+
+pub struct ParseTask(Vec<u8>);
+
+impl Task for ParseTask {
+    type Output = String;
+    type JsValue = String;
+
+    fn compute(&mut self) -> napi::Result<String> {
+        parse_to_json(&self.0)
+    }
+
+    fn resolve(&mut self, _env: Env, output: String) -> napi::Result<String> {
+        Ok(output)
+    }
+}
+
+/// Draw a JSON graph returned by [`parse`] (or saved by `cviz`).
+///
+/// `format` and `detail` take the `--format` and `--detail` values of the
+/// command line, `mermaid` and `handler-chain` when left out.
+#[napi]
+pub fn render(
+    graph_json: String,
+    format: Option<String>,
+    detail: Option<String>,
+) -> napi::Result<String> {
+    let format: OutputFormat = option(format, "mermaid")?;
+    let detail: DetailLevel = option(detail, "handler-chain")?;
+    let graph = parse_json_str(&graph_json).map_err(failure)?;
+    let opts = RenderOptions::new(true);
+    output::render(&graph, format, detail, Direction::LeftToRight, &opts).map_err(failure)
+}
@@ -0,0 +1,21 @@
+const assert = require('node:assert')
+const fs = require('node:fs')
+const path = require('node:path')
+const test = require('node:test')
+const cviz = require('..')
+
+const fixture = fs.readFileSync(path.join(__dirname, '..', '..', 'tests', 'fixtures', 'composed-multiple.wasm'))
+
+test('parses and renders a component', async () => {
+  const graph = cviz.parse(fixture)
+  assert.ok(JSON.parse(graph).nodes.length > 0)
+  assert.strictEqual(await cviz.parseAsync(fixture), graph)
+  assert.match(cviz.render(graph), /^graph LR/m)
+  assert.match(cviz.render(graph, 'ascii', 'full'), /srv/)
+})
+
+test('throws on bad input', async () => {
+  assert.throws(() => cviz.parse(Buffer.from('\0asm')), /end-of-file/)
+  await assert.rejects(cviz.parseAsync(Buffer.from('nope')))
+  assert.throws(() => cviz.render('{}', 'svg'), /Invalid output format: svg/)
+})
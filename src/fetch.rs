@@ -0,0 +1,927 @@
+//! Fetching components from HTTP(S) URLs, OCI registries and, with the
+//! `warg` feature, warg registries.
+//!
+//! Only the subset of the distribution API needed to pull a single wasm
+//! artifact is implemented: resolve the manifest, pick the wasm layer and
+//! download its blob.  When the registry asks for authentication, the
+//! credentials `docker login` stored (directly or through a credential
+//! helper) are used, falling back to an anonymous token.  Warg packages are
+//! pulled with `warg-client`, which verifies the package log and keeps the
+//! content in its own cache.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::io::{IsTerminal, Read, Write};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+/// Prefix that marks a FILE argument as an OCI reference.
+pub const OCI_SCHEME: &str = "oci://";
+
+/// Default for [`FetchOptions::max_size`].
+pub const DEFAULT_MAX_SIZE: u64 = 512 * 1024 * 1024;
+
+/// Limits and feedback applied to every download.
+#[derive(Debug, Clone, Copy)]
+pub struct FetchOptions {
+    /// Downloads larger than this many bytes are aborted.
+    pub max_size: u64,
+    /// Draw a progress line on stderr while downloading.
+    pub progress: bool,
+}
+
+impl Default for FetchOptions {
+    fn default() -> Self {
+        Self {
+            max_size: DEFAULT_MAX_SIZE,
+            progress: std::io::stderr().is_terminal(),
+        }
+    }
+}
+
+/// Whether a FILE argument is an `http://` or `https://` URL.
+pub fn is_url(s: &str) -> bool {
+    s.starts_with("https://") || s.starts_with("http://")
+}
+
+/// Download the body of `url`.
+pub fn download(url: &str, options: &FetchOptions) -> Result<Vec<u8>> {
+    let response = agent().get(url).call().map_err(|e| status_error(url, e))?;
+    read_body(response, url, options)
+}
+
+/// Manifest types we can read, in order of preference.
+const MANIFEST_TYPES: &str = "application/vnd.oci.image.manifest.v1+json, \
+     application/vnd.docker.distribution.manifest.v2+json;q=0.9, \
+     application/vnd.oci.image.index.v1+json;q=0.5";
+
+/// Layer type used for wasm components by `wkg` and `wash`.
+const WASM_LAYER_TYPE: &str = "application/wasm";
+
+/// A parsed `oci://registry/repository[:tag|@digest]` reference.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OciReference {
+    pub registry: String,
+    pub repository: String,
+    /// Tag or `sha256:` digest; `latest` if the reference named neither.
+    pub reference: String,
+}
+
+impl OciReference {
+    /// Parse a reference with or without the `oci://` prefix.  As with
+    /// `docker pull`, a first path segment that does not look like a host
+    /// name means Docker Hub.
+    pub fn parse(s: &str) -> Result<Self> {
+        let s = s.strip_prefix(OCI_SCHEME).unwrap_or(s);
+        let (registry, rest) = match s.split_once('/') {
+            Some((host, rest)) if host.contains(['.', ':']) || host == "localhost" => {
+                (host.to_string(), rest)
+            }
+            _ => ("docker.io".to_string(), s),
+        };
+
+        let (repository, reference) = if let Some((repo, digest)) = rest.split_once('@') {
+            (repo, digest)
+        } else {
+            let last_slash = rest.rfind('/').map_or(0, |i| i + 1);
+            match rest[last_slash..].rfind(':') {
+                Some(i) => (&rest[..last_slash + i], &rest[last_slash + i + 1..]),
+                None => (rest, "latest"),
+            }
+        };
+        if repository.is_empty() || reference.is_empty() {
+            bail!("Invalid OCI reference: {}", s);
+        }
+
+        let repository = if registry == "docker.io" && !repository.contains('/') {
+            format!("library/{}", repository)
+        } else {
+            repository.to_string()
+        };
+        Ok(Self {
+            registry,
+            repository,
+            reference: reference.to_string(),
+        })
+    }
+
+    /// Base URL of the repository's distribution API.
+    fn api_base(&self) -> String {
+        let (scheme, host) = match self.registry.as_str() {
+            "docker.io" => ("https", "registry-1.docker.io"),
+            // Local development registries rarely have certificates.
+            host if host.starts_with("localhost") || host.starts_with("127.0.0.1") => {
+                ("http", host)
+            }
+            host => ("https", host),
+        };
+        format!("{}://{}/v2/{}", scheme, host, self.repository)
+    }
+}
+
+impl std::fmt::Display for OciReference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sep = if self.reference.contains(':') {
+            '@'
+        } else {
+            ':'
+        };
+        write!(
+            f,
+            "{}/{}{}{}",
+            self.registry, self.repository, sep, self.reference
+        )
+    }
+}
+
+/// Download the wasm component that `reference` points at.
+pub fn pull(reference: &OciReference, options: &FetchOptions) -> Result<Vec<u8>> {
+    let client = Registry::new(reference);
+    let manifest: Manifest = client
+        .get(
+            &format!("manifests/{}", reference.reference),
+            MANIFEST_TYPES,
+        )?
+        .into_json()
+        .context("Failed to decode manifest")?;
+    let layer = manifest.wasm_layer()?;
+    if layer.size > options.max_size {
+        bail!(
+            "Layer {} is {} bytes, over the {} byte limit",
+            layer.digest,
+            layer.size,
+            options.max_size
+        );
+    }
+
+    let response = client.get(&format!("blobs/{}", layer.digest), "*/*")?;
+    let bytes = read_body(response, &reference.to_string(), options)?;
+
+    let actual = format!("sha256:{}", hex::encode(Sha256::digest(&bytes)));
+    if layer.digest.starts_with("sha256:") && actual != layer.digest {
+        bail!(
+            "Layer digest mismatch: expected {}, got {}",
+            layer.digest,
+            actual
+        );
+    }
+    Ok(bytes)
+}
+
+/// Where [`resolve_package`] found a package.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PackageSource {
+    /// An OCI artifact, pulled with [`pull`].
+    Oci(OciReference),
+    /// A warg registry server, pulled from with [`pull_warg`].
+    Warg {
+        /// Base URL of the server.
+        url: String,
+        /// `namespace:name`.
+        package: String,
+        /// Exact version to pull, or the latest release if `None`.
+        version: Option<String>,
+    },
+}
+
+/// Resolve a `namespace:name[@version]` package published to `registry`
+/// (a domain such as `wasi.dev`) to where it can be pulled from.
+///
+/// The registry's `/.well-known/wasm-pkg/registry.json` says where its
+/// packages live, following the convention used by `wkg`: an OCI
+/// repository, or a warg server for registries that only serve packages
+/// over the warg protocol.
+pub fn resolve_package(registry: &str, spec: &str) -> Result<PackageSource> {
+    let (package, version) = match spec.split_once('@') {
+        Some((package, version)) => (package, Some(version)),
+        None => (spec, None),
+    };
+    let Some((namespace, name)) = package.split_once(':') else {
+        bail!(
+            "Invalid package name: {} (expected namespace:name)",
+            package
+        );
+    };
+
+    let url = format!("https://{}/.well-known/wasm-pkg/registry.json", registry);
+    let metadata: RegistryMetadata = agent()
+        .get(&url)
+        .call()
+        .map_err(|e| status_error(&url, e))?
+        .into_json()
+        .with_context(|| format!("Failed to decode {}", url))?;
+    Ok(metadata.package_source(registry, namespace, name, version))
+}
+
+/// Pull `package` (`namespace:name`) at `version`, or its latest release,
+/// from the warg registry at `url`, returning the path of the component in
+/// the warg client's content cache.
+///
+/// The client uses the warg configuration `warg login` writes, and checks
+/// the package log against the registry's checkpoints before downloading.
+#[cfg(feature = "warg")]
+pub fn pull_warg(
+    url: &str,
+    package: &str,
+    version: Option<&str>,
+    options: &FetchOptions,
+) -> Result<PathBuf> {
+    let name = warg_protocol::registry::PackageName::new(package)
+        .with_context(|| format!("Invalid package name: {}", package))?;
+    let requirement = match version {
+        Some(version) => semver::VersionReq::parse(&format!("={}", version))
+            .with_context(|| format!("Invalid version: {}", version))?,
+        None => semver::VersionReq::STAR,
+    };
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Failed to start the warg client")?;
+    let download = runtime.block_on(async {
+        let client = warg_client::FileSystemClient::new_with_default_config(Some(url)).await?;
+        client.download(&name, &requirement).await
+    })?;
+    let download = download.with_context(|| match version {
+        Some(version) => format!("{} has no release {}", package, version),
+        None => format!("{} has no releases", package),
+    })?;
+
+    let size = std::fs::metadata(&download.path)
+        .with_context(|| format!("Failed to read {}", download.path.display()))?
+        .len();
+    if size > options.max_size {
+        bail!(
+            "{}@{} is {} bytes, over the {} byte limit",
+            package,
+            download.version,
+            size,
+            options.max_size
+        );
+    }
+    Ok(download.path)
+}
+
+/// Without the `warg` feature, explain how to get a build that can pull
+/// from a warg registry.
+#[cfg(not(feature = "warg"))]
+pub fn pull_warg(
+    url: &str,
+    _package: &str,
+    _version: Option<&str>,
+    _options: &FetchOptions,
+) -> Result<PathBuf> {
+    bail!(
+        "{} is a warg registry, and this build of cviz has no warg client; \
+         rebuild it with `--features warg`, or download the component with \
+         `wkg get` and pipe it to `cviz -`",
+        url
+    )
+}
+
+/// The registry metadata document served at
+/// `/.well-known/wasm-pkg/registry.json`, in both its current and legacy
+/// (flat) layouts.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RegistryMetadata {
+    preferred_protocol: Option<String>,
+    oci: Option<OciMetadata>,
+    warg: Option<WargMetadata>,
+    oci_registry: Option<String>,
+    oci_namespace_prefix: Option<String>,
+    warg_url: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OciMetadata {
+    registry: Option<String>,
+    namespace_prefix: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WargMetadata {
+    url: Option<String>,
+}
+
+impl RegistryMetadata {
+    fn package_source(
+        &self,
+        registry: &str,
+        namespace: &str,
+        name: &str,
+        version: Option<&str>,
+    ) -> PackageSource {
+        let oci = self.oci.as_ref();
+        let host = oci
+            .and_then(|o| o.registry.clone())
+            .or_else(|| self.oci_registry.clone());
+        let prefix = oci
+            .and_then(|o| o.namespace_prefix.clone())
+            .or_else(|| self.oci_namespace_prefix.clone());
+
+        let prefers_warg = self.preferred_protocol.as_deref() == Some("warg");
+        if host.is_none() && (prefers_warg || self.warg.is_some() || self.warg_url.is_some()) {
+            let url = self
+                .warg
+                .as_ref()
+                .and_then(|w| w.url.clone())
+                .or_else(|| self.warg_url.clone())
+                // Like the OCI section, a `warg` section without a URL
+                // means the domain serves it itself.
+                .unwrap_or_else(|| format!("https://{}", registry));
+            return PackageSource::Warg {
+                url,
+                package: format!("{}:{}", namespace, name),
+                version: version.map(str::to_string),
+            };
+        }
+
+        PackageSource::Oci(OciReference {
+            // An empty `oci` section means the domain is its own registry.
+            registry: host.unwrap_or_else(|| registry.to_string()),
+            repository: format!("{}{}/{}", prefix.unwrap_or_default(), namespace, name),
+            reference: version.unwrap_or("latest").to_string(),
+        })
+    }
+}
+
+/// A distribution API session for one repository.
+struct Registry {
+    agent: ureq::Agent,
+    base: String,
+    registry: String,
+    /// `Authorization` header value, once the registry has asked for one.
+    authorization: RefCell<Option<String>>,
+}
+
+impl Registry {
+    fn new(reference: &OciReference) -> Self {
+        Self {
+            agent: agent(),
+            base: reference.api_base(),
+            registry: reference.registry.clone(),
+            authorization: RefCell::new(None),
+        }
+    }
+
+    /// GET `path` under the repository, authenticating and retrying once if
+    /// the registry challenges the request.
+    fn get(&self, path: &str, accept: &str) -> Result<ureq::Response> {
+        let url = format!("{}/{}", self.base, path);
+        let request = || {
+            let req = self.agent.get(&url).set("Accept", accept);
+            match self.authorization.borrow().as_deref() {
+                Some(auth) => req.set("Authorization", auth),
+                None => req,
+            }
+        };
+
+        match request().call() {
+            Err(ureq::Error::Status(401, response)) if self.authorization.borrow().is_none() => {
+                let challenge = response.header("www-authenticate").unwrap_or_default();
+                let credentials = docker_credentials(&self.registry)?;
+                let authorization = self.authorize(challenge, credentials.as_ref())?;
+                *self.authorization.borrow_mut() = Some(authorization);
+                request().call().map_err(|e| match (e, &credentials) {
+                    (ureq::Error::Status(401 | 403, _), None) => login_hint(&self.registry),
+                    (e, _) => status_error(&url, e),
+                })
+            }
+            result => result.map_err(|e| status_error(&url, e)),
+        }
+    }
+
+    /// The `Authorization` header answering a `WWW-Authenticate` challenge.
+    fn authorize(&self, challenge: &str, credentials: Option<&Credentials>) -> Result<String> {
+        if let Some(bearer) = BearerChallenge::parse(challenge) {
+            return Ok(format!("Bearer {}", self.token(&bearer, credentials)?));
+        }
+        match credentials {
+            Some(credentials) if challenge.to_ascii_lowercase().starts_with("basic") => {
+                Ok(format!("Basic {}", credentials.basic()))
+            }
+            Some(_) => bail!("Unsupported registry authentication: {}", challenge),
+            None => Err(login_hint(&self.registry)),
+        }
+    }
+
+    fn token(
+        &self,
+        challenge: &BearerChallenge,
+        credentials: Option<&Credentials>,
+    ) -> Result<String> {
+        #[derive(Deserialize)]
+        struct TokenResponse {
+            token: Option<String>,
+            access_token: Option<String>,
+        }
+
+        let mut request = self.agent.get(&challenge.realm);
+        if let Some(service) = &challenge.service {
+            request = request.query("service", service);
+        }
+        if let Some(scope) = &challenge.scope {
+            request = request.query("scope", scope);
+        }
+        if let Some(credentials) = credentials {
+            request = request.set("Authorization", &format!("Basic {}", credentials.basic()));
+        }
+        let response: TokenResponse = request
+            .call()
+            .map_err(|e| match (e, credentials) {
+                (ureq::Error::Status(401 | 403, _), None) => login_hint(&self.registry),
+                (e, _) => status_error(&challenge.realm, e),
+            })?
+            .into_json()
+            .context("Failed to decode registry token")?;
+        response
+            .token
+            .or(response.access_token)
+            .context("Registry token response contained no token")
+    }
+}
+
+fn login_hint(registry: &str) -> anyhow::Error {
+    anyhow::anyhow!(
+        "{} requires authentication and no credentials were found; run `docker login {}`",
+        registry,
+        registry
+    )
+}
+
+/// A registry username and password (or token).
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Credentials {
+    username: String,
+    password: String,
+}
+
+impl Credentials {
+    /// The base64 payload of a `Basic` authorization header.
+    fn basic(&self) -> String {
+        BASE64.encode(format!("{}:{}", self.username, self.password))
+    }
+}
+
+/// Credentials for `registry` from the Docker client configuration
+/// (`$DOCKER_CONFIG/config.json`, or `~/.docker/config.json`), as written by
+/// `docker login`, `podman login --compat-auth-file` or `oras login`.
+fn docker_credentials(registry: &str) -> Result<Option<Credentials>> {
+    let dir = match std::env::var_os("DOCKER_CONFIG") {
+        Some(dir) => PathBuf::from(dir),
+        None => match std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE")) {
+            Some(home) => PathBuf::from(home).join(".docker"),
+            None => return Ok(None),
+        },
+    };
+    let path = dir.join("config.json");
+    let Ok(text) = std::fs::read_to_string(&path) else {
+        return Ok(None);
+    };
+    let config: DockerConfig = serde_json::from_str(&text)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+    Ok(config.credentials(registry, run_credential_helper))
+}
+
+/// The parts of Docker's `config.json` that locate credentials.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DockerConfig {
+    #[serde(default)]
+    auths: BTreeMap<String, AuthEntry>,
+    /// Credential helper used for every registry.
+    creds_store: Option<String>,
+    /// Per-registry credential helpers.
+    #[serde(default)]
+    cred_helpers: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct AuthEntry {
+    /// base64 of `username:password`.
+    auth: Option<String>,
+    username: Option<String>,
+    password: Option<String>,
+}
+
+impl DockerConfig {
+    /// Look up `registry` the way the Docker CLI does: a per-registry
+    /// helper, then inline `auths`, then the default helper.
+    /// `helper(name, server)` runs `docker-credential-<name>`.
+    fn credentials(
+        &self,
+        registry: &str,
+        helper: impl Fn(&str, &str) -> Option<Credentials>,
+    ) -> Option<Credentials> {
+        // Docker Hub credentials are stored under its legacy index URL.
+        let server = match registry {
+            "docker.io" => "https://index.docker.io/v1/",
+            host => host,
+        };
+        if let Some(name) = self.cred_helpers.get(registry) {
+            return helper(name, server);
+        }
+
+        let inline = self
+            .auths
+            .iter()
+            .find(|(key, _)| config_host(key) == registry)
+            .and_then(|(_, entry)| {
+                if let (Some(username), Some(password)) = (&entry.username, &entry.password) {
+                    return Some(Credentials {
+                        username: username.clone(),
+                        password: password.clone(),
+                    });
+                }
+                let decoded = BASE64.decode(entry.auth.as_deref()?.trim()).ok()?;
+                let (username, password) = std::str::from_utf8(&decoded).ok()?.split_once(':')?;
+                Some(Credentials {
+                    username: username.to_string(),
+                    password: password.to_string(),
+                })
+            });
+        inline.or_else(|| helper(self.creds_store.as_deref()?, server))
+    }
+}
+
+/// The registry host an `auths` key refers to: keys may be bare hosts or
+/// URLs such as `https://index.docker.io/v1/`.
+fn config_host(key: &str) -> &str {
+    let host = key
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .split('/')
+        .next()
+        .unwrap_or(key);
+    match host {
+        "index.docker.io" | "registry-1.docker.io" => "docker.io",
+        host => host,
+    }
+}
+
+/// Ask `docker-credential-<name>` for the credentials of `server`.  A
+/// missing helper or unknown server means no credentials.
+fn run_credential_helper(name: &str, server: &str) -> Option<Credentials> {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct HelperResponse {
+        username: String,
+        secret: String,
+    }
+
+    let mut child = Command::new(format!("docker-credential-{}", name))
+        .arg("get")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    child.stdin.take()?.write_all(server.as_bytes()).ok()?;
+    let output = child.wait_with_output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let response: HelperResponse = serde_json::from_slice(&output.stdout).ok()?;
+    Some(Credentials {
+        username: response.username,
+        password: response.secret,
+    })
+}
+
+fn agent() -> ureq::Agent {
+    ureq::AgentBuilder::new()
+        .user_agent(concat!("cviz/", env!("CARGO_PKG_VERSION")))
+        .build()
+}
+
+/// Read a response body, enforcing the size limit and reporting progress.
+fn read_body(response: ureq::Response, label: &str, options: &FetchOptions) -> Result<Vec<u8>> {
+    let total = response
+        .header("content-length")
+        .and_then(|len| len.parse::<u64>().ok());
+    if let Some(total) = total.filter(|&t| t > options.max_size) {
+        bail!(
+            "{} is {} bytes, over the {} byte limit",
+            label,
+            total,
+            options.max_size
+        );
+    }
+
+    let mut reader = Progress {
+        inner: response.into_reader().take(options.max_size + 1),
+        label,
+        total,
+        read: 0,
+        drawn: None,
+        enabled: options.progress,
+    };
+    let mut bytes = Vec::new();
+    let result = reader.read_to_end(&mut bytes);
+    reader.finish();
+    result.with_context(|| format!("Failed to download {}", label))?;
+    if bytes.len() as u64 > options.max_size {
+        bail!("{} is over the {} byte limit", label, options.max_size);
+    }
+    Ok(bytes)
+}
+
+/// A reader that redraws a one-line progress report on stderr.
+struct Progress<'a, R> {
+    inner: R,
+    label: &'a str,
+    total: Option<u64>,
+    read: u64,
+    drawn: Option<Instant>,
+    enabled: bool,
+}
+
+impl<R> Progress<'_, R> {
+    fn draw(&mut self) {
+        const MIB: f64 = 1024.0 * 1024.0;
+        let done = self.read as f64 / MIB;
+        let line = match self.total {
+            Some(total) if total > 0 => format!(
+                "{:.1} / {:.1} MiB ({}%)",
+                done,
+                total as f64 / MIB,
+                self.read * 100 / total
+            ),
+            _ => format!("{:.1} MiB", done),
+        };
+        eprint!("\r\x1b[2KDownloading {}: {}", self.label, line);
+        self.drawn = Some(Instant::now());
+    }
+
+    /// Clear the progress line, if one was drawn.
+    fn finish(&self) {
+        if self.drawn.is_some() {
+            eprint!("\r\x1b[2K");
+        }
+    }
+}
+
+impl<R: Read> Read for Progress<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.read += n as u64;
+        let due = self
+            .drawn
+            .is_none_or(|at| at.elapsed() >= Duration::from_millis(100));
+        if self.enabled && due {
+            self.draw();
+        }
+        Ok(n)
+    }
+}
+
+fn status_error(url: &str, err: ureq::Error) -> anyhow::Error {
+    match err {
+        ureq::Error::Status(status, response) => {
+            anyhow::anyhow!("GET {} failed: {} {}", url, status, response.status_text())
+        }
+        err => anyhow::Error::new(err).context(format!("GET {} failed", url)),
+    }
+}
+
+/// The parts of a `WWW-Authenticate: Bearer ...` header we need.
+#[derive(Debug, PartialEq, Eq)]
+struct BearerChallenge {
+    realm: String,
+    service: Option<String>,
+    scope: Option<String>,
+}
+
+impl BearerChallenge {
+    fn parse(header: &str) -> Option<Self> {
+        let (scheme, params) = header.trim().split_once(' ')?;
+        if !scheme.eq_ignore_ascii_case("bearer") {
+            return None;
+        }
+
+        let (mut realm, mut service, mut scope) = (None, None, None);
+        let mut rest = params.trim();
+        while let Some((key, after)) = rest.split_once('=') {
+            let (value, after) = match after.strip_prefix('"') {
+                Some(quoted) => quoted.split_once('"').unwrap_or((quoted, "")),
+                None => after.split_once(',').unwrap_or((after, "")),
+            };
+            match key.trim() {
+                "realm" => realm = Some(value.to_string()),
+                "service" => service = Some(value.to_string()),
+                "scope" => scope = Some(value.to_string()),
+                _ => {}
+            }
+            rest = after.trim_start_matches([',', ' ']);
+        }
+        Some(Self {
+            realm: realm?,
+            service,
+            scope,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Manifest {
+    #[serde(default)]
+    layers: Vec<Descriptor>,
+    /// Present when the reference resolved to an image index.
+    #[serde(default)]
+    manifests: Vec<Descriptor>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Descriptor {
+    media_type: String,
+    digest: String,
+    size: u64,
+}
+
+impl Manifest {
+    /// The layer holding the component: the `application/wasm` layer, or
+    /// the only layer if there is just one.
+    fn wasm_layer(&self) -> Result<&Descriptor> {
+        if self.layers.is_empty() && !self.manifests.is_empty() {
+            bail!("Reference is a multi-platform index; pin a specific manifest with @sha256:...");
+        }
+        if let Some(layer) = self.layers.iter().find(|l| l.media_type == WASM_LAYER_TYPE) {
+            return Ok(layer);
+        }
+        match self.layers.as_slice() {
+            [only] => Ok(only),
+            [] => bail!("Manifest has no layers"),
+            layers => {
+                let types: Vec<&str> = layers.iter().map(|l| l.media_type.as_str()).collect();
+                bail!("No {} layer among: {}", WASM_LAYER_TYPE, types.join(", "))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reference(registry: &str, repository: &str, reference: &str) -> OciReference {
+        OciReference {
+            registry: registry.to_string(),
+            repository: repository.to_string(),
+            reference: reference.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_reference() {
+        assert_eq!(
+            OciReference::parse("oci://ghcr.io/org/app:stable").unwrap(),
+            reference("ghcr.io", "org/app", "stable")
+        );
+        assert_eq!(
+            OciReference::parse("oci://localhost:5000/app").unwrap(),
+            reference("localhost:5000", "app", "latest")
+        );
+        assert_eq!(
+            OciReference::parse("ghcr.io/org/app@sha256:abc").unwrap(),
+            reference("ghcr.io", "org/app", "sha256:abc")
+        );
+        assert_eq!(
+            OciReference::parse("oci://hello:1.0").unwrap(),
+            reference("docker.io", "library/hello", "1.0")
+        );
+        assert!(OciReference::parse("oci://ghcr.io/org/app:").is_err());
+
+        assert_eq!(
+            OciReference::parse("oci://localhost:5000/app")
+                .unwrap()
+                .api_base(),
+            "http://localhost:5000/v2/app"
+        );
+    }
+
+    #[test]
+    fn test_parse_challenge() {
+        assert_eq!(
+            BearerChallenge::parse(
+                r#"Bearer realm="https://ghcr.io/token",service="ghcr.io",scope="repository:org/app:pull""#
+            ),
+            Some(BearerChallenge {
+                realm: "https://ghcr.io/token".to_string(),
+                service: Some("ghcr.io".to_string()),
+                scope: Some("repository:org/app:pull".to_string()),
+            })
+        );
+        assert_eq!(BearerChallenge::parse(r#"Basic realm="x""#), None);
+    }
+
+    #[test]
+    fn test_docker_config_credentials() {
+        let config: DockerConfig = serde_json::from_str(
+            r#"{
+                "auths": {
+                    "ghcr.io": { "auth": "dXNlcjpzM2NyM3Q=" },
+                    "https://index.docker.io/v1/": {},
+                    "quay.io": { "username": "robot", "password": "pw" }
+                },
+                "credsStore": "desktop",
+                "credHelpers": { "123.dkr.ecr.us-east-1.amazonaws.com": "ecr-login" }
+            }"#,
+        )
+        .unwrap();
+        let helper = |name: &str, server: &str| {
+            Some(Credentials {
+                username: name.to_string(),
+                password: server.to_string(),
+            })
+        };
+        let creds = |registry| config.credentials(registry, helper).unwrap();
+
+        assert_eq!(creds("ghcr.io").username, "user");
+        assert_eq!(creds("ghcr.io").password, "s3cr3t");
+        assert_eq!(creds("quay.io").username, "robot");
+        assert_eq!(
+            creds("123.dkr.ecr.us-east-1.amazonaws.com").username,
+            "ecr-login"
+        );
+        // Docker Hub's placeholder entry defers to the default store
+        let hub = creds("docker.io");
+        assert_eq!(hub.username, "desktop");
+        assert_eq!(hub.password, "https://index.docker.io/v1/");
+
+        assert_eq!(DockerConfig::default().credentials("ghcr.io", helper), None);
+    }
+
+    #[test]
+    fn test_registry_metadata() {
+        let current: RegistryMetadata = serde_json::from_str(
+            r#"{ "preferredProtocol": "oci",
+                 "oci": { "registry": "ghcr.io", "namespacePrefix": "webassembly/" } }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            current.package_source("wasi.dev", "wasi", "http", Some("0.2.3")),
+            PackageSource::Oci(reference("ghcr.io", "webassembly/wasi/http", "0.2.3"))
+        );
+
+        let legacy: RegistryMetadata = serde_json::from_str(
+            r#"{ "ociRegistry": "ghcr.io", "ociNamespacePrefix": "my-org/" }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            legacy.package_source("example.com", "my", "app", None),
+            PackageSource::Oci(reference("ghcr.io", "my-org/my/app", "latest"))
+        );
+
+        let warg = |json: &str| {
+            serde_json::from_str::<RegistryMetadata>(json)
+                .unwrap()
+                .package_source("example.com", "my", "app", Some("1.0.0"))
+        };
+        let pulled_from = |url: &str| PackageSource::Warg {
+            url: url.to_string(),
+            package: "my:app".to_string(),
+            version: Some("1.0.0".to_string()),
+        };
+        assert_eq!(
+            warg(r#"{ "wargUrl": "https://warg.example.com" }"#),
+            pulled_from("https://warg.example.com")
+        );
+        assert_eq!(
+            warg(r#"{ "preferredProtocol": "warg", "warg": { "url": "https://w.example.com" } }"#),
+            pulled_from("https://w.example.com")
+        );
+        assert_eq!(
+            warg(r#"{ "preferredProtocol": "warg", "warg": {} }"#),
+            pulled_from("https://example.com")
+        );
+    }
+
+    #[test]
+    fn test_wasm_layer_selection() {
+        let manifest: Manifest = serde_json::from_str(
+            r#"{ "layers": [
+                { "mediaType": "application/vnd.wasm.config.v0+json", "digest": "sha256:1", "size": 1 },
+                { "mediaType": "application/wasm", "digest": "sha256:2", "size": 2 }
+            ] }"#,
+        )
+        .unwrap();
+        assert_eq!(manifest.wasm_layer().unwrap().digest, "sha256:2");
+
+        let index: Manifest = serde_json::from_str(
+            r#"{ "manifests": [ { "mediaType": "x", "digest": "sha256:3", "size": 3 } ] }"#,
+        )
+        .unwrap();
+        assert!(index.wasm_layer().is_err());
+    }
+}
@@ -0,0 +1,273 @@
+//! Security review of the host capabilities each instance imports.
+//!
+//! Most host interfaces are harmless, but a few hand a component real
+//! power over its environment: the filesystem, network sockets, environment
+//! variables, randomness and outgoing HTTP requests.  An [`Audit`] lists,
+//! for every instance, which of these it imports directly from the host,
+//! and checks them against an [`AllowList`] so that a component gaining an
+//! unexpected capability fails CI.
+
+use super::unversioned;
+use crate::model::filter::glob_match;
+use crate::model::{CompositionGraph, ExternKind, SYNTHETIC_COMPONENT};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// A kind of sensitive host access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Sensitive {
+    Filesystem,
+    Sockets,
+    Environment,
+    Random,
+    OutgoingHttp,
+}
+
+impl Sensitive {
+    /// The name used in allow-lists and output.
+    pub fn name(self) -> &'static str {
+        match self {
+            Sensitive::Filesystem => "filesystem",
+            Sensitive::Sockets => "sockets",
+            Sensitive::Environment => "environment",
+            Sensitive::Random => "random",
+            Sensitive::OutgoingHttp => "outgoing-http",
+        }
+    }
+
+    /// The kind of access importing `interface` from the host grants, if it
+    /// is sensitive.  In WASI 0.3 a component makes outgoing requests by
+    /// importing `wasi:http/handler`, so that counts as outgoing HTTP too.
+    pub fn of(interface: &str) -> Option<Self> {
+        let name = unversioned(interface);
+        let package = name.split_once('/').map_or(name, |(package, _)| package);
+        match package {
+            "wasi:filesystem" => Some(Sensitive::Filesystem),
+            "wasi:sockets" => Some(Sensitive::Sockets),
+            "wasi:random" => Some(Sensitive::Random),
+            _ => match name {
+                "wasi:cli/environment" => Some(Sensitive::Environment),
+                "wasi:http/outgoing-handler" | "wasi:http/handler" | "wasi:http/client" => {
+                    Some(Sensitive::OutgoingHttp)
+                }
+                _ => None,
+            },
+        }
+    }
+}
+
+impl fmt::Display for Sensitive {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The sensitive capabilities each instance is expected to have.
+///
+/// Keys are instance display labels, which may use `*` and `?` wildcards;
+/// values list the allowed capability kinds (`filesystem`, ...) or
+/// interface names, again with wildcards and optionally without version.
+/// Deserializes from a TOML table such as
+/// `"mdl-*" = ["random"]` and `srv = ["outgoing-http", "wasi:cli/environment"]`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct AllowList {
+    entries: BTreeMap<String, Vec<String>>,
+}
+
+impl AllowList {
+    pub fn insert(&mut self, instance: impl Into<String>, allowed: Vec<String>) {
+        self.entries.insert(instance.into(), allowed);
+    }
+
+    /// Whether `instance` may import `interface`, of kind `kind`.
+    pub fn allows(&self, instance: &str, kind: Sensitive, interface: &str) -> bool {
+        self.entries
+            .iter()
+            .filter(|(pattern, _)| glob_match(pattern, instance))
+            .flat_map(|(_, allowed)| allowed)
+            .any(|a| {
+                a == kind.name()
+                    || glob_match(a, interface)
+                    || glob_match(a, unversioned(interface))
+            })
+    }
+}
+
+/// One sensitive host interface an instance imports.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Grant {
+    pub interface: String,
+    pub kind: Sensitive,
+    /// `false` when an allow-list was given and does not cover it.
+    pub allowed: bool,
+}
+
+/// The sensitive capabilities of one instance.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct InstanceAudit {
+    /// Display label of the instance.
+    pub instance: String,
+    /// Sensitive host interfaces it imports, by kind then name.
+    pub sensitive: Vec<Grant>,
+    /// How many host interfaces it imports in total.
+    pub host_interfaces: usize,
+}
+
+/// The audit of a whole composition, one entry per real instance in graph
+/// order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Audit {
+    pub instances: Vec<InstanceAudit>,
+}
+
+/// Audit the host imports of every real instance in `graph`, checking them
+/// against `allow` if given.  Type imports are not capabilities and are
+/// ignored.
+pub fn audit(graph: &CompositionGraph, allow: Option<&AllowList>) -> Audit {
+    let instances = graph
+        .nodes
+        .values()
+        .filter(|n| n.component_index != SYNTHETIC_COMPONENT)
+        .map(|node| {
+            let label = node.display_label();
+            let host: Vec<&str> = node
+                .imports
+                .iter()
+                .filter(|c| c.is_host_import && c.kind != ExternKind::Type)
+                .map(|c| c.interface_name.as_str())
+                .collect();
+            let mut sensitive: Vec<Grant> = host
+                .iter()
+                .filter_map(|&interface| {
+                    let kind = Sensitive::of(interface)?;
+                    Some(Grant {
+                        interface: interface.to_string(),
+                        kind,
+                        allowed: allow.is_none_or(|a| a.allows(label, kind, interface)),
+                    })
+                })
+                .collect();
+            sensitive.sort_by(|a, b| (a.kind, &a.interface).cmp(&(b.kind, &b.interface)));
+            InstanceAudit {
+                instance: label.to_string(),
+                sensitive,
+                host_interfaces: host.len(),
+            }
+        })
+        .collect();
+    Audit { instances }
+}
+
+impl Audit {
+    /// Sensitive imports the allow-list does not cover, as
+    /// `(instance, grant)` pairs.
+    pub fn violations(&self) -> Vec<(&str, &Grant)> {
+        self.instances
+            .iter()
+            .flat_map(|i| {
+                i.sensitive
+                    .iter()
+                    .filter(|g| !g.allowed)
+                    .map(|g| (i.instance.as_str(), g))
+            })
+            .collect()
+    }
+
+    /// Render the audit as plain text: a header per instance, then one line
+    /// per sensitive interface, marking those the allow-list does not cover.
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        for (i, entry) in self.instances.iter().enumerate() {
+            if i > 0 {
+                out.push('\n');
+            }
+            if entry.sensitive.is_empty() {
+                out.push_str(&format!(
+                    "{}: no sensitive host interfaces ({} in total)\n",
+                    entry.instance, entry.host_interfaces
+                ));
+                continue;
+            }
+            out.push_str(&format!(
+                "{}: {} of {} host interfaces sensitive\n",
+                entry.instance,
+                entry.sensitive.len(),
+                entry.host_interfaces
+            ));
+            for grant in &entry.sensitive {
+                let line = format!("  {:<13}  {}", grant.kind.name(), grant.interface);
+                if grant.allowed {
+                    out.push_str(&format!("{}\n", line));
+                } else {
+                    out.push_str(&format!("{}  (not allowed)\n", line));
+                }
+            }
+        }
+        let violations = self.violations().len();
+        if violations > 0 {
+            out.push_str(&format!(
+                "\n{} sensitive import{} not in the allow-list\n",
+                violations,
+                if violations == 1 { "" } else { "s" }
+            ));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(
+            Sensitive::of("wasi:filesystem/types@0.2.6"),
+            Some(Sensitive::Filesystem)
+        );
+        assert_eq!(
+            Sensitive::of("wasi:http/outgoing-handler@0.2.6"),
+            Some(Sensitive::OutgoingHttp)
+        );
+        assert_eq!(
+            Sensitive::of("wasi:cli/environment@0.2.6"),
+            Some(Sensitive::Environment)
+        );
+        assert_eq!(Sensitive::of("wasi:cli/stdout@0.2.6"), None);
+        assert_eq!(Sensitive::of("wasi:logging/log@0.1.0"), None);
+    }
+
+    #[test]
+    fn test_audit_without_allow_list() {
+        let audit = audit(&simple_chain_graph(), None);
+        assert!(audit.violations().is_empty());
+        assert_eq!(
+            audit.to_text(),
+            "srv: 1 of 1 host interfaces sensitive\n\
+             \x20 outgoing-http  wasi:http/handler@0.3.0\n\
+             \n\
+             middleware: no sensitive host interfaces (1 in total)\n"
+        );
+    }
+
+    #[test]
+    fn test_allow_list() {
+        let graph = simple_chain_graph();
+        let allow: AllowList = toml::from_str("\"mid*\" = [\"outgoing-http\"]\n").unwrap();
+        let result = audit(&graph, Some(&allow));
+        assert_eq!(result.violations().len(), 1);
+        assert_eq!(result.violations()[0].0, "srv");
+        assert!(result.to_text().ends_with(
+            "wasi:http/handler@0.3.0  (not allowed)\n\n\
+             middleware: no sensitive host interfaces (1 in total)\n\n\
+             1 sensitive import not in the allow-list\n"
+        ));
+
+        let allow: AllowList = toml::from_str("srv = [\"wasi:http/*\"]\n").unwrap();
+        assert!(audit(&graph, Some(&allow)).violations().is_empty());
+    }
+}
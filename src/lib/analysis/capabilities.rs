@@ -0,0 +1,211 @@
+//! Which instance can touch which host capability.
+//!
+//! The matrix has one row per component instance and one column per host
+//! interface.  An instance has *direct* access to the interfaces it imports
+//! from the host, and *transitive* access to those imported by any instance
+//! it (transitively) imports from, the same per-instance walk as
+//! [`super::reachability`].
+
+use super::reachability::reachable_instances;
+use crate::model::{CompositionGraph, ExternKind, SYNTHETIC_COMPONENT};
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+/// How an instance can reach a host interface.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Access {
+    None,
+    Transitive,
+    Direct,
+}
+
+impl Access {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Access::None => "none",
+            Access::Transitive => "transitive",
+            Access::Direct => "direct",
+        }
+    }
+}
+
+/// Host interfaces against the instances that can reach them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CapabilityMatrix {
+    /// Column headers: every host import other than a type, sorted by name.
+    pub interfaces: Vec<String>,
+    /// One row per real instance, in graph order.
+    pub rows: Vec<CapabilityRow>,
+}
+
+/// One instance's access to each host interface.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CapabilityRow {
+    /// Display label of the instance.
+    pub instance: String,
+    /// Access to each of [`CapabilityMatrix::interfaces`], in the same order.
+    pub access: Vec<Access>,
+}
+
+/// Build the capability matrix of `graph`.
+pub fn capability_matrix(graph: &CompositionGraph) -> CapabilityMatrix {
+    // Imported types grant nothing, so they get no column
+    let interfaces: Vec<String> = graph
+        .nodes
+        .values()
+        .flat_map(|n| n.imports.iter())
+        .filter(|c| c.is_host_import && c.kind != ExternKind::Type)
+        .map(|c| c.interface_name.clone())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let column = |name: &str| interfaces.binary_search_by(|i| i.as_str().cmp(name)).ok();
+
+    let rows = graph
+        .nodes
+        .iter()
+        .filter(|(_, n)| n.component_index != SYNTHETIC_COMPONENT)
+        .map(|(&id, node)| {
+            let mut access = vec![Access::None; interfaces.len()];
+            for reached in reachable_instances(graph, id) {
+                let level = if reached == id {
+                    Access::Direct
+                } else {
+                    Access::Transitive
+                };
+                let imports = graph.nodes[&reached].imports.iter();
+                for conn in imports.filter(|c| c.is_host_import) {
+                    if let Some(col) = column(&conn.interface_name) {
+                        access[col] = access[col].max(level);
+                    }
+                }
+            }
+            CapabilityRow {
+                instance: node.display_label().to_string(),
+                access,
+            }
+        })
+        .collect();
+
+    CapabilityMatrix { interfaces, rows }
+}
+
+impl CapabilityMatrix {
+    /// Render the matrix as an aligned table.  Interface names are too long
+    /// for column headers, so columns are numbered and listed underneath.
+    pub fn to_table(&self) -> String {
+        if self.interfaces.is_empty() {
+            return "No host interfaces are imported.\n".to_string();
+        }
+        let width = self
+            .rows
+            .iter()
+            .map(|r| r.instance.chars().count())
+            .chain(["INSTANCE".len()])
+            .max()
+            .unwrap_or(0);
+        let cell = (self.interfaces.len().to_string().len()).max(1);
+
+        let mut out = format!("{:<width$}", "INSTANCE", width = width);
+        for i in 1..=self.interfaces.len() {
+            out.push_str(&format!("  {:>cell$}", i, cell = cell));
+        }
+        out.push('\n');
+        for row in &self.rows {
+            let mut line = format!("{:<width$}", row.instance, width = width);
+            for access in &row.access {
+                let mark = match access {
+                    Access::Direct => "●",
+                    Access::Transitive => "○",
+                    Access::None => "·",
+                };
+                line.push_str(&format!("  {:>cell$}", mark, cell = cell));
+            }
+            out.push_str(&line);
+            out.push('\n');
+        }
+
+        out.push('\n');
+        for (i, name) in self.interfaces.iter().enumerate() {
+            out.push_str(&format!("{:>cell$}  {}\n", i + 1, name, cell = cell));
+        }
+        out.push_str("\n● direct   ○ transitive   · none\n");
+        out
+    }
+
+    /// Render the matrix as CSV, with a header row of interface names and
+    /// cells of `direct`, `transitive` or `none`.
+    pub fn to_csv(&self) -> String {
+        let mut out = std::iter::once("instance")
+            .chain(self.interfaces.iter().map(String::as_str))
+            .map(csv_field)
+            .collect::<Vec<_>>()
+            .join(",");
+        out.push('\n');
+        for row in &self.rows {
+            out.push_str(&csv_field(&row.instance));
+            for access in &row.access {
+                out.push(',');
+                out.push_str(access.as_str());
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// `value` as a CSV field, quoted when it contains a separator or quote.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    #[test]
+    fn test_direct_and_transitive() {
+        let matrix = capability_matrix(&simple_chain_graph());
+        assert_eq!(
+            matrix.interfaces,
+            vec!["wasi:http/handler@0.3.0", "wasi:logging/log@0.1.0"]
+        );
+        assert_eq!(matrix.rows[0].instance, "srv");
+        assert_eq!(matrix.rows[0].access, vec![Access::Direct, Access::None]);
+        assert_eq!(matrix.rows[1].instance, "middleware");
+        assert_eq!(
+            matrix.rows[1].access,
+            vec![Access::Transitive, Access::Direct]
+        );
+    }
+
+    #[test]
+    fn test_csv_and_table() {
+        let matrix = capability_matrix(&simple_chain_graph());
+        assert_eq!(
+            matrix.to_csv(),
+            "instance,wasi:http/handler@0.3.0,wasi:logging/log@0.1.0\n\
+             srv,direct,none\n\
+             middleware,transitive,direct\n"
+        );
+        let table = matrix.to_table();
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines[0], "INSTANCE    1  2");
+        assert_eq!(lines[1], "srv         ●  ·");
+        assert_eq!(lines[2], "middleware  ○  ●");
+        assert_eq!(lines[4], "1  wasi:http/handler@0.3.0");
+    }
+
+    #[test]
+    fn test_csv_quoting() {
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("plain"), "plain");
+    }
+}
@@ -0,0 +1,149 @@
+//! Which tool built the composition.
+//!
+//! Most tools sign their output: `wac` and `wasm-compose` add themselves to
+//! the root component's `processed-by` producers, and the componentize-*
+//! toolchains (and `jco`) record themselves as an SDK or processor.  When
+//! nothing is recorded, the shape of the graph can still give a hint: a
+//! composition whose instances carry no names at all was most likely
+//! encoded by `wasm-compose`, which writes no name section, whereas `wac`
+//! names every instance after its `let` binding.
+
+use crate::model::{ComponentMetadata, CompositionGraph};
+use serde::Serialize;
+
+/// How a [`Composer`] was identified.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Evidence {
+    /// Recorded in the root component's `producers` section.
+    Producers,
+    /// Guessed from the shape of the composition.
+    Structure,
+}
+
+/// The tool that produced the root component.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Composer {
+    /// Tool name as recorded, e.g. `"wac-parser"` or `"componentize-py"`.
+    pub tool: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    pub evidence: Evidence,
+}
+
+impl Composer {
+    /// `"wac-parser 0.9.0"`, or `"wasm-compose (guessed)"` when inferred
+    /// from the graph's structure.
+    pub fn summary(&self) -> String {
+        let mut summary = self.tool.clone();
+        if let Some(version) = &self.version {
+            summary.push_str(&format!(" {}", version));
+        }
+        if self.evidence == Evidence::Structure {
+            summary.push_str(" (guessed)");
+        }
+        summary
+    }
+}
+
+/// Prefixes of the producer names of known tools, most specific first:
+/// composition tools come before the toolchains that build the components
+/// they compose.
+const KNOWN_TOOLS: &[&str] = &["wac", "wasm-compose", "componentize", "jco"];
+
+/// Identify the tool that built `graph`'s root component, if possible.
+pub fn composer(graph: &CompositionGraph) -> Option<Composer> {
+    graph
+        .metadata
+        .get(&None)
+        .and_then(recorded_composer)
+        .or_else(|| guess_from_structure(graph))
+}
+
+fn recorded_composer(metadata: &ComponentMetadata) -> Option<Composer> {
+    let tools: Vec<(&String, &String)> = ["processed-by", "sdk"]
+        .iter()
+        .filter_map(|field| metadata.producers.get(*field))
+        .flatten()
+        .collect();
+    KNOWN_TOOLS.iter().find_map(|prefix| {
+        let (name, version) = tools
+            .iter()
+            .find(|(name, _)| name.to_ascii_lowercase().starts_with(prefix))?;
+        Some(Composer {
+            tool: name.to_string(),
+            version: version.split_whitespace().next().map(str::to_string),
+            evidence: Evidence::Producers,
+        })
+    })
+}
+
+fn guess_from_structure(graph: &CompositionGraph) -> Option<Composer> {
+    let instances = graph.real_nodes();
+    (instances.len() > 1 && instances.iter().all(|n| n.is_unnamed())).then(|| Composer {
+        tool: "wasm-compose".to_string(),
+        version: None,
+        evidence: Evidence::Structure,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::ComponentNode;
+    use crate::test_utils::simple_chain_graph;
+    use std::collections::BTreeMap;
+
+    fn with_producers(field: &str, tools: &[(&str, &str)]) -> CompositionGraph {
+        let mut graph = simple_chain_graph();
+        let mut metadata = ComponentMetadata::default();
+        metadata.producers.insert(
+            field.to_string(),
+            tools
+                .iter()
+                .map(|(name, version)| (name.to_string(), version.to_string()))
+                .collect::<BTreeMap<_, _>>(),
+        );
+        graph.metadata.insert(None, metadata);
+        graph
+    }
+
+    #[test]
+    fn test_recorded_composer() {
+        let graph = with_producers("processed-by", &[("wac-parser", "0.9.0")]);
+        let found = composer(&graph).unwrap();
+        assert_eq!(found.summary(), "wac-parser 0.9.0");
+        assert_eq!(found.evidence, Evidence::Producers);
+
+        // the composition tool wins over the toolchain it composed
+        let graph = with_producers(
+            "processed-by",
+            &[("componentize-py", "0.17.0"), ("wasm-compose", "0.239.0")],
+        );
+        assert_eq!(composer(&graph).unwrap().tool, "wasm-compose");
+
+        let graph = with_producers("sdk", &[("ComponentizeJS", "0.18.1")]);
+        assert_eq!(composer(&graph).unwrap().summary(), "ComponentizeJS 0.18.1");
+
+        let graph = with_producers("processed-by", &[("wit-component", "0.244.0")]);
+        assert_eq!(composer(&graph), None);
+    }
+
+    #[test]
+    fn test_guess_from_structure() {
+        let mut graph = CompositionGraph::new();
+        for id in 0..2 {
+            graph.add_node(
+                id,
+                ComponentNode::new(ComponentNode::fallback_name(id), id, id),
+            );
+        }
+        assert_eq!(
+            composer(&graph).unwrap().summary(),
+            "wasm-compose (guessed)"
+        );
+
+        // named instances are not a wasm-compose fingerprint
+        assert_eq!(composer(&simple_chain_graph()), None);
+    }
+}
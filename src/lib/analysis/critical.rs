@@ -0,0 +1,106 @@
+//! Single points of failure between the exports and the host.
+//!
+//! A request entering through an export reaches host interfaces by way of
+//! the instances the exporting instance (transitively) imports from.  When
+//! every route to some host interface runs through one instance, that
+//! instance is critical: if it misbehaves or is removed, the export loses
+//! access to the interface entirely.  The exporting instance itself is not
+//! reported, as it is trivially critical to its own exports.
+
+use crate::model::{CompositionGraph, ExternKind, SYNTHETIC_COMPONENT};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// An instance some export can only reach part of the host through.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CriticalInstance {
+    /// Graph ID of the instance.
+    pub instance: u32,
+    /// Names of the exports cut off from the host without it, sorted.
+    pub exports: Vec<String>,
+    /// Host interfaces those exports can only reach through it, sorted.
+    pub host_interfaces: Vec<String>,
+}
+
+/// The critical instances of `graph`, in graph order.
+pub fn critical_instances(graph: &CompositionGraph) -> Vec<CriticalInstance> {
+    let mut found: BTreeMap<u32, (BTreeSet<String>, BTreeSet<String>)> = BTreeMap::new();
+    for export in &graph.component_exports {
+        let start = export.source_instance;
+        let (reached, instances) = host_reach(graph, start, None);
+        for &id in instances.iter().filter(|&&id| id != start) {
+            if graph.nodes[&id].component_index == SYNTHETIC_COMPONENT {
+                continue;
+            }
+            let (without, _) = host_reach(graph, start, Some(id));
+            let lost: Vec<&str> = reached.difference(&without).copied().collect();
+            if lost.is_empty() {
+                continue;
+            }
+            let (exports, interfaces) = found.entry(id).or_default();
+            exports.insert(export.name.clone());
+            interfaces.extend(lost.into_iter().map(str::to_string));
+        }
+    }
+    found
+        .into_iter()
+        .map(|(instance, (exports, interfaces))| CriticalInstance {
+            instance,
+            exports: exports.into_iter().collect(),
+            host_interfaces: interfaces.into_iter().collect(),
+        })
+        .collect()
+}
+
+/// Host interfaces (other than types) reachable from `start` without going
+/// through `skip`, and the instances visited on the way.
+fn host_reach(
+    graph: &CompositionGraph,
+    start: u32,
+    skip: Option<u32>,
+) -> (BTreeSet<&str>, Vec<u32>) {
+    let mut interfaces = BTreeSet::new();
+    let mut visited = Vec::new();
+    let mut walk = graph.bfs(start);
+    while let Some((id, node)) = walk.next() {
+        if Some(id) == skip {
+            walk.skip_children();
+            continue;
+        }
+        visited.push(id);
+        interfaces.extend(
+            node.imports
+                .iter()
+                .filter(|c| c.is_host_import && c.kind != ExternKind::Type)
+                .map(|c| c.interface_name.as_str()),
+        );
+    }
+    (interfaces, visited)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    #[test]
+    fn test_sole_route_is_critical() {
+        let critical = critical_instances(&simple_chain_graph());
+        assert_eq!(
+            critical,
+            vec![CriticalInstance {
+                instance: 1,
+                exports: vec!["wasi:http/handler@0.3.0".to_string()],
+                host_interfaces: vec!["wasi:http/handler@0.3.0".to_string()],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_redundant_route_is_not_critical() {
+        let mut graph = simple_chain_graph();
+        // middleware can now reach the host's handler without srv
+        let direct = graph.nodes[&1].imports[0].clone();
+        graph.nodes.get_mut(&2).unwrap().add_import(direct);
+        assert!(critical_instances(&graph).is_empty());
+    }
+}
@@ -0,0 +1,532 @@
+//! Composition hygiene checks with rustc-style lint levels.
+//!
+//! Every check belongs to a [`Category`].  Each category has a default
+//! [`LintLevel`] that can be overridden per run (`--deny cycles`) or from a
+//! JSON file, so a team can start with everything as a warning and promote
+//! categories to errors one at a time.
+
+use super::{
+    incompatible_versions, shared_instances, unsatisfied_imports, unused_instances,
+    version_mismatches, ImportProblem,
+};
+use crate::model::{CompositionGraph, SYNTHETIC_COMPONENT};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::str::FromStr;
+
+/// A family of related diagnostics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Category {
+    /// Instances that (transitively) import from themselves.
+    Cycles,
+    /// An interface referenced at more than one version.
+    VersionMismatch,
+    /// A real instance that nothing imports from or exports.
+    UnusedInstance,
+    /// An import left unwired, or wired under a name the component does not
+    /// import.
+    UnsatisfiedImport,
+    /// An import provided at a semver-incompatible version of the same
+    /// interface.
+    IncompatibleVersion,
+    /// The same component instantiated more than once by the root component.
+    DuplicateInstance,
+    /// An instance several other instances import from, sharing its state.
+    SharedInstance,
+    /// A real instance the name section leaves unnamed and nothing labels.
+    UnnamedInstance,
+}
+
+impl Category {
+    pub const ALL: [Category; 8] = [
+        Category::Cycles,
+        Category::VersionMismatch,
+        Category::UnusedInstance,
+        Category::UnsatisfiedImport,
+        Category::IncompatibleVersion,
+        Category::DuplicateInstance,
+        Category::SharedInstance,
+        Category::UnnamedInstance,
+    ];
+
+    /// The name used on the command line and in lint files.
+    pub fn name(self) -> &'static str {
+        match self {
+            Category::Cycles => "cycles",
+            Category::VersionMismatch => "version-mismatch",
+            Category::UnusedInstance => "unused-instance",
+            Category::UnsatisfiedImport => "unsatisfied-import",
+            Category::IncompatibleVersion => "incompatible-version",
+            Category::DuplicateInstance => "duplicate-instance",
+            Category::SharedInstance => "shared-instance",
+            Category::UnnamedInstance => "unnamed-instance",
+        }
+    }
+
+    /// The level used when nothing overrides it.
+    ///
+    /// Unsatisfied imports are errors: such a composition cannot be
+    /// instantiated.
+    pub fn default_level(self) -> LintLevel {
+        match self {
+            Category::UnsatisfiedImport => LintLevel::Deny,
+            _ => LintLevel::Warn,
+        }
+    }
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Category {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Category::ALL
+            .into_iter()
+            .find(|c| c.name() == s.to_lowercase())
+            .ok_or_else(|| {
+                let known: Vec<&str> = Category::ALL.iter().map(|c| c.name()).collect();
+                format!(
+                    "Unknown diagnostic category: {}. Use one of: {}",
+                    s,
+                    known.join(", ")
+                )
+            })
+    }
+}
+
+/// How a diagnostic is reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintLevel {
+    /// Not reported at all.
+    Allow,
+    /// Reported, but does not fail the run.
+    Warn,
+    /// Reported as an error; the run fails.
+    Deny,
+}
+
+impl fmt::Display for LintLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LintLevel::Allow => "allow",
+            LintLevel::Warn => "warning",
+            LintLevel::Deny => "error",
+        })
+    }
+}
+
+/// Per-category level overrides.
+///
+/// Deserializes from a JSON object such as
+/// `{ "cycles": "deny", "unused-instance": "allow" }`; categories that are
+/// not listed keep their default level.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct LintLevels {
+    overrides: BTreeMap<Category, LintLevel>,
+}
+
+impl LintLevels {
+    /// Set the level for `category`, replacing any earlier override.
+    pub fn set(&mut self, category: Category, level: LintLevel) {
+        self.overrides.insert(category, level);
+    }
+
+    /// The effective level for `category`.
+    pub fn level(&self, category: Category) -> LintLevel {
+        self.overrides
+            .get(&category)
+            .copied()
+            .unwrap_or_else(|| category.default_level())
+    }
+
+    /// Raise every category at [`LintLevel::Warn`] to [`LintLevel::Deny`],
+    /// like rustc's `-D warnings`.  Allowed categories stay allowed.
+    pub fn deny_warnings(&mut self) {
+        for category in Category::ALL {
+            if self.level(category) == LintLevel::Warn {
+                self.set(category, LintLevel::Deny);
+            }
+        }
+    }
+}
+
+/// A single finding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub category: Category,
+    /// Effective level; never [`LintLevel::Allow`].
+    pub level: LintLevel,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}[{}]: {}", self.level, self.category, self.message)
+    }
+}
+
+/// Run every check whose level is not `allow`.
+pub fn diagnose(graph: &CompositionGraph, levels: &LintLevels) -> Vec<Diagnostic> {
+    let mut out = Vec::new();
+    let mut emit = |category: Category, message: String| {
+        let level = levels.level(category);
+        if level != LintLevel::Allow {
+            out.push(Diagnostic {
+                category,
+                level,
+                message,
+            });
+        }
+    };
+
+    let label = |id: u32| {
+        graph
+            .get_node(id)
+            .map(|n| n.display_label().to_string())
+            .unwrap_or_else(|| id.to_string())
+    };
+
+    for cycle in cycles(graph) {
+        let mut names: Vec<String> = cycle.iter().map(|&id| label(id)).collect();
+        names.push(names[0].clone());
+        emit(
+            Category::Cycles,
+            format!("instances form an import cycle: {}", names.join(" -> ")),
+        );
+    }
+    for m in version_mismatches(graph) {
+        let versions: Vec<&str> = m
+            .versions
+            .iter()
+            .map(|v| if v.is_empty() { "(unversioned)" } else { v })
+            .collect();
+        emit(
+            Category::VersionMismatch,
+            format!(
+                "{} is used at several versions: {}",
+                m.interface,
+                versions.join(", ")
+            ),
+        );
+    }
+    for id in unused_instances(graph) {
+        emit(
+            Category::UnusedInstance,
+            format!("instance {} is never imported from or exported", label(id)),
+        );
+    }
+    for import in unsatisfied_imports(graph) {
+        let message = match &import.problem {
+            ImportProblem::Unwired => format!(
+                "instance {} imports {}, but nothing is plugged in for it",
+                label(import.instance),
+                import.interface
+            ),
+            ImportProblem::Undeclared {
+                expected: Some(expected),
+            } => format!(
+                "instance {} is given {}, but imports {}",
+                label(import.instance),
+                import.interface,
+                expected
+            ),
+            ImportProblem::Undeclared { expected: None } => format!(
+                "instance {} is given {}, which it does not import",
+                label(import.instance),
+                import.interface
+            ),
+        };
+        emit(Category::UnsatisfiedImport, message);
+    }
+    for group in graph.duplicate_instances() {
+        let total: u32 = group.iter().map(|id| graph.nodes[id].copies).sum();
+        let names: Vec<String> = group.iter().map(|&id| label(id)).collect();
+        emit(
+            Category::DuplicateInstance,
+            format!(
+                "the same component is instantiated {} times: {}",
+                total,
+                names.join(", ")
+            ),
+        );
+    }
+    for shared in shared_instances(graph) {
+        let names: Vec<String> = shared.importers.iter().map(|&id| label(id)).collect();
+        emit(
+            Category::SharedInstance,
+            format!(
+                "instance {} is shared by {} importers: {}",
+                label(shared.instance),
+                names.len(),
+                names.join(", ")
+            ),
+        );
+    }
+    for (&id, node) in &graph.nodes {
+        if node.component_index != SYNTHETIC_COMPONENT && node.is_unnamed() && node.label.is_none()
+        {
+            emit(
+                Category::UnnamedInstance,
+                format!(
+                    "instance {} has no name; label it with --names or --labels",
+                    label(id)
+                ),
+            );
+        }
+    }
+    for m in incompatible_versions(graph) {
+        emit(
+            Category::IncompatibleVersion,
+            format!(
+                "instance {} imports {}, but {} provides {}",
+                label(m.instance),
+                m.import,
+                label(m.provider),
+                m.export
+            ),
+        );
+    }
+    out
+}
+
+/// Import cycles between instances, each listed once starting from its
+/// smallest graph ID.
+fn cycles(graph: &CompositionGraph) -> Vec<Vec<u32>> {
+    fn visit(
+        graph: &CompositionGraph,
+        id: u32,
+        path: &mut Vec<u32>,
+        done: &mut BTreeSet<u32>,
+        found: &mut BTreeSet<Vec<u32>>,
+    ) {
+        if let Some(pos) = path.iter().position(|&p| p == id) {
+            let mut cycle = path[pos..].to_vec();
+            let min = cycle.iter().enumerate().min_by_key(|(_, &v)| v).unwrap().0;
+            cycle.rotate_left(min);
+            found.insert(cycle);
+            return;
+        }
+        if !done.insert(id) {
+            return;
+        }
+        let Some(node) = graph.get_node(id) else {
+            return;
+        };
+        path.push(id);
+        for conn in node.imports.iter().filter(|c| !c.is_host_import) {
+            if let Some(src) = conn.source_instance {
+                visit(graph, src, path, done, found);
+            }
+        }
+        path.pop();
+    }
+
+    let mut done = BTreeSet::new();
+    let mut found = BTreeSet::new();
+    for &id in graph.nodes.keys() {
+        visit(graph, id, &mut Vec::new(), &mut done, &mut found);
+    }
+    found.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{ComponentNode, ExternKind, InterfaceConnection};
+    use crate::test_utils::*;
+
+    fn link(graph: &mut CompositionGraph, from: u32, to: u32) {
+        let node = graph.nodes.get_mut(&from).unwrap();
+        node.add_import(InterfaceConnection {
+            interface_name: "test:loop/back".to_string(),
+            source_instance: Some(to),
+            is_host_import: false,
+            interface_type: None,
+            fingerprint: None,
+            source_export: None,
+            kind: ExternKind::Instance,
+            alias_chain: Vec::new(),
+        });
+    }
+
+    #[test]
+    fn test_clean_graph_has_no_diagnostics() {
+        assert!(diagnose(&simple_chain_graph(), &LintLevels::default()).is_empty());
+    }
+
+    #[test]
+    fn test_cycle_detected_once() {
+        let mut graph = long_chain_graph();
+        let ids: Vec<u32> = graph.nodes.keys().copied().collect();
+        link(&mut graph, ids[0], ids[ids.len() - 1]);
+
+        let diags = diagnose(&graph, &LintLevels::default());
+        let cycles: Vec<_> = diags
+            .iter()
+            .filter(|d| d.category == Category::Cycles)
+            .collect();
+        assert_eq!(cycles.len(), 1, "{:?}", diags);
+        assert_eq!(cycles[0].level, LintLevel::Warn);
+        assert!(cycles[0].message.contains(" -> "), "{}", cycles[0].message);
+    }
+
+    #[test]
+    fn test_levels_override_defaults() {
+        let mut graph = simple_chain_graph();
+        graph.add_node(42, orphan_node());
+
+        let mut levels = LintLevels::default();
+        levels.set(Category::UnusedInstance, LintLevel::Deny);
+        let diags = diagnose(&graph, &levels);
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].level, LintLevel::Deny);
+        assert!(diags[0].to_string().starts_with("error[unused-instance]: "));
+
+        levels.set(Category::UnusedInstance, LintLevel::Allow);
+        assert!(diagnose(&graph, &levels).is_empty());
+    }
+
+    #[test]
+    fn test_deny_warnings() {
+        let mut graph = simple_chain_graph();
+        graph.add_node(42, orphan_node());
+        graph.nodes.get_mut(&1).unwrap().name = "instance_1".to_string();
+
+        let mut levels = LintLevels::default();
+        levels.set(Category::SharedInstance, LintLevel::Allow);
+        levels.deny_warnings();
+        assert_eq!(levels.level(Category::SharedInstance), LintLevel::Allow);
+        let diags = diagnose(&graph, &levels);
+        let found: Vec<(Category, LintLevel)> =
+            diags.iter().map(|d| (d.category, d.level)).collect();
+        assert_eq!(
+            found,
+            [
+                (Category::UnusedInstance, LintLevel::Deny),
+                (Category::UnnamedInstance, LintLevel::Deny),
+            ]
+        );
+        assert_eq!(
+            diags[1].message,
+            "instance instance_1 has no name; label it with --names or --labels"
+        );
+
+        graph.nodes.get_mut(&1).unwrap().label = Some("server".to_string());
+        assert_eq!(diagnose(&graph, &levels).len(), 1);
+    }
+
+    #[test]
+    fn test_shared_instance() {
+        let mut graph = simple_chain_graph();
+        graph.add_node(3, ComponentNode::new("$admin".to_string(), 2, 2));
+        link(&mut graph, 3, 1);
+
+        let mut levels = LintLevels::default();
+        levels.set(Category::UnusedInstance, LintLevel::Allow);
+        let diags = diagnose(&graph, &levels);
+        assert_eq!(diags.len(), 1, "{:?}", diags);
+        assert_eq!(
+            diags[0].to_string(),
+            "warning[shared-instance]: instance srv is shared by 2 importers: middleware, admin"
+        );
+    }
+
+    #[test]
+    fn test_version_mismatch() {
+        let mut graph = simple_chain_graph();
+        let mut node = ComponentNode::new("old".to_string(), 9, 9);
+        node.add_import(InterfaceConnection {
+            interface_name: "wasi:logging/log@0.2.0".to_string(),
+            source_instance: None,
+            is_host_import: true,
+            interface_type: None,
+            fingerprint: None,
+            source_export: None,
+            kind: ExternKind::Instance,
+            alias_chain: Vec::new(),
+        });
+        graph.add_node(9, node);
+
+        let mut levels = LintLevels::default();
+        levels.set(Category::UnusedInstance, LintLevel::Allow);
+        let diags = diagnose(&graph, &levels);
+        assert_eq!(diags.len(), 1, "{:?}", diags);
+        assert_eq!(diags[0].category, Category::VersionMismatch);
+        assert!(diags[0].message.contains("0.1.0, 0.2.0"));
+    }
+
+    #[test]
+    fn test_unsatisfied_import_denied_by_default() {
+        let mut graph = simple_chain_graph();
+        let declared = [
+            "wasi:http/handler@0.3.0",
+            "wasi:logging/log@0.1.0",
+            "wasi:keyvalue/store@0.1.0",
+        ]
+        .map(str::to_string);
+        graph.nodes.get_mut(&2).unwrap().declared_imports = Some(BTreeSet::from(declared));
+
+        let diags = diagnose(&graph, &LintLevels::default());
+        assert_eq!(diags.len(), 1, "{:?}", diags);
+        assert_eq!(
+            diags[0].to_string(),
+            "error[unsatisfied-import]: instance middleware imports wasi:keyvalue/store@0.1.0, \
+             but nothing is plugged in for it"
+        );
+    }
+
+    #[test]
+    fn test_incompatible_version() {
+        let mut graph = simple_chain_graph();
+        let conn = &mut graph.nodes.get_mut(&2).unwrap().imports[0];
+        conn.interface_name = "wasi:http/handler@0.3.0-rc-2026-01-06".to_string();
+        conn.source_export = Some("wasi:http/handler@0.3.0-rc-2025-09-16".to_string());
+
+        let mut levels = LintLevels::default();
+        levels.set(Category::VersionMismatch, LintLevel::Allow);
+        let diags = diagnose(&graph, &levels);
+        assert_eq!(diags.len(), 1, "{:?}", diags);
+        assert_eq!(
+            diags[0].to_string(),
+            "warning[incompatible-version]: instance middleware imports \
+             wasi:http/handler@0.3.0-rc-2026-01-06, but srv provides \
+             wasi:http/handler@0.3.0-rc-2025-09-16"
+        );
+    }
+
+    #[test]
+    fn test_duplicate_instance() {
+        let mut graph = simple_chain_graph();
+        for node in graph.nodes.values_mut() {
+            node.digest = Some("sha256:00".to_string());
+        }
+
+        let diags = diagnose(&graph, &LintLevels::default());
+        assert_eq!(diags.len(), 1, "{:?}", diags);
+        assert_eq!(
+            diags[0].to_string(),
+            "warning[duplicate-instance]: the same component is instantiated 2 times: srv, middleware"
+        );
+    }
+
+    #[test]
+    fn test_lint_file_and_category_names() {
+        let levels: LintLevels =
+            serde_json::from_str(r#"{ "cycles": "deny", "version-mismatch": "allow" }"#).unwrap();
+        assert_eq!(levels.level(Category::Cycles), LintLevel::Deny);
+        assert_eq!(levels.level(Category::VersionMismatch), LintLevel::Allow);
+        assert_eq!(levels.level(Category::UnusedInstance), LintLevel::Warn);
+
+        assert_eq!("Unused-Instance".parse(), Ok(Category::UnusedInstance));
+        assert!("bogus".parse::<Category>().is_err());
+    }
+}
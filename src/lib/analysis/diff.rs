@@ -0,0 +1,395 @@
+//! A readable summary of how one composition differs from another.
+//!
+//! [`json_patch`](crate::output::json_patch) diffs the JSON documents of two
+//! graphs, which is precise but hard to read.  A [`GraphDiff`] instead says
+//! what changed in composition terms: instances added, removed or renamed,
+//! connections rewired, exports moved and interface versions bumped.
+//!
+//! Instances are matched by [stable ID](CompositionGraph::stable_ids), then
+//! by display label.  An instance that disappeared
+//! while another with the same digest (or, lacking digests, the same
+//! package) appeared is reported as renamed, and its connections are
+//! compared under the new name.  Connections are only compared for
+//! instances present on both sides; those of added and removed instances
+//! follow from the instance itself.
+
+use super::unversioned;
+use crate::model::{ComponentNode, CompositionGraph, InterfaceConnection, SYNTHETIC_COMPONENT};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Provider shown for interfaces imported from the host.
+const HOST: &str = "host";
+
+/// How a composition changed between two versions.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct GraphDiff {
+    /// Labels of instances only in the new composition.
+    pub added: Vec<String>,
+    /// Labels of instances only in the old composition.
+    pub removed: Vec<String>,
+    pub renamed: Vec<Renamed>,
+    pub connections: Vec<ConnectionChange>,
+    pub exports: Vec<ExportChange>,
+    pub versions: Vec<VersionBump>,
+}
+
+/// An instance that kept its component but changed its name.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Renamed {
+    pub from: String,
+    pub to: String,
+}
+
+/// An import that appeared, disappeared or is now provided by another
+/// instance.  Providers are display labels, or `host`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConnectionChange {
+    /// Label of the importing instance, as named in the new composition.
+    pub importer: String,
+    /// Interface name without version.
+    pub interface: String,
+    /// `None` when the import is new.
+    pub old_provider: Option<String>,
+    /// `None` when the import was dropped.
+    pub new_provider: Option<String>,
+}
+
+/// An export that appeared, disappeared or is now provided by another
+/// instance.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExportChange {
+    /// Export name without version.
+    pub name: String,
+    /// `None` when the export is new.
+    pub old_source: Option<String>,
+    /// `None` when the export was dropped.
+    pub new_source: Option<String>,
+}
+
+/// An import or export whose interface changed version.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VersionBump {
+    /// Label of the importing instance, or `None` for an export.
+    pub instance: Option<String>,
+    /// Interface name without version.
+    pub interface: String,
+    /// `None` when that side is unversioned.
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// What changed from `old` to `new`.
+pub fn diff_graphs(old: &CompositionGraph, new: &CompositionGraph) -> GraphDiff {
+    let mut diff = GraphDiff::default();
+
+    let (old_ids, new_ids) = (old.stable_ids(), new.stable_ids());
+    let mut unmatched = real(new);
+    let mut pairs = Vec::new();
+    let mut unpaired = Vec::new();
+    for (i, (o, id)) in real(old).into_iter().enumerate() {
+        match unmatched
+            .iter()
+            .position(|&(_, n)| new_ids.get(&n) == old_ids.get(&id))
+        {
+            Some(at) => pairs.push((i, o, unmatched.remove(at).0)),
+            None => unpaired.push((i, o)),
+        }
+    }
+    let mut gone = Vec::new();
+    for (i, o) in unpaired {
+        match unmatched
+            .iter()
+            .position(|(n, _)| n.display_label() == o.display_label())
+        {
+            Some(at) => pairs.push((i, o, unmatched.remove(at).0)),
+            None => gone.push((i, o)),
+        }
+    }
+    let mut renames: BTreeMap<&str, &str> = BTreeMap::new();
+    for (i, o) in gone {
+        match unmatched.iter().position(|(n, _)| same_component(o, n)) {
+            Some(at) => {
+                let (n, _) = unmatched.remove(at);
+                diff.renamed.push(Renamed {
+                    from: o.display_label().to_string(),
+                    to: n.display_label().to_string(),
+                });
+                renames.insert(o.display_label(), n.display_label());
+                pairs.push((i, o, n));
+            }
+            None => diff.removed.push(o.display_label().to_string()),
+        }
+    }
+    pairs.sort_by_key(|&(i, _, _)| i);
+    diff.added = unmatched
+        .iter()
+        .map(|(n, _)| n.display_label().to_string())
+        .collect();
+
+    let old_label = |id: u32| {
+        let label = old.get_node(id).map_or("?", |n| n.display_label());
+        renames.get(label).copied().unwrap_or(label).to_string()
+    };
+    let new_label = |id: u32| {
+        new.get_node(id)
+            .map_or("?", |n| n.display_label())
+            .to_string()
+    };
+
+    for (_, o, n) in pairs {
+        let importer = n.display_label();
+        let mut added: Vec<&InterfaceConnection> = n.imports.iter().collect();
+        for oc in &o.imports {
+            let interface = unversioned(&oc.interface_name);
+            let old_provider = provider(oc, old_label);
+            let Some(at) = added
+                .iter()
+                .position(|nc| unversioned(&nc.interface_name) == interface)
+            else {
+                diff.connections.push(ConnectionChange {
+                    importer: importer.to_string(),
+                    interface: interface.to_string(),
+                    old_provider: Some(old_provider),
+                    new_provider: None,
+                });
+                continue;
+            };
+            let nc = added.remove(at);
+            let new_provider = provider(nc, new_label);
+            if old_provider != new_provider {
+                diff.connections.push(ConnectionChange {
+                    importer: importer.to_string(),
+                    interface: interface.to_string(),
+                    old_provider: Some(old_provider),
+                    new_provider: Some(new_provider),
+                });
+            }
+            if let Some(bump) = version_bump(&oc.interface_name, &nc.interface_name) {
+                diff.versions.push(VersionBump {
+                    instance: Some(importer.to_string()),
+                    ..bump
+                });
+            }
+        }
+        for nc in added {
+            diff.connections.push(ConnectionChange {
+                importer: importer.to_string(),
+                interface: unversioned(&nc.interface_name).to_string(),
+                old_provider: None,
+                new_provider: Some(provider(nc, new_label)),
+            });
+        }
+    }
+
+    let mut added: Vec<_> = new.component_exports.iter().collect();
+    for oe in &old.component_exports {
+        let name = unversioned(&oe.name);
+        let old_source = old_label(oe.source_instance);
+        let Some(at) = added.iter().position(|ne| unversioned(&ne.name) == name) else {
+            diff.exports.push(ExportChange {
+                name: name.to_string(),
+                old_source: Some(old_source),
+                new_source: None,
+            });
+            continue;
+        };
+        let ne = added.remove(at);
+        let new_source = new_label(ne.source_instance);
+        if old_source != new_source {
+            diff.exports.push(ExportChange {
+                name: name.to_string(),
+                old_source: Some(old_source),
+                new_source: Some(new_source),
+            });
+        }
+        diff.versions.extend(version_bump(&oe.name, &ne.name));
+    }
+    for ne in added {
+        diff.exports.push(ExportChange {
+            name: unversioned(&ne.name).to_string(),
+            old_source: None,
+            new_source: Some(new_label(ne.source_instance)),
+        });
+    }
+
+    diff
+}
+
+impl GraphDiff {
+    pub fn is_empty(&self) -> bool {
+        self == &GraphDiff::default()
+    }
+
+    /// Render the diff as plain text, one section per kind of change, with
+    /// `+`, `-` and `~` marking additions, removals and changes.
+    pub fn to_text(&self) -> String {
+        if self.is_empty() {
+            return "No changes\n".to_string();
+        }
+        let mut sections: Vec<(&str, Vec<String>)> = Vec::new();
+
+        let mut instances: Vec<String> = self.added.iter().map(|a| format!("+ {}", a)).collect();
+        instances.extend(self.removed.iter().map(|r| format!("- {}", r)));
+        instances.extend(
+            self.renamed
+                .iter()
+                .map(|r| format!("~ {} renamed to {}", r.from, r.to)),
+        );
+        sections.push(("Instances", instances));
+
+        let connections = self
+            .connections
+            .iter()
+            .map(|c| match (&c.old_provider, &c.new_provider) {
+                (None, Some(p)) => format!("+ {} imports {} from {}", c.importer, c.interface, p),
+                (Some(p), None) => format!("- {} imports {} from {}", c.importer, c.interface, p),
+                (Some(was), Some(now)) => format!(
+                    "~ {} imports {} from {}, was {}",
+                    c.importer, c.interface, now, was
+                ),
+                (None, None) => unreachable!("a connection change has at least one side"),
+            })
+            .collect();
+        sections.push(("Connections", connections));
+
+        let exports = self
+            .exports
+            .iter()
+            .map(|e| match (&e.old_source, &e.new_source) {
+                (None, Some(s)) => format!("+ {} from {}", e.name, s),
+                (Some(s), None) => format!("- {} from {}", e.name, s),
+                (Some(was), Some(now)) => format!("~ {} from {}, was {}", e.name, now, was),
+                (None, None) => unreachable!("an export change has at least one side"),
+            })
+            .collect();
+        sections.push(("Exports", exports));
+
+        let version = |v: &Option<String>| v.clone().unwrap_or_else(|| "unversioned".to_string());
+        let versions = self
+            .versions
+            .iter()
+            .map(|v| {
+                format!(
+                    "~ {} {} → {}",
+                    match &v.instance {
+                        Some(instance) => format!("{} imports {}", instance, v.interface),
+                        None => format!("export {}", v.interface),
+                    },
+                    version(&v.from),
+                    version(&v.to)
+                )
+            })
+            .collect();
+        sections.push(("Versions", versions));
+
+        let mut out = String::new();
+        for (title, lines) in sections.into_iter().filter(|(_, l)| !l.is_empty()) {
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            out.push_str(&format!("{}:\n", title));
+            for line in lines {
+                out.push_str(&format!("  {}\n", line));
+            }
+        }
+        out
+    }
+}
+
+/// The real instances of `graph` with their graph IDs.
+fn real(graph: &CompositionGraph) -> Vec<(&ComponentNode, u32)> {
+    graph
+        .nodes
+        .iter()
+        .filter(|(_, n)| n.component_index != SYNTHETIC_COMPONENT)
+        .map(|(&id, n)| (n, id))
+        .collect()
+}
+
+/// Whether `a` and `b` are the same component under different names.
+fn same_component(a: &ComponentNode, b: &ComponentNode) -> bool {
+    match (&a.digest, &b.digest) {
+        (Some(x), Some(y)) => x == y,
+        _ => a.package.is_some() && a.package == b.package,
+    }
+}
+
+fn provider(conn: &InterfaceConnection, label: impl Fn(u32) -> String) -> String {
+    match conn.source_instance {
+        Some(id) if !conn.is_host_import => label(id),
+        _ => HOST.to_string(),
+    }
+}
+
+/// The version change from interface name `old` to `new`, if any.
+fn version_bump(old: &str, new: &str) -> Option<VersionBump> {
+    let version = |name: &str| name.split_once('@').map(|(_, v)| v.to_string());
+    let (from, to) = (version(old), version(new));
+    (from != to).then(|| VersionBump {
+        instance: None,
+        interface: unversioned(new).to_string(),
+        from,
+        to,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    #[test]
+    fn test_identical_graphs() {
+        let graph = two_chain_graph();
+        let diff = diff_graphs(&graph, &graph);
+        assert!(diff.is_empty());
+        assert_eq!(diff.to_text(), "No changes\n");
+    }
+
+    /// simple_chain_graph with srv renamed to server, every interface
+    /// bumped to 0.3.1, middleware logging through server and a new cache.
+    fn changed_graph() -> CompositionGraph {
+        let mut graph = simple_chain_graph();
+        for node in graph.nodes.values_mut() {
+            for conn in &mut node.imports {
+                conn.interface_name = conn.interface_name.replace("0.3.0", "0.3.1");
+            }
+        }
+        graph.component_exports[0].name = "wasi:http/handler@0.3.1".to_string();
+        let log = &mut graph.nodes.get_mut(&2).unwrap().imports[1];
+        log.is_host_import = false;
+        log.source_instance = Some(1);
+        graph.nodes.get_mut(&1).unwrap().name = "$server".to_string();
+        graph.add_node(3, ComponentNode::new("$cache".to_string(), 2, 2));
+        graph
+    }
+
+    #[test]
+    fn test_changes() {
+        let (mut old, mut new) = (simple_chain_graph(), changed_graph());
+        for graph in [&mut old, &mut new] {
+            graph.nodes.get_mut(&1).unwrap().digest = Some("sha256:aa".to_string());
+        }
+        let diff = diff_graphs(&old, &new);
+        assert_eq!(
+            diff.to_text(),
+            "Instances:\n\
+             \x20 + cache\n\
+             \x20 ~ srv renamed to server\n\
+             \n\
+             Connections:\n\
+             \x20 ~ middleware imports wasi:logging/log from server, was host\n\
+             \n\
+             Versions:\n\
+             \x20 ~ server imports wasi:http/handler 0.3.0 → 0.3.1\n\
+             \x20 ~ middleware imports wasi:http/handler 0.3.0 → 0.3.1\n\
+             \x20 ~ export wasi:http/handler 0.3.0 → 0.3.1\n"
+        );
+
+        // Without a digest to go by, a rename is a removal and an addition
+        let diff = diff_graphs(&simple_chain_graph(), &changed_graph());
+        assert_eq!(diff.removed, vec!["srv"]);
+        assert_eq!(diff.added, vec!["server", "cache"]);
+    }
+}
@@ -0,0 +1,125 @@
+//! What a composed artifact serves: its top-level exports.
+//!
+//! For each export the list names the instance providing it and how many
+//! instances a call passes through behind it, so operators can see at a
+//! glance what the binary offers and how much sits behind each entry point.
+
+use crate::get_chain_for;
+use crate::model::{CompositionGraph, ExternKind, SYNTHETIC_COMPONENT};
+use serde::Serialize;
+
+/// Provider shown for imports the composition re-exports.
+const HOST: &str = "host";
+
+/// One top-level export of the composition.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExportSummary {
+    pub name: String,
+    /// What is exported: `instance`, `func`, `value` or `type`.
+    pub kind: &'static str,
+    /// Display label of the providing instance, or `host` for an import
+    /// the composition re-exports.
+    pub provider: String,
+    /// Number of instances in the handler chain behind the export: 1 when
+    /// the provider handles it alone, 0 when it comes from the host.
+    pub chain_length: usize,
+}
+
+/// Every top-level export of `graph`, ordered by name.
+pub fn export_summaries(graph: &CompositionGraph) -> Vec<ExportSummary> {
+    graph
+        .component_exports
+        .iter()
+        .map(|export| {
+            let provider = graph
+                .get_node(export.source_instance)
+                .filter(|n| n.component_index != SYNTHETIC_COMPONENT);
+            let chain_length = match provider {
+                None => 0,
+                Some(_) if export.kind == ExternKind::Instance => {
+                    get_chain_for(graph, &export.name).len().max(1)
+                }
+                Some(_) => 1,
+            };
+            ExportSummary {
+                name: export.name.clone(),
+                kind: export.kind.as_str(),
+                provider: provider
+                    .map_or_else(|| HOST.to_string(), |n| n.display_label().to_string()),
+                chain_length,
+            }
+        })
+        .collect()
+}
+
+/// Render `exports` as an aligned plain-text table.
+pub fn exports_table(exports: &[ExportSummary]) -> String {
+    if exports.is_empty() {
+        return "No exports: the composition serves nothing.\n".to_string();
+    }
+
+    let header = ["EXPORT", "KIND", "PROVIDER", "CHAIN"];
+    let rows: Vec<[String; 4]> = exports
+        .iter()
+        .map(|e| {
+            [
+                e.name.clone(),
+                e.kind.to_string(),
+                e.provider.clone(),
+                e.chain_length.to_string(),
+            ]
+        })
+        .collect();
+
+    super::text_table(header, &rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    #[test]
+    fn test_export_summaries() {
+        // gateway → service → backend, exported from gateway
+        let graph = long_chain_graph();
+        let exports = export_summaries(&graph);
+        assert_eq!(exports.len(), 1);
+        assert_eq!(exports[0].kind, "instance");
+        assert_eq!(exports[0].provider, "gateway");
+        assert_eq!(exports[0].chain_length, 3);
+
+        let mut graph = simple_chain_graph();
+        graph.add_export_of_kind("run".to_string(), ExternKind::Func, 1, None);
+        graph.add_export("wasi:cli/env".to_string(), SYNTHETIC_COMPONENT, None);
+        let exports = export_summaries(&graph);
+        assert_eq!(
+            exports[..2],
+            [
+                ExportSummary {
+                    name: "run".to_string(),
+                    kind: "func",
+                    provider: "srv".to_string(),
+                    chain_length: 1,
+                },
+                ExportSummary {
+                    name: "wasi:cli/env".to_string(),
+                    kind: "instance",
+                    provider: "host".to_string(),
+                    chain_length: 0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_table() {
+        let table = exports_table(&export_summaries(&simple_chain_graph()));
+        assert_eq!(
+            table,
+            "EXPORT                   KIND      PROVIDER    CHAIN\n\
+             wasi:http/handler@0.3.0  instance  middleware  2\n"
+        );
+        assert!(exports_table(&[]).starts_with("No exports"));
+    }
+}
@@ -0,0 +1,253 @@
+//! A single 0–100 "health score" for a composition.
+//!
+//! The score starts at 100 and each [`HealthFactor`] subtracts a penalty.
+//! What counts as unhealthy is opinionated, so every threshold and penalty
+//! lives in [`HealthWeights`] and can be overridden from a JSON file.
+
+use super::{host_capabilities, longest_chain, unused_instances, version_mismatches};
+use crate::model::CompositionGraph;
+use serde::{Deserialize, Serialize};
+
+/// The best possible score.
+pub const MAX_SCORE: u32 = 100;
+
+/// Thresholds and penalties used to compute a [`HealthReport`].
+///
+/// Missing fields fall back to their defaults when deserialized, so a
+/// weights file only needs to list the values it changes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HealthWeights {
+    /// Longest handler chain (in instances) that carries no penalty.
+    pub max_chain_length: usize,
+    /// Penalty per instance beyond `max_chain_length`.
+    pub chain_length_penalty: u32,
+    /// Number of distinct host interfaces that carries no penalty.
+    pub max_host_capabilities: usize,
+    /// Penalty per host interface beyond `max_host_capabilities`.
+    pub host_capability_penalty: u32,
+    /// Penalty per instance that nothing depends on.
+    pub unused_instance_penalty: u32,
+    /// Penalty per interface referenced at more than one version.
+    pub version_mismatch_penalty: u32,
+}
+
+impl Default for HealthWeights {
+    fn default() -> Self {
+        Self {
+            max_chain_length: 5,
+            chain_length_penalty: 5,
+            max_host_capabilities: 10,
+            host_capability_penalty: 2,
+            unused_instance_penalty: 10,
+            version_mismatch_penalty: 10,
+        }
+    }
+}
+
+/// One contribution to the score.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HealthFactor {
+    /// Stable identifier, e.g. `"unused-instances"`.
+    pub name: &'static str,
+    /// The measured value (chain length, number of instances, ...).
+    pub value: usize,
+    /// Points subtracted from [`MAX_SCORE`] for this factor.
+    pub penalty: u32,
+    /// Human-readable explanation of the value.
+    pub detail: String,
+}
+
+/// The computed score together with the factors that produced it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HealthReport {
+    /// `MAX_SCORE` minus the sum of all penalties, floored at 0.
+    pub score: u32,
+    pub factors: Vec<HealthFactor>,
+}
+
+/// Score `graph` using `weights`.
+pub fn health_score(graph: &CompositionGraph, weights: &HealthWeights) -> HealthReport {
+    let chain = longest_chain(graph);
+    let host = host_capabilities(graph);
+    let unused = unused_instances(graph);
+    let mismatches = version_mismatches(graph);
+
+    let over = |value: usize, limit: usize| value.saturating_sub(limit) as u32;
+
+    let factors = vec![
+        HealthFactor {
+            name: "chain-length",
+            value: chain,
+            penalty: over(chain, weights.max_chain_length) * weights.chain_length_penalty,
+            detail: format!(
+                "longest handler chain has {} instance(s) (limit {})",
+                chain, weights.max_chain_length
+            ),
+        },
+        HealthFactor {
+            name: "host-capabilities",
+            value: host.len(),
+            penalty: over(host.len(), weights.max_host_capabilities)
+                * weights.host_capability_penalty,
+            detail: format!(
+                "{} distinct host interface(s) imported (limit {})",
+                host.len(),
+                weights.max_host_capabilities
+            ),
+        },
+        HealthFactor {
+            name: "unused-instances",
+            value: unused.len(),
+            penalty: unused.len() as u32 * weights.unused_instance_penalty,
+            detail: if unused.is_empty() {
+                "every instance is used".to_string()
+            } else {
+                let names: Vec<&str> = unused
+                    .iter()
+                    .filter_map(|id| graph.get_node(*id))
+                    .map(|n| n.display_label())
+                    .collect();
+                format!("unused: {}", names.join(", "))
+            },
+        },
+        HealthFactor {
+            name: "version-mismatches",
+            value: mismatches.len(),
+            penalty: mismatches.len() as u32 * weights.version_mismatch_penalty,
+            detail: if mismatches.is_empty() {
+                "no interface is used at more than one version".to_string()
+            } else {
+                mismatches
+                    .iter()
+                    .map(|m| format!("{} ({})", m.interface, m.versions.join(", ")))
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            },
+        },
+    ];
+
+    let total: u32 = factors.iter().map(|f| f.penalty).sum();
+    HealthReport {
+        score: MAX_SCORE.saturating_sub(total),
+        factors,
+    }
+}
+
+impl HealthReport {
+    /// Render the report as plain text for the terminal.
+    pub fn to_text(&self) -> String {
+        format!(
+            "Health score: {}/{}\n{}",
+            self.score,
+            MAX_SCORE,
+            self.factors_text()
+        )
+    }
+
+    /// The factor lines of [`Self::to_text`], one per factor.
+    pub(crate) fn factors_text(&self) -> String {
+        let width = self.factors.iter().map(|f| f.name.len()).max().unwrap_or(0);
+        let mut out = String::new();
+        for f in &self.factors {
+            out.push_str(&format!(
+                "  {:<width$}  -{:<3} {}\n",
+                f.name,
+                f.penalty,
+                f.detail,
+                width = width
+            ));
+        }
+        out
+    }
+
+    /// Render the report as a Markdown section with a factor table.
+    pub fn to_markdown(&self) -> String {
+        format!(
+            "## Health score: {}/{}\n\n{}",
+            self.score,
+            MAX_SCORE,
+            self.factors_markdown()
+        )
+    }
+
+    /// The factor table of [`Self::to_markdown`].
+    pub(crate) fn factors_markdown(&self) -> String {
+        let mut out = "| Factor | Value | Penalty | Detail |\n|---|---:|---:|---|\n".to_string();
+        for f in &self.factors {
+            out.push_str(&format!(
+                "| {} | {} | -{} | {} |\n",
+                f.name,
+                f.value,
+                f.penalty,
+                f.detail.replace('|', "\\|")
+            ));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    #[test]
+    fn test_clean_graph_scores_full_marks() {
+        let report = health_score(&simple_chain_graph(), &HealthWeights::default());
+        assert_eq!(report.score, MAX_SCORE);
+        assert!(report.factors.iter().all(|f| f.penalty == 0));
+    }
+
+    #[test]
+    fn test_penalties_are_configurable() {
+        let mut graph = simple_chain_graph();
+        graph.add_node(42, orphan_node());
+
+        let report = health_score(&graph, &HealthWeights::default());
+        assert_eq!(report.score, MAX_SCORE - 10);
+
+        let weights = HealthWeights {
+            unused_instance_penalty: 25,
+            max_chain_length: 1,
+            chain_length_penalty: 3,
+            ..HealthWeights::default()
+        };
+        let report = health_score(&graph, &weights);
+        assert_eq!(report.score, MAX_SCORE - 25 - 3);
+        let unused = report
+            .factors
+            .iter()
+            .find(|f| f.name == "unused-instances")
+            .unwrap();
+        assert!(unused.detail.contains("orphan"), "{}", unused.detail);
+    }
+
+    #[test]
+    fn test_score_floors_at_zero() {
+        let mut graph = simple_chain_graph();
+        for i in 0..20 {
+            graph.add_node(100 + i, orphan_node());
+        }
+        assert_eq!(health_score(&graph, &HealthWeights::default()).score, 0);
+    }
+
+    #[test]
+    fn test_partial_weights_file() {
+        let weights: HealthWeights =
+            serde_json::from_str(r#"{ "unused_instance_penalty": 1 }"#).unwrap();
+        assert_eq!(weights.unused_instance_penalty, 1);
+        assert_eq!(
+            weights.max_chain_length,
+            HealthWeights::default().max_chain_length
+        );
+    }
+
+    #[test]
+    fn test_markdown_rendering() {
+        let report = health_score(&simple_chain_graph(), &HealthWeights::default());
+        let md = report.to_markdown();
+        assert!(md.starts_with("## Health score: 100/100"));
+        assert!(md.contains("| chain-length | 2 | -0 |"), "{}", md);
+    }
+}
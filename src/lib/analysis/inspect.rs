@@ -0,0 +1,307 @@
+//! Everything known about one instance, gathered in one place.
+//!
+//! The diagrams show an instance in context; an [`Inspection`] lists its
+//! details instead: which component it instantiates and how big that is,
+//! what it imports and from where, what it provides and to whom, the
+//! metadata the component carries and where it sits in the handler chains.
+
+use crate::model::CompositionGraph;
+use crate::{find_chain_interfaces, get_chain_for};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Provider shown for interfaces imported from the host.
+const HOST: &str = "host";
+
+/// The details of one instance.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Inspection {
+    /// Graph ID of the instance.
+    pub id: u32,
+    /// Name from the name section, without the `$` prefix.
+    pub name: String,
+    pub label: String,
+    pub component_index: u32,
+    pub component_num: u32,
+    pub component_name: Option<String>,
+    /// `package@version`, if known.
+    pub package: Option<String>,
+    pub depth: u32,
+    pub size: Option<u64>,
+    pub digest: Option<String>,
+    pub features: Vec<String>,
+    pub runs_start: bool,
+    pub copies: u32,
+    pub imports: Vec<Import>,
+    pub provides: Vec<Provided>,
+    /// Names the composition exports the instance's items under.
+    pub exported_as: Vec<String>,
+    /// Set metadata fields of the component, such as `authors`.
+    pub metadata: BTreeMap<String, String>,
+    /// Summary of the tools that produced the component.
+    pub producers: Option<String>,
+    pub chains: Vec<ChainPosition>,
+}
+
+/// One import and where it comes from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Import {
+    pub interface: String,
+    /// What is imported: `instance`, `func`, `value` or `type`.
+    pub kind: &'static str,
+    /// Display label of the providing instance, or `host`.
+    pub provider: String,
+}
+
+/// One item other instances import from the instance.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Provided {
+    pub interface: String,
+    /// Display labels of the importing instances, in graph order.
+    pub importers: Vec<String>,
+}
+
+/// Where the instance sits in one handler chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChainPosition {
+    pub interface: String,
+    /// 1 for the outermost instance.
+    pub position: usize,
+    /// Display labels of the whole chain, outermost first.
+    pub chain: Vec<String>,
+}
+
+/// Inspect instance `id` of `graph`, or `None` if there is no such instance.
+pub fn inspect(graph: &CompositionGraph, id: u32) -> Option<Inspection> {
+    let node = graph.get_node(id)?;
+    let label = |id: u32| {
+        graph
+            .get_node(id)
+            .map_or_else(|| id.to_string(), |n| n.display_label().to_string())
+    };
+
+    let imports = node
+        .imports
+        .iter()
+        .map(|c| Import {
+            interface: c.interface_name.clone(),
+            kind: c.kind.as_str(),
+            provider: match c.source_instance {
+                Some(source) if !c.is_host_import => label(source),
+                _ => HOST.to_string(),
+            },
+        })
+        .collect();
+
+    let mut provides: Vec<Provided> = Vec::new();
+    for (&importer, other) in &graph.nodes {
+        for conn in &other.imports {
+            if conn.is_host_import || conn.source_instance != Some(id) {
+                continue;
+            }
+            let importer = label(importer);
+            match provides
+                .iter_mut()
+                .find(|p| p.interface == conn.interface_name)
+            {
+                Some(p) => p.importers.push(importer),
+                None => provides.push(Provided {
+                    interface: conn.interface_name.clone(),
+                    importers: vec![importer],
+                }),
+            }
+        }
+    }
+
+    let metadata = graph.metadata.get(&Some(node.component_num));
+    let chains = find_chain_interfaces(graph)
+        .into_iter()
+        .filter_map(|interface| {
+            let chain = get_chain_for(graph, &interface);
+            let position = chain.iter().position(|&c| c == id)? + 1;
+            Some(ChainPosition {
+                interface,
+                position,
+                chain: chain.into_iter().map(label).collect(),
+            })
+        })
+        .collect();
+
+    Some(Inspection {
+        id,
+        name: node.name.trim_start_matches('$').to_string(),
+        label: node.display_label().to_string(),
+        component_index: node.component_index,
+        component_num: node.component_num,
+        component_name: node.component_name.clone(),
+        package: node.package_id(),
+        depth: node.depth,
+        size: node.size,
+        digest: node.digest.clone(),
+        features: node.features.iter().map(|f| f.to_string()).collect(),
+        runs_start: node.runs_start,
+        copies: node.copies,
+        imports,
+        provides,
+        exported_as: graph
+            .component_exports
+            .iter()
+            .filter(|e| e.source_instance == id)
+            .map(|e| e.name.clone())
+            .collect(),
+        metadata: metadata
+            .map(|m| {
+                m.fields()
+                    .into_iter()
+                    .map(|(field, value)| (field.to_string(), value.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        producers: metadata.and_then(|m| m.producer_summary()),
+        chains,
+    })
+}
+
+impl Inspection {
+    /// Render the inspection as plain text: a header of component details,
+    /// then a section for each non-empty list.
+    pub fn to_text(&self) -> String {
+        let mut out = format!("{} (instance {})\n", self.label, self.id);
+        let mut field = |name: &str, value: String| {
+            out.push_str(&format!("  {:<11} {}\n", format!("{}:", name), value));
+        };
+        if self.name != self.label {
+            field("name", self.name.clone());
+        }
+        field(
+            "component",
+            format!(
+                "#{} (index {}, depth {})",
+                self.component_num, self.component_index, self.depth
+            ),
+        );
+        if let Some(name) = &self.component_name {
+            field("defined as", name.clone());
+        }
+        if let Some(package) = &self.package {
+            field("package", package.clone());
+        }
+        if let Some(size) = self.size {
+            field("size", format!("{} bytes", size));
+        }
+        if let Some(digest) = &self.digest {
+            field("digest", digest.clone());
+        }
+        if !self.features.is_empty() {
+            field("features", self.features.join(", "));
+        }
+        if self.runs_start {
+            field("start", "runs start logic when instantiated".to_string());
+        }
+        if self.copies > 1 {
+            field("copies", self.copies.to_string());
+        }
+
+        let mut section = |title: &str, lines: Vec<String>| {
+            if lines.is_empty() {
+                return;
+            }
+            out.push_str(&format!("\n{}:\n", title));
+            for line in lines {
+                out.push_str(&format!("  {}\n", line));
+            }
+        };
+        section(
+            "Imports",
+            self.imports
+                .iter()
+                .map(|i| match i.kind {
+                    "instance" => format!("{} from {}", i.interface, i.provider),
+                    kind => format!("{} {} from {}", kind, i.interface, i.provider),
+                })
+                .collect(),
+        );
+        section(
+            "Provides",
+            self.provides
+                .iter()
+                .map(|p| format!("{} to {}", p.interface, p.importers.join(", ")))
+                .collect(),
+        );
+        section("Exported as", self.exported_as.clone());
+        let mut metadata: Vec<String> = self
+            .metadata
+            .iter()
+            .map(|(field, value)| format!("{}: {}", field, value))
+            .collect();
+        metadata.extend(self.producers.iter().map(|p| format!("producers: {}", p)));
+        section("Metadata", metadata);
+        section(
+            "Chains",
+            self.chains
+                .iter()
+                .map(|c| {
+                    format!(
+                        "{}: {} of {} ({})",
+                        c.interface,
+                        c.position,
+                        c.chain.len(),
+                        c.chain.join(" → ")
+                    )
+                })
+                .collect(),
+        );
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    #[test]
+    fn test_inspect_middle_of_chain() {
+        // gateway → service → backend
+        let graph = long_chain_graph();
+        let id = graph.find_node("service").unwrap();
+        let report = inspect(&graph, id).unwrap();
+        assert_eq!(report.provides.len(), 1);
+        assert_eq!(report.provides[0].importers, vec!["gateway"]);
+        assert!(report.exported_as.is_empty());
+        assert_eq!(report.chains.len(), 1);
+        assert_eq!(report.chains[0].position, 2);
+        assert_eq!(
+            report.chains[0].chain,
+            vec!["gateway", "service", "backend"]
+        );
+    }
+
+    #[test]
+    fn test_text() {
+        let graph = simple_chain_graph();
+        let mut report = inspect(&graph, 2).unwrap();
+        report.size = Some(1024);
+        report.producers = Some("Rust / wit-component 0.239.0".to_string());
+        assert_eq!(
+            report.to_text(),
+            "middleware (instance 2)\n\
+             \x20 component:  #1 (index 1, depth 0)\n\
+             \x20 size:       1024 bytes\n\
+             \n\
+             Imports:\n\
+             \x20 wasi:http/handler@0.3.0 from srv\n\
+             \x20 wasi:logging/log@0.1.0 from host\n\
+             \n\
+             Exported as:\n\
+             \x20 wasi:http/handler@0.3.0\n\
+             \n\
+             Metadata:\n\
+             \x20 producers: Rust / wit-component 0.239.0\n\
+             \n\
+             Chains:\n\
+             \x20 wasi:http/handler@0.3.0: 1 of 2 (middleware → srv)\n"
+        );
+        assert!(inspect(&graph, 9).is_none());
+    }
+}
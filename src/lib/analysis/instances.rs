@@ -0,0 +1,127 @@
+//! A one-line-per-instance overview of the composition.
+//!
+//! Before drawing anything it helps to know what there is to draw: which
+//! instances exist, which component each instantiates, how many imports and
+//! exports each one has and how big its component is.
+
+use crate::model::{CompositionGraph, SYNTHETIC_COMPONENT};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// One instance of the composition.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct InstanceSummary {
+    /// Graph ID of the instance.
+    pub id: u32,
+    /// Display label of the instance.
+    pub name: String,
+    pub component_index: u32,
+    /// Number of instantiation arguments, from the host or other instances.
+    pub imports: usize,
+    /// Number of distinct names other instances import from it or the
+    /// composition exports it under.
+    pub exports: usize,
+    /// Size of the component in bytes, if known.
+    pub size: Option<u64>,
+}
+
+/// Every real instance of `graph`, in graph order.
+pub fn instance_summaries(graph: &CompositionGraph) -> Vec<InstanceSummary> {
+    let mut exports: BTreeMap<u32, BTreeSet<&str>> = BTreeMap::new();
+    for conn in graph.nodes.values().flat_map(|n| &n.imports) {
+        if let (false, Some(provider)) = (conn.is_host_import, conn.source_instance) {
+            let name = conn.source_export.as_ref().unwrap_or(&conn.interface_name);
+            exports.entry(provider).or_default().insert(name);
+        }
+    }
+    for export in &graph.component_exports {
+        exports
+            .entry(export.source_instance)
+            .or_default()
+            .insert(&export.name);
+    }
+
+    graph
+        .nodes
+        .iter()
+        .filter(|(_, n)| n.component_index != SYNTHETIC_COMPONENT)
+        .map(|(&id, node)| InstanceSummary {
+            id,
+            name: node.display_label().to_string(),
+            component_index: node.component_index,
+            imports: node.imports.len(),
+            exports: exports.get(&id).map_or(0, BTreeSet::len),
+            size: node.size,
+        })
+        .collect()
+}
+
+/// Render `instances` as an aligned plain-text table.
+pub fn instances_table(instances: &[InstanceSummary]) -> String {
+    if instances.is_empty() {
+        return "No instances.\n".to_string();
+    }
+
+    let header = ["ID", "NAME", "COMPONENT", "IMPORTS", "EXPORTS", "SIZE"];
+    let rows: Vec<[String; 6]> = instances
+        .iter()
+        .map(|i| {
+            [
+                i.id.to_string(),
+                i.name.clone(),
+                i.component_index.to_string(),
+                i.imports.to_string(),
+                i.exports.to_string(),
+                i.size.map_or_else(|| "-".to_string(), |s| s.to_string()),
+            ]
+        })
+        .collect();
+
+    super::text_table(header, &rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    #[test]
+    fn test_instance_summaries() {
+        let mut graph = simple_chain_graph();
+        graph.nodes.get_mut(&1).unwrap().size = Some(2048);
+        let instances = instance_summaries(&graph);
+        assert_eq!(
+            instances,
+            vec![
+                InstanceSummary {
+                    id: 1,
+                    name: "srv".to_string(),
+                    component_index: 0,
+                    imports: 1,
+                    exports: 1,
+                    size: Some(2048),
+                },
+                InstanceSummary {
+                    id: 2,
+                    name: "middleware".to_string(),
+                    component_index: 1,
+                    imports: 2,
+                    exports: 1,
+                    size: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_table() {
+        let table = instances_table(&instance_summaries(&simple_chain_graph()));
+        assert_eq!(
+            table,
+            "ID  NAME        COMPONENT  IMPORTS  EXPORTS  SIZE\n\
+             1   srv         0          1        1        -\n\
+             2   middleware  1          2        1        -\n"
+        );
+        assert!(instances_table(&[]).starts_with("No instances"));
+    }
+}
@@ -0,0 +1,181 @@
+//! Every interface the composition imports or exports, in one list.
+//!
+//! The diagrams answer "what is wired to what"; this list answers "which
+//! interfaces are in play at all": at which version, how many instances deal
+//! in each, and which ones the host has to provide.
+
+use crate::model::{CompositionGraph, ExternKind, SYNTHETIC_COMPONENT};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// One interface, at one version, as used across the composition.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct InterfaceUse {
+    /// Interface name without its version, such as `wasi:http/handler`.
+    pub interface: String,
+    pub version: Option<String>,
+    /// Whether some instance imports it.
+    pub imported: bool,
+    /// Whether the composition exports it.
+    pub exported: bool,
+    /// Number of instances that import or provide it.
+    pub instances: usize,
+    /// Whether the host provides it to at least one instance.
+    pub host_provided: bool,
+}
+
+/// Every interface imported by an instance or exported by the composition,
+/// ordered by name and version.  Functions, values and types imported or
+/// exported on their own are not interfaces and are left out.
+///
+/// Renamed plugs count under both names: the importer uses the import's
+/// name, the provider the name it exports the interface under.
+pub fn interface_uses(graph: &CompositionGraph) -> Vec<InterfaceUse> {
+    #[derive(Default)]
+    struct Use {
+        imported: bool,
+        exported: bool,
+        instances: BTreeSet<u32>,
+        host_provided: bool,
+    }
+    let is_real = |id: u32| {
+        graph
+            .get_node(id)
+            .is_some_and(|n| n.component_index != SYNTHETIC_COMPONENT)
+    };
+
+    let mut uses: BTreeMap<&str, Use> = BTreeMap::new();
+    for (&id, node) in &graph.nodes {
+        if !is_real(id) {
+            continue;
+        }
+        for conn in node
+            .imports
+            .iter()
+            .filter(|c| c.kind == ExternKind::Instance)
+        {
+            let entry = uses.entry(conn.interface_name.as_str()).or_default();
+            entry.imported = true;
+            entry.instances.insert(id);
+            match conn.source_instance {
+                Some(provider) if !conn.is_host_import => {
+                    if is_real(provider) {
+                        let export = conn.source_export.as_ref().unwrap_or(&conn.interface_name);
+                        uses.entry(export).or_default().instances.insert(provider);
+                    }
+                }
+                _ => entry.host_provided = true,
+            }
+        }
+    }
+    for export in graph
+        .component_exports
+        .iter()
+        .filter(|e| e.kind == ExternKind::Instance)
+    {
+        let entry = uses.entry(export.name.as_str()).or_default();
+        entry.exported = true;
+        if is_real(export.source_instance) {
+            entry.instances.insert(export.source_instance);
+        }
+    }
+
+    let mut out: Vec<InterfaceUse> = uses
+        .into_iter()
+        .map(|(name, u)| {
+            let (interface, version) = match name.split_once('@') {
+                Some((base, version)) => (base, Some(version.to_string())),
+                None => (name, None),
+            };
+            InterfaceUse {
+                interface: interface.to_string(),
+                version,
+                imported: u.imported,
+                exported: u.exported,
+                instances: u.instances.len(),
+                host_provided: u.host_provided,
+            }
+        })
+        .collect();
+    out.sort_by(|a, b| (&a.interface, &a.version).cmp(&(&b.interface, &b.version)));
+    out
+}
+
+/// Render `uses` as an aligned plain-text table.
+pub fn interfaces_table(uses: &[InterfaceUse]) -> String {
+    if uses.is_empty() {
+        return "No interfaces are imported or exported.\n".to_string();
+    }
+
+    let header = ["INTERFACE", "VERSION", "DIRECTION", "INSTANCES", "HOST"];
+    let rows: Vec<[String; 5]> = uses
+        .iter()
+        .map(|u| {
+            let direction = match (u.imported, u.exported) {
+                (true, true) => "import, export",
+                (true, false) => "import",
+                _ => "export",
+            };
+            [
+                u.interface.clone(),
+                u.version.clone().unwrap_or_else(|| "-".to_string()),
+                direction.to_string(),
+                u.instances.to_string(),
+                if u.host_provided { "yes" } else { "no" }.to_string(),
+            ]
+        })
+        .collect();
+
+    super::text_table(header, &rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    #[test]
+    fn test_interface_uses() {
+        // srv imports the handler from the host and provides it to
+        // middleware, which logs to the host and is exported as the handler
+        let uses = interface_uses(&simple_chain_graph());
+        assert_eq!(
+            uses,
+            vec![
+                InterfaceUse {
+                    interface: "wasi:http/handler".to_string(),
+                    version: Some("0.3.0".to_string()),
+                    imported: true,
+                    exported: true,
+                    instances: 2,
+                    host_provided: true,
+                },
+                InterfaceUse {
+                    interface: "wasi:logging/log".to_string(),
+                    version: Some("0.1.0".to_string()),
+                    imported: true,
+                    exported: false,
+                    instances: 1,
+                    host_provided: true,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_table() {
+        let table = interfaces_table(&interface_uses(&simple_chain_graph()));
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(
+            lines[0].starts_with("INTERFACE          VERSION"),
+            "{}",
+            table
+        );
+        assert_eq!(
+            lines[2],
+            "wasi:logging/log   0.1.0    import          1          yes"
+        );
+        assert!(interfaces_table(&[]).starts_with("No interfaces"));
+    }
+}
@@ -0,0 +1,519 @@
+//! Whole-graph analyses built on top of a parsed [`CompositionGraph`].
+//!
+//! Each helper here answers one question about the composition (how long is
+//! the longest chain, which instances are never used, ...).  The results are
+//! combined into a single opinionated number by [`health`], and reported as
+//! individually configurable findings by [`diagnostics`], while [`rules`]
+//! checks project-specific assertions and [`topology`] compares against an
+//! expected wiring; [`diff`] summarizes how two versions differ; [`stats`]
+//! counts without judging, [`order`] lists instances as the runtime creates them,
+//! [`reachability`] finds the host interfaces behind an export and
+//! [`capabilities`] tabulates them for every instance, [`audit`] reviews
+//! the sensitive ones against an allow-list, [`paths`] traces the
+//! routes from one instance to another, [`critical`] finds the instances
+//! an export cannot reach the host without, [`inspect`] gathers the
+//! details of a single instance, [`instances`], [`interfaces`] and
+//! [`exports`] list every instance, every interface in play and what the
+//! composition serves, [`composer`] names the tool that built the
+//! binary, while [`sbom`] lists the components it embeds and [`sizes`]
+//! ranks them by how much of the binary they take up; [`why`] explains
+//! what keeps an instance in the composition.
+
+use crate::model::{ComponentNode, CompositionGraph, SYNTHETIC_COMPONENT};
+use crate::{find_chain_interfaces, get_chain_for};
+use std::collections::{BTreeMap, BTreeSet};
+
+pub mod audit;
+pub mod capabilities;
+pub mod composer;
+pub mod critical;
+pub mod diagnostics;
+pub mod diff;
+pub mod exports;
+pub mod health;
+pub mod inspect;
+pub mod instances;
+pub mod interfaces;
+pub mod order;
+pub mod paths;
+pub mod reachability;
+pub mod renames;
+pub mod rules;
+pub mod sbom;
+pub mod sizes;
+pub mod stats;
+pub mod topology;
+pub mod why;
+
+/// An interface that is referenced at more than one version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionMismatch {
+    /// Interface name without the `@version` suffix, e.g. `wasi:http/handler`.
+    pub interface: String,
+    /// Every distinct version seen (unversioned references are `""`).
+    pub versions: Vec<String>,
+}
+
+/// Length (in instances) of the longest handler chain, or 0 if there are none.
+pub fn longest_chain(graph: &CompositionGraph) -> usize {
+    find_chain_interfaces(graph)
+        .iter()
+        .map(|iface| get_chain_for(graph, iface).len())
+        .max()
+        .unwrap_or(0)
+}
+
+/// Distinct interfaces imported from the host, sorted by name.
+pub fn host_capabilities(graph: &CompositionGraph) -> Vec<&str> {
+    graph
+        .nodes
+        .values()
+        .flat_map(|n| n.imports.iter())
+        .filter(|c| c.is_host_import)
+        .map(|c| c.interface_name.as_str())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// Graph IDs of real component instances that nothing depends on.
+///
+/// An instance is considered used when another instance imports from it or
+/// when it backs one of the composition's exports.  Synthetic instances are
+/// never reported, and neither are instances without any imports: those are
+/// almost always shims nested inside another component (e.g. the export
+/// shims emitted by `wit-component`), which are used by their parent in a
+/// scope the graph does not model.
+pub fn unused_instances(graph: &CompositionGraph) -> Vec<u32> {
+    let used: BTreeSet<u32> = graph
+        .nodes
+        .values()
+        .flat_map(|n| n.imports.iter())
+        .filter_map(|c| c.source_instance)
+        .chain(graph.component_exports.iter().map(|e| e.source_instance))
+        .collect();
+
+    graph
+        .nodes
+        .iter()
+        .filter(|(id, node)| {
+            node.component_index != SYNTHETIC_COMPONENT
+                && !node.imports.is_empty()
+                && !used.contains(id)
+        })
+        .map(|(&id, _)| id)
+        .collect()
+}
+
+/// A real instance that several other real instances import from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SharedInstance {
+    /// Graph ID of the shared instance.
+    pub instance: u32,
+    /// Graph IDs of the instances importing from it, in graph order.
+    pub importers: Vec<u32>,
+}
+
+/// Instances imported from by more than one other instance, in graph order.
+///
+/// Whatever state such an instance keeps is shared by all its importers,
+/// which makes it one of the riskier parts of a composition to change.
+/// Imports from synthetic instances, and by them, are not counted.
+pub fn shared_instances(graph: &CompositionGraph) -> Vec<SharedInstance> {
+    let mut importers: BTreeMap<u32, BTreeSet<u32>> = BTreeMap::new();
+    for (&id, node) in &graph.nodes {
+        if node.component_index == SYNTHETIC_COMPONENT {
+            continue;
+        }
+        for conn in node.imports.iter().filter(|c| !c.is_host_import) {
+            if let Some(source) = conn.source_instance.filter(|&s| s != id) {
+                importers.entry(source).or_default().insert(id);
+            }
+        }
+    }
+    importers
+        .into_iter()
+        .filter(|(id, by)| {
+            by.len() > 1
+                && graph
+                    .get_node(*id)
+                    .is_some_and(|n| n.component_index != SYNTHETIC_COMPONENT)
+        })
+        .map(|(instance, by)| SharedInstance {
+            instance,
+            importers: by.into_iter().collect(),
+        })
+        .collect()
+}
+
+/// An import satisfied by another instance's export of the same interface
+/// at a version it is not compatible with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IncompatibleVersion {
+    /// Graph ID of the importing instance.
+    pub instance: u32,
+    /// Name of the import, e.g. `wasi:http/handler@0.3.0-rc-2026-01-06`.
+    pub import: String,
+    /// Graph ID of the providing instance.
+    pub provider: u32,
+    /// Name the provider exports the interface under.
+    pub export: String,
+}
+
+/// Connections whose import and provided export name the same interface at
+/// semver-incompatible versions.
+///
+/// Versions follow the component model's rules: `1.2.0` and `1.5.1` are
+/// compatible, as are `0.2.0` and `0.2.3`, but `0.2.x` and `0.3.x` are not,
+/// and a pre-release such as `0.3.0-rc-2026-01-06` only matches itself.
+/// Connections to the host are not checked, since the host's version is not
+/// known.
+pub fn incompatible_versions(graph: &CompositionGraph) -> Vec<IncompatibleVersion> {
+    let mut out = Vec::new();
+    for (&id, node) in &graph.nodes {
+        for conn in &node.imports {
+            let (Some(provider), Some(export)) = (conn.source_instance, &conn.source_export) else {
+                continue;
+            };
+            if conn.is_host_import || unversioned(export) != unversioned(&conn.interface_name) {
+                continue;
+            }
+            let version = |name: &str| name.split_once('@').map(|(_, v)| v.to_string());
+            if !versions_compatible(
+                version(&conn.interface_name).as_deref(),
+                version(export).as_deref(),
+            ) {
+                out.push(IncompatibleVersion {
+                    instance: id,
+                    import: conn.interface_name.clone(),
+                    provider,
+                    export: export.clone(),
+                });
+            }
+        }
+    }
+    out
+}
+
+/// Whether an import at version `wanted` can be satisfied by an export at
+/// version `provided` (`None` for an unversioned name).
+pub fn versions_compatible(wanted: Option<&str>, provided: Option<&str>) -> bool {
+    let (Some(wanted), Some(provided)) = (wanted, provided) else {
+        return wanted == provided;
+    };
+    let (Ok(a), Ok(b)) = (
+        semver::Version::parse(wanted),
+        semver::Version::parse(provided),
+    ) else {
+        return wanted == provided;
+    };
+    if !a.pre.is_empty() || !b.pre.is_empty() {
+        return a == b;
+    }
+    match (a.major, a.minor) {
+        (0, 0) => a.patch == b.patch && b.major == 0 && b.minor == 0,
+        (0, minor) => b.major == 0 && b.minor == minor,
+        (major, _) => b.major == major,
+    }
+}
+
+/// An import that is not wired the way the importing component declares it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsatisfiedImport {
+    /// Graph ID of the importing instance.
+    pub instance: u32,
+    /// The declared import, or the argument the component does not declare.
+    pub interface: String,
+    pub problem: ImportProblem,
+}
+
+/// What is wrong with an [`UnsatisfiedImport`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportProblem {
+    /// Declared by the component, but nothing is plugged in for it.
+    Unwired,
+    /// Plugged in, but the component declares no such import.  `expected`
+    /// is the unwired import of the same interface at another version, if
+    /// there is one.
+    Undeclared { expected: Option<String> },
+}
+
+/// Imports left unwired or wired under a name the component does not
+/// import, for every instance whose declared imports are known.
+///
+/// A mismatched argument is reported once, as [`ImportProblem::Undeclared`]
+/// naming the import it was probably meant for, rather than also reporting
+/// that import as unwired.
+pub fn unsatisfied_imports(graph: &CompositionGraph) -> Vec<UnsatisfiedImport> {
+    let mut out = Vec::new();
+    for (&id, node) in &graph.nodes {
+        let Some(declared) = &node.declared_imports else {
+            continue;
+        };
+        let wired: BTreeSet<&str> = node
+            .imports
+            .iter()
+            .map(|c| c.interface_name.as_str())
+            .collect();
+        let unwired: Vec<&str> = declared
+            .iter()
+            .map(String::as_str)
+            .filter(|d| !wired.contains(d))
+            .collect();
+
+        let mut paired = BTreeSet::new();
+        let mut undeclared = Vec::new();
+        for &arg in wired.iter().filter(|&&w| !declared.contains(w)) {
+            let expected = unwired
+                .iter()
+                .find(|d| unversioned(d) == unversioned(arg))
+                .map(|d| d.to_string());
+            if let Some(d) = &expected {
+                paired.insert(d.clone());
+            }
+            undeclared.push(UnsatisfiedImport {
+                instance: id,
+                interface: arg.to_string(),
+                problem: ImportProblem::Undeclared { expected },
+            });
+        }
+
+        out.extend(
+            unwired
+                .into_iter()
+                .filter(|d| !paired.contains(*d))
+                .map(|d| UnsatisfiedImport {
+                    instance: id,
+                    interface: d.to_string(),
+                    problem: ImportProblem::Unwired,
+                }),
+        );
+        out.extend(undeclared);
+    }
+    out
+}
+
+/// Lay out `rows` under `header` as a plain-text table, with columns
+/// padded to the widest cell and separated by two spaces.
+pub(crate) fn text_table<const N: usize>(header: [&str; N], rows: &[[String; N]]) -> String {
+    let mut widths = header.map(str::len);
+    for row in rows {
+        for (w, cell) in widths.iter_mut().zip(row) {
+            *w = (*w).max(cell.chars().count());
+        }
+    }
+
+    let mut out = String::new();
+    let mut push_row = |cells: [&str; N]| {
+        let line: Vec<String> = cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, w)| format!("{:<w$}", cell, w = w))
+            .collect();
+        out.push_str(line.join("  ").trim_end());
+        out.push('\n');
+    };
+    push_row(header);
+    for row in rows {
+        push_row(std::array::from_fn(|i| row[i].as_str()));
+    }
+    out
+}
+
+/// A name for the component `node` instantiates: its package, the name
+/// its metadata or the name section gives it, or else the instance's label.
+pub(crate) fn component_name(graph: &CompositionGraph, node: &ComponentNode) -> String {
+    let metadata = graph.metadata.get(&Some(node.component_num));
+    node.package
+        .clone()
+        .or_else(|| metadata.and_then(|m| m.name.clone()))
+        .or_else(|| node.component_name.clone())
+        .unwrap_or_else(|| node.display_label().to_string())
+}
+
+/// `name` without its `@version` suffix.
+fn unversioned(name: &str) -> &str {
+    name.split_once('@').map_or(name, |(base, _)| base)
+}
+
+/// Interfaces that appear at more than one version across imports and exports.
+pub fn version_mismatches(graph: &CompositionGraph) -> Vec<VersionMismatch> {
+    let names = graph
+        .nodes
+        .values()
+        .flat_map(|n| n.imports.iter().map(|c| c.interface_name.as_str()))
+        .chain(graph.component_exports.iter().map(|e| e.name.as_str()));
+
+    let mut versions: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    for name in names {
+        let version = name.split_once('@').map_or("", |(_, v)| v);
+        versions
+            .entry(unversioned(name))
+            .or_default()
+            .insert(version);
+    }
+
+    versions
+        .into_iter()
+        .filter(|(_, v)| v.len() > 1)
+        .map(|(base, v)| VersionMismatch {
+            interface: base.to_string(),
+            versions: v.into_iter().map(str::to_string).collect(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{ExternKind, InterfaceConnection};
+    use crate::test_utils::*;
+
+    #[test]
+    fn test_longest_chain() {
+        assert_eq!(longest_chain(&simple_chain_graph()), 2);
+        assert_eq!(longest_chain(&long_chain_graph()), 3);
+        assert_eq!(longest_chain(&CompositionGraph::new()), 0);
+    }
+
+    #[test]
+    fn test_host_capabilities_deduplicated() {
+        let graph = simple_chain_graph();
+        assert_eq!(
+            host_capabilities(&graph),
+            vec!["wasi:http/handler@0.3.0", "wasi:logging/log@0.1.0"]
+        );
+    }
+
+    #[test]
+    fn test_unused_instances() {
+        let mut graph = simple_chain_graph();
+        assert!(unused_instances(&graph).is_empty());
+
+        graph.add_node(42, orphan_node());
+        assert_eq!(unused_instances(&graph), vec![42]);
+    }
+
+    #[test]
+    fn test_import_free_shims_not_reported() {
+        let mut graph = simple_chain_graph();
+        graph.add_node(
+            42,
+            ComponentNode::new("$wasi:http/handler@0.3.0-shim-instance".to_string(), 0, 3),
+        );
+        assert!(unused_instances(&graph).is_empty());
+    }
+
+    #[test]
+    fn test_unsatisfied_imports() {
+        let mut graph = simple_chain_graph();
+        assert!(unsatisfied_imports(&graph).is_empty());
+
+        // $middleware declares the handler at another version and a store
+        // nobody provides; it is given handler@0.3.0 and log
+        graph.nodes.get_mut(&2).unwrap().declared_imports = Some(BTreeSet::from([
+            "wasi:http/handler@0.2.0".to_string(),
+            "wasi:keyvalue/store@0.1.0".to_string(),
+        ]));
+        assert_eq!(
+            unsatisfied_imports(&graph),
+            vec![
+                UnsatisfiedImport {
+                    instance: 2,
+                    interface: "wasi:keyvalue/store@0.1.0".to_string(),
+                    problem: ImportProblem::Unwired,
+                },
+                UnsatisfiedImport {
+                    instance: 2,
+                    interface: "wasi:http/handler@0.3.0".to_string(),
+                    problem: ImportProblem::Undeclared {
+                        expected: Some("wasi:http/handler@0.2.0".to_string()),
+                    },
+                },
+                UnsatisfiedImport {
+                    instance: 2,
+                    interface: "wasi:logging/log@0.1.0".to_string(),
+                    problem: ImportProblem::Undeclared { expected: None },
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_versions_compatible() {
+        assert!(versions_compatible(Some("1.2.0"), Some("1.5.1")));
+        assert!(versions_compatible(Some("0.2.0"), Some("0.2.3")));
+        assert!(!versions_compatible(Some("0.2.0"), Some("0.3.0")));
+        assert!(!versions_compatible(Some("0.0.1"), Some("0.0.2")));
+        assert!(!versions_compatible(Some("1.0.0"), Some("2.0.0")));
+        assert!(versions_compatible(
+            Some("0.3.0-rc-2026-01-06"),
+            Some("0.3.0-rc-2026-01-06")
+        ));
+        assert!(!versions_compatible(
+            Some("0.3.0-rc-2026-01-06"),
+            Some("0.3.0-rc-2025-09-16")
+        ));
+        assert!(!versions_compatible(
+            Some("0.3.0-rc-2026-01-06"),
+            Some("0.3.0")
+        ));
+        assert!(versions_compatible(None, None));
+        assert!(!versions_compatible(Some("0.2.0"), None));
+    }
+
+    #[test]
+    fn test_incompatible_versions() {
+        let mut graph = simple_chain_graph();
+        assert!(incompatible_versions(&graph).is_empty());
+
+        // $middleware imports the handler from $srv
+        let conn = &mut graph.nodes.get_mut(&2).unwrap().imports[0];
+        conn.interface_name = "wasi:http/handler@0.3.0-rc-2026-01-06".to_string();
+        conn.source_export = Some("wasi:http/handler@0.3.0-rc-2025-09-16".to_string());
+        assert_eq!(
+            incompatible_versions(&graph),
+            vec![IncompatibleVersion {
+                instance: 2,
+                import: "wasi:http/handler@0.3.0-rc-2026-01-06".to_string(),
+                provider: 1,
+                export: "wasi:http/handler@0.3.0-rc-2025-09-16".to_string(),
+            }]
+        );
+
+        // A compatible patch release is fine, and so is a renamed plug
+        let conn = &mut graph.nodes.get_mut(&2).unwrap().imports[0];
+        conn.source_export = Some("wasi:http/handler@0.3.0-rc-2026-01-06".to_string());
+        assert!(incompatible_versions(&graph).is_empty());
+        let conn = &mut graph.nodes.get_mut(&2).unwrap().imports[0];
+        conn.source_export = Some("my:app/handler@1.0.0".to_string());
+        assert!(incompatible_versions(&graph).is_empty());
+    }
+
+    #[test]
+    fn test_version_mismatches() {
+        let mut graph = simple_chain_graph();
+        assert!(version_mismatches(&graph).is_empty());
+
+        let mut node = ComponentNode::new("$old".to_string(), 7, 7);
+        node.add_import(InterfaceConnection {
+            interface_name: "wasi:http/handler@0.2.0".to_string(),
+            source_instance: None,
+            is_host_import: true,
+            interface_type: None,
+            fingerprint: None,
+            source_export: None,
+            kind: ExternKind::Instance,
+            alias_chain: Vec::new(),
+        });
+        graph.add_node(42, node);
+
+        assert_eq!(
+            version_mismatches(&graph),
+            vec![VersionMismatch {
+                interface: "wasi:http/handler".to_string(),
+                versions: vec!["0.2.0".to_string(), "0.3.0".to_string()],
+            }]
+        );
+    }
+}
@@ -0,0 +1,156 @@
+//! The order in which the runtime instantiates a composition's instances.
+//!
+//! An instance can only be created once everything it imports from exists,
+//! so the order is a topological sort of the import edges.  Ties keep the
+//! order of the graph IDs, which for a parsed binary is the order of the
+//! component's instance section — the order the runtime actually uses.
+//! Knowing it helps when reasoning about start functions, or about which
+//! instantiation failed first.
+
+use crate::model::{CompositionGraph, SYNTHETIC_COMPONENT};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// One instance, in instantiation order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OrderedInstance {
+    /// Position in the order, starting at 1.
+    pub position: usize,
+    /// Display label of the instance.
+    pub instance: String,
+    /// Instances that must exist first because this one imports from them.
+    pub after: Vec<String>,
+    /// Whether instantiating it runs start logic.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub runs_start: bool,
+}
+
+/// Instances the root component creates, in the order they are
+/// instantiated.
+///
+/// Nested instances are created while instantiating the component that
+/// contains them, and interface bundles instantiate nothing, so neither is
+/// listed; an import through a bundle still orders the bundled instances
+/// first.  Instances caught in an import cycle, which the runtime could not
+/// instantiate, come last in graph order.
+pub fn instantiation_order(graph: &CompositionGraph) -> Vec<OrderedInstance> {
+    let listed = |id: &u32| {
+        graph
+            .get_node(*id)
+            .is_some_and(|n| n.depth == 0 && n.component_index != SYNTHETIC_COMPONENT)
+    };
+
+    // Direct dependencies of each listed instance, looking through bundles
+    let mut deps: BTreeMap<u32, BTreeSet<u32>> = BTreeMap::new();
+    for &id in graph.nodes.keys().filter(|id| listed(id)) {
+        let mut found = BTreeSet::new();
+        let mut seen = BTreeSet::new();
+        let mut stack = vec![id];
+        while let Some(current) = stack.pop() {
+            let Some(node) = graph.get_node(current) else {
+                continue;
+            };
+            for src in node.imports.iter().filter_map(|c| c.source_instance) {
+                if !seen.insert(src) || src == id {
+                    continue;
+                }
+                if listed(&src) {
+                    found.insert(src);
+                } else if graph
+                    .get_node(src)
+                    .is_some_and(|n| n.component_index == SYNTHETIC_COMPONENT)
+                {
+                    stack.push(src);
+                }
+            }
+        }
+        deps.insert(id, found);
+    }
+
+    let mut order = Vec::new();
+    let mut done = BTreeSet::new();
+    while done.len() < deps.len() {
+        let ready = deps
+            .iter()
+            .find(|(id, d)| !done.contains(*id) && d.is_subset(&done))
+            .map(|(&id, _)| id);
+        // A cycle: take the first remaining instance anyway
+        let next = ready.unwrap_or_else(|| *deps.keys().find(|id| !done.contains(*id)).unwrap());
+        done.insert(next);
+        order.push(next);
+    }
+
+    let label = |id: u32| graph.nodes[&id].display_label().to_string();
+    order
+        .into_iter()
+        .enumerate()
+        .map(|(i, id)| OrderedInstance {
+            position: i + 1,
+            instance: label(id),
+            after: deps[&id].iter().map(|&d| label(d)).collect(),
+            runs_start: graph.nodes[&id].runs_start,
+        })
+        .collect()
+}
+
+/// Render `order` as a numbered plain-text list.
+pub fn order_text(order: &[OrderedInstance]) -> String {
+    if order.is_empty() {
+        return "No instances to instantiate.\n".to_string();
+    }
+    let width = order
+        .iter()
+        .map(|o| o.instance.chars().count())
+        .max()
+        .unwrap_or(0);
+    let digits = order.len().to_string().len();
+    let mut out = String::new();
+    for o in order {
+        let mut line = format!(
+            "{:>digits$}. {:<width$}",
+            o.position,
+            o.instance,
+            digits = digits,
+            width = width
+        );
+        if o.runs_start {
+            line.push_str("  [start]");
+        }
+        if !o.after.is_empty() {
+            line.push_str(&format!("  after {}", o.after.join(", ")));
+        }
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::ComponentNode;
+    use crate::test_utils::*;
+
+    #[test]
+    fn test_providers_come_first() {
+        let mut graph = simple_chain_graph();
+        // Listed before $srv in graph order, but imports from $middleware
+        let mut first = ComponentNode::new("$first".to_string(), 5, 5);
+        let mut import = graph.nodes[&2].imports[0].clone();
+        import.source_instance = Some(2);
+        first.add_import(import);
+        first.runs_start = true;
+        graph.add_node(0, first);
+
+        let order = instantiation_order(&graph);
+        let names: Vec<&str> = order.iter().map(|o| o.instance.as_str()).collect();
+        assert_eq!(names, vec!["srv", "middleware", "first"]);
+        assert_eq!(order[1].after, vec!["srv"]);
+        assert!(order[2].runs_start);
+
+        assert_eq!(
+            order_text(&order),
+            "1. srv\n2. middleware  after srv\n3. first       [start]  after middleware\n"
+        );
+    }
+}
@@ -0,0 +1,245 @@
+//! Every route by which one instance reaches another, or a host interface.
+//!
+//! Answers questions like "how does the auth middleware end up using the
+//! keyvalue host import?": starting from an instance, imports are followed
+//! in the direction calls are made until the target is found, and each
+//! distinct chain of connections is reported.  Paths never visit an
+//! instance twice, and two imports between the same pair of instances
+//! count as two paths.
+
+use super::unversioned;
+use crate::model::{short_interface_name, CompositionGraph};
+use serde::Serialize;
+
+/// Where a path should end.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathTarget {
+    /// An instance, by graph ID.
+    Instance(u32),
+    /// A host interface, by full name.
+    Host(String),
+}
+
+impl PathTarget {
+    /// Resolve `name` to an instance (see [`CompositionGraph::find_node`])
+    /// or, failing that, to a host interface.  The interface may omit its
+    /// `@version` suffix when only one version is imported from the host.
+    pub fn resolve(graph: &CompositionGraph, name: &str) -> Option<Self> {
+        if let Some(id) = graph.find_node(name) {
+            return Some(Self::Instance(id));
+        }
+        let host = graph.host_interfaces();
+        if host.iter().any(|i| i == name) {
+            return Some(Self::Host(name.to_string()));
+        }
+        let mut matching = host.into_iter().filter(|i| unversioned(i) == name);
+        let interface = matching.next()?;
+        matching.next().is_none().then_some(Self::Host(interface))
+    }
+}
+
+/// One connection along a path: `importer` imports `interface` from
+/// `provider`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Hop {
+    #[serde(skip)]
+    pub importer_id: u32,
+    /// Display label of the importing instance.
+    pub importer: String,
+    /// Full interface name.
+    pub interface: String,
+    /// Graph ID of the providing instance, `None` for the host.
+    #[serde(skip)]
+    pub provider_id: Option<u32>,
+    /// Display label of the providing instance, `None` for the host.
+    pub provider: Option<String>,
+}
+
+/// All paths between two ends of a composition.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Paths {
+    /// Display label of the starting instance.
+    pub from: String,
+    /// Display label of the target instance, or the host interface name.
+    pub to: String,
+    /// Each path as its connections in order, shortest paths first.
+    pub paths: Vec<Vec<Hop>>,
+}
+
+/// Find every path from instance `from` to `to`.
+pub fn connection_paths(graph: &CompositionGraph, from: u32, to: &PathTarget) -> Paths {
+    let label = |id: u32| {
+        graph
+            .get_node(id)
+            .map_or_else(|| id.to_string(), |n| n.display_label().to_string())
+    };
+    let mut paths = Vec::new();
+    walk(
+        graph,
+        from,
+        to,
+        &mut vec![from],
+        &mut Vec::new(),
+        &mut paths,
+    );
+    paths.sort_by_key(Vec::len);
+
+    Paths {
+        from: label(from),
+        to: match to {
+            PathTarget::Instance(id) => label(*id),
+            PathTarget::Host(interface) => interface.clone(),
+        },
+        paths: paths
+            .into_iter()
+            .map(|path| {
+                path.into_iter()
+                    .map(|(importer_id, interface, provider_id)| Hop {
+                        importer_id,
+                        importer: label(importer_id),
+                        interface,
+                        provider_id,
+                        provider: provider_id.map(label),
+                    })
+                    .collect()
+            })
+            .collect(),
+    }
+}
+
+type RawHop = (u32, String, Option<u32>);
+
+/// Depth-first search from the last instance on `visited`, recording each
+/// path that reaches `to` in `found`.
+fn walk(
+    graph: &CompositionGraph,
+    id: u32,
+    to: &PathTarget,
+    visited: &mut Vec<u32>,
+    hops: &mut Vec<RawHop>,
+    found: &mut Vec<Vec<RawHop>>,
+) {
+    let Some(node) = graph.get_node(id) else {
+        return;
+    };
+    for conn in &node.imports {
+        let provider = conn.source_instance.filter(|_| !conn.is_host_import);
+        hops.push((id, conn.interface_name.clone(), provider));
+        match (provider, to) {
+            (None, PathTarget::Host(interface)) if *interface == conn.interface_name => {
+                found.push(hops.clone());
+            }
+            (Some(next), PathTarget::Instance(target)) if next == *target => {
+                found.push(hops.clone());
+            }
+            (Some(next), _) if !visited.contains(&next) => {
+                visited.push(next);
+                walk(graph, next, to, visited, hops, found);
+                visited.pop();
+            }
+            _ => {}
+        }
+        hops.pop();
+    }
+}
+
+impl Paths {
+    /// Render the paths as plain text, one per line, with connections
+    /// labelled by their short interface names.
+    pub fn to_text(&self) -> String {
+        let count = self.paths.len();
+        let mut out = format!(
+            "{} path{} from {} to {}\n",
+            count,
+            if count == 1 { "" } else { "s" },
+            self.from,
+            self.to
+        );
+        if count == 0 {
+            return out;
+        }
+        out.push('\n');
+        let width = count.to_string().len();
+        for (i, path) in self.paths.iter().enumerate() {
+            let mut line = format!("{:>width$}. {}", i + 1, self.from, width = width);
+            for hop in path {
+                line.push_str(&format!(
+                    " ─{}→ {}",
+                    short_interface_name(&hop.interface),
+                    hop.provider.as_deref().unwrap_or("host")
+                ));
+            }
+            out.push_str(&line);
+            out.push('\n');
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{ComponentNode, ExternKind, InterfaceConnection};
+    use crate::test_utils::*;
+
+    fn import(interface: &str, source: Option<u32>) -> InterfaceConnection {
+        InterfaceConnection {
+            interface_name: interface.to_string(),
+            source_instance: source,
+            is_host_import: source.is_none(),
+            interface_type: None,
+            fingerprint: None,
+            source_export: None,
+            kind: ExternKind::Instance,
+            alias_chain: Vec::new(),
+        }
+    }
+
+    /// `auth` reaches `kv` directly and through `cache`.
+    fn diamond() -> CompositionGraph {
+        let mut graph = CompositionGraph::new();
+        let mut kv = ComponentNode::new("$kv".to_string(), 0, 0);
+        kv.add_import(import("wasi:keyvalue/store@0.2.0", None));
+        graph.add_node(1, kv);
+        let mut cache = ComponentNode::new("$cache".to_string(), 1, 1);
+        cache.add_import(import("my:kv/api", Some(1)));
+        graph.add_node(2, cache);
+        let mut auth = ComponentNode::new("$auth".to_string(), 2, 2);
+        auth.add_import(import("my:cache/api", Some(2)));
+        auth.add_import(import("my:kv/api", Some(1)));
+        graph.add_node(3, auth);
+        graph
+    }
+
+    #[test]
+    fn test_paths_to_host_interface() {
+        let graph = diamond();
+        let to = PathTarget::resolve(&graph, "wasi:keyvalue/store").unwrap();
+        assert_eq!(
+            to,
+            PathTarget::Host("wasi:keyvalue/store@0.2.0".to_string())
+        );
+        let paths = connection_paths(&graph, 3, &to);
+        assert_eq!(
+            paths.to_text(),
+            "2 paths from auth to wasi:keyvalue/store@0.2.0\n\n\
+             1. auth ─api→ kv ─store→ host\n\
+             2. auth ─api→ cache ─api→ kv ─store→ host\n"
+        );
+        assert_eq!(paths.paths[1][1].provider_id, Some(1));
+    }
+
+    #[test]
+    fn test_paths_to_instance() {
+        let graph = simple_chain_graph();
+        let to = PathTarget::resolve(&graph, "srv").unwrap();
+        let paths = connection_paths(&graph, 2, &to);
+        assert_eq!(paths.paths.len(), 1);
+        assert_eq!(paths.paths[0][0].interface, "wasi:http/handler@0.3.0");
+
+        // Imports only lead away from the exporting instance
+        let paths = connection_paths(&graph, 1, &PathTarget::Instance(2));
+        assert!(paths.paths.is_empty());
+        assert_eq!(paths.to_text(), "0 paths from srv to middleware\n");
+    }
+}
@@ -0,0 +1,163 @@
+//! The host capabilities reachable behind one exported interface.
+//!
+//! A caller of an export can make the instance providing it do anything
+//! that instance can do, including calling into the instances it imports
+//! from.  Following imports transitively from the export therefore gives
+//! the effective capability surface of that export: every host interface a
+//! request entering through it could end up using.
+//!
+//! The walk is per instance, not per function: an instance that imports a
+//! host interface is assumed to be able to use it from any of its exports.
+
+use super::unversioned;
+use crate::model::{CompositionGraph, SYNTHETIC_COMPONENT};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// What a single export can reach.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Reachability {
+    /// Name of the export the walk started from.
+    pub export: String,
+    /// Display labels of the instances reached, the exporting one first.
+    pub instances: Vec<String>,
+    /// Host interfaces reached, sorted by name.
+    pub host_interfaces: Vec<ReachedInterface>,
+}
+
+/// A host interface reached from an export.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReachedInterface {
+    /// Interface name, e.g. `wasi:filesystem/types@0.2.0`.
+    pub interface: String,
+    /// Display labels of the reached instances importing it from the host.
+    pub via: Vec<String>,
+}
+
+/// Find the export named `export` and walk its imports transitively.
+///
+/// `export` may omit the `@version` suffix when only one version of the
+/// interface is exported.  Returns `None` if no export matches.
+pub fn reachable_from_export(graph: &CompositionGraph, export: &str) -> Option<Reachability> {
+    let exact = graph.component_exports.iter().find(|e| e.name == export);
+    let info = match exact {
+        Some(info) => info,
+        None => {
+            let mut matching = graph
+                .component_exports
+                .iter()
+                .filter(|e| unversioned(&e.name) == export);
+            let info = matching.next()?;
+            if matching.next().is_some() {
+                return None;
+            }
+            info
+        }
+    };
+
+    let mut visited = Vec::new();
+    let mut reached: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for id in reachable_instances(graph, info.source_instance) {
+        let node = &graph.nodes[&id];
+        let real = node.component_index != SYNTHETIC_COMPONENT;
+        if real {
+            visited.push(node.display_label().to_string());
+        }
+        for conn in node.imports.iter().filter(|c| c.is_host_import) {
+            let via = reached.entry(conn.interface_name.as_str()).or_default();
+            if real && !via.iter().any(|v| v == node.display_label()) {
+                via.push(node.display_label().to_string());
+            }
+        }
+    }
+
+    Some(Reachability {
+        export: info.name.clone(),
+        instances: visited,
+        host_interfaces: reached
+            .into_iter()
+            .map(|(interface, via)| ReachedInterface {
+                interface: interface.to_string(),
+                via,
+            })
+            .collect(),
+    })
+}
+
+/// Graph IDs of `start` and every instance it transitively imports from,
+/// breadth-first.  Synthetic instances are included.
+pub(crate) fn reachable_instances(graph: &CompositionGraph, start: u32) -> Vec<u32> {
+    graph.bfs(start).map(|(id, _)| id).collect()
+}
+
+impl Reachability {
+    /// Render the reachable interfaces as plain text, one per line with the
+    /// instances importing it.
+    pub fn to_text(&self) -> String {
+        let mut out = format!(
+            "{} reaches {} host interface{} through {}\n",
+            self.export,
+            self.host_interfaces.len(),
+            if self.host_interfaces.len() == 1 {
+                ""
+            } else {
+                "s"
+            },
+            self.instances.join(" → ")
+        );
+        if self.host_interfaces.is_empty() {
+            return out;
+        }
+        let width = self
+            .host_interfaces
+            .iter()
+            .map(|r| r.interface.chars().count())
+            .max()
+            .unwrap_or(0);
+        out.push('\n');
+        for r in &self.host_interfaces {
+            out.push_str(&format!(
+                "  {:<width$}  via {}\n",
+                r.interface,
+                r.via.join(", "),
+                width = width
+            ));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    #[test]
+    fn test_reaches_through_imports() {
+        let reach = reachable_from_export(&simple_chain_graph(), "wasi:http/handler").unwrap();
+        assert_eq!(reach.export, "wasi:http/handler@0.3.0");
+        assert_eq!(reach.instances, vec!["middleware", "srv"]);
+        assert_eq!(
+            reach.host_interfaces,
+            vec![
+                ReachedInterface {
+                    interface: "wasi:http/handler@0.3.0".to_string(),
+                    via: vec!["srv".to_string()],
+                },
+                ReachedInterface {
+                    interface: "wasi:logging/log@0.1.0".to_string(),
+                    via: vec!["middleware".to_string()],
+                },
+            ]
+        );
+        assert_eq!(
+            reach.to_text().lines().next(),
+            Some("wasi:http/handler@0.3.0 reaches 2 host interfaces through middleware → srv")
+        );
+    }
+
+    #[test]
+    fn test_unknown_export() {
+        assert!(reachable_from_export(&simple_chain_graph(), "wasi:cli/run").is_none());
+    }
+}
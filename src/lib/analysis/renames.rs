@@ -0,0 +1,112 @@
+//! Instantiation arguments that plug an interface in under a different name.
+//!
+//! Composition tools let an instance export (or host import) satisfy an
+//! import of another name.  Such renamed plugs are legitimate, but they are
+//! also the usual answer to "why is my middleware not being called": the
+//! handler that was wired in is not the one its name suggests.
+
+use crate::model::CompositionGraph;
+use serde::Serialize;
+
+/// One instantiation argument whose provider exports it under another name.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RenamedPlug {
+    /// Display label of the instance being instantiated.
+    pub instance: String,
+    /// Name of the import (the `with` argument) on that instance.
+    pub import: String,
+    /// Display label of the providing instance, or `None` for the host.
+    pub provider: Option<String>,
+    /// Name under which the provider offers the interface.
+    pub export: String,
+}
+
+/// Every renamed plug in `graph`, ordered by instance and import name.
+pub fn renamed_plugs(graph: &CompositionGraph) -> Vec<RenamedPlug> {
+    let mut plugs: Vec<RenamedPlug> = graph
+        .nodes
+        .values()
+        .flat_map(|node| {
+            node.imports
+                .iter()
+                .filter(|c| c.is_renamed())
+                .map(move |c| RenamedPlug {
+                    instance: node.display_label().to_string(),
+                    import: c.interface_name.clone(),
+                    provider: c
+                        .source_instance
+                        .and_then(|id| graph.get_node(id))
+                        .map(|n| n.display_label().to_string()),
+                    export: c.source_export.clone().unwrap_or_default(),
+                })
+        })
+        .collect();
+    plugs.sort_by(|a, b| (&a.instance, &a.import).cmp(&(&b.instance, &b.import)));
+    plugs
+}
+
+/// Render `plugs` as an aligned plain-text table.
+pub fn plugs_table(plugs: &[RenamedPlug]) -> String {
+    if plugs.is_empty() {
+        return "No renamed plugs: every import is satisfied under its own name.\n".to_string();
+    }
+
+    let header = ["INSTANCE", "IMPORT", "PROVIDER", "PROVIDED AS"];
+    let rows: Vec<[String; 4]> = plugs
+        .iter()
+        .map(|p| {
+            [
+                p.instance.clone(),
+                p.import.clone(),
+                p.provider.clone().unwrap_or_else(|| "(host)".to_string()),
+                p.export.clone(),
+            ]
+        })
+        .collect();
+
+    super::text_table(header, &rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    fn renamed_graph() -> CompositionGraph {
+        let mut graph = simple_chain_graph();
+        let mw = graph.nodes.get_mut(&2).unwrap();
+        mw.imports[0].interface_name = "my:app/inner-handler".to_string();
+        mw.imports[0].source_export = Some("wasi:http/handler@0.3.0".to_string());
+        mw.imports[1].source_export = Some("wasi:logging/log@0.1.0".to_string());
+        graph
+    }
+
+    #[test]
+    fn test_only_renamed_imports_are_reported() {
+        let plugs = renamed_plugs(&renamed_graph());
+        assert_eq!(
+            plugs,
+            vec![RenamedPlug {
+                instance: "middleware".to_string(),
+                import: "my:app/inner-handler".to_string(),
+                provider: Some("srv".to_string()),
+                export: "wasi:http/handler@0.3.0".to_string(),
+            }]
+        );
+        assert!(renamed_plugs(&simple_chain_graph()).is_empty());
+    }
+
+    #[test]
+    fn test_table() {
+        let table = plugs_table(&renamed_plugs(&renamed_graph()));
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("INSTANCE    IMPORT"), "{}", table);
+        assert!(
+            lines[1].starts_with("middleware  my:app/inner-handler  srv"),
+            "{}",
+            table
+        );
+        assert!(plugs_table(&[]).starts_with("No renamed plugs"));
+    }
+}
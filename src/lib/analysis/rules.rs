@@ -0,0 +1,234 @@
+//! Project-specific assertions about a composition's shape.
+//!
+//! The [diagnostics](super::diagnostics) catch problems any composition can
+//! have; a [`RuleSet`] states what one particular composition must look
+//! like, such as "every handler chain passes through auth before the
+//! router" or "nothing imports `wasi:sockets`".  Rule sets are read from
+//! TOML:
+//!
+//! ```toml
+//! [[rules]]
+//! name = "auth guards the router"
+//! chain-order = ["*auth*", "*router*"]
+//!
+//! [[rules]]
+//! forbid-import = "wasi:sockets/*"
+//! ```
+
+use super::unversioned;
+use crate::model::filter::glob_match;
+use crate::model::{ComponentNode, CompositionGraph, SYNTHETIC_COMPONENT};
+use crate::{find_chain_interfaces, get_chain_for};
+use serde::Deserialize;
+use std::fmt;
+
+/// Rules read from a rules file, checked in order.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RuleSet {
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+}
+
+/// One named assertion.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Rule {
+    /// Shown with each violation; defaults to the assertion's kind.
+    pub name: Option<String>,
+    #[serde(flatten)]
+    pub assertion: Assertion,
+}
+
+/// What a [`Rule`] asserts.  Instance patterns match display labels and
+/// interface patterns match names with or without version; both may use
+/// `*` and `?` wildcards.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Assertion {
+    /// Every handler chain includes an instance matching each pattern, in
+    /// this order from the outermost instance in.
+    ChainOrder(Vec<String>),
+    /// No instance imports an interface matching the pattern.
+    ForbidImport(String),
+    /// The composition exports an interface matching the pattern.
+    RequireExport(String),
+}
+
+/// A rule the composition breaks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// Name of the broken rule.
+    pub rule: String,
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "error[{}]: {}", self.rule, self.message)
+    }
+}
+
+impl RuleSet {
+    /// Check every rule against `graph`, returning the violations in rule
+    /// order.
+    pub fn check(&self, graph: &CompositionGraph) -> Vec<Violation> {
+        let mut out = Vec::new();
+        for rule in &self.rules {
+            let name = rule.name.as_deref().unwrap_or(rule.assertion.kind());
+            for message in rule.assertion.check(graph) {
+                out.push(Violation {
+                    rule: name.to_string(),
+                    message,
+                });
+            }
+        }
+        out
+    }
+}
+
+impl Assertion {
+    /// The key the assertion is written with.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Assertion::ChainOrder(_) => "chain-order",
+            Assertion::ForbidImport(_) => "forbid-import",
+            Assertion::RequireExport(_) => "require-export",
+        }
+    }
+
+    /// A message for each way `graph` breaks the assertion.
+    fn check(&self, graph: &CompositionGraph) -> Vec<String> {
+        match self {
+            Assertion::ChainOrder(patterns) => find_chain_interfaces(graph)
+                .into_iter()
+                .filter_map(|interface| {
+                    let chain: Vec<&ComponentNode> = get_chain_for(graph, &interface)
+                        .into_iter()
+                        .filter_map(|id| graph.get_node(id))
+                        .collect();
+                    chain_order_problem(&chain, patterns)
+                        .map(|problem| format!("in the {} chain, {}", interface, problem))
+                })
+                .collect(),
+            Assertion::ForbidImport(pattern) => graph
+                .nodes
+                .values()
+                .filter(|n| n.component_index != SYNTHETIC_COMPONENT)
+                .flat_map(|n| n.imports.iter().map(move |c| (n, c)))
+                .filter(|(_, c)| interface_matches(pattern, &c.interface_name))
+                .map(|(n, c)| {
+                    format!(
+                        "instance {} imports {}",
+                        n.display_label(),
+                        c.interface_name
+                    )
+                })
+                .collect(),
+            Assertion::RequireExport(pattern) => {
+                let exported = graph
+                    .component_exports
+                    .iter()
+                    .any(|e| interface_matches(pattern, &e.name));
+                if exported {
+                    Vec::new()
+                } else {
+                    vec![format!("nothing matching {} is exported", pattern)]
+                }
+            }
+        }
+    }
+}
+
+/// Why `chain` does not contain instances matching `patterns` in order, if
+/// it does not.
+fn chain_order_problem(chain: &[&ComponentNode], patterns: &[String]) -> Option<String> {
+    let position = |pattern: &str, from: usize| {
+        chain[from..]
+            .iter()
+            .position(|n| instance_matches(pattern, n))
+            .map(|i| i + from)
+    };
+    let mut next = 0;
+    let mut previous: Option<(&str, usize)> = None;
+    for pattern in patterns {
+        match position(pattern, next) {
+            Some(at) => {
+                previous = Some((pattern, at));
+                next = at + 1;
+            }
+            None => {
+                let Some(at) = position(pattern, 0) else {
+                    return Some(format!("no instance matches {}", pattern));
+                };
+                let (before, before_at) = previous?;
+                return Some(format!(
+                    "{} ({}) does not come after {} ({})",
+                    pattern,
+                    chain[at].display_label(),
+                    before,
+                    chain[before_at].display_label()
+                ));
+            }
+        }
+    }
+    None
+}
+
+fn instance_matches(pattern: &str, node: &ComponentNode) -> bool {
+    glob_match(pattern, node.display_label())
+        || glob_match(pattern, node.name.trim_start_matches('$'))
+}
+
+fn interface_matches(pattern: &str, name: &str) -> bool {
+    glob_match(pattern, name) || glob_match(pattern, unversioned(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    fn rules(toml: &str) -> RuleSet {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn test_chain_order() {
+        // gateway → service → backend
+        let graph = long_chain_graph();
+        let ok = rules("[[rules]]\nchain-order = [\"gate*\", \"backend\"]\n");
+        assert!(ok.check(&graph).is_empty());
+
+        let reversed =
+            rules("[[rules]]\nname = \"order\"\nchain-order = [\"backend\", \"gateway\"]\n");
+        let violations = reversed.check(&graph);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(
+            violations[0].to_string(),
+            "error[order]: in the wasi:messaging/consumer@0.2.0 chain, \
+             gateway (gateway) does not come after backend (backend)"
+        );
+
+        let missing = rules("[[rules]]\nchain-order = [\"*auth*\"]\n");
+        assert!(missing.check(&graph)[0]
+            .message
+            .ends_with("no instance matches *auth*"));
+    }
+
+    #[test]
+    fn test_imports_and_exports() {
+        let graph = simple_chain_graph();
+        let set = rules(
+            "[[rules]]\nforbid-import = \"wasi:logging/*\"\n\n\
+             [[rules]]\nrequire-export = \"wasi:http/handler\"\n\n\
+             [[rules]]\nrequire-export = \"wasi:cli/run\"\n",
+        );
+        let violations: Vec<String> = set.check(&graph).iter().map(|v| v.to_string()).collect();
+        assert_eq!(
+            violations,
+            vec![
+                "error[forbid-import]: instance middleware imports wasi:logging/log@0.1.0",
+                "error[require-export]: nothing matching wasi:cli/run is exported",
+            ]
+        );
+    }
+}
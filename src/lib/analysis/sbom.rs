@@ -0,0 +1,433 @@
+//! Software bill of materials for a composition.
+//!
+//! A composed binary embeds every component it was built from.  [`sbom`]
+//! lists them once each, however many instances they back, with their
+//! version, digest, size, license and producers, and records which
+//! components depend on which as implied by the wiring: an instance that
+//! imports from another makes its component depend on the other's.  The
+//! result is written as a [CycloneDX](https://cyclonedx.org) 1.5 or
+//! [SPDX](https://spdx.dev) 2.3 JSON document.
+
+use crate::model::{CompositionGraph, SYNTHETIC_COMPONENT};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// What the document says about itself rather than the components.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SbomOptions {
+    /// Name of the composition, used for the root component.
+    pub name: String,
+    /// Creation time as an RFC 3339 UTC timestamp, e.g. `2026-01-06T12:00:00Z`.
+    pub created: String,
+}
+
+/// One component embedded in the composition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SbomComponent {
+    /// `component_num` of the component definition.
+    pub num: u32,
+    pub name: String,
+    pub version: Option<String>,
+    /// Hex SHA-256 of the embedded bytes.
+    pub sha256: Option<String>,
+    pub size: Option<u64>,
+    /// SPDX license expression.
+    pub licenses: Option<String>,
+    /// `producers` field (`language`, `processed-by`, `sdk`) to tool name
+    /// to version.
+    pub producers: BTreeMap<String, BTreeMap<String, String>>,
+    /// `component_num`s of the components this one imports from.
+    pub depends_on: BTreeSet<u32>,
+}
+
+/// The components and the relationships between them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sbom {
+    /// The root (composed) component's own version, if it carries one.
+    pub version: Option<String>,
+    /// Root component license, if it carries one.
+    pub licenses: Option<String>,
+    /// Embedded components, by `component_num`.
+    pub components: Vec<SbomComponent>,
+    /// `component_num`s of the components backing the composition's exports.
+    pub exported: BTreeSet<u32>,
+}
+
+/// Gather the bill of materials of `graph`.  Synthetic instances are left
+/// out, as they have no component of their own.
+pub fn sbom(graph: &CompositionGraph) -> Sbom {
+    let num_of = |id: u32| {
+        graph
+            .get_node(id)
+            .filter(|n| n.component_index != SYNTHETIC_COMPONENT)
+            .map(|n| n.component_num)
+    };
+
+    let mut components: BTreeMap<u32, SbomComponent> = BTreeMap::new();
+    for node in graph.nodes.values() {
+        if node.component_index == SYNTHETIC_COMPONENT {
+            continue;
+        }
+        let metadata = graph.metadata.get(&Some(node.component_num));
+        let entry = components
+            .entry(node.component_num)
+            .or_insert_with(|| SbomComponent {
+                num: node.component_num,
+                name: super::component_name(graph, node),
+                version: node
+                    .package_version
+                    .clone()
+                    .or_else(|| metadata.and_then(|m| m.version.clone())),
+                sha256: node
+                    .digest
+                    .as_deref()
+                    .map(|d| d.trim_start_matches("sha256:").to_string()),
+                size: node.size,
+                licenses: metadata.and_then(|m| m.licenses.clone()),
+                producers: metadata.map(|m| m.producers.clone()).unwrap_or_default(),
+                depends_on: BTreeSet::new(),
+            });
+        for conn in node.imports.iter().filter(|c| !c.is_host_import) {
+            if let Some(dep) = conn.source_instance.and_then(num_of) {
+                if dep != node.component_num {
+                    entry.depends_on.insert(dep);
+                }
+            }
+        }
+    }
+
+    let root = graph.metadata.get(&None);
+    Sbom {
+        version: root.and_then(|m| m.version.clone()),
+        licenses: root.and_then(|m| m.licenses.clone()),
+        components: components.into_values().collect(),
+        exported: graph
+            .component_exports
+            .iter()
+            .filter_map(|e| num_of(e.source_instance))
+            .collect(),
+    }
+}
+
+/// The producers as `(field, "tool version")` pairs.
+fn producer_entries(component: &SbomComponent) -> Vec<(String, String)> {
+    component
+        .producers
+        .iter()
+        .flat_map(|(field, tools)| {
+            tools.iter().map(move |(tool, version)| {
+                let value = if version.is_empty() {
+                    tool.clone()
+                } else {
+                    format!("{} {}", tool, version)
+                };
+                (field.clone(), value)
+            })
+        })
+        .collect()
+}
+
+fn bom_ref(num: u32) -> String {
+    format!("component-{}", num)
+}
+
+impl Sbom {
+    /// Render as a CycloneDX 1.5 JSON document.
+    pub fn to_cyclonedx(&self, options: &SbomOptions) -> Value {
+        let components: Vec<Value> = self
+            .components
+            .iter()
+            .map(|c| {
+                let mut component = json!({
+                    "type": "library",
+                    "bom-ref": bom_ref(c.num),
+                    "name": c.name,
+                });
+                if let Some(version) = &c.version {
+                    component["version"] = json!(version);
+                }
+                if let Some(sha256) = &c.sha256 {
+                    component["hashes"] = json!([{ "alg": "SHA-256", "content": sha256 }]);
+                }
+                if let Some(licenses) = &c.licenses {
+                    component["licenses"] = json!([{ "expression": licenses }]);
+                }
+                let mut properties: Vec<Value> = c
+                    .size
+                    .map(|size| json!({ "name": "cviz:size", "value": size.to_string() }))
+                    .into_iter()
+                    .collect();
+                properties.extend(producer_entries(c).into_iter().map(|(field, value)| {
+                    json!({ "name": format!("cviz:producers:{}", field), "value": value })
+                }));
+                if !properties.is_empty() {
+                    component["properties"] = json!(properties);
+                }
+                component
+            })
+            .collect();
+
+        let mut root = json!({
+            "type": "application",
+            "bom-ref": "root",
+            "name": options.name,
+        });
+        if let Some(version) = &self.version {
+            root["version"] = json!(version);
+        }
+        if let Some(licenses) = &self.licenses {
+            root["licenses"] = json!([{ "expression": licenses }]);
+        }
+
+        let mut dependencies = vec![json!({
+            "ref": "root",
+            "dependsOn": self.exported.iter().map(|&n| bom_ref(n)).collect::<Vec<_>>(),
+        })];
+        dependencies.extend(self.components.iter().map(|c| {
+            json!({
+                "ref": bom_ref(c.num),
+                "dependsOn": c.depends_on.iter().map(|&n| bom_ref(n)).collect::<Vec<_>>(),
+            })
+        }));
+
+        json!({
+            "bomFormat": "CycloneDX",
+            "specVersion": "1.5",
+            "version": 1,
+            "metadata": {
+                "timestamp": options.created,
+                "tools": {
+                    "components": [{
+                        "type": "application",
+                        "name": "cviz",
+                        "version": env!("CARGO_PKG_VERSION"),
+                    }],
+                },
+                "component": root,
+            },
+            "components": components,
+            "dependencies": dependencies,
+        })
+    }
+
+    /// Render as an SPDX 2.3 JSON document.  The document namespace is
+    /// derived from the name, creation time and component digests, so it
+    /// changes whenever the document does.
+    pub fn to_spdx(&self, options: &SbomOptions) -> Value {
+        let spdx_id = |num: u32| format!("SPDXRef-component-{}", num);
+        let package =
+            |id: String, name: &str, version: Option<&String>, licenses: Option<&String>| {
+                let mut package = json!({
+                    "SPDXID": id,
+                    "name": name,
+                    "downloadLocation": "NOASSERTION",
+                    "filesAnalyzed": false,
+                    "licenseConcluded": "NOASSERTION",
+                    "licenseDeclared": licenses.map_or("NOASSERTION", String::as_str),
+                    "copyrightText": "NOASSERTION",
+                });
+                if let Some(version) = version {
+                    package["versionInfo"] = json!(version);
+                }
+                package
+            };
+
+        let mut packages = vec![package(
+            "SPDXRef-root".to_string(),
+            &options.name,
+            self.version.as_ref(),
+            self.licenses.as_ref(),
+        )];
+        for c in &self.components {
+            let mut p = package(
+                spdx_id(c.num),
+                &c.name,
+                c.version.as_ref(),
+                c.licenses.as_ref(),
+            );
+            if let Some(sha256) = &c.sha256 {
+                p["checksums"] = json!([{ "algorithm": "SHA256", "checksumValue": sha256 }]);
+            }
+            let mut comment: Vec<String> = c
+                .size
+                .map(|size| format!("size: {} bytes", size))
+                .into_iter()
+                .collect();
+            comment.extend(
+                producer_entries(c)
+                    .into_iter()
+                    .map(|(field, value)| format!("{}: {}", field, value)),
+            );
+            if !comment.is_empty() {
+                p["comment"] = json!(comment.join("; "));
+            }
+            packages.push(p);
+        }
+
+        let relationship = |from: String, kind: &str, to: String| {
+            json!({
+                "spdxElementId": from,
+                "relationshipType": kind,
+                "relatedSpdxElement": to,
+            })
+        };
+        let mut relationships = vec![relationship(
+            "SPDXRef-DOCUMENT".to_string(),
+            "DESCRIBES",
+            "SPDXRef-root".to_string(),
+        )];
+        for c in &self.components {
+            relationships.push(relationship(
+                "SPDXRef-root".to_string(),
+                "CONTAINS",
+                spdx_id(c.num),
+            ));
+        }
+        for &n in &self.exported {
+            relationships.push(relationship(
+                "SPDXRef-root".to_string(),
+                "DEPENDS_ON",
+                spdx_id(n),
+            ));
+        }
+        for c in &self.components {
+            for &dep in &c.depends_on {
+                relationships.push(relationship(spdx_id(c.num), "DEPENDS_ON", spdx_id(dep)));
+            }
+        }
+
+        let mut hasher = Sha256::new();
+        hasher.update(options.name.as_bytes());
+        hasher.update(options.created.as_bytes());
+        for c in &self.components {
+            hasher.update(c.sha256.as_deref().unwrap_or(&c.name).as_bytes());
+        }
+        let namespace = format!(
+            "https://spdx.org/spdxdocs/{}-{}",
+            options
+                .name
+                .chars()
+                .map(|ch| if ch.is_ascii_alphanumeric() || ch == '.' {
+                    ch
+                } else {
+                    '-'
+                })
+                .collect::<String>(),
+            &hex::encode(hasher.finalize())[..16]
+        );
+
+        json!({
+            "spdxVersion": "SPDX-2.3",
+            "dataLicense": "CC0-1.0",
+            "SPDXID": "SPDXRef-DOCUMENT",
+            "name": options.name,
+            "documentNamespace": namespace,
+            "creationInfo": {
+                "created": options.created,
+                "creators": [format!("Tool: cviz-{}", env!("CARGO_PKG_VERSION"))],
+            },
+            "packages": packages,
+            "relationships": relationships,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::ComponentMetadata;
+    use crate::test_utils::*;
+
+    fn options() -> SbomOptions {
+        SbomOptions {
+            name: "app".to_string(),
+            created: "2026-01-06T12:00:00Z".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_sbom_components_and_dependencies() {
+        let mut graph = simple_chain_graph();
+        let srv = graph.nodes.get_mut(&1).unwrap();
+        srv.package = Some("my:srv".to_string());
+        srv.package_version = Some("1.2.0".to_string());
+        srv.digest = Some("sha256:abcd".to_string());
+        srv.size = Some(2048);
+        graph.metadata.insert(
+            Some(0),
+            ComponentMetadata {
+                licenses: Some("Apache-2.0".to_string()),
+                producers: BTreeMap::from([(
+                    "language".to_string(),
+                    BTreeMap::from([("Rust".to_string(), "1.89.0".to_string())]),
+                )]),
+                ..ComponentMetadata::default()
+            },
+        );
+
+        let bom = sbom(&graph);
+        assert_eq!(bom.components.len(), 2);
+        assert_eq!(bom.components[0].name, "my:srv");
+        assert_eq!(bom.components[0].sha256.as_deref(), Some("abcd"));
+        // $middleware imports the handler from $srv
+        assert_eq!(bom.components[1].name, "middleware");
+        assert_eq!(bom.components[1].depends_on, BTreeSet::from([0]));
+        assert_eq!(bom.exported, BTreeSet::from([1]));
+
+        let cdx = bom.to_cyclonedx(&options());
+        assert_eq!(cdx["bomFormat"], "CycloneDX");
+        assert_eq!(cdx["components"][0]["version"], "1.2.0");
+        assert_eq!(cdx["components"][0]["hashes"][0]["content"], "abcd");
+        assert_eq!(
+            cdx["components"][0]["licenses"][0]["expression"],
+            "Apache-2.0"
+        );
+        assert_eq!(
+            cdx["components"][0]["properties"],
+            json!([
+                { "name": "cviz:size", "value": "2048" },
+                { "name": "cviz:producers:language", "value": "Rust 1.89.0" },
+            ])
+        );
+        assert_eq!(
+            cdx["dependencies"],
+            json!([
+                { "ref": "root", "dependsOn": ["component-1"] },
+                { "ref": "component-0", "dependsOn": [] },
+                { "ref": "component-1", "dependsOn": ["component-0"] },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_spdx_document() {
+        let doc = sbom(&simple_chain_graph()).to_spdx(&options());
+        assert_eq!(doc["spdxVersion"], "SPDX-2.3");
+        assert_eq!(doc["creationInfo"]["created"], "2026-01-06T12:00:00Z");
+        assert!(doc["documentNamespace"]
+            .as_str()
+            .unwrap()
+            .starts_with("https://spdx.org/spdxdocs/app-"));
+        assert_eq!(doc["packages"].as_array().unwrap().len(), 3);
+        let relationships: Vec<(&str, &str, &str)> = doc["relationships"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| {
+                (
+                    r["spdxElementId"].as_str().unwrap(),
+                    r["relationshipType"].as_str().unwrap(),
+                    r["relatedSpdxElement"].as_str().unwrap(),
+                )
+            })
+            .collect();
+        assert!(relationships.contains(&("SPDXRef-DOCUMENT", "DESCRIBES", "SPDXRef-root")));
+        assert!(relationships.contains(&("SPDXRef-root", "CONTAINS", "SPDXRef-component-0")));
+        assert!(relationships.contains(&(
+            "SPDXRef-component-1",
+            "DEPENDS_ON",
+            "SPDXRef-component-0"
+        )));
+    }
+}
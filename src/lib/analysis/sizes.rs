@@ -0,0 +1,136 @@
+//! Embedded components ranked by size.
+//!
+//! When a composed binary grows, the question is which component is to
+//! blame.  [`size_ranking`] lists every embedded component once, largest
+//! first, with its share of the composition and the instances it backs.
+
+use crate::model::{CompositionGraph, SYNTHETIC_COMPONENT};
+use crate::output::human_size;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// One embedded component and its size.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ComponentSize {
+    /// `component_num` of the component definition.
+    pub component_num: u32,
+    pub name: String,
+    pub size: u64,
+    /// Share of the composition's size, in percent.
+    pub percent: f64,
+    /// Whether the component is nested inside another embedded one, whose
+    /// size already includes it.
+    pub nested: bool,
+    /// Display labels of the instances of the component, in graph order.
+    pub instances: Vec<String>,
+}
+
+/// Every embedded component whose size is known, largest first (ties in
+/// binary order).  Percentages are of the components instantiated by the
+/// root, which together make up nearly all of a composed binary; nested
+/// components are counted within the component containing them.
+pub fn size_ranking(graph: &CompositionGraph) -> Vec<ComponentSize> {
+    let mut components: BTreeMap<u32, ComponentSize> = BTreeMap::new();
+    for node in graph.nodes.values() {
+        let Some(size) = node
+            .size
+            .filter(|_| node.component_index != SYNTHETIC_COMPONENT)
+        else {
+            continue;
+        };
+        let entry = components
+            .entry(node.component_num)
+            .or_insert_with(|| ComponentSize {
+                component_num: node.component_num,
+                name: super::component_name(graph, node),
+                size,
+                percent: 0.0,
+                nested: true,
+                instances: Vec::new(),
+            });
+        entry.nested &= node.depth > 0;
+        entry.instances.push(node.display_label().to_string());
+    }
+
+    let total: u64 = components
+        .values()
+        .filter(|c| !c.nested)
+        .map(|c| c.size)
+        .sum();
+    let mut ranking: Vec<ComponentSize> = components.into_values().collect();
+    for component in &mut ranking {
+        if total > 0 {
+            component.percent = component.size as f64 * 100.0 / total as f64;
+        }
+    }
+    ranking.sort_by_key(|c| std::cmp::Reverse(c.size));
+    ranking
+}
+
+/// Render `ranking` as an aligned plain-text table.
+pub fn sizes_table(ranking: &[ComponentSize]) -> String {
+    if ranking.is_empty() {
+        return "No component sizes known.\n".to_string();
+    }
+
+    let header = ["SIZE", "SHARE", "COMPONENT", "INSTANCES"];
+    let rows: Vec<[String; 4]> = ranking
+        .iter()
+        .map(|c| {
+            [
+                human_size(c.size),
+                format!("{:.1}%", c.percent),
+                if c.nested {
+                    format!("{} (nested)", c.name)
+                } else {
+                    c.name.clone()
+                },
+                c.instances.join(", "),
+            ]
+        })
+        .collect();
+
+    super::text_table(header, &rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    #[test]
+    fn test_size_ranking() {
+        let mut graph = simple_chain_graph();
+        assert!(size_ranking(&graph).is_empty());
+
+        graph.nodes.get_mut(&1).unwrap().size = Some(1024);
+        graph.nodes.get_mut(&2).unwrap().size = Some(3072);
+        let ranking = size_ranking(&graph);
+        assert_eq!(
+            ranking
+                .iter()
+                .map(|c| (c.name.as_str(), c.percent))
+                .collect::<Vec<_>>(),
+            vec![("middleware", 75.0), ("srv", 25.0)]
+        );
+        assert_eq!(
+            sizes_table(&ranking),
+            "SIZE     SHARE  COMPONENT   INSTANCES\n\
+             3.0 KiB  75.0%  middleware  middleware\n\
+             1.0 KiB  25.0%  srv         srv\n"
+        );
+    }
+
+    #[test]
+    fn test_nested_components_not_double_counted() {
+        let mut graph = simple_chain_graph();
+        graph.nodes.get_mut(&1).unwrap().size = Some(1000);
+        let mw = graph.nodes.get_mut(&2).unwrap();
+        mw.size = Some(250);
+        mw.depth = 1;
+        let ranking = size_ranking(&graph);
+        assert_eq!(ranking[0].percent, 100.0);
+        assert!(ranking[1].nested);
+        assert_eq!(ranking[1].percent, 25.0);
+    }
+}
@@ -0,0 +1,307 @@
+//! Size and shape counts for a composition.
+//!
+//! The numbers are meant to be recorded over time (e.g. from CI as JSON) to
+//! see a composition grow.  The [`super::health`] score is reported
+//! alongside them so that quality can be tracked the same way.
+
+use super::health::{health_score, HealthReport, HealthWeights, MAX_SCORE};
+use super::{host_capabilities, longest_chain, shared_instances};
+use crate::model::{CompositionGraph, SYNTHETIC_COMPONENT};
+use serde::Serialize;
+
+/// Counts describing the whole composition.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GraphStats {
+    /// Real (non-synthetic) component instances.
+    pub instances: usize,
+    /// Imports satisfied by another instance rather than the host.
+    pub connections: usize,
+    /// Distinct interfaces imported from the host.
+    pub host_interfaces: usize,
+    /// Items the composition exports.
+    pub exports: usize,
+    /// Length (in instances) of the longest handler chain.
+    pub max_chain_depth: usize,
+    /// Per-instance connection counts, in graph order.
+    pub nodes: Vec<NodeStats>,
+    /// Instances several others import from (see [`shared_instances`]).
+    pub shared: Vec<SharedStats>,
+    /// The health score and the factors that lowered it.
+    pub health: HealthReport,
+}
+
+/// Connection counts for one instance.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NodeStats {
+    /// Display label of the instance.
+    pub instance: String,
+    /// Imports it receives from other instances.
+    pub fan_in: usize,
+    /// Imports of other instances it provides.
+    pub fan_out: usize,
+    /// Imports it receives from the host.
+    pub host_imports: usize,
+}
+
+/// An instance imported from by more than one other instance.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SharedStats {
+    /// Display label of the shared instance.
+    pub instance: String,
+    /// Display labels of the instances importing from it.
+    pub importers: Vec<String>,
+}
+
+/// Compute the statistics of `graph`, scoring its health with `weights`.
+pub fn graph_stats(graph: &CompositionGraph, weights: &HealthWeights) -> GraphStats {
+    let label = |id: u32| {
+        graph
+            .get_node(id)
+            .map_or_else(|| id.to_string(), |n| n.display_label().to_string())
+    };
+    let nodes: Vec<NodeStats> = graph
+        .nodes
+        .iter()
+        .filter(|(_, n)| n.component_index != SYNTHETIC_COMPONENT)
+        .map(|(&id, node)| NodeStats {
+            instance: node.display_label().to_string(),
+            fan_in: node
+                .imports
+                .iter()
+                .filter(|c| !c.is_host_import && c.source_instance.is_some())
+                .count(),
+            fan_out: graph
+                .nodes
+                .values()
+                .flat_map(|n| n.imports.iter())
+                .filter(|c| c.source_instance == Some(id))
+                .count(),
+            host_imports: node.imports.iter().filter(|c| c.is_host_import).count(),
+        })
+        .collect();
+
+    GraphStats {
+        instances: nodes.len(),
+        connections: graph
+            .nodes
+            .values()
+            .flat_map(|n| n.imports.iter())
+            .filter(|c| !c.is_host_import && c.source_instance.is_some())
+            .count(),
+        host_interfaces: host_capabilities(graph).len(),
+        exports: graph.component_exports.len(),
+        max_chain_depth: longest_chain(graph),
+        nodes,
+        shared: shared_instances(graph)
+            .into_iter()
+            .map(|s| SharedStats {
+                instance: label(s.instance),
+                importers: s.importers.into_iter().map(label).collect(),
+            })
+            .collect(),
+        health: health_score(graph, weights),
+    }
+}
+
+impl GraphStats {
+    /// The totals, labelled, in the order they are printed.
+    fn totals(&self) -> [(&'static str, String); 6] {
+        [
+            ("Instances", self.instances.to_string()),
+            ("Connections", self.connections.to_string()),
+            ("Host interfaces", self.host_interfaces.to_string()),
+            ("Exports", self.exports.to_string()),
+            ("Max chain depth", self.max_chain_depth.to_string()),
+            (
+                "Health score",
+                format!("{}/{}", self.health.score, MAX_SCORE),
+            ),
+        ]
+    }
+
+    /// Render the statistics as plain text: the totals, an aligned table of
+    /// per-instance counts, the shared instances, then the factors behind
+    /// the health score.
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        for (name, value) in self.totals() {
+            out.push_str(&format!("{:<16} {}\n", format!("{}:", name), value));
+        }
+
+        if !self.nodes.is_empty() {
+            let width = self
+                .nodes
+                .iter()
+                .map(|n| n.instance.chars().count())
+                .chain(["INSTANCE".len()])
+                .max()
+                .unwrap_or(0);
+            out.push('\n');
+            out.push_str(&format!(
+                "{:<width$}  FAN-IN  FAN-OUT  HOST\n",
+                "INSTANCE",
+                width = width
+            ));
+            for n in &self.nodes {
+                out.push_str(&format!(
+                    "{:<width$}  {:<6}  {:<7}  {}\n",
+                    n.instance,
+                    n.fan_in,
+                    n.fan_out,
+                    n.host_imports,
+                    width = width
+                ));
+            }
+        }
+        if !self.shared.is_empty() {
+            out.push_str("\nShared instances:\n");
+            for s in &self.shared {
+                out.push_str(&format!(
+                    "  {}  imported by {}\n",
+                    s.instance,
+                    s.importers.join(", ")
+                ));
+            }
+        }
+        out.push_str("\nHealth factors:\n");
+        out.push_str(&self.health.factors_text());
+        out
+    }
+
+    /// Render the statistics as a Markdown section: a table of totals, the
+    /// per-instance counts, the shared instances and the health factors.
+    pub fn to_markdown(&self) -> String {
+        let escape = |s: &str| s.replace('|', "\\|");
+        let mut out = "## Statistics\n\n| Metric | Value |\n|---|---:|\n".to_string();
+        for (name, value) in self.totals() {
+            out.push_str(&format!("| {} | {} |\n", name, value));
+        }
+        if !self.nodes.is_empty() {
+            out.push_str(
+                "\n### Instances\n\n| Instance | Fan-in | Fan-out | Host |\n|---|---:|---:|---:|\n",
+            );
+            for n in &self.nodes {
+                out.push_str(&format!(
+                    "| {} | {} | {} | {} |\n",
+                    escape(&n.instance),
+                    n.fan_in,
+                    n.fan_out,
+                    n.host_imports
+                ));
+            }
+        }
+        if !self.shared.is_empty() {
+            out.push_str("\n### Shared instances\n\n");
+            for s in &self.shared {
+                out.push_str(&format!(
+                    "- {} imported by {}\n",
+                    s.instance,
+                    s.importers.join(", ")
+                ));
+            }
+        }
+        out.push_str("\n### Health factors\n\n");
+        out.push_str(&self.health.factors_markdown());
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    #[test]
+    fn test_counts() {
+        let stats = graph_stats(&simple_chain_graph(), &HealthWeights::default());
+        assert_eq!(stats.instances, 2);
+        assert_eq!(stats.connections, 1);
+        assert_eq!(stats.host_interfaces, 2);
+        assert_eq!(stats.exports, 1);
+        assert_eq!(stats.max_chain_depth, 2);
+        assert_eq!(
+            stats.nodes,
+            vec![
+                NodeStats {
+                    instance: "srv".to_string(),
+                    fan_in: 0,
+                    fan_out: 1,
+                    host_imports: 1,
+                },
+                NodeStats {
+                    instance: "middleware".to_string(),
+                    fan_in: 1,
+                    fan_out: 0,
+                    host_imports: 1,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_text() {
+        let text = graph_stats(&simple_chain_graph(), &HealthWeights::default()).to_text();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "Instances:       2");
+        assert_eq!(lines[4], "Max chain depth: 2");
+        assert_eq!(lines[5], "Health score:    100/100");
+        assert_eq!(lines[7], "INSTANCE    FAN-IN  FAN-OUT  HOST");
+        assert_eq!(lines[8], "srv         0       1        1");
+        assert!(!text.contains("Shared instances"));
+        assert_eq!(lines[11], "Health factors:");
+        assert!(lines[12].starts_with("  chain-length  "), "{}", text);
+    }
+
+    #[test]
+    fn test_shared() {
+        let mut graph = simple_chain_graph();
+        let mut admin = orphan_node();
+        admin.add_import(graph.nodes[&2].imports[0].clone());
+        graph.add_node(7, admin);
+
+        let stats = graph_stats(&graph, &HealthWeights::default());
+        assert_eq!(
+            stats.shared,
+            vec![SharedStats {
+                instance: "srv".to_string(),
+                importers: vec!["middleware".to_string(), "orphan".to_string()],
+            }]
+        );
+        assert!(stats.to_text().contains(
+            "\nShared instances:\n  srv  imported by middleware, orphan\n\nHealth factors:\n"
+        ));
+    }
+
+    #[test]
+    fn test_health() {
+        let mut graph = simple_chain_graph();
+        graph.add_node(7, orphan_node());
+        let weights = HealthWeights {
+            unused_instance_penalty: 30,
+            ..HealthWeights::default()
+        };
+        let stats = graph_stats(&graph, &weights);
+        assert_eq!(stats.health, health_score(&graph, &weights));
+        assert_eq!(stats.health.score, MAX_SCORE - 30);
+        assert!(stats.to_text().contains("Health score:    70/100\n"));
+
+        let json = serde_json::to_value(&stats).unwrap();
+        assert_eq!(json["health"]["score"], 70);
+        assert_eq!(json["health"]["factors"][2]["name"], "unused-instances");
+    }
+
+    #[test]
+    fn test_markdown() {
+        let md = graph_stats(&simple_chain_graph(), &HealthWeights::default()).to_markdown();
+        assert!(
+            md.starts_with("## Statistics\n\n| Metric | Value |\n"),
+            "{}",
+            md
+        );
+        assert!(md.contains("| Instances | 2 |\n"), "{}", md);
+        assert!(md.contains("| Health score | 100/100 |\n"), "{}", md);
+        assert!(md.contains("| srv | 0 | 1 | 1 |\n"), "{}", md);
+        assert!(md.contains("### Health factors\n\n| Factor |"), "{}", md);
+        assert!(md.contains("| chain-length | 2 | -0 |"), "{}", md);
+    }
+}
@@ -0,0 +1,259 @@
+//! Comparing a composition against the topology it is expected to have.
+//!
+//! A [`Topology`] lists a composition's instances, the connections between
+//! them and its exports, by name only.  Checked into a repository as YAML
+//! it pins down how a composition is wired, and [`Topology::compare`]
+//! reports every instance, connection or export that was added, removed or
+//! rewired since, so that composition regressions fail CI:
+//!
+//! ```yaml
+//! nodes: [srv, middleware]
+//! edges:
+//!   - importer: middleware
+//!     provider: srv
+//!     interface: wasi:http/handler
+//! exports:
+//!   wasi:http/handler: middleware
+//! ```
+//!
+//! Interface names without a version match any version.  Connections to
+//! the host are not part of a topology.
+
+use super::unversioned;
+use crate::model::{CompositionGraph, SYNTHETIC_COMPONENT};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// The names of a composition's instances, connections and exports.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Topology {
+    /// Display labels of the real instances, once per instance.
+    #[serde(default)]
+    pub nodes: Vec<String>,
+    /// Connections between real instances.
+    #[serde(default)]
+    pub edges: Vec<Edge>,
+    /// Export name to the display label of the instance providing it.
+    #[serde(default)]
+    pub exports: BTreeMap<String, String>,
+}
+
+/// An instance importing an interface from another.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Edge {
+    pub importer: String,
+    pub provider: String,
+    pub interface: String,
+}
+
+/// How a composition differs from its expected topology.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "change", content = "description", rename_all = "lowercase")]
+pub enum TopologyChange {
+    /// Present in the composition but not expected.
+    Added(String),
+    /// Expected but missing from the composition.
+    Removed(String),
+    /// Present in both, but wired to a different instance.
+    Changed(String),
+}
+
+impl fmt::Display for TopologyChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TopologyChange::Added(what) => write!(f, "+ {}", what),
+            TopologyChange::Removed(what) => write!(f, "- {}", what),
+            TopologyChange::Changed(what) => write!(f, "~ {}", what),
+        }
+    }
+}
+
+impl Topology {
+    /// The topology of `graph`.
+    pub fn of(graph: &CompositionGraph) -> Self {
+        let label = |id: u32| {
+            graph
+                .get_node(id)
+                .map_or_else(|| id.to_string(), |n| n.display_label().to_string())
+        };
+        let real = |id: u32| {
+            graph
+                .get_node(id)
+                .is_some_and(|n| n.component_index != SYNTHETIC_COMPONENT)
+        };
+        let mut edges = Vec::new();
+        for (&id, node) in graph.nodes.iter().filter(|(&id, _)| real(id)) {
+            for conn in node.imports.iter().filter(|c| !c.is_host_import) {
+                if let Some(source) = conn.source_instance.filter(|&s| real(s)) {
+                    edges.push(Edge {
+                        importer: label(id),
+                        provider: label(source),
+                        interface: conn.interface_name.clone(),
+                    });
+                }
+            }
+        }
+        Topology {
+            nodes: graph
+                .nodes
+                .keys()
+                .filter(|&&id| real(id))
+                .map(|&id| label(id))
+                .collect(),
+            edges,
+            exports: graph
+                .component_exports
+                .iter()
+                .map(|e| (e.name.clone(), label(e.source_instance)))
+                .collect(),
+        }
+    }
+
+    /// What `actual` adds to, removes from or changes in this expected
+    /// topology: instances first, then connections, then exports.
+    pub fn compare(&self, actual: &Topology) -> Vec<TopologyChange> {
+        let mut changes = Vec::new();
+
+        let mut unmatched: Vec<&String> = actual.nodes.iter().collect();
+        for node in &self.nodes {
+            match unmatched.iter().position(|&n| n == node) {
+                Some(at) => {
+                    unmatched.remove(at);
+                }
+                None => changes.push(TopologyChange::Removed(format!("instance {}", node))),
+            }
+        }
+        for node in unmatched {
+            changes.push(TopologyChange::Added(format!("instance {}", node)));
+        }
+
+        let mut unmatched: Vec<&Edge> = actual.edges.iter().collect();
+        let mut missing = Vec::new();
+        for expected in &self.edges {
+            let found = unmatched.iter().position(|e| {
+                e.importer == expected.importer
+                    && e.provider == expected.provider
+                    && same_interface(&expected.interface, &e.interface)
+            });
+            match found {
+                Some(at) => {
+                    unmatched.remove(at);
+                }
+                None => missing.push(expected),
+            }
+        }
+        for expected in missing {
+            let rewired = unmatched.iter().position(|e| {
+                e.importer == expected.importer && same_interface(&expected.interface, &e.interface)
+            });
+            match rewired {
+                Some(at) => {
+                    let e = unmatched.remove(at);
+                    changes.push(TopologyChange::Changed(format!(
+                        "{} imports {} from {}, expected {}",
+                        e.importer, e.interface, e.provider, expected.provider
+                    )));
+                }
+                None => changes.push(TopologyChange::Removed(format!(
+                    "{} imports {} from {}",
+                    expected.importer, expected.interface, expected.provider
+                ))),
+            }
+        }
+        for e in unmatched {
+            changes.push(TopologyChange::Added(format!(
+                "{} imports {} from {}",
+                e.importer, e.interface, e.provider
+            )));
+        }
+
+        let mut unmatched: Vec<(&String, &String)> = actual.exports.iter().collect();
+        for (name, instance) in &self.exports {
+            match unmatched
+                .iter()
+                .position(|(actual, _)| same_interface(name, actual))
+            {
+                Some(at) => {
+                    let (actual, from) = unmatched.remove(at);
+                    if from != instance {
+                        changes.push(TopologyChange::Changed(format!(
+                            "export {} comes from {}, expected {}",
+                            actual, from, instance
+                        )));
+                    }
+                }
+                None => changes.push(TopologyChange::Removed(format!(
+                    "export {} from {}",
+                    name, instance
+                ))),
+            }
+        }
+        for (name, instance) in unmatched {
+            changes.push(TopologyChange::Added(format!(
+                "export {} from {}",
+                name, instance
+            )));
+        }
+
+        changes
+    }
+}
+
+/// Whether the `expected` interface name matches `actual`, ignoring the
+/// version when `expected` has none.
+fn same_interface(expected: &str, actual: &str) -> bool {
+    expected == actual || (!expected.contains('@') && unversioned(actual) == expected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    fn expected(yaml: &str) -> Topology {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn test_matching_topology() {
+        let actual = Topology::of(&simple_chain_graph());
+        let topology = expected(
+            "nodes: [srv, middleware]\n\
+             edges:\n  - importer: middleware\n    provider: srv\n    interface: wasi:http/handler\n\
+             exports:\n  wasi:http/handler: middleware\n",
+        );
+        assert!(topology.compare(&actual).is_empty());
+        // A topology always matches itself
+        assert!(actual.compare(&actual).is_empty());
+    }
+
+    #[test]
+    fn test_reported_changes() {
+        let actual = Topology::of(&two_chain_graph());
+        let topology = expected(
+            "nodes: [srv-http, mw-http, auth]\n\
+             edges:\n  - importer: mw-http\n    provider: auth\n    interface: wasi:http/handler\n\
+             exports:\n  wasi:http/handler: srv-http\n",
+        );
+        let changes: Vec<String> = topology
+            .compare(&actual)
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                "- instance auth",
+                "+ instance db",
+                "+ instance cache",
+                "~ mw-http imports wasi:http/handler@0.3.0 from srv-http, expected auth",
+                "+ cache imports wasi:keyvalue/store@0.1.0 from db",
+                "~ export wasi:http/handler@0.3.0 comes from mw-http, expected srv-http",
+                "+ export wasi:keyvalue/store@0.1.0 from cache",
+            ]
+        );
+    }
+}
@@ -0,0 +1,226 @@
+//! Why an instance is part of the composition.
+//!
+//! The reverse of following imports: starting from one instance, walk up to
+//! everything that depends on it.  An [`Explanation`] names the exports that
+//! end up relying on the instance (with the route from each), the handler
+//! chains it sits in and the instances importing from it directly.  An
+//! instance with no reason to be there is probably dead weight.
+
+use super::inspect::ChainPosition;
+use crate::model::CompositionGraph;
+use crate::{find_chain_interfaces, get_chain_for};
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+
+/// The reasons instance `id` is part of the composition.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Explanation {
+    /// Graph ID of the instance.
+    pub id: u32,
+    pub label: String,
+    /// Exports that depend on the instance, in export order.
+    pub exports: Vec<ExportRoute>,
+    pub chains: Vec<ChainPosition>,
+    /// Instances importing from it directly, in graph order.
+    pub consumers: Vec<Consumer>,
+    /// Display labels of every instance depending on it, directly or
+    /// through others, in graph order.
+    pub dependents: Vec<String>,
+}
+
+/// An export that reaches the instance.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExportRoute {
+    pub export: String,
+    /// Display labels of a shortest route of imports from the exporting
+    /// instance to this one, both included.
+    pub route: Vec<String>,
+}
+
+/// An instance importing from the explained one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Consumer {
+    pub instance: String,
+    /// The interfaces it imports, in import order.
+    pub interfaces: Vec<String>,
+}
+
+/// Explain instance `id` of `graph`, or `None` if there is no such instance.
+pub fn explain(graph: &CompositionGraph, id: u32) -> Option<Explanation> {
+    let node = graph.get_node(id)?;
+    let label = |id: u32| {
+        graph
+            .get_node(id)
+            .map_or_else(|| id.to_string(), |n| n.display_label().to_string())
+    };
+
+    // Importer to the interfaces it takes from each provider.
+    let mut importers: BTreeMap<u32, BTreeMap<u32, Vec<String>>> = BTreeMap::new();
+    for (&importer, other) in &graph.nodes {
+        for conn in other.imports.iter().filter(|c| !c.is_host_import) {
+            if let Some(source) = conn.source_instance.filter(|&s| s != importer) {
+                importers
+                    .entry(source)
+                    .or_default()
+                    .entry(importer)
+                    .or_default()
+                    .push(conn.interface_name.clone());
+            }
+        }
+    }
+
+    // Breadth-first up the imports, remembering which provider each
+    // dependent was reached from so routes can be rebuilt.
+    let mut towards: BTreeMap<u32, u32> = BTreeMap::new();
+    let mut queue = VecDeque::from([id]);
+    while let Some(current) = queue.pop_front() {
+        for &importer in importers.get(&current).into_iter().flat_map(BTreeMap::keys) {
+            if importer != id && !towards.contains_key(&importer) {
+                towards.insert(importer, current);
+                queue.push_back(importer);
+            }
+        }
+    }
+
+    let exports = graph
+        .component_exports
+        .iter()
+        .filter(|e| e.source_instance == id || towards.contains_key(&e.source_instance))
+        .map(|e| {
+            let mut route = vec![label(e.source_instance)];
+            let mut at = e.source_instance;
+            while let Some(&next) = towards.get(&at) {
+                route.push(label(next));
+                at = next;
+            }
+            ExportRoute {
+                export: e.name.clone(),
+                route,
+            }
+        })
+        .collect();
+
+    let chains = find_chain_interfaces(graph)
+        .into_iter()
+        .filter_map(|interface| {
+            let chain = get_chain_for(graph, &interface);
+            let position = chain.iter().position(|&c| c == id)? + 1;
+            Some(ChainPosition {
+                interface,
+                position,
+                chain: chain.into_iter().map(label).collect(),
+            })
+        })
+        .collect();
+
+    let consumers = importers
+        .remove(&id)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(importer, interfaces)| Consumer {
+            instance: label(importer),
+            interfaces,
+        })
+        .collect();
+
+    Some(Explanation {
+        id,
+        label: node.display_label().to_string(),
+        exports,
+        chains,
+        consumers,
+        dependents: towards.into_keys().map(label).collect(),
+    })
+}
+
+impl Explanation {
+    /// Whether nothing depends on the instance.
+    pub fn is_unused(&self) -> bool {
+        self.exports.is_empty() && self.consumers.is_empty()
+    }
+
+    /// Render the explanation as plain text, one reason per line.
+    pub fn to_text(&self) -> String {
+        let mut out = format!("{} (instance {})\n", self.label, self.id);
+        if self.is_unused() {
+            out.push_str("  nothing imports from it and no export depends on it\n");
+            return out;
+        }
+        for e in &self.exports {
+            if e.route.len() == 1 {
+                out.push_str(&format!("  backs export {}\n", e.export));
+            } else {
+                out.push_str(&format!(
+                    "  export {} depends on it: {}\n",
+                    e.export,
+                    e.route.join(" → ")
+                ));
+            }
+        }
+        for c in &self.chains {
+            out.push_str(&format!(
+                "  {} of {} in the {} chain ({})\n",
+                c.position,
+                c.chain.len(),
+                c.interface,
+                c.chain.join(" → ")
+            ));
+        }
+        for c in &self.consumers {
+            out.push_str(&format!(
+                "  {} imports {}\n",
+                c.instance,
+                c.interfaces.join(", ")
+            ));
+        }
+        if !self.dependents.is_empty() {
+            out.push_str(&format!(
+                "\nDepended on by: {}\n",
+                self.dependents.join(", ")
+            ));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    #[test]
+    fn test_explain_end_of_chain() {
+        // gateway → service → backend, exported from gateway
+        let graph = long_chain_graph();
+        let id = graph.find_node("backend").unwrap();
+        let why = explain(&graph, id).unwrap();
+        assert_eq!(why.exports.len(), 1);
+        assert_eq!(why.exports[0].route, vec!["gateway", "service", "backend"]);
+        assert_eq!(why.chains[0].position, 3);
+        assert_eq!(why.consumers.len(), 1);
+        assert_eq!(why.consumers[0].instance, "service");
+        assert_eq!(why.dependents, vec!["service", "gateway"]);
+        assert!(!why.is_unused());
+    }
+
+    #[test]
+    fn test_text() {
+        let graph = simple_chain_graph();
+        assert_eq!(
+            explain(&graph, 1).unwrap().to_text(),
+            "srv (instance 1)\n\
+             \x20 export wasi:http/handler@0.3.0 depends on it: middleware → srv\n\
+             \x20 2 of 2 in the wasi:http/handler@0.3.0 chain (middleware → srv)\n\
+             \x20 middleware imports wasi:http/handler@0.3.0\n\
+             \n\
+             Depended on by: middleware\n"
+        );
+
+        let mut graph = simple_chain_graph();
+        graph.add_node(42, orphan_node());
+        let why = explain(&graph, 42).unwrap();
+        assert!(why.is_unused());
+        assert!(why.to_text().contains("nothing imports from it"));
+        assert!(explain(&graph, 9).is_none());
+    }
+}
@@ -0,0 +1,184 @@
+//! The error type returned throughout the library.
+//!
+//! Every fallible function returns a [`CvizError`], whose variants tell the
+//! kinds of failure apart so callers can react to them (show a core
+//! module's imports instead of a diagram, list the anomalies of a strict
+//! parse, ...) without matching on messages.
+
+use crate::model::GraphIssues;
+use crate::parse::component::Anomalies;
+use crate::parse::module::NotAComponent;
+use std::fmt;
+
+/// Result type of the library's fallible functions.
+pub type Result<T, E = CvizError> = std::result::Result<T, E>;
+
+/// Why a library call failed.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum CvizError {
+    /// The input is not valid in the format it was read as.
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+    /// A core module was given where a component was expected.  It carries
+    /// the module's imports and exports, so they can be shown instead.
+    #[error(transparent)]
+    NotAComponent(#[from] NotAComponent),
+    /// A strict parse found references it could not follow.
+    #[error(transparent)]
+    Anomalies(#[from] Anomalies),
+    /// The graph read is inconsistent, e.g. an import names an instance
+    /// that does not exist.
+    #[error(transparent)]
+    Validation(#[from] GraphIssues),
+    /// A graph could not be written out.
+    #[error("failed to render: {0}")]
+    Render(#[source] serde_json::Error),
+    /// The library was built without the cargo feature, named here, that
+    /// the call needs, e.g. `mermaid` for Mermaid output.
+    #[error("cviz was built without the `{0}` feature")]
+    MissingFeature(&'static str),
+}
+
+/// Input that could not be read, with where in it the problem lies when
+/// that is known.
+#[derive(Debug)]
+pub struct ParseError {
+    /// What is wrong, e.g. `unexpected end-of-file`.
+    pub message: String,
+    /// The top-level section of a binary the problem lies in, e.g. `core
+    /// module`.
+    pub section: Option<String>,
+    /// Byte offset of the problem in a binary.
+    pub offset: Option<usize>,
+    source: Option<Box<dyn std::error::Error + Send + Sync + 'static>>,
+}
+
+impl ParseError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            section: None,
+            offset: None,
+            source: None,
+        }
+    }
+
+    /// A `message` about input that `source` failed to read.
+    pub(crate) fn caused_by(
+        message: impl Into<String>,
+        source: impl std::error::Error + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            source: Some(Box::new(source)),
+            ..Self::new(message)
+        }
+    }
+
+    pub(crate) fn at(mut self, offset: usize) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Fill in the offset and section of an error from the binary `buff`.
+    pub(crate) fn in_binary(mut self, buff: &[u8], offset: usize) -> Self {
+        self.offset = Some(offset);
+        if let Some(section) = crate::parse::sections::section_at(buff, offset) {
+            self.section = Some(section);
+        }
+        self
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)?;
+        match (&self.section, self.offset) {
+            (Some(section), Some(offset)) => {
+                write!(f, " (in the {} section, at offset {:#x})", section, offset)
+            }
+            (Some(section), None) => write!(f, " (in the {} section)", section),
+            (None, Some(offset)) => write!(f, " (at offset {:#x})", offset),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source
+            .as_deref()
+            .map(|e| e as &(dyn std::error::Error + 'static))
+    }
+}
+
+impl From<wirm::wasmparser::BinaryReaderError> for ParseError {
+    fn from(err: wirm::wasmparser::BinaryReaderError) -> Self {
+        Self {
+            offset: Some(err.offset()),
+            ..Self::new(err.message())
+        }
+    }
+}
+
+impl From<wirm::error::Error> for ParseError {
+    fn from(err: wirm::error::Error) -> Self {
+        match err {
+            wirm::error::Error::BinaryReaderError(err) => err.into(),
+            err => Self::new(err.to_string()),
+        }
+    }
+}
+
+impl CvizError {
+    /// A parse error for binary `buff`, locating its offset in a section.
+    pub(crate) fn binary(buff: &[u8], err: impl Into<ParseError>) -> Self {
+        let err: ParseError = err.into();
+        match err.offset {
+            Some(offset) => err.in_binary(buff, offset).into(),
+            None => err.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::component::parse_component;
+
+    #[test]
+    fn test_truncated_binary_is_located() {
+        let wasm = wat::parse_str(
+            r#"(component
+                (core module (func (export "f")))
+                (component)
+                (instance (instantiate 0))
+            )"#,
+        )
+        .unwrap();
+        let truncated = &wasm[..wasm.len() - 3];
+        let Err(CvizError::Parse(err)) = parse_component(truncated) else {
+            panic!("truncated binary was not a parse error");
+        };
+        let offset = err.offset.expect("offset of the problem");
+        assert!(offset <= truncated.len(), "{}", offset);
+        assert_eq!(err.section.as_deref(), Some("instance"), "{}", err);
+        assert!(
+            err.to_string().ends_with(&format!(
+                "(in the instance section, at offset {:#x})",
+                offset
+            )),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(ParseError::new("bad").to_string(), "bad");
+        assert_eq!(
+            ParseError::new("bad").at(16).to_string(),
+            "bad (at offset 0x10)"
+        );
+    }
+}
@@ -1,14 +1,54 @@
-use crate::model::{CompositionGraph, ExportInfo, InterfaceConnection};
+//! Parse WebAssembly component compositions and draw, check and compare them.
+//!
+//! This is the library behind the `cviz` command line tool; everything the
+//! CLI does is available here, and the CLI itself only adds argument
+//! handling, fetching and file output on top.
+//!
+//! - [`parse`] builds a [`CompositionGraph`] from a component binary, a
+//!   JSON graph, WAC source, a wadm manifest or a wasm-compose config.
+//! - [`model`] holds the graph itself: instances, the interfaces wiring them
+//!   together, exports, types and metadata, plus naming and filtering.
+//! - [`output`] renders a graph as ASCII, mermaid or JSON at a chosen
+//!   [`DetailLevel`](output::DetailLevel).  Each renderer is behind the
+//!   cargo feature of the same name (`ascii`, `mermaid`, `json`), all on by
+//!   default.
+//! - [`analysis`] answers questions about a graph: statistics, diagnostics,
+//!   diffs, capability audits and more.
+//!
+//! Fallible functions return a [`CvizError`], whose variants tell a
+//! malformed input from a core module given in place of a component, a
+//! strict parse's anomalies or an inconsistent graph.
+//!
+//! ```no_run
+//! use cviz::output::{ascii, DetailLevel};
+//! use cviz::parse::component::parse_component;
+//!
+//! let bytes = std::fs::read("composed.wasm")?;
+//! let graph = parse_component(&bytes)?;
+//! for interface in cviz::find_chain_interfaces(&graph) {
+//!     println!("chain: {}", interface);
+//! }
+//! print!("{}", ascii::generate_ascii(&graph, DetailLevel::AllInterfaces, false));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::model::{CompositionGraph, ExternKind, InterfaceConnection};
 use std::collections::HashSet;
 
+pub mod analysis;
+pub mod error;
 pub mod model;
 pub mod output;
 pub mod parse;
 #[cfg(test)]
 mod snapshot_tests;
 #[cfg(test)]
+// Some graphs are only drawn by the renderers' tests
+#[cfg_attr(not(all(feature = "ascii", feature = "mermaid")), allow(dead_code))]
 pub(crate) mod test_utils;
 
+pub use error::{CvizError, Result};
+
 /// Check if this is the connection for a specific interface
 pub fn is_connection_for(conn: &InterfaceConnection, interface_name: &str) -> bool {
     conn.interface_name.contains(interface_name)
@@ -32,12 +72,40 @@ pub fn find_chain_interfaces(graph: &CompositionGraph) -> Vec<String> {
         .map(|c| c.interface_name.as_str())
         .collect();
 
-    graph
+    let mut names: Vec<String> = graph
         .component_exports
-        .keys()
-        .filter(|name| inter_component.contains(name.as_str()))
-        .cloned()
-        .collect()
+        .iter()
+        .filter(|e| e.kind == ExternKind::Instance && inter_component.contains(e.name.as_str()))
+        .map(|e| e.name.clone())
+        .collect();
+    names.dedup();
+    names
+}
+
+/// The interfaces whose chains to draw: the exported instance interfaces
+/// whose name contains `filter` (e.g. `wasi:messaging`), even when no other
+/// instance imports them, or the middleware chains found by
+/// [`find_chain_interfaces`] when no filter is given.
+pub fn chain_interfaces_matching(graph: &CompositionGraph, filter: Option<&str>) -> Vec<String> {
+    let Some(filter) = filter else {
+        return find_chain_interfaces(graph);
+    };
+    let mut names = exported_interfaces(graph);
+    names.retain(|name| name.contains(filter));
+    names
+}
+
+/// Every instance interface the composition exports, whether or not it
+/// forms a middleware chain.
+pub fn exported_interfaces(graph: &CompositionGraph) -> Vec<String> {
+    let mut names: Vec<String> = graph
+        .component_exports
+        .iter()
+        .filter(|e| e.kind == ExternKind::Instance)
+        .map(|e| e.name.clone())
+        .collect();
+    names.dedup();
+    names
 }
 
 /// Get the chain in request-flow order (outermost → innermost).
@@ -53,16 +121,8 @@ pub fn get_chain_for(graph: &CompositionGraph, interface_name: &str) -> Vec<u32>
     let export_instance = graph
         .component_exports
         .iter()
-        .find(|(name, _)| name.contains(interface_name))
-        .map(
-            |(
-                _,
-                ExportInfo {
-                    source_instance: idx,
-                    ..
-                },
-            )| *idx,
-        );
+        .find(|e| e.name.contains(interface_name))
+        .map(|e| e.source_instance);
 
     let Some(start) = export_instance else {
         return vec![];
@@ -77,25 +137,11 @@ pub fn get_chain_for(graph: &CompositionGraph, interface_name: &str) -> Vec<u32>
     });
 
     if start_has_relevant_import {
-        let mut chain = Vec::new();
-        let mut current: Option<(u32, bool)> = Some((start, false));
-        let mut visited = std::collections::HashSet::new();
-
-        while let Some((idx, is_host)) = current {
-            if is_host || visited.contains(&idx) {
-                break;
-            }
-            visited.insert(idx);
-            chain.push(idx);
-
-            current = graph.nodes.get(&idx).and_then(|node| {
-                node.imports
-                    .iter()
-                    .find(|conn| is_connection_for(conn, interface_name) && !conn.is_host_import)
-                    .and_then(|conn| conn.source_instance.map(|src| (src, conn.is_host_import)))
-            });
-        }
-        return chain;
+        return graph
+            .dfs(start)
+            .along(interface_name)
+            .map(|(id, _)| id)
+            .collect();
     }
 
     // Fallback: the export source is a synthetic shim whose constructor args are
@@ -191,6 +237,22 @@ mod tests {
         assert!(chains[0].contains("handler"));
     }
 
+    #[test]
+    fn test_chain_interfaces_matching() {
+        let graph = two_chain_graph();
+        assert_eq!(
+            chain_interfaces_matching(&graph, Some("wasi:keyvalue")),
+            vec!["wasi:keyvalue/store@0.1.0"]
+        );
+        assert!(chain_interfaces_matching(&graph, Some("wasi:messaging")).is_empty());
+
+        let mut all = chain_interfaces_matching(&graph, None);
+        all.sort();
+        let mut chains = find_chain_interfaces(&graph);
+        chains.sort();
+        assert_eq!(all, chains);
+    }
+
     #[test]
     fn test_get_chain_for_http_handler() {
         let graph = simple_chain_graph();
@@ -231,7 +293,7 @@ mod tests {
         // An interface that is exported but never imported inter-component
         // (only imported from the host) should NOT be identified as a chain.
         let mut graph = CompositionGraph::new();
-        use crate::model::{ComponentNode, InterfaceConnection};
+        use crate::model::{ComponentNode, ExternKind, InterfaceConnection};
 
         let mut srv = ComponentNode::new("$srv".to_string(), 0, 0);
         srv.add_import(InterfaceConnection {
@@ -240,6 +302,9 @@ mod tests {
             is_host_import: true,
             interface_type: None,
             fingerprint: None,
+            source_export: None,
+            kind: ExternKind::Instance,
+            alias_chain: Vec::new(),
         });
         graph.add_node(1, srv);
         // Export it, but nobody imports it from another component
@@ -267,7 +332,7 @@ mod tests {
         // Build a graph where two nodes import each other on the same interface.
         // get_chain_for must terminate without panicking.
         let mut graph = CompositionGraph::new();
-        use crate::model::{ComponentNode, InterfaceConnection};
+        use crate::model::{ComponentNode, ExternKind, InterfaceConnection};
 
         let mut a = ComponentNode::new("$a".to_string(), 0, 0);
         a.add_import(InterfaceConnection {
@@ -276,6 +341,9 @@ mod tests {
             is_host_import: false,
             interface_type: None,
             fingerprint: None,
+            source_export: None,
+            kind: ExternKind::Instance,
+            alias_chain: Vec::new(),
         });
         graph.add_node(1, a);
 
@@ -286,6 +354,9 @@ mod tests {
             is_host_import: false,
             interface_type: None,
             fingerprint: None,
+            source_export: None,
+            kind: ExternKind::Instance,
+            alias_chain: Vec::new(),
         });
         graph.add_node(2, b);
 
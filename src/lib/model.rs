@@ -1,5 +1,13 @@
 use sha2::{Digest, Sha256};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+pub mod builder;
+pub mod filter;
+pub mod labels;
+pub mod naming;
+#[cfg(feature = "petgraph")]
+pub mod petgraph;
+pub mod traverse;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct ValueTypeId(u32);
@@ -60,6 +68,57 @@ pub struct ComponentNode {
     /// Each entry describes a dependency on another instance or the host.
     /// These connections define the edges of the composition graph.
     pub imports: Vec<InterfaceConnection>,
+
+    /// Package the instantiated component was published as, e.g. `"my:srv"`.
+    ///
+    /// Taken from the component's own name section, which registry tooling
+    /// sets to the package name.  `None` when the component is unnamed or
+    /// its name is not a `namespace:name` package name.
+    pub package: Option<String>,
+
+    /// Version of that package, from the component's `version` metadata.
+    pub package_version: Option<String>,
+
+    /// Name the name section gives the instantiated component definition,
+    /// e.g. `"auth"` for `(component $auth ...)`.
+    ///
+    /// Release builds often strip instance names but keep these.
+    pub component_name: Option<String>,
+
+    /// Friendlier label chosen by [`naming::resolve_labels`] for an instance
+    /// the name section left unnamed (see [`ComponentNode::is_unnamed`]).
+    pub label: Option<String>,
+
+    /// Number of component definitions enclosing the instance: 0 for
+    /// instances created by the root component, 1 for those created inside
+    /// a component it defines, and so on.
+    pub depth: u32,
+
+    /// Size in bytes of the instantiated component as embedded in the
+    /// binary, including any components and core modules it contains.
+    pub size: Option<u64>,
+
+    /// Content digest of those same bytes, as `sha256:<hex>`, for checking
+    /// that a component is the exact binary that was reviewed.
+    pub digest: Option<String>,
+
+    /// Non-default wasm features used by the core modules inside the
+    /// instantiated component.
+    pub features: BTreeSet<WasmFeature>,
+
+    /// Whether instantiating the component runs start logic: a component
+    /// `start` function, or a start function of a core module inside it.
+    pub runs_start: bool,
+    /// Names of the imports the instantiated component declares, or `None`
+    /// when its definition is not known.  Compared against `imports` to
+    /// find imports left unwired (see [`crate::analysis::unsatisfied_imports`]);
+    /// imports plugged in with items the graph does not model, such as
+    /// functions defined by the enclosing component, are left out.
+    pub declared_imports: Option<BTreeSet<String>>,
+
+    /// Number of identical instances this node stands for: 1, or more once
+    /// [`CompositionGraph::collapse_duplicates`] has merged copies into it.
+    pub copies: u32,
 }
 impl ComponentNode {
     pub fn new(name: String, component_index: u32, component_num: u32) -> Self {
@@ -68,16 +127,71 @@ impl ComponentNode {
             component_index,
             component_num,
             imports: Vec::new(),
+            package: None,
+            package_version: None,
+            component_name: None,
+            label: None,
+            depth: 0,
+            size: None,
+            digest: None,
+            features: BTreeSet::new(),
+            runs_start: false,
+            declared_imports: None,
+            copies: 1,
         }
     }
 
+    /// Name given to instance `index` when the name section has no entry
+    /// for it.
+    pub fn fallback_name(index: u32) -> String {
+        format!("instance_{}", index)
+    }
+
+    /// Whether this instance carries a [fallback name](Self::fallback_name)
+    /// rather than one from the name section.
+    pub fn is_unnamed(&self) -> bool {
+        self.name
+            .trim_start_matches('$')
+            .strip_prefix("instance_")
+            .is_some_and(|n| n.parse::<u32>().is_ok())
+    }
+
+    /// `package@version` (or just `package` when unversioned), if known.
+    pub fn package_id(&self) -> Option<String> {
+        let package = self.package.as_ref()?;
+        Some(match &self.package_version {
+            Some(version) => format!("{}@{}", package, version),
+            None => package.clone(),
+        })
+    }
+
     pub fn add_import(&mut self, connection: InterfaceConnection) {
         self.imports.push(connection);
     }
 
+    /// Namespace the instance belongs to: that of its package (`acme` for
+    /// `acme:auth`), or else the part of its label before the first `:`,
+    /// `-` or `_` (`mdl` for `mdl-a`).  `None` for synthetic instances,
+    /// unlabelled unnamed ones, and labels without such a prefix.
+    pub fn namespace(&self) -> Option<String> {
+        if self.component_index == SYNTHETIC_COMPONENT {
+            return None;
+        }
+        if let Some((namespace, _)) = self.package.as_deref().and_then(|p| p.split_once(':')) {
+            return Some(namespace.to_string());
+        }
+        if self.is_unnamed() && self.label.is_none() {
+            return None;
+        }
+        let (prefix, rest) = self.display_label().split_once([':', '-', '_'])?;
+        (!prefix.is_empty() && !rest.is_empty()).then(|| prefix.to_string())
+    }
+
     /// Get a display label for the node
     pub fn display_label(&self) -> &str {
-        self.name.trim_start_matches('$')
+        self.label
+            .as_deref()
+            .unwrap_or_else(|| self.name.trim_start_matches('$'))
     }
 }
 
@@ -135,6 +249,113 @@ pub struct InterfaceConnection {
     /// whether two interfaces are structurally identical.
     // TODO: Can i make this non-optional?
     pub fingerprint: Option<String>,
+
+    /// Name under which the provider offers this interface, when known.
+    ///
+    /// For an instance export this is the export name; for a host import it
+    /// is the name of the root component's import.  When it differs from
+    /// `interface_name` the interface was plugged in under a new name.
+    pub source_export: Option<String>,
+
+    /// What is being passed: usually a whole instance, but instantiation
+    /// arguments can also be individual functions, values or types.
+    pub kind: ExternKind,
+
+    /// Intermediate alias hops between `source_instance` and the item
+    /// passed, outermost instance first.
+    ///
+    /// When an instance exported by `source_instance` is aliased and one of
+    /// *its* exports plugged in, this holds the name of that nested
+    /// instance (and so on for deeper nesting); `source_export` is the final
+    /// hop.  Empty when the provider's own export was passed directly.
+    pub alias_chain: Vec<String>,
+}
+
+/// The kind of item an [`InterfaceConnection`] carries or an [`ExportInfo`]
+/// exports.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ExternKind {
+    #[default]
+    Instance,
+    Func,
+    Value,
+    Type,
+}
+
+impl ExternKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ExternKind::Instance => "instance",
+            ExternKind::Func => "func",
+            ExternKind::Value => "value",
+            ExternKind::Type => "type",
+        }
+    }
+}
+
+impl std::fmt::Display for ExternKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for ExternKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "instance" => Ok(ExternKind::Instance),
+            "func" => Ok(ExternKind::Func),
+            "value" => Ok(ExternKind::Value),
+            "type" => Ok(ExternKind::Type),
+            _ => Err(format!("unknown connection kind: {}", s)),
+        }
+    }
+}
+
+/// A WebAssembly proposal beyond the default feature set that a core module
+/// relies on, and so a runtime must enable to run it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum WasmFeature {
+    /// Shared memories or atomic instructions.
+    Threads,
+    /// 64-bit memories or tables.
+    Memory64,
+    /// More than one memory in a module.
+    MultiMemory,
+    /// Relaxed SIMD instructions.
+    RelaxedSimd,
+}
+
+impl WasmFeature {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            WasmFeature::Threads => "threads",
+            WasmFeature::Memory64 => "memory64",
+            WasmFeature::MultiMemory => "multi-memory",
+            WasmFeature::RelaxedSimd => "relaxed-simd",
+        }
+    }
+}
+
+impl std::fmt::Display for WasmFeature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for WasmFeature {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "threads" => Ok(WasmFeature::Threads),
+            "memory64" => Ok(WasmFeature::Memory64),
+            "multi-memory" => Ok(WasmFeature::MultiMemory),
+            "relaxed-simd" => Ok(WasmFeature::RelaxedSimd),
+            _ => Err(format!("unknown wasm feature: {}", s)),
+        }
+    }
 }
 
 impl InterfaceConnection {
@@ -152,9 +373,19 @@ impl InterfaceConnection {
             is_host_import: false,
             interface_type,
             fingerprint,
+            source_export: None,
+            kind: ExternKind::Instance,
+            alias_chain: Vec::new(),
         }
     }
 
+    /// Whether the provider offers this interface under a different name.
+    pub fn is_renamed(&self) -> bool {
+        self.source_export
+            .as_ref()
+            .is_some_and(|name| *name != self.interface_name)
+    }
+
     /// Checks whether this connection is type-compatible with another.
     ///
     /// Compatibility is determined by comparing the deterministic fingerprints
@@ -384,14 +615,16 @@ pub struct CompositionGraph {
     /// the interfaces it imports from other instances or the host.
     pub nodes: BTreeMap<u32, ComponentNode>,
 
-    /// Interfaces exported by the final composed component.
+    /// Items exported by the final composed component, sorted by name.
     ///
-    /// The key is the fully-qualified interface name (for example
-    /// `"wasi:http/handler@0.3.0"`), and the value is the instance index
-    /// providing that interface.
+    /// Usually these are interfaces (for example `"wasi:http/handler@0.3.0"`),
+    /// but functions, values and types can be exported too; see
+    /// [`ExportInfo::kind`].  Names are unique in a component binary, but
+    /// graphs built from other sources may export several items under one
+    /// name, so look exports up with [`CompositionGraph::export`].
     ///
     /// This effectively defines the public surface of the composed component.
-    pub component_exports: BTreeMap<String, ExportInfo>,
+    pub component_exports: Vec<ExportInfo>,
 
     /// Global arena containing all unique value types referenced in the graph.
     ///
@@ -410,6 +643,195 @@ pub struct CompositionGraph {
     /// The arena is shared across the entire graph so that all interface
     /// signatures refer to a single canonical set of type definitions.
     pub arena: TypeArena,
+
+    /// Core modules and core instances defined inside each component.
+    ///
+    /// The key is the `component_num` of the component definition that
+    /// contains them (see [`ComponentNode::component_num`]), so every
+    /// instance of the same component shares one entry.  Core items defined
+    /// directly in the root component are not recorded.
+    pub core: BTreeMap<u32, CoreStructure>,
+
+    /// Authorship and licensing metadata read from custom sections.
+    ///
+    /// Keyed like [`core`](Self::core) by `component_num`; the `None` key
+    /// holds the metadata of the root (composed) component itself.
+    /// Components without any metadata have no entry.
+    pub metadata: BTreeMap<Option<u32>, ComponentMetadata>,
+}
+
+/// Metadata a component carries about itself, as written by
+/// `wasm-tools metadata add` and similar tools.
+///
+/// Each field comes from the custom section of the same name (`authors`,
+/// `licenses`, ...) or, for older components, from the JSON
+/// `registry-metadata` section.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ComponentMetadata {
+    /// The component's own name (from its name section), usually the
+    /// package name it was published under.
+    pub name: Option<String>,
+    pub authors: Option<String>,
+    pub description: Option<String>,
+    /// SPDX license expression, e.g. `"Apache-2.0 WITH LLVM-exception"`.
+    pub licenses: Option<String>,
+    /// URL of the source code.
+    pub source: Option<String>,
+    pub homepage: Option<String>,
+    /// Source revision (e.g. a git commit) the component was built from.
+    pub revision: Option<String>,
+    pub version: Option<String>,
+    /// Contents of the `producers` sections of the component and the core
+    /// modules it embeds: field (`language`, `processed-by`, `sdk`) to tool
+    /// name to version.
+    pub producers: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl ComponentMetadata {
+    /// The fields that are set, as `(field name, value)` pairs in a fixed order.
+    pub fn fields(&self) -> Vec<(&'static str, &str)> {
+        [
+            ("name", &self.name),
+            ("authors", &self.authors),
+            ("description", &self.description),
+            ("licenses", &self.licenses),
+            ("source", &self.source),
+            ("homepage", &self.homepage),
+            ("revision", &self.revision),
+            ("version", &self.version),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| Some((name, value.as_deref()?)))
+        .collect()
+    }
+
+    /// Mutable access to the field stored in the custom section `name`.
+    pub fn field_mut(&mut self, name: &str) -> Option<&mut Option<String>> {
+        match name {
+            "authors" => Some(&mut self.authors),
+            "description" => Some(&mut self.description),
+            "licenses" => Some(&mut self.licenses),
+            "source" => Some(&mut self.source),
+            "homepage" => Some(&mut self.homepage),
+            "revision" => Some(&mut self.revision),
+            "version" => Some(&mut self.version),
+            _ => None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields().is_empty() && self.producers.is_empty()
+    }
+
+    /// A one-line summary of the producers, e.g.
+    /// `"Rust / rustc 1.89.0, wit-component 0.239.0"`: the languages, then
+    /// the SDKs and tools with their versions.
+    pub fn producer_summary(&self) -> Option<String> {
+        let entries = |field: &str| {
+            self.producers
+                .get(field)
+                .into_iter()
+                .flat_map(|values| values.iter())
+        };
+        let languages: Vec<&str> = entries("language").map(|(name, _)| name.as_str()).collect();
+        let tools: Vec<String> = entries("sdk")
+            .chain(entries("processed-by"))
+            .map(|(name, version)| {
+                // rustc reports e.g. "1.89.0 (29483883e 2025-08-04)"
+                match version.split_whitespace().next() {
+                    Some(version) => format!("{} {}", name, version),
+                    None => name.clone(),
+                }
+            })
+            .collect();
+        match (languages.is_empty(), tools.is_empty()) {
+            (true, true) => None,
+            (false, true) => Some(languages.join(", ")),
+            (true, false) => Some(tools.join(", ")),
+            (false, false) => Some(format!("{} / {}", languages.join(", "), tools.join(", "))),
+        }
+    }
+}
+
+/// The core wasm layer of one component: the modules it embeds and the core
+/// instances that instantiate and link them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CoreStructure {
+    pub modules: Vec<CoreModule>,
+    pub instances: Vec<CoreInstance>,
+}
+
+impl CoreStructure {
+    /// Display name of the module at `index` (its name, or `module N`).
+    pub fn module_label(&self, index: u32) -> String {
+        self.modules
+            .iter()
+            .find(|m| m.index == index)
+            .and_then(|m| m.name.clone())
+            .unwrap_or_else(|| format!("module {}", index))
+    }
+
+    /// Display name of the core instance at `index` (its name, or `#N`).
+    pub fn instance_label(&self, index: u32) -> String {
+        self.instances
+            .iter()
+            .find(|i| i.index == index)
+            .and_then(|i| i.name.clone())
+            .unwrap_or_else(|| format!("#{}", index))
+    }
+}
+
+/// A core wasm module embedded in a component.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoreModule {
+    /// Index in the component's core module index space.
+    pub index: u32,
+    /// Name from the component or module name section, e.g. `"main"` or
+    /// `"wit-component:adapter:wasi_snapshot_preview1"`.
+    pub name: Option<String>,
+}
+
+/// A core wasm instance created inside a component.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoreInstance {
+    /// Index in the component's core instance index space.
+    pub index: u32,
+    /// Name from the component name section, if any.
+    pub name: Option<String>,
+    pub kind: CoreInstanceKind,
+}
+
+/// How a [`CoreInstance`] was created.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CoreInstanceKind {
+    /// Instantiation of a core module.  Each argument satisfies the module's
+    /// imports from module name `name` with another core instance.
+    Instantiate {
+        module_index: u32,
+        args: Vec<(String, u32)>,
+    },
+    /// A bag of core items (functions, memories, ...) exported under the given names.
+    FromExports(Vec<String>),
+}
+
+/// Imports and exports of a plain core wasm module, shown instead of a
+/// composition graph when the input turns out not to be a component.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ModuleInterface {
+    /// Imports grouped by the module name they are imported from, e.g.
+    /// `"wasi_snapshot_preview1"`, in section order within each group.
+    pub imports: BTreeMap<String, Vec<ModuleItem>>,
+    pub exports: Vec<ModuleItem>,
+}
+
+/// One import or export of a core module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleItem {
+    pub name: String,
+    /// `func`, `table`, `memory`, `global` or `tag`.
+    pub kind: String,
+    /// Signature of a function, e.g. `(i32, i32) -> i32`.
+    pub signature: Option<String>,
 }
 
 impl CompositionGraph {
@@ -418,13 +840,15 @@ impl CompositionGraph {
     }
     pub fn new_with(
         nodes: BTreeMap<u32, ComponentNode>,
-        component_exports: BTreeMap<String, ExportInfo>,
+        component_exports: Vec<ExportInfo>,
         arena: TypeArena,
     ) -> Self {
         Self {
             nodes,
             component_exports,
             arena,
+            core: BTreeMap::new(),
+            metadata: BTreeMap::new(),
         }
     }
 
@@ -432,16 +856,134 @@ impl CompositionGraph {
         self.nodes.insert(instance_index, node);
     }
 
+    /// Drop the instances nested more than `max_depth` components deep, so
+    /// that each component instantiated at `max_depth` stands for the inner
+    /// composition it contains.  With a `max_depth` of 0 only the root
+    /// component's instances are kept.
+    pub fn collapse_nested(&mut self, max_depth: u32) {
+        self.nodes.retain(|_, node| node.depth <= max_depth);
+        // Instances only import from their own component's scope, so no
+        // kept instance should refer to a dropped one; if one does, the
+        // import is left to the host rather than dangling.
+        let remaining: std::collections::HashSet<u32> = self.nodes.keys().copied().collect();
+        for node in self.nodes.values_mut() {
+            for import in &mut node.imports {
+                if import
+                    .source_instance
+                    .is_some_and(|id| !remaining.contains(&id))
+                {
+                    import.source_instance = None;
+                    import.is_host_import = true;
+                }
+            }
+        }
+    }
+
+    /// Groups of instances of the same component created by the root
+    /// component, each listing graph IDs in order.  Only groups standing for
+    /// more than one instance are returned, which includes a single node
+    /// that [`collapse_duplicates`](Self::collapse_duplicates) merged copies
+    /// into.
+    ///
+    /// Instances are the same component when their digests match or, for
+    /// graphs without digests, when they instantiate the same package or
+    /// component index.  Nested instances are not compared: components
+    /// commonly embed identical helpers (such as the shims `wit-component`
+    /// generates) in each copy.
+    pub fn duplicate_instances(&self) -> Vec<Vec<u32>> {
+        let mut groups: BTreeMap<String, Vec<u32>> = BTreeMap::new();
+        for (&id, node) in &self.nodes {
+            if node.depth > 0 || node.component_index == SYNTHETIC_COMPONENT {
+                continue;
+            }
+            let key = match (&node.digest, node.package_id()) {
+                (Some(digest), _) => digest.clone(),
+                (None, Some(package)) => package,
+                (None, None) => format!("component {}", node.component_index),
+            };
+            groups.entry(key).or_default().push(id);
+        }
+        let mut groups: Vec<Vec<u32>> = groups
+            .into_values()
+            .filter(|ids| ids.iter().map(|id| self.nodes[id].copies).sum::<u32>() > 1)
+            .collect();
+        groups.sort();
+        groups
+    }
+
+    /// Merge each group of [`duplicate_instances`](Self::duplicate_instances)
+    /// into its first instance, which counts the merged ones in
+    /// [`ComponentNode::copies`].  Connections to and from the merged
+    /// instances move to the kept one; a connection between two copies is
+    /// dropped rather than drawn as a loop.
+    pub fn collapse_duplicates(&mut self) {
+        for group in self.duplicate_instances() {
+            let (&keep, rest) = group.split_first().unwrap();
+            let merged: Vec<ComponentNode> =
+                rest.iter().filter_map(|id| self.nodes.remove(id)).collect();
+            let redirect = |id: u32| if rest.contains(&id) { keep } else { id };
+
+            for node in self.nodes.values_mut() {
+                for import in &mut node.imports {
+                    import.source_instance = import.source_instance.map(redirect);
+                }
+            }
+            for export in &mut self.component_exports {
+                export.source_instance = redirect(export.source_instance);
+            }
+
+            let node = self.nodes.get_mut(&keep).unwrap();
+            for copy in merged {
+                node.copies += copy.copies;
+                node.imports
+                    .extend(copy.imports.into_iter().map(|mut import| {
+                        import.source_instance = import.source_instance.map(redirect);
+                        import
+                    }));
+            }
+            let mut seen = HashSet::new();
+            node.imports.retain(|import| {
+                import.source_instance != Some(keep)
+                    && seen.insert((import.interface_name.clone(), import.source_instance))
+            });
+            let mut seen = HashSet::new();
+            self.component_exports
+                .retain(|e| seen.insert((e.name.clone(), e.kind, e.source_instance)));
+        }
+    }
+
     pub fn get_node(&self, id: u32) -> Option<&ComponentNode> {
         self.nodes.get(&id)
     }
 
+    /// Record an instance export; see [`add_export_of_kind`](Self::add_export_of_kind).
     pub fn add_export(
         &mut self,
         interface_name: String,
         source_instance: u32,
         interface_type: Option<InterfaceType>,
     ) {
+        self.add_export_of_kind(
+            interface_name,
+            ExternKind::Instance,
+            source_instance,
+            interface_type,
+        );
+    }
+
+    /// Record an export of `kind`.  An export with the same name, kind and
+    /// source is updated in place, since the parser refines export types as
+    /// it goes; anything else is added after any exports sharing its name.
+    ///
+    /// Returns the recorded entry so callers can fill in the remaining
+    /// fields.
+    pub fn add_export_of_kind(
+        &mut self,
+        name: String,
+        kind: ExternKind,
+        source_instance: u32,
+        interface_type: Option<InterfaceType>,
+    ) -> &mut ExportInfo {
         let (ty, fingerprint) = match interface_type {
             Some(t) => {
                 let id = t.intern(&mut self.arena);
@@ -451,14 +993,128 @@ impl CompositionGraph {
             None => (None, None),
         };
 
-        self.component_exports.insert(
-            interface_name,
-            ExportInfo {
-                source_instance,
-                ty,
-                fingerprint,
-            },
-        );
+        let existing = self
+            .component_exports
+            .iter()
+            .position(|e| e.name == name && e.kind == kind && e.source_instance == source_instance);
+        let at = match existing {
+            Some(at) => {
+                let export = &mut self.component_exports[at];
+                export.ty = ty;
+                export.fingerprint = fingerprint;
+                at
+            }
+            None => {
+                let at = self.component_exports.partition_point(|e| e.name <= name);
+                self.component_exports.insert(
+                    at,
+                    ExportInfo {
+                        name,
+                        kind,
+                        source_instance,
+                        ty,
+                        fingerprint,
+                        alias_chain: Vec::new(),
+                    },
+                );
+                at
+            }
+        };
+        &mut self.component_exports[at]
+    }
+
+    /// The first export called `name`.
+    pub fn export(&self, name: &str) -> Option<&ExportInfo> {
+        self.component_exports.iter().find(|e| e.name == name)
+    }
+
+    /// Mutable access to the first export called `name`.
+    pub fn export_mut(&mut self, name: &str) -> Option<&mut ExportInfo> {
+        self.component_exports.iter_mut().find(|e| e.name == name)
+    }
+
+    /// Summary of the tools that produced `node`'s component, if recorded.
+    pub fn producers_of(&self, node: &ComponentNode) -> Option<String> {
+        self.metadata
+            .get(&Some(node.component_num))
+            .and_then(ComponentMetadata::producer_summary)
+    }
+
+    /// Description of `node`'s component, if recorded.
+    pub fn description_of(&self, node: &ComponentNode) -> Option<&str> {
+        self.metadata
+            .get(&Some(node.component_num))
+            .and_then(|m| m.description.as_deref())
+    }
+
+    /// Look up an instance by name, display label (without the `$` prefix),
+    /// numeric graph ID or [stable ID](Self::stable_ids).
+    pub fn find_node(&self, name: &str) -> Option<u32> {
+        self.nodes
+            .iter()
+            .find(|(_, n)| n.name == name || n.display_label() == name)
+            .map(|(&id, _)| id)
+            .or_else(|| name.parse().ok().filter(|id| self.nodes.contains_key(id)))
+            .or_else(|| {
+                self.stable_ids()
+                    .into_iter()
+                    .find(|(_, stable)| stable == name)
+                    .map(|(id, _)| id)
+            })
+    }
+
+    /// Identifiers for the instances that, unlike graph IDs, survive
+    /// rebuilding the composition: 12 hex digits of a SHA-256 over the
+    /// component's digest, the instance name and its wiring (each import
+    /// with the name of its provider).  Fallback names such as `instance_3`
+    /// are derived from the index, so they are left out of the hash.
+    ///
+    /// Identical instances wired identically hash alike; the second and
+    /// later of those, in graph order, get `-2`, `-3`, ... appended.
+    pub fn stable_ids(&self) -> BTreeMap<u32, String> {
+        let key = |node: &ComponentNode| {
+            if node.is_unnamed() {
+                node.digest.clone().unwrap_or_default()
+            } else {
+                node.name.trim_start_matches('$').to_string()
+            }
+        };
+        let mut seen: BTreeMap<String, u32> = BTreeMap::new();
+        self.nodes
+            .iter()
+            .map(|(&id, node)| {
+                let mut wiring: Vec<String> = node
+                    .imports
+                    .iter()
+                    .map(|c| {
+                        let provider = match c.source_instance.and_then(|s| self.get_node(s)) {
+                            Some(source) if !c.is_host_import => key(source),
+                            _ => "host".to_string(),
+                        };
+                        format!("{}\0{}", c.interface_name, provider)
+                    })
+                    .collect();
+                wiring.sort();
+
+                let mut hasher = Sha256::new();
+                hasher.update(node.digest.as_deref().unwrap_or_default());
+                hasher.update([0]);
+                if !node.is_unnamed() {
+                    hasher.update(node.name.trim_start_matches('$'));
+                }
+                for line in wiring {
+                    hasher.update([0]);
+                    hasher.update(line);
+                }
+                let hash = hex::encode(&hasher.finalize()[..6]);
+                let copies = seen.entry(hash.clone()).or_default();
+                *copies += 1;
+                match *copies {
+                    1 => (id, hash),
+                    n => (id, format!("{}-{}", hash, n)),
+                }
+            })
+            .collect()
     }
 
     /// Get all real (non-synthetic) component nodes
@@ -482,54 +1138,155 @@ impl CompositionGraph {
         interfaces.into_iter().collect()
     }
 
-    pub fn validate(&self) -> Result<(), String> {
-        for (
-            iface,
-            ExportInfo {
-                source_instance: src,
-                ..
-            },
-        ) in &self.component_exports
-        {
-            if !self.nodes.contains_key(src) {
-                return Err(format!(
-                    "Export '{}' references unknown instance {}",
-                    iface, src
-                ));
-            }
-        }
-
-        for (id, node) in &self.nodes {
+    /// Check that every reference in the graph points somewhere: exports
+    /// (other than re-exported imports) and instance imports name instances
+    /// that exist, and no instance imports from itself.  Every problem found is reported, in graph
+    /// order, imports before exports.
+    pub fn validate(&self) -> Result<(), GraphIssues> {
+        let mut issues = Vec::new();
+        for (&id, node) in &self.nodes {
             for conn in &node.imports {
                 // Host imports point to a synthetic provider that is never in the
                 // nodes map — skip them.
                 if conn.is_host_import {
                     continue;
                 }
-                if let Some(inst) = conn.source_instance {
-                    if !self.nodes.contains_key(&inst) {
-                        return Err(format!(
-                            "Instance {} imports from unknown instance {}",
-                            id, inst
-                        ));
+                let interface = conn.interface_name.clone();
+                match conn.source_instance {
+                    None => issues.push(GraphIssue::MissingImportSource {
+                        instance: id,
+                        interface,
+                    }),
+                    Some(source) if source == id => issues.push(GraphIssue::SelfImport {
+                        instance: id,
+                        interface,
+                    }),
+                    Some(source) if !self.nodes.contains_key(&source) => {
+                        issues.push(GraphIssue::UnknownImportSource {
+                            instance: id,
+                            interface,
+                            source,
+                        })
                     }
-                } else {
-                    return Err(format!("Instance {} imports from unknown instance", id));
-                };
+                    Some(_) => {}
+                }
             }
         }
+        for export in &self.component_exports {
+            // Imports re-exported unchanged are marked as coming from the
+            // synthetic component rather than an instance.
+            if export.source_instance != SYNTHETIC_COMPONENT
+                && !self.nodes.contains_key(&export.source_instance)
+            {
+                issues.push(GraphIssue::UnknownExportSource {
+                    export: export.name.clone(),
+                    source: export.source_instance,
+                });
+            }
+        }
+
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(GraphIssues(issues))
+        }
+    }
+}
+
+/// A dangling or circular reference found by [`CompositionGraph::validate`].
+/// Instances are identified by graph ID.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraphIssue {
+    /// An import that is not from the host names no providing instance.
+    MissingImportSource { instance: u32, interface: String },
+    /// An import names a providing instance that is not in the graph.
+    UnknownImportSource {
+        instance: u32,
+        interface: String,
+        source: u32,
+    },
+    /// An instance imports an interface from itself.
+    SelfImport { instance: u32, interface: String },
+    /// An export names a providing instance that is not in the graph.
+    UnknownExportSource { export: String, source: u32 },
+    /// A [builder](builder::CompositionGraphBuilder) was given two
+    /// instances with the same name.
+    DuplicateInstanceName { name: String },
+    /// A [builder](builder::CompositionGraphBuilder) was given a
+    /// connection or export naming an instance it was not given.
+    UnknownInstanceName { name: String },
+}
 
+impl std::fmt::Display for GraphIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GraphIssue::MissingImportSource {
+                instance,
+                interface,
+            } => write!(
+                f,
+                "instance {} imports {} from no instance",
+                instance, interface
+            ),
+            GraphIssue::UnknownImportSource {
+                instance,
+                interface,
+                source,
+            } => write!(
+                f,
+                "instance {} imports {} from unknown instance {}",
+                instance, interface, source
+            ),
+            GraphIssue::SelfImport {
+                instance,
+                interface,
+            } => write!(f, "instance {} imports {} from itself", instance, interface),
+            GraphIssue::UnknownExportSource { export, source } => write!(
+                f,
+                "export {} references unknown instance {}",
+                export, source
+            ),
+            GraphIssue::DuplicateInstanceName { name } => {
+                write!(f, "instance {} is defined more than once", name)
+            }
+            GraphIssue::UnknownInstanceName { name } => {
+                write!(f, "no instance is named {}", name)
+            }
+        }
+    }
+}
+
+/// The problems that failed [`CompositionGraph::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphIssues(pub Vec<GraphIssue>);
+
+impl std::fmt::Display for GraphIssues {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid composition graph, {} issue(s):", self.0.len())?;
+        for issue in &self.0 {
+            write!(f, "\n  - {}", issue)?;
+        }
         Ok(())
     }
 }
 
+impl std::error::Error for GraphIssues {}
+
 pub struct ExportInfo {
+    /// Export name, e.g. `"wasi:http/handler@0.3.0"` or `"run"`.
+    pub name: String,
+    /// What is exported.
+    pub kind: ExternKind,
     /// Index of the instance providing this export
     pub source_instance: u32,
     /// Fingerprint of the exported interface type
     pub fingerprint: Option<String>,
     /// Reference to the type in the global arena
     pub ty: Option<InternedId>,
+    /// Intermediate alias hops between `source_instance` and the exported
+    /// item, outermost instance first; empty when the instance's own export
+    /// is re-exported directly.
+    pub alias_chain: Vec<String>,
 }
 
 use std::collections::HashMap;
@@ -826,6 +1583,209 @@ mod tests {
         assert_eq!(conn2.short_label(), "streams");
     }
 
+    #[test]
+    fn test_producer_summary() {
+        let mut metadata = ComponentMetadata::default();
+        assert_eq!(metadata.producer_summary(), None);
+
+        metadata.producers.insert(
+            "processed-by".to_string(),
+            [
+                (
+                    "rustc".to_string(),
+                    "1.89.0 (29483883e 2025-08-04)".to_string(),
+                ),
+                ("wit-component".to_string(), "0.239.0".to_string()),
+            ]
+            .into(),
+        );
+        assert_eq!(
+            metadata.producer_summary().as_deref(),
+            Some("rustc 1.89.0, wit-component 0.239.0")
+        );
+
+        metadata.producers.insert(
+            "language".to_string(),
+            [("Rust".to_string(), String::new())].into(),
+        );
+        assert_eq!(
+            metadata.producer_summary().as_deref(),
+            Some("Rust / rustc 1.89.0, wit-component 0.239.0")
+        );
+    }
+
+    #[test]
+    fn test_namespace() {
+        let mut node = ComponentNode::new("$mdl-a".to_string(), 0, 0);
+        assert_eq!(node.namespace().as_deref(), Some("mdl"));
+        node.package = Some("acme:auth".to_string());
+        assert_eq!(node.namespace().as_deref(), Some("acme"));
+
+        assert_eq!(
+            ComponentNode::new("$srv".to_string(), 0, 0).namespace(),
+            None
+        );
+        assert_eq!(
+            ComponentNode::new("$-x".to_string(), 0, 0).namespace(),
+            None
+        );
+        assert_eq!(
+            ComponentNode::new("$instance_3".to_string(), 0, 0).namespace(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_find_node() {
+        let mut graph = CompositionGraph::new();
+        graph.add_node(3, ComponentNode::new("$srv".to_string(), 0, 0));
+        assert_eq!(graph.find_node("srv"), Some(3));
+        assert_eq!(graph.find_node("$srv"), Some(3));
+        assert_eq!(graph.find_node("3"), Some(3));
+        assert_eq!(graph.find_node("4"), None);
+        assert_eq!(graph.find_node("mdl"), None);
+        let stable = graph.stable_ids()[&3].clone();
+        assert_eq!(graph.find_node(&stable), Some(3));
+    }
+
+    #[test]
+    fn test_stable_ids() {
+        let ids = crate::test_utils::simple_chain_graph().stable_ids();
+        assert_eq!(ids.len(), 2);
+        assert!(ids.values().all(|id| id.len() == 12));
+
+        // Renumbering the instances keeps their IDs
+        let mut renumbered = CompositionGraph::new();
+        let mut graph = crate::test_utils::simple_chain_graph();
+        let mut srv = graph.nodes.remove(&1).unwrap();
+        let mut mw = graph.nodes.remove(&2).unwrap();
+        mw.imports[0].source_instance = Some(7);
+        srv.component_num = 4;
+        renumbered.add_node(7, srv);
+        renumbered.add_node(3, mw);
+        assert_eq!(renumbered.stable_ids()[&7], ids[&1]);
+        assert_eq!(renumbered.stable_ids()[&3], ids[&2]);
+
+        // Rewiring an instance changes its ID; identical instances are told apart
+        renumbered.nodes.get_mut(&3).unwrap().imports[0].is_host_import = true;
+        assert_ne!(renumbered.stable_ids()[&3], ids[&2]);
+        let copy = ComponentNode::new("$srv".to_string(), 0, 0);
+        let mut copies = CompositionGraph::new();
+        copies.add_node(1, copy.clone());
+        copies.add_node(2, copy);
+        let ids = copies.stable_ids();
+        assert_eq!(ids[&2], format!("{}-2", ids[&1]));
+    }
+
+    #[test]
+    fn test_validate_reports_every_issue() {
+        let mut graph = crate::test_utils::simple_chain_graph();
+        assert!(graph.validate().is_ok());
+
+        let imports = &mut graph.nodes.get_mut(&2).unwrap().imports;
+        imports[0].source_instance = Some(7);
+        imports[1].is_host_import = false;
+        graph.add_export("wasi:cli/run@0.2.0".to_string(), 5, None);
+        let issues = graph.validate().unwrap_err();
+        assert_eq!(
+            issues.to_string(),
+            "invalid composition graph, 3 issue(s):\n  \
+             - instance 2 imports wasi:http/handler@0.3.0 from unknown instance 7\n  \
+             - instance 2 imports wasi:logging/log@0.1.0 from no instance\n  \
+             - export wasi:cli/run@0.2.0 references unknown instance 5"
+        );
+    }
+
+    #[test]
+    fn test_collapse_nested() {
+        let mut graph = crate::test_utils::simple_chain_graph();
+        let mut shim = ComponentNode::new("$shim".to_string(), 0, 3);
+        shim.depth = 1;
+        graph.add_node(3, shim);
+
+        graph.collapse_nested(1);
+        assert_eq!(graph.nodes.len(), 3);
+        graph.collapse_nested(0);
+        let kept: Vec<u32> = graph.nodes.keys().copied().collect();
+        assert_eq!(kept, vec![1, 2]);
+        assert!(graph.validate().is_ok());
+    }
+
+    #[test]
+    fn test_collapse_duplicates() {
+        // $srv and $middleware instantiate the same package, one feeding
+        // the other
+        let mut graph = crate::test_utils::simple_chain_graph();
+        for node in graph.nodes.values_mut() {
+            node.package = Some("my:mw".to_string());
+        }
+        let mut nested = ComponentNode::new("$shim".to_string(), 0, 3);
+        nested.package = Some("my:mw".to_string());
+        nested.depth = 1;
+        graph.add_node(3, nested);
+        assert_eq!(graph.duplicate_instances(), vec![vec![1, 2]]);
+
+        graph.collapse_duplicates();
+        assert_eq!(graph.nodes.keys().copied().collect::<Vec<_>>(), vec![1, 3]);
+        let kept = &graph.nodes[&1];
+        assert_eq!(kept.copies, 2);
+        let imports: Vec<(&str, Option<u32>)> = kept
+            .imports
+            .iter()
+            .map(|c| (c.interface_name.as_str(), c.source_instance))
+            .collect();
+        assert_eq!(
+            imports,
+            vec![
+                ("wasi:http/handler@0.3.0", None),
+                ("wasi:logging/log@0.1.0", None),
+            ]
+        );
+        assert_eq!(graph.component_exports[0].source_instance, 1);
+        assert!(graph.validate().is_ok());
+
+        // The merged node still counts as duplicated
+        assert_eq!(graph.duplicate_instances(), vec![vec![1]]);
+    }
+
+    #[test]
+    fn test_exports_sharing_a_name() {
+        let mut graph = CompositionGraph::new();
+        graph.add_export("wasi:http/handler@0.3.0".to_string(), 2, None);
+        graph.add_export("wasi:cli/run@0.2.0".to_string(), 1, None);
+        graph.add_export("wasi:http/handler@0.3.0".to_string(), 1, None);
+        graph.add_export_of_kind(
+            "wasi:http/handler@0.3.0".to_string(),
+            ExternKind::Func,
+            1,
+            None,
+        );
+        // re-recording an existing export updates it in place
+        graph.add_export("wasi:http/handler@0.3.0".to_string(), 2, None);
+
+        let exports: Vec<(&str, ExternKind, u32)> = graph
+            .component_exports
+            .iter()
+            .map(|e| (e.name.as_str(), e.kind, e.source_instance))
+            .collect();
+        assert_eq!(
+            exports,
+            vec![
+                ("wasi:cli/run@0.2.0", ExternKind::Instance, 1),
+                ("wasi:http/handler@0.3.0", ExternKind::Instance, 2),
+                ("wasi:http/handler@0.3.0", ExternKind::Instance, 1),
+                ("wasi:http/handler@0.3.0", ExternKind::Func, 1),
+            ]
+        );
+        assert_eq!(
+            graph
+                .export("wasi:http/handler@0.3.0")
+                .unwrap()
+                .source_instance,
+            2
+        );
+    }
+
     #[test]
     fn test_short_interface_name() {
         assert_eq!(short_interface_name("wasi:http/handler@0.3.0"), "handler");
@@ -0,0 +1,324 @@
+//! Assemble a [`CompositionGraph`] by hand.
+//!
+//! Tools that plan compositions rather than parse them (a WAC planner, a
+//! test generator) can describe instances and their wiring by name and get
+//! a graph the renderers and analyses accept, without filling in
+//! [`ComponentNode`]s and [`InterfaceConnection`]s themselves:
+//!
+//! ```
+//! use cviz::model::builder::CompositionGraphBuilder;
+//!
+//! let graph = CompositionGraphBuilder::new()
+//!     .add_node("srv")
+//!     .add_node("auth")
+//!     .add_host_import("srv", "wasi:http/handler@0.3.0")
+//!     .add_connection("auth", "srv", "wasi:http/handler@0.3.0")
+//!     .add_export("wasi:http/handler@0.3.0", "auth")
+//!     .build()?;
+//! assert_eq!(cviz::find_chain_interfaces(&graph), ["wasi:http/handler@0.3.0"]);
+//! # Ok::<(), cviz::CvizError>(())
+//! ```
+
+use super::{
+    naming, ComponentNode, CompositionGraph, ExternKind, GraphIssue, GraphIssues,
+    InterfaceConnection, InterfaceType, TypeArena,
+};
+use crate::error::Result;
+use std::collections::BTreeMap;
+
+/// Collects instances, connections and exports, then checks and assembles
+/// them with [`build`](Self::build).
+///
+/// Instances are referred to by name (with or without the `$` prefix the
+/// parsers give them) and numbered from 0 in the order they are added, so
+/// connections may name an instance added later.
+#[derive(Default)]
+pub struct CompositionGraphBuilder {
+    graph: CompositionGraph,
+    /// Instance name, without `$`, to graph ID.
+    ids: BTreeMap<String, u32>,
+    connections: Vec<Connection>,
+    exports: Vec<Export>,
+    issues: Vec<GraphIssue>,
+}
+
+/// An import waiting for its instances to be resolved.
+struct Connection {
+    importer: String,
+    /// `None` for a host import.
+    provider: Option<String>,
+    interface: String,
+    interface_type: Option<InterfaceType>,
+}
+
+struct Export {
+    name: String,
+    instance: String,
+    interface_type: Option<InterfaceType>,
+}
+
+impl CompositionGraphBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The arena the value types of typed connections and exports are to
+    /// be interned in.
+    pub fn arena_mut(&mut self) -> &mut TypeArena {
+        &mut self.graph.arena
+    }
+
+    /// Add an instance called `name`.
+    pub fn add_node(self, name: impl Into<String>) -> Self {
+        self.add_node_with(name, |_| {})
+    }
+
+    /// Add an instance called `name`, letting `configure` fill in the rest
+    /// of it (package, size, ...).  Imports are best added with
+    /// [`add_connection`](Self::add_connection).
+    pub fn add_node_with(
+        mut self,
+        name: impl Into<String>,
+        configure: impl FnOnce(&mut ComponentNode),
+    ) -> Self {
+        let name = name.into();
+        let key = name.trim_start_matches('$').to_string();
+        if self.ids.contains_key(&key) {
+            self.issues
+                .push(GraphIssue::DuplicateInstanceName { name: key });
+            return self;
+        }
+        let id = self.ids.len() as u32;
+        let mut node = ComponentNode::new(format!("${}", key), id, id);
+        configure(&mut node);
+        self.graph.add_node(id, node);
+        self.ids.insert(key, id);
+        self
+    }
+
+    /// Have `importer` import `interface` from `provider`.
+    pub fn add_connection(
+        self,
+        importer: impl Into<String>,
+        provider: impl Into<String>,
+        interface: impl Into<String>,
+    ) -> Self {
+        self.connect(
+            importer.into(),
+            Some(provider.into()),
+            interface.into(),
+            None,
+        )
+    }
+
+    /// [`add_connection`](Self::add_connection), with the interface's type
+    /// so renderers can show it and compatibility checks can compare it.
+    pub fn add_typed_connection(
+        self,
+        importer: impl Into<String>,
+        provider: impl Into<String>,
+        interface: impl Into<String>,
+        interface_type: InterfaceType,
+    ) -> Self {
+        self.connect(
+            importer.into(),
+            Some(provider.into()),
+            interface.into(),
+            Some(interface_type),
+        )
+    }
+
+    /// Have `importer` import `interface` from the host.
+    pub fn add_host_import(
+        self,
+        importer: impl Into<String>,
+        interface: impl Into<String>,
+    ) -> Self {
+        self.connect(importer.into(), None, interface.into(), None)
+    }
+
+    /// Export `name` from `instance`.
+    pub fn add_export(self, name: impl Into<String>, instance: impl Into<String>) -> Self {
+        self.export(name.into(), instance.into(), None)
+    }
+
+    /// [`add_export`](Self::add_export), with the exported interface's type.
+    pub fn add_typed_export(
+        self,
+        name: impl Into<String>,
+        instance: impl Into<String>,
+        interface_type: InterfaceType,
+    ) -> Self {
+        self.export(name.into(), instance.into(), Some(interface_type))
+    }
+
+    fn connect(
+        mut self,
+        importer: String,
+        provider: Option<String>,
+        interface: String,
+        interface_type: Option<InterfaceType>,
+    ) -> Self {
+        self.connections.push(Connection {
+            importer,
+            provider,
+            interface,
+            interface_type,
+        });
+        self
+    }
+
+    fn export(
+        mut self,
+        name: String,
+        instance: String,
+        interface_type: Option<InterfaceType>,
+    ) -> Self {
+        self.exports.push(Export {
+            name,
+            instance,
+            interface_type,
+        });
+        self
+    }
+
+    /// Resolve the names used and assemble the graph.
+    ///
+    /// Fails with [`CvizError::Validation`](crate::CvizError::Validation)
+    /// listing every name that was defined twice or never, and every
+    /// problem [`CompositionGraph::validate`] finds, such as an instance
+    /// importing from itself.
+    pub fn build(self) -> Result<CompositionGraph> {
+        let Self {
+            mut graph,
+            ids,
+            connections,
+            exports,
+            mut issues,
+        } = self;
+        let mut resolve = |name: &str| {
+            let name = name.trim_start_matches('$');
+            let id = ids.get(name).copied();
+            if id.is_none() {
+                let issue = GraphIssue::UnknownInstanceName {
+                    name: name.to_string(),
+                };
+                if !issues.contains(&issue) {
+                    issues.push(issue);
+                }
+            }
+            id
+        };
+
+        let mut imports = Vec::new();
+        for c in connections {
+            let importer = resolve(&c.importer);
+            let provider = c.provider.as_deref().map(&mut resolve);
+            let (Some(importer), None | Some(Some(_))) = (importer, provider) else {
+                continue;
+            };
+            let mut conn = InterfaceConnection::from_instance(
+                c.interface,
+                provider.flatten(),
+                c.interface_type,
+                &graph.arena,
+            );
+            conn.is_host_import = provider.is_none();
+            imports.push((importer, conn));
+        }
+        let exports: Vec<_> = exports
+            .into_iter()
+            .filter_map(|e| Some((e.name, resolve(&e.instance)?, e.interface_type)))
+            .collect();
+
+        for (importer, conn) in imports {
+            if let Some(node) = graph.nodes.get_mut(&importer) {
+                node.add_import(conn);
+            }
+        }
+        for (name, instance, interface_type) in exports {
+            graph.add_export_of_kind(name, ExternKind::Instance, instance, interface_type);
+        }
+
+        if let Err(GraphIssues(found)) = graph.validate() {
+            issues.extend(found);
+        }
+        if !issues.is_empty() {
+            return Err(GraphIssues(issues).into());
+        }
+        naming::resolve_labels(&mut graph, &naming::default_resolvers());
+        Ok(graph)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::simple_chain_graph;
+    use crate::CvizError;
+
+    #[test]
+    fn test_builds_simple_chain() {
+        let graph = CompositionGraphBuilder::new()
+            .add_node("srv")
+            .add_node_with("$middleware", |node| node.size = Some(1024))
+            .add_host_import("srv", "wasi:http/handler@0.3.0")
+            .add_connection("middleware", "srv", "wasi:http/handler@0.3.0")
+            .add_host_import("middleware", "wasi:logging/log@0.1.0")
+            .add_export("wasi:http/handler@0.3.0", "middleware")
+            .build()
+            .unwrap();
+
+        let expected = simple_chain_graph();
+        let labels = |g: &CompositionGraph| {
+            g.nodes
+                .values()
+                .map(|n| {
+                    let imports: Vec<_> = n
+                        .imports
+                        .iter()
+                        .map(|c| (c.interface_name.clone(), c.is_host_import))
+                        .collect();
+                    (n.name.clone(), imports)
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(labels(&graph), labels(&expected));
+        assert_eq!(graph.nodes[&1].size, Some(1024));
+        assert_eq!(graph.nodes[&1].imports[0].source_instance, Some(0));
+        assert_eq!(graph.component_exports[0].source_instance, 1);
+        assert_eq!(
+            crate::get_chain_for(&graph, "wasi:http/handler@0.3.0"),
+            vec![1, 0]
+        );
+    }
+
+    #[test]
+    fn test_build_reports_every_issue() {
+        let Err(CvizError::Validation(GraphIssues(issues))) = CompositionGraphBuilder::new()
+            .add_node("srv")
+            .add_node("srv")
+            .add_connection("srv", "srv", "wasi:http/handler@0.3.0")
+            .add_connection("srv", "db", "wasi:keyvalue/store@0.1.0")
+            .add_export("wasi:keyvalue/store@0.1.0", "db")
+            .build()
+        else {
+            panic!("invalid composition was built");
+        };
+        assert_eq!(
+            issues,
+            vec![
+                GraphIssue::DuplicateInstanceName {
+                    name: "srv".to_string()
+                },
+                GraphIssue::UnknownInstanceName {
+                    name: "db".to_string()
+                },
+                GraphIssue::SelfImport {
+                    instance: 0,
+                    interface: "wasi:http/handler@0.3.0".to_string(),
+                },
+            ]
+        );
+    }
+}
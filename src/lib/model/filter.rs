@@ -0,0 +1,185 @@
+//! Trimming a graph down to the part a diagram should show.
+//!
+//! Filters on interface and instance names take shell-style globs (`*` matches any run of characters, `?`
+//! a single one) matched against the whole name, so `wasi:keyvalue/*`
+//! selects every keyvalue interface at any version.
+
+use super::{ComponentNode, CompositionGraph, InterfaceConnection};
+use std::collections::BTreeSet;
+
+/// Whether `pattern` matches all of `text`.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` and the text position it was tried at
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                // Let the last `*` swallow one more character
+                Some((sp, st)) => {
+                    p = sp + 1;
+                    t = st + 1;
+                    star = Some((sp, st + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Whether `name` passes include globs `only` (any may match; empty lets
+/// everything through) and exclude globs `exclude` (none may match).
+fn selected(name: &str, only: &[String], exclude: &[String]) -> bool {
+    (only.is_empty() || only.iter().any(|g| glob_match(g, name)))
+        && !exclude.iter().any(|g| glob_match(g, name))
+}
+
+impl CompositionGraph {
+    /// Keep only the imports and exports whose interface name is selected by
+    /// the `only` and `exclude` globs, then drop the instances the filter
+    /// left without any connection.  Instances that had none to begin with
+    /// are kept.
+    pub fn filter_interfaces(&mut self, only: &[String], exclude: &[String]) {
+        if only.is_empty() && exclude.is_empty() {
+            return;
+        }
+        let before = self.connected_instances();
+        for node in self.nodes.values_mut() {
+            node.imports
+                .retain(|c| selected(&c.interface_name, only, exclude));
+            // Filtered-out imports are hidden, not unwired
+            if let Some(declared) = &mut node.declared_imports {
+                declared.retain(|name| selected(name, only, exclude));
+            }
+        }
+        self.component_exports
+            .retain(|e| selected(&e.name, only, exclude));
+        let after = self.connected_instances();
+        self.nodes
+            .retain(|id, _| after.contains(id) || !before.contains(id));
+    }
+
+    /// Keep only the instances whose name is selected by the `only` and
+    /// `exclude` globs, matched against both the displayed label and the
+    /// instance name.  Connections to and exports of a removed instance are
+    /// removed with it.
+    pub fn filter_nodes(&mut self, only: &[String], exclude: &[String]) {
+        if only.is_empty() && exclude.is_empty() {
+            return;
+        }
+        let matches = |globs: &[String], node: &ComponentNode| {
+            globs.iter().any(|g| {
+                glob_match(g, node.display_label())
+                    || glob_match(g, node.name.trim_start_matches('$'))
+            })
+        };
+        self.nodes
+            .retain(|_, node| (only.is_empty() || matches(only, node)) && !matches(exclude, node));
+
+        let kept: BTreeSet<u32> = self.nodes.keys().copied().collect();
+        let dropped =
+            |c: &InterfaceConnection| c.source_instance.is_some_and(|id| !kept.contains(&id));
+        for node in self.nodes.values_mut() {
+            let hidden: Vec<String> = node
+                .imports
+                .iter()
+                .filter(|c| dropped(c))
+                .map(|c| c.interface_name.clone())
+                .collect();
+            node.imports.retain(|c| !dropped(c));
+            // Imports from a filtered-out instance are hidden, not unwired
+            if let Some(declared) = &mut node.declared_imports {
+                declared.retain(|name| !hidden.contains(name));
+            }
+        }
+        self.component_exports
+            .retain(|e| kept.contains(&e.source_instance));
+    }
+
+    /// Instances with at least one import, one importer or one export.
+    fn connected_instances(&self) -> BTreeSet<u32> {
+        self.nodes
+            .iter()
+            .filter(|(_, n)| !n.imports.is_empty())
+            .map(|(&id, _)| id)
+            .chain(
+                self.nodes
+                    .values()
+                    .flat_map(|n| n.imports.iter())
+                    .filter_map(|c| c.source_instance),
+            )
+            .chain(self.component_exports.iter().map(|e| e.source_instance))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("wasi:keyvalue/*", "wasi:keyvalue/store@0.2.0"));
+        assert!(glob_match("*log*", "wasi:logging/log@0.1.0"));
+        assert!(glob_match(
+            "wasi:http/handler@0.3.?",
+            "wasi:http/handler@0.3.0"
+        ));
+        assert!(glob_match("a*b*c", "aXXbYbc"));
+        assert!(!glob_match("wasi:http/*", "wasi:logging/log@0.1.0"));
+        assert!(!glob_match("wasi", "wasi:http/handler"));
+    }
+
+    #[test]
+    fn test_filter_interfaces_prunes_isolated() {
+        let mut graph = simple_chain_graph();
+        graph.add_node(7, orphan_node());
+        graph.add_node(8, ComponentNode::new("$idle".to_string(), 8, 8));
+        graph.filter_interfaces(&["wasi:http/*".to_string()], &[]);
+
+        // The orphan only imported logging; $idle never had a connection.
+        assert!(!graph.nodes.contains_key(&7));
+        assert!(graph.nodes.contains_key(&8));
+        assert_eq!(graph.nodes[&2].imports.len(), 1);
+        assert_eq!(graph.component_exports.len(), 1);
+    }
+
+    #[test]
+    fn test_exclude_interface() {
+        let mut graph = simple_chain_graph();
+        graph.filter_interfaces(&[], &["wasi:logging/*".to_string()]);
+        assert_eq!(graph.nodes.len(), 2);
+        assert!(graph.nodes[&2]
+            .imports
+            .iter()
+            .all(|c| c.interface_name.starts_with("wasi:http/")));
+    }
+
+    #[test]
+    fn test_filter_nodes() {
+        let mut graph = simple_chain_graph();
+        graph.add_node(7, orphan_node());
+        graph.filter_nodes(&[], &["srv".to_string(), "orph*".to_string()]);
+        assert_eq!(graph.nodes.keys().copied().collect::<Vec<_>>(), vec![2]);
+        // The handler $middleware took from $srv goes with it
+        assert_eq!(graph.nodes[&2].imports.len(), 1);
+        assert_eq!(graph.component_exports.len(), 1);
+
+        let mut graph = simple_chain_graph();
+        graph.filter_nodes(&["s*".to_string()], &[]);
+        assert_eq!(graph.nodes.keys().copied().collect::<Vec<_>>(), vec![1]);
+        assert!(graph.component_exports.is_empty());
+    }
+}
@@ -0,0 +1,170 @@
+//! Short labels for interface names.
+//!
+//! Diagrams label connections with [`short_interface_name`], the last path
+//! segment of the name: `handler` for `wasi:http/handler@0.3.0`.  Teams
+//! abbreviate differently, so a [`LabelStyle`] can keep the namespace,
+//! package or version, or rewrite names with regular expressions.
+
+use super::short_interface_name;
+use regex_automata::meta::Regex;
+use serde::Deserialize;
+use std::str::FromStr;
+
+/// Which parts of an interface name a short label keeps.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LabelStyle {
+    /// Keep the namespace and package: `wasi:http/handler`.
+    pub keep_namespace: bool,
+    /// Keep the package: `http/handler`.
+    pub keep_package: bool,
+    /// Keep the version: `handler@0.3.0`.
+    pub keep_version: bool,
+    /// Rewrites tried in order before the rules above; the first whose
+    /// pattern matches the full name produces the label.
+    pub rewrites: Vec<Rewrite>,
+}
+
+impl LabelStyle {
+    /// Whether labels are plain [`short_interface_name`]s.
+    pub fn is_default(&self) -> bool {
+        !self.keep_namespace && !self.keep_package && !self.keep_version && self.rewrites.is_empty()
+    }
+
+    /// The label for the interface called `full_name`.
+    pub fn label(&self, full_name: &str) -> String {
+        if let Some(label) = self.rewrites.iter().find_map(|r| r.apply(full_name)) {
+            return label;
+        }
+
+        let mut label = short_interface_name(full_name);
+        let Some((package, rest)) = full_name.rsplit_once('/') else {
+            return label;
+        };
+        if self.keep_namespace {
+            label = format!("{}/{}", package, label);
+        } else if self.keep_package {
+            let package = package.split_once(':').map_or(package, |(_, p)| p);
+            label = format!("{}/{}", package, label);
+        }
+        if let Some((_, version)) = rest.split_once('@').filter(|_| self.keep_version) {
+            label = format!("{}@{}", label, version);
+        }
+        label
+    }
+}
+
+/// A regular expression whose first match in an interface name is replaced
+/// to form the label, e.g. `^acme:platform/` replaced with `p/`.
+/// Replacements may refer to capture groups as `$1` or `${name}`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "RewriteSpec")]
+pub struct Rewrite {
+    regex: Regex,
+    replacement: String,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RewriteSpec {
+    pattern: String,
+    replacement: String,
+}
+
+impl TryFrom<RewriteSpec> for Rewrite {
+    type Error = String;
+
+    fn try_from(spec: RewriteSpec) -> Result<Self, String> {
+        Rewrite::new(&spec.pattern, spec.replacement)
+    }
+}
+
+impl Rewrite {
+    pub fn new(pattern: &str, replacement: impl Into<String>) -> Result<Self, String> {
+        let regex = Regex::new(pattern)
+            .map_err(|e| format!("Invalid label pattern '{}': {}", pattern, e))?;
+        Ok(Self {
+            regex,
+            replacement: replacement.into(),
+        })
+    }
+
+    /// `full_name` with the first match replaced, or `None` if the pattern
+    /// does not match.
+    pub fn apply(&self, full_name: &str) -> Option<String> {
+        let mut caps = self.regex.create_captures();
+        self.regex.captures(full_name, &mut caps);
+        let m = caps.get_match()?;
+        Some(format!(
+            "{}{}{}",
+            &full_name[..m.start()],
+            caps.interpolate_string(full_name, &self.replacement),
+            &full_name[m.end()..]
+        ))
+    }
+}
+
+/// Parses `PATTERN=REPLACEMENT`, split at the last `=`.
+impl FromStr for Rewrite {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let (pattern, replacement) = s
+            .rsplit_once('=')
+            .ok_or_else(|| format!("Expected PATTERN=REPLACEMENT, got '{}'", s))?;
+        Rewrite::new(pattern, replacement)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HANDLER: &str = "wasi:http/handler@0.3.0";
+
+    #[test]
+    fn test_keep_parts() {
+        let mut style = LabelStyle::default();
+        assert!(style.is_default());
+        assert_eq!(style.label(HANDLER), "handler");
+
+        style.keep_version = true;
+        assert_eq!(style.label(HANDLER), "handler@0.3.0");
+        style.keep_package = true;
+        assert_eq!(style.label(HANDLER), "http/handler@0.3.0");
+        style.keep_namespace = true;
+        assert_eq!(style.label(HANDLER), "wasi:http/handler@0.3.0");
+
+        // Names without a package are left alone
+        assert_eq!(style.label("run"), "run");
+    }
+
+    #[test]
+    fn test_rewrites() {
+        let style = LabelStyle {
+            rewrites: vec![
+                "^wasi:(\\w+)/(?<name>[\\w-]+)@.*$=${name} ($1)"
+                    .parse()
+                    .unwrap(),
+                "^acme:=".parse().unwrap(),
+            ],
+            ..LabelStyle::default()
+        };
+        assert_eq!(style.label(HANDLER), "handler (http)");
+        assert_eq!(style.label("acme:auth/check"), "auth/check");
+        // No rewrite matches: the usual rules apply
+        assert_eq!(style.label("my:app/config"), "config");
+    }
+
+    #[test]
+    fn test_from_toml() {
+        let style: LabelStyle = toml::from_str(
+            "keep_package = true\n\n[[rewrites]]\npattern = \"^my:\"\nreplacement = \"\"\n",
+        )
+        .unwrap();
+        assert_eq!(style.label("my:app/config"), "app/config");
+        assert_eq!(style.label(HANDLER), "http/handler");
+
+        assert!("([=x".parse::<Rewrite>().is_err());
+    }
+}
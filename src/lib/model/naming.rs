@@ -0,0 +1,274 @@
+//! Labels for instances the name section leaves unnamed.
+//!
+//! Compositions built without a name section get instances called
+//! `instance_7`, which says nothing about what they do.  An ordered chain of
+//! [`NameResolver`]s proposes something better: the first proposal that no
+//! other instance is already displayed as becomes the instance's
+//! [`label`](ComponentNode::label), and the fallback name is kept when no
+//! resolver has a usable answer.  [`LabelOverrides`] then relabels any
+//! instance, named or not, that the user mapped explicitly.
+
+use super::{short_interface_name, ComponentNode, CompositionGraph};
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// One link in the resolver chain.
+pub trait NameResolver {
+    /// Propose a label for the unnamed instance `node` (graph ID `id`), or
+    /// `None` to defer to the next resolver.
+    fn resolve(&self, graph: &CompositionGraph, id: u32, node: &ComponentNode) -> Option<String>;
+}
+
+/// Labels from a user-supplied mapping.
+///
+/// Keys are matched against the instance's fallback name (`instance_7`),
+/// then its package with and without version (`my:auth@1.0.0`, `my:auth`).
+/// Deserializes from a JSON object such as `{ "instance_7": "auth" }`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct NameMap {
+    names: BTreeMap<String, String>,
+}
+
+impl NameMap {
+    pub fn insert(&mut self, key: impl Into<String>, label: impl Into<String>) {
+        self.names.insert(key.into(), label.into());
+    }
+}
+
+impl NameResolver for NameMap {
+    fn resolve(&self, _: &CompositionGraph, _: u32, node: &ComponentNode) -> Option<String> {
+        [
+            Some(node.name.trim_start_matches('$').to_string()),
+            node.package_id(),
+            node.package.clone(),
+        ]
+        .into_iter()
+        .flatten()
+        .find_map(|key| self.names.get(&key).cloned())
+    }
+}
+
+/// Display labels forced onto instances, whether the name section names
+/// them or not, for binaries whose embedded names are machine-generated.
+///
+/// Keys are matched against the instance name (`srv`, with or without the
+/// `$`), then its [stable ID](CompositionGraph::stable_ids), then the
+/// component's digest (`sha256:<hex>`, or just the hex).
+/// Deserializes from a TOML table such as `srv = "gateway"`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct LabelOverrides {
+    labels: BTreeMap<String, String>,
+}
+
+impl LabelOverrides {
+    pub fn insert(&mut self, key: impl Into<String>, label: impl Into<String>) {
+        self.labels.insert(key.into(), label.into());
+    }
+
+    /// The label the mapping gives `node`, whose stable ID is `stable_id`,
+    /// if any.
+    pub fn label_for(&self, node: &ComponentNode, stable_id: Option<&str>) -> Option<&str> {
+        let name = node.name.trim_start_matches('$');
+        let digest = node.digest.as_deref();
+        [
+            Some(name),
+            Some(node.name.as_str()),
+            stable_id,
+            digest,
+            digest.and_then(|d| d.strip_prefix("sha256:")),
+        ]
+        .into_iter()
+        .flatten()
+        .find_map(|key| self.labels.get(key))
+        .map(String::as_str)
+    }
+
+    /// Set the label of every instance in `graph` the mapping covers,
+    /// replacing any label a [`NameResolver`] chose.
+    pub fn apply(&self, graph: &mut CompositionGraph) {
+        let stable_ids = graph.stable_ids();
+        for (id, node) in graph.nodes.iter_mut() {
+            if let Some(label) = self.label_for(node, stable_ids.get(id).map(String::as_str)) {
+                node.label = Some(label.to_string());
+            }
+        }
+    }
+}
+
+/// The name the name section gives the instantiated component definition:
+/// `(component $auth ...)` becomes `auth`.  A package-style name is
+/// shortened like [`PackageName`] does.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ComponentName;
+
+impl NameResolver for ComponentName {
+    fn resolve(&self, _: &CompositionGraph, _: u32, node: &ComponentNode) -> Option<String> {
+        let name = node.component_name.as_deref()?;
+        let name = name.split('@').next().unwrap_or(name);
+        Some(name.rsplit(':').next().unwrap_or(name).to_string())
+    }
+}
+
+/// The name of the package the instantiated component was published as,
+/// without its namespace: `my:auth` becomes `auth`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PackageName;
+
+impl NameResolver for PackageName {
+    fn resolve(&self, _: &CompositionGraph, _: u32, node: &ComponentNode) -> Option<String> {
+        let package = node.package.as_deref()?;
+        Some(package.rsplit(':').next().unwrap_or(package).to_string())
+    }
+}
+
+/// The interface the instance provides: what the composition exports from
+/// it, or failing that what other instances import from it.
+/// `wasi:http/handler@0.3.0` becomes `handler`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProvidedInterface;
+
+impl NameResolver for ProvidedInterface {
+    fn resolve(&self, graph: &CompositionGraph, id: u32, _: &ComponentNode) -> Option<String> {
+        let exported = graph
+            .component_exports
+            .iter()
+            .find(|e| e.source_instance == id)
+            .map(|e| &e.name);
+        let imported = || {
+            graph
+                .nodes
+                .values()
+                .flat_map(|n| &n.imports)
+                .find(|c| !c.is_host_import && c.source_instance == Some(id))
+                .map(|c| c.source_export.as_ref().unwrap_or(&c.interface_name))
+        };
+        exported
+            .or_else(imported)
+            .map(|name| short_interface_name(name))
+    }
+}
+
+/// The chain applied when parsing: component name, package name, then
+/// provided interface.
+pub fn default_resolvers() -> Vec<Box<dyn NameResolver>> {
+    vec![
+        Box::new(ComponentName),
+        Box::new(PackageName),
+        Box::new(ProvidedInterface),
+    ]
+}
+
+/// Re-label every [unnamed](ComponentNode::is_unnamed) instance in `graph`
+/// using `resolvers` in order, replacing any earlier labels.
+pub fn resolve_labels(graph: &mut CompositionGraph, resolvers: &[Box<dyn NameResolver>]) {
+    let mut taken: BTreeSet<String> = graph
+        .nodes
+        .values()
+        .filter(|n| !n.is_unnamed())
+        .map(|n| n.display_label().to_string())
+        .collect();
+
+    let mut labels = Vec::new();
+    for (&id, node) in graph.nodes.iter().filter(|(_, n)| n.is_unnamed()) {
+        let label = resolvers
+            .iter()
+            .filter_map(|r| r.resolve(graph, id, node))
+            .find(|label| !label.is_empty() && !taken.contains(label));
+        if let Some(label) = &label {
+            taken.insert(label.clone());
+        }
+        labels.push((id, label));
+    }
+
+    for (id, label) in labels {
+        if let Some(node) = graph.nodes.get_mut(&id) {
+            node.label = label;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    /// `simple_chain_graph` with both instances stripped of their names.
+    fn unnamed_chain() -> CompositionGraph {
+        let mut graph = simple_chain_graph();
+        for (&id, node) in graph.nodes.iter_mut() {
+            node.name = ComponentNode::fallback_name(id);
+        }
+        graph
+    }
+
+    fn label(graph: &CompositionGraph, id: u32) -> &str {
+        graph.nodes[&id].display_label()
+    }
+
+    #[test]
+    fn test_default_chain() {
+        let mut graph = unnamed_chain();
+        graph.nodes.get_mut(&2).unwrap().package = Some("my:auth".to_string());
+        resolve_labels(&mut graph, &default_resolvers());
+
+        // node 1 has no package, but node 2 imports its handler
+        assert_eq!(label(&graph, 1), "handler");
+        assert_eq!(label(&graph, 2), "auth");
+    }
+
+    #[test]
+    fn test_component_name_first() {
+        let mut graph = unnamed_chain();
+        let node = graph.nodes.get_mut(&2).unwrap();
+        node.package = Some("my:auth".to_string());
+        node.component_name = Some("my:auth-impl@0.1.0".to_string());
+        resolve_labels(&mut graph, &default_resolvers());
+        assert_eq!(label(&graph, 2), "auth-impl");
+    }
+
+    #[test]
+    fn test_mapping_wins_and_labels_stay_unique() {
+        let mut graph = unnamed_chain();
+        let mut names = NameMap::default();
+        names.insert("instance_1", "frontend");
+        names.insert("instance_2", "frontend");
+        let mut resolvers: Vec<Box<dyn NameResolver>> = vec![Box::new(names)];
+        resolvers.extend(default_resolvers());
+        resolve_labels(&mut graph, &resolvers);
+
+        assert_eq!(label(&graph, 1), "frontend");
+        // "frontend" is taken, so the next resolver's answer is used
+        assert_eq!(label(&graph, 2), "handler");
+    }
+
+    #[test]
+    fn test_named_instances_untouched() {
+        let mut graph = simple_chain_graph();
+        graph.nodes.get_mut(&2).unwrap().package = Some("my:auth".to_string());
+        resolve_labels(&mut graph, &default_resolvers());
+        assert!(graph.nodes.values().all(|n| n.label.is_none()));
+        assert_eq!(label(&graph, 2), "middleware");
+    }
+
+    #[test]
+    fn test_label_overrides() {
+        let mut graph = simple_chain_graph();
+        graph.nodes.get_mut(&2).unwrap().digest = Some("sha256:abc123".to_string());
+        let overrides: LabelOverrides =
+            toml::from_str("srv = \"gateway\"\nabc123 = \"auth\"\n").unwrap();
+        overrides.apply(&mut graph);
+
+        // Named instances are relabelled too
+        assert_eq!(label(&graph, 1), "gateway");
+        assert_eq!(label(&graph, 2), "auth");
+
+        let mut graph = simple_chain_graph();
+        let stable = graph.stable_ids()[&1].clone();
+        let mut overrides = LabelOverrides::default();
+        overrides.insert(stable, "gateway");
+        overrides.apply(&mut graph);
+        assert_eq!(label(&graph, 1), "gateway");
+    }
+}
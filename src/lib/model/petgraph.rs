@@ -0,0 +1,143 @@
+//! Conversion to and from [`petgraph`] graphs (the `petgraph` feature).
+//!
+//! The model keeps instances in a `BTreeMap` and their wiring in each
+//! node's `imports`, which is convenient for rendering but means every
+//! analysis writes its own traversal.  [`CompositionGraph::to_petgraph`]
+//! hands the instance graph to petgraph instead, so its algorithms
+//! (dominators, strongly connected components, shortest paths, ...) can be
+//! run as-is.
+
+use super::{ComponentNode, CompositionGraph, InterfaceConnection};
+use petgraph::graph::{DiGraph, NodeIndex};
+use petgraph::visit::EdgeRef;
+use std::collections::BTreeMap;
+
+/// A node of an [`InstanceGraph`]: an instance and its graph ID.
+#[derive(Debug, Clone)]
+pub struct Instance {
+    /// The instance's key in [`CompositionGraph::nodes`].
+    pub id: u32,
+    /// The instance, holding only the imports that are not edges: those
+    /// from the host or from instances outside the graph.
+    pub node: ComponentNode,
+}
+
+/// The instances of a composition with one edge per import between them,
+/// pointing from the importing instance to the one providing the
+/// interface, i.e. in the direction calls are made.
+pub type InstanceGraph = DiGraph<Instance, InterfaceConnection>;
+
+impl CompositionGraph {
+    /// The instances and the connections between them as a petgraph graph.
+    ///
+    /// Nodes are added in graph-ID order and edges in import order.  Host
+    /// imports stay in the node weights; exports, the type arena, core
+    /// structure and metadata are not part of the result.
+    pub fn to_petgraph(&self) -> InstanceGraph {
+        let mut graph = InstanceGraph::new();
+        let indices: BTreeMap<u32, NodeIndex> = self
+            .nodes
+            .iter()
+            .map(|(&id, node)| {
+                let mut node = node.clone();
+                node.imports.retain(|c| !self.is_instance_edge(c));
+                (id, graph.add_node(Instance { id, node }))
+            })
+            .collect();
+
+        for (id, node) in &self.nodes {
+            for conn in node.imports.iter().filter(|c| self.is_instance_edge(c)) {
+                if let Some(source) = conn.source_instance {
+                    graph.add_edge(indices[id], indices[&source], conn.clone());
+                }
+            }
+        }
+        graph
+    }
+
+    /// The composition described by `graph`, as produced by
+    /// [`to_petgraph`](Self::to_petgraph) and possibly modified since.
+    ///
+    /// Each edge becomes an import of its source instance, after the
+    /// imports the node weight already holds.  The result has no exports
+    /// and an empty type arena; copy them over from the original graph if
+    /// the connections carry interface types.
+    pub fn from_petgraph(graph: &InstanceGraph) -> Self {
+        let mut nodes: BTreeMap<u32, ComponentNode> = graph
+            .node_weights()
+            .map(|instance| (instance.id, instance.node.clone()))
+            .collect();
+
+        for edge in graph.edge_references() {
+            let provider = graph[edge.target()].id;
+            let importer = graph[edge.source()].id;
+            if let Some(node) = nodes.get_mut(&importer) {
+                node.imports.push(InterfaceConnection {
+                    source_instance: Some(provider),
+                    is_host_import: false,
+                    ..edge.weight().clone()
+                });
+            }
+        }
+
+        let mut result = CompositionGraph::new();
+        result.nodes = nodes;
+        result
+    }
+
+    fn is_instance_edge(&self, conn: &InterfaceConnection) -> bool {
+        !conn.is_host_import
+            && conn
+                .source_instance
+                .is_some_and(|id| self.nodes.contains_key(&id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+    use petgraph::algo::{dominators, tarjan_scc};
+
+    #[test]
+    fn test_round_trip() {
+        let graph = simple_chain_graph();
+        let pg = graph.to_petgraph();
+        assert_eq!(pg.node_count(), 2);
+        assert_eq!(pg.edge_count(), 1);
+
+        let edge = pg.edge_references().next().unwrap();
+        assert_eq!(pg[edge.source()].id, 2);
+        assert_eq!(pg[edge.target()].id, 1);
+        assert_eq!(edge.weight().interface_name, "wasi:http/handler@0.3.0");
+        // The instance import became the edge; the host import stays
+        assert_eq!(pg[edge.source()].node.imports.len(), 1);
+
+        let back = CompositionGraph::from_petgraph(&pg);
+        let names = |g: &CompositionGraph, id: u32| -> Vec<(String, Option<u32>)> {
+            let mut imports: Vec<_> = g.nodes[&id]
+                .imports
+                .iter()
+                .map(|c| (c.interface_name.clone(), c.source_instance))
+                .collect();
+            imports.sort();
+            imports
+        };
+        for id in [1, 2] {
+            assert_eq!(names(&back, id), names(&graph, id));
+        }
+    }
+
+    #[test]
+    fn test_algorithms() {
+        let mut graph = simple_chain_graph();
+        graph.add_node(7, orphan_node());
+        let pg = graph.to_petgraph();
+
+        assert_eq!(tarjan_scc(&pg).len(), 3);
+        let root = pg.node_indices().find(|&i| pg[i].id == 2).unwrap();
+        let doms = dominators::simple_fast(&pg, root);
+        let srv = pg.node_indices().find(|&i| pg[i].id == 1).unwrap();
+        assert_eq!(doms.immediate_dominator(srv), Some(root));
+    }
+}
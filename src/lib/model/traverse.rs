@@ -0,0 +1,301 @@
+//! Walking the instance graph.
+//!
+//! Instances point at the instances they import from, so most questions
+//! about a composition ("what can this export reach?", "what sits behind
+//! this instance?", "who depends on it?") are walks along those imports or
+//! against them.  [`CompositionGraph::walk`] and its shorthands yield the
+//! instances reached, each once; [`CompositionGraph::visit`] drives a
+//! [`GraphVisitor`] that can prune the walk as it goes.
+
+use super::{ComponentNode, CompositionGraph, InterfaceConnection};
+use crate::is_connection_for;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+/// The order instances are reached in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    BreadthFirst,
+    /// Pre-order: an instance comes before everything reached through it.
+    DepthFirst,
+}
+
+/// Which way connections are followed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edges {
+    /// From an instance to the instances it imports from.
+    Imports,
+    /// From an instance to the instances importing from it.
+    Importers,
+}
+
+/// What a [`GraphVisitor`] wants done after seeing an instance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow {
+    /// Carry on through the instance.
+    Continue,
+    /// Do not follow the instance's connections, but carry on elsewhere.
+    SkipChildren,
+    /// End the walk.
+    Stop,
+}
+
+/// Callbacks for [`CompositionGraph::visit`].
+pub trait GraphVisitor {
+    /// Called once for every instance reached.
+    fn visit_node(&mut self, id: u32, node: &ComponentNode) -> Flow;
+
+    /// Called for every import, host imports included, of an instance whose
+    /// [`visit_node`](Self::visit_node) returned [`Flow::Continue`].
+    fn visit_import(&mut self, _importer: u32, _connection: &InterfaceConnection) {}
+}
+
+/// The instances reached from a starting instance, each yielded once with
+/// its graph ID, the start first.  Created by [`CompositionGraph::walk`].
+///
+/// An instance's neighbours are only looked up when the walk moves past it,
+/// so [`skip_children`](Self::skip_children) can prune the instance just
+/// yielded.
+pub struct Walk<'a> {
+    graph: &'a CompositionGraph,
+    order: Order,
+    edges: Edges,
+    interface: Option<&'a str>,
+    /// Importer lists, when walking against imports.
+    importers: BTreeMap<u32, Vec<u32>>,
+    pending: VecDeque<u32>,
+    seen: BTreeSet<u32>,
+    /// The instance last yielded, whose neighbours are still to be queued.
+    last: Option<u32>,
+}
+
+impl<'a> Walk<'a> {
+    /// Only follow connections for `interface` (matched as by
+    /// [`is_connection_for`]).
+    pub fn along(mut self, interface: &'a str) -> Self {
+        self.interface = Some(interface);
+        if self.edges == Edges::Importers {
+            self.importers = self.graph.importers(self.interface);
+        }
+        self
+    }
+
+    /// Do not follow the connections of the instance just yielded.
+    pub fn skip_children(&mut self) {
+        self.last = None;
+    }
+
+    fn neighbours(&self, id: u32) -> Vec<u32> {
+        match self.edges {
+            Edges::Imports => self.graph.providers(id, self.interface),
+            Edges::Importers => self.importers.get(&id).cloned().unwrap_or_default(),
+        }
+    }
+}
+
+impl<'a> Iterator for Walk<'a> {
+    type Item = (u32, &'a ComponentNode);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(last) = self.last.take() {
+            let neighbours = self.neighbours(last);
+            match self.order {
+                Order::BreadthFirst => self.pending.extend(neighbours),
+                Order::DepthFirst => self.pending.extend(neighbours.into_iter().rev()),
+            }
+        }
+        loop {
+            let id = match self.order {
+                Order::BreadthFirst => self.pending.pop_front(),
+                Order::DepthFirst => self.pending.pop_back(),
+            }?;
+            if !self.seen.insert(id) {
+                continue;
+            }
+            if let Some(node) = self.graph.get_node(id) {
+                self.last = Some(id);
+                return Some((id, node));
+            }
+        }
+    }
+}
+
+impl CompositionGraph {
+    /// Walk from instance `start` in `order`, following `edges`.  Imports
+    /// naming instances missing from the graph are not followed; nothing
+    /// is yielded when `start` itself is missing.
+    pub fn walk(&self, start: u32, order: Order, edges: Edges) -> Walk<'_> {
+        Walk {
+            graph: self,
+            order,
+            edges,
+            interface: None,
+            importers: match edges {
+                Edges::Imports => BTreeMap::new(),
+                Edges::Importers => self.importers(None),
+            },
+            pending: VecDeque::from([start]),
+            seen: BTreeSet::new(),
+            last: None,
+        }
+    }
+
+    /// `start` and every instance it transitively imports from,
+    /// breadth-first.
+    pub fn bfs(&self, start: u32) -> Walk<'_> {
+        self.walk(start, Order::BreadthFirst, Edges::Imports)
+    }
+
+    /// `start` and every instance it transitively imports from,
+    /// depth-first.
+    pub fn dfs(&self, start: u32) -> Walk<'_> {
+        self.walk(start, Order::DepthFirst, Edges::Imports)
+    }
+
+    /// Walk the imports from the instance behind export `name`, or `None`
+    /// if there is no such export.
+    pub fn walk_from_export(&self, name: &str, order: Order) -> Option<Walk<'_>> {
+        let export = self.export(name)?;
+        Some(self.walk(export.source_instance, order, Edges::Imports))
+    }
+
+    /// Instances `id` imports from, in import order, each once.  Host
+    /// imports, imports from itself and from missing instances are left out.
+    pub fn successors(&self, id: u32) -> Vec<u32> {
+        self.providers(id, None)
+    }
+
+    /// Instances importing from `id`, in graph order.
+    pub fn predecessors(&self, id: u32) -> Vec<u32> {
+        self.importers(None).remove(&id).unwrap_or_default()
+    }
+
+    /// Walk from `start` as [`walk`](Self::walk) does, handing each
+    /// instance and its imports to `visitor`.
+    pub fn visit(&self, start: u32, order: Order, edges: Edges, visitor: &mut impl GraphVisitor) {
+        let mut walk = self.walk(start, order, edges);
+        while let Some((id, node)) = walk.next() {
+            match visitor.visit_node(id, node) {
+                Flow::Continue => {
+                    for connection in &node.imports {
+                        visitor.visit_import(id, connection);
+                    }
+                }
+                Flow::SkipChildren => walk.skip_children(),
+                Flow::Stop => return,
+            }
+        }
+    }
+
+    /// The instance connections of `id` (those for `interface`, if given).
+    fn instance_edges<'a>(
+        &'a self,
+        id: u32,
+        interface: Option<&'a str>,
+    ) -> impl Iterator<Item = u32> + 'a {
+        self.get_node(id)
+            .into_iter()
+            .flat_map(|node| &node.imports)
+            .filter(move |c| !c.is_host_import)
+            .filter(move |c| interface.is_none_or(|i| is_connection_for(c, i)))
+            .filter_map(|c| c.source_instance)
+            .filter(move |&source| source != id && self.nodes.contains_key(&source))
+    }
+
+    fn providers(&self, id: u32, interface: Option<&str>) -> Vec<u32> {
+        let mut providers: Vec<u32> = Vec::new();
+        for source in self.instance_edges(id, interface) {
+            if !providers.contains(&source) {
+                providers.push(source);
+            }
+        }
+        providers
+    }
+
+    /// Provider to the instances importing from it, in graph order.
+    fn importers(&self, interface: Option<&str>) -> BTreeMap<u32, Vec<u32>> {
+        let mut importers: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
+        for &id in self.nodes.keys() {
+            for source in self.instance_edges(id, interface) {
+                let list = importers.entry(source).or_default();
+                if !list.contains(&id) {
+                    list.push(id);
+                }
+            }
+        }
+        importers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    fn ids(walk: Walk<'_>) -> Vec<u32> {
+        walk.map(|(id, _)| id).collect()
+    }
+
+    #[test]
+    fn test_walk_orders() {
+        // $app imports from $left and $right, both from $base
+        let graph = diamond_graph();
+        let id = |name| graph.find_node(name).unwrap();
+        let (top, left, right, bottom) = (id("app"), id("left"), id("right"), id("base"));
+
+        assert_eq!(ids(graph.bfs(top)), vec![top, left, right, bottom]);
+        assert_eq!(ids(graph.dfs(top)), vec![top, left, bottom, right]);
+        assert_eq!(
+            ids(graph.walk(bottom, Order::BreadthFirst, Edges::Importers)),
+            vec![bottom, left, right, top]
+        );
+        assert_eq!(graph.successors(top), vec![left, right]);
+        assert_eq!(graph.predecessors(bottom), vec![left, right]);
+        assert!(ids(graph.bfs(99)).is_empty());
+    }
+
+    #[test]
+    fn test_walk_along_interface() {
+        let graph = diamond_graph();
+        let app = graph.find_node("app").unwrap();
+        let left = graph.find_node("left").unwrap();
+        assert_eq!(ids(graph.bfs(app).along("test:side/left")), vec![app, left]);
+
+        let graph = long_chain_graph();
+        let export = "wasi:messaging/consumer@0.2.0";
+        let walk = graph.walk_from_export(export, Order::DepthFirst).unwrap();
+        assert_eq!(
+            ids(walk.along(export)),
+            crate::get_chain_for(&graph, export)
+        );
+        assert!(graph.walk_from_export("nope", Order::DepthFirst).is_none());
+    }
+
+    #[test]
+    fn test_visitor_can_prune() {
+        // Instances seen, imports seen, instance to stop at
+        struct Collect(Vec<u32>, usize, u32);
+        impl GraphVisitor for Collect {
+            fn visit_node(&mut self, id: u32, _: &ComponentNode) -> Flow {
+                self.0.push(id);
+                if id == self.2 {
+                    Flow::SkipChildren
+                } else {
+                    Flow::Continue
+                }
+            }
+            fn visit_import(&mut self, _: u32, connection: &InterfaceConnection) {
+                assert!(!connection.interface_name.is_empty());
+                self.1 += 1;
+            }
+        }
+
+        let graph = long_chain_graph();
+        let gateway = graph.find_node("gateway").unwrap();
+        let service = graph.find_node("service").unwrap();
+        let mut visitor = Collect(Vec::new(), 0, service);
+        graph.visit(gateway, Order::DepthFirst, Edges::Imports, &mut visitor);
+        // $backend is never reached and $service's imports are skipped
+        assert_eq!(visitor.0, vec![gateway, service]);
+        assert_eq!(visitor.1, graph.nodes[&gateway].imports.len());
+    }
+}
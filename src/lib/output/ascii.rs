@@ -1,19 +1,318 @@
-use crate::model::{short_interface_name, CompositionGraph};
-use crate::output::{build_all_interfaces_view, build_full_view, DetailLevel, SymbolMap};
-use crate::{find_chain_interfaces, get_chain_for};
+use crate::analysis::composer::composer;
+use crate::model::{
+    CompositionGraph, CoreInstanceKind, ModuleInterface, ModuleItem, SYNTHETIC_COMPONENT,
+};
+use crate::output::diagram::{
+    build_all_interfaces_view, build_dependency_view, build_full_view, drawn_chain,
+    ConnectionsView, SymbolMap,
+};
+use crate::output::links::{osc8, strip_osc, LinkTemplates};
+use crate::output::{human_size, DetailLevel, RenderOptions};
+use crate::{chain_interfaces_matching, exported_interfaces};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
 /// Generate an ASCII diagram from the composition graph
 pub fn generate_ascii(graph: &CompositionGraph, detail: DetailLevel, show_types: bool) -> String {
-    match detail {
-        DetailLevel::HandlerChain => generate_handler_chain_ascii(graph, show_types),
-        DetailLevel::AllInterfaces => generate_all_interfaces_ascii(graph, show_types),
-        DetailLevel::Full => generate_full_ascii(graph, show_types),
+    generate_ascii_with_options(graph, detail, &RenderOptions::new(show_types))
+}
+
+/// Generate an ASCII diagram from the composition graph using the given
+/// [`RenderOptions`].
+pub fn generate_ascii_with_options(
+    graph: &CompositionGraph,
+    detail: DetailLevel,
+    opts: &RenderOptions,
+) -> String {
+    let link = Linker::new(opts.links.as_ref(), graph);
+    let mut output = match detail {
+        DetailLevel::HandlerChain => generate_handler_chain_ascii(graph, opts, &link),
+        DetailLevel::Chains => generate_chains_ascii(graph, opts, &link),
+        DetailLevel::AllInterfaces => generate_all_interfaces_ascii(graph, opts, &link),
+        DetailLevel::Full => generate_full_ascii(graph, opts, &link),
+    };
+    if let Some(composer) = composer(graph) {
+        output.insert_str(0, &format!("Composed by {}\n", composer.summary()));
+    }
+
+    append_legend(&mut output, &link, opts);
+    output
+}
+
+/// Generate an ASCII tree of everything instance `root` needs, directly or
+/// transitively, starting from `root` rather than from the exports.
+///
+/// Instances reached more than once (shared dependencies or cycles) are
+/// expanded only the first time and marked `(see above)` afterwards.
+pub fn generate_dependency_ascii(
+    graph: &CompositionGraph,
+    root: u32,
+    opts: &RenderOptions,
+) -> String {
+    let link = Linker::new(opts.links.as_ref(), graph);
+    let mut view = build_dependency_view(graph, root, opts.show_types);
+    if !opts.labels.is_default() {
+        view.relabel(&opts.labels);
+    }
+    if opts.hide_host {
+        view.drop_host_imports();
+    }
+
+    let Some(root_node) = view.nodes.first() else {
+        return box_content("Dependencies", &["Instance not found"]);
+    };
+
+    let mut tree = DependencyTree {
+        view: &view,
+        link: &link,
+        show_types: opts.show_types,
+        symbols: SymbolMap::new(),
+        expanded: HashSet::from([root_node.name.as_str()]),
+        lines: vec![format!("  [{}]", link.node(&root_node.display))],
+    };
+    tree.children(&root_node.name, "  ");
+
+    let DependencyTree {
+        mut lines, symbols, ..
+    } = tree;
+    if lines.len() == 1 {
+        lines.push("  (no dependencies)".to_string());
+    }
+    if !symbols.is_empty() {
+        lines.push(String::new());
+        lines.extend(symbols.key_lines().into_iter().map(|l| format!("  {}", l)));
+    }
+
+    let mut output = box_content(&format!("Dependencies of {}", root_node.display), &lines);
+    append_legend(&mut output, &link, opts);
+    output
+}
+
+/// Line-by-line writer for [`generate_dependency_ascii`].
+struct DependencyTree<'a> {
+    view: &'a ConnectionsView,
+    link: &'a Linker<'a>,
+    show_types: bool,
+    symbols: SymbolMap,
+    /// Instances whose dependencies have already been written.
+    expanded: HashSet<&'a str>,
+    lines: Vec<String>,
+}
+
+impl<'a> DependencyTree<'a> {
+    /// Write the dependencies of the instance named `name`, each line
+    /// starting with `prefix`.
+    fn children(&mut self, name: &str, prefix: &str) {
+        let edges: Vec<_> = self
+            .view
+            .edges
+            .iter()
+            .filter(|e| e.to_name == name)
+            .collect();
+        let missing: Vec<_> = self
+            .view
+            .missing
+            .iter()
+            .filter(|m| m.to_name == name)
+            .collect();
+        let count = edges.len() + missing.len();
+
+        for (i, edge) in edges.iter().enumerate() {
+            let last = i + 1 == count;
+            let sym = self.symbols.assign(
+                self.show_types,
+                edge.fingerprint.as_deref(),
+                edge.type_lines.clone(),
+            );
+            let label = format!(
+                "{}{}",
+                self.link.iface(&edge.interface_name, &edge.label),
+                sym
+            );
+            let branch = if last { "└── " } else { "├── " };
+
+            if edge.is_dashed {
+                self.lines
+                    .push(format!("{}{}{} from {{host}}", prefix, branch, label));
+                continue;
+            }
+
+            let target = format!("[{}]", self.link.node(&edge.from_display));
+            if !self.expanded.insert(edge.from_name.as_str()) {
+                self.lines.push(format!(
+                    "{}{}{} from {} (see above)",
+                    prefix, branch, label, target
+                ));
+                continue;
+            }
+            self.lines
+                .push(format!("{}{}{} from {}", prefix, branch, label, target));
+            let child_prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+            self.children(&edge.from_name, &child_prefix);
+        }
+        for (i, m) in missing.iter().enumerate() {
+            let branch = if edges.len() + i + 1 == count {
+                "└── "
+            } else {
+                "├── "
+            };
+            self.lines.push(format!(
+                "{}{}{} from (missing)",
+                prefix,
+                branch,
+                self.link.iface(&m.interface_name, &m.label)
+            ));
+        }
     }
 }
 
-/// Generate ASCII diagram showing all middleware chains (request flow direction)
-fn generate_handler_chain_ascii(graph: &CompositionGraph, show_types: bool) -> String {
-    let chain_interfaces = find_chain_interfaces(graph);
+/// Generate the ASCII view of a plain core module: one box per module name
+/// it imports from, then its exports.
+pub fn generate_module_ascii(module: &ModuleInterface) -> String {
+    let lines = |items: &[ModuleItem]| -> Vec<String> {
+        let rows: Vec<[String; 3]> = items
+            .iter()
+            .map(|item| {
+                [
+                    item.name.clone(),
+                    item.kind.clone(),
+                    item.signature.clone().unwrap_or_default(),
+                ]
+            })
+            .collect();
+        format_table(&["NAME", "KIND", "SIGNATURE"], &rows)
+            .into_iter()
+            .map(|l| format!("  {}", l.trim_end()))
+            .collect()
+    };
+
+    let mut boxes: Vec<String> = module
+        .imports
+        .iter()
+        .map(|(name, imports)| box_content(&format!("Imports from {}", name), &lines(imports)))
+        .collect();
+    boxes.push(if module.exports.is_empty() {
+        box_content("Exports", &["  (none)"])
+    } else {
+        box_content("Exports", &lines(&module.exports))
+    });
+    boxes.join("\n")
+}
+
+/// Append the abbreviation legend to `output` when `opts.legend` is set.
+fn append_legend(output: &mut String, link: &Linker, opts: &RenderOptions) {
+    if opts.legend {
+        if let Some(legend) = legend_box(link) {
+            output.push('\n');
+            output.push_str(&legend);
+        }
+    }
+}
+
+/// Applies the optional OSC 8 hyperlink templates to diagram labels, and
+/// records every abbreviated interface label for the legend.
+struct Linker<'a> {
+    templates: Option<&'a LinkTemplates>,
+    /// Instance display label → `package@version`, for node templates.
+    packages: HashMap<&'a str, String>,
+    /// Short label → full interface names it was used for.
+    abbreviations: RefCell<BTreeMap<String, BTreeSet<String>>>,
+}
+
+impl<'a> Linker<'a> {
+    fn new(templates: Option<&'a LinkTemplates>, graph: &'a CompositionGraph) -> Self {
+        let packages = graph
+            .nodes
+            .values()
+            .filter_map(|n| Some((n.display_label(), n.package_id()?)))
+            .collect();
+        Self {
+            templates,
+            packages,
+            abbreviations: RefCell::default(),
+        }
+    }
+
+    /// Link an instance label using the node template.
+    fn node(&self, label: &str) -> String {
+        let package = self.packages.get(label).map(String::as_str);
+        match self.templates.and_then(|l| l.node_url(label, package)) {
+            Some(url) => osc8(&url, label),
+            None => label.to_string(),
+        }
+    }
+
+    /// Link `text` (a short or full rendering of `full_name`) using the
+    /// interface template.
+    fn iface(&self, full_name: &str, text: &str) -> String {
+        if text != full_name {
+            self.abbreviations
+                .borrow_mut()
+                .entry(text.to_string())
+                .or_default()
+                .insert(full_name.to_string());
+        }
+        match self.templates.and_then(|l| l.interface_url(full_name)) {
+            Some(url) => osc8(&url, text),
+            None => text.to_string(),
+        }
+    }
+}
+
+/// One piece of a rendered handler chain, laid out left to right.
+enum ChainSegment {
+    /// An arrow carrying the (already linked and symbol-suffixed) interface label.
+    Arrow(String),
+    /// A component instance box.
+    Node(String),
+    /// The terminal box for a chain that ends in a host import.
+    Host,
+}
+
+/// Render a chain as three lines: box tops, box bodies joined by labelled
+/// arrows, and box bottoms.
+fn render_chain(segments: &[ChainSegment]) -> [String; 3] {
+    let mut rows: [String; 3] = Default::default();
+    for (i, seg) in segments.iter().enumerate() {
+        if i > 0 {
+            rows.iter_mut().for_each(|r| r.push(' '));
+        }
+        match seg {
+            ChainSegment::Arrow(label) => {
+                let arrow = format!("──── {} ───▶", label);
+                let width = display_width(&arrow);
+                rows[0].push_str(&" ".repeat(width));
+                rows[1].push_str(&arrow);
+                rows[2].push_str(&" ".repeat(width));
+            }
+            ChainSegment::Node(label) => {
+                let bar = "─".repeat(display_width(label) + 2);
+                rows[0].push_str(&format!("┌{}┐", bar));
+                rows[1].push_str(&format!("│ {} │", label));
+                rows[2].push_str(&format!("└{}┘", bar));
+            }
+            ChainSegment::Host => {
+                rows[0].push_str("╔══════╗");
+                rows[1].push_str("║ host ║");
+                rows[2].push_str("╚══════╝");
+            }
+        }
+    }
+    rows
+}
+
+/// Generate ASCII diagram showing all middleware chains (request flow direction).
+///
+/// Each chain is drawn on one row: the export enters as an arrow on the left,
+/// every hop is a box joined to the next by an arrow labelled with the
+/// interface, and a chain whose last component imports the interface from the
+/// host ends in a double-lined `host` box.
+fn generate_handler_chain_ascii(
+    graph: &CompositionGraph,
+    opts: &RenderOptions,
+    link: &Linker,
+) -> String {
+    let chain_interfaces = chain_interfaces_matching(graph, opts.interface.as_deref());
 
     if chain_interfaces.is_empty() {
         return box_content("Service Chains", &["No service chains found"]);
@@ -23,82 +322,156 @@ fn generate_handler_chain_ascii(graph: &CompositionGraph, show_types: bool) -> S
     let mut lines = Vec::new();
 
     for (i, iface) in chain_interfaces.iter().enumerate() {
-        let chain = get_chain_for(graph, iface);
-        if chain.is_empty() {
+        let Some(segments) = chain_segments(graph, iface, opts, &mut symbols, link) else {
             continue;
-        }
+        };
 
         // Separator between chains
         if i > 0 {
             lines.push(String::new());
         }
 
-        let short = short_interface_name(iface);
+        lines.extend(
+            render_chain(&segments)
+                .into_iter()
+                .map(|l| format!("  {}", l)),
+        );
+    }
 
-        let export_sym: String = show_types
-            .then(|| {
-                graph
-                    .component_exports
-                    .get(iface.as_str())
-                    .and_then(|info| symbols.symbol_for_export(info, &graph.arena))
-                    .map(str::to_string)
-            })
-            .flatten()
-            .unwrap_or_default();
+    // Key — shared across all chains
+    if !symbols.is_empty() {
+        lines.push(String::new());
+        lines.extend(symbols.key_lines().into_iter().map(|l| format!("  {}", l)));
+    }
 
-        // Export entry point
-        if let Some(&first_idx) = chain.first() {
-            if let Some(first_node) = graph.get_node(first_idx) {
-                lines.push(format!(
-                    "[Export: {}{}] ──> {}",
-                    short,
-                    export_sym,
-                    first_node.display_label()
-                ));
-            }
-        }
+    box_content("Service Chains", &lines)
+}
 
-        // Chain connections
-        for window in chain.windows(2) {
-            if let [from_idx, to_idx] = window {
-                if let (Some(from_node), Some(to_node)) =
-                    (graph.get_node(*from_idx), graph.get_node(*to_idx))
-                {
-                    let conn_sym: String = show_types
-                        .then(|| {
-                            from_node
-                                .imports
-                                .iter()
-                                .find(|c| &c.interface_name == iface)
-                                .and_then(|c| symbols.symbol_for_conn(c, &graph.arena))
-                                .map(str::to_string)
-                        })
-                        .flatten()
-                        .unwrap_or_default();
-                    lines.push(format!(
-                        "{} ── {}{} ──> {}",
-                        from_node.display_label(),
-                        short,
-                        conn_sym,
-                        to_node.display_label()
-                    ));
-                }
+/// The segments of the chain of `iface`, from its export down to the
+/// host, or `None` if it has no chain.
+fn chain_segments(
+    graph: &CompositionGraph,
+    iface: &str,
+    opts: &RenderOptions,
+    symbols: &mut SymbolMap,
+    link: &Linker,
+) -> Option<Vec<ChainSegment>> {
+    let chain = drawn_chain(graph, iface, opts);
+    if chain.is_empty() {
+        return None;
+    }
+
+    let show_types = opts.show_types;
+    let short = link.iface(iface, &opts.labels.label(iface));
+
+    let export_sym: String = show_types
+        .then(|| {
+            graph
+                .export(iface)
+                .and_then(|info| symbols.symbol_for_export(info, &graph.arena))
+                .map(str::to_string)
+        })
+        .flatten()
+        .unwrap_or_default();
+
+    // Export entry arrow
+    let mut segments = vec![ChainSegment::Arrow(format!("{}{}", short, export_sym))];
+
+    for (pos, idx) in chain.iter().enumerate() {
+        let Some(node) = graph.get_node(*idx) else {
+            continue;
+        };
+        let is_synthetic = node.component_index == SYNTHETIC_COMPONENT;
+        let label = link.node(node.display_label());
+        segments.push(ChainSegment::Node(if is_synthetic {
+            format!("{} (synthetic)", label)
+        } else {
+            label
+        }));
+
+        // The hop to the next box (or to the host, for the last box) is
+        // labelled with this node's import of the chain interface.
+        let is_last = pos + 1 == chain.len();
+        let Some(conn) = node
+            .imports
+            .iter()
+            .find(|c| c.interface_name == iface && (!is_last || c.is_host_import))
+        else {
+            // A synthetic bundle imports the interface's items one by one
+            if is_synthetic && !is_last {
+                segments.push(ChainSegment::Arrow(short.clone()));
             }
+            continue;
+        };
+        let conn_sym: String = show_types
+            .then(|| symbols.symbol_for_conn(conn, &graph.arena))
+            .flatten()
+            .map(str::to_string)
+            .unwrap_or_default();
+        segments.push(ChainSegment::Arrow(format!("{}{}", short, conn_sym)));
+        if is_last {
+            segments.push(ChainSegment::Host);
         }
     }
+    Some(segments)
+}
 
-    // Key — shared across all chains
-    if !symbols.is_empty() {
-        lines.push(String::new());
-        lines.extend(symbols.key_lines());
+/// Generate one lane per exported instance interface, each in a box titled
+/// with the interface, so that every flow through the composition is shown
+/// separately.
+fn generate_chains_ascii(graph: &CompositionGraph, opts: &RenderOptions, link: &Linker) -> String {
+    let mut symbols = SymbolMap::new();
+    let mut lanes = Vec::new();
+
+    let mut interfaces = exported_interfaces(graph);
+    if let Some(filter) = &opts.interface {
+        interfaces.retain(|iface| iface.contains(filter.as_str()));
+    }
+    for iface in interfaces {
+        let Some(segments) = chain_segments(graph, &iface, opts, &mut symbols, link) else {
+            continue;
+        };
+        let lines: Vec<String> = render_chain(&segments)
+            .into_iter()
+            .map(|l| format!("  {}", l))
+            .collect();
+        lanes.push(box_content(&iface, &lines));
     }
 
-    box_content("Service Chains", &lines)
+    if lanes.is_empty() {
+        return box_content("Chains", &["No exported interfaces found"]);
+    }
+
+    let mut output = lanes.join("\n");
+    // Key — shared across all lanes
+    if !symbols.is_empty() {
+        let key: Vec<String> = symbols
+            .key_lines()
+            .into_iter()
+            .map(|l| format!("  {}", l))
+            .collect();
+        output.push('\n');
+        output.push_str(&box_content("Types", &key));
+    }
+    output
 }
 
 /// Generate ASCII diagram showing all interface connections
-fn generate_all_interfaces_ascii(graph: &CompositionGraph, show_types: bool) -> String {
-    let view = build_all_interfaces_view(graph, show_types);
+fn generate_all_interfaces_ascii(
+    graph: &CompositionGraph,
+    opts: &RenderOptions,
+    link: &Linker,
+) -> String {
+    let show_types = opts.show_types;
+    let mut view = build_all_interfaces_view(graph, show_types, opts.show_synthetic);
+    if !opts.labels.is_default() {
+        view.relabel(&opts.labels);
+    }
+    if opts.hide_host {
+        view.drop_host_imports();
+    } else if opts.group_host {
+        view.group_host_imports();
+    }
 
     if view.nodes.is_empty() {
         return box_content("Component Instances", &["No component instances found"]);
@@ -110,7 +483,10 @@ fn generate_all_interfaces_ascii(graph: &CompositionGraph, show_types: bool) ->
         let host_lines: Vec<String> = view
             .host_names
             .iter()
-            .map(|i| format!("  {{{}}}", short_interface_name(i)))
+            .map(|i| {
+                let label = opts.labels.label(i);
+                format!("  {{{}}}", link.iface(i, &label))
+            })
             .collect();
         output.push_str(&box_content("Host Imports", &host_lines));
         output.push('\n');
@@ -119,13 +495,31 @@ fn generate_all_interfaces_ascii(graph: &CompositionGraph, show_types: bool) ->
     let instance_lines: Vec<String> = view
         .nodes
         .iter()
-        .map(|n| format!("  [{}]", n.display))
+        .map(|n| {
+            let mut line = format!("  [{}]", link.node(&n.display));
+            if n.copies > 1 {
+                line.push_str(&format!(" x{}", n.copies));
+            }
+            if n.is_synthetic {
+                line.push_str(" (synthetic)");
+            }
+            if n.unused {
+                line.push_str(" (unused)");
+            }
+            if n.shared {
+                line.push_str(" (shared)");
+            }
+            if n.critical {
+                line.push_str(" (critical)");
+            }
+            line
+        })
         .collect();
     output.push_str(&box_content("Component Instances", &instance_lines));
     output.push('\n');
 
     let mut symbols = SymbolMap::new();
-    let mut connection_lines = Vec::new();
+    let mut rows = Vec::new();
 
     for edge in &view.edges {
         let sym = symbols.assign(
@@ -133,17 +527,24 @@ fn generate_all_interfaces_ascii(graph: &CompositionGraph, show_types: bool) ->
             edge.fingerprint.as_deref(),
             edge.type_lines.clone(),
         );
-        if edge.is_dashed {
-            connection_lines.push(format!(
-                "  {{{}}} --- {}{} --> [{}]",
-                edge.from_display, edge.label, sym, edge.to_display
-            ));
+        let source = if edge.is_dashed {
+            format!("{{{}}}", link.iface(&edge.from_name, &edge.from_display))
         } else {
-            connection_lines.push(format!(
-                "  [{}] ── {}{} ──> [{}]",
-                edge.from_display, edge.label, sym, edge.to_display
-            ));
-        }
+            format!("[{}]", link.node(&edge.from_display))
+        };
+        rows.push([
+            source,
+            format!("{}{}", link.iface(&edge.interface_name, &edge.label), sym),
+            format!("[{}]", link.node(&edge.to_display)),
+        ]);
+    }
+
+    for m in &view.missing {
+        rows.push([
+            "(missing)".to_string(),
+            link.iface(&m.interface_name, &m.label),
+            format!("[{}]", link.node(&m.to_display)),
+        ]);
     }
 
     for exp in &view.exports {
@@ -152,10 +553,19 @@ fn generate_all_interfaces_ascii(graph: &CompositionGraph, show_types: bool) ->
             exp.fingerprint.as_deref(),
             exp.type_lines.clone(),
         );
-        connection_lines.push(format!(
-            "  [{}] ──> (Export: {}{})",
-            exp.from_display, exp.short_name, sym
-        ));
+        rows.push([
+            format!("[{}]", link.node(&exp.from_display)),
+            format!("{}{}", link.iface(&exp.full_name, &exp.short_name), sym),
+            "(Export)".to_string(),
+        ]);
+    }
+
+    let mut connection_lines = Vec::new();
+    if !rows.is_empty() {
+        connection_lines = format_table(&["SOURCE", "INTERFACE", "TARGET"], &rows)
+            .into_iter()
+            .map(|l| format!("  {}", l))
+            .collect();
     }
 
     if !symbols.is_empty() {
@@ -171,7 +581,8 @@ fn generate_all_interfaces_ascii(graph: &CompositionGraph, show_types: bool) ->
 }
 
 /// Generate a full ASCII diagram with all details
-fn generate_full_ascii(graph: &CompositionGraph, show_types: bool) -> String {
+fn generate_full_ascii(graph: &CompositionGraph, opts: &RenderOptions, link: &Linker) -> String {
+    let show_types = opts.show_types;
     let view = build_full_view(graph, show_types);
 
     let mut instance_lines: Vec<String> = view
@@ -179,9 +590,38 @@ fn generate_full_ascii(graph: &CompositionGraph, show_types: bool) -> String {
         .iter()
         .map(|n| {
             if n.is_synthetic {
-                format!("  [{}] (synthetic)", n.display)
+                format!("  [{}] (synthetic)", link.node(&n.display))
             } else {
-                format!("  [{}] [comp:{}]", n.display, n.component_index)
+                let mut line = format!("  [{}]", link.node(&n.display));
+                if n.copies > 1 {
+                    line.push_str(&format!(" x{}", n.copies));
+                }
+                if let Some(size) = n.size {
+                    line.push_str(&format!(" ({})", human_size(size)));
+                }
+                line.push_str(&format!(" [comp:{}]", n.component_index));
+                if let Some(package) = &n.package {
+                    line.push_str(&format!(" {}", package));
+                }
+                if let Some(producers) = &n.produced_by {
+                    line.push_str(&format!(" ({})", producers));
+                }
+                if n.runs_start {
+                    line.push_str(" [start]");
+                }
+                if !n.features.is_empty() {
+                    line.push_str(&format!(" [needs: {}]", n.features.join(", ")));
+                }
+                if n.unused {
+                    line.push_str(" (unused)");
+                }
+                if n.shared {
+                    line.push_str(" (shared)");
+                }
+                if n.critical {
+                    line.push_str(" (critical)");
+                }
+                line
             }
         })
         .collect();
@@ -204,8 +644,20 @@ fn generate_full_ascii(graph: &CompositionGraph, show_types: bool) -> String {
             edge.type_lines.clone(),
         );
         connection_lines.push(format!(
-            "  [{}] ── {}{} ──> [{}]",
-            edge.from_display, edge.label, sym, edge.to_display
+            "  [{}]{} ── {}{} ──> [{}]",
+            link.node(&edge.from_display),
+            alias_hops(&edge.via),
+            link.iface(&edge.interface_name, &edge.label),
+            sym,
+            link.node(&edge.to_display)
+        ));
+    }
+
+    for m in &view.missing {
+        connection_lines.push(format!(
+            "  (missing) ── {} ──> [{}]",
+            link.iface(&m.interface_name, &m.label),
+            link.node(&m.to_display)
         ));
     }
 
@@ -216,8 +668,11 @@ fn generate_full_ascii(graph: &CompositionGraph, show_types: bool) -> String {
             exp.type_lines.clone(),
         );
         connection_lines.push(format!(
-            "  [{}] ──> (Export: {}{})",
-            exp.from_display, exp.full_name, sym
+            "  [{}]{} ──> (Export: {}{})",
+            link.node(&exp.from_display),
+            alias_hops(&exp.via),
+            link.iface(&exp.full_name, &exp.full_name),
+            sym
         ));
     }
 
@@ -230,14 +685,232 @@ fn generate_full_ascii(graph: &CompositionGraph, show_types: bool) -> String {
         output.push_str(&box_content("Connections", &connection_lines));
     }
 
+    let core_lines = core_structure_lines(graph, link);
+    if !core_lines.is_empty() {
+        output.push('\n');
+        output.push_str(&box_content("Core Modules", &core_lines));
+    }
+
+    let metadata = metadata_lines(graph, link);
+    if !metadata.is_empty() {
+        output.push('\n');
+        output.push_str(&box_content("Metadata", &metadata));
+    }
+
+    let digests = digest_lines(graph, link);
+    if !digests.is_empty() {
+        output.push('\n');
+        output.push_str(&box_content("Digests", &digests));
+    }
+
     output
 }
 
+/// ` ──> (alias: a) ──> (alias: b)` for the nested instances aliased along a
+/// connection; empty when there are none.
+fn alias_hops(via: &[String]) -> String {
+    via.iter()
+        .map(|hop| format!(" ──> (alias: {})", hop))
+        .collect()
+}
+
+/// `component N ([inst-a], [inst-b])`: a component definition and the
+/// instances created from it.
+fn component_heading(graph: &CompositionGraph, comp_num: u32, link: &Linker) -> String {
+    let users: Vec<String> = graph
+        .nodes
+        .values()
+        .filter(|n| n.component_num == comp_num)
+        .map(|n| format!("[{}]", link.node(n.display_label())))
+        .collect();
+    if users.is_empty() {
+        format!("component {}", comp_num)
+    } else {
+        format!("component {} ({})", comp_num, users.join(", "))
+    }
+}
+
+/// List the authorship and licensing metadata of the composition and of
+/// each component that carries any.
+fn metadata_lines(graph: &CompositionGraph, link: &Linker) -> Vec<String> {
+    let mut lines = Vec::new();
+    for (comp_num, metadata) in &graph.metadata {
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        match comp_num {
+            Some(num) => lines.push(format!("  {}", component_heading(graph, *num, link))),
+            None => lines.push("  composition".to_string()),
+        }
+        let summary = metadata.producer_summary();
+        let mut fields = metadata.fields();
+        if let Some(summary) = &summary {
+            fields.push(("producers", summary));
+        }
+        let width = fields.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+        for (name, value) in fields {
+            lines.push(format!("    {:<width$}  {}", name, value, width = width));
+        }
+    }
+    lines
+}
+
+/// `sha256:<hex>  component N ([inst])` for each component whose digest is
+/// known.
+fn digest_lines(graph: &CompositionGraph, link: &Linker) -> Vec<String> {
+    let digests: BTreeMap<u32, &str> = graph
+        .nodes
+        .values()
+        .filter_map(|n| Some((n.component_num, n.digest.as_deref()?)))
+        .collect();
+    digests
+        .into_iter()
+        .map(|(num, digest)| format!("  {}  {}", digest, component_heading(graph, num, link)))
+        .collect()
+}
+
+/// Describe the core modules embedded in each component and how its core
+/// instances instantiate and link them.
+fn core_structure_lines(graph: &CompositionGraph, link: &Linker) -> Vec<String> {
+    let mut lines = Vec::new();
+    for (comp_num, core) in &graph.core {
+        if core.modules.is_empty() && core.instances.is_empty() {
+            continue;
+        }
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+
+        lines.push(format!("  {}", component_heading(graph, *comp_num, link)));
+
+        for module in &core.modules {
+            lines.push(format!(
+                "    module {}: {}",
+                module.index,
+                core.module_label(module.index)
+            ));
+        }
+        for inst in &core.instances {
+            let label = match &inst.name {
+                Some(name) => format!("#{} {}", inst.index, name),
+                None => format!("#{}", inst.index),
+            };
+            match &inst.kind {
+                CoreInstanceKind::Instantiate { module_index, args } => {
+                    lines.push(format!(
+                        "    {} = instantiate {}",
+                        label,
+                        core.module_label(*module_index)
+                    ));
+                    // One line per import module, naming the providing
+                    // instance when it differs from the import name.
+                    for (i, (name, idx)) in args.iter().enumerate() {
+                        let branch = if i + 1 == args.len() { "└" } else { "├" };
+                        let source = core.instance_label(*idx);
+                        if &source == name {
+                            lines.push(format!("        {} {}", branch, name));
+                        } else {
+                            lines.push(format!("        {} {} ← {}", branch, name, source));
+                        }
+                    }
+                }
+                CoreInstanceKind::FromExports(names) => {
+                    let plural = if names.len() == 1 { "" } else { "s" };
+                    lines.push(format!("    {} = {} export{}", label, names.len(), plural));
+                }
+            }
+        }
+    }
+    lines
+}
+
+/// Build the footer box mapping each abbreviated interface label used in the
+/// diagram back to its full name, or `None` if nothing was abbreviated.
+fn legend_box(link: &Linker) -> Option<String> {
+    let abbreviations = link.abbreviations.borrow();
+    if abbreviations.is_empty() {
+        return None;
+    }
+
+    let width = abbreviations
+        .keys()
+        .map(|k| display_width(k))
+        .max()
+        .unwrap_or(0);
+    let mut lines = Vec::new();
+    for (short, full_names) in abbreviations.iter() {
+        for full in full_names {
+            // `short` may carry a hyperlink; re-link the full name the same way.
+            let full = match link.templates.and_then(|l| l.interface_url(full)) {
+                Some(url) => osc8(&url, full),
+                None => full.clone(),
+            };
+            lines.push(format!(
+                "  {}{}  {}",
+                short,
+                " ".repeat(width - display_width(short)),
+                full
+            ));
+        }
+    }
+    Some(box_content("Legend", &lines))
+}
+
 /// Calculate the display width of a string (number of terminal columns).
 /// Uses char count instead of byte length to handle multi-byte Unicode
 /// characters like box-drawing characters (─) which are 3 bytes but 1 column.
+/// OSC 8 hyperlink escape sequences occupy no columns and are ignored.
 fn display_width(s: &str) -> usize {
-    s.chars().count()
+    if s.contains('\x1b') {
+        strip_osc(s).chars().count()
+    } else {
+        s.chars().count()
+    }
+}
+
+/// Lay out `rows` as an aligned table below a `header` row.
+///
+/// Columns are separated by `" │ "` and padded to the widest cell (in
+/// display columns), with a `─┼─` rule under the header.  Trailing padding
+/// on the last column is omitted.
+pub(crate) fn format_table<const N: usize>(
+    header: &[&str; N],
+    rows: &[[String; N]],
+) -> Vec<String> {
+    let mut widths: [usize; N] = header.map(display_width);
+    for row in rows {
+        for (w, cell) in widths.iter_mut().zip(row) {
+            *w = (*w).max(display_width(cell));
+        }
+    }
+
+    let render_row = |cells: &[&str]| -> String {
+        let mut line = String::new();
+        for (i, (cell, w)) in cells.iter().zip(&widths).enumerate() {
+            if i > 0 {
+                line.push_str(" │ ");
+            }
+            line.push_str(cell);
+            if i + 1 < N {
+                line.push_str(&" ".repeat(w - display_width(cell)));
+            }
+        }
+        line
+    };
+
+    let mut lines = vec![render_row(header)];
+    lines.push(
+        widths
+            .iter()
+            .map(|w| "─".repeat(*w))
+            .collect::<Vec<_>>()
+            .join("─┼─"),
+    );
+    for row in rows {
+        let cells: Vec<&str> = row.iter().map(String::as_str).collect();
+        lines.push(render_row(&cells));
+    }
+    lines
 }
 
 /// Create a box around content with a title
@@ -287,8 +960,8 @@ fn box_content(title: &str, lines: &[impl AsRef<str>]) -> String {
 mod tests {
     use super::*;
     use crate::model::{
-        ComponentNode, FuncSignature, InstanceInterface, InterfaceConnection, InterfaceType,
-        ValueType,
+        labels::LabelStyle, ComponentNode, ExternKind, FuncSignature, InstanceInterface,
+        InterfaceConnection, InterfaceType, ValueType, WasmFeature,
     };
     use crate::test_utils::*;
     use std::collections::BTreeMap;
@@ -302,6 +975,19 @@ mod tests {
             .unwrap_or_else(|| panic!("'{}' not found in output:\n{}", needle, output))
     }
 
+    /// Return true if `output` has a Connections table row whose cells are
+    /// exactly `cells`, in order.
+    fn has_row(output: &str, cells: &[&str]) -> bool {
+        output.lines().any(|l| {
+            let row: Vec<&str> = l
+                .trim_matches(|c: char| c == '│' || c.is_whitespace())
+                .split('│')
+                .map(str::trim)
+                .collect();
+            row == cells
+        })
+    }
+
     /// Build a graph: host → $srv → $middleware → export(handler)
     fn test_graph() -> CompositionGraph {
         let mut graph = CompositionGraph::new();
@@ -313,6 +999,9 @@ mod tests {
             is_host_import: true,
             interface_type: None,
             fingerprint: None,
+            source_export: None,
+            kind: ExternKind::Instance,
+            alias_chain: Vec::new(),
         });
         graph.add_node(1, srv);
 
@@ -323,6 +1012,9 @@ mod tests {
             is_host_import: false,
             interface_type: None,
             fingerprint: None,
+            source_export: None,
+            kind: ExternKind::Instance,
+            alias_chain: Vec::new(),
         });
         mw.add_import(InterfaceConnection {
             interface_name: "wasi:logging/log@0.1.0".to_string(),
@@ -330,6 +1022,9 @@ mod tests {
             is_host_import: true,
             interface_type: None,
             fingerprint: None,
+            source_export: None,
+            kind: ExternKind::Instance,
+            alias_chain: Vec::new(),
         });
         graph.add_node(2, mw);
 
@@ -368,94 +1063,402 @@ mod tests {
             is_host_import: true,
             interface_type: Some(iface_type.clone()),
             fingerprint: Some(iface_type.fingerprint(&graph.arena)),
+            source_export: None,
+            kind: ExternKind::Instance,
+            alias_chain: Vec::new(),
         });
         graph.add_node(1, srv);
 
-        let mut mw = ComponentNode::new("$middleware".to_string(), 1, 1);
-        mw.add_import(InterfaceConnection {
-            interface_name: "wasi:http/handler@0.3.0".to_string(),
-            source_instance: Some(1),
-            is_host_import: false,
-            interface_type: Some(iface_type.clone()),
-            fingerprint: Some(iface_type.fingerprint(&graph.arena)),
-        });
-        graph.add_node(2, mw);
+        let mut mw = ComponentNode::new("$middleware".to_string(), 1, 1);
+        mw.add_import(InterfaceConnection {
+            interface_name: "wasi:http/handler@0.3.0".to_string(),
+            source_instance: Some(1),
+            is_host_import: false,
+            interface_type: Some(iface_type.clone()),
+            fingerprint: Some(iface_type.fingerprint(&graph.arena)),
+            source_export: None,
+            kind: ExternKind::Instance,
+            alias_chain: Vec::new(),
+        });
+        graph.add_node(2, mw);
+
+        graph.add_export("wasi:http/handler@0.3.0".to_string(), 2, Some(iface_type));
+        graph
+    }
+
+    #[test]
+    fn test_box_content() {
+        let result = box_content("Test", &["line 1", "line 2"]);
+        assert!(result.contains("Test"));
+        assert!(result.contains("line 1"));
+        assert!(result.contains("line 2"));
+        assert!(result.contains("┌"));
+        assert!(result.contains("└"));
+    }
+
+    #[test]
+    fn test_handler_chain_ascii() {
+        let graph = test_graph();
+        let output = generate_ascii(&graph, DetailLevel::HandlerChain, false);
+
+        assert!(output.contains("Service Chains"), "should have title");
+        assert!(output.contains("srv"), "should show srv node");
+        assert!(output.contains("middleware"), "should show middleware node");
+        assert!(output.contains("handler"), "should show handler label");
+        // Request flow order: export → middleware → srv → host
+        assert!(
+            output.contains("  ──── handler ───▶ │ middleware │"),
+            "should show export arrow entering the outermost handler, got:\n{}",
+            output
+        );
+        assert!(
+            output.contains("│ middleware │ ──── handler ───▶ │ srv │"),
+            "should show request flow from middleware to srv, got:\n{}",
+            output
+        );
+        assert!(
+            output.contains("│ srv │ ──── handler ───▶ ║ host ║"),
+            "should end at the host, got:\n{}",
+            output
+        );
+    }
+
+    #[test]
+    fn test_handler_chain_boxes_align() {
+        let graph = long_chain_graph();
+        let output = generate_ascii(&graph, DetailLevel::HandlerChain, false);
+        let top = line_pos(&output, "┌─────────┐");
+        let lines: Vec<&str> = output.lines().collect();
+        // Box corners sit directly above and below the box sides.
+        let col = |l: &str, c: char| -> Vec<usize> {
+            l.chars()
+                .enumerate()
+                .filter(|(_, ch)| *ch == c)
+                .map(|(i, _)| i)
+                .collect()
+        };
+        assert_eq!(col(lines[top], '┌'), col(lines[top + 2], '└'));
+        assert_eq!(col(lines[top], '┐'), col(lines[top + 2], '┘'));
+        let sides: Vec<usize> = col(lines[top + 1], '│')
+            .into_iter()
+            .filter(|&i| i > 0 && i + 1 < lines[top + 1].chars().count())
+            .collect();
+        let mut corners = col(lines[top], '┌');
+        corners.extend(col(lines[top], '┐'));
+        corners.sort();
+        assert_eq!(sides, corners, "got:\n{}", output);
+    }
+
+    #[test]
+    fn test_handler_chain_without_host_import() {
+        let mut graph = simple_chain_graph();
+        for node in graph.nodes.values_mut() {
+            node.imports.retain(|c| !c.is_host_import);
+        }
+        let output = generate_ascii(&graph, DetailLevel::HandlerChain, false);
+        assert!(output.contains("│ srv │"), "got:\n{}", output);
+        assert!(
+            !output.contains("host"),
+            "no host box without a host import, got:\n{}",
+            output
+        );
+    }
+
+    #[test]
+    fn test_all_interfaces_ascii() {
+        let graph = test_graph();
+        let output = generate_ascii(&graph, DetailLevel::AllInterfaces, false);
+
+        assert!(
+            output.contains("Host Imports"),
+            "should have host imports section"
+        );
+        assert!(output.contains("handler"), "should show handler interface");
+        assert!(output.contains("log"), "should show log interface");
+        assert!(
+            output.contains("Component Instances"),
+            "should list instances"
+        );
+        assert!(output.contains("srv"), "should show srv");
+        assert!(output.contains("middleware"), "should show middleware");
+        assert!(
+            output.contains("Connections"),
+            "should have connections section"
+        );
+        assert!(output.contains("Export"), "should show export");
+    }
+
+    #[test]
+    fn test_full_ascii() {
+        let graph = test_graph();
+        let output = generate_ascii(&graph, DetailLevel::Full, false);
+
+        assert!(
+            output.contains("All Instances"),
+            "should have instances section"
+        );
+        assert!(output.contains("srv"), "should show srv");
+        assert!(output.contains("middleware"), "should show middleware");
+        assert!(
+            output.contains("wasi:http/handler@0.3.0"),
+            "should show full interface name"
+        );
+        assert!(output.contains("Connections"), "should have connections");
+    }
+
+    #[test]
+    fn test_composer_header() {
+        let mut graph = test_graph();
+        let output = generate_ascii(&graph, DetailLevel::AllInterfaces, false);
+        assert!(!output.contains("Composed by"), "{}", output);
+
+        let mut metadata = crate::model::ComponentMetadata::default();
+        metadata.producers.insert(
+            "processed-by".to_string(),
+            BTreeMap::from([("wac-parser".to_string(), "0.9.0".to_string())]),
+        );
+        graph.metadata.insert(None, metadata);
+        let output = generate_ascii(&graph, DetailLevel::AllInterfaces, false);
+        assert!(
+            output.starts_with("Composed by wac-parser 0.9.0\n┌"),
+            "{}",
+            output
+        );
+    }
+
+    #[test]
+    fn test_handler_chain_for_one_interface() {
+        let graph = two_chain_graph();
+        let opts = RenderOptions {
+            interface: Some("wasi:keyvalue".to_string()),
+            ..RenderOptions::new(false)
+        };
+        let output = generate_ascii_with_options(&graph, DetailLevel::HandlerChain, &opts);
+
+        assert!(output.contains("store"), "{}", output);
+        assert!(!output.contains("handler"), "{}", output);
+    }
+
+    #[test]
+    fn test_unused_instances_marked() {
+        let mut graph = simple_chain_graph();
+        graph.add_node(42, orphan_node());
+
+        let output = generate_ascii(&graph, DetailLevel::AllInterfaces, false);
+        assert!(output.contains("[orphan] (unused)"), "{}", output);
+        assert!(!output.contains("[srv] (unused)"), "{}", output);
+
+        let output = generate_ascii(&graph, DetailLevel::Full, false);
+        assert!(output.contains("(unused)"), "{}", output);
+    }
+
+    #[test]
+    fn test_show_versions_ascii() {
+        let graph = simple_chain_graph();
+        let opts = RenderOptions {
+            labels: LabelStyle {
+                keep_version: true,
+                ..LabelStyle::default()
+            },
+            ..RenderOptions::new(false)
+        };
+        let output = generate_ascii_with_options(&graph, DetailLevel::HandlerChain, &opts);
+        assert!(output.contains("handler@0.3.0"), "{}", output);
+
+        let output = generate_ascii_with_options(&graph, DetailLevel::AllInterfaces, &opts);
+        assert!(output.contains("{log@0.1.0}"), "{}", output);
+        assert!(
+            output.contains("[srv]           │ handler@0.3.0"),
+            "{}",
+            output
+        );
+    }
 
-        graph.add_export("wasi:http/handler@0.3.0".to_string(), 2, Some(iface_type));
-        graph
+    #[test]
+    fn test_group_host_ascii() {
+        let graph = simple_chain_graph();
+        let opts = RenderOptions {
+            group_host: true,
+            ..RenderOptions::new(false)
+        };
+        let output = generate_ascii_with_options(&graph, DetailLevel::AllInterfaces, &opts);
+        assert!(output.contains("{wasi:logging}"), "{}", output);
+        assert!(!output.contains("{log}"), "{}", output);
     }
 
     #[test]
-    fn test_box_content() {
-        let result = box_content("Test", &["line 1", "line 2"]);
-        assert!(result.contains("Test"));
-        assert!(result.contains("line 1"));
-        assert!(result.contains("line 2"));
-        assert!(result.contains("┌"));
-        assert!(result.contains("└"));
+    fn test_hide_host_ascii() {
+        let graph = simple_chain_graph();
+        let opts = RenderOptions {
+            hide_host: true,
+            ..RenderOptions::new(false)
+        };
+        let output = generate_ascii_with_options(&graph, DetailLevel::AllInterfaces, &opts);
+        assert!(!output.contains("Host Imports"), "{}", output);
+        assert!(!output.contains("{log}"), "{}", output);
+        assert!(output.contains("[srv]"), "{}", output);
     }
 
     #[test]
-    fn test_handler_chain_ascii() {
-        let graph = test_graph();
-        let output = generate_ascii(&graph, DetailLevel::HandlerChain, false);
+    fn test_missing_imports_drawn() {
+        let mut graph = simple_chain_graph();
+        let node = graph.nodes.get_mut(&2).unwrap();
+        node.declared_imports = Some(
+            node.imports
+                .iter()
+                .map(|c| c.interface_name.clone())
+                .chain(["wasi:keyvalue/store@0.1.0".to_string()])
+                .collect(),
+        );
 
-        assert!(output.contains("Service Chains"), "should have title");
-        assert!(output.contains("srv"), "should show srv node");
-        assert!(output.contains("middleware"), "should show middleware node");
-        assert!(output.contains("handler"), "should show handler label");
-        assert!(output.contains("Export"), "should show export at start");
-        // Request flow order: export → middleware → srv
+        let output = generate_ascii(&graph, DetailLevel::AllInterfaces, false);
+        let row = output
+            .lines()
+            .find(|l| l.contains("(missing)"))
+            .unwrap_or_else(|| panic!("{}", output));
+        assert!(row.contains("store"), "{}", row);
+        assert!(row.contains("[middleware]"), "{}", row);
+
+        let output = generate_ascii(&graph, DetailLevel::Full, false);
         assert!(
-            output.contains("[Export: handler] ──> middleware"),
-            "should show export pointing to outermost handler"
+            output.contains("(missing) ── wasi:keyvalue/store@0.1.0 ──> [middleware]"),
+            "{}",
+            output
+        );
+
+        let output = generate_dependency_ascii(&graph, 2, &RenderOptions::new(false));
+        assert!(output.contains("└── store from (missing)"), "{}", output);
+    }
+
+    #[test]
+    fn test_duplicate_instances_annotated() {
+        let mut graph = simple_chain_graph();
+        for node in graph.nodes.values_mut() {
+            node.package = Some("my:mw".to_string());
+        }
+
+        let output = generate_ascii(&graph, DetailLevel::AllInterfaces, false);
+        assert!(output.contains("[srv] x2"), "{}", output);
+        assert!(output.contains("[middleware] x2"), "{}", output);
+
+        graph.collapse_duplicates();
+        let output = generate_ascii(&graph, DetailLevel::Full, false);
+        assert!(output.contains("[srv] x2 [comp:0]"), "{}", output);
+        assert!(!output.contains("middleware"), "{}", output);
+    }
+
+    #[test]
+    fn test_chains_ascii_lanes() {
+        let graph = two_chain_graph();
+        let output = generate_ascii(&graph, DetailLevel::Chains, false);
+
+        let titles: Vec<&str> = output
+            .lines()
+            .filter(|l| l.contains("wasi:"))
+            .map(|l| l.trim_matches(|c: char| c == '│' || c.is_whitespace()))
+            .collect();
+        assert_eq!(
+            titles,
+            vec!["wasi:http/handler@0.3.0", "wasi:keyvalue/store@0.1.0"],
+            "{}",
+            output
         );
         assert!(
-            output.contains("middleware ── handler ──> srv"),
-            "should show request flow from middleware to srv"
+            output.contains("│ cache │ ──── store ───▶ │ db │"),
+            "{}",
+            output
         );
+
+        // --interface narrows the lanes too
+        let opts = RenderOptions {
+            interface: Some("keyvalue".to_string()),
+            ..RenderOptions::new(false)
+        };
+        let output = generate_ascii_with_options(&graph, DetailLevel::Chains, &opts);
+        assert!(!output.contains("wasi:http/handler"), "{}", output);
     }
 
     #[test]
-    fn test_all_interfaces_ascii() {
-        let graph = test_graph();
-        let output = generate_ascii(&graph, DetailLevel::AllInterfaces, false);
+    fn test_full_ascii_component_size() {
+        let mut graph = test_graph();
+        graph.nodes.get_mut(&1).unwrap().size = Some(1_258_291);
+        let output = generate_ascii(&graph, DetailLevel::Full, false);
+
+        assert!(output.contains("[srv] (1.2 MiB) [comp:0]"), "{}", output);
+        assert!(output.contains("[middleware] [comp:1]"), "{}", output);
+    }
+
+    #[test]
+    fn test_full_ascii_features() {
+        let mut graph = test_graph();
+        graph.nodes.get_mut(&2).unwrap().features =
+            [WasmFeature::Threads, WasmFeature::RelaxedSimd].into();
+        let output = generate_ascii(&graph, DetailLevel::Full, false);
 
         assert!(
-            output.contains("Host Imports"),
-            "should have host imports section"
+            output.contains("[middleware] [comp:1] [needs: threads, relaxed-simd]"),
+            "{}",
+            output
         );
-        assert!(output.contains("handler"), "should show handler interface");
-        assert!(output.contains("log"), "should show log interface");
+    }
+
+    #[test]
+    fn test_full_ascii_start_logic() {
+        let mut graph = test_graph();
+        graph.nodes.get_mut(&2).unwrap().runs_start = true;
+        let output = generate_ascii(&graph, DetailLevel::Full, false);
+
         assert!(
-            output.contains("Component Instances"),
-            "should list instances"
+            output.contains("[middleware] [comp:1] [start]"),
+            "{}",
+            output
         );
-        assert!(output.contains("srv"), "should show srv");
-        assert!(output.contains("middleware"), "should show middleware");
+        assert!(!output.contains("[srv] [comp:0] [start]"), "{}", output);
+    }
+
+    #[test]
+    fn test_full_ascii_digests() {
+        let mut graph = test_graph();
+        let digest = format!("sha256:{}", "0f".repeat(32));
+        graph.nodes.get_mut(&1).unwrap().digest = Some(digest.clone());
+        let output = generate_ascii(&graph, DetailLevel::Full, false);
+
+        assert!(output.contains("Digests"), "{}", output);
         assert!(
-            output.contains("Connections"),
-            "should have connections section"
+            output.contains(&format!("  {}  component 0 ([srv])", digest)),
+            "{}",
+            output
         );
-        assert!(output.contains("Export"), "should show export");
+
+        // only the full view lists digests
+        let all = generate_ascii(&graph, DetailLevel::AllInterfaces, false);
+        assert!(!all.contains("Digests"));
     }
 
     #[test]
-    fn test_full_ascii() {
-        let graph = test_graph();
+    fn test_full_ascii_alias_chain() {
+        let mut graph = test_graph();
+        graph.nodes.get_mut(&2).unwrap().imports[0].alias_chain = vec!["nested".to_string()];
+        graph.component_exports[0].alias_chain = vec!["a".to_string(), "b".to_string()];
         let output = generate_ascii(&graph, DetailLevel::Full, false);
 
         assert!(
-            output.contains("All Instances"),
-            "should have instances section"
+            output
+                .contains("[srv] ──> (alias: nested) ── wasi:http/handler@0.3.0 ──> [middleware]"),
+            "{}",
+            output
         );
-        assert!(output.contains("srv"), "should show srv");
-        assert!(output.contains("middleware"), "should show middleware");
         assert!(
-            output.contains("wasi:http/handler@0.3.0"),
-            "should show full interface name"
+            output.contains(
+                "[middleware] ──> (alias: a) ──> (alias: b) ──> (Export: wasi:http/handler@0.3.0)"
+            ),
+            "{}",
+            output
         );
-        assert!(output.contains("Connections"), "should have connections");
+
+        // alias hops are only drawn in the full view
+        let all = generate_ascii(&graph, DetailLevel::AllInterfaces, false);
+        assert!(!all.contains("alias"), "{}", all);
     }
 
     #[test]
@@ -515,12 +1518,15 @@ mod tests {
     fn test_chain_request_flow_order() {
         let graph = simple_chain_graph();
         let output = generate_ascii(&graph, DetailLevel::HandlerChain, false);
-        // Export entry point must appear before the middleware→srv edge
-        let export_pos = line_pos(&output, "[Export: handler] ──>");
-        let edge_pos = line_pos(&output, "middleware ── handler ──> srv");
+        // Export entry arrow comes first, then middleware, then srv.
+        let row = output.lines().nth(line_pos(&output, "───▶")).unwrap();
+        let export_pos = row.find("──── handler").unwrap();
+        let mw_pos = row.find("middleware").unwrap();
+        let srv_pos = row.find("│ srv │").unwrap();
         assert!(
-            export_pos < edge_pos,
-            "export entry should precede chain edge"
+            export_pos < mw_pos && mw_pos < srv_pos,
+            "export entry should precede chain hops, got:\n{}",
+            output
         );
     }
 
@@ -528,9 +1534,14 @@ mod tests {
     fn test_long_chain_order() {
         let graph = long_chain_graph(); // messaging/consumer: gateway → service → backend
         let output = generate_ascii(&graph, DetailLevel::HandlerChain, false);
-        let export_pos = line_pos(&output, "[Export: consumer] ──>");
-        let first_edge_pos = line_pos(&output, "gateway ── consumer ──> service");
-        let second_edge_pos = line_pos(&output, "service ── consumer ──> backend");
+        let row = output.lines().nth(line_pos(&output, "───▶")).unwrap();
+        let export_pos = row.find("──── consumer").unwrap();
+        let first_edge_pos = row
+            .find("│ gateway │ ──── consumer ───▶ │ service │")
+            .unwrap();
+        let second_edge_pos = row
+            .find("│ service │ ──── consumer ───▶ │ backend │")
+            .unwrap();
         assert!(
             export_pos < first_edge_pos,
             "export should precede gateway→service"
@@ -550,11 +1561,11 @@ mod tests {
         let graph = two_chain_graph();
         let output = generate_ascii(&graph, DetailLevel::HandlerChain, false);
         assert!(
-            output.contains("[Export: handler]"),
+            output.contains("  ──── handler ───▶ │ mw-http │"),
             "should show http handler export"
         );
         assert!(
-            output.contains("[Export: store]"),
+            output.contains("  ──── store ───▶ │ cache │"),
             "should show keyvalue store export"
         );
     }
@@ -564,12 +1575,12 @@ mod tests {
         let graph = two_chain_graph();
         let output = generate_ascii(&graph, DetailLevel::HandlerChain, false);
         assert!(
-            output.contains("mw-http ── handler ──> srv-http"),
+            output.contains("│ mw-http │ ──── handler ───▶ │ srv-http │"),
             "should show http handler chain edge, got:\n{}",
             output
         );
         assert!(
-            output.contains("cache ── store ──> db"),
+            output.contains("│ cache │ ──── store ───▶ │ db │"),
             "should show keyvalue chain edge, got:\n{}",
             output
         );
@@ -607,11 +1618,11 @@ mod tests {
         assert!(output.contains("service"), "should show service node");
         assert!(output.contains("backend"), "should show backend node");
         assert!(
-            output.contains("gateway ── consumer ──> service"),
+            output.contains("│ gateway │ ──── consumer ───▶ │ service │"),
             "should show first hop"
         );
         assert!(
-            output.contains("service ── consumer ──> backend"),
+            output.contains("│ service │ ──── consumer ───▶ │ backend │"),
             "should show second hop"
         );
     }
@@ -636,13 +1647,13 @@ mod tests {
     fn test_handler_chain_types_symbol_in_export() {
         let graph = typed_chain_graph();
         let output = generate_ascii(&graph, DetailLevel::HandlerChain, true);
-        // The export label should have a symbol appended, making it longer than just "[Export: handler]"
+        // The export arrow label should have a symbol appended after "handler".
         let export_line = output
             .lines()
-            .find(|l| l.contains("[Export: handler"))
-            .unwrap_or_else(|| panic!("no export line found in:\n{}", output));
+            .find(|l| l.contains("│  ──── handler"))
+            .unwrap_or_else(|| panic!("no export arrow found in:\n{}", output));
         assert!(
-            export_line.len() > "[Export: handler]".len() + 5,
+            !export_line.contains("│  ──── handler ───▶"),
             "export label should include a type symbol, got: {}",
             export_line
         );
@@ -677,7 +1688,12 @@ mod tests {
         // The two key lines should have different symbols (first char differs)
         let symbols: Vec<&str> = key_lines
             .iter()
-            .map(|l| l.split_whitespace().next().unwrap_or(""))
+            .map(|l| {
+                l.trim_start_matches('│')
+                    .split_whitespace()
+                    .next()
+                    .unwrap_or("")
+            })
             .collect();
         assert_ne!(
             symbols[0], symbols[1],
@@ -708,11 +1724,11 @@ mod tests {
     fn test_all_interfaces_host_edge_format() {
         let graph = simple_chain_graph();
         let output = generate_ascii(&graph, DetailLevel::AllInterfaces, false);
-        // Host import edges use dashed style with braces for the interface name.
+        // Host import rows use braces for the source.
         // $srv imports handler directly from the host.
         assert!(
-            output.contains("{handler} --- handler --> [srv]"),
-            "host edge should use dashed format, got:\n{}",
+            has_row(&output, &["{handler}", "handler", "[srv]"]),
+            "host row should show the braced source, got:\n{}",
             output
         );
     }
@@ -722,8 +1738,8 @@ mod tests {
         let graph = simple_chain_graph();
         let output = generate_ascii(&graph, DetailLevel::AllInterfaces, false);
         assert!(
-            output.contains("[srv] ── handler ──> [middleware]"),
-            "component edge should use solid arrow format, got:\n{}",
+            has_row(&output, &["[srv]", "handler", "[middleware]"]),
+            "component row should show source and target, got:\n{}",
             output
         );
     }
@@ -733,25 +1749,52 @@ mod tests {
         let graph = simple_chain_graph();
         let output = generate_ascii(&graph, DetailLevel::AllInterfaces, false);
         assert!(
-            output.contains("[middleware] ──> (Export: handler)"),
-            "export line should use expected format, got:\n{}",
+            has_row(&output, &["[middleware]", "handler", "(Export)"]),
+            "export row should use expected format, got:\n{}",
+            output
+        );
+    }
+
+    #[test]
+    fn test_all_interfaces_table_header_aligned() {
+        let graph = two_chain_graph();
+        let output = generate_ascii(&graph, DetailLevel::AllInterfaces, false);
+        assert!(has_row(&output, &["SOURCE", "INTERFACE", "TARGET"]));
+        // Every row of the table places its column separators at the same offsets.
+        let seps: Vec<Vec<usize>> = output
+            .lines()
+            .filter(|l| l.contains(" │ ") || l.contains("─┼─"))
+            .map(|l| {
+                l.chars()
+                    .enumerate()
+                    .skip(1)
+                    .filter(|(_, c)| *c == '│' || *c == '┼')
+                    .map(|(i, _)| i)
+                    .filter(|&i| i + 1 < l.chars().count())
+                    .collect()
+            })
+            .collect();
+        assert!(seps.len() > 2, "expected table rows, got:\n{}", output);
+        assert!(
+            seps.windows(2).all(|w| w[0] == w[1]),
+            "column separators should line up, got:\n{}",
             output
         );
     }
 
     #[test]
     fn test_all_interfaces_type_lines_on_export() {
-        // Type lines should appear *below the export line*, not just somewhere in the output.
+        // Type lines should appear *below the export row*, not just somewhere in the output.
         let graph = typed_chain_graph();
         let output = generate_ascii(&graph, DetailLevel::AllInterfaces, true);
-        let export_pos = line_pos(&output, "──> (Export: handler");
+        let export_pos = line_pos(&output, "(Export)");
         let sig_after_export = output
             .lines()
             .skip(export_pos + 1)
             .any(|l| l.contains("`handle`: (u32) -> bool"));
         assert!(
             sig_after_export,
-            "type signature should appear after the export line, got:\n{}",
+            "type signature should appear after the export row, got:\n{}",
             output
         );
     }
@@ -767,20 +1810,20 @@ mod tests {
         assert!(output.contains("cache"), "should show cache");
         // Both chain edges
         assert!(
-            output.contains("[srv-http] ── handler ──> [mw-http]"),
+            has_row(&output, &["[srv-http]", "handler", "[mw-http]"]),
             "should show handler edge"
         );
         assert!(
-            output.contains("[db] ── store ──> [cache]"),
+            has_row(&output, &["[db]", "store", "[cache]"]),
             "should show store edge"
         );
         // Both exports
         assert!(
-            output.contains("(Export: handler)"),
+            has_row(&output, &["[mw-http]", "handler", "(Export)"]),
             "should show handler export"
         );
         assert!(
-            output.contains("(Export: store)"),
+            has_row(&output, &["[cache]", "store", "(Export)"]),
             "should show store export"
         );
     }
@@ -809,6 +1852,113 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_full_core_modules() {
+        use crate::model::{CoreInstance, CoreInstanceKind, CoreModule, CoreStructure};
+        let mut graph = simple_chain_graph();
+        graph.core.insert(
+            0,
+            CoreStructure {
+                modules: vec![
+                    CoreModule {
+                        index: 0,
+                        name: Some("main".to_string()),
+                    },
+                    CoreModule {
+                        index: 1,
+                        name: None,
+                    },
+                ],
+                instances: vec![
+                    CoreInstance {
+                        index: 0,
+                        name: Some("adapter".to_string()),
+                        kind: CoreInstanceKind::Instantiate {
+                            module_index: 1,
+                            args: vec![],
+                        },
+                    },
+                    CoreInstance {
+                        index: 1,
+                        name: None,
+                        kind: CoreInstanceKind::Instantiate {
+                            module_index: 0,
+                            args: vec![("wasi".to_string(), 0)],
+                        },
+                    },
+                ],
+            },
+        );
+
+        let output = generate_ascii(&graph, DetailLevel::Full, false);
+        assert!(output.contains("Core Modules"), "{}", output);
+        assert!(output.contains("component 0 ([srv])"), "{}", output);
+        assert!(output.contains("module 0: main"), "{}", output);
+        assert!(
+            output.contains("#0 adapter = instantiate module 1"),
+            "{}",
+            output
+        );
+        assert!(output.contains("#1 = instantiate main"), "{}", output);
+        assert!(output.contains("└ wasi ← adapter"), "{}", output);
+
+        // Only the full view shows the core layer.
+        let all = generate_ascii(&graph, DetailLevel::AllInterfaces, false);
+        assert!(!all.contains("Core Modules"));
+    }
+
+    #[test]
+    fn test_full_metadata() {
+        use crate::model::ComponentMetadata;
+        let mut graph = simple_chain_graph();
+        graph.metadata.insert(
+            None,
+            ComponentMetadata {
+                licenses: Some("Apache-2.0".to_string()),
+                ..Default::default()
+            },
+        );
+        graph.metadata.insert(
+            Some(1),
+            ComponentMetadata {
+                authors: Some("Jane Doe".to_string()),
+                licenses: Some("MIT".to_string()),
+                ..Default::default()
+            },
+        );
+
+        let output = generate_ascii(&graph, DetailLevel::Full, false);
+        assert!(output.contains("Metadata"), "{}", output);
+        assert!(output.contains("composition"), "{}", output);
+        assert!(output.contains("licenses  Apache-2.0"), "{}", output);
+        assert!(output.contains("component 1 ([middleware])"), "{}", output);
+        assert!(output.contains("authors   Jane Doe"), "{}", output);
+
+        // Producers annotate the instance and are listed with the metadata.
+        graph.metadata.get_mut(&Some(1)).unwrap().producers.insert(
+            "language".to_string(),
+            [("Rust".to_string(), String::new())].into(),
+        );
+        let output = generate_ascii(&graph, DetailLevel::Full, false);
+        assert!(
+            output.contains("[middleware] [comp:1] (Rust)"),
+            "{}",
+            output
+        );
+        assert!(output.contains("producers  Rust"), "{}", output);
+
+        graph.nodes.get_mut(&2).unwrap().package = Some("my:mw".to_string());
+        let output = generate_ascii(&graph, DetailLevel::Full, false);
+        assert!(
+            output.contains("[middleware] [comp:1] my:mw (Rust)"),
+            "{}",
+            output
+        );
+
+        let all = generate_ascii(&graph, DetailLevel::AllInterfaces, false);
+        assert!(!all.contains("Metadata"));
+    }
+
     #[test]
     fn test_handler_chain_no_key_when_types_disabled() {
         let graph = typed_chain_graph();
@@ -825,9 +1975,9 @@ mod tests {
     fn test_handler_chain_key_after_chain_content() {
         let graph = typed_chain_graph();
         let output = generate_ascii(&graph, DetailLevel::HandlerChain, true);
-        // When show_types=true the edge label gets a symbol appended (e.g. "──handler✦──> srv"),
+        // When show_types=true the edge label gets a symbol appended (e.g. "handler✦ ───▶"),
         // so search for the portion that is always present regardless of symbol.
-        let chain_edge_pos = line_pos(&output, "──> srv");
+        let chain_edge_pos = line_pos(&output, "───▶ │ srv │");
         let key_pos = line_pos(&output, "`handle`: (u32) -> bool");
         assert!(
             key_pos > chain_edge_pos,
@@ -835,6 +1985,197 @@ mod tests {
         );
     }
 
+    // -----------------------------------------------------------------------
+    // OSC 8 hyperlinks
+    // -----------------------------------------------------------------------
+
+    fn linked_opts() -> RenderOptions {
+        RenderOptions {
+            links: Some(LinkTemplates {
+                interface: Some("https://docs/{namespace}/{package}/{name}".to_string()),
+                node: Some("https://catalog/{name}".to_string()),
+            }),
+            ..RenderOptions::default()
+        }
+    }
+
+    #[test]
+    fn test_hyperlinks_wrap_labels() {
+        let graph = simple_chain_graph();
+        let output =
+            generate_ascii_with_options(&graph, DetailLevel::AllInterfaces, &linked_opts());
+        assert!(
+            output.contains(&osc8("https://docs/wasi/http/handler", "handler")),
+            "interface label should be hyperlinked, got:\n{}",
+            output
+        );
+        assert!(
+            output.contains(&osc8("https://catalog/middleware", "middleware")),
+            "instance label should be hyperlinked, got:\n{}",
+            output
+        );
+    }
+
+    #[test]
+    fn test_hyperlinks_preserve_box_alignment() {
+        let graph = simple_chain_graph();
+        for detail in [
+            DetailLevel::HandlerChain,
+            DetailLevel::AllInterfaces,
+            DetailLevel::Full,
+        ] {
+            let plain = generate_ascii(&graph, detail, false);
+            let linked = generate_ascii_with_options(&graph, detail, &linked_opts());
+            assert_ne!(plain, linked, "links should change the raw output");
+            assert_eq!(
+                plain,
+                strip_osc(&linked),
+                "stripping the escapes should yield the plain diagram"
+            );
+        }
+    }
+
+    #[test]
+    fn test_hyperlinks_use_node_package() {
+        let mut graph = simple_chain_graph();
+        let mw = graph.nodes.get_mut(&2).unwrap();
+        mw.package = Some("my:middleware".to_string());
+        mw.package_version = Some("0.2.0".to_string());
+        let opts = RenderOptions {
+            links: Some(LinkTemplates {
+                interface: None,
+                node: Some("https://registry/{package}@{version}".to_string()),
+            }),
+            ..RenderOptions::default()
+        };
+        let output = generate_ascii_with_options(&graph, DetailLevel::AllInterfaces, &opts);
+        assert!(
+            output.contains(&osc8(
+                "https://registry/my%3Amiddleware@0.2.0",
+                "middleware"
+            )),
+            "instance with a package should be hyperlinked, got:\n{}",
+            output
+        );
+        assert!(
+            !output.contains("https://registry/@"),
+            "instance without a package should be left unlinked, got:\n{}",
+            output
+        );
+    }
+
+    #[test]
+    fn test_no_hyperlinks_by_default() {
+        let graph = simple_chain_graph();
+        let output = generate_ascii(&graph, DetailLevel::AllInterfaces, false);
+        assert!(!output.contains('\x1b'), "no escapes without templates");
+    }
+
+    // -----------------------------------------------------------------------
+    // Legend
+    // -----------------------------------------------------------------------
+
+    fn legend_opts() -> RenderOptions {
+        RenderOptions {
+            legend: true,
+            ..RenderOptions::default()
+        }
+    }
+
+    #[test]
+    fn test_legend_maps_short_labels() {
+        let graph = simple_chain_graph();
+        let output =
+            generate_ascii_with_options(&graph, DetailLevel::AllInterfaces, &legend_opts());
+        let legend_pos = line_pos(&output, "Legend");
+        let rest: Vec<&str> = output.lines().skip(legend_pos).collect();
+        assert!(
+            rest.iter()
+                .any(|l| l.contains("handler") && l.contains("wasi:http/handler@0.3.0")),
+            "legend should map handler to its full name, got:\n{}",
+            output
+        );
+        assert!(
+            rest.iter().any(|l| l.contains("wasi:logging/log@0.1.0")),
+            "legend should cover host-only labels, got:\n{}",
+            output
+        );
+    }
+
+    #[test]
+    fn test_legend_off_by_default() {
+        let graph = simple_chain_graph();
+        let output = generate_ascii(&graph, DetailLevel::HandlerChain, false);
+        assert!(!output.contains("Legend"));
+    }
+
+    #[test]
+    fn test_legend_omitted_when_nothing_abbreviated() {
+        // The full view prints full interface names, so there is nothing to expand.
+        let graph = simple_chain_graph();
+        let output = generate_ascii_with_options(&graph, DetailLevel::Full, &legend_opts());
+        assert!(!output.contains("Legend"), "got:\n{}", output);
+    }
+
+    // -----------------------------------------------------------------------
+    // Dependency tree (--root)
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn test_dependency_tree_structure() {
+        let graph = simple_chain_graph();
+        let root = graph.find_node("middleware").unwrap();
+        let output = generate_dependency_ascii(&graph, root, &RenderOptions::default());
+        assert!(output.contains("Dependencies of middleware"), "{}", output);
+        let tree: Vec<&str> = output
+            .lines()
+            .skip(line_pos(&output, "[middleware]"))
+            .take(4)
+            .map(|l| l.trim_matches(|c: char| c == '│' || c.is_whitespace()))
+            .collect();
+        assert_eq!(
+            tree,
+            vec![
+                "[middleware]",
+                "├── handler from [srv]",
+                "└── handler from {host}",
+                "└── log from {host}",
+            ],
+            "got:\n{}",
+            output
+        );
+        assert!(
+            output.contains("│   └── handler from {host}"),
+            "srv's host import should be nested under srv, got:\n{}",
+            output
+        );
+    }
+
+    #[test]
+    fn test_dependency_tree_shared_dependency_expanded_once() {
+        let graph = diamond_graph();
+        let root = graph.find_node("app").unwrap();
+        let output = generate_dependency_ascii(&graph, root, &RenderOptions::default());
+        assert_eq!(output.matches("log from {host}").count(), 1, "{}", output);
+        assert!(
+            output.contains("store from [base] (see above)"),
+            "second path to base should not be expanded again, got:\n{}",
+            output
+        );
+    }
+
+    #[test]
+    fn test_dependency_tree_leaf() {
+        let mut graph = simple_chain_graph();
+        graph.add_node(9, ComponentNode::new("$leaf".to_string(), 9, 9));
+        let output = generate_dependency_ascii(&graph, 9, &RenderOptions::default());
+        assert!(output.contains("(no dependencies)"), "{}", output);
+        assert!(
+            !output.contains("middleware"),
+            "only the root's dependencies are shown"
+        );
+    }
+
     #[test]
     fn test_two_chains_blank_line_separator() {
         let graph = two_chain_graph();
@@ -850,4 +2191,37 @@ mod tests {
             output
         );
     }
+
+    #[test]
+    fn test_module_view() {
+        let mut module = ModuleInterface::default();
+        module.imports.insert(
+            "wasi_snapshot_preview1".to_string(),
+            vec![ModuleItem {
+                name: "proc_exit".to_string(),
+                kind: "func".to_string(),
+                signature: Some("(i32) -> ()".to_string()),
+            }],
+        );
+        let output = generate_module_ascii(&module);
+        assert!(
+            output.contains("Imports from wasi_snapshot_preview1"),
+            "{}",
+            output
+        );
+        assert!(has_row(&output, &["proc_exit", "func", "(i32) -> ()"]));
+        assert!(output.contains("(none)"), "no exports:\n{}", output);
+    }
+
+    #[test]
+    fn test_non_instance_connection_labelled_with_kind() {
+        let mut graph = simple_chain_graph();
+        graph.nodes.get_mut(&2).unwrap().imports[1].kind = ExternKind::Func;
+        let output = generate_ascii(&graph, DetailLevel::AllInterfaces, false);
+        assert!(
+            has_row(&output, &["{log}", "log (func)", "[middleware]"]),
+            "{}",
+            output
+        );
+    }
 }
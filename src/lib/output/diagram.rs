@@ -0,0 +1,1184 @@
+//! The views the ASCII and Mermaid renderers draw from: which instances,
+//! edges and exports a diagram shows at each detail level, independent of
+//! how it is drawn.
+
+use super::{host_group, RenderOptions};
+use crate::analysis::critical::critical_instances;
+use crate::analysis::{shared_instances, unsatisfied_imports, unused_instances, ImportProblem};
+use crate::get_chain_for;
+use crate::model::{
+    labels::LabelStyle, short_interface_name, ComponentNode, CompositionGraph, ExportInfo,
+    ExternKind, FuncSignature, InterfaceConnection, InterfaceType, InternedId, TypeArena,
+    SYNTHETIC_COMPONENT,
+};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Format a function signature as `(param-type, ...) -> result-type`.
+///
+/// Parameters are shown as `name: type` when their names are known, and
+/// async functions are prefixed with `async`.
+///
+/// Uses [`TypeArena::display_val`] so that large complex types (variants with
+/// many cases, records with many fields, etc.) are summarised rather than
+/// expanded in full.  Fingerprinting is unaffected — it always uses the
+/// lossless [`TypeArena::canonical_val`].
+pub(crate) fn format_func_sig(sig: &FuncSignature, arena: &TypeArena) -> String {
+    let named = sig.param_names.len() == sig.params.len();
+    let params: Vec<String> = sig
+        .params
+        .iter()
+        .enumerate()
+        .map(|(i, id)| match named {
+            true => format!("{}: {}", sig.param_names[i], arena.display_val(*id)),
+            false => arena.display_val(*id),
+        })
+        .collect();
+    let results: Vec<String> = sig
+        .results
+        .iter()
+        .map(|id| arena.display_val(*id))
+        .collect();
+    let result_str = match results.as_slice() {
+        [] => "()".to_string(),
+        [single] => single.clone(),
+        _ => format!("({})", results.join(", ")),
+    };
+    let prefix = if sig.is_async { "async " } else { "" };
+    format!("{}({}) -> {}", prefix, params.join(", "), result_str)
+}
+
+/// Label for an import or export: `name`, followed by the kind of item for
+/// anything but a whole instance, e.g. `log (func)`.
+fn kind_label(kind: ExternKind, name: String) -> String {
+    match kind {
+        ExternKind::Instance => name,
+        kind => format!("{} ({})", name, kind),
+    }
+}
+
+/// Return type lines for an [`InterfaceConnection`], or an empty vec when
+/// `show_types` is false or the connection carries no type information.
+pub(crate) fn connection_type_lines(
+    conn: &InterfaceConnection,
+    arena: &TypeArena,
+    show_types: bool,
+) -> Vec<String> {
+    if !show_types {
+        return vec![];
+    }
+    conn.interface_type
+        .as_ref()
+        .map(|t| format_interface_type_lines(t, arena))
+        .unwrap_or_default()
+}
+
+/// Return type lines for an [`ExportInfo`], or an empty vec when `show_types`
+/// is false or the export carries no interface type.
+pub(crate) fn export_type_lines(
+    export_info: &ExportInfo,
+    arena: &TypeArena,
+    show_types: bool,
+) -> Vec<String> {
+    if !show_types {
+        return vec![];
+    }
+    match export_info.ty {
+        Some(InternedId::Interface(id)) => {
+            format_interface_type_lines(arena.lookup_interface(id), arena)
+        }
+        _ => vec![],
+    }
+}
+
+/// Return one display line per exported function in the interface.
+///
+/// - `Instance` interfaces produce `"fn-name: (params) -> result"` per function.
+/// - `Func` interfaces produce a single `"(params) -> result"` line.
+pub(crate) fn format_interface_type_lines(iface: &InterfaceType, arena: &TypeArena) -> Vec<String> {
+    match iface {
+        InterfaceType::Func(sig) => vec![format_func_sig(sig, arena)],
+        InterfaceType::Instance(inst) => inst
+            .functions
+            .iter()
+            .map(|(name, sig)| format!("`{}`: {}", name, format_func_sig(sig, arena)))
+            .collect(),
+    }
+}
+
+const SYMBOL_POOL: &[char] = &[
+    '✦', '✧', '◆', '◇', '★', '☆', '●', '○', '▲', '△', '▼', '▽', '■', '□', '◉', '♦', '♠', '✱', '✴',
+    '❖',
+];
+
+/// Compute the symbol string for a given assignment index using base-N encoding
+/// over [`SYMBOL_POOL`].
+///
+/// - Indices `0..N` produce single-character identifiers (`"✦"`, `"✧"`, …).
+/// - Indices `N..N+N²` produce two-character identifiers (`"✦✦"`, `"✦✧"`, …).
+/// - Indices beyond that produce three-character identifiers, and so on.
+///
+/// This guarantees an unbounded, collision-free sequence of compact identifiers
+/// without ever reusing a symbol string.
+fn symbol_at(index: usize) -> String {
+    let n = SYMBOL_POOL.len();
+    // Find which "length tier" this index falls into and the offset within it.
+    // Tier 1 covers [0, n), tier 2 covers [n, n + n²), tier 3 covers [n + n², n + n² + n³), …
+    let mut tier_size = n;
+    let mut offset = index;
+    let mut len = 1;
+    while offset >= tier_size {
+        offset -= tier_size;
+        tier_size *= n;
+        len += 1;
+    }
+    // Decode `offset` as a base-N number of `len` digits (most-significant first).
+    let mut digits = vec![0usize; len];
+    let mut remainder = offset;
+    for d in digits.iter_mut().rev() {
+        *d = remainder % n;
+        remainder /= n;
+    }
+    digits.iter().map(|&d| SYMBOL_POOL[d]).collect()
+}
+
+/// Assigns a unique identifier to each distinct interface type encountered
+/// during rendering and collects a display key.
+///
+/// Types are distinguished by fingerprint, so structurally identical interfaces
+/// always receive the same symbol within one diagram.  Identifiers are drawn
+/// from [`SYMBOL_POOL`] via [`symbol_at`]: single glyphs first, then
+/// two-glyph combinations, then three-glyph, and so on — so the pool never
+/// truly exhausts.
+pub(crate) struct SymbolMap {
+    /// `(fingerprint, symbol string, formatted type lines)`
+    entries: Vec<(String, String, Vec<String>)>,
+}
+
+impl SymbolMap {
+    pub(crate) fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Return (or assign) the symbol for a connection's interface type.
+    /// Returns `None` if the connection carries no type info.
+    pub(crate) fn symbol_for_conn(
+        &mut self,
+        conn: &InterfaceConnection,
+        arena: &TypeArena,
+    ) -> Option<&str> {
+        let fp = conn.fingerprint.as_ref()?;
+        let iface = conn.interface_type.as_ref()?;
+        Some(self.get_or_insert(fp, iface, arena))
+    }
+
+    /// Return (or assign) the symbol for an export's interface type.
+    /// Returns `None` if the export carries no interface type.
+    pub(crate) fn symbol_for_export(
+        &mut self,
+        export_info: &ExportInfo,
+        arena: &TypeArena,
+    ) -> Option<&str> {
+        let fp = export_info.fingerprint.as_ref()?;
+        let id = match export_info.ty {
+            Some(InternedId::Interface(id)) => id,
+            _ => return None,
+        };
+        Some(self.get_or_insert(fp, arena.lookup_interface(id), arena))
+    }
+
+    fn get_or_insert(&mut self, fp: &str, iface: &InterfaceType, arena: &TypeArena) -> &str {
+        if let Some(pos) = self.entries.iter().position(|(f, _, _)| f == fp) {
+            return &self.entries[pos].1;
+        }
+        let symbol = symbol_at(self.entries.len());
+        let lines = format_interface_type_lines(iface, arena);
+        self.entries.push((fp.to_string(), symbol, lines));
+        &self.entries.last().unwrap().1
+    }
+
+    /// Return (or assign) the symbol for a pre-computed fingerprint + type lines,
+    /// or an empty string when `show_types` is false or no fingerprint is present.
+    ///
+    /// This is the primary entry point for AllInterfaces/Full renderers that
+    /// receive type data from the [`DiagramEdge`]/[`DiagramExport`] IR.
+    pub(crate) fn assign(
+        &mut self,
+        show_types: bool,
+        fingerprint: Option<&str>,
+        type_lines: Vec<String>,
+    ) -> String {
+        if !show_types {
+            return String::new();
+        }
+        let Some(fp) = fingerprint else {
+            return String::new();
+        };
+        if let Some(pos) = self.entries.iter().position(|(f, _, _)| f == fp) {
+            return self.entries[pos].1.clone();
+        }
+        let symbol = symbol_at(self.entries.len());
+        self.entries.push((fp.to_string(), symbol, type_lines));
+        self.entries.last().unwrap().1.clone()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Return key lines: `"✦ fn-name: sig"` per unique symbol, with
+    /// continuation lines for multi-function interfaces indented.
+    pub(crate) fn key_lines(&self) -> Vec<String> {
+        let mut out = Vec::new();
+        for (_, symbol, type_lines) in &self.entries {
+            for (i, line) in type_lines.iter().enumerate() {
+                if i == 0 {
+                    out.push(format!("{} {}", symbol, line));
+                } else {
+                    out.push(format!("  {}", line));
+                }
+            }
+        }
+        out
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Intermediate representation — shared graph traversal
+// ---------------------------------------------------------------------------
+
+/// A node to be rendered in the diagram.
+pub(crate) struct DiagramNode {
+    pub name: String,
+    pub display: String,
+    pub is_synthetic: bool,
+    pub component_index: u32,
+    /// `package@version` of the component; only filled in the full view.
+    pub package: Option<String>,
+    /// See [`ComponentNode::namespace`].
+    pub namespace: Option<String>,
+    /// Summary of the producing tools; only filled in the full view.
+    pub produced_by: Option<String>,
+    /// Size of the component in bytes; only filled in the full view.
+    pub size: Option<u64>,
+    /// Non-default wasm features the component uses; only filled in the
+    /// full view.
+    pub features: Vec<String>,
+    /// Whether the component runs start logic; only filled in the full
+    /// view.
+    pub runs_start: bool,
+    /// Nothing imports from the instance and it backs no export (see
+    /// [`unused_instances`]); not filled in the dependency view.
+    pub unused: bool,
+    /// Several other instances import from it (see [`shared_instances`]);
+    /// not filled in the dependency view.
+    pub shared: bool,
+    /// Some export can only reach part of the host through it (see
+    /// [`critical_instances`]); not filled in the dependency view.
+    pub critical: bool,
+    /// Instances of the same component this node belongs with, itself
+    /// included (see [`CompositionGraph::duplicate_instances`]); 1 when it
+    /// is the only one.
+    pub copies: u32,
+}
+
+/// A directed edge between two nodes.
+pub(crate) struct DiagramEdge {
+    /// Raw name of the source (interface_name for host imports, node.name otherwise).
+    /// Renderers that need sanitized IDs (Mermaid) apply their own transform.
+    pub from_name: String,
+    pub from_display: String,
+    pub to_name: String,
+    pub to_display: String,
+    /// Fully-qualified interface name carried by this edge.
+    pub interface_name: String,
+    /// Ready-to-use edge label (short interface name or full name, depending on mode).
+    pub label: String,
+    /// Pre-formatted type lines for this connection (empty when show_types=false).
+    pub type_lines: Vec<String>,
+    /// Fingerprint for deduplication in a [`SymbolMap`] (None when no type info).
+    pub fingerprint: Option<String>,
+    /// true if host import
+    pub is_dashed: bool,
+    /// Nested instances aliased between source and target, outermost first
+    /// (Full only; empty elsewhere).
+    pub via: Vec<String>,
+}
+
+/// An exported interface.
+pub(crate) struct DiagramExport {
+    pub from_name: String,
+    pub from_display: String,
+    pub full_name: String,
+    pub short_name: String,
+    /// Pre-formatted type lines for this export (empty when show_types=false).
+    pub type_lines: Vec<String>,
+    /// Fingerprint for deduplication in a [`SymbolMap`] (None when no type info).
+    pub fingerprint: Option<String>,
+    /// Nested instances aliased on the way to the export, outermost first
+    /// (Full only; empty elsewhere).
+    pub via: Vec<String>,
+}
+
+/// A declared import nothing is plugged in for, drawn as an edge from
+/// nowhere.
+pub(crate) struct MissingImport {
+    pub to_name: String,
+    pub to_display: String,
+    /// Fully-qualified name of the import.
+    pub interface_name: String,
+    /// Ready-to-use edge label (short or full name, depending on mode).
+    pub label: String,
+}
+
+/// Pre-computed graph data for rendering, independent of output format.
+pub(crate) struct ConnectionsView {
+    /// Raw host interface names (AllInterfaces only; empty for Full).
+    pub host_names: Vec<String>,
+    pub nodes: Vec<DiagramNode>,
+    pub edges: Vec<DiagramEdge>,
+    pub exports: Vec<DiagramExport>,
+    /// Unwired imports of the instances in `nodes`.
+    pub missing: Vec<MissingImport>,
+}
+
+impl ConnectionsView {
+    /// Replace the short interface labels with the ones `style` gives.
+    pub(crate) fn relabel(&mut self, style: &LabelStyle) {
+        let relabel = |label: &mut String, full: &str| {
+            let short = short_interface_name(full);
+            if let Some(rest) = label.strip_prefix(short.as_str()) {
+                *label = format!("{}{}", style.label(full), rest);
+            }
+        };
+        for edge in &mut self.edges {
+            relabel(&mut edge.label, &edge.interface_name);
+            if edge.is_dashed {
+                relabel(&mut edge.from_display, &edge.from_name);
+            }
+        }
+        for export in &mut self.exports {
+            relabel(&mut export.short_name, &export.full_name);
+        }
+        for missing in &mut self.missing {
+            relabel(&mut missing.label, &missing.interface_name);
+        }
+    }
+
+    /// Leave the host out: drop the host interface nodes and the dashed
+    /// edges from them, keeping only what the instances do among themselves.
+    pub(crate) fn drop_host_imports(&mut self) {
+        self.host_names.clear();
+        self.edges.retain(|e| !e.is_dashed);
+    }
+
+    /// Merge the `wasi:` host interfaces into one node per package, e.g.
+    /// `wasi:io/error` and `wasi:io/streams` into `wasi:io`.  The edges from
+    /// a package to the same instance become one edge listing every
+    /// interface; such merged edges carry no type information.
+    pub(crate) fn group_host_imports(&mut self) {
+        let mut groups = BTreeSet::new();
+        for name in std::mem::take(&mut self.host_names) {
+            groups.insert(host_group(&name).map_or(name.clone(), str::to_string));
+        }
+        self.host_names = groups.into_iter().collect();
+
+        let mut edges: Vec<DiagramEdge> = Vec::new();
+        let mut merged: BTreeMap<(String, String), usize> = BTreeMap::new();
+        for mut edge in std::mem::take(&mut self.edges) {
+            let group = edge
+                .is_dashed
+                .then(|| host_group(&edge.from_name))
+                .flatten()
+                .map(str::to_string);
+            let Some(group) = group else {
+                edges.push(edge);
+                continue;
+            };
+            match merged.get(&(group.clone(), edge.to_name.clone())) {
+                Some(&at) => {
+                    let existing = &mut edges[at];
+                    existing.label = format!("{}, {}", existing.label, edge.label);
+                    existing.type_lines.clear();
+                    existing.fingerprint = None;
+                }
+                None => {
+                    merged.insert((group.clone(), edge.to_name.clone()), edges.len());
+                    edge.from_display = group.clone();
+                    edge.from_name = group.clone();
+                    edge.interface_name = group;
+                    edges.push(edge);
+                }
+            }
+        }
+        self.edges = edges;
+    }
+}
+
+/// Total copies of each instance's component, for instances that are one
+/// of several.
+fn copies_by_id(graph: &CompositionGraph) -> BTreeMap<u32, u32> {
+    let mut copies = BTreeMap::new();
+    for group in graph.duplicate_instances() {
+        let total = group.iter().map(|id| graph.nodes[id].copies).sum();
+        copies.extend(group.into_iter().map(|id| (id, total)));
+    }
+    copies
+}
+
+/// The unwired imports of the instances `include` accepts, labelled with
+/// `label`.
+fn missing_imports(
+    graph: &CompositionGraph,
+    include: impl Fn(&ComponentNode) -> bool,
+    label: impl Fn(&str) -> String,
+) -> Vec<MissingImport> {
+    unsatisfied_imports(graph)
+        .into_iter()
+        .filter(|u| u.problem == ImportProblem::Unwired)
+        .filter_map(|u| {
+            let node = graph.get_node(u.instance).filter(|n| include(n))?;
+            Some(MissingImport {
+                to_name: node.name.clone(),
+                to_display: node.display_label().to_string(),
+                label: label(&u.interface),
+                interface_name: u.interface,
+            })
+        })
+        .collect()
+}
+
+/// The chain of `iface` to draw: [`get_chain_for`], preceded by the synthetic
+/// instance the composition exports `iface` from when `opts` shows synthetic
+/// instances and the chain leaves it out.
+pub(crate) fn drawn_chain(graph: &CompositionGraph, iface: &str, opts: &RenderOptions) -> Vec<u32> {
+    let mut chain = get_chain_for(graph, iface);
+    if !opts.show_synthetic || chain.is_empty() {
+        return chain;
+    }
+    if let Some(export) = graph.export(iface) {
+        let source = export.source_instance;
+        let synthetic = graph
+            .get_node(source)
+            .is_some_and(|n| n.component_index == SYNTHETIC_COMPONENT);
+        if synthetic && !chain.contains(&source) {
+            chain.insert(0, source);
+        }
+    }
+    chain
+}
+
+/// Build a [`ConnectionsView`] for `AllInterfaces` detail level.
+///
+/// Includes real (non-synthetic) component nodes, host-import edges (dashed),
+/// inter-component edges (solid), and exported interfaces.  Edge labels use
+/// the short interface name.  With `show_synthetic`, synthetic instances are
+/// included too, along with their connections and exports.
+pub(crate) fn build_all_interfaces_view(
+    graph: &CompositionGraph,
+    show_types: bool,
+    show_synthetic: bool,
+) -> ConnectionsView {
+    let shown = |n: &ComponentNode| show_synthetic || n.component_index != SYNTHETIC_COMPONENT;
+    let component_nodes: Vec<&ComponentNode> = graph.nodes.values().filter(|n| shown(n)).collect();
+    let unused: BTreeSet<u32> = unused_instances(graph).into_iter().collect();
+    let shared: BTreeSet<u32> = shared_instances(graph)
+        .into_iter()
+        .map(|s| s.instance)
+        .collect();
+    let critical: BTreeSet<u32> = critical_instances(graph)
+        .into_iter()
+        .map(|c| c.instance)
+        .collect();
+    let copies = copies_by_id(graph);
+
+    let nodes = graph
+        .nodes
+        .iter()
+        .filter(|(_, n)| shown(n))
+        .map(|(id, n)| DiagramNode {
+            name: n.name.clone(),
+            display: n.display_label().to_string(),
+            is_synthetic: n.component_index == SYNTHETIC_COMPONENT,
+            component_index: n.component_index,
+            package: None,
+            namespace: n.namespace(),
+            produced_by: None,
+            size: None,
+            features: Vec::new(),
+            runs_start: false,
+            unused: unused.contains(id),
+            shared: shared.contains(id),
+            critical: critical.contains(id),
+            copies: copies.get(id).copied().unwrap_or(n.copies),
+        })
+        .collect();
+
+    let mut edges = Vec::new();
+    for node in &component_nodes {
+        for import in &node.imports {
+            if import.is_host_import {
+                edges.push(DiagramEdge {
+                    from_name: import.interface_name.clone(),
+                    from_display: short_interface_name(&import.interface_name),
+                    to_name: node.name.clone(),
+                    to_display: node.display_label().to_string(),
+                    interface_name: import.interface_name.clone(),
+                    label: kind_label(import.kind, import.short_label()),
+                    type_lines: connection_type_lines(import, &graph.arena, show_types),
+                    fingerprint: import.fingerprint.clone(),
+                    is_dashed: true,
+                    via: Vec::new(),
+                });
+            } else if let Some(src) = import.source_instance.and_then(|id| graph.get_node(id)) {
+                if shown(src) {
+                    edges.push(DiagramEdge {
+                        from_name: src.name.clone(),
+                        from_display: src.display_label().to_string(),
+                        to_name: node.name.clone(),
+                        to_display: node.display_label().to_string(),
+                        interface_name: import.interface_name.clone(),
+                        label: kind_label(import.kind, import.short_label()),
+                        type_lines: connection_type_lines(import, &graph.arena, show_types),
+                        fingerprint: import.fingerprint.clone(),
+                        is_dashed: false,
+                        via: Vec::new(),
+                    });
+                }
+            }
+        }
+    }
+
+    let mut exports = Vec::new();
+    for export_info in &graph.component_exports {
+        let export_name = &export_info.name;
+        if let Some(node) = graph.get_node(export_info.source_instance) {
+            if shown(node) {
+                exports.push(DiagramExport {
+                    from_name: node.name.clone(),
+                    from_display: node.display_label().to_string(),
+                    full_name: export_name.clone(),
+                    short_name: kind_label(export_info.kind, short_interface_name(export_name)),
+                    type_lines: export_type_lines(export_info, &graph.arena, show_types),
+                    fingerprint: export_info.fingerprint.clone(),
+                    via: Vec::new(),
+                });
+            }
+        }
+    }
+
+    let mut host_names = graph.host_interfaces();
+    if show_synthetic {
+        for edge in edges.iter().filter(|e| e.is_dashed) {
+            if !host_names.contains(&edge.from_name) {
+                host_names.push(edge.from_name.clone());
+            }
+        }
+        host_names.sort();
+    }
+
+    ConnectionsView {
+        host_names,
+        nodes,
+        edges,
+        exports,
+        missing: missing_imports(
+            graph,
+            |n| n.component_index != SYNTHETIC_COMPONENT,
+            short_interface_name,
+        ),
+    }
+}
+
+/// Build a [`ConnectionsView`] for `Full` detail level.
+///
+/// Includes all nodes (including synthetic), all non-host-import edges with
+/// full interface names, and all exported interfaces.
+pub(crate) fn build_full_view(graph: &CompositionGraph, show_types: bool) -> ConnectionsView {
+    let unused: BTreeSet<u32> = unused_instances(graph).into_iter().collect();
+    let shared: BTreeSet<u32> = shared_instances(graph)
+        .into_iter()
+        .map(|s| s.instance)
+        .collect();
+    let critical: BTreeSet<u32> = critical_instances(graph)
+        .into_iter()
+        .map(|c| c.instance)
+        .collect();
+    let copies = copies_by_id(graph);
+    let nodes = graph
+        .nodes
+        .iter()
+        .map(|(id, n)| DiagramNode {
+            name: n.name.clone(),
+            display: n.display_label().to_string(),
+            is_synthetic: n.component_index == SYNTHETIC_COMPONENT,
+            component_index: n.component_index,
+            package: n.package_id(),
+            namespace: n.namespace(),
+            produced_by: graph.producers_of(n),
+            size: n.size,
+            features: n.features.iter().map(|f| f.to_string()).collect(),
+            runs_start: n.runs_start,
+            unused: unused.contains(id),
+            shared: shared.contains(id),
+            critical: critical.contains(id),
+            copies: copies.get(id).copied().unwrap_or(n.copies),
+        })
+        .collect();
+
+    let mut edges = Vec::new();
+    for node in graph.nodes.values() {
+        for import in &node.imports {
+            if !import.is_host_import {
+                if let Some(src) = import.source_instance.and_then(|id| graph.get_node(id)) {
+                    edges.push(DiagramEdge {
+                        from_name: src.name.clone(),
+                        from_display: src.display_label().to_string(),
+                        to_name: node.name.clone(),
+                        to_display: node.display_label().to_string(),
+                        interface_name: import.interface_name.clone(),
+                        label: kind_label(import.kind, import.interface_name.clone()),
+                        type_lines: connection_type_lines(import, &graph.arena, show_types),
+                        fingerprint: import.fingerprint.clone(),
+                        is_dashed: false,
+                        via: import.alias_chain.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    let mut exports = Vec::new();
+    for export_info in &graph.component_exports {
+        let export_name = &export_info.name;
+        if let Some(node) = graph.get_node(export_info.source_instance) {
+            exports.push(DiagramExport {
+                from_name: node.name.clone(),
+                from_display: node.display_label().to_string(),
+                full_name: export_name.clone(),
+                short_name: kind_label(export_info.kind, short_interface_name(export_name)),
+                type_lines: export_type_lines(export_info, &graph.arena, show_types),
+                fingerprint: export_info.fingerprint.clone(),
+                via: export_info.alias_chain.clone(),
+            });
+        }
+    }
+
+    ConnectionsView {
+        host_names: vec![],
+        nodes,
+        edges,
+        exports,
+        missing: missing_imports(graph, |_| true, str::to_string),
+    }
+}
+
+/// Build a [`ConnectionsView`] rooted at instance `root`.
+///
+/// Includes `root` and every instance it transitively imports from (in
+/// breadth-first order), the edges between them, and a dashed edge for each
+/// host import along the way.  Exports are omitted: the view answers "what
+/// does this instance need", not "who uses it".  Edge labels use the short
+/// interface name.
+pub(crate) fn build_dependency_view(
+    graph: &CompositionGraph,
+    root: u32,
+    show_types: bool,
+) -> ConnectionsView {
+    let order: Vec<&ComponentNode> = graph.bfs(root).map(|(_, node)| node).collect();
+
+    let nodes = order
+        .iter()
+        .map(|n| DiagramNode {
+            name: n.name.clone(),
+            display: n.display_label().to_string(),
+            is_synthetic: n.component_index == SYNTHETIC_COMPONENT,
+            component_index: n.component_index,
+            package: None,
+            namespace: n.namespace(),
+            produced_by: None,
+            size: None,
+            features: Vec::new(),
+            runs_start: false,
+            unused: false,
+            shared: false,
+            critical: false,
+            copies: n.copies,
+        })
+        .collect();
+
+    let mut host_names = std::collections::BTreeSet::new();
+    let mut edges = Vec::new();
+    for node in &order {
+        for import in &node.imports {
+            let (from_name, from_display) = if import.is_host_import {
+                host_names.insert(import.interface_name.clone());
+                (
+                    import.interface_name.clone(),
+                    short_interface_name(&import.interface_name),
+                )
+            } else if let Some(src) = import.source_instance.and_then(|id| graph.get_node(id)) {
+                (src.name.clone(), src.display_label().to_string())
+            } else {
+                continue;
+            };
+            edges.push(DiagramEdge {
+                from_name,
+                from_display,
+                to_name: node.name.clone(),
+                to_display: node.display_label().to_string(),
+                interface_name: import.interface_name.clone(),
+                label: kind_label(import.kind, import.short_label()),
+                type_lines: connection_type_lines(import, &graph.arena, show_types),
+                fingerprint: import.fingerprint.clone(),
+                is_dashed: import.is_host_import,
+                via: Vec::new(),
+            });
+        }
+    }
+
+    ConnectionsView {
+        host_names: host_names.into_iter().collect(),
+        nodes,
+        edges,
+        exports: vec![],
+        missing: missing_imports(
+            graph,
+            |n| order.iter().any(|o| std::ptr::eq(*o, n)),
+            short_interface_name,
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_symbol_at_tier_boundaries() {
+        let n = SYMBOL_POOL.len();
+
+        // Last single-char symbol
+        assert_eq!(symbol_at(n - 1).chars().count(), 1);
+        // First two-char symbol
+        assert_eq!(symbol_at(n).chars().count(), 2);
+        // Last two-char symbol
+        assert_eq!(symbol_at(n + n * n - 1).chars().count(), 2);
+        // First three-char symbol
+        assert_eq!(symbol_at(n + n * n).chars().count(), 3);
+    }
+
+    #[test]
+    fn test_symbol_at_no_duplicates() {
+        let n = SYMBOL_POOL.len();
+        // Verify the first n + n² symbols are all distinct
+        let symbols: Vec<String> = (0..n + n * n).map(symbol_at).collect();
+        let unique: std::collections::HashSet<&String> = symbols.iter().collect();
+        assert_eq!(symbols.len(), unique.len(), "symbol_at produced duplicates");
+    }
+
+    // -----------------------------------------------------------------------
+    // format_func_sig edge cases
+    // -----------------------------------------------------------------------
+
+    use crate::model::{FuncSignature, InterfaceType, ValueType};
+
+    fn make_arena() -> crate::model::TypeArena {
+        crate::model::TypeArena::default()
+    }
+
+    #[test]
+    fn test_format_func_sig_no_params() {
+        let mut arena = make_arena();
+        let bool_id = arena.intern_val(ValueType::Bool);
+        let sig = FuncSignature {
+            is_async: false,
+            param_names: vec![],
+            params: vec![],
+            results: vec![bool_id],
+        };
+        assert_eq!(format_func_sig(&sig, &arena), "() -> bool");
+    }
+
+    #[test]
+    fn test_format_func_sig_no_results() {
+        let mut arena = make_arena();
+        let u32_id = arena.intern_val(ValueType::U32);
+        let sig = FuncSignature {
+            is_async: false,
+            param_names: vec![],
+            params: vec![u32_id],
+            results: vec![],
+        };
+        assert_eq!(format_func_sig(&sig, &arena), "(u32) -> ()");
+    }
+
+    #[test]
+    fn test_format_func_sig_multiple_results() {
+        let mut arena = make_arena();
+        let u32_id = arena.intern_val(ValueType::U32);
+        let str_id = arena.intern_val(ValueType::String);
+        let bool_id = arena.intern_val(ValueType::Bool);
+        let sig = FuncSignature {
+            is_async: false,
+            param_names: vec![],
+            params: vec![u32_id, str_id],
+            results: vec![bool_id, str_id],
+        };
+        assert_eq!(
+            format_func_sig(&sig, &arena),
+            "(u32, string) -> (bool, string)"
+        );
+    }
+
+    #[test]
+    fn test_format_func_sig_named_async() {
+        let mut arena = make_arena();
+        let u32_id = arena.intern_val(ValueType::U32);
+        let str_id = arena.intern_val(ValueType::String);
+        let sig = FuncSignature {
+            is_async: true,
+            param_names: vec!["id".to_string(), "key".to_string()],
+            params: vec![u32_id, str_id],
+            results: vec![str_id],
+        };
+        assert_eq!(
+            format_func_sig(&sig, &arena),
+            "async (id: u32, key: string) -> string"
+        );
+    }
+
+    #[test]
+    fn test_format_interface_type_lines_func_variant() {
+        let mut arena = make_arena();
+        let u32_id = arena.intern_val(ValueType::U32);
+        let bool_id = arena.intern_val(ValueType::Bool);
+        let sig = FuncSignature {
+            is_async: false,
+            param_names: vec![],
+            params: vec![u32_id],
+            results: vec![bool_id],
+        };
+        // Func variant: single bare sig line, no backtick-name prefix
+        let iface = InterfaceType::Func(sig);
+        let lines = format_interface_type_lines(&iface, &arena);
+        assert_eq!(lines, vec!["(u32) -> bool"]);
+    }
+
+    #[test]
+    fn test_connection_type_lines_missing_type_info() {
+        use crate::model::InterfaceConnection;
+        let arena = make_arena();
+        let conn = InterfaceConnection {
+            interface_name: "wasi:http/handler@0.3.0".to_string(),
+            source_instance: None,
+            is_host_import: true,
+            interface_type: None, // no type info
+            fingerprint: None,
+            source_export: None,
+            kind: ExternKind::Instance,
+            alias_chain: Vec::new(),
+        };
+        // show_types=true but no type info → should return empty, not panic
+        let lines = connection_type_lines(&conn, &arena, true);
+        assert!(
+            lines.is_empty(),
+            "missing type info should produce no type lines"
+        );
+    }
+
+    // -----------------------------------------------------------------------
+    // ConnectionsView IR tests
+    // -----------------------------------------------------------------------
+
+    use crate::test_utils::*;
+
+    #[test]
+    fn test_view_all_interfaces_node_count() {
+        let graph = simple_chain_graph();
+        let view = build_all_interfaces_view(&graph, false, false);
+        // $srv and $middleware are real; no synthetic nodes in this graph
+        assert_eq!(view.nodes.len(), 2);
+        assert!(view.nodes.iter().any(|n| n.display.contains("srv")));
+        assert!(view.nodes.iter().any(|n| n.display.contains("middleware")));
+    }
+
+    #[test]
+    fn test_view_all_interfaces_host_names() {
+        let graph = simple_chain_graph();
+        let view = build_all_interfaces_view(&graph, false, false);
+        // Two distinct host interfaces: handler (from srv) and log (from middleware)
+        assert_eq!(view.host_names.len(), 2);
+        assert!(view.host_names.iter().any(|n| n.contains("handler")));
+        assert!(view.host_names.iter().any(|n| n.contains("log")));
+    }
+
+    #[test]
+    fn test_view_all_interfaces_edge_dashed() {
+        let graph = simple_chain_graph();
+        let view = build_all_interfaces_view(&graph, false, false);
+        // 2 host-import edges (dashed) + 1 component edge (solid)
+        let dashed: Vec<_> = view.edges.iter().filter(|e| e.is_dashed).collect();
+        let solid: Vec<_> = view.edges.iter().filter(|e| !e.is_dashed).collect();
+        assert_eq!(
+            dashed.len(),
+            2,
+            "two host imports should produce dashed edges"
+        );
+        assert_eq!(
+            solid.len(),
+            1,
+            "one inter-component import should produce a solid edge"
+        );
+    }
+
+    #[test]
+    fn test_view_all_interfaces_edge_endpoints() {
+        let graph = simple_chain_graph();
+        let view = build_all_interfaces_view(&graph, false, false);
+        let solid = view.edges.iter().find(|e| !e.is_dashed).unwrap();
+        assert!(
+            solid.from_display.contains("srv"),
+            "solid edge should come from srv"
+        );
+        assert!(
+            solid.to_display.contains("middleware"),
+            "solid edge should go to middleware"
+        );
+        assert_eq!(
+            solid.label, "handler",
+            "edge label should be short interface name"
+        );
+    }
+
+    #[test]
+    fn test_view_all_interfaces_export() {
+        let graph = simple_chain_graph();
+        let view = build_all_interfaces_view(&graph, false, false);
+        assert_eq!(view.exports.len(), 1);
+        let exp = &view.exports[0];
+        assert!(exp.from_display.contains("middleware"));
+        assert_eq!(exp.short_name, "handler");
+        assert!(exp.full_name.contains("wasi:http/handler"));
+    }
+
+    #[test]
+    fn test_view_all_interfaces_non_http_chain() {
+        // Verify the IR works for a non-http chain (keyvalue/store)
+        let graph = two_chain_graph();
+        let view = build_all_interfaces_view(&graph, false, false);
+        let kv_export = view
+            .exports
+            .iter()
+            .find(|e| e.full_name.contains("keyvalue"));
+        assert!(kv_export.is_some(), "should have a keyvalue export");
+        assert_eq!(kv_export.unwrap().short_name, "store");
+
+        let kv_solid = view
+            .edges
+            .iter()
+            .find(|e| !e.is_dashed && e.label == "store");
+        assert!(kv_solid.is_some(), "should have solid keyvalue/store edge");
+        let kv_solid = kv_solid.unwrap();
+        assert!(kv_solid.from_display.contains("db"));
+        assert!(kv_solid.to_display.contains("cache"));
+    }
+
+    #[test]
+    fn test_view_all_interfaces_excludes_synthetic_source() {
+        // A synthetic node as the *source* of an import should not produce an
+        // edge in AllInterfaces mode (only real component sources are shown).
+        use crate::model::{ComponentNode, ExternKind, InterfaceConnection, SYNTHETIC_COMPONENT};
+
+        let mut graph = CompositionGraph::new();
+
+        // A real component that imports from a synthetic source
+        let mut real = ComponentNode::new("$real".to_string(), 0, 0);
+        real.add_import(InterfaceConnection {
+            interface_name: "wasi:http/handler@0.3.0".to_string(),
+            source_instance: Some(99), // will be a synthetic node
+            is_host_import: false,
+            interface_type: None,
+            fingerprint: None,
+            source_export: None,
+            kind: ExternKind::Instance,
+            alias_chain: Vec::new(),
+        });
+        graph.add_node(1, real);
+
+        // The synthetic node at idx 99
+        let synthetic = ComponentNode::new(
+            "$synthetic".to_string(),
+            SYNTHETIC_COMPONENT,
+            SYNTHETIC_COMPONENT,
+        );
+        graph.add_node(99, synthetic);
+
+        let view = build_all_interfaces_view(&graph, false, false);
+
+        // The edge from the synthetic source should be dropped
+        assert!(
+            view.edges.is_empty(),
+            "edges from synthetic source nodes should be excluded, got: {:?}",
+            view.edges
+                .iter()
+                .map(|e| (&e.from_display, &e.to_display))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_view_full_includes_all_nodes() {
+        let graph = simple_chain_graph();
+        let view = build_full_view(&graph, false);
+        // Full includes all nodes; host_names is empty
+        assert!(view.nodes.len() >= 2);
+        assert!(
+            view.host_names.is_empty(),
+            "Full mode has no host node list"
+        );
+    }
+
+    #[test]
+    fn test_view_full_no_dashed_edges() {
+        let graph = simple_chain_graph();
+        let view = build_full_view(&graph, false);
+        assert!(
+            view.edges.iter().all(|e| !e.is_dashed),
+            "Full mode skips host imports so no edge should be dashed"
+        );
+    }
+
+    #[test]
+    fn test_view_full_edge_uses_full_name() {
+        let graph = simple_chain_graph();
+        let view = build_full_view(&graph, false);
+        let edge = view
+            .edges
+            .iter()
+            .find(|e| e.label.contains("handler"))
+            .unwrap();
+        assert!(
+            edge.label.contains("wasi:http/handler@0.3.0"),
+            "Full mode should use full interface name as label, got: {}",
+            edge.label
+        );
+    }
+
+    #[test]
+    fn test_view_all_interfaces_two_chains() {
+        let graph = two_chain_graph();
+        let view = build_all_interfaces_view(&graph, false, false);
+        // 4 real nodes: srv-http, mw-http, db, cache
+        assert_eq!(view.nodes.len(), 4);
+        // 2 solid edges (one per chain) + 2 host-import edges (one per inner node)
+        let solid: Vec<_> = view.edges.iter().filter(|e| !e.is_dashed).collect();
+        assert_eq!(solid.len(), 2, "two inter-component edges expected");
+        // 2 exports
+        assert_eq!(view.exports.len(), 2);
+        let names: Vec<&str> = view.exports.iter().map(|e| e.short_name.as_str()).collect();
+        assert!(names.contains(&"handler"), "should have handler export");
+        assert!(names.contains(&"store"), "should have store export");
+    }
+
+    #[test]
+    fn test_view_full_synthetic_node_included() {
+        use crate::model::{ComponentNode, SYNTHETIC_COMPONENT};
+        let mut graph = CompositionGraph::new();
+
+        let real = ComponentNode::new("$real".to_string(), 0, 0);
+        graph.add_node(1, real);
+        let synthetic = ComponentNode::new(
+            "$synth".to_string(),
+            SYNTHETIC_COMPONENT,
+            SYNTHETIC_COMPONENT,
+        );
+        graph.add_node(99, synthetic);
+
+        let view = build_full_view(&graph, false);
+        assert_eq!(
+            view.nodes.len(),
+            2,
+            "Full mode should include synthetic nodes"
+        );
+        assert!(
+            view.nodes.iter().any(|n| n.is_synthetic),
+            "synthetic flag should be set"
+        );
+        assert!(view.nodes.iter().any(|n| n.display.contains("synth")));
+    }
+
+    #[test]
+    fn test_view_host_interfaces_deduplicated() {
+        use crate::model::{ComponentNode, ExternKind, InterfaceConnection};
+        let mut graph = CompositionGraph::new();
+
+        // Two real nodes both importing the same host interface
+        let mut a = ComponentNode::new("$a".to_string(), 0, 0);
+        a.add_import(InterfaceConnection {
+            interface_name: "wasi:logging/log@0.1.0".to_string(),
+            source_instance: None,
+            is_host_import: true,
+            interface_type: None,
+            fingerprint: None,
+            source_export: None,
+            kind: ExternKind::Instance,
+            alias_chain: Vec::new(),
+        });
+        graph.add_node(1, a);
+
+        let mut b = ComponentNode::new("$b".to_string(), 1, 1);
+        b.add_import(InterfaceConnection {
+            interface_name: "wasi:logging/log@0.1.0".to_string(),
+            source_instance: None,
+            is_host_import: true,
+            interface_type: None,
+            fingerprint: None,
+            source_export: None,
+            kind: ExternKind::Instance,
+            alias_chain: Vec::new(),
+        });
+        graph.add_node(2, b);
+
+        let view = build_all_interfaces_view(&graph, false, false);
+        assert_eq!(
+            view.host_names.len(),
+            1,
+            "same host interface imported by two nodes should appear once"
+        );
+        assert_eq!(view.host_names[0], "wasi:logging/log@0.1.0");
+    }
+
+    #[test]
+    fn test_group_host_imports() {
+        let mut graph = crate::test_utils::simple_chain_graph();
+        let mut context = graph.nodes[&2].imports[1].clone();
+        context.interface_name = "wasi:logging/context@0.1.0".to_string();
+        graph.nodes.get_mut(&2).unwrap().add_import(context);
+
+        let mut view = build_all_interfaces_view(&graph, false, false);
+        view.group_host_imports();
+        assert_eq!(view.host_names, vec!["wasi:http", "wasi:logging"]);
+
+        let host_edges: Vec<(&str, &str, &str)> = view
+            .edges
+            .iter()
+            .filter(|e| e.is_dashed)
+            .map(|e| {
+                (
+                    e.from_name.as_str(),
+                    e.label.as_str(),
+                    e.to_display.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            host_edges,
+            vec![
+                ("wasi:http", "handler", "srv"),
+                ("wasi:logging", "log, context", "middleware"),
+            ]
+        );
+    }
+}
@@ -1,29 +1,52 @@
+use crate::analysis::composer::{composer, Composer};
+use crate::analysis::critical::critical_instances;
+use crate::analysis::diagnostics::{diagnose, Category, LintLevel, LintLevels};
+use crate::error::{CvizError, Result};
 use crate::model::{
-    CompositionGraph, FuncSignature, InterfaceConnection, InterfaceType, InternedId, TypeArena,
-    ValueType, ValueTypeId,
+    CompositionGraph, CoreInstanceKind, ExternKind, FuncSignature, InterfaceConnection,
+    InterfaceType, InternedId, ModuleInterface, ModuleItem, TypeArena, ValueType, ValueTypeId,
 };
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Version of the JSON layout [`generate_json`] writes.  Graphs saved in
+/// earlier layouts are [migrated](crate::parse::json::migrate) when read.
+///
+/// - 2: core structure, metadata and the instance details added since.
+/// - 3: `exports` on each node and the default lint `warnings`.
+pub const JSON_VERSION: u32 = 3;
 
 /// Serialize a [`CompositionGraph`] to JSON.
-pub fn generate_json(graph: &CompositionGraph, pretty: bool) -> Result<String, serde_json::Error> {
-    let model = generate_json_model(graph);
+pub fn generate_json(graph: &CompositionGraph, pretty: bool) -> Result<String> {
+    to_json(&generate_json_model(graph), pretty)
+}
+
+/// Serialize `value`, indented if `pretty`.
+pub(crate) fn to_json(value: &impl Serialize, pretty: bool) -> Result<String> {
     if pretty {
-        serde_json::to_string_pretty(&model)
+        serde_json::to_string_pretty(value)
     } else {
-        serde_json::to_string(&model)
+        serde_json::to_string(value)
     }
+    .map_err(CvizError::Render)
 }
 
-fn generate_json_model(graph: &CompositionGraph) -> JsonCompositionGraph {
+pub(crate) fn generate_json_model(graph: &CompositionGraph) -> JsonCompositionGraph {
     let arena = &graph.arena;
+    let critical: BTreeSet<u32> = critical_instances(graph)
+        .into_iter()
+        .map(|c| c.instance)
+        .collect();
 
+    let mut stable_ids = graph.stable_ids();
     let nodes = graph
         .nodes
         .iter()
         .map(|(&id, node)| JsonNode {
             id,
-            name: node.display_label().to_string(),
+            name: node.name.trim_start_matches('$').to_string(),
+            stable_id: stable_ids.remove(&id).unwrap_or_default(),
+            label: node.label.clone(),
             component_index: node.component_index,
             component_num: node.component_num,
             imports: node
@@ -31,16 +54,40 @@ fn generate_json_model(graph: &CompositionGraph) -> JsonCompositionGraph {
                 .iter()
                 .map(|ic| JsonInterfaceConnection::from_ir(ic, arena))
                 .collect(),
+            package: node.package.clone(),
+            package_version: node.package_version.clone(),
+            component_name: node.component_name.clone(),
+            depth: node.depth,
+            size: node.size,
+            digest: node.digest.clone(),
+            features: node.features.iter().map(|f| f.to_string()).collect(),
+            runs_start: node.runs_start,
+            declared_imports: node
+                .declared_imports
+                .as_ref()
+                .map(|names| names.iter().cloned().collect()),
+            copies: node.copies,
+            produced_by: graph.producers_of(node),
+            description: graph.description_of(node).map(str::to_string),
+            critical: critical.contains(&id),
+            exports: graph
+                .component_exports
+                .iter()
+                .filter(|e| e.source_instance == id)
+                .map(|e| e.name.clone())
+                .collect(),
         })
         .collect();
 
     let exports = graph
         .component_exports
         .iter()
-        .map(|(iface, info)| JsonExport {
-            interface: iface.clone(),
+        .map(|info| JsonExport {
+            interface: info.name.clone(),
+            kind: (info.kind != ExternKind::Instance).then(|| info.kind.to_string()),
             source_instance: info.source_instance,
             fingerprint: info.fingerprint.clone(),
+            alias_chain: info.alias_chain.clone(),
             interface_type: match &info.ty {
                 Some(InternedId::Interface(id)) => Some(InterfaceTypeJson::from_ir(
                     arena.lookup_interface(*id),
@@ -51,27 +98,319 @@ fn generate_json_model(graph: &CompositionGraph) -> JsonCompositionGraph {
         })
         .collect();
 
+    let core = graph
+        .core
+        .iter()
+        .map(|(&component_num, core)| JsonCoreStructure {
+            component_num,
+            modules: core
+                .modules
+                .iter()
+                .map(|m| JsonCoreModule {
+                    index: m.index,
+                    name: m.name.clone(),
+                })
+                .collect(),
+            instances: core
+                .instances
+                .iter()
+                .map(|i| JsonCoreInstance {
+                    index: i.index,
+                    name: i.name.clone(),
+                    kind: match &i.kind {
+                        CoreInstanceKind::Instantiate { module_index, args } => {
+                            JsonCoreInstanceKind::Instantiate {
+                                module: *module_index,
+                                args: args
+                                    .iter()
+                                    .map(|(name, instance)| JsonCoreInstanceArg {
+                                        name: name.clone(),
+                                        instance: *instance,
+                                    })
+                                    .collect(),
+                            }
+                        }
+                        CoreInstanceKind::FromExports(names) => JsonCoreInstanceKind::FromExports {
+                            exports: names.clone(),
+                        },
+                    },
+                })
+                .collect(),
+        })
+        .collect();
+
+    let metadata = graph
+        .metadata
+        .iter()
+        .map(|(&component_num, m)| JsonComponentMetadata {
+            component_num,
+            name: m.name.clone(),
+            authors: m.authors.clone(),
+            description: m.description.clone(),
+            licenses: m.licenses.clone(),
+            source: m.source.clone(),
+            homepage: m.homepage.clone(),
+            revision: m.revision.clone(),
+            version: m.version.clone(),
+            producers: m.producers.clone(),
+        })
+        .collect();
+
+    let warnings = diagnose(graph, &LintLevels::default())
+        .into_iter()
+        .map(|d| JsonWarning {
+            category: d.category,
+            level: d.level,
+            message: d.message,
+        })
+        .collect();
+
     JsonCompositionGraph {
-        version: 2,
+        version: JSON_VERSION,
         nodes,
         exports,
+        core,
+        metadata,
+        warnings,
+        composed_by: composer(graph),
     }
 }
 
+/// Serialize the imports and exports of a plain core module to JSON.
+pub fn generate_module_json(module: &ModuleInterface, pretty: bool) -> Result<String> {
+    let items = |items: &[ModuleItem]| -> Vec<JsonModuleItem> {
+        items
+            .iter()
+            .map(|item| JsonModuleItem {
+                name: item.name.clone(),
+                kind: item.kind.clone(),
+                signature: item.signature.clone(),
+            })
+            .collect()
+    };
+    let model = JsonModule {
+        kind: "core-module".to_string(),
+        imports: module
+            .imports
+            .iter()
+            .map(|(name, imports)| (name.clone(), items(imports)))
+            .collect(),
+        exports: items(&module.exports),
+    };
+    to_json(&model, pretty)
+}
+
 #[derive(Deserialize, Serialize)]
 pub struct JsonCompositionGraph {
     pub version: u32,
     pub nodes: Vec<JsonNode>,
     pub exports: Vec<JsonExport>,
+
+    /// Core modules and core instances inside each component definition
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub core: Vec<JsonCoreStructure>,
+
+    /// Authorship and licensing metadata of the root and nested components
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub metadata: Vec<JsonComponentMetadata>,
+
+    /// Findings of the lint checks at their default levels (derived;
+    /// ignored when reading a graph back)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<JsonWarning>,
+
+    /// The tool that built the composition, when it can be identified.
+    /// Derived from the other fields, so it is ignored when reading JSON.
+    #[serde(default, skip_serializing_if = "Option::is_none", skip_deserializing)]
+    pub composed_by: Option<Composer>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct JsonWarning {
+    pub category: Category,
+    pub level: LintLevel,
+    pub message: String,
+}
+
+/// A core module, which has no composition graph to serialize
+#[derive(Deserialize, Serialize)]
+pub struct JsonModule {
+    /// Always `"core-module"`, telling this apart from a graph
+    pub kind: String,
+    /// Imports grouped by the module name they are imported from
+    pub imports: BTreeMap<String, Vec<JsonModuleItem>>,
+    pub exports: Vec<JsonModuleItem>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct JsonModuleItem {
+    pub name: String,
+    pub kind: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct JsonComponentMetadata {
+    /// `component_num` of the nested component, or absent for the root component
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub component_num: Option<u32>,
+    /// The component's own name, usually its package name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authors: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub licenses: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub homepage: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revision: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// `producers` section contents: field -> tool name -> version
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub producers: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct JsonCoreStructure {
+    /// `component_num` of the component definition containing these items
+    pub component_num: u32,
+    pub modules: Vec<JsonCoreModule>,
+    pub instances: Vec<JsonCoreInstance>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct JsonCoreModule {
+    pub index: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct JsonCoreInstance {
+    pub index: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(flatten)]
+    pub kind: JsonCoreInstanceKind,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum JsonCoreInstanceKind {
+    /// Instantiation of `module`; each arg maps an import module name to a core instance
+    Instantiate {
+        module: u32,
+        args: Vec<JsonCoreInstanceArg>,
+    },
+    /// A bag of core items exported under the given names
+    FromExports { exports: Vec<String> },
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct JsonCoreInstanceArg {
+    pub name: String,
+    pub instance: u32,
 }
 
 #[derive(Deserialize, Serialize)]
 pub struct JsonNode {
     pub id: u32,
     pub name: String,
+
+    /// ID that survives rebuilding the composition (see
+    /// [`CompositionGraph::stable_ids`]; ignored when reading a graph back)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub stable_id: String,
+
+    /// Label resolved for an unnamed instance (see `model::naming`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+
     pub component_index: u32,
     pub component_num: u32,
     pub imports: Vec<JsonInterfaceConnection>,
+
+    /// Package the instantiated component was published as, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package: Option<String>,
+
+    /// Version of that package, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package_version: Option<String>,
+
+    /// Name section name of the instantiated component definition, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub component_name: Option<String>,
+
+    /// Number of component definitions enclosing the instance (0 at the root)
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub depth: u32,
+
+    /// Size in bytes of the instantiated component, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+
+    /// `sha256:<hex>` digest of the instantiated component, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub digest: Option<String>,
+
+    /// Non-default wasm features the instantiated component's core modules
+    /// use, e.g. `threads`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub features: Vec<String>,
+
+    /// Whether instantiating the component runs start logic
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub runs_start: bool,
+
+    /// Imports the instantiated component declares, if its definition is
+    /// known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub declared_imports: Option<Vec<String>>,
+
+    /// Number of identical instances merged into this one (1 unless
+    /// duplicates were collapsed)
+    #[serde(default = "one", skip_serializing_if = "is_one")]
+    pub copies: u32,
+
+    /// Summary of the tools that produced this instance's component
+    /// (derived from `metadata`; ignored when reading a graph back)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub produced_by: Option<String>,
+
+    /// Description of this instance's component (derived from `metadata`;
+    /// ignored when reading a graph back)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Whether some export can only reach part of the host through this
+    /// instance (derived; ignored when reading a graph back)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub critical: bool,
+
+    /// Names of the exports this instance provides (derived from `exports`;
+    /// ignored when reading a graph back)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exports: Vec<String>,
+}
+
+fn is_zero(n: &u32) -> bool {
+    *n == 0
+}
+
+fn one() -> u32 {
+    1
+}
+
+fn is_one(n: &u32) -> bool {
+    *n == 1
 }
 
 #[derive(Deserialize, Serialize)]
@@ -96,6 +435,18 @@ pub struct JsonInterfaceConnection {
     /// Deterministic fingerprint of the interface type
     #[serde(skip_serializing_if = "Option::is_none")]
     pub fingerprint: Option<String>,
+
+    /// Name under which the provider offers this interface, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_export: Option<String>,
+
+    /// Kind of item passed (`func`, `value`, `type`); absent for an instance
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+
+    /// Nested instances aliased on the way from the provider, outermost first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alias_chain: Vec<String>,
 }
 
 impl JsonInterfaceConnection {
@@ -110,6 +461,9 @@ impl JsonInterfaceConnection {
                 .as_ref()
                 .map(|t| InterfaceTypeJson::from_ir(t, arena)),
             fingerprint: ic.fingerprint.clone(),
+            source_export: ic.source_export.clone(),
+            kind: (ic.kind != ExternKind::Instance).then(|| ic.kind.to_string()),
+            alias_chain: ic.alias_chain.clone(),
         }
     }
 }
@@ -143,6 +497,11 @@ impl InterfaceTypeJson {
 
 #[derive(Deserialize, Serialize)]
 pub struct FuncSignatureJson {
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_async: bool,
+    /// Parameter names, parallel to `params` (omitted when unknown)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub param_names: Vec<String>,
     pub params: Vec<ValueTypeJson>,
     pub results: Vec<ValueTypeJson>,
 }
@@ -150,6 +509,8 @@ pub struct FuncSignatureJson {
 impl FuncSignatureJson {
     fn from_ir(f: &FuncSignature, arena: &TypeArena) -> Self {
         FuncSignatureJson {
+            is_async: f.is_async,
+            param_names: f.param_names.clone(),
             params: f
                 .params
                 .iter()
@@ -290,17 +651,23 @@ impl ValueTypeJson {
 #[derive(Deserialize, Serialize)]
 pub struct JsonExport {
     pub interface: String,
+    /// Kind of item exported (`func`, `value`, `type`); absent for an instance
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
     pub source_instance: u32,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub interface_type: Option<InterfaceTypeJson>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub fingerprint: Option<String>,
+    /// Nested instances aliased on the way from the provider, outermost first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alias_chain: Vec<String>,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::model::{ComponentNode, InterfaceConnection};
+    use crate::model::{ComponentNode, ExternKind, InterfaceConnection};
 
     fn test_graph() -> CompositionGraph {
         let mut graph = CompositionGraph::new();
@@ -312,6 +679,9 @@ mod tests {
             is_host_import: true,
             interface_type: None,
             fingerprint: None,
+            source_export: None,
+            kind: ExternKind::Instance,
+            alias_chain: Vec::new(),
         });
         graph.add_node(1, srv);
 
@@ -322,6 +692,9 @@ mod tests {
             is_host_import: false,
             interface_type: None,
             fingerprint: None,
+            source_export: None,
+            kind: ExternKind::Instance,
+            alias_chain: Vec::new(),
         });
         mw.add_import(InterfaceConnection {
             interface_name: "wasi:logging/log@0.1.0".to_string(),
@@ -329,6 +702,9 @@ mod tests {
             is_host_import: true,
             interface_type: None,
             fingerprint: None,
+            source_export: None,
+            kind: ExternKind::Instance,
+            alias_chain: Vec::new(),
         });
         graph.add_node(2, mw);
 
@@ -348,6 +724,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_critical_flag() {
+        let model = generate_json_model(&test_graph());
+        let critical: Vec<&str> = model
+            .nodes
+            .iter()
+            .filter(|n| n.critical)
+            .map(|n| n.name.as_str())
+            .collect();
+        // middleware reaches the host's handler only through srv
+        assert_eq!(critical, vec!["srv"]);
+        let json = generate_json(&test_graph(), false).unwrap();
+        assert_eq!(json.matches("\"critical\":true").count(), 1);
+    }
+
     #[test]
     fn test_empty_graph_json() {
         let graph = CompositionGraph::new();
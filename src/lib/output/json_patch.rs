@@ -0,0 +1,188 @@
+//! RFC 6902 JSON Patch between two graphs.
+//!
+//! The patch is computed on the JSON form produced by
+//! [`generate_json`](super::json::generate_json), so applying it to the old
+//! graph's JSON yields exactly the new graph's JSON.  Storing the first graph
+//! plus a chain of patches is usually far smaller than storing every graph.
+
+use super::json::{generate_json_model, to_json};
+use crate::error::Result;
+use crate::model::CompositionGraph;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// One RFC 6902 operation.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOp {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+}
+
+/// Compute the patch that transforms `old` into `new`.
+pub fn graph_patch(old: &CompositionGraph, new: &CompositionGraph) -> Vec<PatchOp> {
+    let old = serde_json::to_value(generate_json_model(old)).expect("graph model serializes");
+    let new = serde_json::to_value(generate_json_model(new)).expect("graph model serializes");
+    diff_values(&old, &new)
+}
+
+/// Serialize the patch between `old` and `new` as a JSON array.
+pub fn generate_json_patch(
+    old: &CompositionGraph,
+    new: &CompositionGraph,
+    pretty: bool,
+) -> Result<String> {
+    to_json(&graph_patch(old, new), pretty)
+}
+
+/// Compute the patch that transforms the JSON value `old` into `new`.
+///
+/// Objects are compared key by key and arrays element by element; elements
+/// past the end of the shorter array are removed (last first) or appended.
+pub fn diff_values(old: &Value, new: &Value) -> Vec<PatchOp> {
+    let mut ops = Vec::new();
+    diff_at(&mut String::new(), old, new, &mut ops);
+    ops
+}
+
+fn diff_at(path: &mut String, old: &Value, new: &Value, ops: &mut Vec<PatchOp>) {
+    match (old, new) {
+        (Value::Object(a), Value::Object(b)) => diff_objects(path, a, b, ops),
+        (Value::Array(a), Value::Array(b)) => {
+            let common = a.len().min(b.len());
+            for i in 0..common {
+                with_segment(path, &i.to_string(), |path| {
+                    diff_at(path, &a[i], &b[i], ops)
+                });
+            }
+            for i in (common..a.len()).rev() {
+                ops.push(PatchOp::Remove {
+                    path: format!("{}/{}", path, i),
+                });
+            }
+            for value in &b[common..] {
+                ops.push(PatchOp::Add {
+                    path: format!("{}/-", path),
+                    value: value.clone(),
+                });
+            }
+        }
+        _ if old == new => {}
+        _ => ops.push(PatchOp::Replace {
+            path: path.clone(),
+            value: new.clone(),
+        }),
+    }
+}
+
+fn diff_objects(
+    path: &mut String,
+    old: &Map<String, Value>,
+    new: &Map<String, Value>,
+    ops: &mut Vec<PatchOp>,
+) {
+    for (key, a) in old {
+        with_segment(path, key, |path| match new.get(key) {
+            Some(b) => diff_at(path, a, b, ops),
+            None => ops.push(PatchOp::Remove { path: path.clone() }),
+        });
+    }
+    for (key, b) in new {
+        if !old.contains_key(key) {
+            with_segment(path, key, |path| {
+                ops.push(PatchOp::Add {
+                    path: path.clone(),
+                    value: b.clone(),
+                })
+            });
+        }
+    }
+}
+
+/// Run `f` with `segment` (escaped per RFC 6901) appended to `path`.
+fn with_segment(path: &mut String, segment: &str, f: impl FnOnce(&mut String)) {
+    let len = path.len();
+    path.push('/');
+    path.push_str(&segment.replace('~', "~0").replace('/', "~1"));
+    f(path);
+    path.truncate(len);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::ComponentNode;
+    use crate::test_utils::*;
+    use serde_json::json;
+
+    /// Minimal RFC 6902 applier covering the operations we emit.
+    fn apply(doc: &mut Value, ops: &[PatchOp]) {
+        for op in ops {
+            let (path, value) = match op {
+                PatchOp::Add { path, value } | PatchOp::Replace { path, value } => {
+                    (path, Some(value.clone()))
+                }
+                PatchOp::Remove { path } => (path, None),
+            };
+            let (parent, last) = path.rsplit_once('/').unwrap();
+            let last = last.replace("~1", "/").replace("~0", "~");
+            let target = doc.pointer_mut(parent).unwrap();
+            match (target, op) {
+                (Value::Array(arr), PatchOp::Add { .. }) if last == "-" => arr.push(value.unwrap()),
+                (Value::Array(arr), PatchOp::Remove { .. }) => {
+                    arr.remove(last.parse().unwrap());
+                }
+                (Value::Array(arr), _) => arr[last.parse::<usize>().unwrap()] = value.unwrap(),
+                (Value::Object(obj), PatchOp::Remove { .. }) => {
+                    obj.remove(&last);
+                }
+                (Value::Object(obj), _) => {
+                    obj.insert(last, value.unwrap());
+                }
+                (other, _) => panic!("cannot apply {:?} to {}", op, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_identical_graphs_have_empty_patch() {
+        assert!(graph_patch(&long_chain_graph(), &long_chain_graph()).is_empty());
+    }
+
+    #[test]
+    fn test_patch_applies_to_old_graph() {
+        let old = simple_chain_graph();
+        let mut new = simple_chain_graph();
+        new.add_node(42, orphan_node());
+        new.nodes.get_mut(&1).unwrap().imports.pop();
+        new.add_node(7, ComponentNode::new("$renamed".to_string(), 7, 7));
+
+        let ops = graph_patch(&old, &new);
+        assert!(!ops.is_empty());
+
+        let mut doc = serde_json::to_value(generate_json_model(&old)).unwrap();
+        apply(&mut doc, &ops);
+        assert_eq!(
+            doc,
+            serde_json::to_value(generate_json_model(&new)).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_value_diff_ops() {
+        let old = json!({ "a/b": 1, "gone": true, "list": [1, 2, 3] });
+        let new = json!({ "a/b": 2, "list": [1, 5], "new": null });
+        let ops = serde_json::to_value(diff_values(&old, &new)).unwrap();
+        assert_eq!(
+            ops,
+            json!([
+                { "op": "replace", "path": "/a~1b", "value": 2 },
+                { "op": "remove", "path": "/gone" },
+                { "op": "replace", "path": "/list/1", "value": 5 },
+                { "op": "remove", "path": "/list/2" },
+                { "op": "add", "path": "/new", "value": null },
+            ])
+        );
+    }
+}
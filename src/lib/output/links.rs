@@ -0,0 +1,304 @@
+//! OSC 8 terminal hyperlinks.
+//!
+//! Terminals that understand the OSC 8 escape sequence render the wrapped
+//! text as a clickable link.  Link targets are produced from user-supplied
+//! URL templates so that interface names can point at their WIT
+//! documentation or registry page, and instance names at whatever internal
+//! catalogue describes the component.
+
+/// URL templates used to turn diagram labels into terminal hyperlinks.
+///
+/// Interface templates may use the following placeholders, all taken from a
+/// fully-qualified interface name such as `wasi:http/handler@0.3.0`:
+///
+/// - `{interface}` — the full name (`wasi:http/handler@0.3.0`)
+/// - `{namespace}` — `wasi`
+/// - `{package}` — `http`
+/// - `{name}` — `handler`
+/// - `{version}` — `0.3.0` (empty when unversioned)
+///
+/// Node templates support `{name}`, the instance's display label, and the
+/// package the instance was built from (see [`ComponentNode::package`]):
+///
+/// - `{package}` — `my:srv`
+/// - `{version}` — `1.2.0` (empty when unversioned)
+///
+/// A node template that uses `{package}` produces no link for instances
+/// whose package is unknown.
+///
+/// Substituted values are percent-encoded, so a name containing `/`, `:`,
+/// `@` or spaces stays within the part of the URL its placeholder is in.
+///
+/// [`ComponentNode::package`]: crate::model::ComponentNode::package
+#[derive(Debug, Clone, Default)]
+pub struct LinkTemplates {
+    /// Template applied to interface names.
+    pub interface: Option<String>,
+    /// Template applied to component instance names.
+    pub node: Option<String>,
+}
+
+impl LinkTemplates {
+    /// Expand the interface template for a fully-qualified interface name.
+    pub fn interface_url(&self, full_name: &str) -> Option<String> {
+        let template = self.interface.as_ref()?;
+        let parts = InterfaceParts::parse(full_name);
+        Some(
+            template
+                .replace("{interface}", &percent_encode(full_name))
+                .replace("{namespace}", &percent_encode(parts.namespace))
+                .replace("{package}", &percent_encode(parts.package))
+                .replace("{name}", &percent_encode(parts.name))
+                .replace("{version}", &percent_encode(parts.version)),
+        )
+    }
+
+    /// Expand the node template for an instance display label and, if
+    /// known, its `package@version` ID.
+    pub fn node_url(&self, name: &str, package_id: Option<&str>) -> Option<String> {
+        let template = self.node.as_ref()?;
+        let (package, version) = match package_id {
+            Some(id) => id.split_once('@').unwrap_or((id, "")),
+            None if template.contains("{package}") => return None,
+            None => ("", ""),
+        };
+        Some(
+            template
+                .replace("{name}", &percent_encode(name))
+                .replace("{package}", &percent_encode(package))
+                .replace("{version}", &percent_encode(version)),
+        )
+    }
+}
+
+/// Percent-encode every byte of `value` outside the RFC 3986 unreserved set.
+fn percent_encode(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
+
+/// The components of a `namespace:package/name@version` interface name.
+///
+/// Missing components are empty strings, so a bare name like `"simple"`
+/// parses as `name = "simple"` with everything else empty.
+struct InterfaceParts<'a> {
+    namespace: &'a str,
+    package: &'a str,
+    name: &'a str,
+    version: &'a str,
+}
+
+impl<'a> InterfaceParts<'a> {
+    fn parse(full_name: &'a str) -> Self {
+        let (path, version) = full_name.split_once('@').unwrap_or((full_name, ""));
+        let (namespace, rest) = path.split_once(':').unwrap_or(("", path));
+        let (package, name) = rest.split_once('/').unwrap_or(("", rest));
+        Self {
+            namespace,
+            package,
+            name,
+            version,
+        }
+    }
+}
+
+/// Whether the terminal described by the environment renders OSC 8
+/// hyperlinks.
+///
+/// There is no way to query a terminal for OSC 8 support, so this follows
+/// the usual heuristics: `FORCE_HYPERLINK` wins, `TERM=dumb` never links,
+/// and otherwise the terminal must identify itself as one known to
+/// support them.  Terminals that don't understand OSC 8 would print the
+/// escape sequences as garbage, so unknown terminals get plain text.
+pub fn supports_hyperlinks(env: impl Fn(&str) -> Option<String>) -> bool {
+    if let Some(force) = env("FORCE_HYPERLINK") {
+        return force != "0";
+    }
+    let term = env("TERM").unwrap_or_default();
+    if term == "dumb" {
+        return false;
+    }
+    if [
+        "DOMTERM",
+        "WT_SESSION",
+        "KONSOLE_VERSION",
+        "KITTY_WINDOW_ID",
+    ]
+    .iter()
+    .any(|var| env(var).is_some())
+    {
+        return true;
+    }
+    // VTE-based terminals (GNOME Terminal, Tilix, ...) since 0.50.
+    if env("VTE_VERSION")
+        .and_then(|v| v.parse::<u32>().ok())
+        .is_some_and(|v| v >= 5000)
+    {
+        return true;
+    }
+    let program = env("TERM_PROGRAM").unwrap_or_default();
+    matches!(
+        program.as_str(),
+        "iTerm.app" | "WezTerm" | "vscode" | "ghostty" | "Hyper" | "rio"
+    ) || ["kitty", "alacritty", "ghostty", "wezterm", "foot"]
+        .iter()
+        .any(|t| term.contains(t))
+}
+
+/// Wrap `text` in an OSC 8 hyperlink pointing at `url`.
+///
+/// Control characters are dropped from both, so neither can end the escape
+/// sequence early or smuggle in one of its own.
+#[cfg(feature = "ascii")]
+pub(crate) fn osc8(url: &str, text: &str) -> String {
+    let clean = |s: &str| s.chars().filter(|c| !c.is_control()).collect::<String>();
+    format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", clean(url), clean(text))
+}
+
+/// Strip OSC escape sequences (as emitted by [`osc8`]) from `s`.
+///
+/// Used when measuring the on-screen width of a line: the escape sequences
+/// occupy no terminal columns.
+#[cfg(feature = "ascii")]
+pub(crate) fn strip_osc(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' && chars.peek() == Some(&']') {
+            // Skip until the string terminator: ESC '\' or BEL.
+            while let Some(c) = chars.next() {
+                if c == '\x07' || (c == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                    break;
+                }
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interface_url_placeholders() {
+        let links = LinkTemplates {
+            interface: Some("https://docs/{namespace}/{package}/{name}?v={version}".into()),
+            node: None,
+        };
+        assert_eq!(
+            links.interface_url("wasi:http/handler@0.3.0").unwrap(),
+            "https://docs/wasi/http/handler?v=0.3.0"
+        );
+        assert_eq!(
+            links.interface_url("simple").unwrap(),
+            "https://docs///simple?v="
+        );
+        assert!(links.node_url("srv", None).is_none());
+    }
+
+    #[test]
+    fn test_node_url() {
+        let links = LinkTemplates {
+            interface: None,
+            node: Some("https://catalog/{name}".into()),
+        };
+        assert_eq!(
+            links.node_url("mdl-a", None).unwrap(),
+            "https://catalog/mdl-a"
+        );
+        assert!(links.interface_url("wasi:http/handler").is_none());
+    }
+
+    #[test]
+    fn test_node_url_package() {
+        let links = LinkTemplates {
+            interface: None,
+            node: Some("https://registry/{package}/{version}".into()),
+        };
+        assert_eq!(
+            links.node_url("srv", Some("my:srv@1.2.0")).unwrap(),
+            "https://registry/my%3Asrv/1.2.0"
+        );
+        assert_eq!(
+            links.node_url("srv", Some("my:srv")).unwrap(),
+            "https://registry/my%3Asrv/"
+        );
+        assert!(links.node_url("srv", None).is_none());
+    }
+
+    #[test]
+    fn test_supports_hyperlinks() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |key: &str| {
+                vars.iter()
+                    .find(|(k, _)| *k == key)
+                    .map(|(_, v)| v.to_string())
+            }
+        };
+        assert!(!supports_hyperlinks(env(&[])));
+        assert!(!supports_hyperlinks(env(&[("TERM", "xterm-256color")])));
+        assert!(supports_hyperlinks(env(&[("TERM_PROGRAM", "WezTerm")])));
+        assert!(supports_hyperlinks(env(&[("TERM", "xterm-kitty")])));
+        assert!(supports_hyperlinks(env(&[("VTE_VERSION", "7600")])));
+        assert!(!supports_hyperlinks(env(&[("VTE_VERSION", "4800")])));
+        assert!(!supports_hyperlinks(env(&[
+            ("TERM", "dumb"),
+            ("WT_SESSION", "1")
+        ])));
+        assert!(supports_hyperlinks(env(&[
+            ("TERM", "dumb"),
+            ("FORCE_HYPERLINK", "1")
+        ])));
+        assert!(!supports_hyperlinks(env(&[
+            ("TERM_PROGRAM", "iTerm.app"),
+            ("FORCE_HYPERLINK", "0")
+        ])));
+    }
+
+    #[test]
+    #[cfg(feature = "ascii")]
+    fn test_strip_osc_round_trip() {
+        let linked = format!("[{}] ──> x", osc8("https://example.com", "srv"));
+        assert_eq!(strip_osc(&linked), "[srv] ──> x");
+        assert_eq!(strip_osc("plain ── text"), "plain ── text");
+    }
+
+    #[test]
+    fn test_hostile_names_are_encoded() {
+        let links = LinkTemplates {
+            interface: Some("https://docs/{name}?v={version}".into()),
+            node: Some("https://catalog/{name}".into()),
+        };
+        let hostile = "evil\x1b]8;;https://attacker\x07 a:b/c@d";
+        assert_eq!(
+            links.node_url(hostile, None).unwrap(),
+            "https://catalog/evil%1B%5D8%3B%3Bhttps%3A%2F%2Fattacker%07%20a%3Ab%2Fc%40d"
+        );
+        assert_eq!(
+            links.interface_url("wasi:http/x y@1\x07").unwrap(),
+            "https://docs/x%20y?v=1%07"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "ascii")]
+    fn test_osc8_drops_control_characters() {
+        let linked = osc8("https://x/\x1b\\\x07", "srv\x1b]8;;https://attacker\x07");
+        assert_eq!(
+            linked,
+            "\x1b]8;;https://x/\\\x1b\\srv]8;;https://attacker\x1b]8;;\x1b\\"
+        );
+        assert_eq!(strip_osc(&linked), "srv]8;;https://attacker");
+    }
+}
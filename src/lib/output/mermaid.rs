@@ -1,8 +1,15 @@
-use crate::model::{short_interface_name, CompositionGraph};
-use crate::output::{
-    build_all_interfaces_view, build_full_view, DetailLevel, Direction, SymbolMap,
+use crate::analysis::composer::composer;
+use crate::analysis::paths::Hop;
+use crate::model::{
+    short_interface_name, ComponentNode, CompositionGraph, ModuleInterface, SYNTHETIC_COMPONENT,
 };
-use crate::{find_chain_interfaces, get_chain_for};
+use crate::output::diagram::{
+    build_all_interfaces_view, build_dependency_view, build_full_view, drawn_chain,
+    ConnectionsView, DiagramNode, MissingImport, SymbolMap,
+};
+use crate::output::{host_group, human_size, DetailLevel, Direction, RenderOptions};
+use crate::{chain_interfaces_matching, exported_interfaces};
+use std::collections::{BTreeMap, BTreeSet};
 
 /// Generate a Mermaid diagram from the composition graph
 pub fn generate_mermaid(
@@ -11,10 +18,31 @@ pub fn generate_mermaid(
     direction: Direction,
     show_types: bool,
 ) -> String {
-    match detail {
-        DetailLevel::HandlerChain => generate_handler_chain(graph, direction, show_types),
-        DetailLevel::AllInterfaces => generate_all_interfaces(graph, direction, show_types),
-        DetailLevel::Full => generate_full(graph, direction, show_types),
+    generate_mermaid_with_options(graph, detail, direction, &RenderOptions::new(show_types))
+}
+
+/// Generate a Mermaid diagram from the composition graph using the given
+/// [`RenderOptions`].  Hyperlinks and the legend apply to ASCII output only
+/// and are ignored.
+pub fn generate_mermaid_with_options(
+    graph: &CompositionGraph,
+    detail: DetailLevel,
+    direction: Direction,
+    opts: &RenderOptions,
+) -> String {
+    let output = match detail {
+        DetailLevel::HandlerChain => generate_handler_chain(graph, direction, opts),
+        DetailLevel::Chains => generate_chains(graph, direction, opts),
+        DetailLevel::AllInterfaces => generate_all_interfaces(graph, direction, opts),
+        DetailLevel::Full => generate_full(graph, direction, opts),
+    };
+    match composer(graph) {
+        Some(composer) => format!(
+            "---\ntitle: \"Composed by {}\"\n---\n{}",
+            composer.summary(),
+            output
+        ),
+        None => output,
     }
 }
 
@@ -86,12 +114,13 @@ const INIT_DIRECTIVE: &str = "%%{init: {'flowchart': {'wrappingWidth': 600}}}%%\
 fn generate_handler_chain(
     graph: &CompositionGraph,
     direction: Direction,
-    show_types: bool,
+    opts: &RenderOptions,
 ) -> String {
+    let show_types = opts.show_types;
     let mut output = String::from(INIT_DIRECTIVE);
     output.push_str(&format!("graph {}\n", direction.to_mermaid()));
 
-    let chain_interfaces = find_chain_interfaces(graph);
+    let chain_interfaces = chain_interfaces_matching(graph, opts.interface.as_deref());
 
     if chain_interfaces.is_empty() {
         output.push_str("    empty[\"No middleware chains found\"]\n");
@@ -104,10 +133,10 @@ fn generate_handler_chain(
     // "Middleware Chains" subgraph
     output.push_str("    subgraph composition[\"Service Chains\"]\n");
     for iface in &chain_interfaces {
-        for &idx in &get_chain_for(graph, iface) {
+        for &idx in &drawn_chain(graph, iface, opts) {
             if let Some(node) = graph.get_node(idx) {
                 let id = sanitize_for_mermaid(&node.name);
-                output.push_str(&format!("        {}[\"{}\"]\n", id, node.display_label()));
+                output.push_str(&format!("        {}[\"{}\"]\n", id, chain_node_label(node)));
             }
         }
     }
@@ -115,17 +144,16 @@ fn generate_handler_chain(
 
     // Edges per chain
     for iface in &chain_interfaces {
-        let chain = get_chain_for(graph, iface);
+        let chain = drawn_chain(graph, iface, opts);
         if chain.is_empty() {
             continue;
         }
-        let short = short_interface_name(iface);
+        let short = opts.labels.label(iface);
 
         let export_sym: String = show_types
             .then(|| {
                 graph
-                    .component_exports
-                    .get(iface.as_str())
+                    .export(iface)
                     .and_then(|info| symbols.symbol_for_export(info, &graph.arena))
                     .map(str::to_string)
             })
@@ -172,6 +200,101 @@ fn generate_handler_chain(
         }
     }
 
+    let drawn: Vec<&str> = chain_interfaces
+        .iter()
+        .flat_map(|iface| drawn_chain(graph, iface, opts))
+        .filter_map(|idx| graph.get_node(idx))
+        .map(|n| n.name.as_str())
+        .collect();
+    output.push_str(&node_tooltips(graph, drawn));
+
+    // Key subgraph — shared across all chains
+    output.push_str(&render_key(&symbols));
+
+    output
+}
+
+/// Generate one subgraph per exported instance interface holding that
+/// interface's chain.  Instances are drawn once per chain they belong to,
+/// under IDs prefixed with the chain's, so that flows sharing an instance
+/// stay apart.
+fn generate_chains(graph: &CompositionGraph, direction: Direction, opts: &RenderOptions) -> String {
+    let show_types = opts.show_types;
+    let mut output = format!("{INIT_DIRECTIVE}graph {}\n", direction.to_mermaid());
+
+    let mut interfaces = exported_interfaces(graph);
+    if let Some(filter) = &opts.interface {
+        interfaces.retain(|iface| iface.contains(filter.as_str()));
+    }
+
+    let mut symbols = SymbolMap::new();
+    let mut drawn = Vec::new();
+    for iface in &interfaces {
+        let lane = sanitize_for_mermaid(iface);
+        let nodes: Vec<(String, &ComponentNode)> = drawn_chain(graph, iface, opts)
+            .into_iter()
+            .filter_map(|idx| graph.get_node(idx))
+            .map(|n| (format!("{}_{}", lane, sanitize_for_mermaid(&n.name)), n))
+            .collect();
+        let Some((first_id, _)) = nodes.first() else {
+            continue;
+        };
+
+        // Separator between chains
+        if !drawn.is_empty() {
+            output.push('\n');
+        }
+        output.push_str(&format!("    subgraph {}[\"{}\"]\n", lane, iface));
+        for (id, node) in &nodes {
+            output.push_str(&format!("        {}[\"{}\"]\n", id, chain_node_label(node)));
+        }
+        output.push_str("    end\n");
+
+        let short = opts.labels.label(iface);
+        let export_sym: String = show_types
+            .then(|| {
+                graph
+                    .export(iface)
+                    .and_then(|info| symbols.symbol_for_export(info, &graph.arena))
+                    .map(str::to_string)
+            })
+            .flatten()
+            .unwrap_or_default();
+        output.push_str(&format!(
+            "    export_{}([\"Export: {}{}\"]) --> {}\n",
+            lane, short, export_sym, first_id
+        ));
+
+        for pair in nodes.windows(2) {
+            let [(from_id, from_node), (to_id, _)] = pair else {
+                continue;
+            };
+            let conn_sym: String = show_types
+                .then(|| {
+                    from_node
+                        .imports
+                        .iter()
+                        .find(|c| &c.interface_name == iface)
+                        .and_then(|c| symbols.symbol_for_conn(c, &graph.arena))
+                        .map(str::to_string)
+                })
+                .flatten()
+                .unwrap_or_default();
+            output.push_str(&format!(
+                "    {} -->|\"{}{}\"| {}\n",
+                from_id, short, conn_sym, to_id
+            ));
+        }
+
+        drawn.extend(nodes.iter().map(|(id, n)| (id.clone(), n.name.as_str())));
+    }
+
+    if drawn.is_empty() {
+        output.push_str("    empty[\"No exported interfaces found\"]\n");
+        return output;
+    }
+
+    output.push_str(&node_tooltips_by_id(graph, drawn));
     // Key subgraph — shared across all chains
     output.push_str(&render_key(&symbols));
 
@@ -182,9 +305,18 @@ fn generate_handler_chain(
 fn generate_all_interfaces(
     graph: &CompositionGraph,
     direction: Direction,
-    show_types: bool,
+    opts: &RenderOptions,
 ) -> String {
-    let view = build_all_interfaces_view(graph, show_types);
+    let show_types = opts.show_types;
+    let mut view = build_all_interfaces_view(graph, show_types, opts.show_synthetic);
+    if !opts.labels.is_default() {
+        view.relabel(&opts.labels);
+    }
+    if opts.hide_host {
+        view.drop_host_imports();
+    } else if opts.group_host {
+        view.group_host_imports();
+    }
     let mut output = format!("{INIT_DIRECTIVE}graph {}\n", direction.to_mermaid());
 
     if view.nodes.is_empty() {
@@ -198,21 +330,28 @@ fn generate_all_interfaces(
             output.push_str(&format!(
                 "        {}[\"{}\"]\n",
                 sanitize_for_mermaid(name),
-                short_interface_name(name)
+                opts.labels.label(name)
             ));
         }
         output.push_str("    end\n\n");
     }
 
     output.push_str("    subgraph composition[\"Component Instances\"]\n");
-    for node in &view.nodes {
-        output.push_str(&format!(
-            "        {}[\"{}\"]\n",
+    output.push_str(&node_lines(&view.nodes, opts.group_namespaces, |node| {
+        let suffix = if node.is_synthetic {
+            " (synthetic)"
+        } else {
+            ""
+        };
+        format!(
+            "{}[\"{}{}\"]",
             sanitize_for_mermaid(&node.name),
-            node.display
-        ));
-    }
+            display_with_copies(node),
+            suffix
+        )
+    }));
     output.push_str("    end\n\n");
+    output.push_str(&node_classes(&view.nodes));
 
     let mut symbols = SymbolMap::new();
 
@@ -236,6 +375,8 @@ fn generate_all_interfaces(
             ));
         }
     }
+    output.push_str(&missing_edges(&view.missing));
+    output.push_str(&highlight_styles(graph, &view, &opts.highlight));
 
     output.push('\n');
     for exp in &view.exports {
@@ -253,30 +394,186 @@ fn generate_all_interfaces(
         ));
     }
 
+    output.push_str(&node_tooltips(
+        graph,
+        view.nodes.iter().map(|n| n.name.as_str()),
+    ));
     output.push_str(&render_key(&symbols));
 
     output
 }
 
+/// Styles drawing the edges `view` has for `hops`, and the nodes they
+/// connect, in bold orange.  Edges come first in the diagram, so an edge's
+/// index in `view.edges` is its Mermaid link index.
+fn highlight_styles(graph: &CompositionGraph, view: &ConnectionsView, hops: &[Hop]) -> String {
+    let name = |id: u32| graph.get_node(id).map(|n| n.name.as_str());
+    let mut links = Vec::new();
+    let mut nodes = BTreeSet::new();
+    for (i, edge) in view.edges.iter().enumerate() {
+        let on_path = hops.iter().any(|hop| {
+            let from = match hop.provider_id {
+                Some(provider) => !edge.is_dashed && name(provider) == Some(&edge.from_name),
+                None => {
+                    edge.is_dashed
+                        && (edge.from_name == hop.interface
+                            || host_group(&hop.interface) == Some(edge.from_name.as_str()))
+                }
+            };
+            from && name(hop.importer_id) == Some(&edge.to_name)
+                && (edge.is_dashed || edge.interface_name == hop.interface)
+        });
+        if on_path {
+            links.push(i.to_string());
+            nodes.insert(sanitize_for_mermaid(&edge.from_name));
+            nodes.insert(sanitize_for_mermaid(&edge.to_name));
+        }
+    }
+    if links.is_empty() {
+        return String::new();
+    }
+    format!(
+        "    linkStyle {} stroke:#f80,stroke-width:3px\n    classDef path stroke:#f80,stroke-width:3px\n    class {} path\n",
+        links.join(","),
+        nodes.into_iter().collect::<Vec<_>>().join(",")
+    )
+}
+
+/// Definition lines for `nodes` inside a subgraph, as written by `line`.
+/// With `group`, instances sharing a namespace are wrapped in a nested
+/// subgraph per namespace, after the instances without one.
+fn node_lines(nodes: &[DiagramNode], group: bool, line: impl Fn(&DiagramNode) -> String) -> String {
+    let mut out = String::new();
+    let mut namespaces: BTreeMap<&str, Vec<&DiagramNode>> = BTreeMap::new();
+    for node in nodes {
+        match node.namespace.as_deref().filter(|_| group) {
+            Some(namespace) => namespaces.entry(namespace).or_default().push(node),
+            None => out.push_str(&format!("        {}\n", line(node))),
+        }
+    }
+    for (namespace, members) in namespaces {
+        out.push_str(&format!(
+            "        subgraph ns_{}[\"{}\"]\n",
+            sanitize_for_mermaid(namespace),
+            namespace
+        ));
+        for node in members {
+            out.push_str(&format!("            {}\n", line(node)));
+        }
+        out.push_str("        end\n");
+    }
+    out
+}
+
+/// The node's display name, suffixed with `x2` and so on when it is one of
+/// several instances of the same component.
+fn display_with_copies(node: &DiagramNode) -> String {
+    if node.copies > 1 {
+        format!("{} x{}", node.display, node.copies)
+    } else {
+        node.display.clone()
+    }
+}
+
+/// Dashed edges from red "missing" placeholders to the instances whose
+/// imports nothing is plugged in for.
+fn missing_edges(missing: &[MissingImport]) -> String {
+    if missing.is_empty() {
+        return String::new();
+    }
+    let mut out = String::new();
+    let mut ids = Vec::new();
+    for (i, m) in missing.iter().enumerate() {
+        let id = format!("missing_{}", i);
+        out.push_str(&format!(
+            "    {}[\"missing\"] -.->|\"{}\"| {}\n",
+            id,
+            m.label,
+            sanitize_for_mermaid(&m.to_name)
+        ));
+        ids.push(id);
+    }
+    out.push_str("    classDef missing stroke:#d33,color:#d33,stroke-dasharray:5 5\n");
+    out.push_str(&format!("    class {} missing\n", ids.join(",")));
+    out
+}
+
+/// Style instances nothing uses with a dashed red border, instances several
+/// others import from with a thick purple one and critical instances with a
+/// thick red one.
+fn node_classes(nodes: &[DiagramNode]) -> String {
+    let mut out = String::new();
+    for (class, style, applies) in [
+        (
+            "unused",
+            "stroke:#d33,stroke-dasharray:5 5",
+            (|n: &DiagramNode| n.unused) as fn(&DiagramNode) -> bool,
+        ),
+        ("shared", "stroke:#93c,stroke-width:3px", |n| n.shared),
+        ("critical", "stroke:#d33,stroke-width:3px", |n| n.critical),
+    ] {
+        let ids: Vec<String> = nodes
+            .iter()
+            .filter(|n| applies(n))
+            .map(|n| sanitize_for_mermaid(&n.name))
+            .collect();
+        if !ids.is_empty() {
+            out.push_str(&format!(
+                "    classDef {} {}\n    class {} {}\n\n",
+                class,
+                style,
+                ids.join(","),
+                class
+            ));
+        }
+    }
+    out
+}
+
 /// Generate a full diagram with all details
-fn generate_full(graph: &CompositionGraph, direction: Direction, show_types: bool) -> String {
+fn generate_full(graph: &CompositionGraph, direction: Direction, opts: &RenderOptions) -> String {
+    let show_types = opts.show_types;
     let view = build_full_view(graph, show_types);
     let mut output = format!("{INIT_DIRECTIVE}graph {}\n", direction.to_mermaid());
 
     output.push_str("    subgraph all[\"All Instances\"]\n");
-    for node in &view.nodes {
-        let label = if node.is_synthetic {
-            format!("{} (synthetic)", node.display)
+    output.push_str(&node_lines(&view.nodes, opts.group_namespaces, |node| {
+        let label = match (node.is_synthetic, node.size) {
+            (true, _) => format!("{} (synthetic)", node.display),
+            (false, Some(size)) => format!(
+                "{} ({}) [comp:{}]",
+                display_with_copies(node),
+                human_size(size),
+                node.component_index
+            ),
+            (false, None) => format!(
+                "{} [comp:{}]",
+                display_with_copies(node),
+                node.component_index
+            ),
+        };
+        let label = match &node.package {
+            Some(package) => format!("{}<br/>{}", label, package),
+            None => label,
+        };
+        let label = match &node.produced_by {
+            Some(producers) => format!("{}<br/>{}", label, producers.replace('"', "'")),
+            None => label,
+        };
+        let label = if node.runs_start {
+            format!("{}<br/>runs start logic", label)
         } else {
-            format!("{} [comp:{}]", node.display, node.component_index)
+            label
         };
-        output.push_str(&format!(
-            "        {}[\"{}\"]\n",
-            sanitize_for_mermaid(&node.name),
+        let label = if node.features.is_empty() {
             label
-        ));
-    }
+        } else {
+            format!("{}<br/>needs: {}", label, node.features.join(", "))
+        };
+        format!("{}[\"{}\"]", sanitize_for_mermaid(&node.name), label)
+    }));
     output.push_str("    end\n\n");
+    output.push_str(&node_classes(&view.nodes));
 
     let mut symbols = SymbolMap::new();
 
@@ -286,14 +583,16 @@ fn generate_full(graph: &CompositionGraph, direction: Direction, show_types: boo
             edge.fingerprint.as_deref(),
             edge.type_lines.clone(),
         );
+        let from = alias_hops(&mut output, &edge.from_name, &edge.via);
         output.push_str(&format!(
             "    {} -->|\"{}{}\"| {}\n",
-            sanitize_for_mermaid(&edge.from_name),
+            from,
             edge.label,
             sym,
             sanitize_for_mermaid(&edge.to_name)
         ));
     }
+    output.push_str(&missing_edges(&view.missing));
 
     output.push('\n');
     for exp in &view.exports {
@@ -302,20 +601,269 @@ fn generate_full(graph: &CompositionGraph, direction: Direction, show_types: boo
             exp.fingerprint.as_deref(),
             exp.type_lines.clone(),
         );
+        let from = alias_hops(&mut output, &exp.from_name, &exp.via);
         output.push_str(&format!(
             "    {} --> export_{}([\"Export: {}{}\"])\n",
-            sanitize_for_mermaid(&exp.from_name),
+            from,
             sanitize_for_mermaid(&exp.full_name),
             exp.full_name,
             sym
         ));
     }
 
+    output.push_str(&node_tooltips(
+        graph,
+        view.nodes.iter().map(|n| n.name.as_str()),
+    ));
+    output.push_str(&render_key(&symbols));
+
+    output
+}
+
+/// Generate a Mermaid diagram of everything instance `root` needs, directly
+/// or transitively.  The root is drawn with a double border.
+pub fn generate_dependency_mermaid(
+    graph: &CompositionGraph,
+    root: u32,
+    direction: Direction,
+    show_types: bool,
+) -> String {
+    generate_dependency_mermaid_with_options(
+        graph,
+        root,
+        direction,
+        &RenderOptions::new(show_types),
+    )
+}
+
+/// Generate a Mermaid diagram of everything instance `root` needs using the
+/// given [`RenderOptions`], of which only `show_types` and `hide_host`
+/// apply.
+pub fn generate_dependency_mermaid_with_options(
+    graph: &CompositionGraph,
+    root: u32,
+    direction: Direction,
+    opts: &RenderOptions,
+) -> String {
+    let show_types = opts.show_types;
+    let mut view = build_dependency_view(graph, root, show_types);
+    if opts.hide_host {
+        view.drop_host_imports();
+    }
+    let mut output = format!("{INIT_DIRECTIVE}graph {}\n", direction.to_mermaid());
+
+    let Some(root_node) = view.nodes.first() else {
+        output.push_str("    empty[\"Instance not found\"]\n");
+        return output;
+    };
+
+    if !view.host_names.is_empty() {
+        output.push_str("    subgraph host[\"Host Imports\"]\n");
+        for name in &view.host_names {
+            output.push_str(&format!(
+                "        {}[\"{}\"]\n",
+                sanitize_for_mermaid(name),
+                short_interface_name(name)
+            ));
+        }
+        output.push_str("    end\n\n");
+    }
+
+    output.push_str(&format!(
+        "    subgraph deps[\"Dependencies of {}\"]\n",
+        root_node.display
+    ));
+    for node in &view.nodes {
+        let (open, close) = if node.name == root_node.name {
+            ("[[", "]]")
+        } else {
+            ("[", "]")
+        };
+        output.push_str(&format!(
+            "        {}{}\"{}\"{}\n",
+            sanitize_for_mermaid(&node.name),
+            open,
+            node.display,
+            close
+        ));
+    }
+    output.push_str("    end\n\n");
+
+    let mut symbols = SymbolMap::new();
+    for edge in &view.edges {
+        let sym = symbols.assign(
+            show_types,
+            edge.fingerprint.as_deref(),
+            edge.type_lines.clone(),
+        );
+        let arrow = if edge.is_dashed { "-.->" } else { "-->" };
+        output.push_str(&format!(
+            "    {} {}|\"{}{}\"| {}\n",
+            sanitize_for_mermaid(&edge.from_name),
+            arrow,
+            edge.label,
+            sym,
+            sanitize_for_mermaid(&edge.to_name)
+        ));
+    }
+    output.push_str(&missing_edges(&view.missing));
+
+    output.push_str(&node_tooltips(
+        graph,
+        view.nodes.iter().map(|n| n.name.as_str()),
+    ));
     output.push_str(&render_key(&symbols));
 
     output
 }
 
+/// Generate a Mermaid diagram of a plain core module: the module names it
+/// imports from point at the module, labelled with the imported items, and
+/// the module points at its exports.
+pub fn generate_module_mermaid(module: &ModuleInterface, direction: Direction) -> String {
+    let mut output = format!("{INIT_DIRECTIVE}graph {}\n", direction.to_mermaid());
+    output.push_str("    module[\"Core module\"]\n");
+
+    for (name, imports) in &module.imports {
+        let items: Vec<&str> = imports.iter().map(|i| i.name.as_str()).collect();
+        output.push_str(&format!(
+            "    import_{}{{{{\"{}\"}}}} -->|\"{}\"| module\n",
+            sanitize_for_mermaid(name),
+            name,
+            items.join(", ")
+        ));
+    }
+    for export in &module.exports {
+        output.push_str(&format!(
+            "    module --> export_{}([\"Export: {} ({})\"])\n",
+            sanitize_for_mermaid(&export.name),
+            export.name,
+            export.kind
+        ));
+    }
+    output
+}
+
+/// Draw the nested instances aliased on the way out of `from`, one hexagon
+/// per hop, and return the ID of the last node for the caller's edge.
+fn alias_hops(output: &mut String, from: &str, via: &[String]) -> String {
+    let mut id = sanitize_for_mermaid(from);
+    let mut path = from.to_string();
+    for hop in via {
+        path = format!("{}.{}", path, hop);
+        let hop_id = format!("alias_{}", sanitize_for_mermaid(&path));
+        output.push_str(&format!(
+            "    {} --> {}{{{{\"alias: {}\"}}}}\n",
+            id, hop_id, hop
+        ));
+        id = hop_id;
+    }
+    id
+}
+
+/// `click` lines giving each drawn instance whose component has a
+/// description a tooltip, linking to the component's homepage or source
+/// when known.
+fn node_tooltips<'a>(graph: &CompositionGraph, names: impl IntoIterator<Item = &'a str>) -> String {
+    node_tooltips_by_id(
+        graph,
+        names
+            .into_iter()
+            .map(|name| (sanitize_for_mermaid(name), name)),
+    )
+}
+
+/// [`node_tooltips`] for instances drawn under the given node IDs.
+fn node_tooltips_by_id<'a>(
+    graph: &CompositionGraph,
+    nodes: impl IntoIterator<Item = (String, &'a str)>,
+) -> String {
+    let mut seen = BTreeSet::new();
+    let mut output = String::new();
+    for (id, name) in nodes {
+        if !seen.insert(id.clone()) {
+            continue;
+        }
+        let Some(node) = graph.nodes.values().find(|n| n.name == name) else {
+            continue;
+        };
+        let Some(metadata) = graph.metadata.get(&Some(node.component_num)) else {
+            continue;
+        };
+        let Some(description) = &metadata.description else {
+            continue;
+        };
+        let url = metadata
+            .homepage
+            .as_deref()
+            .or(metadata.source.as_deref())
+            .unwrap_or("#");
+        let tooltip = description.lines().next().unwrap_or_default();
+        output.push_str(&format!(
+            "    click {} \"{}\" \"{}\"\n",
+            id,
+            url,
+            tooltip.replace('"', "'")
+        ));
+    }
+    output
+}
+
+/// Wrap a Mermaid diagram in a standalone HTML page that renders it with
+/// mermaid.js from a CDN, for previewing in a browser.
+pub fn generate_html_page(diagram: &str, title: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n\
+         <html>\n\
+         <head>\n\
+         <meta charset=\"utf-8\">\n\
+         <title>{title}</title>\n\
+         <script type=\"module\">\n\
+         import mermaid from \"https://cdn.jsdelivr.net/npm/mermaid@11/dist/mermaid.esm.min.mjs\";\n\
+         mermaid.initialize({{ startOnLoad: true }});\n\
+         </script>\n\
+         </head>\n\
+         <body>\n\
+         <pre class=\"mermaid\">\n{diagram}\n</pre>\n\
+         </body>\n\
+         </html>\n",
+        title = escape_html(title),
+        diagram = escape_html(diagram.trim_end()),
+    )
+}
+
+/// A mermaid.live editor link with `diagram` preloaded.
+pub fn live_editor_url(diagram: &str) -> String {
+    use base64::engine::general_purpose::URL_SAFE;
+    use base64::Engine;
+
+    let state = serde_json::json!({
+        "code": diagram,
+        "mermaid": "{\"theme\": \"default\"}",
+    });
+    format!(
+        "https://mermaid.live/edit#base64:{}",
+        URL_SAFE.encode(state.to_string())
+    )
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Label of an instance in a chain, marking synthetic ones drawn with
+/// [`RenderOptions::show_synthetic`].
+fn chain_node_label(node: &ComponentNode) -> String {
+    if node.component_index == SYNTHETIC_COMPONENT {
+        format!("{} (synthetic)", node.display_label())
+    } else {
+        node.display_label().to_string()
+    }
+}
+
 /// Sanitize a string for use as a Mermaid node ID
 fn sanitize_for_mermaid(s: &str) -> String {
     s.chars()
@@ -335,8 +883,8 @@ fn sanitize_for_mermaid(s: &str) -> String {
 mod tests {
     use super::*;
     use crate::model::{
-        ComponentNode, FuncSignature, InstanceInterface, InterfaceConnection, InterfaceType,
-        ValueType,
+        labels::LabelStyle, ComponentNode, ExternKind, FuncSignature, InstanceInterface,
+        InterfaceConnection, InterfaceType, ValueType,
     };
     use crate::output::Direction;
     use crate::test_utils::*;
@@ -353,6 +901,9 @@ mod tests {
             is_host_import: true,
             interface_type: None,
             fingerprint: None,
+            source_export: None,
+            kind: ExternKind::Instance,
+            alias_chain: Vec::new(),
         });
         graph.add_node(1, srv);
 
@@ -363,6 +914,9 @@ mod tests {
             is_host_import: false,
             interface_type: None,
             fingerprint: None,
+            source_export: None,
+            kind: ExternKind::Instance,
+            alias_chain: Vec::new(),
         });
         mw.add_import(InterfaceConnection {
             interface_name: "wasi:logging/log@0.1.0".to_string(),
@@ -370,6 +924,9 @@ mod tests {
             is_host_import: true,
             interface_type: None,
             fingerprint: None,
+            source_export: None,
+            kind: ExternKind::Instance,
+            alias_chain: Vec::new(),
         });
         graph.add_node(2, mw);
 
@@ -404,6 +961,9 @@ mod tests {
             is_host_import: true,
             interface_type: Some(iface_type.clone()),
             fingerprint: Some(iface_type.fingerprint(&graph.arena)),
+            source_export: None,
+            kind: ExternKind::Instance,
+            alias_chain: Vec::new(),
         });
         graph.add_node(1, srv);
 
@@ -414,6 +974,9 @@ mod tests {
             is_host_import: false,
             interface_type: Some(iface_type.clone()),
             fingerprint: Some(iface_type.fingerprint(&graph.arena)),
+            source_export: None,
+            kind: ExternKind::Instance,
+            alias_chain: Vec::new(),
         });
         graph.add_node(2, mw);
 
@@ -493,6 +1056,241 @@ mod tests {
         assert!(output.contains("Export"), "should have export");
     }
 
+    #[test]
+    fn test_show_versions_mermaid() {
+        let graph = simple_chain_graph();
+        let opts = RenderOptions {
+            labels: LabelStyle {
+                keep_version: true,
+                ..LabelStyle::default()
+            },
+            ..RenderOptions::new(false)
+        };
+        for detail in [
+            DetailLevel::HandlerChain,
+            DetailLevel::Chains,
+            DetailLevel::AllInterfaces,
+        ] {
+            let output =
+                generate_mermaid_with_options(&graph, detail, Direction::LeftToRight, &opts);
+            assert!(output.contains("|\"handler@0.3.0\"|"), "{}", output);
+        }
+    }
+
+    #[test]
+    fn test_highlight_paths() {
+        use crate::analysis::paths::{connection_paths, PathTarget};
+
+        let graph = simple_chain_graph();
+        let target = PathTarget::Host("wasi:http/handler@0.3.0".to_string());
+        let paths = connection_paths(&graph, 2, &target);
+        let opts = RenderOptions {
+            highlight: paths.paths.into_iter().flatten().collect(),
+            ..RenderOptions::new(false)
+        };
+        let output = generate_mermaid_with_options(
+            &graph,
+            DetailLevel::AllInterfaces,
+            Direction::LeftToRight,
+            &opts,
+        );
+        // Edges: srv's handler from the host, then middleware's from srv and
+        // its log import
+        assert!(
+            output.contains("    linkStyle 0,1 stroke:#f80"),
+            "{}",
+            output
+        );
+        assert!(
+            output.contains("    class middleware,srv,wasi_http_handler_0_3_0 path"),
+            "{}",
+            output
+        );
+    }
+
+    #[test]
+    fn test_group_namespaces() {
+        let mut graph = simple_chain_graph();
+        graph.nodes.get_mut(&1).unwrap().package = Some("acme:srv".to_string());
+        graph.nodes.get_mut(&2).unwrap().package = Some("acme:middleware".to_string());
+        graph.add_node(42, orphan_node());
+        let opts = RenderOptions {
+            group_namespaces: true,
+            ..RenderOptions::new(false)
+        };
+        for detail in [DetailLevel::AllInterfaces, DetailLevel::Full] {
+            let output =
+                generate_mermaid_with_options(&graph, detail, Direction::LeftToRight, &opts);
+            let grouped = output
+                .find("        subgraph ns_acme[\"acme\"]\n            srv[")
+                .unwrap_or_else(|| panic!("{}", output));
+            assert!(
+                output.find("        orphan[").unwrap() < grouped,
+                "{}",
+                output
+            );
+            assert!(output.contains("            middleware["), "{}", output);
+        }
+    }
+
+    #[test]
+    fn test_unused_instances_styled() {
+        let mut graph = simple_chain_graph();
+        assert!(
+            !generate_mermaid(&graph, DetailLevel::Full, Direction::LeftToRight, false)
+                .contains("classDef unused")
+        );
+
+        graph.add_node(42, orphan_node());
+        for detail in [DetailLevel::AllInterfaces, DetailLevel::Full] {
+            let output = generate_mermaid(&graph, detail, Direction::LeftToRight, false);
+            assert!(
+                output.contains("    classDef unused stroke:#d33,stroke-dasharray:5 5\n"),
+                "{}",
+                output
+            );
+            assert!(output.contains("    class orphan unused\n"), "{}", output);
+        }
+    }
+
+    #[test]
+    fn test_shared_instances_styled() {
+        let mut graph = simple_chain_graph();
+        let mut admin = orphan_node();
+        admin.add_import(graph.nodes[&2].imports[0].clone());
+        graph.add_node(7, admin);
+
+        for detail in [DetailLevel::AllInterfaces, DetailLevel::Full] {
+            let output = generate_mermaid(&graph, detail, Direction::LeftToRight, false);
+            assert!(
+                output.contains("    classDef shared stroke:#93c,stroke-width:3px\n"),
+                "{}",
+                output
+            );
+            assert!(output.contains("    class srv shared\n"), "{}", output);
+        }
+    }
+
+    #[test]
+    fn test_missing_imports_drawn() {
+        let mut graph = simple_chain_graph();
+        let node = graph.nodes.get_mut(&2).unwrap();
+        let mut declared: BTreeSet<String> = node
+            .imports
+            .iter()
+            .map(|c| c.interface_name.clone())
+            .collect();
+        declared.insert("wasi:keyvalue/store@0.1.0".to_string());
+        node.declared_imports = Some(declared);
+
+        let output = generate_mermaid(
+            &graph,
+            DetailLevel::AllInterfaces,
+            Direction::LeftToRight,
+            false,
+        );
+        assert!(
+            output.contains("    missing_0[\"missing\"] -.->|\"store\"| middleware\n"),
+            "{}",
+            output
+        );
+        assert!(
+            output.contains("    class missing_0 missing\n"),
+            "{}",
+            output
+        );
+    }
+
+    #[test]
+    fn test_chains_mermaid_lanes() {
+        let graph = two_chain_graph();
+        let output = generate_mermaid(&graph, DetailLevel::Chains, Direction::LeftToRight, false);
+
+        assert!(
+            output.contains("    subgraph wasi_http_handler_0_3_0[\"wasi:http/handler@0.3.0\"]\n"),
+            "{}",
+            output
+        );
+        assert!(
+            output.contains(
+                "    subgraph wasi_keyvalue_store_0_1_0[\"wasi:keyvalue/store@0.1.0\"]\n"
+            ),
+            "{}",
+            output
+        );
+        assert!(
+            output.contains(
+                "    export_wasi_keyvalue_store_0_1_0([\"Export: store\"]) --> wasi_keyvalue_store_0_1_0_cache\n"
+            ),
+            "{}",
+            output
+        );
+        assert!(
+            output.contains(
+                "    wasi_keyvalue_store_0_1_0_cache -->|\"store\"| wasi_keyvalue_store_0_1_0_db\n"
+            ),
+            "{}",
+            output
+        );
+    }
+
+    #[test]
+    fn test_composer_title() {
+        let mut graph = test_graph();
+        let mut metadata = crate::model::ComponentMetadata::default();
+        metadata.producers.insert(
+            "processed-by".to_string(),
+            BTreeMap::from([("wasm-compose".to_string(), "0.239.0".to_string())]),
+        );
+        graph.metadata.insert(None, metadata);
+        let output = generate_mermaid(&graph, DetailLevel::Full, Direction::TopDown, false);
+        assert!(
+            output.starts_with(&format!(
+                "---\ntitle: \"Composed by wasm-compose 0.239.0\"\n---\n{INIT_DIRECTIVE}"
+            )),
+            "{}",
+            output
+        );
+    }
+
+    #[test]
+    fn test_description_tooltips() {
+        let mut graph = test_graph();
+        let metadata = crate::model::ComponentMetadata {
+            description: Some("Serves \"requests\"\nand more".to_string()),
+            homepage: Some("https://example.com/srv".to_string()),
+            ..Default::default()
+        };
+        let srv = graph.nodes[&1].component_num;
+        graph.metadata.insert(Some(srv), metadata);
+
+        let output = generate_mermaid(&graph, DetailLevel::Full, Direction::TopDown, false);
+        assert!(
+            output.contains("    click srv \"https://example.com/srv\" \"Serves 'requests'\"\n"),
+            "{}",
+            output
+        );
+        assert_eq!(output.matches("    click ").count(), 1, "{}", output);
+    }
+
+    #[test]
+    fn test_full_mermaid_alias_chain() {
+        let mut graph = test_graph();
+        graph.nodes.get_mut(&2).unwrap().imports[0].alias_chain = vec!["nested".to_string()];
+        let output = generate_mermaid(&graph, DetailLevel::Full, Direction::TopDown, false);
+
+        assert!(
+            output.contains("    srv --> alias_srv_nested{{\"alias: nested\"}}\n"),
+            "{}",
+            output
+        );
+        assert!(
+            output.contains("    alias_srv_nested -->|\"wasi:http/handler@0.3.0\"| middleware\n"),
+            "{}",
+            output
+        );
+    }
+
     #[test]
     fn test_full_mermaid() {
         let graph = test_graph();
@@ -816,6 +1614,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_dependency_mermaid() {
+        let graph = chain_plus_utility_graph();
+        let root = graph.find_node("middleware").unwrap();
+        let output = generate_dependency_mermaid(&graph, root, Direction::LeftToRight, false);
+        assert!(
+            output.contains("middleware[[\"middleware\"]]"),
+            "{}",
+            output
+        );
+        assert!(
+            output.contains("srv -->|\"handler\"| middleware"),
+            "{}",
+            output
+        );
+        assert!(
+            output.contains("wasi_http_handler_0_3_0 -.->|\"handler\"| srv"),
+            "{}",
+            output
+        );
+        assert!(
+            !output.contains("logger"),
+            "unrelated instances are excluded"
+        );
+    }
+
     #[test]
     fn test_all_interfaces_two_chains_mermaid() {
         let graph = two_chain_graph();
@@ -868,4 +1692,23 @@ mod tests {
             "synthetic node should be labelled as synthetic"
         );
     }
+
+    #[test]
+    fn test_html_page_and_live_link() {
+        use base64::engine::general_purpose::URL_SAFE;
+        use base64::Engine;
+
+        let diagram = "graph LR\n    a -->|\"<handler>\"| b\n";
+        let page = generate_html_page(diagram, "a & b");
+        assert!(page.contains("<title>a &amp; b</title>"));
+        assert!(page.contains("a --&gt;|&quot;&lt;handler&gt;&quot;| b\n</pre>"));
+
+        let url = live_editor_url(diagram);
+        let payload = url
+            .strip_prefix("https://mermaid.live/edit#base64:")
+            .unwrap();
+        let state: serde_json::Value =
+            serde_json::from_slice(&URL_SAFE.decode(payload).unwrap()).unwrap();
+        assert_eq!(state["code"], diagram);
+    }
 }
@@ -1,403 +1,120 @@
+#[cfg(feature = "ascii")]
 pub mod ascii;
+#[cfg(any(feature = "ascii", feature = "mermaid"))]
+// Each renderer draws only some of what a view holds
+#[cfg_attr(not(all(feature = "ascii", feature = "mermaid")), allow(dead_code))]
+pub(crate) mod diagram;
+#[cfg(feature = "json")]
 pub mod json;
+#[cfg(feature = "json")]
+pub mod json_patch;
+pub mod links;
+#[cfg(feature = "mermaid")]
 pub mod mermaid;
 
-use crate::model::{
-    short_interface_name, CompositionGraph, ExportInfo, FuncSignature, InterfaceConnection,
-    InterfaceType, InternedId, TypeArena, SYNTHETIC_COMPONENT,
-};
+use crate::analysis::paths::Hop;
+use crate::error::CvizError;
+use crate::model::{labels::LabelStyle, CompositionGraph};
+use links::LinkTemplates;
 
-/// Format a function signature as `(param-type, ...) -> result-type`.
-///
-/// Uses [`TypeArena::display_val`] so that large complex types (variants with
-/// many cases, records with many fields, etc.) are summarised rather than
-/// expanded in full.  Fingerprinting is unaffected — it always uses the
-/// lossless [`TypeArena::canonical_val`].
-pub(crate) fn format_func_sig(sig: &FuncSignature, arena: &TypeArena) -> String {
-    let params: Vec<String> = sig.params.iter().map(|id| arena.display_val(*id)).collect();
-    let results: Vec<String> = sig
-        .results
-        .iter()
-        .map(|id| arena.display_val(*id))
-        .collect();
-    let result_str = match results.as_slice() {
-        [] => "()".to_string(),
-        [single] => single.clone(),
-        _ => format!("({})", results.join(", ")),
-    };
-    format!("({}) -> {}", params.join(", "), result_str)
-}
-
-/// Return type lines for an [`InterfaceConnection`], or an empty vec when
-/// `show_types` is false or the connection carries no type information.
-pub(crate) fn connection_type_lines(
-    conn: &InterfaceConnection,
-    arena: &TypeArena,
-    show_types: bool,
-) -> Vec<String> {
-    if !show_types {
-        return vec![];
+/// A byte count in binary units with one decimal, e.g. `1.2 MiB`.
+pub(crate) fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
     }
-    conn.interface_type
-        .as_ref()
-        .map(|t| format_interface_type_lines(t, arena))
-        .unwrap_or_default()
-}
-
-/// Return type lines for an [`ExportInfo`], or an empty vec when `show_types`
-/// is false or the export carries no interface type.
-pub(crate) fn export_type_lines(
-    export_info: &ExportInfo,
-    arena: &TypeArena,
-    show_types: bool,
-) -> Vec<String> {
-    if !show_types {
-        return vec![];
-    }
-    match export_info.ty {
-        Some(InternedId::Interface(id)) => {
-            format_interface_type_lines(arena.lookup_interface(id), arena)
-        }
-        _ => vec![],
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
     }
+    format!("{:.1} {}", size, UNITS[unit])
 }
 
-/// Return one display line per exported function in the interface.
-///
-/// - `Instance` interfaces produce `"fn-name: (params) -> result"` per function.
-/// - `Func` interfaces produce a single `"(params) -> result"` line.
-pub(crate) fn format_interface_type_lines(iface: &InterfaceType, arena: &TypeArena) -> Vec<String> {
-    match iface {
-        InterfaceType::Func(sig) => vec![format_func_sig(sig, arena)],
-        InterfaceType::Instance(inst) => inst
-            .functions
-            .iter()
-            .map(|(name, sig)| format!("`{}`: {}", name, format_func_sig(sig, arena)))
-            .collect(),
-    }
-}
-
-const SYMBOL_POOL: &[char] = &[
-    '✦', '✧', '◆', '◇', '★', '☆', '●', '○', '▲', '△', '▼', '▽', '■', '□', '◉', '♦', '♠', '✱', '✴',
-    '❖',
-];
-
-/// Compute the symbol string for a given assignment index using base-N encoding
-/// over [`SYMBOL_POOL`].
-///
-/// - Indices `0..N` produce single-character identifiers (`"✦"`, `"✧"`, …).
-/// - Indices `N..N+N²` produce two-character identifiers (`"✦✦"`, `"✦✧"`, …).
-/// - Indices beyond that produce three-character identifiers, and so on.
-///
-/// This guarantees an unbounded, collision-free sequence of compact identifiers
-/// without ever reusing a symbol string.
-fn symbol_at(index: usize) -> String {
-    let n = SYMBOL_POOL.len();
-    // Find which "length tier" this index falls into and the offset within it.
-    // Tier 1 covers [0, n), tier 2 covers [n, n + n²), tier 3 covers [n + n², n + n² + n³), …
-    let mut tier_size = n;
-    let mut offset = index;
-    let mut len = 1;
-    while offset >= tier_size {
-        offset -= tier_size;
-        tier_size *= n;
-        len += 1;
+/// The package a `wasi:` interface is grouped under by
+/// [`RenderOptions::group_host`], e.g. `wasi:http` for
+/// `wasi:http/types@0.2.0`; `None` for other interfaces.
+pub fn host_group(interface: &str) -> Option<&str> {
+    if !interface.starts_with("wasi:") {
+        return None;
     }
-    // Decode `offset` as a base-N number of `len` digits (most-significant first).
-    let mut digits = vec![0usize; len];
-    let mut remainder = offset;
-    for d in digits.iter_mut().rev() {
-        *d = remainder % n;
-        remainder /= n;
-    }
-    digits.iter().map(|&d| SYMBOL_POOL[d]).collect()
+    interface.split_once('/').map(|(package, _)| package)
 }
 
-/// Assigns a unique identifier to each distinct interface type encountered
-/// during rendering and collects a display key.
-///
-/// Types are distinguished by fingerprint, so structurally identical interfaces
-/// always receive the same symbol within one diagram.  Identifiers are drawn
-/// from [`SYMBOL_POOL`] via [`symbol_at`]: single glyphs first, then
-/// two-glyph combinations, then three-glyph, and so on — so the pool never
-/// truly exhausts.
-pub(crate) struct SymbolMap {
-    /// `(fingerprint, symbol string, formatted type lines)`
-    entries: Vec<(String, String, Vec<String>)>,
+/// Options controlling how a diagram is rendered.
+#[derive(Debug, Clone, Default)]
+pub struct RenderOptions {
+    /// Show WIT type information on interface connections
+    pub show_types: bool,
+    /// Wrap instance and interface names in OSC 8 terminal hyperlinks
+    /// (ASCII output only).  `None` disables hyperlinks.
+    pub links: Option<LinkTemplates>,
+    /// Append a legend mapping abbreviated interface labels to their full
+    /// names (ASCII output only).
+    pub legend: bool,
+    /// Draw only the chains of exported interfaces whose name contains this
+    /// (handler-chain view only).  `None` draws every middleware chain.
+    pub interface: Option<String>,
+    /// Draw `wasi:` host imports as one node per package (`wasi:http`,
+    /// `wasi:io`, ...) instead of one per interface (all-interfaces view
+    /// only).
+    pub group_host: bool,
+    /// Cluster instances by [namespace](ComponentNode::namespace)
+    /// (Mermaid all-interfaces and full views).
+    pub group_namespaces: bool,
+    /// How interface names are shortened for labels.
+    pub labels: LabelStyle,
+    /// Leave out the host imports and the dashed edges from them, for an
+    /// application-only view (all-interfaces and dependency views).
+    pub hide_host: bool,
+    /// Draw synthetic instances, such as interface bundles wrapping host
+    /// imports, that the handler-chain, chains and all-interfaces views
+    /// leave out.
+    pub show_synthetic: bool,
+    /// Connections to emphasise, such as the [paths](crate::analysis::paths)
+    /// between two instances (Mermaid all-interfaces view only).
+    pub highlight: Vec<Hop>,
 }
 
-impl SymbolMap {
-    pub(crate) fn new() -> Self {
+impl RenderOptions {
+    pub fn new(show_types: bool) -> Self {
         Self {
-            entries: Vec::new(),
-        }
-    }
-
-    /// Return (or assign) the symbol for a connection's interface type.
-    /// Returns `None` if the connection carries no type info.
-    pub(crate) fn symbol_for_conn(
-        &mut self,
-        conn: &InterfaceConnection,
-        arena: &TypeArena,
-    ) -> Option<&str> {
-        let fp = conn.fingerprint.as_ref()?;
-        let iface = conn.interface_type.as_ref()?;
-        Some(self.get_or_insert(fp, iface, arena))
-    }
-
-    /// Return (or assign) the symbol for an export's interface type.
-    /// Returns `None` if the export carries no interface type.
-    pub(crate) fn symbol_for_export(
-        &mut self,
-        export_info: &ExportInfo,
-        arena: &TypeArena,
-    ) -> Option<&str> {
-        let fp = export_info.fingerprint.as_ref()?;
-        let id = match export_info.ty {
-            Some(InternedId::Interface(id)) => id,
-            _ => return None,
-        };
-        Some(self.get_or_insert(fp, arena.lookup_interface(id), arena))
-    }
-
-    fn get_or_insert(&mut self, fp: &str, iface: &InterfaceType, arena: &TypeArena) -> &str {
-        if let Some(pos) = self.entries.iter().position(|(f, _, _)| f == fp) {
-            return &self.entries[pos].1;
-        }
-        let symbol = symbol_at(self.entries.len());
-        let lines = format_interface_type_lines(iface, arena);
-        self.entries.push((fp.to_string(), symbol, lines));
-        &self.entries.last().unwrap().1
-    }
-
-    /// Return (or assign) the symbol for a pre-computed fingerprint + type lines,
-    /// or an empty string when `show_types` is false or no fingerprint is present.
-    ///
-    /// This is the primary entry point for AllInterfaces/Full renderers that
-    /// receive type data from the [`DiagramEdge`]/[`DiagramExport`] IR.
-    pub(crate) fn assign(
-        &mut self,
-        show_types: bool,
-        fingerprint: Option<&str>,
-        type_lines: Vec<String>,
-    ) -> String {
-        if !show_types {
-            return String::new();
-        }
-        let Some(fp) = fingerprint else {
-            return String::new();
-        };
-        if let Some(pos) = self.entries.iter().position(|(f, _, _)| f == fp) {
-            return self.entries[pos].1.clone();
+            show_types,
+            ..Self::default()
         }
-        let symbol = symbol_at(self.entries.len());
-        self.entries.push((fp.to_string(), symbol, type_lines));
-        self.entries.last().unwrap().1.clone()
     }
-
-    pub(crate) fn is_empty(&self) -> bool {
-        self.entries.is_empty()
-    }
-
-    /// Return key lines: `"✦ fn-name: sig"` per unique symbol, with
-    /// continuation lines for multi-function interfaces indented.
-    pub(crate) fn key_lines(&self) -> Vec<String> {
-        let mut out = Vec::new();
-        for (_, symbol, type_lines) in &self.entries {
-            for (i, line) in type_lines.iter().enumerate() {
-                if i == 0 {
-                    out.push(format!("{} {}", symbol, line));
-                } else {
-                    out.push(format!("  {}", line));
-                }
-            }
-        }
-        out
-    }
-}
-
-// ---------------------------------------------------------------------------
-// Intermediate representation — shared graph traversal
-// ---------------------------------------------------------------------------
-
-/// A node to be rendered in the diagram.
-pub(crate) struct DiagramNode {
-    pub name: String,
-    pub display: String,
-    pub is_synthetic: bool,
-    pub component_index: u32,
-}
-
-/// A directed edge between two nodes.
-pub(crate) struct DiagramEdge {
-    /// Raw name of the source (interface_name for host imports, node.name otherwise).
-    /// Renderers that need sanitized IDs (Mermaid) apply their own transform.
-    pub from_name: String,
-    pub from_display: String,
-    pub to_name: String,
-    pub to_display: String,
-    /// Ready-to-use edge label (short interface name or full name, depending on mode).
-    pub label: String,
-    /// Pre-formatted type lines for this connection (empty when show_types=false).
-    pub type_lines: Vec<String>,
-    /// Fingerprint for deduplication in a [`SymbolMap`] (None when no type info).
-    pub fingerprint: Option<String>,
-    /// true if host import
-    pub is_dashed: bool,
 }
 
-/// An exported interface.
-pub(crate) struct DiagramExport {
-    pub from_name: String,
-    pub from_display: String,
-    pub full_name: String,
-    pub short_name: String,
-    /// Pre-formatted type lines for this export (empty when show_types=false).
-    pub type_lines: Vec<String>,
-    /// Fingerprint for deduplication in a [`SymbolMap`] (None when no type info).
-    pub fingerprint: Option<String>,
-}
-
-/// Pre-computed graph data for rendering, independent of output format.
-pub(crate) struct ConnectionsView {
-    /// Raw host interface names (AllInterfaces only; empty for Full).
-    pub host_names: Vec<String>,
-    pub nodes: Vec<DiagramNode>,
-    pub edges: Vec<DiagramEdge>,
-    pub exports: Vec<DiagramExport>,
-}
-
-/// Build a [`ConnectionsView`] for `AllInterfaces` detail level.
+/// Render `graph` in `format`: a diagram at `detail` for ASCII and Mermaid
+/// (drawn in `direction`, for Mermaid), or the JSON graph.
 ///
-/// Includes real (non-synthetic) component nodes, host-import edges (dashed),
-/// inter-component edges (solid), and exported interfaces.  Edge labels use
-/// the short interface name.
-pub(crate) fn build_all_interfaces_view(
+/// Only writing JSON can fail, besides asking for a format cviz was built
+/// without, which fails with [`CvizError::MissingFeature`].
+#[cfg_attr(
+    not(all(feature = "ascii", feature = "mermaid", feature = "json")),
+    allow(unused_variables)
+)]
+pub fn render(
     graph: &CompositionGraph,
-    show_types: bool,
-) -> ConnectionsView {
-    let component_nodes = graph.real_nodes();
-
-    let nodes = component_nodes
-        .iter()
-        .map(|n| DiagramNode {
-            name: n.name.clone(),
-            display: n.display_label().to_string(),
-            is_synthetic: false,
-            component_index: n.component_index,
-        })
-        .collect();
-
-    let mut edges = Vec::new();
-    for node in &component_nodes {
-        for import in &node.imports {
-            if import.is_host_import {
-                edges.push(DiagramEdge {
-                    from_name: import.interface_name.clone(),
-                    from_display: short_interface_name(&import.interface_name),
-                    to_name: node.name.clone(),
-                    to_display: node.display_label().to_string(),
-                    label: import.short_label(),
-                    type_lines: connection_type_lines(import, &graph.arena, show_types),
-                    fingerprint: import.fingerprint.clone(),
-                    is_dashed: true,
-                });
-            } else if let Some(src) = import.source_instance.and_then(|id| graph.get_node(id)) {
-                if src.component_index != SYNTHETIC_COMPONENT {
-                    edges.push(DiagramEdge {
-                        from_name: src.name.clone(),
-                        from_display: src.display_label().to_string(),
-                        to_name: node.name.clone(),
-                        to_display: node.display_label().to_string(),
-                        label: import.short_label(),
-                        type_lines: connection_type_lines(import, &graph.arena, show_types),
-                        fingerprint: import.fingerprint.clone(),
-                        is_dashed: false,
-                    });
-                }
-            }
-        }
-    }
-
-    let mut exports = Vec::new();
-    for (export_name, export_info) in &graph.component_exports {
-        if let Some(node) = graph.get_node(export_info.source_instance) {
-            if node.component_index != SYNTHETIC_COMPONENT {
-                exports.push(DiagramExport {
-                    from_name: node.name.clone(),
-                    from_display: node.display_label().to_string(),
-                    full_name: export_name.clone(),
-                    short_name: short_interface_name(export_name),
-                    type_lines: export_type_lines(export_info, &graph.arena, show_types),
-                    fingerprint: export_info.fingerprint.clone(),
-                });
-            }
-        }
-    }
-
-    ConnectionsView {
-        host_names: graph.host_interfaces(),
-        nodes,
-        edges,
-        exports,
-    }
-}
-
-/// Build a [`ConnectionsView`] for `Full` detail level.
-///
-/// Includes all nodes (including synthetic), all non-host-import edges with
-/// full interface names, and all exported interfaces.
-pub(crate) fn build_full_view(graph: &CompositionGraph, show_types: bool) -> ConnectionsView {
-    let nodes = graph
-        .nodes
-        .values()
-        .map(|n| DiagramNode {
-            name: n.name.clone(),
-            display: n.display_label().to_string(),
-            is_synthetic: n.component_index == SYNTHETIC_COMPONENT,
-            component_index: n.component_index,
-        })
-        .collect();
-
-    let mut edges = Vec::new();
-    for node in graph.nodes.values() {
-        for import in &node.imports {
-            if !import.is_host_import {
-                if let Some(src) = import.source_instance.and_then(|id| graph.get_node(id)) {
-                    edges.push(DiagramEdge {
-                        from_name: src.name.clone(),
-                        from_display: src.display_label().to_string(),
-                        to_name: node.name.clone(),
-                        to_display: node.display_label().to_string(),
-                        label: import.interface_name.clone(),
-                        type_lines: connection_type_lines(import, &graph.arena, show_types),
-                        fingerprint: import.fingerprint.clone(),
-                        is_dashed: false,
-                    });
-                }
-            }
-        }
-    }
-
-    let mut exports = Vec::new();
-    for (export_name, export_info) in &graph.component_exports {
-        if let Some(node) = graph.get_node(export_info.source_instance) {
-            exports.push(DiagramExport {
-                from_name: node.name.clone(),
-                from_display: node.display_label().to_string(),
-                full_name: export_name.clone(),
-                short_name: short_interface_name(export_name),
-                type_lines: export_type_lines(export_info, &graph.arena, show_types),
-                fingerprint: export_info.fingerprint.clone(),
-            });
-        }
-    }
-
-    ConnectionsView {
-        host_names: vec![],
-        nodes,
-        edges,
-        exports,
+    format: OutputFormat,
+    detail: DetailLevel,
+    direction: Direction,
+    opts: &RenderOptions,
+) -> crate::Result<String> {
+    match format {
+        #[cfg(feature = "ascii")]
+        OutputFormat::Ascii => Ok(ascii::generate_ascii_with_options(graph, detail, opts)),
+        #[cfg(feature = "mermaid")]
+        OutputFormat::Mermaid => Ok(mermaid::generate_mermaid_with_options(
+            graph, detail, direction, opts,
+        )),
+        #[cfg(feature = "json")]
+        OutputFormat::Json => json::generate_json(graph, false),
+        #[cfg(feature = "json")]
+        OutputFormat::JsonPretty => json::generate_json(graph, true),
+        #[allow(unreachable_patterns)]
+        format => Err(CvizError::MissingFeature(format.feature())),
     }
 }
 
@@ -411,6 +128,17 @@ pub enum OutputFormat {
     JsonPretty,
 }
 
+impl OutputFormat {
+    /// The cargo feature the renderer for this format is behind.
+    pub fn feature(self) -> &'static str {
+        match self {
+            OutputFormat::Ascii => "ascii",
+            OutputFormat::Mermaid => "mermaid",
+            OutputFormat::Json | OutputFormat::JsonPretty => "json",
+        }
+    }
+}
+
 impl std::str::FromStr for OutputFormat {
     type Err = String;
 
@@ -463,6 +191,8 @@ pub enum DetailLevel {
     /// Only show the HTTP handler chain
     #[default]
     HandlerChain,
+    /// Show the chain of every exported interface in a lane of its own
+    Chains,
     /// Show all interfaces
     AllInterfaces,
     /// Show everything including internal details
@@ -475,6 +205,7 @@ impl std::str::FromStr for DetailLevel {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
             "handler-chain" | "handler" => Ok(DetailLevel::HandlerChain),
+            "chains" => Ok(DetailLevel::Chains),
             "all-interfaces" | "all" => Ok(DetailLevel::AllInterfaces),
             "full" => Ok(DetailLevel::Full),
             _ => Err(format!("Invalid detail level: {}", s)),
@@ -482,6 +213,60 @@ impl std::str::FromStr for DetailLevel {
     }
 }
 
+/// One input's part of a document combining several, see [`combine_sections`].
+#[derive(Debug, Clone)]
+pub struct Section {
+    /// The input the section is about, such as its path.
+    pub title: String,
+    pub body: String,
+    /// Whether `body` is a diagram rather than a report such as the health
+    /// score, which matters for Mermaid, where only diagrams are fenced.
+    pub diagram: bool,
+}
+
+/// Combine the output for several inputs into one document with a section
+/// per input: titled blocks for ASCII, a Markdown document with a fenced
+/// diagram per input for Mermaid, and a JSON array of `{"input", "output"}`
+/// objects for JSON, where `output` is the input's JSON output (or its text,
+/// if that is not JSON).
+pub fn combine_sections(format: OutputFormat, sections: &[Section]) -> String {
+    match format {
+        OutputFormat::Ascii => sections
+            .iter()
+            .map(|s| format!("== {} ==\n\n{}\n", s.title, s.body.trim_end()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        OutputFormat::Mermaid => sections
+            .iter()
+            .map(|s| {
+                if s.diagram {
+                    format!("## {}\n\n```mermaid\n{}\n```\n", s.title, s.body.trim_end())
+                } else {
+                    format!("## {}\n\n{}\n", s.title, s.body.trim_end())
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        OutputFormat::Json | OutputFormat::JsonPretty => {
+            let sections: Vec<serde_json::Value> = sections
+                .iter()
+                .map(|s| {
+                    let output = serde_json::from_str(&s.body)
+                        .unwrap_or_else(|_| serde_json::Value::String(s.body.clone()));
+                    serde_json::json!({ "input": s.title, "output": output })
+                })
+                .collect();
+            let sections = serde_json::Value::Array(sections);
+            if matches!(format, OutputFormat::JsonPretty) {
+                serde_json::to_string_pretty(&sections)
+            } else {
+                serde_json::to_string(&sections)
+            }
+            .expect("JSON values serialize")
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -504,396 +289,153 @@ mod tests {
     }
 
     #[test]
-    fn test_direction_parse() {
-        assert!(matches!(
-            "lr".parse::<Direction>().unwrap(),
-            Direction::LeftToRight
-        ));
-        assert!(matches!(
-            "td".parse::<Direction>().unwrap(),
-            Direction::TopDown
-        ));
-    }
-
-    #[test]
-    fn test_detail_level_parse() {
-        assert!(matches!(
-            "handler-chain".parse::<DetailLevel>().unwrap(),
-            DetailLevel::HandlerChain
-        ));
-        assert!(matches!(
-            "all-interfaces".parse::<DetailLevel>().unwrap(),
-            DetailLevel::AllInterfaces
-        ));
-        assert!(matches!(
-            "full".parse::<DetailLevel>().unwrap(),
-            DetailLevel::Full
-        ));
-    }
-
-    #[test]
-    fn test_symbol_at_tier_boundaries() {
-        let n = SYMBOL_POOL.len();
-
-        // Last single-char symbol
-        assert_eq!(symbol_at(n - 1).chars().count(), 1);
-        // First two-char symbol
-        assert_eq!(symbol_at(n).chars().count(), 2);
-        // Last two-char symbol
-        assert_eq!(symbol_at(n + n * n - 1).chars().count(), 2);
-        // First three-char symbol
-        assert_eq!(symbol_at(n + n * n).chars().count(), 3);
-    }
-
-    #[test]
-    fn test_symbol_at_no_duplicates() {
-        let n = SYMBOL_POOL.len();
-        // Verify the first n + n² symbols are all distinct
-        let symbols: Vec<String> = (0..n + n * n).map(symbol_at).collect();
-        let unique: std::collections::HashSet<&String> = symbols.iter().collect();
-        assert_eq!(symbols.len(), unique.len(), "symbol_at produced duplicates");
-    }
-
-    // -----------------------------------------------------------------------
-    // format_func_sig edge cases
-    // -----------------------------------------------------------------------
-
-    use crate::model::{FuncSignature, InterfaceType, ValueType};
-
-    fn make_arena() -> crate::model::TypeArena {
-        crate::model::TypeArena::default()
-    }
-
-    #[test]
-    fn test_format_func_sig_no_params() {
-        let mut arena = make_arena();
-        let bool_id = arena.intern_val(ValueType::Bool);
-        let sig = FuncSignature {
-            is_async: false,
-            param_names: vec![],
-            params: vec![],
-            results: vec![bool_id],
-        };
-        assert_eq!(format_func_sig(&sig, &arena), "() -> bool");
-    }
-
-    #[test]
-    fn test_format_func_sig_no_results() {
-        let mut arena = make_arena();
-        let u32_id = arena.intern_val(ValueType::U32);
-        let sig = FuncSignature {
-            is_async: false,
-            param_names: vec![],
-            params: vec![u32_id],
-            results: vec![],
-        };
-        assert_eq!(format_func_sig(&sig, &arena), "(u32) -> ()");
-    }
-
-    #[test]
-    fn test_format_func_sig_multiple_results() {
-        let mut arena = make_arena();
-        let u32_id = arena.intern_val(ValueType::U32);
-        let str_id = arena.intern_val(ValueType::String);
-        let bool_id = arena.intern_val(ValueType::Bool);
-        let sig = FuncSignature {
-            is_async: false,
-            param_names: vec![],
-            params: vec![u32_id, str_id],
-            results: vec![bool_id, str_id],
-        };
+    #[cfg(not(feature = "mermaid"))]
+    fn test_render_without_feature() {
+        let graph = crate::test_utils::simple_chain_graph();
+        let err = render(
+            &graph,
+            OutputFormat::Mermaid,
+            DetailLevel::default(),
+            Direction::default(),
+            &RenderOptions::default(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, CvizError::MissingFeature("mermaid")));
         assert_eq!(
-            format_func_sig(&sig, &arena),
-            "(u32, string) -> (bool, string)"
+            err.to_string(),
+            "cviz was built without the `mermaid` feature"
         );
     }
 
     #[test]
-    fn test_format_interface_type_lines_func_variant() {
-        let mut arena = make_arena();
-        let u32_id = arena.intern_val(ValueType::U32);
-        let bool_id = arena.intern_val(ValueType::Bool);
-        let sig = FuncSignature {
-            is_async: false,
-            param_names: vec![],
-            params: vec![u32_id],
-            results: vec![bool_id],
-        };
-        // Func variant: single bare sig line, no backtick-name prefix
-        let iface = InterfaceType::Func(sig);
-        let lines = format_interface_type_lines(&iface, &arena);
-        assert_eq!(lines, vec!["(u32) -> bool"]);
-    }
-
-    #[test]
-    fn test_connection_type_lines_missing_type_info() {
-        use crate::model::InterfaceConnection;
-        let arena = make_arena();
-        let conn = InterfaceConnection {
-            interface_name: "wasi:http/handler@0.3.0".to_string(),
-            source_instance: None,
-            is_host_import: true,
-            interface_type: None, // no type info
-            fingerprint: None,
+    #[cfg(feature = "ascii")]
+    fn test_show_synthetic() {
+        use diagram::{build_all_interfaces_view, drawn_chain};
+
+        // The handler is exported from `out`, a bundle of mw-inst's function
+        let wat = r#"(component
+  (import "wasi:http/handler@0.3.0" (instance $host
+      (export "handle" (func (param "req" u32) (result u32)))))
+  (component $mw
+    (import "wasi:http/handler@0.3.0" (instance $h (export "handle" (func (param "req" u32) (result u32)))))
+    (alias export $h "handle" (func $f))
+    (instance $o (export "handle" (func $f)))
+    (export "wasi:http/handler@0.3.0" (instance $o)))
+  (component $srv
+    (import "wasi:http/handler@0.3.0" (instance $h (export "handle" (func (param "req" u32) (result u32)))))
+    (alias export $h "handle" (func $f))
+    (instance $o (export "handle" (func $f)))
+    (export "wasi:http/handler@0.3.0" (instance $o)))
+  (instance $srv-inst (instantiate $srv (with "wasi:http/handler@0.3.0" (instance $host))))
+  (instance $mw-inst (instantiate $mw (with "wasi:http/handler@0.3.0" (instance $srv-inst "wasi:http/handler@0.3.0"))))
+  (alias export $mw-inst "wasi:http/handler@0.3.0" (instance $e))
+  (alias export $e "handle" (func $g))
+  (instance $out (export "handle" (func $g)))
+  (export "wasi:http/handler@0.3.0" (instance $out))
+)"#;
+        let bytes = wat::parse_str(wat).unwrap();
+        let graph = crate::parse::component::parse_component(&bytes).unwrap();
+        let bundle = graph.find_node("out").unwrap();
+
+        let hidden = RenderOptions::default();
+        let shown = RenderOptions {
+            show_synthetic: true,
+            ..RenderOptions::default()
         };
-        // show_types=true but no type info → should return empty, not panic
-        let lines = connection_type_lines(&conn, &arena, true);
-        assert!(
-            lines.is_empty(),
-            "missing type info should produce no type lines"
-        );
-    }
-
-    // -----------------------------------------------------------------------
-    // ConnectionsView IR tests
-    // -----------------------------------------------------------------------
-
-    use crate::test_utils::*;
-
-    #[test]
-    fn test_view_all_interfaces_node_count() {
-        let graph = simple_chain_graph();
-        let view = build_all_interfaces_view(&graph, false);
-        // $srv and $middleware are real; no synthetic nodes in this graph
-        assert_eq!(view.nodes.len(), 2);
-        assert!(view.nodes.iter().any(|n| n.display.contains("srv")));
-        assert!(view.nodes.iter().any(|n| n.display.contains("middleware")));
-    }
+        let chain = drawn_chain(&graph, "wasi:http/handler@0.3.0", &hidden);
+        assert!(!chain.contains(&bundle), "{:?}", chain);
+        let chain = drawn_chain(&graph, "wasi:http/handler@0.3.0", &shown);
+        assert_eq!(chain[0], bundle, "{:?}", chain);
+
+        let view = build_all_interfaces_view(&graph, false, false);
+        assert!(view.nodes.iter().all(|n| !n.is_synthetic));
+        let view = build_all_interfaces_view(&graph, false, true);
+        assert!(view
+            .nodes
+            .iter()
+            .any(|n| n.is_synthetic && n.name.contains("out")));
+        assert!(view.exports.iter().any(|e| e.from_name.contains("out")));
 
-    #[test]
-    fn test_view_all_interfaces_host_names() {
-        let graph = simple_chain_graph();
-        let view = build_all_interfaces_view(&graph, false);
-        // Two distinct host interfaces: handler (from srv) and log (from middleware)
-        assert_eq!(view.host_names.len(), 2);
-        assert!(view.host_names.iter().any(|n| n.contains("handler")));
-        assert!(view.host_names.iter().any(|n| n.contains("log")));
+        let ascii = ascii::generate_ascii_with_options(&graph, DetailLevel::HandlerChain, &shown);
+        assert!(ascii.contains("│ out (synthetic) │"), "{}", ascii);
+        let ascii = ascii::generate_ascii_with_options(&graph, DetailLevel::AllInterfaces, &hidden);
+        assert!(!ascii.contains("[out]"), "{}", ascii);
     }
 
     #[test]
-    fn test_view_all_interfaces_edge_dashed() {
-        let graph = simple_chain_graph();
-        let view = build_all_interfaces_view(&graph, false);
-        // 2 host-import edges (dashed) + 1 component edge (solid)
-        let dashed: Vec<_> = view.edges.iter().filter(|e| e.is_dashed).collect();
-        let solid: Vec<_> = view.edges.iter().filter(|e| !e.is_dashed).collect();
+    fn test_combine_sections() {
+        let sections = [
+            Section {
+                title: "a.wasm".to_string(),
+                body: "graph LR\n    a\n".to_string(),
+                diagram: true,
+            },
+            Section {
+                title: "b.wasm".to_string(),
+                body: "Score: 90/100".to_string(),
+                diagram: false,
+            },
+        ];
         assert_eq!(
-            dashed.len(),
-            2,
-            "two host imports should produce dashed edges"
+            combine_sections(OutputFormat::Ascii, &sections),
+            "== a.wasm ==\n\ngraph LR\n    a\n\n== b.wasm ==\n\nScore: 90/100\n"
         );
         assert_eq!(
-            solid.len(),
-            1,
-            "one inter-component import should produce a solid edge"
+            combine_sections(OutputFormat::Mermaid, &sections),
+            "## a.wasm\n\n```mermaid\ngraph LR\n    a\n```\n\n## b.wasm\n\nScore: 90/100\n"
         );
-    }
 
-    #[test]
-    fn test_view_all_interfaces_edge_endpoints() {
-        let graph = simple_chain_graph();
-        let view = build_all_interfaces_view(&graph, false);
-        let solid = view.edges.iter().find(|e| !e.is_dashed).unwrap();
-        assert!(
-            solid.from_display.contains("srv"),
-            "solid edge should come from srv"
-        );
-        assert!(
-            solid.to_display.contains("middleware"),
-            "solid edge should go to middleware"
-        );
+        let json = [Section {
+            title: "a.wasm".to_string(),
+            body: "{\"nodes\":[]}".to_string(),
+            diagram: true,
+        }];
         assert_eq!(
-            solid.label, "handler",
-            "edge label should be short interface name"
-        );
-    }
-
-    #[test]
-    fn test_view_all_interfaces_export() {
-        let graph = simple_chain_graph();
-        let view = build_all_interfaces_view(&graph, false);
-        assert_eq!(view.exports.len(), 1);
-        let exp = &view.exports[0];
-        assert!(exp.from_display.contains("middleware"));
-        assert_eq!(exp.short_name, "handler");
-        assert!(exp.full_name.contains("wasi:http/handler"));
-    }
-
-    #[test]
-    fn test_view_all_interfaces_non_http_chain() {
-        // Verify the IR works for a non-http chain (keyvalue/store)
-        let graph = two_chain_graph();
-        let view = build_all_interfaces_view(&graph, false);
-        let kv_export = view
-            .exports
-            .iter()
-            .find(|e| e.full_name.contains("keyvalue"));
-        assert!(kv_export.is_some(), "should have a keyvalue export");
-        assert_eq!(kv_export.unwrap().short_name, "store");
-
-        let kv_solid = view
-            .edges
-            .iter()
-            .find(|e| !e.is_dashed && e.label == "store");
-        assert!(kv_solid.is_some(), "should have solid keyvalue/store edge");
-        let kv_solid = kv_solid.unwrap();
-        assert!(kv_solid.from_display.contains("db"));
-        assert!(kv_solid.to_display.contains("cache"));
-    }
-
-    #[test]
-    fn test_view_all_interfaces_excludes_synthetic_source() {
-        // A synthetic node as the *source* of an import should not produce an
-        // edge in AllInterfaces mode (only real component sources are shown).
-        use crate::model::{ComponentNode, InterfaceConnection, SYNTHETIC_COMPONENT};
-
-        let mut graph = CompositionGraph::new();
-
-        // A real component that imports from a synthetic source
-        let mut real = ComponentNode::new("$real".to_string(), 0, 0);
-        real.add_import(InterfaceConnection {
-            interface_name: "wasi:http/handler@0.3.0".to_string(),
-            source_instance: Some(99), // will be a synthetic node
-            is_host_import: false,
-            interface_type: None,
-            fingerprint: None,
-        });
-        graph.add_node(1, real);
-
-        // The synthetic node at idx 99
-        let synthetic = ComponentNode::new(
-            "$synthetic".to_string(),
-            SYNTHETIC_COMPONENT,
-            SYNTHETIC_COMPONENT,
-        );
-        graph.add_node(99, synthetic);
-
-        let view = build_all_interfaces_view(&graph, false);
-
-        // The edge from the synthetic source should be dropped
-        assert!(
-            view.edges.is_empty(),
-            "edges from synthetic source nodes should be excluded, got: {:?}",
-            view.edges
-                .iter()
-                .map(|e| (&e.from_display, &e.to_display))
-                .collect::<Vec<_>>()
-        );
-    }
-
-    #[test]
-    fn test_view_full_includes_all_nodes() {
-        let graph = simple_chain_graph();
-        let view = build_full_view(&graph, false);
-        // Full includes all nodes; host_names is empty
-        assert!(view.nodes.len() >= 2);
-        assert!(
-            view.host_names.is_empty(),
-            "Full mode has no host node list"
+            combine_sections(OutputFormat::Json, &json),
+            r#"[{"input":"a.wasm","output":{"nodes":[]}}]"#
         );
     }
 
     #[test]
-    fn test_view_full_no_dashed_edges() {
-        let graph = simple_chain_graph();
-        let view = build_full_view(&graph, false);
-        assert!(
-            view.edges.iter().all(|e| !e.is_dashed),
-            "Full mode skips host imports so no edge should be dashed"
-        );
+    fn test_human_size() {
+        assert_eq!(human_size(512), "512 B");
+        assert_eq!(human_size(1536), "1.5 KiB");
+        assert_eq!(human_size(1_258_291), "1.2 MiB");
+        assert_eq!(human_size(3 << 30), "3.0 GiB");
     }
 
     #[test]
-    fn test_view_full_edge_uses_full_name() {
-        let graph = simple_chain_graph();
-        let view = build_full_view(&graph, false);
-        let edge = view
-            .edges
-            .iter()
-            .find(|e| e.label.contains("handler"))
-            .unwrap();
-        assert!(
-            edge.label.contains("wasi:http/handler@0.3.0"),
-            "Full mode should use full interface name as label, got: {}",
-            edge.label
-        );
-    }
-
-    #[test]
-    fn test_view_all_interfaces_two_chains() {
-        let graph = two_chain_graph();
-        let view = build_all_interfaces_view(&graph, false);
-        // 4 real nodes: srv-http, mw-http, db, cache
-        assert_eq!(view.nodes.len(), 4);
-        // 2 solid edges (one per chain) + 2 host-import edges (one per inner node)
-        let solid: Vec<_> = view.edges.iter().filter(|e| !e.is_dashed).collect();
-        assert_eq!(solid.len(), 2, "two inter-component edges expected");
-        // 2 exports
-        assert_eq!(view.exports.len(), 2);
-        let names: Vec<&str> = view.exports.iter().map(|e| e.short_name.as_str()).collect();
-        assert!(names.contains(&"handler"), "should have handler export");
-        assert!(names.contains(&"store"), "should have store export");
+    fn test_direction_parse() {
+        assert!(matches!(
+            "lr".parse::<Direction>().unwrap(),
+            Direction::LeftToRight
+        ));
+        assert!(matches!(
+            "td".parse::<Direction>().unwrap(),
+            Direction::TopDown
+        ));
     }
 
     #[test]
-    fn test_view_full_synthetic_node_included() {
-        use crate::model::{ComponentNode, SYNTHETIC_COMPONENT};
-        let mut graph = CompositionGraph::new();
-
-        let real = ComponentNode::new("$real".to_string(), 0, 0);
-        graph.add_node(1, real);
-        let synthetic = ComponentNode::new(
-            "$synth".to_string(),
-            SYNTHETIC_COMPONENT,
-            SYNTHETIC_COMPONENT,
-        );
-        graph.add_node(99, synthetic);
-
-        let view = build_full_view(&graph, false);
-        assert_eq!(
-            view.nodes.len(),
-            2,
-            "Full mode should include synthetic nodes"
-        );
-        assert!(
-            view.nodes.iter().any(|n| n.is_synthetic),
-            "synthetic flag should be set"
-        );
-        assert!(view.nodes.iter().any(|n| n.display.contains("synth")));
+    fn test_detail_level_parse() {
+        assert!(matches!(
+            "handler-chain".parse::<DetailLevel>().unwrap(),
+            DetailLevel::HandlerChain
+        ));
+        assert!(matches!(
+            "all-interfaces".parse::<DetailLevel>().unwrap(),
+            DetailLevel::AllInterfaces
+        ));
+        assert!(matches!(
+            "full".parse::<DetailLevel>().unwrap(),
+            DetailLevel::Full
+        ));
     }
 
     #[test]
-    fn test_view_host_interfaces_deduplicated() {
-        use crate::model::{ComponentNode, InterfaceConnection};
-        let mut graph = CompositionGraph::new();
-
-        // Two real nodes both importing the same host interface
-        let mut a = ComponentNode::new("$a".to_string(), 0, 0);
-        a.add_import(InterfaceConnection {
-            interface_name: "wasi:logging/log@0.1.0".to_string(),
-            source_instance: None,
-            is_host_import: true,
-            interface_type: None,
-            fingerprint: None,
-        });
-        graph.add_node(1, a);
-
-        let mut b = ComponentNode::new("$b".to_string(), 1, 1);
-        b.add_import(InterfaceConnection {
-            interface_name: "wasi:logging/log@0.1.0".to_string(),
-            source_instance: None,
-            is_host_import: true,
-            interface_type: None,
-            fingerprint: None,
-        });
-        graph.add_node(2, b);
-
-        let view = build_all_interfaces_view(&graph, false);
-        assert_eq!(
-            view.host_names.len(),
-            1,
-            "same host interface imported by two nodes should appear once"
-        );
-        assert_eq!(view.host_names[0], "wasi:logging/log@0.1.0");
+    fn test_host_group() {
+        assert_eq!(host_group("wasi:io/streams@0.2.6"), Some("wasi:io"));
+        assert_eq!(host_group("my:app/config"), None);
+        assert_eq!(host_group("import-type-request"), None);
     }
 }
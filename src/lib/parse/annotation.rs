@@ -0,0 +1,122 @@
+//! Diagrams embedded in a component binary by `cviz annotate`.
+//!
+//! The JSON graph and a mermaid diagram of a composition can be stored in
+//! custom sections of the binary itself, so the artifact documents its own
+//! structure for anyone holding it.  Only top-level sections are read and
+//! written; those of nested components and core modules are left alone.
+
+use super::sections::{custom_section, sections, write_u32};
+use crate::error::{ParseError, Result};
+
+/// Custom section holding the graph as written by `--format json`.
+pub const GRAPH_SECTION: &str = "cviz:graph";
+/// Custom section holding a mermaid diagram.
+pub const MERMAID_SECTION: &str = "cviz:mermaid";
+
+/// The diagrams embedded in a binary.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Annotations {
+    /// The JSON graph, from [`GRAPH_SECTION`].
+    pub graph: Option<String>,
+    /// The mermaid diagram, from [`MERMAID_SECTION`].
+    pub mermaid: Option<String>,
+}
+
+/// Read the diagrams embedded in `buff`.
+pub fn read_annotations(buff: &[u8]) -> Result<Annotations> {
+    let mut annotations = Annotations::default();
+    for section in sections(buff)?.iter().filter(|s| s.id == 0) {
+        let (name, data) = custom_section(&buff[section.contents.clone()])?;
+        let slot = match name {
+            GRAPH_SECTION => &mut annotations.graph,
+            MERMAID_SECTION => &mut annotations.mermaid,
+            _ => continue,
+        };
+        let text = std::str::from_utf8(data).map_err(|e| {
+            ParseError::caused_by(format!("custom section {} is not UTF-8", name), e)
+                .at(section.range.start)
+        })?;
+        *slot = Some(text.to_string());
+    }
+    Ok(annotations)
+}
+
+/// Copy `buff` with `annotations` embedded at its end, replacing any
+/// diagrams embedded earlier.  Sections for unset fields are removed.
+pub fn write_annotations(buff: &[u8], annotations: &Annotations) -> Result<Vec<u8>> {
+    let mut out = buff[..8.min(buff.len())].to_vec();
+    for section in sections(buff)? {
+        if section.id == 0 {
+            let (name, _) = custom_section(&buff[section.contents.clone()])?;
+            if name == GRAPH_SECTION || name == MERMAID_SECTION {
+                continue;
+            }
+        }
+        out.extend_from_slice(&buff[section.range]);
+    }
+
+    let embedded = [
+        (GRAPH_SECTION, &annotations.graph),
+        (MERMAID_SECTION, &annotations.mermaid),
+    ];
+    for (name, text) in embedded {
+        let Some(text) = text else { continue };
+        let mut contents = Vec::new();
+        write_u32(&mut contents, name.len() as u32);
+        contents.extend_from_slice(name.as_bytes());
+        contents.extend_from_slice(text.as_bytes());
+        out.push(0);
+        let size = u32::try_from(contents.len())
+            .map_err(|e| ParseError::caused_by("annotation is too large", e))?;
+        write_u32(&mut out, size);
+        out.extend_from_slice(&contents);
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::component::parse_component;
+
+    #[test]
+    fn test_annotations_round_trip() {
+        let wasm = wat::parse_str(
+            r#"(component
+                (component $c)
+                (instance $srv (instantiate $c))
+                (@custom "licenses" "MIT")
+            )"#,
+        )
+        .unwrap();
+        assert_eq!(read_annotations(&wasm).unwrap(), Annotations::default());
+
+        let annotations = Annotations {
+            graph: Some("{}".to_string()),
+            mermaid: Some("graph LR\n".repeat(100)),
+        };
+        let annotated = write_annotations(&wasm, &annotations).unwrap();
+        assert_eq!(read_annotations(&annotated).unwrap(), annotations);
+        // Other sections survive and the binary still parses
+        let graph = parse_component(&annotated).unwrap();
+        assert_eq!(graph.nodes.len(), 1);
+        assert_eq!(graph.metadata[&None].licenses.as_deref(), Some("MIT"));
+
+        // Annotating again replaces, rather than adds to, earlier diagrams
+        let replaced = Annotations {
+            graph: Some("{\"nodes\":[]}".to_string()),
+            mermaid: None,
+        };
+        let reannotated = write_annotations(&annotated, &replaced).unwrap();
+        assert_eq!(read_annotations(&reannotated).unwrap(), replaced);
+        assert_eq!(
+            write_annotations(&reannotated, &Annotations::default()).unwrap(),
+            wasm
+        );
+    }
+
+    #[test]
+    fn test_rejects_non_wasm() {
+        assert!(read_annotations(b"{\"nodes\":[]}").is_err());
+    }
+}
@@ -1,19 +1,30 @@
+use crate::error::{CvizError, ParseError, Result};
 use crate::model::{
-    ComponentNode, CompositionGraph, FuncSignature, InstanceInterface, InterfaceConnection,
-    InterfaceType, TypeArena, ValueType, ValueTypeId, SYNTHETIC_COMPONENT,
+    naming, ComponentMetadata, ComponentNode, CompositionGraph, CoreInstance, CoreInstanceKind,
+    CoreModule, CoreStructure, ExportInfo, ExternKind, FuncSignature, InstanceInterface,
+    InterfaceConnection, InterfaceType, TypeArena, ValueType, ValueTypeId, WasmFeature,
+    SYNTHETIC_COMPONENT,
 };
-use anyhow::Result;
-use std::collections::HashMap;
+use crate::parse::features::ModuleFeatures;
+use crate::parse::limits::{self, Limits};
+use crate::parse::module;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::ops::Range;
+use std::panic::AssertUnwindSafe;
+use tracing::{debug, trace};
 use wirm::ir::component::concrete::{ConcreteFuncType, ConcreteType, ConcreteValType};
 use wirm::ir::component::refs::{GetCompRefs, GetItemRef};
 use wirm::ir::component::visitor::{
     walk_structural, ComponentVisitor, ItemKind, ResolvedItem, VisitCtx,
 };
+use wirm::ir::types::CustomSection;
 use wirm::wasmparser::{
-    ComponentAlias, ComponentExport, ComponentExternalKind, ComponentInstance, ComponentTypeRef,
-    PrimitiveValType,
+    BinaryReader, ComponentAlias, ComponentExport, ComponentExternalKind, ComponentInstance,
+    ComponentTypeRef, Instance, Parser, Payload, PrimitiveValType, ProducersSectionReader,
 };
-use wirm::Component;
+use wirm::{Component, Module};
 
 /// Parse the top-level interface (instance-kind) imports of a plain Wasm component.
 ///
@@ -29,30 +40,194 @@ use wirm::Component;
 pub fn parse_component_imports(buff: &[u8]) -> Result<Vec<(String, Option<String>)>> {
     use wirm::wasmparser::ComponentTypeRef;
 
-    let component = Component::parse(buff, false, false).expect("Unable to parse");
-    let mut arena = crate::model::TypeArena::default();
-    let mut imports = Vec::new();
-
-    for import in component.imports.iter() {
-        if let ComponentTypeRef::Instance(_) = import.ty {
-            let name = import.name.0.to_string();
-            let fingerprint = component
-                .concretize_import(&name)
-                .and_then(|ct| concrete_to_interface_type(ct, &mut arena))
-                .map(|it| it.fingerprint(&arena));
-            imports.push((name, fingerprint));
+    contain_panics(|| {
+        let component = read_component(buff, &Limits::default())?;
+        let mut arena = crate::model::TypeArena::default();
+        let mut imports = Vec::new();
+
+        for import in component.imports.iter() {
+            if let ComponentTypeRef::Instance(_) = import.ty {
+                let name = import.name.0.to_string();
+                let fingerprint = component
+                    .concretize_import(&name)
+                    .and_then(|ct| concrete_to_interface_type(ct, &mut arena))
+                    .map(|it| it.fingerprint(&arena));
+                imports.push((name, fingerprint));
+            }
+        }
+
+        Ok(imports)
+    })
+}
+
+/// Parse the top-level interface (instance-kind) exports of a plain Wasm component.
+///
+/// The export-side counterpart to [`parse_component_imports`], returning one
+/// `(interface_name, fingerprint)` pair per `instance`-kind export.
+pub fn parse_component_exports(buff: &[u8]) -> Result<Vec<(String, Option<String>)>> {
+    contain_panics(|| {
+        let component = read_component(buff, &Limits::default())?;
+        let mut arena = crate::model::TypeArena::default();
+        let mut exports = Vec::new();
+
+        for export in component.exports.iter() {
+            if export.kind == ComponentExternalKind::Instance {
+                let name = export.name.0.to_string();
+                let fingerprint = component
+                    .concretize_export(&name)
+                    .and_then(|ct| concrete_to_interface_type(ct, &mut arena))
+                    .map(|it| it.fingerprint(&arena));
+                exports.push((name, fingerprint));
+            }
+        }
+
+        Ok(exports)
+    })
+}
+
+/// Options for [`parse_component_with_options`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ParseOptions {
+    /// Fail with [`Anomalies`] instead of skipping over references the
+    /// parser cannot make sense of.
+    pub strict: bool,
+    /// Bounds the binary is held to before it is parsed.
+    pub limits: Limits,
+}
+
+/// Check `buff` against `limits` and read it.
+fn read_component<'a>(buff: &'a [u8], limits: &Limits) -> Result<Component<'a>> {
+    limits::check(buff, limits)?;
+    Component::parse(buff, false, false).map_err(|e| CvizError::binary(buff, e))
+}
+
+/// Run `parse`, turning a panic into a [`ParseError`].
+///
+/// [`limits::check`] keeps invalid binaries away from the component parser,
+/// except for the sections after a rejected instantiation, which it cannot
+/// validate.  A panic on a corrupt one of those is the parser's, not a
+/// bug in the caller, so it is reported like any other unreadable binary.
+/// This does not help in a build with `panic = "abort"`.
+fn contain_panics<T>(parse: impl FnOnce() -> Result<T>) -> Result<T> {
+    std::panic::catch_unwind(AssertUnwindSafe(parse)).unwrap_or_else(|panic| {
+        let reason = panic
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("the parser panicked");
+        let message = format!("could not read the component: {}", reason);
+        Err(ParseError::new(message).into())
+    })
+}
+
+/// A reference in a component that the parser could not follow.
+///
+/// Outside strict mode these are skipped: the affected import is drawn as a
+/// host import and the affected export is left out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Anomaly {
+    /// Resolving an alias led back to an alias already followed.
+    AliasLoop {
+        /// What was being resolved, e.g. `export wasi:http/handler@0.3.0`.
+        context: String,
+    },
+    /// An instance is plugged in from an instance that is not part of the
+    /// composition.
+    UnknownInstance {
+        instance: String,
+        interface: String,
+        /// `component_num` of the nested component containing `instance`,
+        /// or `None` in the root component.
+        scope: Option<u32>,
+    },
+    /// A root export refers to an instance that is not part of the
+    /// composition.
+    MissingExportSource { export: String },
+}
+
+impl fmt::Display for Anomaly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Anomaly::AliasLoop { context } => write!(f, "alias loop while resolving {}", context),
+            Anomaly::UnknownInstance {
+                instance,
+                interface,
+                scope,
+            } => {
+                write!(
+                    f,
+                    "instance {} imports {} from an unknown instance",
+                    instance, interface
+                )?;
+                match scope {
+                    Some(num) => write!(f, " (in component {})", num),
+                    None => Ok(()),
+                }
+            }
+            Anomaly::MissingExportSource { export } => write!(
+                f,
+                "export {} refers to an instance missing from the composition",
+                export
+            ),
         }
     }
+}
 
-    Ok(imports)
+/// The anomalies that failed a strict parse, in the order they were found.
+///
+/// Returned (as [`CvizError::Anomalies`]) by [`parse_component_with_options`]
+/// when [`ParseOptions::strict`] is set.
+#[derive(Debug)]
+pub struct Anomalies(pub Vec<Anomaly>);
+
+impl fmt::Display for Anomalies {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} parse anomaly(ies):", self.0.len())?;
+        for anomaly in &self.0 {
+            write!(f, "\n  - {}", anomaly)?;
+        }
+        Ok(())
+    }
 }
 
-/// Parse a WebAssembly component file and extract its composition graph
+impl std::error::Error for Anomalies {}
+
+/// Parse a WebAssembly component file and extract its composition graph.
+///
+/// A core module fails with [`CvizError::NotAComponent`], which carries its
+/// imports and exports; a binary that cannot be read fails with
+/// [`CvizError::Parse`], located at the offending section and offset, as
+/// does one that is invalid or exceeds the default [`Limits`].
 pub fn parse_component(buff: &[u8]) -> Result<CompositionGraph> {
-    let component = Component::parse(buff, false, false).expect("Unable to parse");
+    parse_component_with_options(buff, &ParseOptions::default())
+}
+
+/// [`parse_component`], failing with [`Anomalies`] in strict mode.
+pub fn parse_component_with_options(
+    buff: &[u8],
+    options: &ParseOptions,
+) -> Result<CompositionGraph> {
+    if module::is_core_module(buff) {
+        return Err(module::NotAComponent(module::parse_module(buff)?).into());
+    }
+    limits::check(buff, &options.limits)?;
+    read_checked(buff, options)
+}
+
+/// Read the component in `buff`, which has passed [`limits::check`], into a
+/// graph.
+pub(crate) fn read_checked(buff: &[u8], options: &ParseOptions) -> Result<CompositionGraph> {
+    contain_panics(|| read_graph(buff, options))
+}
+
+fn read_graph(buff: &[u8], options: &ParseOptions) -> Result<CompositionGraph> {
+    let component = Component::parse(buff, false, false).map_err(|e| CvizError::binary(buff, e))?;
     let mut visitor = Visitor::new();
 
     walk_structural(&component, &mut visitor);
+    if options.strict && !visitor.anomalies.is_empty() {
+        return Err(Anomalies(visitor.anomalies).into());
+    }
     visitor.postprocess();
 
     // Post-process: fill in fingerprints for top-level instance exports that the visitor
@@ -72,8 +247,7 @@ pub fn parse_component(buff: &[u8]) -> Result<CompositionGraph> {
                 if let Some(it) = concrete_to_interface_type(ct, &mut visitor.graph.arena) {
                     let source = visitor
                         .graph
-                        .component_exports
-                        .get(name)
+                        .export(name)
                         .map_or(SYNTHETIC_COMPONENT, |e| e.source_instance);
                     visitor.graph.add_export(name.to_string(), source, Some(it));
                 }
@@ -81,7 +255,104 @@ pub fn parse_component(buff: &[u8]) -> Result<CompositionGraph> {
         }
     }
 
-    Ok(visitor.graph)
+    let mut graph = visitor.graph;
+    let components = embedded_components(buff).map_err(|e| CvizError::binary(buff, e))?;
+    for node in graph.nodes.values_mut() {
+        let Some(embedded) = components.get(node.component_num as usize) else {
+            continue;
+        };
+        let Some(bytes) = buff.get(embedded.range.clone()) else {
+            continue;
+        };
+        node.size = Some(bytes.len() as u64);
+        node.digest = Some(format!("sha256:{}", hex::encode(Sha256::digest(bytes))));
+        node.features = embedded.features.clone();
+        node.runs_start = embedded.runs_start;
+    }
+    naming::resolve_labels(&mut graph, &naming::default_resolvers());
+    graph.validate()?;
+    debug!(
+        instances = graph.nodes.len(),
+        exports = graph.component_exports.len(),
+        anomalies = visitor.anomalies.len(),
+        "parsed component"
+    );
+    Ok(graph)
+}
+
+/// A component nested in the binary being parsed.
+struct EmbeddedComponent {
+    /// Where the component's own binary lies.
+    range: Range<usize>,
+    /// Features used by the core modules inside it, at any depth.
+    features: BTreeSet<WasmFeature>,
+    /// Whether it, or a component or core module inside it, has a start
+    /// function.
+    runs_start: bool,
+}
+
+/// The components nested in `buff`, at any depth, in the order they appear
+/// in the binary: the order the visitor numbers them in, so that a node's
+/// `component_num` indexes its component.
+fn embedded_components(buff: &[u8]) -> wirm::wasmparser::Result<Vec<EmbeddedComponent>> {
+    let mut components: Vec<EmbeddedComponent> = Vec::new();
+    // One entry per nested component or core module being read: the
+    // component's index in `components`, or `None` for a core module.
+    let mut open: Vec<Option<usize>> = Vec::new();
+    let mut module: Option<ModuleFeatures> = None;
+    for payload in Parser::new(0).parse_all(buff) {
+        let payload = payload?;
+        match &payload {
+            Payload::ComponentSection {
+                unchecked_range, ..
+            } => {
+                debug!(
+                    component = components.len(),
+                    depth = open.len(),
+                    range = ?unchecked_range,
+                    "nested component section"
+                );
+                open.push(Some(components.len()));
+                components.push(EmbeddedComponent {
+                    range: unchecked_range.clone(),
+                    features: BTreeSet::new(),
+                    runs_start: false,
+                });
+            }
+            Payload::ModuleSection {
+                unchecked_range, ..
+            } => {
+                debug!(depth = open.len(), range = ?unchecked_range, "core module section");
+                open.push(None);
+                module = Some(ModuleFeatures::default());
+            }
+            Payload::ComponentStartSection { .. } | Payload::StartSection { .. } => {
+                debug!(depth = open.len(), "start section");
+                for &index in open.iter().flatten() {
+                    if let Some(component) = components.get_mut(index) {
+                        component.runs_start = true;
+                    }
+                }
+            }
+            Payload::End(_) => {
+                // The root component's own end leaves nothing to pop.
+                if let Some(None) = open.pop() {
+                    let features = module.take().unwrap_or_default().finish();
+                    for &index in open.iter().flatten() {
+                        if let Some(component) = components.get_mut(index) {
+                            component.features.extend(&features);
+                        }
+                    }
+                }
+            }
+            payload => {
+                if let Some(module) = &mut module {
+                    module.record(payload)?;
+                }
+            }
+        }
+    }
+    Ok(components)
 }
 struct Visitor {
     curr_comp_num: u32,
@@ -97,6 +368,11 @@ struct Visitor {
     /// scope-independent identity lets us correctly correlate inner-scope shim
     /// instances across scope boundaries.
     inst_ptr_to_graph_id: HashMap<usize, u32>,
+    /// `component_num` of each nested component currently being walked
+    /// (empty while in the root component).
+    comp_num_stack: Vec<u32>,
+    /// References that could not be followed, see [`Anomaly`].
+    anomalies: Vec<Anomaly>,
 }
 impl Visitor {
     pub fn new() -> Self {
@@ -106,8 +382,129 @@ impl Visitor {
             graph: CompositionGraph::new(),
             next_graph_id: 0,
             inst_ptr_to_graph_id: HashMap::new(),
+            comp_num_stack: Vec::new(),
+            anomalies: Vec::new(),
         }
     }
+
+    /// Add the connection through which `node` receives `item` as
+    /// `interface_name`: from the instance providing it, or from the host.
+    fn add_connection(
+        &mut self,
+        cx: &VisitCtx,
+        node: &mut ComponentNode,
+        interface_name: String,
+        kind: ExternKind,
+        interface_type: Option<InterfaceType>,
+        item: ResolvedItem,
+    ) {
+        match item {
+            ResolvedItem::CompInst(_, inst) => {
+                let source = self
+                    .inst_ptr_to_graph_id
+                    .get(&(inst as *const ComponentInstance as usize))
+                    .copied();
+                if source.is_none() {
+                    self.anomaly(Anomaly::UnknownInstance {
+                        instance: node.name.clone(),
+                        interface: interface_name.clone(),
+                        scope: self.comp_num_stack.last().copied(),
+                    });
+                }
+                trace!(instance = %node.name, interface = %interface_name, provider = ?source, "connected");
+                let mut connection = InterfaceConnection::from_instance(
+                    interface_name,
+                    source,
+                    interface_type,
+                    &self.graph.arena,
+                );
+                connection.kind = kind;
+                node.add_import(connection);
+            }
+            ResolvedItem::Import(_id, imp) => {
+                // Satisfied by the host (component import section),
+                // not by another composed instance — always a host import.
+                if let ComponentTypeRef::Instance(_)
+                | ComponentTypeRef::Func(_)
+                | ComponentTypeRef::Value(_)
+                | ComponentTypeRef::Type(_) = imp.ty
+                {
+                    trace!(instance = %node.name, interface = %interface_name, "connected to host");
+                    let mut connection = InterfaceConnection::from_instance(
+                        interface_name,
+                        None,
+                        interface_type,
+                        &self.graph.arena,
+                    );
+                    connection.source_export = Some(imp.name.0.to_string());
+                    connection.kind = kind;
+                    node.add_import(connection);
+                }
+            }
+            ResolvedItem::Alias(_, alias) => {
+                let Some((target, chain)) = follow_alias(cx, alias) else {
+                    self.anomaly(Anomaly::AliasLoop {
+                        context: format!("{} of instance {}", interface_name, node.name),
+                    });
+                    return;
+                };
+                debug!(
+                    instance = %node.name,
+                    interface = %interface_name,
+                    through = ?chain,
+                    "resolved alias"
+                );
+                let added = node.imports.len();
+                self.add_connection(cx, node, interface_name, kind, interface_type, target);
+                // The outermost alias names the export that was plugged in,
+                // unless the host provides it.
+                if let Some(conn) = node.imports.get_mut(added) {
+                    conn.alias_chain = chain;
+                    if let ComponentAlias::InstanceExport { name, .. } = alias {
+                        conn.source_export.get_or_insert_with(|| name.to_string());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Record a reference that could not be followed.
+    fn anomaly(&mut self, anomaly: Anomaly) {
+        debug!(%anomaly, "skipping reference");
+        self.anomalies.push(anomaly);
+    }
+
+    /// The core structure of the nested component currently being walked,
+    /// or `None` in the root component.
+    fn curr_core(&mut self) -> Option<&mut CoreStructure> {
+        let num = *self.comp_num_stack.last()?;
+        Some(self.graph.core.entry(num).or_default())
+    }
+
+    /// Merge a custom section into the metadata of the component currently
+    /// being walked.
+    fn record_metadata(&mut self, name: &str, data: &[u8]) {
+        let key = self.comp_num_stack.last().copied();
+        let mut metadata = self.graph.metadata.remove(&key).unwrap_or_default();
+        read_metadata_section(&mut metadata, name, data);
+        if !metadata.is_empty() {
+            self.graph.metadata.insert(key, metadata);
+        }
+    }
+
+    /// The graph ID of `inst`, the source of root export `export_name`,
+    /// recording an anomaly if it is not part of the graph.
+    fn export_source(&mut self, inst: &ComponentInstance, export_name: &str) -> Option<u32> {
+        let ptr = inst as *const ComponentInstance as usize;
+        let graph_id = self.inst_ptr_to_graph_id.get(&ptr).copied();
+        if graph_id.is_none() {
+            self.anomaly(Anomaly::MissingExportSource {
+                export: export_name.to_string(),
+            });
+        }
+        graph_id
+    }
     pub fn postprocess(&mut self) {
         // Mark host imports on the connections.
         // Any import whose source_instance is not a known graph node (or is None)
@@ -131,19 +528,74 @@ impl ComponentVisitor<'_> for Visitor {
     fn enter_root_component(&mut self, _cx: &VisitCtx<'_>, _component: &Component<'_>) {
         self.comp_id_to_num.push(HashMap::new());
     }
-    fn exit_root_component(&mut self, _cx: &VisitCtx<'_>, _component: &Component<'_>) {
+    fn exit_root_component(&mut self, cx: &VisitCtx<'_>, _component: &Component<'_>) {
+        if let Some(name) = cx.lookup_root_comp_name().filter(|n| n.contains(':')) {
+            self.graph.metadata.entry(None).or_default().name = Some(name.to_string());
+        }
         self.comp_id_to_num.pop();
     }
     fn enter_component(&mut self, _cx: &VisitCtx, id: u32, _component: &Component) {
         if let Some(outer) = self.comp_id_to_num.last_mut() {
             outer.insert(id, self.curr_comp_num);
         }
+        self.comp_num_stack.push(self.curr_comp_num);
         self.curr_comp_num += 1;
         self.comp_id_to_num.push(HashMap::new());
     }
 
-    fn exit_component(&mut self, _: &VisitCtx, _: u32, _component: &Component) {
+    fn exit_component(&mut self, cx: &VisitCtx, _: u32, _component: &Component) {
+        // While exiting, the context still points at the nested component, so
+        // this is the component's own name rather than the root's.  Only
+        // `namespace:name` package names are kept; text-format identifiers
+        // like `$lib` also end up in the name section.
+        let name = cx.lookup_root_comp_name().filter(|n| n.contains(':'));
+        if let (Some(name), Some(&num)) = (name, self.comp_num_stack.last()) {
+            self.graph.metadata.entry(Some(num)).or_default().name = Some(name.to_string());
+        }
         self.comp_id_to_num.pop();
+        self.comp_num_stack.pop();
+    }
+
+    fn visit_module(&mut self, cx: &VisitCtx, id: u32, module: &Module) {
+        // Language and compiler information usually lives on the core module
+        // rather than the component that wraps it.
+        for sect in module.custom_sections.iter() {
+            if sect.name == "producers" {
+                self.record_metadata(sect.name, &sect.data);
+            }
+        }
+
+        let name = cx
+            .lookup_module_name(id)
+            .map(str::to_string)
+            .or_else(|| module.module_name.clone());
+        if let Some(core) = self.curr_core() {
+            core.modules.push(CoreModule { index: id, name });
+        }
+    }
+
+    fn visit_core_instance(&mut self, cx: &VisitCtx, id: u32, instance: &Instance) {
+        let kind = match instance {
+            Instance::Instantiate { module_index, args } => CoreInstanceKind::Instantiate {
+                module_index: *module_index,
+                args: args.iter().map(|a| (a.name.to_string(), a.index)).collect(),
+            },
+            Instance::FromExports(exports) => {
+                CoreInstanceKind::FromExports(exports.iter().map(|e| e.name.to_string()).collect())
+            }
+        };
+        let name = cx.lookup_core_inst_name(id).map(str::to_string);
+        if let Some(core) = self.curr_core() {
+            core.instances.push(CoreInstance {
+                index: id,
+                name,
+                kind,
+            });
+        }
+    }
+
+    fn visit_custom_section(&mut self, _cx: &VisitCtx, sect: &CustomSection) {
+        self.record_metadata(sect.name, &sect.data);
     }
 
     // Process component instances - ** this is where the composition wiring lives **
@@ -151,22 +603,53 @@ impl ComponentVisitor<'_> for Visitor {
         let name = cx
             .lookup_comp_inst_name(id)
             .map(|n| n.to_string())
-            .unwrap_or_else(|| format!("instance_{}", id));
+            .unwrap_or_else(|| ComponentNode::fallback_name(id));
         match instance {
             ComponentInstance::Instantiate {
                 component_index,
                 args,
             } => {
-                let instantiated_comp = if let ResolvedItem::Component(_, comp) =
-                    cx.resolve(&instance.get_comp_refs().first().unwrap().ref_)
-                {
-                    Some(comp)
-                } else {
-                    None
+                let instantiated_comp =
+                    instance
+                        .get_comp_refs()
+                        .first()
+                        .and_then(|r| match cx.resolve(&r.ref_) {
+                            ResolvedItem::Component(_, comp) => Some(comp),
+                            _ => None,
+                        });
+
+                // Validation guarantees the component was defined, but the
+                // sections after a rejected instantiation are not validated
+                let Some(&comp_num) = self
+                    .comp_id_to_num
+                    .last()
+                    .and_then(|nums| nums.get(component_index))
+                else {
+                    debug!(
+                        component = component_index,
+                        "instantiated component not found"
+                    );
+                    return;
                 };
-
-                let comp_num = self.comp_id_to_num.last().unwrap()[component_index];
                 let mut node = ComponentNode::new(name, *component_index, comp_num);
+                if let Some(metadata) = self.graph.metadata.get(&Some(comp_num)) {
+                    node.package = metadata.name.clone();
+                    node.package_version = metadata.version.clone();
+                }
+                node.component_name = cx.lookup_comp_name(*component_index).map(str::to_string);
+                node.depth = self.comp_num_stack.len() as u32;
+                node.declared_imports = instantiated_comp.map(|comp| {
+                    comp.imports
+                        .iter()
+                        .filter(|imp| {
+                            !matches!(
+                                imp.ty,
+                                ComponentTypeRef::Module(_) | ComponentTypeRef::Component(_)
+                            )
+                        })
+                        .map(|imp| imp.name.0.to_string())
+                        .collect()
+                });
 
                 // Assign a sequential graph ID and register the ptr→id mapping so
                 // that later cx.resolve() calls returning this instance can find it.
@@ -177,6 +660,11 @@ impl ComponentVisitor<'_> for Visitor {
 
                 // Process the "with" arguments - these are the interface connections
                 for arg in args.iter() {
+                    // Nested components and core modules passed as arguments
+                    // are definitions rather than connections.
+                    let Some(kind) = connection_kind(arg.kind) else {
+                        continue;
+                    };
                     let interface_name = arg.name.to_string();
                     let interface_type =
                         pull_type_info(&interface_name, &instantiated_comp, &mut self.graph);
@@ -184,54 +672,63 @@ impl ComponentVisitor<'_> for Visitor {
                     // The arg.index is the instance providing this interface
                     // It might be an alias, so resolve it to the actual source instance
                     let item = cx.resolve(&arg.get_item_ref().ref_);
-                    match item {
-                        ResolvedItem::CompInst(_, inst) => {
-                            let source = self
-                                .inst_ptr_to_graph_id
-                                .get(&(inst as *const ComponentInstance as usize))
-                                .copied();
-                            let connection = InterfaceConnection::from_instance(
-                                interface_name,
-                                source,
-                                interface_type,
-                                &self.graph.arena,
-                            );
-                            node.add_import(connection);
+                    let added = node.imports.len();
+                    self.add_connection(
+                        cx,
+                        &mut node,
+                        interface_name.clone(),
+                        kind,
+                        interface_type,
+                        item,
+                    );
+                    // Plugged in with something the graph does not model,
+                    // such as a function the enclosing component defines
+                    if node.imports.len() == added {
+                        if let Some(declared) = &mut node.declared_imports {
+                            declared.remove(&interface_name);
                         }
-                        ResolvedItem::Import(_id, imp) => {
-                            // This arg is satisfied by the host (component import section),
-                            // not by another composed instance — always a host import.
-                            if let ComponentTypeRef::Instance(_) = imp.ty {
-                                let connection = InterfaceConnection::from_instance(
-                                    interface_name,
-                                    None,
-                                    interface_type,
-                                    &self.graph.arena,
-                                );
-                                node.add_import(connection);
-                            }
-                        }
-                        ResolvedItem::Alias(_, alias) => {
-                            resolve_inst_alias(
-                                cx,
-                                alias,
-                                &interface_name,
-                                interface_type,
-                                &mut node,
-                                &self.inst_ptr_to_graph_id,
-                                &self.graph.arena,
-                            );
-                        }
-                        _ => {}
                     }
                 }
 
+                debug!(
+                    id = graph_id,
+                    name = %node.name,
+                    component = comp_num,
+                    depth = node.depth,
+                    imports = node.imports.len(),
+                    "mapped instance"
+                );
                 self.graph.add_node(graph_id, node);
             }
-            ComponentInstance::FromExports(_) => {
-                // This is a synthetic instance created from exports
-                // These often wrap host imports - we don't track them as nodes
-                // since they're just interface bundles, not actual components
+            ComponentInstance::FromExports(exports) => {
+                // An interface bundle: an instance made up of items exported
+                // by other instances or the host, often wrapping host
+                // imports.  It instantiates no component, so it becomes a
+                // synthetic node whose imports are the bundled items.
+                let mut node = ComponentNode::new(name, SYNTHETIC_COMPONENT, SYNTHETIC_COMPONENT);
+                node.depth = self.comp_num_stack.len() as u32;
+                let graph_id = self.next_graph_id;
+                self.next_graph_id += 1;
+                self.inst_ptr_to_graph_id
+                    .insert(instance as *const ComponentInstance as usize, graph_id);
+
+                for export in exports.iter() {
+                    let Some(kind) = connection_kind(export.kind) else {
+                        continue;
+                    };
+                    let item = cx.resolve(&export.get_item_ref().ref_);
+                    let name = export.name.0.to_string();
+                    self.add_connection(cx, &mut node, name, kind, None, item);
+                }
+
+                debug!(
+                    id = graph_id,
+                    name = %node.name,
+                    depth = node.depth,
+                    items = node.imports.len(),
+                    "mapped interface bundle"
+                );
+                self.graph.add_node(graph_id, node);
             }
         }
     }
@@ -244,30 +741,142 @@ impl ComponentVisitor<'_> for Visitor {
             return;
         }
 
+        // Components and core modules exported whole are not tracked.
+        let Some(kind) = connection_kind(export.kind) else {
+            return;
+        };
         let export_name = export.name.0.to_string();
         let item = cx.resolve(&export.get_item_ref().ref_);
 
-        // Only track instance exports
         match item {
             ResolvedItem::CompInst(_, inst) => {
-                let ptr = inst as *const ComponentInstance as usize;
-                if let Some(&graph_id) = self.inst_ptr_to_graph_id.get(&ptr) {
-                    let iface_type =
-                        pull_export_type_from_instance(&export_name, inst, &mut self.graph, cx);
-                    self.graph.add_export(export_name, graph_id, iface_type);
-                }
+                let Some(graph_id) = self.export_source(inst, &export_name) else {
+                    return;
+                };
+                let iface_type =
+                    pull_export_type_from_instance(&export_name, inst, &mut self.graph, cx);
+                debug!(export = %export_name, instance = graph_id, "mapped export");
+                self.graph.add_export(export_name, graph_id, iface_type);
             }
             ResolvedItem::Alias(_, alias) => {
-                let graph = &mut self.graph;
-                let ptr_map = &self.inst_ptr_to_graph_id;
+                let Some((target, chain)) = follow_alias(cx, alias) else {
+                    self.anomaly(Anomaly::AliasLoop {
+                        context: format!("export {}", export_name),
+                    });
+                    return;
+                };
+                debug!(export = %export_name, through = ?chain, "resolved alias");
+                // Aliases of host-provided items are not tracked.
+                let ResolvedItem::CompInst(_, inst) = target else {
+                    return;
+                };
+                let Some(graph_id) = self.export_source(inst, &export_name) else {
+                    return;
+                };
+                debug!(export = %export_name, instance = graph_id, "mapped export");
                 let outer_comp = cx.curr_component();
-                resolve_imp_alias(cx, alias, &export_name, graph, ptr_map, outer_comp);
+                let info = add_aliased_export(
+                    cx,
+                    inst,
+                    graph_id,
+                    &export_name,
+                    kind,
+                    &mut self.graph,
+                    outer_comp,
+                );
+                info.alias_chain = chain;
             }
             _ => {}
         }
     }
 }
 
+/// Fill `metadata` from one custom section, ignoring sections that carry no
+/// metadata or fail to decode.
+///
+/// The `producers` section is merged into [`ComponentMetadata::producers`].
+/// The per-field sections (`authors`, `licenses`, ...) hold a plain UTF-8
+/// string.  The older `registry-metadata` section holds JSON and only fills
+/// fields that are still unset.
+fn read_metadata_section(metadata: &mut ComponentMetadata, name: &str, data: &[u8]) {
+    if name == "producers" {
+        let Ok(reader) = ProducersSectionReader::new(BinaryReader::new(data, 0)) else {
+            return;
+        };
+        for field in reader.into_iter().flatten() {
+            let values = metadata
+                .producers
+                .entry(field.name.to_string())
+                .or_default();
+            for value in field.values.into_iter().flatten() {
+                values.insert(value.name.to_string(), value.version.to_string());
+            }
+        }
+        return;
+    }
+    if let Some(field) = metadata.field_mut(name) {
+        if let Ok(value) = std::str::from_utf8(data) {
+            *field = Some(value.to_string());
+        }
+        return;
+    }
+    if name == "package-docs" {
+        if metadata.description.is_none() {
+            metadata.description = package_docs(data);
+        }
+        return;
+    }
+    if name != "registry-metadata" {
+        return;
+    }
+    let Ok(json) = serde_json::from_slice::<serde_json::Value>(data) else {
+        return;
+    };
+    let fill = |field: &mut Option<String>, value: Option<String>| {
+        if field.is_none() {
+            *field = value;
+        }
+    };
+    let authors = json["authors"].as_array().map(|a| {
+        a.iter()
+            .filter_map(|v| v.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    });
+    fill(&mut metadata.authors, authors);
+    let text = |key: &str| json[key].as_str().map(str::to_string);
+    fill(&mut metadata.description, text("description"));
+    fill(&mut metadata.licenses, text("license"));
+    let link = |ty: &str| {
+        json["links"]
+            .as_array()?
+            .iter()
+            .find(|l| l["ty"].as_str() == Some(ty))?["value"]
+            .as_str()
+            .map(str::to_string)
+    };
+    fill(&mut metadata.source, link("Repository"));
+    fill(&mut metadata.homepage, link("Homepage"));
+}
+
+/// The documentation `wit-component` keeps in a `package-docs` section: a
+/// version byte followed by JSON.  Prefers the package's own docs, falling
+/// back to those of its world when it has exactly one.
+fn package_docs(data: &[u8]) -> Option<String> {
+    let json = serde_json::from_slice::<serde_json::Value>(data.get(1..)?).ok()?;
+    let docs = |value: &serde_json::Value| {
+        value["docs"]
+            .as_str()
+            .map(str::trim)
+            .filter(|d| !d.is_empty())
+            .map(str::to_string)
+    };
+    docs(&json).or_else(|| match json["worlds"].as_object() {
+        Some(worlds) if worlds.len() == 1 => worlds.values().next().and_then(docs),
+        _ => None,
+    })
+}
+
 fn pull_export_type_from_instance(
     export_name: &str,
     inst: &ComponentInstance,
@@ -437,93 +1046,86 @@ fn prim_to_val_type(p: PrimitiveValType) -> ValueType {
     }
 }
 
-fn resolve_inst_alias(
-    cx: &VisitCtx,
+/// The [`ExternKind`] of an instantiation argument, or `None` for
+/// components and core modules, which are not tracked as connections.
+fn connection_kind(kind: ComponentExternalKind) -> Option<ExternKind> {
+    match kind {
+        ComponentExternalKind::Instance => Some(ExternKind::Instance),
+        ComponentExternalKind::Func => Some(ExternKind::Func),
+        ComponentExternalKind::Value => Some(ExternKind::Value),
+        ComponentExternalKind::Type => Some(ExternKind::Type),
+        ComponentExternalKind::Module | ComponentExternalKind::Component => None,
+    }
+}
+
+/// Follow `alias` through any aliases it refers to, returning the item it
+/// finally reaches along with the names of the nested instances passed
+/// through on the way, outermost instance first.  `None` if the aliases
+/// form a loop.
+fn follow_alias<'a>(
+    cx: &VisitCtx<'a>,
     alias: &ComponentAlias,
-    interface_name: &str,
-    interface_type: Option<InterfaceType>,
-    node: &mut ComponentNode,
-    inst_ptr_to_graph_id: &HashMap<usize, u32>,
-    arena: &TypeArena,
-) {
-    let inst_ref = alias.get_item_ref();
-
-    match cx.resolve(&inst_ref.ref_) {
-        ResolvedItem::CompInst(_, inst) => {
-            let source = inst_ptr_to_graph_id
-                .get(&(inst as *const ComponentInstance as usize))
-                .copied();
-            let connection = InterfaceConnection::from_instance(
-                interface_name.to_string(),
-                source,
-                interface_type,
-                arena,
-            );
-            node.add_import(connection);
+) -> Option<(ResolvedItem<'a, 'a>, Vec<String>)> {
+    let mut seen = HashSet::new();
+    let mut chain = Vec::new();
+    let mut item = cx.resolve(&alias.get_item_ref().ref_);
+    while let ResolvedItem::Alias(_, nested) = item {
+        if !seen.insert(nested as *const ComponentAlias as usize) {
+            return None;
         }
-        ResolvedItem::Alias(_, nested_alias) => resolve_inst_alias(
-            cx,
-            nested_alias,
-            interface_name,
-            interface_type,
-            node,
-            inst_ptr_to_graph_id,
-            arena,
-        ),
-        _ => {}
+        if let ComponentAlias::InstanceExport { name, .. } = nested {
+            chain.push(name.to_string());
+        }
+        item = cx.resolve(&nested.get_item_ref().ref_);
     }
+    chain.reverse();
+    Some((item, chain))
 }
-fn resolve_imp_alias(
+
+/// Record the root export `export_name` of an item aliased from `inst`,
+/// whose graph ID is `graph_id`.
+fn add_aliased_export<'g>(
     cx: &VisitCtx,
-    alias: &ComponentAlias,
+    inst: &ComponentInstance,
+    graph_id: u32,
     export_name: &str,
-    graph: &mut CompositionGraph,
-    inst_ptr_to_graph_id: &HashMap<usize, u32>,
+    kind: ExternKind,
+    graph: &'g mut CompositionGraph,
     outer_comp: &Component,
-) {
-    let inst_ref = alias.get_item_ref();
-    let resolved = cx.resolve(&inst_ref.ref_);
-
-    match resolved {
-        ResolvedItem::CompInst(_, inst) => {
-            let ptr = inst as *const ComponentInstance as usize;
-            if let Some(&graph_id) = inst_ptr_to_graph_id.get(&ptr) {
-                let mut iface_type = pull_export_type_from_instance(export_name, inst, graph, cx);
-
-                // If the nested component produced an interface with unnamed
-                // resources (no type_exports), try the outer component's own
-                // concretize_export which can resolve through alias outer.
-                let has_type_exports = iface_type.as_ref().is_some_and(|it| match it {
+) -> &'g mut ExportInfo {
+    // Interface types are only recovered for whole instances.
+    if kind != ExternKind::Instance {
+        return graph.add_export_of_kind(export_name.to_string(), kind, graph_id, None);
+    }
+    let mut iface_type = pull_export_type_from_instance(export_name, inst, graph, cx);
+
+    // If the nested component produced an interface with unnamed
+    // resources (no type_exports), try the outer component's own
+    // concretize_export which can resolve through alias outer.
+    let has_type_exports = iface_type.as_ref().is_some_and(|it| match it {
+        InterfaceType::Instance(inst) => !inst.type_exports.is_empty(),
+        _ => true,
+    });
+    if !has_type_exports {
+        if let Some(ct) = outer_comp.concretize_export(export_name) {
+            if let Some(better) = concrete_to_interface_type(ct, &mut graph.arena) {
+                let better_has_te = match &better {
                     InterfaceType::Instance(inst) => !inst.type_exports.is_empty(),
-                    _ => true,
-                });
-                if !has_type_exports {
-                    if let Some(ct) = outer_comp.concretize_export(export_name) {
-                        if let Some(better) = concrete_to_interface_type(ct, &mut graph.arena) {
-                            let better_has_te = match &better {
-                                InterfaceType::Instance(inst) => !inst.type_exports.is_empty(),
-                                _ => false,
-                            };
-                            if better_has_te {
-                                iface_type = Some(better);
-                            }
-                        }
-                    }
+                    _ => false,
+                };
+                if better_has_te {
+                    iface_type = Some(better);
                 }
-
-                graph.add_export(export_name.to_string(), graph_id, iface_type);
             }
         }
-        ResolvedItem::Alias(_, nested_alias) => resolve_imp_alias(
-            cx,
-            nested_alias,
-            export_name,
-            graph,
-            inst_ptr_to_graph_id,
-            outer_comp,
-        ),
-        _ => {}
     }
+
+    graph.add_export_of_kind(
+        export_name.to_string(),
+        ExternKind::Instance,
+        graph_id,
+        iface_type,
+    )
 }
 
 #[cfg(test)]
@@ -598,8 +1200,8 @@ mod tests {
         assert!(
             graph
                 .component_exports
-                .keys()
-                .any(|k| k.contains("wasi:http/handler")),
+                .iter()
+                .any(|e| e.name.contains("wasi:http/handler")),
             "expected handler export"
         );
     }
@@ -692,8 +1294,7 @@ mod tests {
         let graph = parse_component(&bytes).expect("failed to parse component");
 
         let export = graph
-            .component_exports
-            .get("wasi:http/handler@0.3.0")
+            .export("wasi:http/handler@0.3.0")
             .expect("expected export for wasi:http/handler@0.3.0");
         assert!(
             export.fingerprint.is_some(),
@@ -722,8 +1323,7 @@ mod tests {
         let graph = parse_component(&bytes).expect("failed to parse component");
 
         let export = graph
-            .component_exports
-            .get("wasi:http/handler@0.3.0")
+            .export("wasi:http/handler@0.3.0")
             .expect("expected export for wasi:http/handler@0.3.0");
         assert!(
             export.fingerprint.is_some(),
@@ -746,8 +1346,7 @@ mod tests {
         let graph = parse_component(&bytes).expect("failed to parse component");
 
         let export = graph
-            .component_exports
-            .get("wasi:http/handler@0.3.0")
+            .export("wasi:http/handler@0.3.0")
             .expect("expected export for wasi:http/handler@0.3.0");
         assert!(
             export.fingerprint.is_some(),
@@ -781,13 +1380,11 @@ mod tests {
         let mw_graph = parse_component(&mw_bytes).expect("failed to parse middleware");
 
         let chain_fp = chain_graph
-            .component_exports
-            .get("wasi:http/handler@0.3.0")
+            .export("wasi:http/handler@0.3.0")
             .and_then(|e| e.fingerprint.as_ref())
             .expect("chain should have fingerprint");
         let mw_fp = mw_graph
-            .component_exports
-            .get("wasi:http/handler@0.3.0")
+            .export("wasi:http/handler@0.3.0")
             .and_then(|e| e.fingerprint.as_ref())
             .expect("middleware should have fingerprint");
 
@@ -870,12 +1467,10 @@ mod tests {
         let graph = parse_component(&bytes).expect("failed to parse component");
 
         let export_a = graph
-            .component_exports
-            .get("test:iface/a@0.1.0")
+            .export("test:iface/a@0.1.0")
             .expect("export for test:iface/a@0.1.0 missing");
         let export_b = graph
-            .component_exports
-            .get("test:iface/b@0.1.0")
+            .export("test:iface/b@0.1.0")
             .expect("export for test:iface/b@0.1.0 missing");
 
         let src_a = export_a.source_instance;
@@ -924,13 +1519,11 @@ mod tests {
         let mw_graph = parse_component(&mw_bytes).expect("failed to parse middleware");
 
         let chain_fp = chain_graph
-            .component_exports
-            .get("wasi:http/handler@0.3.0")
+            .export("wasi:http/handler@0.3.0")
             .and_then(|e| e.fingerprint.as_ref())
             .expect("chain should have fingerprint");
         let mw_fp = mw_graph
-            .component_exports
-            .get("wasi:http/handler@0.3.0")
+            .export("wasi:http/handler@0.3.0")
             .and_then(|e| e.fingerprint.as_ref())
             .expect("middleware should have fingerprint");
 
@@ -1034,4 +1627,564 @@ mod tests {
             inst.type_exports.keys().collect::<Vec<_>>()
         );
     }
+
+    /// Core modules inside a nested component are recorded under that
+    /// component's `component_num`, along with how its core instances link them.
+    #[test]
+    fn core_modules_and_linking() {
+        let wat = r#"(component
+            (component $lib
+                (core module $impl (func (export "f")))
+                (core module $user (import "dep" "f" (func)))
+                (core instance $impl-inst (instantiate $impl))
+                (core instance $user-inst (instantiate $user (with "dep" (instance $impl-inst))))
+            )
+            (instance $lib-inst (instantiate $lib))
+        )"#;
+        let bytes = wat::parse_str(wat).expect("failed to parse WAT");
+        let graph = parse_component(&bytes).expect("failed to parse component");
+
+        let node = graph.nodes.values().next().expect("expected one instance");
+        let core = graph
+            .core
+            .get(&node.component_num)
+            .expect("expected core structure for the instantiated component");
+
+        let names: Vec<_> = core.modules.iter().map(|m| m.name.as_deref()).collect();
+        assert_eq!(names, vec![Some("impl"), Some("user")]);
+
+        assert_eq!(core.instances.len(), 2);
+        assert_eq!(
+            core.instances[1].kind,
+            CoreInstanceKind::Instantiate {
+                module_index: 1,
+                args: vec![("dep".to_string(), 0)],
+            }
+        );
+        assert_eq!(core.instance_label(0), "impl-inst");
+    }
+
+    #[test]
+    fn metadata_custom_sections() {
+        let wat = r#"(component
+            (@custom "licenses" "Apache-2.0")
+            (component $lib
+                (@custom "authors" "Jane Doe")
+                (@custom "registry-metadata" "{\"license\":\"MIT\",\"description\":\"a lib\",\"links\":[{\"ty\":\"Repository\",\"value\":\"https://example.com/lib\"}]}")
+            )
+            (component $bare)
+            (instance $lib-inst (instantiate $lib))
+            (instance $bare-inst (instantiate $bare))
+        )"#;
+        let bytes = wat::parse_str(wat).expect("failed to parse WAT");
+        let graph = parse_component(&bytes).expect("failed to parse component");
+
+        let root = &graph.metadata[&None];
+        assert_eq!(root.licenses.as_deref(), Some("Apache-2.0"));
+        assert_eq!(root.authors, None);
+
+        let lib = &graph.metadata[&Some(0)];
+        assert_eq!(lib.authors.as_deref(), Some("Jane Doe"));
+        assert_eq!(lib.licenses.as_deref(), Some("MIT"));
+        assert_eq!(lib.description.as_deref(), Some("a lib"));
+        assert_eq!(lib.source.as_deref(), Some("https://example.com/lib"));
+
+        // Components without metadata get no entry.
+        assert_eq!(graph.metadata.len(), 2);
+    }
+
+    #[test]
+    fn package_name_and_version_on_nodes() {
+        let wat = r#"(component (@name "my:app")
+            (component $lib (@name "my:lib")
+                (@custom "version" "1.2.0")
+            )
+            (component $bare)
+            (instance $lib-inst (instantiate $lib))
+            (instance $bare-inst (instantiate $bare))
+        )"#;
+        let bytes = wat::parse_str(wat).expect("failed to parse WAT");
+        let graph = parse_component(&bytes).expect("failed to parse component");
+
+        let lib = &graph.nodes[&graph.find_node("lib-inst").unwrap()];
+        assert_eq!(lib.package.as_deref(), Some("my:lib"));
+        assert_eq!(lib.package_id().as_deref(), Some("my:lib@1.2.0"));
+
+        let bare = &graph.nodes[&graph.find_node("bare-inst").unwrap()];
+        assert_eq!(bare.package_id(), None);
+
+        assert_eq!(graph.metadata[&None].name.as_deref(), Some("my:app"));
+    }
+
+    #[test]
+    fn func_arguments_are_connections() {
+        let wat = r#"(component
+            (import "log" (func $log))
+            (component $lib
+                (import "f" (func $f))
+                (export "get" (func $f))
+            )
+            (component $app
+                (import "get" (func))
+                (import "log" (func))
+            )
+            (instance $lib-inst (instantiate $lib (with "f" (func $log))))
+            (instance $app-inst (instantiate $app
+                (with "get" (func $lib-inst "get"))
+                (with "log" (func $log))
+            ))
+        )"#;
+        let bytes = wat::parse_str(wat).expect("failed to parse WAT");
+        let graph = parse_component(&bytes).expect("failed to parse component");
+
+        let lib = graph.find_node("lib-inst").unwrap();
+        let app = &graph.nodes[&graph.find_node("app-inst").unwrap()];
+        let imports: Vec<(&str, ExternKind, Option<u32>, bool)> = app
+            .imports
+            .iter()
+            .map(|c| {
+                (
+                    c.interface_name.as_str(),
+                    c.kind,
+                    c.source_instance,
+                    c.is_host_import,
+                )
+            })
+            .collect();
+        assert_eq!(
+            imports,
+            vec![
+                ("get", ExternKind::Func, Some(lib), false),
+                ("log", ExternKind::Func, None, true),
+            ]
+        );
+        let lib = &graph.nodes[&lib];
+        assert_eq!(lib.imports[0].kind, ExternKind::Func);
+        assert_eq!(lib.imports[0].source_export.as_deref(), Some("log"));
+    }
+
+    #[test]
+    fn nested_instance_aliases_are_kept() {
+        let wat = r#"(component
+            (import "host" (instance $host (export "log" (func))))
+            (component $lib
+                (import "host" (instance $h (export "log" (func))))
+                (instance $handler (export "log" (func $h "log")))
+                (instance $nested (export "handler" (instance $handler)))
+                (export "nested" (instance $nested))
+            )
+            (component $app
+                (import "handler" (instance (export "log" (func))))
+            )
+            (instance $lib-inst (instantiate $lib (with "host" (instance $host))))
+            (instance $app-inst (instantiate $app
+                (with "handler" (instance $lib-inst "nested" "handler"))
+            ))
+            (export "handler" (instance $lib-inst "nested" "handler"))
+        )"#;
+        let bytes = wat::parse_str(wat).expect("failed to parse WAT");
+        let graph = parse_component(&bytes).expect("failed to parse component");
+
+        let lib = graph.find_node("lib-inst");
+        let app = &graph.nodes[&graph.find_node("app-inst").unwrap()];
+        let handler = &app.imports[0];
+        assert_eq!(handler.source_instance, lib);
+        assert_eq!(handler.source_export.as_deref(), Some("handler"));
+        assert_eq!(handler.alias_chain, vec!["nested"]);
+
+        let export = graph
+            .export("handler")
+            .expect("expected export for handler");
+        assert_eq!(Some(export.source_instance), lib);
+        assert_eq!(export.alias_chain, vec!["nested"]);
+    }
+
+    #[test]
+    fn from_exports_instances_are_bundle_nodes() {
+        let wat = r#"(component
+            (import "wasi:logging/log" (instance $log (export "log" (func))))
+            (component $lib
+                (import "f" (func $f))
+                (export "get" (func $f))
+            )
+            (instance $lib-inst (instantiate $lib (with "f" (func $log "log"))))
+            (instance $bundle
+                (export "log" (func $log "log"))
+                (export "get" (func $lib-inst "get"))
+            )
+            (component $app
+                (import "deps" (instance (export "log" (func)) (export "get" (func))))
+            )
+            (instance $app-inst (instantiate $app (with "deps" (instance $bundle))))
+        )"#;
+        let bytes = wat::parse_str(wat).expect("failed to parse WAT");
+        let graph = parse_component(&bytes).expect("failed to parse component");
+
+        let lib = graph.find_node("lib-inst");
+        let bundle_id = graph.find_node("bundle").expect("bundle should be a node");
+        let bundle = &graph.nodes[&bundle_id];
+        assert_eq!(bundle.component_index, SYNTHETIC_COMPONENT);
+        let items: Vec<(&str, ExternKind, Option<u32>, Option<&str>)> = bundle
+            .imports
+            .iter()
+            .map(|c| {
+                (
+                    c.interface_name.as_str(),
+                    c.kind,
+                    c.source_instance,
+                    c.source_export.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            items,
+            vec![
+                ("log", ExternKind::Func, None, Some("wasi:logging/log")),
+                ("get", ExternKind::Func, lib, Some("get")),
+            ]
+        );
+        assert!(bundle.imports[0].is_host_import);
+
+        let app = &graph.nodes[&graph.find_node("app-inst").unwrap()];
+        assert_eq!(app.imports[0].source_instance, Some(bundle_id));
+        assert!(!graph.real_nodes().iter().any(|n| n.name == "$bundle"));
+    }
+
+    #[test]
+    fn nested_instances_record_their_depth() {
+        let wat = r#"(component
+            (component $outer
+                (component $inner)
+                (instance $inner-inst (instantiate $inner))
+            )
+            (instance $outer-inst (instantiate $outer))
+        )"#;
+        let bytes = wat::parse_str(wat).expect("failed to parse WAT");
+        let mut graph = parse_component(&bytes).expect("failed to parse component");
+
+        let depth = |graph: &CompositionGraph, name: &str| {
+            graph.find_node(name).map(|id| graph.nodes[&id].depth)
+        };
+        assert_eq!(depth(&graph, "outer-inst"), Some(0));
+        assert_eq!(depth(&graph, "inner-inst"), Some(1));
+
+        graph.collapse_nested(0);
+        assert_eq!(depth(&graph, "outer-inst"), Some(0));
+        assert_eq!(depth(&graph, "inner-inst"), None);
+    }
+
+    #[test]
+    fn func_exports_are_recorded() {
+        let wat = r#"(component
+            (component $lib
+                (import "f" (func $f))
+                (export "get" (func $f))
+            )
+            (import "f" (func $f))
+            (instance $lib-inst (instantiate $lib (with "f" (func $f))))
+            (export "get" (func $lib-inst "get"))
+        )"#;
+        let bytes = wat::parse_str(wat).expect("failed to parse WAT");
+        let graph = parse_component(&bytes).expect("failed to parse component");
+
+        let export = graph.export("get").expect("expected export for get");
+        assert_eq!(export.kind, ExternKind::Func);
+        assert_eq!(Some(export.source_instance), graph.find_node("lib-inst"));
+    }
+
+    #[test]
+    fn component_names_label_unnamed_instances() {
+        let wat = r#"(component
+            (component $auth)
+            (component $cache (@name "my:cache"))
+            (instance (instantiate $auth))
+            (instance (instantiate $cache))
+        )"#;
+        let bytes = wat::parse_str(wat).expect("failed to parse WAT");
+        let graph = parse_component(&bytes).expect("failed to parse component");
+
+        let auth = &graph.nodes[&0];
+        assert!(auth.is_unnamed());
+        assert_eq!(auth.component_name.as_deref(), Some("auth"));
+        assert_eq!(auth.display_label(), "auth");
+        // The enclosing component's name for the definition wins over the
+        // package name.
+        assert_eq!(graph.nodes[&1].display_label(), "cache");
+    }
+
+    #[test]
+    fn producers_of_component_and_core_module() {
+        let wat = r#"(component
+            (@producers (processed-by "wac" "0.9.0"))
+            (component $lib
+                (@producers (processed-by "wit-component" "0.244.0"))
+                (core module $m
+                    (@producers (language "Rust" "") (processed-by "rustc" "1.93.0 (abc 2026-01-19)"))
+                )
+            )
+            (instance $lib-inst (instantiate $lib))
+        )"#;
+        let bytes = wat::parse_str(wat).expect("failed to parse WAT");
+        let graph = parse_component(&bytes).expect("failed to parse component");
+
+        let root = graph.metadata[&None].producer_summary();
+        assert_eq!(root.as_deref(), Some("wac 0.9.0"));
+
+        let node = graph.nodes.values().next().expect("expected one instance");
+        assert_eq!(
+            graph.producers_of(node).as_deref(),
+            Some("Rust / rustc 1.93.0, wit-component 0.244.0")
+        );
+    }
+
+    #[test]
+    fn strict_parse_accepts_well_formed_compositions() {
+        let bytes = wat::parse_str(two_middleware_chain_wat()).expect("failed to parse WAT");
+        let strict = ParseOptions {
+            strict: true,
+            ..ParseOptions::default()
+        };
+        let graph = parse_component_with_options(&bytes, &strict).expect("no anomalies");
+        assert_eq!(graph.real_nodes().len(), 2);
+    }
+
+    #[test]
+    fn malformed_input_is_an_error() {
+        assert!(parse_component(br#"{"nodes": []}"#).is_err());
+        assert!(parse_component_imports(b"\0asm\x0d\0\x01").is_err());
+    }
+
+    #[test]
+    fn invalid_components_are_an_error() {
+        // A component importing an instance of a type that does not exist
+        let bytes = wat::parse_str(r#"(component (import "a" (instance (type 5))))"#).unwrap();
+        let err = parse_component(&bytes).err().expect("an invalid component");
+        let err = err.to_string();
+        assert!(err.contains("type index out of bounds"), "{}", err);
+        assert!(parse_component_imports(&bytes).is_err());
+        assert!(parse_component_exports(&bytes).is_err());
+
+        let err = contain_panics::<()>(|| panic!("no index")).unwrap_err();
+        assert_eq!(err.to_string(), "could not read the component: no index");
+    }
+
+    #[test]
+    fn instances_record_their_component_size() {
+        let bytes = wat::parse_str(two_middleware_chain_wat()).expect("failed to parse WAT");
+        let graph = parse_component(&bytes).expect("failed to parse component");
+
+        let sizes: Vec<u64> = graph.real_nodes().iter().filter_map(|n| n.size).collect();
+        assert_eq!(sizes.len(), 2, "every instance should have a size");
+        assert!(sizes.iter().all(|&s| s > 0 && s < bytes.len() as u64));
+    }
+
+    #[test]
+    fn instances_record_their_component_digest() {
+        let bytes = wat::parse_str(two_middleware_chain_wat()).expect("failed to parse WAT");
+        let graph = parse_component(&bytes).expect("failed to parse component");
+
+        // the digest covers the embedded component as a standalone binary
+        let components = embedded_components(&bytes).unwrap();
+        let first = &bytes[components[0].range.clone()];
+        assert!(first.starts_with(b"\0asm"));
+        let node = graph
+            .real_nodes()
+            .into_iter()
+            .find(|n| n.component_num == 0)
+            .expect("an instance of the first component");
+        assert_eq!(
+            node.digest,
+            Some(format!("sha256:{}", hex::encode(Sha256::digest(first))))
+        );
+    }
+
+    #[test]
+    fn instances_record_the_features_their_modules_use() {
+        let wat = r#"(component
+            (component $threaded
+                (component $inner
+                    (core module $m (memory 1 1 shared))
+                )
+                (core module $plain (memory 1))
+                (instance $i (instantiate $inner))
+            )
+            (component $plain
+                (core module $m (memory 1))
+            )
+            (instance $t (instantiate $threaded))
+            (instance $p (instantiate $plain))
+        )"#;
+        let bytes = wat::parse_str(wat).expect("failed to parse WAT");
+        let graph = parse_component(&bytes).expect("failed to parse component");
+
+        let features = |name: &str| {
+            let node = graph.nodes.values().find(|n| n.name == name).unwrap();
+            node.features.iter().copied().collect::<Vec<_>>()
+        };
+        // a component needs what the components nested in it need
+        assert_eq!(features("t"), vec![WasmFeature::Threads]);
+        assert_eq!(features("i"), vec![WasmFeature::Threads]);
+        assert_eq!(features("p"), vec![]);
+    }
+
+    #[test]
+    fn instances_record_whether_they_run_start_logic() {
+        let wat = r#"(component
+            (component $eager
+                (core module $m (func $init) (start $init))
+            )
+            (component $lazy
+                (core module $m (func (export "init")))
+            )
+            (instance $e (instantiate $eager))
+            (instance $l (instantiate $lazy))
+        )"#;
+        let bytes = wat::parse_str(wat).expect("failed to parse WAT");
+        let graph = parse_component(&bytes).expect("failed to parse component");
+
+        let runs_start = |name: &str| {
+            graph
+                .nodes
+                .values()
+                .find(|n| n.name == name)
+                .unwrap()
+                .runs_start
+        };
+        assert!(runs_start("e"));
+        assert!(!runs_start("l"));
+    }
+
+    #[test]
+    fn instances_record_their_declared_imports() {
+        let wat = r#"(component
+            (import "wasi:logging/log@0.1.0" (instance $log))
+            (component $app
+                (import "wasi:logging/log@0.1.0" (instance))
+                (import "wasi:keyvalue/store@0.1.0" (instance))
+            )
+            (instance $a (instantiate $app
+                (with "wasi:logging/log@0.1.0" (instance $log))
+            ))
+        )"#;
+        let bytes = wat::parse_str(wat).expect("failed to parse WAT");
+        let graph = parse_component(&bytes).expect("failed to parse component");
+
+        let node = graph.nodes.values().find(|n| n.name == "a").unwrap();
+        assert_eq!(
+            node.declared_imports,
+            Some(BTreeSet::from([
+                "wasi:keyvalue/store@0.1.0".to_string(),
+                "wasi:logging/log@0.1.0".to_string(),
+            ]))
+        );
+        let unsatisfied = crate::analysis::unsatisfied_imports(&graph);
+        assert_eq!(unsatisfied.len(), 1, "{:?}", unsatisfied);
+        assert_eq!(unsatisfied[0].interface, "wasi:keyvalue/store@0.1.0");
+    }
+
+    #[test]
+    fn anomalies_name_what_failed() {
+        let err = Anomalies(vec![
+            Anomaly::UnknownInstance {
+                instance: "auth".to_string(),
+                interface: "wasi:http/handler@0.3.0".to_string(),
+                scope: Some(2),
+            },
+            Anomaly::MissingExportSource {
+                export: "wasi:http/handler@0.3.0".to_string(),
+            },
+            Anomaly::AliasLoop {
+                context: "export wasi:cli/run@0.2.0".to_string(),
+            },
+        ]);
+        assert_eq!(
+            err.to_string(),
+            "3 parse anomaly(ies):\n  \
+             - instance auth imports wasi:http/handler@0.3.0 from an unknown instance (in component 2)\n  \
+             - export wasi:http/handler@0.3.0 refers to an instance missing from the composition\n  \
+             - alias loop while resolving export wasi:cli/run@0.2.0"
+        );
+    }
+
+    #[test]
+    fn package_docs_become_the_description() {
+        let mut metadata = ComponentMetadata::default();
+        read_metadata_section(
+            &mut metadata,
+            "package-docs",
+            b"\x01{\"worlds\":{\"service\":{\"docs\":\"Auth service.\\nChecks tokens.\"}}}",
+        );
+        assert_eq!(
+            metadata.description.as_deref(),
+            Some("Auth service.\nChecks tokens.")
+        );
+
+        // an empty package doc falls through, and never replaces a
+        // description from `registry-metadata`
+        let mut metadata = ComponentMetadata::default();
+        read_metadata_section(&mut metadata, "package-docs", b"\x01{\"docs\":\"\"}");
+        assert_eq!(metadata.description, None);
+        metadata.description = Some("From the registry".to_string());
+        read_metadata_section(&mut metadata, "package-docs", b"\x01{\"docs\":\"Other\"}");
+        assert_eq!(metadata.description.as_deref(), Some("From the registry"));
+    }
+
+    #[test]
+    fn renamed_plug_records_source_export() {
+        let wat = r#"(component
+            (import "wasi:http/handler@0.3.0" (instance $host
+                (export "handle" (func))
+            ))
+
+            (component $inner
+                (import "wasi:http/handler@0.3.0" (instance $imp
+                    (export "handle" (func))
+                ))
+                (alias export $imp "handle" (func $f))
+                (instance $out (export "handle" (func $f)))
+                (export "wasi:http/handler@0.3.0" (instance $out))
+            )
+            (component $outer
+                (import "my:app/next" (instance $imp
+                    (export "handle" (func))
+                ))
+            )
+
+            (instance $a (instantiate $inner
+                (with "wasi:http/handler@0.3.0" (instance $host))
+            ))
+            (alias export $a "wasi:http/handler@0.3.0" (instance $a-out))
+            (instance $b (instantiate $outer
+                (with "my:app/next" (instance $a-out))
+            ))
+        )"#;
+        let bytes = wat::parse_str(wat).expect("failed to parse WAT");
+        let graph = parse_component(&bytes).expect("failed to parse component");
+
+        let conns: Vec<&InterfaceConnection> = graph
+            .nodes
+            .values()
+            .flat_map(|n| n.imports.iter())
+            .collect();
+        let host = conns
+            .iter()
+            .find(|c| c.interface_name == "wasi:http/handler@0.3.0")
+            .expect("host connection");
+        assert_eq!(
+            host.source_export.as_deref(),
+            Some("wasi:http/handler@0.3.0")
+        );
+        assert!(!host.is_renamed());
+
+        let plug = conns
+            .iter()
+            .find(|c| c.interface_name == "my:app/next")
+            .expect("renamed connection");
+        assert_eq!(
+            plug.source_export.as_deref(),
+            Some("wasi:http/handler@0.3.0")
+        );
+        assert!(plug.is_renamed());
+        assert!(!plug.is_host_import);
+    }
 }
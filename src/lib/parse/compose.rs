@@ -0,0 +1,256 @@
+//! Build a [`CompositionGraph`] from a `wasm-compose` configuration file.
+//!
+//! The graph shows the composition the configuration *asks for*, to be
+//! compared with what `wasm-tools compose` produced.  Every entry under
+//! `instantiations` becomes an instance, and `$input` is the root component
+//! being composed.  Explicit `arguments` become connections.  When a
+//! dependency's component file can be found (via `dependencies`, or as
+//! `<name>.wasm` in the config directory or its `search-paths`), its
+//! remaining imports are added too: wired to the instantiation of the same
+//! name if there is one, from the host otherwise.
+
+use crate::error::{ParseError, Result};
+use crate::model::{naming, ComponentNode, CompositionGraph, ExternKind, InterfaceConnection};
+use crate::parse::component::{parse_component_exports, parse_component_imports};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Name of the root instantiation in a `wasm-compose` configuration.
+pub const INPUT_INSTANCE: &str = "$input";
+
+/// The parts of a `wasm-compose` configuration that shape the graph.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ComposeConfig {
+    #[serde(default)]
+    search_paths: Vec<PathBuf>,
+    #[serde(default)]
+    dependencies: BTreeMap<String, Dependency>,
+    #[serde(default)]
+    instantiations: BTreeMap<String, Instantiation>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Dependency {
+    Path(PathBuf),
+    Detailed { path: PathBuf },
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Instantiation {
+    /// Dependency to instantiate; defaults to the instantiation's own name.
+    dependency: Option<String>,
+    #[serde(default)]
+    arguments: BTreeMap<String, Argument>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Argument {
+    /// Pass the whole instance.
+    Instance(String),
+    /// Pass one export of an instance.
+    Export {
+        instance: String,
+        export: Option<String>,
+    },
+}
+
+impl ComposeConfig {
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        serde_yaml::from_str(yaml)
+            .map_err(|e| ParseError::caused_by("Invalid wasm-compose configuration", e).into())
+    }
+
+    /// Build the intended composition graph.
+    ///
+    /// Relative paths resolve against `base_dir`, the directory holding the
+    /// configuration.  `input` is the root component, if available; its
+    /// exports become the composition's exports.
+    pub fn to_graph(&self, base_dir: &Path, input: Option<&[u8]>) -> Result<CompositionGraph> {
+        let mut names: Vec<&str> = vec![INPUT_INSTANCE];
+        names.extend(
+            self.instantiations
+                .keys()
+                .map(String::as_str)
+                .filter(|&n| n != INPUT_INSTANCE),
+        );
+        let ids: BTreeMap<&str, u32> = names.iter().zip(0..).map(|(&n, i)| (n, i)).collect();
+
+        let mut graph = CompositionGraph::new();
+        for (&name, &id) in &ids {
+            let inst = self.instantiations.get(name);
+            let mut node = ComponentNode::new(instance_name(name), id, id);
+
+            for (arg, value) in inst.iter().flat_map(|i| &i.arguments) {
+                let (instance, export) = match value {
+                    Argument::Instance(instance) => (instance, None),
+                    Argument::Export { instance, export } => (instance, export.clone()),
+                };
+                let source = *ids.get(instance.as_str()).ok_or_else(|| {
+                    ParseError::new(format!(
+                        "argument `{}` of `{}` refers to unknown instantiation `{}`",
+                        arg, name, instance
+                    ))
+                })?;
+                node.add_import(connection(arg.clone(), Some(source), export, None));
+            }
+
+            let bytes = if name == INPUT_INSTANCE {
+                input.map(<[u8]>::to_vec)
+            } else {
+                let dependency = inst.and_then(|i| i.dependency.as_deref()).unwrap_or(name);
+                self.read_dependency(base_dir, dependency)?
+            };
+            if let Some(bytes) = bytes {
+                let imports = parse_component_imports(&bytes)?;
+                node.declared_imports = Some(imports.iter().map(|(i, _)| i.clone()).collect());
+                for (import, fingerprint) in imports {
+                    if node.imports.iter().any(|c| c.interface_name == import) {
+                        continue;
+                    }
+                    let source = ids.get(import.as_str()).copied().filter(|&s| s != id);
+                    node.add_import(connection(import, source, None, fingerprint));
+                }
+                if name == INPUT_INSTANCE {
+                    for (export, fingerprint) in parse_component_exports(&bytes)? {
+                        graph.add_export(export.clone(), id, None);
+                        if let Some(info) = graph.export_mut(&export) {
+                            info.fingerprint = fingerprint;
+                        }
+                    }
+                }
+            }
+            graph.add_node(id, node);
+        }
+
+        graph.validate()?;
+        naming::resolve_labels(&mut graph, &naming::default_resolvers());
+        Ok(graph)
+    }
+
+    /// Read the component file of `dependency`, if it can be found.
+    fn read_dependency(&self, base_dir: &Path, dependency: &str) -> Result<Option<Vec<u8>>> {
+        let path = match self.dependencies.get(dependency) {
+            Some(Dependency::Path(path) | Dependency::Detailed { path }) => {
+                let path = base_dir.join(path);
+                if !path.exists() {
+                    return Err(ParseError::new(format!(
+                        "dependency `{}` not found at {}",
+                        dependency,
+                        path.display()
+                    ))
+                    .into());
+                }
+                path
+            }
+            None => {
+                let file = format!("{}.wasm", dependency);
+                let found = std::iter::once(base_dir.to_path_buf())
+                    .chain(self.search_paths.iter().map(|p| base_dir.join(p)))
+                    .map(|dir| dir.join(&file))
+                    .find(|p| p.exists());
+                match found {
+                    Some(path) => path,
+                    None => return Ok(None),
+                }
+            }
+        };
+        std::fs::read(&path).map(Some).map_err(|e| {
+            ParseError::caused_by(format!("Failed to read dependency: {}", path.display()), e)
+                .into()
+        })
+    }
+}
+
+/// Graph name for an instantiation: `$input` stays as is, others gain the
+/// `$` prefix the component parser uses.
+fn instance_name(name: &str) -> String {
+    if name.starts_with('$') {
+        name.to_string()
+    } else {
+        format!("${}", name)
+    }
+}
+
+fn connection(
+    interface_name: String,
+    source_instance: Option<u32>,
+    source_export: Option<String>,
+    fingerprint: Option<String>,
+) -> InterfaceConnection {
+    InterfaceConnection {
+        interface_name,
+        source_instance,
+        is_host_import: source_instance.is_none(),
+        interface_type: None,
+        fingerprint,
+        source_export,
+        kind: ExternKind::Instance,
+        alias_chain: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+search-paths:
+  - deps
+dependencies:
+  auth: components/auth.wasm
+instantiations:
+  $input:
+    arguments:
+      wasi:http/handler@0.3.0: router
+  router:
+    arguments:
+      auth:
+        instance: auth
+        export: wasi:http/handler@0.3.0
+  auth: {}
+"#;
+
+    #[test]
+    fn test_instances_and_arguments() {
+        let config = ComposeConfig::from_yaml(CONFIG).unwrap();
+        // No component files exist, but `auth` is listed explicitly.
+        let err = config
+            .to_graph(Path::new("/nonexistent"), None)
+            .err()
+            .unwrap();
+        assert!(
+            err.to_string().contains("dependency `auth` not found"),
+            "{}",
+            err
+        );
+
+        let mut config = config;
+        config.dependencies.clear();
+        let graph = config.to_graph(Path::new("/nonexistent"), None).unwrap();
+        let labels: Vec<&str> = graph.nodes.values().map(|n| n.display_label()).collect();
+        assert_eq!(labels, vec!["input", "auth", "router"]);
+
+        let input = &graph.nodes[&0];
+        assert_eq!(input.imports[0].source_instance, Some(2));
+        let router = &graph.nodes[&2];
+        assert_eq!(router.imports[0].interface_name, "auth");
+        assert_eq!(router.imports[0].source_instance, Some(1));
+        assert!(router.imports[0].is_renamed());
+    }
+
+    #[test]
+    fn test_unknown_instance() {
+        let config =
+            ComposeConfig::from_yaml("instantiations:\n  a:\n    arguments:\n      x: missing\n")
+                .unwrap();
+        let err = config.to_graph(Path::new("."), None).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "argument `x` of `a` refers to unknown instantiation `missing`"
+        );
+    }
+}
@@ -0,0 +1,184 @@
+//! Detect the non-default wasm features a core module relies on.
+//!
+//! Memory-related features show up in a module's memory and table types;
+//! instruction-level ones (atomics, relaxed SIMD) only in its code, so every
+//! function body is scanned.  Which proposal an instruction belongs to comes
+//! from wasmparser's operator tables.
+
+use crate::model::WasmFeature;
+use std::collections::BTreeSet;
+use wirm::wasmparser::{
+    for_each_visit_operator, for_each_visit_simd_operator, MemoryType, Payload, Result, TableType,
+    TypeRef, VisitOperator, VisitSimdOperator,
+};
+
+/// Features found so far in the core module being read.
+#[derive(Debug, Default)]
+pub(crate) struct ModuleFeatures {
+    features: BTreeSet<WasmFeature>,
+    memories: usize,
+}
+
+impl ModuleFeatures {
+    /// Take note of the features used in one payload of the module.
+    pub(crate) fn record(&mut self, payload: &Payload) -> Result<()> {
+        match payload {
+            Payload::ImportSection(reader) => {
+                for import in reader.clone().into_imports() {
+                    match import?.ty {
+                        TypeRef::Memory(ty) => self.memory(&ty),
+                        TypeRef::Table(ty) => self.table(&ty),
+                        _ => {}
+                    }
+                }
+            }
+            Payload::MemorySection(reader) => {
+                for ty in reader.clone() {
+                    self.memory(&ty?);
+                }
+            }
+            Payload::TableSection(reader) => {
+                for table in reader.clone() {
+                    self.table(&table?.ty);
+                }
+            }
+            Payload::CodeSectionEntry(body) => {
+                let mut operators = body.get_operators_reader()?;
+                let mut scan = OperatorScan::default();
+                while !operators.eof() {
+                    operators.visit_operator(&mut scan)?;
+                }
+                self.features.extend(scan.features);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// The features the module uses, once all its payloads are recorded.
+    pub(crate) fn finish(mut self) -> BTreeSet<WasmFeature> {
+        if self.memories > 1 {
+            self.features.insert(WasmFeature::MultiMemory);
+        }
+        self.features
+    }
+
+    fn memory(&mut self, ty: &MemoryType) {
+        self.memories += 1;
+        if ty.shared {
+            self.features.insert(WasmFeature::Threads);
+        }
+        if ty.memory64 {
+            self.features.insert(WasmFeature::Memory64);
+        }
+    }
+
+    fn table(&mut self, ty: &TableType) {
+        if ty.table64 {
+            self.features.insert(WasmFeature::Memory64);
+        }
+    }
+}
+
+/// Collects the features of the instructions it visits.
+#[derive(Default)]
+struct OperatorScan {
+    features: BTreeSet<WasmFeature>,
+}
+
+impl OperatorScan {
+    fn visit(&mut self, proposal: &str) {
+        let feature = match proposal {
+            "threads" => WasmFeature::Threads,
+            "relaxed_simd" => WasmFeature::RelaxedSimd,
+            _ => return,
+        };
+        self.features.insert(feature);
+    }
+}
+
+macro_rules! visit_proposal {
+    ($( @$proposal:ident $op:ident $({ $($arg:ident: $argty:ty),* })? => $visit:ident ($($ann:tt)*))*) => {
+        $(
+            fn $visit(&mut self $($(, $arg: $argty)*)?) {
+                $($(let _ = $arg;)*)?
+                self.visit(stringify!($proposal));
+            }
+        )*
+    };
+}
+
+impl<'a> VisitOperator<'a> for OperatorScan {
+    type Output = ();
+
+    fn simd_visitor(&mut self) -> Option<&mut dyn VisitSimdOperator<'a, Output = ()>> {
+        Some(self)
+    }
+
+    for_each_visit_operator!(visit_proposal);
+}
+
+impl VisitSimdOperator<'_> for OperatorScan {
+    for_each_visit_simd_operator!(visit_proposal);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wirm::wasmparser::Parser;
+
+    fn features_of(wat: &str) -> BTreeSet<WasmFeature> {
+        let bytes = wat::parse_str(wat).expect("failed to parse WAT");
+        let mut module = ModuleFeatures::default();
+        for payload in Parser::new(0).parse_all(&bytes) {
+            module.record(&payload.unwrap()).unwrap();
+        }
+        module.finish()
+    }
+
+    #[test]
+    fn test_default_features() {
+        let features = features_of(
+            r#"(module
+                (memory 1)
+                (func (param i32) (result i32) (i32.add (local.get 0) (i32.const 1)))
+            )"#,
+        );
+        assert!(features.is_empty(), "{:?}", features);
+    }
+
+    #[test]
+    fn test_memory_features() {
+        let features = features_of(
+            r#"(module
+                (import "env" "memory" (memory 1 1 shared))
+                (memory i64 1)
+            )"#,
+        );
+        assert_eq!(
+            features,
+            BTreeSet::from([
+                WasmFeature::Threads,
+                WasmFeature::Memory64,
+                WasmFeature::MultiMemory
+            ])
+        );
+    }
+
+    #[test]
+    fn test_instruction_features() {
+        let features = features_of(
+            r#"(module
+                (memory 1)
+                (func (param i32) (result i32)
+                    (i32.atomic.load (local.get 0)))
+                (func (param v128 v128) (result v128)
+                    (i8x16.relaxed_swizzle (local.get 0) (local.get 1)))
+            )"#,
+        );
+        assert_eq!(
+            features,
+            BTreeSet::from([WasmFeature::Threads, WasmFeature::RelaxedSimd])
+        );
+    }
+}
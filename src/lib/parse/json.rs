@@ -1,41 +1,104 @@
+use crate::error::{ParseError, Result};
 use crate::model::{
-    ComponentNode, CompositionGraph, ExportInfo, FuncSignature, InstanceInterface,
-    InterfaceConnection, InterfaceType, InternedId, TypeArena, ValueType, ValueTypeId,
+    ComponentMetadata, ComponentNode, CompositionGraph, CoreInstance, CoreInstanceKind, CoreModule,
+    CoreStructure, ExportInfo, ExternKind, FuncSignature, InstanceInterface, InterfaceConnection,
+    InterfaceType, InternedId, TypeArena, ValueType, ValueTypeId, WasmFeature,
 };
 use crate::output::json::{
-    FuncSignatureJson, InterfaceTypeJson, JsonCompositionGraph, JsonExport, ValueTypeJson,
+    FuncSignatureJson, InterfaceTypeJson, JsonCompositionGraph, JsonCoreInstanceKind,
+    JsonCoreStructure, JsonExport, ValueTypeJson, JSON_VERSION,
 };
 use serde::de::Error as _;
+use serde_json::Value;
 use std::collections::BTreeMap;
 use std::fs::File;
 
-pub fn parse_json(json_reader: &File) -> anyhow::Result<CompositionGraph> {
-    let graph = CompositionGraph::from_json_reader(json_reader)?;
-    if let Err(e) = graph.validate() {
-        return Err(anyhow::anyhow!(e));
-    }
+pub fn parse_json(json_reader: &File) -> Result<CompositionGraph> {
+    parse_json_value(serde_json::from_reader(json_reader).map_err(invalid_json)?)
+}
+
+pub fn parse_json_str(json: &str) -> Result<CompositionGraph> {
+    parse_json_value(serde_json::from_str(json).map_err(invalid_json)?)
+}
+
+fn parse_json_value(value: Value) -> Result<CompositionGraph> {
+    let model: JsonCompositionGraph =
+        serde_json::from_value(migrate(value)?).map_err(invalid_json)?;
+    let graph = CompositionGraph::from_json_model(model).map_err(invalid_json)?;
+    graph.validate()?;
     Ok(graph)
 }
 
-pub fn parse_json_str(json: &str) -> anyhow::Result<CompositionGraph> {
-    let graph = CompositionGraph::from_json_str(json)?;
-    if let Err(e) = graph.validate() {
-        return Err(anyhow::anyhow!(e));
+/// Oldest JSON layout still read.
+pub const MIN_JSON_VERSION: u32 = 1;
+
+/// Bring a saved graph in any layout from [`MIN_JSON_VERSION`] on up to
+/// [`JSON_VERSION`], one version at a time, so it reads like one written
+/// today.
+///
+/// Fails on documents without a version or with one newer than this build
+/// writes.
+pub fn migrate(mut value: Value) -> Result<Value> {
+    if value.get("kind").and_then(Value::as_str) == Some("core-module") {
+        return Err(
+            ParseError::new("this is the JSON of a core module, not a composition graph").into(),
+        );
     }
-    Ok(graph)
+    let version = value
+        .get("version")
+        .and_then(Value::as_u64)
+        .ok_or_else(|| ParseError::new("JSON graph has no version"))?;
+    if version > u64::from(JSON_VERSION) {
+        return Err(ParseError::new(format!(
+            "JSON graph version {} is newer than this cviz reads (up to {})",
+            version, JSON_VERSION
+        ))
+        .into());
+    }
+    if version < u64::from(MIN_JSON_VERSION) {
+        return Err(ParseError::new(format!("unsupported JSON graph version {}", version)).into());
+    }
+
+    for from in version..u64::from(JSON_VERSION) {
+        match from {
+            // Version 2 only added optional fields.
+            1 => {}
+            2 => derive_node_exports(&mut value),
+            _ => unreachable!("no migration from version {}", from),
+        }
+    }
+    value["version"] = JSON_VERSION.into();
+    Ok(value)
 }
 
-impl CompositionGraph {
-    fn from_json_str(input: &str) -> Result<Self, serde_json::Error> {
-        let model: JsonCompositionGraph = serde_json::from_str(input)?;
-        Self::from_json_model(model)
+/// List on each node the exports it provides, as version 3 does.
+fn derive_node_exports(value: &mut Value) {
+    let mut provided: BTreeMap<u64, Vec<Value>> = BTreeMap::new();
+    for export in value["exports"].as_array().into_iter().flatten() {
+        if let (Some(source), Some(name)) =
+            (export["source_instance"].as_u64(), export.get("interface"))
+        {
+            provided.entry(source).or_default().push(name.clone());
+        }
     }
-    fn from_json_reader<R: std::io::Read>(reader: R) -> Result<Self, serde_json::Error> {
-        let model: JsonCompositionGraph = serde_json::from_reader(reader)?;
-        Self::from_json_model(model)
+    let nodes = value.get_mut("nodes").and_then(Value::as_array_mut);
+    for node in nodes.into_iter().flatten().filter_map(Value::as_object_mut) {
+        if let Some(exports) = node
+            .get("id")
+            .and_then(Value::as_u64)
+            .and_then(|id| provided.remove(&id))
+        {
+            node.insert("exports".to_string(), Value::Array(exports));
+        }
     }
 }
 
+/// A JSON syntax or schema error; serde_json's message already gives its
+/// line and column.
+fn invalid_json(err: serde_json::Error) -> ParseError {
+    ParseError::new(err.to_string())
+}
+
 impl CompositionGraph {
     fn from_json_model(model: JsonCompositionGraph) -> Result<Self, serde_json::Error> {
         let mut arena = TypeArena::default();
@@ -47,6 +110,24 @@ impl CompositionGraph {
                 json_node.component_index,
                 json_node.component_num,
             );
+            node.package = json_node.package;
+            node.package_version = json_node.package_version;
+            node.component_name = json_node.component_name;
+            node.label = json_node.label;
+            node.depth = json_node.depth;
+            node.size = json_node.size;
+            node.digest = json_node.digest;
+            node.runs_start = json_node.runs_start;
+            node.copies = json_node.copies;
+            node.declared_imports = json_node
+                .declared_imports
+                .map(|names| names.into_iter().collect());
+            node.features = json_node
+                .features
+                .iter()
+                .map(|f| f.parse::<WasmFeature>())
+                .collect::<Result<_, _>>()
+                .map_err(serde_json::Error::custom)?;
 
             for conn in json_node.imports {
                 let interface_type = conn
@@ -54,6 +135,13 @@ impl CompositionGraph {
                     .map(|t| convert_interface_type(t, &mut arena))
                     .transpose()
                     .map_err(serde_json::Error::custom)?;
+                let kind = conn
+                    .kind
+                    .as_deref()
+                    .map(str::parse::<ExternKind>)
+                    .transpose()
+                    .map_err(serde_json::Error::custom)?
+                    .unwrap_or_default();
 
                 node.add_import(InterfaceConnection {
                     interface_name: conn.interface,
@@ -61,20 +149,77 @@ impl CompositionGraph {
                     is_host_import: conn.is_host_import,
                     interface_type,
                     fingerprint: conn.fingerprint,
+                    source_export: conn.source_export,
+                    kind,
+                    alias_chain: conn.alias_chain,
                 });
             }
 
             nodes.insert(json_node.id, node);
         }
 
-        let mut component_exports = BTreeMap::new();
-        for export in model.exports {
-            let iface_name = export.interface.clone();
-            let info = convert_export(export, &mut arena).map_err(serde_json::Error::custom)?;
-            component_exports.insert(iface_name, info);
+        let component_exports = model
+            .exports
+            .into_iter()
+            .map(|export| convert_export(export, &mut arena))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(serde_json::Error::custom)?;
+
+        let mut graph = CompositionGraph::new_with(nodes, component_exports, arena);
+        for json_core in model.core {
+            graph
+                .core
+                .insert(json_core.component_num, convert_core_structure(json_core));
         }
+        for m in model.metadata {
+            graph.metadata.insert(
+                m.component_num,
+                ComponentMetadata {
+                    name: m.name,
+                    authors: m.authors,
+                    description: m.description,
+                    licenses: m.licenses,
+                    source: m.source,
+                    homepage: m.homepage,
+                    revision: m.revision,
+                    version: m.version,
+                    producers: m.producers,
+                },
+            );
+        }
+        Ok(graph)
+    }
+}
 
-        Ok(CompositionGraph::new_with(nodes, component_exports, arena))
+fn convert_core_structure(json: JsonCoreStructure) -> CoreStructure {
+    CoreStructure {
+        modules: json
+            .modules
+            .into_iter()
+            .map(|m| CoreModule {
+                index: m.index,
+                name: m.name,
+            })
+            .collect(),
+        instances: json
+            .instances
+            .into_iter()
+            .map(|i| CoreInstance {
+                index: i.index,
+                name: i.name,
+                kind: match i.kind {
+                    JsonCoreInstanceKind::Instantiate { module, args } => {
+                        CoreInstanceKind::Instantiate {
+                            module_index: module,
+                            args: args.into_iter().map(|a| (a.name, a.instance)).collect(),
+                        }
+                    }
+                    JsonCoreInstanceKind::FromExports { exports } => {
+                        CoreInstanceKind::FromExports(exports)
+                    }
+                },
+            })
+            .collect(),
     }
 }
 
@@ -88,10 +233,19 @@ fn convert_export(json: JsonExport, arena: &mut TypeArena) -> Result<ExportInfo,
         }
         None => (None, json.fingerprint),
     };
+    let kind = json
+        .kind
+        .as_deref()
+        .map(str::parse::<ExternKind>)
+        .transpose()?
+        .unwrap_or_default();
     Ok(ExportInfo {
+        name: json.interface,
+        kind,
         source_instance: json.source_instance,
         fingerprint,
         ty,
+        alias_chain: json.alias_chain,
     })
 }
 
@@ -128,9 +282,16 @@ fn convert_func_signature(
         .into_iter()
         .map(|v| intern_value_type(v, arena))
         .collect::<Result<Vec<_>, _>>()?;
+    if !json.param_names.is_empty() && json.param_names.len() != params.len() {
+        return Err(format!(
+            "Function has {} parameter(s) but {} parameter name(s)",
+            params.len(),
+            json.param_names.len()
+        ));
+    }
     Ok(FuncSignature {
-        is_async: false,
-        param_names: vec![],
+        is_async: json.is_async,
+        param_names: json.param_names,
         params,
         results,
     })
@@ -194,12 +355,14 @@ fn intern_value_type(json: ValueTypeJson, arena: &mut TypeArena) -> Result<Value
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::CvizError;
     use crate::model::{
-        ComponentNode, FuncSignature, InstanceInterface, InterfaceConnection, InterfaceType,
-        ValueType,
+        ComponentNode, ExternKind, FuncSignature, GraphIssue, InstanceInterface,
+        InterfaceConnection, InterfaceType, ValueType,
     };
     use crate::output::json::generate_json;
-    use std::collections::BTreeMap;
+    use crate::test_utils::simple_chain_graph;
+    use std::collections::{BTreeMap, BTreeSet};
 
     /// Serialize a graph to JSON then parse it back, returning the round-tripped graph.
     fn round_trip(graph: &CompositionGraph) -> CompositionGraph {
@@ -218,6 +381,9 @@ mod tests {
             is_host_import: true,
             interface_type: None,
             fingerprint: None,
+            source_export: None,
+            kind: ExternKind::Instance,
+            alias_chain: Vec::new(),
         });
         graph.add_node(1, srv);
 
@@ -228,6 +394,9 @@ mod tests {
             is_host_import: false,
             interface_type: None,
             fingerprint: None,
+            source_export: None,
+            kind: ExternKind::Instance,
+            alias_chain: Vec::new(),
         });
         graph.add_node(2, mw);
 
@@ -250,13 +419,212 @@ mod tests {
         assert!(!mw.imports[0].is_host_import);
         assert_eq!(mw.imports[0].source_instance, Some(1));
 
-        assert!(rt.component_exports.contains_key("wasi:http/handler@0.3.0"));
+        assert!(rt.export("wasi:http/handler@0.3.0").is_some());
+    }
+
+    #[test]
+    fn test_round_trip_core_structure() {
+        let mut graph = CompositionGraph::new();
+        graph.add_node(1, ComponentNode::new("$srv".to_string(), 0, 0));
+        let core = CoreStructure {
+            modules: vec![CoreModule {
+                index: 0,
+                name: Some("main".to_string()),
+            }],
+            instances: vec![
+                CoreInstance {
+                    index: 0,
+                    name: Some("env".to_string()),
+                    kind: CoreInstanceKind::FromExports(vec!["memory".to_string()]),
+                },
+                CoreInstance {
+                    index: 1,
+                    name: None,
+                    kind: CoreInstanceKind::Instantiate {
+                        module_index: 0,
+                        args: vec![("env".to_string(), 0)],
+                    },
+                },
+            ],
+        };
+        graph.core.insert(0, core.clone());
+
+        let rt = round_trip(&graph);
+        assert_eq!(rt.core.get(&0), Some(&core));
+    }
+
+    #[test]
+    fn test_round_trip_metadata() {
+        let mut graph = CompositionGraph::new();
+        let mut node = ComponentNode::new("$srv".to_string(), 0, 0);
+        node.package = Some("my:srv".to_string());
+        node.package_version = Some("1.0.0".to_string());
+        graph.add_node(1, node);
+        let root = ComponentMetadata {
+            licenses: Some("Apache-2.0".to_string()),
+            ..Default::default()
+        };
+        let srv = ComponentMetadata {
+            authors: Some("Jane Doe".to_string()),
+            revision: Some("abc123".to_string()),
+            ..Default::default()
+        };
+        graph.metadata.insert(None, root.clone());
+        graph.metadata.insert(Some(0), srv.clone());
+
+        let rt = round_trip(&graph);
+        assert_eq!(rt.nodes[&1].package_id().as_deref(), Some("my:srv@1.0.0"));
+        assert_eq!(rt.metadata.get(&None), Some(&root));
+        assert_eq!(rt.metadata.get(&Some(0)), Some(&srv));
+    }
+
+    #[test]
+    fn test_older_versions_are_migrated() {
+        let json = generate_json(&simple_chain_graph(), false).unwrap();
+        for version in MIN_JSON_VERSION..JSON_VERSION {
+            let mut old: Value = serde_json::from_str(&json).unwrap();
+            old["version"] = version.into();
+            for node in old["nodes"].as_array_mut().unwrap() {
+                node.as_object_mut().unwrap().remove("exports");
+            }
+            old.as_object_mut().unwrap().remove("warnings");
+
+            let migrated = migrate(old.clone()).unwrap();
+            assert_eq!(migrated["version"], JSON_VERSION);
+            assert_eq!(
+                migrated["nodes"][1]["exports"],
+                serde_json::json!(["wasi:http/handler@0.3.0"])
+            );
+            let graph = parse_json_str(&old.to_string()).unwrap();
+            assert_eq!(graph.nodes.len(), 2);
+        }
+    }
+
+    #[test]
+    fn test_unreadable_versions_are_rejected() {
+        let err = parse_json_str(r#"{"version": 99, "nodes": [], "exports": []}"#)
+            .err()
+            .unwrap();
+        assert!(
+            err.to_string().contains("newer than this cviz reads"),
+            "{}",
+            err
+        );
+        assert!(parse_json_str(r#"{"nodes": [], "exports": []}"#).is_err());
+        let err = parse_json_str(r#"{"kind": "core-module", "imports": {}, "exports": []}"#)
+            .err()
+            .unwrap();
+        assert!(matches!(err, CvizError::Parse(_)));
+        assert!(err.to_string().contains("core module"), "{}", err);
+    }
+
+    #[test]
+    fn test_invalid_graph_is_rejected() {
+        let mut graph = simple_chain_graph();
+        graph.nodes.get_mut(&2).unwrap().imports[0].source_instance = Some(2);
+        graph.component_exports[0].source_instance = 9;
+        let json = generate_json(&graph, false).unwrap();
+
+        let Err(err) = parse_json_str(&json) else {
+            panic!("invalid graph was accepted");
+        };
+        let CvizError::Validation(issues) = err else {
+            panic!("expected typed issues, got {:?}", err);
+        };
+        assert_eq!(
+            issues.0,
+            vec![
+                GraphIssue::SelfImport {
+                    instance: 2,
+                    interface: "wasi:http/handler@0.3.0".to_string(),
+                },
+                GraphIssue::UnknownExportSource {
+                    export: "wasi:http/handler@0.3.0".to_string(),
+                    source: 9,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_round_trip_connection_kind() {
+        let mut graph = simple_chain_graph();
+        graph.nodes.get_mut(&2).unwrap().imports[1].kind = ExternKind::Func;
+
+        let json = generate_json(&graph, false).unwrap();
+        assert!(json.contains("\"kind\":\"func\""), "{}", json);
+        let rt = round_trip(&graph);
+        assert_eq!(rt.nodes[&2].imports[0].kind, ExternKind::Instance);
+        assert_eq!(rt.nodes[&2].imports[1].kind, ExternKind::Func);
+    }
+
+    #[test]
+    fn test_round_trip_alias_chain() {
+        let mut graph = simple_chain_graph();
+        graph.nodes.get_mut(&2).unwrap().imports[0].alias_chain = vec!["nested".to_string()];
+        graph.component_exports[0].alias_chain = vec!["outer".to_string()];
+
+        let rt = round_trip(&graph);
+        assert_eq!(rt.nodes[&2].imports[0].alias_chain, vec!["nested"]);
+        assert!(rt.nodes[&2].imports[1].alias_chain.is_empty());
+        assert_eq!(rt.component_exports[0].alias_chain, vec!["outer"]);
+    }
+
+    #[test]
+    fn test_round_trip_export_kind() {
+        let mut graph = simple_chain_graph();
+        graph.add_export_of_kind("get".to_string(), ExternKind::Func, 1, None);
+
+        let rt = round_trip(&graph);
+        let exports: Vec<(&str, ExternKind)> = rt
+            .component_exports
+            .iter()
+            .map(|e| (e.name.as_str(), e.kind))
+            .collect();
+        assert_eq!(
+            exports,
+            vec![
+                ("get", ExternKind::Func),
+                ("wasi:http/handler@0.3.0", ExternKind::Instance)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_round_trip_resolved_label() {
+        let mut graph = CompositionGraph::new();
+        let mut node = ComponentNode::new(ComponentNode::fallback_name(3), 0, 0);
+        node.label = Some("auth".to_string());
+        node.component_name = Some("auth".to_string());
+        node.depth = 2;
+        node.size = Some(1_258_291);
+        node.digest = Some(format!("sha256:{}", "ab".repeat(32)));
+        node.features = BTreeSet::from([WasmFeature::Threads, WasmFeature::MultiMemory]);
+        node.runs_start = true;
+        node.copies = 3;
+        node.declared_imports = Some(BTreeSet::from(["wasi:http/handler@0.3.0".to_string()]));
+        graph.add_node(3, node);
+
+        let rt = round_trip(&graph);
+        assert!(rt.nodes[&3].is_unnamed());
+        assert_eq!(rt.nodes[&3].component_name.as_deref(), Some("auth"));
+        assert_eq!(rt.nodes[&3].display_label(), "auth");
+        assert_eq!(rt.nodes[&3].depth, 2);
+        assert_eq!(rt.nodes[&3].size, Some(1_258_291));
+        assert_eq!(rt.nodes[&3].digest, graph.nodes[&3].digest);
+        assert_eq!(rt.nodes[&3].features, graph.nodes[&3].features);
+        assert!(rt.nodes[&3].runs_start);
+        assert_eq!(rt.nodes[&3].copies, 3);
+        assert_eq!(
+            rt.nodes[&3].declared_imports,
+            graph.nodes[&3].declared_imports
+        );
     }
 
     #[test]
     fn test_round_trip_typed_interface() {
         // Build a graph with a typed instance interface that uses several complex types:
-        // greet(name: string, count: u32) -> result<list<string>, u32>
+        // async greet(name: string, count: u32) -> result<list<string>, u32>
         // status() -> record { code: u32, message: string }
         let mut graph = CompositionGraph::new();
         let arena = &mut graph.arena;
@@ -277,8 +645,8 @@ mod tests {
         functions.insert(
             "greet".to_string(),
             FuncSignature {
-                is_async: false,
-                param_names: vec![],
+                is_async: true,
+                param_names: vec!["name".to_string(), "count".to_string()],
                 params: vec![str_id, u32_id],
                 results: vec![result_ty],
             },
@@ -306,6 +674,9 @@ mod tests {
             is_host_import: true,
             interface_type: Some(iface),
             fingerprint: Some(fingerprint.clone()),
+            source_export: None,
+            kind: ExternKind::Instance,
+            alias_chain: Vec::new(),
         });
         graph.add_node(1, node);
 
@@ -332,6 +703,8 @@ mod tests {
         let greet = &inst.functions["greet"];
         assert_eq!(greet.params.len(), 2);
         assert_eq!(greet.results.len(), 1);
+        assert_eq!(greet.param_names, ["name", "count"]);
+        assert!(greet.is_async);
 
         // The result type should round-trip as result<list<string>, u32>
         assert!(
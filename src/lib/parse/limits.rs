@@ -0,0 +1,379 @@
+//! Checks run on a component binary before it is parsed.
+//!
+//! The component parser trusts the binary it is given: type references
+//! that point nowhere or back at themselves can send it into unbounded
+//! recursion.  So a binary is first validated, and held to [`Limits`] on
+//! what cviz is prepared to read, and only then parsed.  A binary failing
+//! either check is reported as a [`ParseError`], never a panic.
+//!
+//! One kind of invalid binary is let through: an instantiation the
+//! validator rejects, because an import was left out or plugged in with
+//! the wrong type.  Reporting those is part of what cviz is for (see
+//! [`unsatisfied_imports`](crate::analysis::unsatisfied_imports)).  The
+//! validator stops at such an instantiation, so the sections after it are
+//! only held to the limits.
+
+use crate::error::{CvizError, ParseError, Result};
+use tracing::debug;
+use wirm::wasmparser::{
+    BinaryReaderError, ComponentInstance, ComponentName, CustomSectionReader, IndirectNameMap,
+    KnownCustom, Name, NameMap, Parser, Payload, TypeRef, Validator, WasmFeatures,
+};
+
+/// Bounds on the components cviz reads, so that a malformed or hostile
+/// binary fails cleanly rather than exhausting memory or the stack.
+///
+/// The defaults are far beyond any real composition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    /// Most component instances, at any depth.
+    pub max_instances: usize,
+    /// Deepest nesting of components and core modules.
+    pub max_depth: usize,
+    /// Longest import, export or instantiation argument name, in bytes.
+    pub max_name_len: usize,
+    /// Largest section, in bytes.  A nested component or core module is
+    /// one section of its parent.
+    pub max_section_size: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_instances: 100_000,
+            max_depth: 64,
+            max_name_len: 4096,
+            max_section_size: 1 << 30,
+        }
+    }
+}
+
+/// Validate the component in `buff` and hold it to `limits`.
+///
+/// Function bodies are only decoded, not validated: cviz never looks
+/// inside them.
+pub(crate) fn check(buff: &[u8], limits: &Limits) -> Result<()> {
+    let mut checker = Checker::new(limits);
+    for payload in Parser::new(0).parse_all(buff) {
+        let payload = payload.map_err(|e| CvizError::binary(buff, e))?;
+        checker.payload(&payload, buff)?;
+    }
+    Ok(())
+}
+
+/// [`check`], one payload at a time, for a binary that is still being
+/// read.
+pub(crate) struct Checker<'l> {
+    limits: &'l Limits,
+    // Dropped after a rejected instantiation, see the module docs
+    validator: Option<Validator>,
+    // The component or core module being read, and those enclosing it
+    scopes: Vec<Scope>,
+    // Where the nested component or core module about to start ends
+    nested_end: usize,
+    instances: usize,
+}
+
+impl<'l> Checker<'l> {
+    pub(crate) fn new(limits: &'l Limits) -> Self {
+        Self {
+            limits,
+            validator: Some(Validator::new_with_features(WasmFeatures::all())),
+            scopes: Vec::new(),
+            nested_end: usize::MAX,
+            instances: 0,
+        }
+    }
+
+    /// Check the next `payload` of the binary, of which `buff` holds at
+    /// least as much as has been parsed.
+    pub(crate) fn payload(&mut self, payload: &Payload, buff: &[u8]) -> Result<()> {
+        let located = |err: ParseError, offset: usize| CvizError::from(err.in_binary(buff, offset));
+        let binary = |err: BinaryReaderError| CvizError::binary(buff, err);
+        let limits = self.limits;
+
+        if let Some(Err(e)) = self.validator.as_mut().map(|v| v.payload(payload)) {
+            if !is_rejected_instantiation(payload, &e) {
+                return Err(binary(e));
+            }
+            debug!(error = %e, "not validating past a rejected instantiation");
+            self.validator = None;
+        }
+
+        if let Some((_, range)) = payload.as_section() {
+            if range.len() > limits.max_section_size {
+                let message = format!(
+                    "section of {} bytes is larger than the limit of {}",
+                    range.len(),
+                    limits.max_section_size
+                );
+                return Err(located(ParseError::new(message), range.start));
+            }
+        }
+
+        let mut names = Vec::new();
+        match payload {
+            Payload::Version { range, .. } => {
+                self.scopes.push(Scope {
+                    end: self.nested_end,
+                    funcs: 0,
+                });
+                if self.scopes.len() > limits.max_depth {
+                    let message = format!(
+                        "components are nested deeper than the limit of {}",
+                        limits.max_depth
+                    );
+                    return Err(located(ParseError::new(message), range.start));
+                }
+            }
+            Payload::End(_) => {
+                self.scopes.pop();
+            }
+            Payload::ModuleSection {
+                unchecked_range, ..
+            }
+            | Payload::ComponentSection {
+                unchecked_range, ..
+            } => {
+                // The parser leaves it to the nested parser to notice this
+                let parent_end = self.scopes.last().map_or(usize::MAX, |s| s.end);
+                if unchecked_range.end > parent_end {
+                    let message = "nested section runs past the end of its parent";
+                    return Err(located(ParseError::new(message), unchecked_range.start));
+                }
+                self.nested_end = unchecked_range.end;
+            }
+            Payload::ImportSection(reader) => {
+                for import in reader.clone().into_imports() {
+                    if let TypeRef::Func(_) | TypeRef::FuncExact(_) = import.map_err(binary)?.ty {
+                        self.current().funcs += 1;
+                    }
+                }
+            }
+            Payload::CodeSectionStart { count, .. } => self.current().funcs += count,
+            Payload::CodeSectionEntry(body) => {
+                for local in body.get_locals_reader().map_err(binary)? {
+                    local.map_err(binary)?;
+                }
+                for op in body.get_operators_reader().map_err(binary)? {
+                    op.map_err(binary)?;
+                }
+            }
+            Payload::CustomSection(reader) => {
+                let funcs = self.current().funcs;
+                check_custom_section(reader, funcs).map_err(|e| CvizError::binary(buff, e))?;
+            }
+            Payload::ComponentInstanceSection(reader) => {
+                self.instances += reader.count() as usize;
+                if self.instances > limits.max_instances {
+                    let message = format!("more than {} component instances", limits.max_instances);
+                    return Err(located(ParseError::new(message), reader.range().start));
+                }
+                for instance in reader.clone().into_iter_with_offsets() {
+                    let (offset, instance) = instance.map_err(binary)?;
+                    match instance {
+                        ComponentInstance::Instantiate { args, .. } => {
+                            names.extend(args.iter().map(|a| (a.name, offset)))
+                        }
+                        ComponentInstance::FromExports(exports) => {
+                            names.extend(exports.iter().map(|e| (e.name.0, offset)))
+                        }
+                    }
+                }
+            }
+            Payload::ComponentImportSection(reader) => {
+                for import in reader.clone().into_iter_with_offsets() {
+                    let (offset, import) = import.map_err(binary)?;
+                    names.push((import.name.0, offset));
+                }
+            }
+            Payload::ComponentExportSection(reader) => {
+                for export in reader.clone().into_iter_with_offsets() {
+                    let (offset, export) = export.map_err(binary)?;
+                    names.push((export.name.0, offset));
+                }
+            }
+            _ => {}
+        }
+        if let Some((name, offset)) = names
+            .into_iter()
+            .find(|(name, _)| name.len() > limits.max_name_len)
+        {
+            let message = format!(
+                "name of {} bytes is longer than the limit of {}: {}...",
+                name.len(),
+                limits.max_name_len,
+                name.chars().take(40).collect::<String>()
+            );
+            return Err(located(ParseError::new(message), offset));
+        }
+        Ok(())
+    }
+
+    /// The innermost scope.  The parser starts every component or module
+    /// with its version header, so there always is one.
+    fn current(&mut self) -> &mut Scope {
+        self.scopes
+            .last_mut()
+            .expect("payload outside a component or module")
+    }
+}
+
+/// A component or core module being checked.
+struct Scope {
+    /// Offset its binary ends at.
+    end: usize,
+    /// Functions of a core module, imported ones included.
+    funcs: u32,
+}
+
+/// Whether `err`, from validating `payload`, is about a component being
+/// instantiated with an import left out or of the wrong type.
+fn is_rejected_instantiation(payload: &Payload, err: &BinaryReaderError) -> bool {
+    matches!(payload, Payload::ComponentInstanceSection(_))
+        && (err.message().starts_with("missing import named")
+            || err.message().starts_with("type mismatch"))
+}
+
+/// Decode the custom sections the component parser reads, which the
+/// validator leaves alone, making sure function names refer to one of the
+/// `funcs` functions of the module.
+fn check_custom_section(reader: &CustomSectionReader, funcs: u32) -> Result<(), ParseError> {
+    let out_of_bounds =
+        |offset| ParseError::new("function name for a function that does not exist").at(offset);
+    let check_map = |map: NameMap| map.into_iter().try_for_each(|n| n.map(drop));
+    let check_indirect = |map: IndirectNameMap| {
+        map.into_iter()
+            .try_for_each(|n| n.and_then(|n| check_map(n.names)))
+    };
+    match reader.as_known() {
+        KnownCustom::Name(names) => {
+            for name in names {
+                match name? {
+                    Name::Function(map) => {
+                        for naming in map.into_iter_with_offsets() {
+                            let (offset, naming) = naming?;
+                            if naming.index >= funcs {
+                                return Err(out_of_bounds(offset));
+                            }
+                        }
+                    }
+                    Name::Type(map)
+                    | Name::Table(map)
+                    | Name::Memory(map)
+                    | Name::Global(map)
+                    | Name::Element(map)
+                    | Name::Data(map)
+                    | Name::Tag(map) => check_map(map)?,
+                    Name::Local(map) | Name::Label(map) | Name::Field(map) => check_indirect(map)?,
+                    Name::Module { .. } | Name::Unknown { .. } => {}
+                }
+            }
+        }
+        KnownCustom::ComponentName(names) => {
+            for name in names {
+                match name? {
+                    ComponentName::CoreFuncs(map)
+                    | ComponentName::CoreGlobals(map)
+                    | ComponentName::CoreMemories(map)
+                    | ComponentName::CoreTables(map)
+                    | ComponentName::CoreTags(map)
+                    | ComponentName::CoreModules(map)
+                    | ComponentName::CoreInstances(map)
+                    | ComponentName::CoreTypes(map)
+                    | ComponentName::Types(map)
+                    | ComponentName::Instances(map)
+                    | ComponentName::Components(map)
+                    | ComponentName::Funcs(map)
+                    | ComponentName::Values(map) => check_map(map)?,
+                    ComponentName::Component { .. } | ComponentName::Unknown { .. } => {}
+                }
+            }
+        }
+        KnownCustom::Producers(producers) => {
+            let mut fields = 0;
+            for field in producers {
+                for value in field?.values {
+                    value?;
+                }
+                fields += 1;
+            }
+            if fields == 0 {
+                return Err(
+                    ParseError::new("producers section has no fields").at(reader.data_offset())
+                );
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nested(depth: usize) -> Vec<u8> {
+        let wat = "(component ".repeat(depth) + &")".repeat(depth);
+        wat::parse_str(wat).unwrap()
+    }
+
+    #[test]
+    fn test_limits() {
+        let limits = Limits {
+            max_depth: 3,
+            ..Limits::default()
+        };
+        assert!(check(&nested(3), &limits).is_ok());
+        let err = check(&nested(4), &limits).unwrap_err().to_string();
+        assert!(err.contains("nested deeper than the limit of 3"), "{}", err);
+
+        let wat = format!(
+            r#"(component
+                (import "{}" (instance))
+                (instance)
+                (instance)
+            )"#,
+            "x".repeat(50)
+        );
+        let bytes = wat::parse_str(wat).unwrap();
+        assert!(check(&bytes, &Limits::default()).is_ok());
+        let limits = Limits {
+            max_name_len: 49,
+            ..Limits::default()
+        };
+        let err = check(&bytes, &limits).unwrap_err().to_string();
+        assert!(err.contains("name of 50 bytes"), "{}", err);
+        assert!(err.contains("in the import section"), "{}", err);
+        let limits = Limits {
+            max_instances: 1,
+            ..Limits::default()
+        };
+        let err = check(&bytes, &limits).unwrap_err().to_string();
+        assert!(err.contains("more than 1 component instances"), "{}", err);
+        let limits = Limits {
+            max_section_size: 10,
+            ..Limits::default()
+        };
+        assert!(check(&bytes, &limits).is_err());
+    }
+
+    #[test]
+    fn test_invalid_types_are_rejected() {
+        // An import of an instance type that was never defined
+        let bytes = wat::parse_str(r#"(component (import "a" (instance (type 5))))"#).unwrap();
+        let err = check(&bytes, &Limits::default()).unwrap_err().to_string();
+        assert!(err.contains("type index out of bounds"), "{}", err);
+        assert!(err.contains("in the import section"), "{}", err);
+
+        // An instantiation missing an import is still read
+        let bytes = wat::parse_str(
+            r#"(component
+                (component $app (import "a" (instance)))
+                (instance (instantiate $app))
+            )"#,
+        )
+        .unwrap();
+        assert!(check(&bytes, &Limits::default()).is_ok());
+    }
+}
@@ -1,2 +1,13 @@
+pub mod annotation;
 pub mod component;
+pub mod compose;
+pub(crate) mod features;
+#[cfg(feature = "json")]
 pub mod json;
+pub mod limits;
+pub mod module;
+pub(crate) mod sections;
+#[cfg(feature = "async")]
+pub mod stream;
+pub mod wac;
+pub mod wadm;
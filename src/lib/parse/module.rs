@@ -0,0 +1,197 @@
+//! Recognise plain core wasm modules and read their imports and exports.
+//!
+//! cviz visualizes components; a core module has no instances to draw, but
+//! its import and export lists still answer most "what does this binary
+//! need?" questions.
+
+use crate::error::{CvizError, Result};
+use crate::model::{ModuleInterface, ModuleItem};
+use std::fmt;
+use wirm::wasmparser::{ExternalKind, FuncType, Parser, Payload, TypeRef};
+
+/// The input is a core module rather than a component.
+///
+/// Returned (as [`CvizError::NotAComponent`]) by
+/// [`parse_component`](crate::parse::component::parse_component), carrying
+/// the module's imports and exports so callers can show those instead.
+#[derive(Debug)]
+pub struct NotAComponent(pub ModuleInterface);
+
+impl fmt::Display for NotAComponent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "input is a core wasm module, not a component")
+    }
+}
+
+impl std::error::Error for NotAComponent {}
+
+/// Whether `buff` starts with the header of a core module (wasm magic
+/// number followed by version 1), as opposed to a component's.
+pub fn is_core_module(buff: &[u8]) -> bool {
+    buff.starts_with(b"\0asm\x01\0\0\0")
+}
+
+/// Read the imports and exports of the core module in `buff`.
+pub fn parse_module(buff: &[u8]) -> Result<ModuleInterface> {
+    read_module(buff).map_err(|e| CvizError::binary(buff, e))
+}
+
+fn read_module(buff: &[u8]) -> wirm::wasmparser::Result<ModuleInterface> {
+    let mut types: Vec<FuncType> = Vec::new();
+    // Type index of every function, imported ones first.
+    let mut funcs: Vec<u32> = Vec::new();
+    let mut module = ModuleInterface::default();
+    let mut exports = Vec::new();
+
+    for payload in Parser::new(0).parse_all(buff) {
+        match payload? {
+            Payload::TypeSection(reader) => {
+                for ty in reader.into_iter_err_on_gc_types() {
+                    types.push(ty?);
+                }
+            }
+            Payload::ImportSection(reader) => {
+                for import in reader.into_imports() {
+                    let import = import?;
+                    let (kind, signature) = match import.ty {
+                        TypeRef::Func(ty) | TypeRef::FuncExact(ty) => {
+                            funcs.push(ty);
+                            ("func", types.get(ty as usize).map(signature))
+                        }
+                        TypeRef::Table(_) => ("table", None),
+                        TypeRef::Memory(_) => ("memory", None),
+                        TypeRef::Global(_) => ("global", None),
+                        TypeRef::Tag(_) => ("tag", None),
+                    };
+                    module
+                        .imports
+                        .entry(import.module.to_string())
+                        .or_default()
+                        .push(ModuleItem {
+                            name: import.name.to_string(),
+                            kind: kind.to_string(),
+                            signature,
+                        });
+                }
+            }
+            Payload::FunctionSection(reader) => {
+                for ty in reader {
+                    funcs.push(ty?);
+                }
+            }
+            Payload::ExportSection(reader) => {
+                for export in reader {
+                    let export = export?;
+                    exports.push((export.name.to_string(), export.kind, export.index));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Exports precede the code but may follow the function section, so
+    // function signatures are resolved once everything has been read.
+    module.exports = exports
+        .into_iter()
+        .map(|(name, kind, index)| {
+            let (kind, signature) = match kind {
+                ExternalKind::Func | ExternalKind::FuncExact => (
+                    "func",
+                    funcs
+                        .get(index as usize)
+                        .and_then(|&ty| types.get(ty as usize))
+                        .map(signature),
+                ),
+                ExternalKind::Table => ("table", None),
+                ExternalKind::Memory => ("memory", None),
+                ExternalKind::Global => ("global", None),
+                ExternalKind::Tag => ("tag", None),
+            };
+            ModuleItem {
+                name,
+                kind: kind.to_string(),
+                signature,
+            }
+        })
+        .collect();
+    Ok(module)
+}
+
+/// Format a core function type as `(i32, i32) -> i32`, in the style of
+/// component function signatures.
+fn signature(ty: &FuncType) -> String {
+    let list = |types: &[wirm::wasmparser::ValType]| {
+        types
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let results = match ty.results() {
+        [] => "()".to_string(),
+        [single] => single.to_string(),
+        results => format!("({})", list(results)),
+    };
+    format!("({}) -> {}", list(ty.params()), results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn module() -> Vec<u8> {
+        wat::parse_str(
+            r#"(module
+                (import "wasi_snapshot_preview1" "fd_write"
+                    (func (param i32 i32 i32 i32) (result i32)))
+                (import "wasi_snapshot_preview1" "proc_exit" (func (param i32)))
+                (import "env" "memory" (memory 1))
+                (func (export "_start"))
+                (global (export "counter") i32 (i32.const 0)))"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_imports_and_exports() {
+        let bytes = module();
+        assert!(is_core_module(&bytes));
+        let module = parse_module(&bytes).unwrap();
+
+        let wasi = &module.imports["wasi_snapshot_preview1"];
+        assert_eq!(wasi[0].name, "fd_write");
+        assert_eq!(
+            wasi[0].signature.as_deref(),
+            Some("(i32, i32, i32, i32) -> i32")
+        );
+        assert_eq!(wasi[1].signature.as_deref(), Some("(i32) -> ()"));
+        assert_eq!(module.imports["env"][0].kind, "memory");
+
+        let exports: Vec<(&str, &str, Option<&str>)> = module
+            .exports
+            .iter()
+            .map(|e| (e.name.as_str(), e.kind.as_str(), e.signature.as_deref()))
+            .collect();
+        assert_eq!(
+            exports,
+            vec![
+                ("_start", "func", Some("() -> ()")),
+                ("counter", "global", None)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_component_rejects_module() {
+        let err = crate::parse::component::parse_component(&module())
+            .err()
+            .unwrap();
+        let CvizError::NotAComponent(NotAComponent(module)) = err else {
+            panic!("expected NotAComponent, got {:?}", err);
+        };
+        assert_eq!(module.exports.len(), 2);
+
+        let component = wat::parse_str("(component)").unwrap();
+        assert!(!is_core_module(&component));
+    }
+}
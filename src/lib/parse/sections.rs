@@ -0,0 +1,191 @@
+//! The top-level sections of a wasm binary, read without parsing them.
+//!
+//! Enough to copy a binary section by section (see
+//! [`annotation`](super::annotation)) and to say which section an error
+//! offset falls in.
+
+use crate::error::ParseError;
+use std::ops::Range;
+
+/// One top-level section of a binary.
+pub(crate) struct Section {
+    pub id: u8,
+    /// The whole section, header included.
+    pub range: Range<usize>,
+    /// The section's contents.
+    pub contents: Range<usize>,
+}
+
+/// Read an unsigned LEB128 `u32` at `*pos`, advancing past it.
+pub(crate) fn read_u32(buff: &[u8], pos: &mut usize) -> Result<u32, ParseError> {
+    let start = *pos;
+    let mut value: u32 = 0;
+    for shift in (0..35).step_by(7) {
+        let Some(&byte) = buff.get(*pos) else {
+            return Err(ParseError::new("unexpected end of binary").at(start));
+        };
+        *pos += 1;
+        value |= u32::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(ParseError::new("invalid LEB128 integer").at(start))
+}
+
+/// Append `value` as unsigned LEB128.
+pub(crate) fn write_u32(out: &mut Vec<u8>, mut value: u32) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+/// The section starting at `*pos`, advancing past it.
+fn next_section(buff: &[u8], pos: &mut usize) -> Result<Section, ParseError> {
+    let start = *pos;
+    let id = buff[start];
+    *pos += 1;
+    let size = read_u32(buff, pos)? as usize;
+    let end = pos
+        .checked_add(size)
+        .filter(|&end| end <= buff.len())
+        .ok_or_else(|| ParseError::new("section runs past the end of the binary").at(start))?;
+    let section = Section {
+        id,
+        range: start..end,
+        contents: *pos..end,
+    };
+    *pos = end;
+    Ok(section)
+}
+
+/// The top-level sections of the module or component in `buff`.
+pub(crate) fn sections(buff: &[u8]) -> Result<Vec<Section>, ParseError> {
+    if !buff.starts_with(b"\0asm") || buff.len() < 8 {
+        return Err(ParseError::new("not a wasm binary"));
+    }
+    let mut sections = Vec::new();
+    let mut pos = 8;
+    while pos < buff.len() {
+        sections.push(next_section(buff, &mut pos)?);
+    }
+    Ok(sections)
+}
+
+/// The name and data of a custom section's contents.
+pub(crate) fn custom_section(contents: &[u8]) -> Result<(&str, &[u8]), ParseError> {
+    let mut pos = 0;
+    let len = read_u32(contents, &mut pos)? as usize;
+    let (name, data) = pos
+        .checked_add(len)
+        .filter(|&end| end <= contents.len())
+        .map(|end| (&contents[pos..end], &contents[end..]))
+        .ok_or_else(|| ParseError::new("custom section name runs past the section"))?;
+    let name = std::str::from_utf8(name)
+        .map_err(|e| ParseError::caused_by("custom section name is not UTF-8", e))?;
+    Ok((name, data))
+}
+
+/// The name of the top-level section of `buff` holding byte `offset`, as
+/// far as the binary can be read.
+pub(crate) fn section_at(buff: &[u8], offset: usize) -> Option<String> {
+    if !buff.starts_with(b"\0asm") || offset < 8 {
+        return None;
+    }
+    let core = crate::parse::module::is_core_module(buff);
+    let mut pos = 8;
+    while pos < buff.len() {
+        // A truncated binary usually fails in its last section, whose
+        // declared size runs past the end: it still holds the offset.
+        let start = pos;
+        let id = buff[start];
+        pos += 1;
+        let size = read_u32(buff, &mut pos).ok()? as usize;
+        let contents = pos..pos.saturating_add(size);
+        pos = contents.end;
+        if !(start..contents.end).contains(&offset) {
+            continue;
+        }
+        if id == 0 {
+            let contents = &buff[contents.start..contents.end.min(buff.len())];
+            let (name, _) = custom_section(contents).ok()?;
+            return Some(format!("custom \"{}\"", name));
+        }
+        let names: &[&str] = if core {
+            &[
+                "",
+                "type",
+                "import",
+                "function",
+                "table",
+                "memory",
+                "global",
+                "export",
+                "start",
+                "element",
+                "code",
+                "data",
+                "data count",
+                "tag",
+            ]
+        } else {
+            &[
+                "",
+                "core module",
+                "core instance",
+                "core type",
+                "component",
+                "instance",
+                "alias",
+                "type",
+                "canonical function",
+                "start",
+                "import",
+                "export",
+                "value",
+            ]
+        };
+        return names.get(id as usize).map(|n| n.to_string());
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_section_at() {
+        let wasm = wat::parse_str(
+            r#"(component
+                (core module)
+                (@custom "licenses" "MIT")
+            )"#,
+        )
+        .unwrap();
+        let sections = sections(&wasm).unwrap();
+        assert_eq!(sections.len(), 2);
+        assert_eq!(
+            section_at(&wasm, sections[0].contents.start).as_deref(),
+            Some("core module")
+        );
+        assert_eq!(
+            section_at(&wasm, sections[1].contents.end - 1).as_deref(),
+            Some("custom \"licenses\"")
+        );
+        assert_eq!(section_at(&wasm, 0), None);
+        assert_eq!(section_at(&wasm, wasm.len()), None);
+
+        let truncated = &wasm[..wasm.len() - 1];
+        assert_eq!(
+            section_at(truncated, wasm.len() - 1).as_deref(),
+            Some("custom \"licenses\"")
+        );
+    }
+}
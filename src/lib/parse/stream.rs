@@ -0,0 +1,199 @@
+//! Parse a component while it is still arriving, e.g. over the network.
+//!
+//! The binary is validated and held to the [`Limits`] section by section
+//! as it is read, so a service receiving an invalid or oversized upload
+//! can turn it away as soon as the offending section is in, without
+//! waiting for (or buffering) the rest.  The graph itself is built once
+//! the stream ends: the component parser needs the whole binary.
+
+use crate::error::{CvizError, ParseError, Result};
+use crate::model::CompositionGraph;
+use crate::parse::component::{read_checked, ParseOptions};
+use crate::parse::limits::{Checker, Limits};
+use crate::parse::module;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tracing::debug;
+use wirm::wasmparser::{Chunk, Encoding, Parser, Payload};
+
+/// Bytes asked of the reader at a time.
+const READ_SIZE: usize = 64 * 1024;
+
+/// [`parse_component`](crate::parse::component::parse_component) for a
+/// component read from `reader`.
+pub async fn parse_component_stream(reader: impl AsyncRead + Unpin) -> Result<CompositionGraph> {
+    parse_component_stream_with_options(reader, &ParseOptions::default()).await
+}
+
+/// [`parse_component_stream`], failing with
+/// [`Anomalies`](crate::parse::component::Anomalies) in strict mode.
+pub async fn parse_component_stream_with_options(
+    mut reader: impl AsyncRead + Unpin,
+    options: &ParseOptions,
+) -> Result<CompositionGraph> {
+    let mut buff = Vec::new();
+    let mut checker = Checker::new(&options.limits);
+    // The parser of the component or module being read, then of those
+    // enclosing it
+    let mut parsers = vec![Parser::new(0)];
+    // How much of `buff` has been parsed
+    let mut parsed = 0;
+    let mut eof = false;
+
+    while let Some(parser) = parsers.last_mut() {
+        let chunk = parser
+            .parse(&buff[parsed..], eof)
+            .map_err(|e| CvizError::binary(&buff, e))?;
+        let (payload, consumed) = match chunk {
+            Chunk::NeedMoreData(needed) => {
+                check_pending(needed, parsed, &options.limits)?;
+                eof = read_more(&mut reader, &mut buff).await?;
+                continue;
+            }
+            Chunk::Parsed { payload, consumed } => (payload, consumed),
+        };
+        if let Payload::Version {
+            encoding: Encoding::Module,
+            ..
+        } = payload
+        {
+            if parsers.len() == 1 {
+                // Not checked, as parse_component does not either
+                reader.read_to_end(&mut buff).await.map_err(read_failed)?;
+                return Err(module::NotAComponent(module::parse_module(&buff)?).into());
+            }
+        }
+        checker.payload(&payload, &buff)?;
+        parsed += consumed;
+        match payload {
+            Payload::ModuleSection { parser, .. } | Payload::ComponentSection { parser, .. } => {
+                parsers.push(parser)
+            }
+            Payload::End(_) => {
+                parsers.pop();
+            }
+            _ => {}
+        }
+    }
+    debug!(bytes = buff.len(), "read component");
+    read_checked(&buff, options)
+}
+
+/// Fail before reading on if the section at `offset` needs more than
+/// `needed` further bytes and so would exceed `limits` once it is in.
+fn check_pending(needed: u64, offset: usize, limits: &Limits) -> Result<()> {
+    if needed > limits.max_section_size as u64 {
+        let message = format!(
+            "section of more than {} bytes is larger than the limit of {}",
+            needed, limits.max_section_size
+        );
+        return Err(ParseError::new(message).at(offset).into());
+    }
+    Ok(())
+}
+
+/// Append what `reader` has next to `buff`, returning whether the stream
+/// has ended.
+async fn read_more(reader: &mut (impl AsyncRead + Unpin), buff: &mut Vec<u8>) -> Result<bool> {
+    buff.reserve(READ_SIZE);
+    let read = reader.read_buf(buff).await.map_err(read_failed)?;
+    Ok(read == 0)
+}
+
+fn read_failed(err: std::io::Error) -> CvizError {
+    ParseError::caused_by("failed to read the component", err).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::component::parse_component;
+    use tokio::io::AsyncWriteExt;
+
+    const WAT: &str = r#"(component
+        (import "wasi:http/handler@0.3.0" (instance $host
+            (export "handle" (func (param "req" u32) (result u32)))))
+        (component $mw
+            (import "wasi:http/handler@0.3.0" (instance $h
+                (export "handle" (func (param "req" u32) (result u32)))))
+            (export "wasi:http/handler@0.3.0" (instance $h)))
+        (instance $mw-inst (instantiate $mw
+            (with "wasi:http/handler@0.3.0" (instance $host))))
+        (export "wasi:http/handler@0.3.0" (instance $mw-inst "wasi:http/handler@0.3.0"))
+    )"#;
+
+    /// A reader receiving `bytes` a few at a time, and only the first
+    /// `sent` of them unless all are.
+    fn trickle(bytes: Vec<u8>, sent: Option<usize>) -> tokio::io::DuplexStream {
+        let (mut tx, rx) = tokio::io::duplex(16);
+        tokio::spawn(async move {
+            let end = sent.unwrap_or(bytes.len());
+            tx.write_all(&bytes[..end]).await.unwrap();
+            if sent.is_some() {
+                // Leave the stream open
+                std::future::pending::<()>().await;
+            }
+        });
+        rx
+    }
+
+    #[tokio::test]
+    async fn test_parse_component_stream() {
+        let bytes = wat::parse_str(WAT).unwrap();
+        let graph = parse_component_stream(trickle(bytes.clone(), None))
+            .await
+            .unwrap();
+        let expected = parse_component(&bytes).unwrap();
+        let names = |graph: &CompositionGraph| -> Vec<String> {
+            let nodes = graph.nodes.values().map(|n| n.name.clone());
+            let exports = graph.component_exports.iter().map(|e| e.name.clone());
+            nodes.chain(exports).collect()
+        };
+        assert_eq!(names(&graph), names(&expected));
+        assert_eq!(graph.nodes.len(), 1);
+
+        let truncated = parse_component_stream(&bytes[..bytes.len() - 3]).await;
+        assert!(truncated.is_err());
+
+        let module = wat::parse_str("(module (func (export \"run\")))").unwrap();
+        let err = parse_component_stream(&module[..]).await.err().unwrap();
+        assert!(matches!(err, CvizError::NotAComponent(_)), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_rejected_before_the_end() {
+        let options = ParseOptions {
+            limits: Limits {
+                max_section_size: 1000,
+                ..Limits::default()
+            },
+            ..ParseOptions::default()
+        };
+        // A custom section announcing 100 000 bytes, of which none arrive
+        let mut bytes = b"\0asm\x0d\0\x01\0\0\xa0\x8d\x06".to_vec();
+        let sent = bytes.len();
+        bytes.resize(sent + 100_000, 0);
+        let err = parse_component_stream_with_options(trickle(bytes, Some(sent)), &options)
+            .await
+            .err()
+            .unwrap();
+        assert!(
+            err.to_string().contains("larger than the limit of 1000"),
+            "{}",
+            err
+        );
+
+        // An invalid section fails as soon as it is in
+        let mut bytes = wat::parse_str(r#"(component (import "a" (instance (type 5))))"#).unwrap();
+        let sent = bytes.len();
+        bytes.resize(sent + 100_000, 0);
+        let err = parse_component_stream(trickle(bytes, Some(sent)))
+            .await
+            .err()
+            .unwrap();
+        assert!(
+            err.to_string().contains("type index out of bounds"),
+            "{}",
+            err
+        );
+    }
+}
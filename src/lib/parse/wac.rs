@@ -0,0 +1,460 @@
+//! Build a [`CompositionGraph`] from WAC composition source.
+//!
+//! This reads the wiring a `.wac` file describes, so a composition can be
+//! previewed before `wac compose` produces a binary.  Only the statements
+//! that shape the graph are interpreted:
+//!
+//! - `let x = new ns:pkg@1.0.0 { a: y.b, c, ... };` adds an instance;
+//! - `let x = y.b;` and `import x: ...;` bind names used as arguments;
+//! - `export x.b;` (optionally `as`/`with` a name) exports an item.
+//!
+//! Type, interface and world declarations are skipped.  Since the component
+//! binaries are not read, the graph carries no type information and the
+//! implicit imports a `...` argument would forward are not shown.
+
+use crate::error::{ParseError, Result};
+use crate::model::{naming, ComponentNode, CompositionGraph, ExternKind, InterfaceConnection};
+use std::collections::HashMap;
+
+/// Parse WAC source into a composition graph.
+pub fn parse_wac_str(src: &str) -> Result<CompositionGraph> {
+    let mut parser = Parser {
+        src,
+        pos: 0,
+        graph: CompositionGraph::new(),
+        bindings: HashMap::new(),
+        next_id: 0,
+    };
+    parser.document()?;
+    let mut graph = parser.graph;
+    graph.validate()?;
+    naming::resolve_labels(&mut graph, &naming::default_resolvers());
+    Ok(graph)
+}
+
+/// What a name or expression refers to.
+#[derive(Debug, Clone)]
+enum Item {
+    /// A whole instance, by graph ID.
+    Instance(u32),
+    /// A named export of an instance.
+    Export(u32, String),
+    /// An item imported from the host under this name.
+    Import(String),
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+    graph: CompositionGraph,
+    bindings: HashMap<String, Item>,
+    next_id: u32,
+}
+
+impl Parser<'_> {
+    fn document(&mut self) -> Result<()> {
+        loop {
+            self.skip_trivia();
+            if self.pos == self.src.len() {
+                return Ok(());
+            }
+            let keyword = self.ident()?;
+            match keyword.as_str() {
+                "let" => {
+                    let name = self.ident()?;
+                    self.expect('=')?;
+                    let item = self.expr(Some(&name))?;
+                    self.expect(';')?;
+                    self.bindings.insert(name, item);
+                }
+                "import" => self.import()?,
+                "export" => self.export()?,
+                "package" | "type" | "interface" | "world" | "use" | "include" | "record"
+                | "variant" | "enum" | "flags" | "resource" => self.skip_item()?,
+                other => return Err(self.error(&format!("unexpected `{}`", other)).into()),
+            }
+        }
+    }
+
+    /// `import name [with "name"]: <type>;`
+    fn import(&mut self) -> Result<()> {
+        let name = self.ident()?;
+        let mut import_name = name.clone();
+        if self.eat_keyword("with") {
+            import_name = self.string()?;
+        }
+        self.expect(':')?;
+        self.skip_trivia();
+        // `import handler: wasi:http/handler@0.2.0;` imports that interface.
+        let start = self.pos;
+        let path = self.package_path();
+        if path.contains('/') && import_name == name {
+            import_name = path;
+        } else {
+            self.pos = start;
+        }
+        self.skip_item()?;
+        self.bindings.insert(name, Item::Import(import_name));
+        Ok(())
+    }
+
+    /// `export <expr> [as name | with "name"];`
+    fn export(&mut self) -> Result<()> {
+        let item = self.expr(None)?;
+        let name = if self.eat_keyword("as") {
+            self.skip_trivia();
+            if self.peek() == Some('"') {
+                Some(self.string()?)
+            } else {
+                Some(self.ident()?)
+            }
+        } else if self.eat_keyword("with") {
+            Some(self.string()?)
+        } else {
+            None
+        };
+        self.expect(';')?;
+
+        let (source, default_name) = match item {
+            Item::Export(id, export) => (id, Some(export)),
+            Item::Instance(id) => (id, None),
+            // Re-exporting a host import does not touch any instance.
+            Item::Import(_) => return Ok(()),
+        };
+        let name = name
+            .or(default_name)
+            .ok_or_else(|| self.error("exporting an instance needs `as` or `with` a name"))?;
+        self.graph.add_export(name, source, None);
+        Ok(())
+    }
+
+    /// `postfix := primary ('.' ident | '[' string ']')*`
+    fn expr(&mut self, binding: Option<&str>) -> Result<Item> {
+        self.skip_trivia();
+        let mut item = if self.eat_keyword("new") {
+            self.instantiate(binding)?
+        } else if self.eat('(') {
+            let item = self.expr(None)?;
+            self.expect(')')?;
+            item
+        } else {
+            let name = self.ident()?;
+            self.bindings
+                .get(&name)
+                .cloned()
+                .ok_or_else(|| self.error(&format!("undefined name `{}`", name)))?
+        };
+
+        loop {
+            self.skip_trivia();
+            let export = if self.eat('.') {
+                self.ident()?
+            } else if self.eat('[') {
+                let name = self.string()?;
+                self.expect(']')?;
+                name
+            } else {
+                return Ok(item);
+            };
+            item = match item {
+                Item::Instance(id) => Item::Export(id, export),
+                Item::Import(name) => Item::Import(format!("{}.{}", name, export)),
+                Item::Export(..) => {
+                    return Err(self.error("nested export access is not supported").into())
+                }
+            };
+        }
+    }
+
+    /// `new ns:pkg[@version] { arg, ... }`, after `new`.
+    fn instantiate(&mut self, binding: Option<&str>) -> Result<Item> {
+        self.skip_trivia();
+        let package = self.package_path();
+        if package.is_empty() {
+            return Err(self.error("expected a package name after `new`").into());
+        }
+
+        let id = self.next_id;
+        self.next_id += 1;
+        let name = match binding {
+            Some(name) => format!("${}", name),
+            None => ComponentNode::fallback_name(id),
+        };
+        let mut node = ComponentNode::new(name, id, id);
+        match package.split_once('@') {
+            Some((package, version)) => {
+                node.package = Some(package.to_string());
+                node.package_version = Some(version.to_string());
+            }
+            None => node.package = Some(package),
+        }
+
+        self.expect('{')?;
+        loop {
+            self.skip_trivia();
+            if self.eat('}') {
+                break;
+            }
+            if self.src[self.pos..].starts_with("...") {
+                // Forwarded implicit imports are unknown without the binary.
+                self.pos += 3;
+            } else {
+                let import = if self.peek() == Some('"') {
+                    self.string()?
+                } else {
+                    self.ident()?
+                };
+                self.skip_trivia();
+                let value = if self.eat(':') {
+                    self.expr(None)?
+                } else {
+                    self.bindings
+                        .get(&import)
+                        .cloned()
+                        .ok_or_else(|| self.error(&format!("undefined name `{}`", import)))?
+                };
+                node.add_import(connection(import, value));
+            }
+            self.skip_trivia();
+            if !self.eat(',') {
+                self.expect('}')?;
+                break;
+            }
+        }
+
+        self.graph.add_node(id, node);
+        Ok(Item::Instance(id))
+    }
+
+    /// Skip the rest of a declaration: up to a `;` or a closing `}` at
+    /// nesting depth zero.
+    fn skip_item(&mut self) -> Result<()> {
+        let mut depth = 0usize;
+        loop {
+            self.skip_trivia();
+            if self.src[self.pos..].starts_with("->") {
+                self.pos += 2;
+                continue;
+            }
+            match self.peek() {
+                None => return Err(self.error("unexpected end of input").into()),
+                Some('"') => {
+                    self.string()?;
+                    continue;
+                }
+                Some('{' | '(' | '<') => depth += 1,
+                Some('}' | ')' | '>') => {
+                    depth = depth.saturating_sub(1);
+                    if depth == 0 && self.peek() == Some('}') {
+                        self.pos += 1;
+                        self.eat(';');
+                        return Ok(());
+                    }
+                }
+                Some(';') if depth == 0 => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                _ => {}
+            }
+            self.pos += self.peek().map_or(1, char::len_utf8);
+        }
+    }
+
+    fn skip_trivia(&mut self) {
+        loop {
+            let rest = &self.src[self.pos..];
+            let trimmed = rest.trim_start();
+            self.pos += rest.len() - trimmed.len();
+            if trimmed.starts_with("//") {
+                self.pos += trimmed.find('\n').unwrap_or(trimmed.len());
+            } else if let Some(body) = trimmed.strip_prefix("/*") {
+                self.pos += body.find("*/").map_or(trimmed.len(), |end| end + 4);
+            } else {
+                return;
+            }
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.src[self.pos..].chars().next()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_trivia();
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<()> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{}`", c)).into())
+        }
+    }
+
+    /// Consume `keyword` if it is the next whole word.
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        self.skip_trivia();
+        let rest = &self.src[self.pos..];
+        let boundary = rest[keyword.len().min(rest.len())..]
+            .chars()
+            .next()
+            .is_none_or(|c| !is_ident_char(c));
+        if rest.starts_with(keyword) && boundary {
+            self.pos += keyword.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// A kebab-case identifier, with an optional `%` escape prefix.
+    fn ident(&mut self) -> Result<String> {
+        self.skip_trivia();
+        let rest = &self.src[self.pos..];
+        let body = rest.strip_prefix('%').unwrap_or(rest);
+        let len = body.find(|c| !is_ident_char(c)).unwrap_or(body.len());
+        if len == 0 {
+            return Err(self.error("expected an identifier").into());
+        }
+        self.pos += rest.len() - body.len() + len;
+        Ok(body[..len].to_string())
+    }
+
+    fn string(&mut self) -> Result<String> {
+        self.skip_trivia();
+        let rest = &self.src[self.pos..];
+        let body = rest
+            .strip_prefix('"')
+            .ok_or_else(|| self.error("expected a string"))?;
+        let end = body
+            .find('"')
+            .ok_or_else(|| self.error("unterminated string"))?;
+        self.pos += end + 2;
+        Ok(body[..end].to_string())
+    }
+
+    /// A package path such as `ns:pkg/iface@1.0.0-rc.1`.
+    fn package_path(&mut self) -> String {
+        let rest = &self.src[self.pos..];
+        let len = rest
+            .find(|c: char| !(is_ident_char(c) || matches!(c, ':' | '/' | '@' | '.' | '+')))
+            .unwrap_or(rest.len());
+        self.pos += len;
+        rest[..len].to_string()
+    }
+
+    /// An error pointing at the current line and column.
+    fn error(&self, message: &str) -> ParseError {
+        let before = &self.src[..self.pos];
+        let line = before.matches('\n').count() + 1;
+        let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+        ParseError::new(format!("{}:{}: {}", line, column, message))
+    }
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '-' || c == '_'
+}
+
+/// The connection for argument `import` satisfied by `value`.
+fn connection(import: String, value: Item) -> InterfaceConnection {
+    let (source_instance, source_export) = match value {
+        Item::Instance(id) => (Some(id), None),
+        Item::Export(id, export) => (Some(id), Some(export)),
+        Item::Import(name) => (None, Some(name)),
+    };
+    InterfaceConnection {
+        interface_name: import,
+        source_instance,
+        is_host_import: source_instance.is_none(),
+        interface_type: None,
+        fingerprint: None,
+        source_export,
+        kind: ExternKind::Instance,
+        alias_chain: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROXY: &str = r#"
+        package example:composition;
+
+        // The host provides logging
+        import log: wasi:logging/logging@0.1.0;
+
+        interface unused {
+            record r { a: u32 }
+            f: func(a: list<u8>) -> result<u32>;
+        }
+
+        let svc = new example:service@1.2.0 { log, ... };
+        let mw = new example:middleware {
+            "wasi:http/handler@0.3.0": svc["wasi:http/handler@0.3.0"],
+            inner: svc.handler, /* renamed plug */
+            log: log,
+        };
+        export mw["wasi:http/handler@0.3.0"];
+        export mw.admin as "admin-api";
+    "#;
+
+    #[test]
+    fn test_instances_and_wiring() {
+        let graph = parse_wac_str(PROXY).unwrap();
+        assert_eq!(graph.nodes.len(), 2);
+
+        let svc = &graph.nodes[&0];
+        assert_eq!(svc.display_label(), "svc");
+        assert_eq!(svc.package_id().as_deref(), Some("example:service@1.2.0"));
+        assert_eq!(svc.imports.len(), 1);
+        assert!(svc.imports[0].is_host_import);
+        assert_eq!(
+            svc.imports[0].source_export.as_deref(),
+            Some("wasi:logging/logging@0.1.0")
+        );
+
+        let mw = &graph.nodes[&1];
+        let inner = mw
+            .imports
+            .iter()
+            .find(|c| c.interface_name == "inner")
+            .unwrap();
+        assert_eq!(inner.source_instance, Some(0));
+        assert!(inner.is_renamed());
+        assert_eq!(mw.imports.iter().filter(|c| c.is_host_import).count(), 1);
+    }
+
+    #[test]
+    fn test_exports() {
+        let graph = parse_wac_str(PROXY).unwrap();
+        let exports: Vec<(&str, u32)> = graph
+            .component_exports
+            .iter()
+            .map(|e| (e.name.as_str(), e.source_instance))
+            .collect();
+        assert_eq!(
+            exports,
+            vec![("admin-api", 1), ("wasi:http/handler@0.3.0", 1)]
+        );
+    }
+
+    #[test]
+    fn test_errors_point_at_source() {
+        let err = parse_wac_str("let a = new x:y { b: nope };").err().unwrap();
+        assert_eq!(err.to_string(), "1:26: undefined name `nope`");
+
+        let err = parse_wac_str("let a = new x:y {}\nexport a;")
+            .err()
+            .unwrap();
+        assert!(err.to_string().starts_with("2:1: expected `;`"), "{}", err);
+    }
+}
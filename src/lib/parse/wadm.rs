@@ -0,0 +1,275 @@
+//! Build a [`CompositionGraph`] from a wasmCloud wadm application manifest.
+//!
+//! Every entry under `spec.components`, whether a component or a
+//! capability provider, becomes an instance named after it.  A `link` trait
+//! says that its source invokes interfaces on a target at runtime, so links
+//! from components become imports from the target.  A capability provider
+//! linking into a component is how the host calls into the application
+//! (an HTTP server invoking `wasi:http/incoming-handler`, say), so those
+//! links become the application's exports.
+
+use crate::error::{ParseError, Result};
+use crate::model::{
+    naming, ComponentMetadata, ComponentNode, CompositionGraph, ExternKind, InterfaceConnection,
+};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// The parts of a wadm manifest that shape the graph.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Manifest {
+    metadata: ManifestMetadata,
+    spec: Spec,
+}
+
+#[derive(Debug, Deserialize)]
+struct ManifestMetadata {
+    name: String,
+    #[serde(default)]
+    annotations: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Spec {
+    #[serde(default)]
+    components: Vec<Component>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Component {
+    name: String,
+    /// `component` or `capability`.
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    traits: Vec<Trait>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Trait {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    properties: serde_yaml::Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct Link {
+    target: Target,
+    namespace: String,
+    package: String,
+    #[serde(default)]
+    interfaces: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Target {
+    Name(String),
+    Detailed { name: String },
+}
+
+impl Target {
+    fn name(&self) -> &str {
+        match self {
+            Target::Name(name) | Target::Detailed { name } => name,
+        }
+    }
+}
+
+/// Whether `yaml` looks like a wadm manifest (an OAM `Application`) rather
+/// than some other YAML document.
+pub fn is_manifest(yaml: &str) -> bool {
+    serde_yaml::from_str::<serde_yaml::Value>(yaml).is_ok_and(|doc| {
+        doc.get("apiVersion").is_some()
+            && doc.get("kind").and_then(|k| k.as_str()) == Some("Application")
+    })
+}
+
+impl Manifest {
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        serde_yaml::from_str(yaml)
+            .map_err(|e| ParseError::caused_by("Invalid wadm manifest", e).into())
+    }
+
+    /// Build the graph of the application's components and links.
+    pub fn to_graph(&self) -> Result<CompositionGraph> {
+        let mut ids = BTreeMap::new();
+        for (id, component) in (0..).zip(&self.spec.components) {
+            if ids.insert(component.name.as_str(), id).is_some() {
+                return Err(ParseError::new(format!(
+                    "component `{}` is defined more than once",
+                    component.name
+                ))
+                .into());
+            }
+        }
+
+        let mut graph = CompositionGraph::new();
+        for (id, component) in (0..).zip(&self.spec.components) {
+            let mut node = ComponentNode::new(format!("${}", component.name), id, id);
+            for link in component.links()? {
+                let target = *ids.get(link.target.name()).ok_or_else(|| {
+                    ParseError::new(format!(
+                        "`{}` links to unknown component `{}`",
+                        component.name,
+                        link.target.name()
+                    ))
+                })?;
+                for interface in &link.interfaces {
+                    let name = format!("{}:{}/{}", link.namespace, link.package, interface);
+                    if component.kind == "capability" {
+                        graph.add_export(name, target, None);
+                    } else {
+                        node.add_import(InterfaceConnection {
+                            interface_name: name,
+                            source_instance: Some(target),
+                            is_host_import: false,
+                            interface_type: None,
+                            fingerprint: None,
+                            source_export: None,
+                            kind: ExternKind::Instance,
+                            alias_chain: Vec::new(),
+                        });
+                    }
+                }
+            }
+            graph.add_node(id, node);
+        }
+
+        let annotation = |key: &str| self.metadata.annotations.get(key).cloned();
+        graph.metadata.insert(
+            None,
+            ComponentMetadata {
+                name: Some(self.metadata.name.clone()),
+                description: annotation("description"),
+                version: annotation("version"),
+                ..ComponentMetadata::default()
+            },
+        );
+
+        graph.validate()?;
+        naming::resolve_labels(&mut graph, &naming::default_resolvers());
+        Ok(graph)
+    }
+}
+
+impl Component {
+    /// The component's `link` traits.  Other traits (scalers, config) do not
+    /// affect the graph.
+    fn links(&self) -> Result<Vec<Link>> {
+        self.traits
+            .iter()
+            .filter(|t| t.kind == "link")
+            .map(|t| {
+                serde_yaml::from_value(t.properties.clone()).map_err(|e| {
+                    ParseError::caused_by(format!("Invalid link on `{}`", self.name), e).into()
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"
+apiVersion: core.oam.dev/v1beta1
+kind: Application
+metadata:
+  name: hello-kv
+  annotations:
+    version: v0.1.0
+spec:
+  components:
+    - name: hello
+      type: component
+      properties:
+        image: ghcr.io/example/hello:0.1.0
+      traits:
+        - type: spreadscaler
+          properties:
+            instances: 1
+        - type: link
+          properties:
+            target:
+              name: kvredis
+            namespace: wasi
+            package: keyvalue
+            interfaces: [atomics, store]
+    - name: kvredis
+      type: capability
+      properties:
+        image: ghcr.io/wasmcloud/keyvalue-redis:0.28.1
+    - name: httpserver
+      type: capability
+      properties:
+        image: ghcr.io/wasmcloud/http-server:0.23.0
+      traits:
+        - type: link
+          properties:
+            target: hello
+            namespace: wasi
+            package: http
+            interfaces: [incoming-handler]
+"#;
+
+    #[test]
+    fn test_components_and_links() {
+        assert!(is_manifest(MANIFEST));
+        let graph = Manifest::from_yaml(MANIFEST).unwrap().to_graph().unwrap();
+
+        let names: Vec<&str> = graph.nodes.values().map(|n| n.display_label()).collect();
+        assert_eq!(names, vec!["hello", "kvredis", "httpserver"]);
+
+        let hello = &graph.nodes[&0];
+        let imports: Vec<(&str, Option<u32>)> = hello
+            .imports
+            .iter()
+            .map(|c| (c.interface_name.as_str(), c.source_instance))
+            .collect();
+        assert_eq!(
+            imports,
+            vec![
+                ("wasi:keyvalue/atomics", Some(1)),
+                ("wasi:keyvalue/store", Some(1))
+            ]
+        );
+
+        // the provider invoking `hello` is the application's entry point
+        assert_eq!(
+            graph
+                .export("wasi:http/incoming-handler")
+                .unwrap()
+                .source_instance,
+            0
+        );
+        assert!(graph.nodes[&2].imports.is_empty());
+        assert_eq!(graph.metadata[&None].version.as_deref(), Some("v0.1.0"));
+    }
+
+    #[test]
+    fn test_unknown_target() {
+        let manifest = MANIFEST.replace(
+            "name: kvredis\n            namespace",
+            "name: redis\n            namespace",
+        );
+        let err = Manifest::from_yaml(&manifest)
+            .unwrap()
+            .to_graph()
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "`hello` links to unknown component `redis`"
+        );
+    }
+
+    #[test]
+    fn test_not_a_manifest() {
+        assert!(!is_manifest("instantiations:\n  a: {}\n"));
+        assert!(!is_manifest("{ not yaml"));
+    }
+}
@@ -9,10 +9,10 @@
 /// Or accept all pending snapshots without interactive review:
 ///
 ///   cargo insta accept
-#[cfg(test)]
+#[cfg(all(test, feature = "ascii", feature = "mermaid"))]
 mod tests {
     use crate::model::CompositionGraph;
-    use crate::output::{ascii, mermaid, DetailLevel, Direction};
+    use crate::output::{ascii, mermaid, DetailLevel, Direction, RenderOptions};
     use crate::test_utils::*;
 
     fn ascii_snap(graph: &CompositionGraph, detail: DetailLevel, show_types: bool) -> String {
@@ -442,4 +442,42 @@ mod tests {
             true
         ));
     }
+
+    // -----------------------------------------------------------------------
+    // dependency views (--root)
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn long_chain_ascii_dependencies() {
+        let graph = long_chain_graph();
+        let root = graph.find_node("gateway").unwrap();
+        insta::assert_snapshot!(ascii::generate_dependency_ascii(
+            &graph,
+            root,
+            &RenderOptions::default()
+        ));
+    }
+
+    #[test]
+    fn diamond_ascii_dependencies() {
+        let graph = diamond_graph();
+        let root = graph.find_node("app").unwrap();
+        insta::assert_snapshot!(ascii::generate_dependency_ascii(
+            &graph,
+            root,
+            &RenderOptions::default()
+        ));
+    }
+
+    #[test]
+    fn diamond_mermaid_dependencies() {
+        let graph = diamond_graph();
+        let root = graph.find_node("app").unwrap();
+        insta::assert_snapshot!(mermaid::generate_dependency_mermaid(
+            &graph,
+            root,
+            Direction::LeftToRight,
+            false
+        ));
+    }
 }
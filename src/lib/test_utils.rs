@@ -11,8 +11,8 @@
 ///
 /// All builders use consecutive indices starting at 1 for real components.
 use crate::model::{
-    ComponentNode, CompositionGraph, FuncSignature, InstanceInterface, InterfaceConnection,
-    InterfaceType, ValueType,
+    ComponentNode, CompositionGraph, ExternKind, FuncSignature, InstanceInterface,
+    InterfaceConnection, InterfaceType, ValueType,
 };
 use std::collections::BTreeMap;
 
@@ -33,6 +33,9 @@ pub(crate) fn simple_chain_graph() -> CompositionGraph {
         is_host_import: true,
         interface_type: None,
         fingerprint: None,
+        source_export: None,
+        kind: ExternKind::Instance,
+        alias_chain: Vec::new(),
     });
     graph.add_node(1, srv);
 
@@ -43,6 +46,9 @@ pub(crate) fn simple_chain_graph() -> CompositionGraph {
         is_host_import: false,
         interface_type: None,
         fingerprint: None,
+        source_export: None,
+        kind: ExternKind::Instance,
+        alias_chain: Vec::new(),
     });
     mw.add_import(InterfaceConnection {
         interface_name: "wasi:logging/log@0.1.0".to_string(),
@@ -50,6 +56,9 @@ pub(crate) fn simple_chain_graph() -> CompositionGraph {
         is_host_import: true,
         interface_type: None,
         fingerprint: None,
+        source_export: None,
+        kind: ExternKind::Instance,
+        alias_chain: Vec::new(),
     });
     graph.add_node(2, mw);
 
@@ -79,6 +88,9 @@ pub(crate) fn two_chain_graph() -> CompositionGraph {
         is_host_import: true,
         interface_type: None,
         fingerprint: None,
+        source_export: None,
+        kind: ExternKind::Instance,
+        alias_chain: Vec::new(),
     });
     graph.add_node(1, srv_http);
 
@@ -89,6 +101,9 @@ pub(crate) fn two_chain_graph() -> CompositionGraph {
         is_host_import: false,
         interface_type: None,
         fingerprint: None,
+        source_export: None,
+        kind: ExternKind::Instance,
+        alias_chain: Vec::new(),
     });
     graph.add_node(2, mw_http);
 
@@ -102,6 +117,9 @@ pub(crate) fn two_chain_graph() -> CompositionGraph {
         is_host_import: true,
         interface_type: None,
         fingerprint: None,
+        source_export: None,
+        kind: ExternKind::Instance,
+        alias_chain: Vec::new(),
     });
     graph.add_node(3, db);
 
@@ -112,6 +130,9 @@ pub(crate) fn two_chain_graph() -> CompositionGraph {
         is_host_import: false,
         interface_type: None,
         fingerprint: None,
+        source_export: None,
+        kind: ExternKind::Instance,
+        alias_chain: Vec::new(),
     });
     graph.add_node(4, cache);
 
@@ -141,6 +162,9 @@ pub(crate) fn long_chain_graph() -> CompositionGraph {
         is_host_import: true,
         interface_type: None,
         fingerprint: None,
+        source_export: None,
+        kind: ExternKind::Instance,
+        alias_chain: Vec::new(),
     });
     graph.add_node(1, backend);
 
@@ -151,6 +175,9 @@ pub(crate) fn long_chain_graph() -> CompositionGraph {
         is_host_import: false,
         interface_type: None,
         fingerprint: None,
+        source_export: None,
+        kind: ExternKind::Instance,
+        alias_chain: Vec::new(),
     });
     graph.add_node(2, service);
 
@@ -161,6 +188,9 @@ pub(crate) fn long_chain_graph() -> CompositionGraph {
         is_host_import: false,
         interface_type: None,
         fingerprint: None,
+        source_export: None,
+        kind: ExternKind::Instance,
+        alias_chain: Vec::new(),
     });
     graph.add_node(3, gateway);
 
@@ -189,6 +219,9 @@ pub(crate) fn chain_plus_utility_graph() -> CompositionGraph {
         is_host_import: true,
         interface_type: None,
         fingerprint: None,
+        source_export: None,
+        kind: ExternKind::Instance,
+        alias_chain: Vec::new(),
     });
     graph.add_node(1, srv);
 
@@ -199,6 +232,9 @@ pub(crate) fn chain_plus_utility_graph() -> CompositionGraph {
         is_host_import: false,
         interface_type: None,
         fingerprint: None,
+        source_export: None,
+        kind: ExternKind::Instance,
+        alias_chain: Vec::new(),
     });
     graph.add_node(2, mw);
 
@@ -209,6 +245,9 @@ pub(crate) fn chain_plus_utility_graph() -> CompositionGraph {
         is_host_import: true,
         interface_type: None,
         fingerprint: None,
+        source_export: None,
+        kind: ExternKind::Instance,
+        alias_chain: Vec::new(),
     });
     graph.add_node(3, logger);
 
@@ -248,6 +287,9 @@ pub(crate) fn typed_chain_graph() -> CompositionGraph {
         is_host_import: true,
         interface_type: Some(iface_type.clone()),
         fingerprint: Some(iface_type.fingerprint(&graph.arena)),
+        source_export: None,
+        kind: ExternKind::Instance,
+        alias_chain: Vec::new(),
     });
     graph.add_node(1, srv);
 
@@ -258,6 +300,9 @@ pub(crate) fn typed_chain_graph() -> CompositionGraph {
         is_host_import: false,
         interface_type: Some(iface_type.clone()),
         fingerprint: Some(iface_type.fingerprint(&graph.arena)),
+        source_export: None,
+        kind: ExternKind::Instance,
+        alias_chain: Vec::new(),
     });
     graph.add_node(2, mw);
 
@@ -312,6 +357,9 @@ pub(crate) fn two_typed_chain_graph() -> CompositionGraph {
         is_host_import: true,
         interface_type: Some(handler_type.clone()),
         fingerprint: Some(handler_type.fingerprint(&graph.arena)),
+        source_export: None,
+        kind: ExternKind::Instance,
+        alias_chain: Vec::new(),
     });
     graph.add_node(1, srv_http);
 
@@ -322,6 +370,9 @@ pub(crate) fn two_typed_chain_graph() -> CompositionGraph {
         is_host_import: false,
         interface_type: Some(handler_type.clone()),
         fingerprint: Some(handler_type.fingerprint(&graph.arena)),
+        source_export: None,
+        kind: ExternKind::Instance,
+        alias_chain: Vec::new(),
     });
     graph.add_node(2, mw_http);
 
@@ -335,6 +386,9 @@ pub(crate) fn two_typed_chain_graph() -> CompositionGraph {
         is_host_import: true,
         interface_type: Some(kv_type.clone()),
         fingerprint: Some(kv_type.fingerprint(&graph.arena)),
+        source_export: None,
+        kind: ExternKind::Instance,
+        alias_chain: Vec::new(),
     });
     graph.add_node(3, db);
 
@@ -345,6 +399,9 @@ pub(crate) fn two_typed_chain_graph() -> CompositionGraph {
         is_host_import: false,
         interface_type: Some(kv_type.clone()),
         fingerprint: Some(kv_type.fingerprint(&graph.arena)),
+        source_export: None,
+        kind: ExternKind::Instance,
+        alias_chain: Vec::new(),
     });
     graph.add_node(4, cache);
 
@@ -387,6 +444,9 @@ pub(crate) fn shim_export_direct_graph() -> CompositionGraph {
         is_host_import: false,
         interface_type: None,
         fingerprint: None,
+        source_export: None,
+        kind: ExternKind::Instance,
+        alias_chain: Vec::new(),
     });
     graph.add_node(2, consumer);
 
@@ -419,6 +479,9 @@ pub(crate) fn shim_export_one_middleware_graph() -> CompositionGraph {
         is_host_import: false,
         interface_type: None,
         fingerprint: None,
+        source_export: None,
+        kind: ExternKind::Instance,
+        alias_chain: Vec::new(),
     });
     graph.add_node(2, middleware);
 
@@ -429,6 +492,9 @@ pub(crate) fn shim_export_one_middleware_graph() -> CompositionGraph {
         is_host_import: false,
         interface_type: None,
         fingerprint: None,
+        source_export: None,
+        kind: ExternKind::Instance,
+        alias_chain: Vec::new(),
     });
     graph.add_node(3, consumer);
 
@@ -463,6 +529,9 @@ pub(crate) fn shim_export_three_middleware_graph() -> CompositionGraph {
         is_host_import: false,
         interface_type: None,
         fingerprint: None,
+        source_export: None,
+        kind: ExternKind::Instance,
+        alias_chain: Vec::new(),
     });
     graph.add_node(2, mdl_c);
 
@@ -473,6 +542,9 @@ pub(crate) fn shim_export_three_middleware_graph() -> CompositionGraph {
         is_host_import: false,
         interface_type: None,
         fingerprint: None,
+        source_export: None,
+        kind: ExternKind::Instance,
+        alias_chain: Vec::new(),
     });
     graph.add_node(3, mdl_b);
 
@@ -483,6 +555,9 @@ pub(crate) fn shim_export_three_middleware_graph() -> CompositionGraph {
         is_host_import: false,
         interface_type: None,
         fingerprint: None,
+        source_export: None,
+        kind: ExternKind::Instance,
+        alias_chain: Vec::new(),
     });
     graph.add_node(4, mdl_a);
 
@@ -493,6 +568,9 @@ pub(crate) fn shim_export_three_middleware_graph() -> CompositionGraph {
         is_host_import: false,
         interface_type: None,
         fingerprint: None,
+        source_export: None,
+        kind: ExternKind::Instance,
+        alias_chain: Vec::new(),
     });
     graph.add_node(5, consumer);
 
@@ -502,3 +580,61 @@ pub(crate) fn shim_export_three_middleware_graph() -> CompositionGraph {
     graph.add_export("test:svc/api@1.0.0".to_string(), 6, None);
     graph
 }
+
+// ---------------------------------------------------------------------------
+// Diamond: $app imports from $left and $right, which both import from $base
+// (which imports log from the host).  No exports.
+// ---------------------------------------------------------------------------
+
+pub(crate) fn diamond_graph() -> CompositionGraph {
+    let mut graph = CompositionGraph::new();
+
+    let conn = |name: &str, source: Option<u32>| InterfaceConnection {
+        interface_name: name.to_string(),
+        source_instance: source,
+        is_host_import: source.is_none(),
+        interface_type: None,
+        fingerprint: None,
+        source_export: None,
+        kind: ExternKind::Instance,
+        alias_chain: Vec::new(),
+    };
+
+    let mut base = ComponentNode::new("$base".to_string(), 0, 0);
+    base.add_import(conn("wasi:logging/log@0.1.0", None));
+    graph.add_node(1, base);
+
+    let mut left = ComponentNode::new("$left".to_string(), 1, 1);
+    left.add_import(conn("test:base/store@1.0.0", Some(1)));
+    graph.add_node(2, left);
+
+    let mut right = ComponentNode::new("$right".to_string(), 2, 2);
+    right.add_import(conn("test:base/store@1.0.0", Some(1)));
+    graph.add_node(3, right);
+
+    let mut app = ComponentNode::new("$app".to_string(), 3, 3);
+    app.add_import(conn("test:side/left@1.0.0", Some(2)));
+    app.add_import(conn("test:side/right@1.0.0", Some(3)));
+    graph.add_node(4, app);
+
+    graph
+}
+
+// ---------------------------------------------------------------------------
+// Orphan: an instance with a host import that nothing depends on
+// ---------------------------------------------------------------------------
+
+pub(crate) fn orphan_node() -> ComponentNode {
+    let mut node = ComponentNode::new("$orphan".to_string(), 7, 7);
+    node.add_import(InterfaceConnection {
+        interface_name: "wasi:logging/log@0.1.0".to_string(),
+        source_instance: None,
+        is_host_import: true,
+        interface_type: None,
+        fingerprint: None,
+        source_export: None,
+        kind: ExternKind::Instance,
+        alias_chain: Vec::new(),
+    });
+    node
+}
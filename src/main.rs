@@ -1,40 +1,922 @@
-use std::path::PathBuf;
+mod fetch;
+mod progress;
+mod server;
+
+use std::path::{Path, PathBuf};
+
+use std::io::{IsTerminal, Read, Write};
 
 use anyhow::{Context, Result};
-use clap::Parser;
+use clap::builder::{PossibleValue, PossibleValuesParser, TypedValueParser};
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+use cviz::analysis::audit::{audit, AllowList};
+use cviz::analysis::capabilities::capability_matrix;
+use cviz::analysis::diagnostics::{diagnose, Category, Diagnostic, LintLevel, LintLevels};
+use cviz::analysis::diff::diff_graphs;
+use cviz::analysis::exports::{export_summaries, exports_table};
+use cviz::analysis::health::{health_score, HealthWeights};
+use cviz::analysis::inspect::inspect;
+use cviz::analysis::instances::{instance_summaries, instances_table};
+use cviz::analysis::interfaces::{interface_uses, interfaces_table};
+use cviz::analysis::order::{instantiation_order, order_text};
+use cviz::analysis::paths::{connection_paths, PathTarget};
+use cviz::analysis::reachability::reachable_from_export;
+use cviz::analysis::renames::{plugs_table, renamed_plugs};
+use cviz::analysis::rules::RuleSet;
+use cviz::analysis::sbom::{sbom, SbomOptions};
+use cviz::analysis::sizes::{size_ranking, sizes_table};
+use cviz::analysis::stats::graph_stats;
+use cviz::analysis::topology::Topology;
+use cviz::analysis::why::explain;
+use cviz::model::filter::glob_match;
+use cviz::model::labels::{LabelStyle, Rewrite};
+use cviz::model::naming::{self, LabelOverrides, NameMap, NameResolver};
+use cviz::model::CompositionGraph;
 use cviz::output;
-use cviz::output::{DetailLevel, Direction, OutputFormat};
+use cviz::output::links::{supports_hyperlinks, LinkTemplates};
+use cviz::output::{DetailLevel, Direction, OutputFormat, RenderOptions};
+use cviz::parse::annotation::{read_annotations, write_annotations, Annotations};
+use cviz::parse::component::{parse_component_with_options, ParseOptions};
+use cviz::parse::module::NotAComponent;
+use cviz::{chain_interfaces_matching, exported_interfaces, find_chain_interfaces, CvizError};
+use tracing::{error, info, warn, Level};
 
 #[derive(Parser, Debug)]
 #[command(name = "cviz")]
 #[command(about = "Visualize WebAssembly component composition")]
 #[command(version = concat!(env!("CARGO_PKG_VERSION"), " (", env!("CVIZ_GIT_SHA"), ") with wasmparser ", env!("WASMPARSER_VERSION")))]
+#[command(args_conflicts_with_subcommands = true)]
 struct Args {
-    /// Path to the .wasm component file
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Drawing options, for `cviz <FILE>` without the `graph` subcommand
+    #[command(flatten)]
+    graph: GraphArgs,
+
+    /// Print what cviz is doing on stderr: -v for debug events such as the
+    /// sections, instances and aliases the parser sees, -vv for every
+    /// connection too
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    verbose: u8,
+
+    /// Print only errors: no progress, notes or diagnostic warnings
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// When to color log messages on stderr
+    #[arg(
+        long,
+        value_name = "WHEN",
+        env = "CVIZ_COLOR",
+        default_value = "auto",
+        global = true
+    )]
+    color: clap::ColorChoice,
+
+    /// Largest component to download from a URL or registry, in bytes
+    #[arg(long, value_name = "BYTES", env = "CVIZ_MAX_DOWNLOAD_SIZE", default_value_t = fetch::DEFAULT_MAX_SIZE, global = true)]
+    max_download_size: u64,
+
+    /// Root component (`$input`) for a wasm-compose config given as FILE
+    #[arg(long, value_name = "FILE", global = true)]
+    compose_input: Option<PathBuf>,
+
+    /// What the inputs are, instead of guessing from the file extension
+    /// (or, for stdin and URLs, the wasm magic number)
+    #[arg(long, value_name = "FORMAT", default_value = "auto", global = true, value_parser = one_of(["auto", "wasm", "json", "wac", "wadm", "compose"], parse_input_format))]
+    input_format: InputFormat,
+
+    /// Fail on references the parser cannot follow (alias loops, unknown
+    /// instances, exports of missing instances) instead of skipping them
+    #[arg(long, global = true)]
+    strict: bool,
+
+    /// Expand instances of nested components only this many levels deep;
+    /// deeper inner compositions collapse into the instance containing them
+    /// (0 shows only the root component's instances)
+    #[arg(long, value_name = "N", global = true)]
+    depth: Option<u32>,
+
+    /// Merge instances of the same component created by the root component
+    /// into one node, annotated with the number of copies
+    #[arg(long, global = true)]
+    collapse_duplicates: bool,
+
+    /// Keep only connections whose interface matches this glob, e.g.
+    /// `wasi:keyvalue/*`, dropping instances left unconnected (repeatable)
+    #[arg(long, value_name = "GLOB", global = true)]
+    only_interface: Vec<String>,
+
+    /// Drop connections whose interface matches this glob, and instances left
+    /// unconnected (repeatable)
+    #[arg(long, value_name = "GLOB", global = true)]
+    exclude_interface: Vec<String>,
+
+    /// Keep only instances whose name matches this glob, e.g. `mdl-*`
+    /// (repeatable)
+    #[arg(long, value_name = "GLOB", global = true)]
+    only_node: Vec<String>,
+
+    /// Hide instances whose name matches this glob, along with their
+    /// connections (repeatable)
+    #[arg(long, value_name = "GLOB", global = true)]
+    exclude_node: Vec<String>,
+
+    /// Report a diagnostic category as an error and fail the run
+    #[arg(long, value_name = "CATEGORY", value_parser = one_of(Category::ALL.map(Category::name), parse_category), global = true)]
+    deny: Vec<Category>,
+
+    /// Report a diagnostic category as a warning (the default)
+    #[arg(long, value_name = "CATEGORY", value_parser = one_of(Category::ALL.map(Category::name), parse_category), global = true)]
+    warn: Vec<Category>,
+
+    /// Silence a diagnostic category
+    #[arg(long, value_name = "CATEGORY", value_parser = one_of(Category::ALL.map(Category::name), parse_category), global = true)]
+    allow: Vec<Category>,
+
+    /// Report every diagnostic that would be a warning as an error, and parse
+    /// strictly (as with --strict), so that only a clean composition passes
+    #[arg(long, global = true)]
+    deny_warnings: bool,
+
+    /// JSON file of per-category lint levels, e.g. {"cycles": "deny"}
+    /// (command-line flags take precedence)
+    #[arg(long, value_name = "FILE", env = "CVIZ_LINTS", global = true)]
+    lints: Option<PathBuf>,
+}
+
+/// The arguments of `cviz graph`, which `cviz <FILE>` takes too.
+#[derive(clap::Args, Debug)]
+struct GraphArgs {
+    /// Path to the .wasm component file, an http(s):// or oci:// URL, or `-`
+    /// for stdin (the default when stdin is not a terminal).  Several files,
+    /// or a directory, are drawn one by one into --output-dir or together
+    /// with --combine
     #[arg(value_name = "FILE")]
-    file: PathBuf,
+    files: Vec<PathBuf>,
+
+    /// Inputs to draw from a directory given as FILE
+    #[arg(long, value_name = "GLOB", default_value = "*.wasm")]
+    glob: String,
+
+    /// Write one output per input into this directory
+    #[arg(long, value_name = "DIR", conflicts_with = "output")]
+    output_dir: Option<PathBuf>,
+
+    /// Draw several inputs into one document with a section per input
+    /// (titled blocks for ascii, Markdown for mermaid, an array for json)
+    #[arg(long, conflicts_with_all = ["output_dir", "open"])]
+    combine: bool,
+
+    /// Name of each file written to --output-dir (placeholders: {stem} for the
+    /// input's file name without extension, {name} for the whole file name,
+    /// {ext} for txt, mmd or json depending on --format)
+    #[arg(
+        long,
+        value_name = "TEMPLATE",
+        default_value = "{stem}.{ext}",
+        requires = "output_dir"
+    )]
+    output_name: String,
+
+    /// Treat FILE as a package name (`namespace:name[@version]`) published to
+    /// this registry domain, e.g. `wasi.dev`
+    #[arg(long, value_name = "DOMAIN")]
+    registry: Option<String>,
 
     /// Output format
-    #[arg(short, long, default_value = "ascii", value_parser = parse_format)]
+    #[arg(short, long, env = "CVIZ_FORMAT", default_value = "ascii", value_parser = one_of(["ascii", "mermaid", "json", "json-pretty"], parse_format))]
     format: OutputFormat,
 
     /// Diagram direction (mermaid only)
-    #[arg(short, long, default_value = "lr", value_parser = parse_direction)]
+    #[arg(short, long, env = "CVIZ_DIRECTION", default_value = "lr", value_parser = one_of(["lr", "td"], parse_direction))]
     direction: Direction,
 
     /// Detail level
-    #[arg(short = 'l', long, default_value = "handler-chain", value_parser = parse_detail)]
+    #[arg(short = 'l', long, env = "CVIZ_DETAIL", default_value = "handler-chain", value_parser = one_of(["handler-chain", "chains", "all-interfaces", "full"], parse_detail))]
     detail: DetailLevel,
 
     /// Show WIT type information on interface connections
     #[arg(short = 't', long, default_value = "true")]
     types: bool,
 
-    /// Output file (stdout if not specified)
+    /// Draw the chains of exported interfaces whose name contains NAME, e.g.
+    /// `wasi:messaging`, instead of every middleware chain (handler-chain
+    /// detail level)
+    #[arg(long, value_name = "NAME")]
+    interface: Option<String>,
+
+    /// Fail unless the composition exports a middleware chain, or, given
+    /// `--require-chain=NAME`, a chain whose interface name contains NAME
+    #[arg(long, value_name = "NAME", num_args = 0..=1, require_equals = true, default_missing_value = "")]
+    require_chain: Option<String>,
+
+    /// Render the dependency tree of this instance (name or ID) instead of the
+    /// export-rooted view (ascii and mermaid only)
+    #[arg(long, value_name = "INSTANCE")]
+    root: Option<String>,
+
+    /// JSON file mapping unnamed instances (by fallback name such as
+    /// `instance_7`, or by package) to display labels
+    #[arg(long, value_name = "FILE")]
+    names: Option<PathBuf>,
+
+    /// TOML file mapping instance names, stable IDs or component digests to
+    /// display labels, overriding the names embedded in the binary
+    #[arg(long, value_name = "FILE")]
+    labels: Option<PathBuf>,
+
+    /// Output file (stdout if not specified or `-`)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Preview the diagram in the default browser (mermaid only), or print a
+    /// mermaid.live link if no browser can be started
+    #[arg(long)]
+    open: bool,
+
+    /// URL template for interface hyperlinks in terminal output
+    /// (placeholders: {interface}, {namespace}, {package}, {name}, {version})
+    #[arg(long, value_name = "TEMPLATE", env = "CVIZ_INTERFACE_URL")]
+    interface_url: Option<String>,
+
+    /// URL template for instance hyperlinks in terminal output
+    /// (placeholders: {name}, {package}, {version})
+    #[arg(long, value_name = "TEMPLATE", env = "CVIZ_NODE_URL")]
+    node_url: Option<String>,
+
+    /// When to emit hyperlinks for --interface-url/--node-url
+    #[arg(long, value_name = "WHEN", env = "CVIZ_HYPERLINKS", default_value = "auto", value_parser = one_of(["auto", "always", "never"], parse_hyperlinks))]
+    hyperlinks: Hyperlinks,
+
+    /// Append a legend mapping short interface labels to their full names (ascii only)
+    #[arg(long)]
+    legend: bool,
+
+    /// Draw `wasi:` host imports as one node per package (`wasi:http`,
+    /// `wasi:io`, ...) instead of one per interface (all-interfaces view)
+    #[arg(long)]
+    group_host: bool,
+
+    /// Leave out host imports and the dashed edges from them, for an
+    /// application-only view (all-interfaces view and --root)
+    #[arg(long)]
+    no_host: bool,
+
+    /// Draw synthetic instances, such as interface bundles wrapping host
+    /// imports, in the handler-chain, chains and all-interfaces views
+    #[arg(long)]
+    show_synthetic: bool,
+
+    /// Cluster instances by package namespace, or by name prefix such as
+    /// `mdl` in `mdl-a` (mermaid all-interfaces and full views)
+    #[arg(long)]
+    group_namespaces: bool,
+
+    /// Keep the `@version` suffix in short interface labels, e.g.
+    /// `handler@0.3.0`, to make version skew visible
+    #[arg(long)]
+    show_versions: bool,
+
+    /// Keep the namespace and package in short interface labels, e.g.
+    /// `wasi:http/handler`
+    #[arg(long)]
+    keep_namespace: bool,
+
+    /// Keep the package in short interface labels, e.g. `http/handler`
+    #[arg(long)]
+    keep_package: bool,
+
+    /// Label interfaces matching a regular expression with the match
+    /// replaced, e.g. `^acme:platform/=p/`; capture groups are available as
+    /// `$1` or `${name}` (repeatable, first match wins)
+    #[arg(long, value_name = "REGEX=REPLACEMENT")]
+    label_rewrite: Vec<Rewrite>,
+
+    /// TOML file with the interface label settings above, for sharing them
+    /// across a team
+    #[arg(long, value_name = "FILE")]
+    label_style: Option<PathBuf>,
+
+    /// Deprecated alias of `cviz health`
+    #[arg(long, hide = true, value_name = "FORMAT", group = "scored", value_parser = report_format([ReportFormat::Text, ReportFormat::Markdown, ReportFormat::Json]))]
+    health: Option<ReportFormat>,
+
+    /// Weights for the deprecated `--health` and `--stats`
+    #[arg(long, hide = true, value_name = "FILE", requires = "scored")]
+    health_weights: Option<PathBuf>,
+
+    /// Deprecated alias of `cviz stats`
+    #[arg(long, hide = true, value_name = "FORMAT", group = "scored", value_parser = report_format([ReportFormat::Text, ReportFormat::Markdown, ReportFormat::Json]))]
+    stats: Option<ReportFormat>,
+
+    /// Deprecated alias of `cviz list order`
+    #[arg(long, hide = true, value_name = "FORMAT", value_parser = report_format([ReportFormat::Text, ReportFormat::Json]))]
+    order: Option<ReportFormat>,
+
+    /// Deprecated alias of `cviz reachable`, in the ascii or json format
+    #[arg(long, hide = true, value_name = "EXPORT")]
+    reachable: Option<String>,
+
+    /// Deprecated alias of `cviz capabilities`
+    #[arg(long, hide = true, value_name = "FORMAT", value_parser = report_format([ReportFormat::Text, ReportFormat::Csv, ReportFormat::Json]))]
+    capabilities: Option<ReportFormat>,
+
+    /// Deprecated alias of `cviz list renames`
+    #[arg(long, hide = true, value_name = "FORMAT", value_parser = report_format([ReportFormat::Text, ReportFormat::Json]))]
+    renames: Option<ReportFormat>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Draw a diagram of a composition (what `cviz <FILE>` does)
+    Graph(Box<GraphArgs>),
+    /// Print counts of instances, connections, host interfaces and exports,
+    /// the longest chain, per-instance fan-in/fan-out and the health score
+    Stats(StatsArgs),
+    /// Print the composition health score and the factors that lowered it
+    Health(HealthArgs),
+    /// List the host interfaces transitively reachable from an export
+    Reachable(ReachableArgs),
+    /// Print a matrix of which instance can reach which host interface,
+    /// directly or transitively
+    Capabilities(CapabilitiesArgs),
+    /// List one kind of item in a composition
+    List {
+        #[command(subcommand)]
+        list: ListCommand,
+    },
+    /// Compare two compositions (.wasm components or JSON graphs)
+    Diff(DiffArgs),
+    /// Check a composition for problems such as unused instances, failing
+    /// if any diagnostic is denied
+    Validate(ValidateArgs),
+    /// Check a composition against the assertions in a rules file, failing
+    /// if any is violated
+    Check(CheckArgs),
+    /// Compare a composition against an expected topology, failing if any
+    /// instance, connection or export was added, removed or rewired
+    Verify(VerifyArgs),
+    /// Print every connection path from one instance to another instance
+    /// or to a host interface
+    Path(PathArgs),
+    /// Print everything known about one instance: its component, imports,
+    /// what it provides, metadata and position in the handler chains
+    Inspect(InspectArgs),
+    /// Explain why an instance is part of the composition: the exports that
+    /// depend on it, its place in the handler chains and who imports from it
+    Why(WhyArgs),
+    /// List the sensitive host interfaces (filesystem, sockets,
+    /// environment, random, outgoing HTTP) each instance imports, failing if
+    /// an allow-list is given and does not cover them
+    Audit(AuditArgs),
+    /// Print a software bill of materials listing the embedded components
+    /// and the dependencies between them
+    Sbom(SbomArgs),
+    /// List the embedded components from largest to smallest, with their
+    /// share of the composition's size
+    Top(TopArgs),
+    /// Embed the JSON graph and a mermaid diagram of a component in custom
+    /// sections of the binary itself, or print the ones embedded earlier
+    Annotate(AnnotateArgs),
+    /// Print a completion script for a shell, to be sourced from its
+    /// startup file
+    Completions {
+        /// The shell to complete for
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
+    /// Serve a live diagram of FILE, re-read on every request, for a browser
+    /// tab to keep open during development
+    Serve(ServeArgs),
+    /// Serve parsing and rendering over HTTP
+    Api(ApiArgs),
+}
+
+/// The subcommands of `cviz list`.
+#[derive(Subcommand, Debug)]
+enum ListCommand {
+    /// Instances in the order the runtime instantiates them, with what each
+    /// one waits for
+    Order(ListArgs),
+    /// Imports that are plugged in under a different name
+    Renames(ListArgs),
+    /// Instances with their component, import and export counts and size
+    Instances(ListArgs),
+    /// Top-level exports with their kind, provider and the length of the
+    /// chain behind them
+    Exports(ListArgs),
+    /// Interfaces imported or exported, with their versions, how many
+    /// instances use them and whether the host provides them
+    Interfaces(ListArgs),
+}
+
+/// Arguments of each `cviz list` subcommand.
+#[derive(clap::Args, Debug)]
+struct ListArgs {
+    /// The composition (`-` for stdin, or an http(s):// or oci:// URL)
+    #[arg(value_name = "FILE")]
+    file: PathBuf,
+
+    /// Output format
+    #[arg(short, long, default_value = "text", value_parser = report_format([ReportFormat::Text, ReportFormat::Json]))]
+    format: ReportFormat,
+
+    /// Output file (stdout if not specified or `-`)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+/// Arguments of `cviz stats`.
+#[derive(clap::Args, Debug)]
+struct StatsArgs {
+    /// The composition (`-` for stdin, or an http(s):// or oci:// URL)
+    #[arg(value_name = "FILE")]
+    file: PathBuf,
+
+    /// Output format
+    #[arg(short, long, default_value = "text", value_parser = report_format([ReportFormat::Text, ReportFormat::Markdown, ReportFormat::Json]))]
+    format: ReportFormat,
+
+    /// JSON file overriding the health score thresholds and penalties
+    #[arg(long, value_name = "FILE")]
+    health_weights: Option<PathBuf>,
+
+    /// Output file (stdout if not specified or `-`)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+/// Arguments of `cviz health`.
+#[derive(clap::Args, Debug)]
+struct HealthArgs {
+    /// The composition (`-` for stdin, or an http(s):// or oci:// URL)
+    #[arg(value_name = "FILE")]
+    file: PathBuf,
+
+    /// Output format
+    #[arg(short, long, default_value = "text", value_parser = report_format([ReportFormat::Text, ReportFormat::Markdown, ReportFormat::Json]))]
+    format: ReportFormat,
+
+    /// JSON file overriding the health score thresholds and penalties
+    #[arg(long, value_name = "FILE")]
+    health_weights: Option<PathBuf>,
+
+    /// Output file (stdout if not specified or `-`)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+/// Arguments of `cviz reachable`.
+#[derive(clap::Args, Debug)]
+struct ReachableArgs {
+    /// The composition (`-` for stdin, or an http(s):// or oci:// URL)
+    #[arg(value_name = "FILE")]
+    file: PathBuf,
+
+    /// The export to start from, e.g. `wasi:http/handler`; the version may
+    /// be left out when only one is exported
+    #[arg(value_name = "EXPORT")]
+    export: String,
+
+    /// Output format
+    #[arg(short, long, default_value = "text", value_parser = report_format([ReportFormat::Text, ReportFormat::Json]))]
+    format: ReportFormat,
+
+    /// Output file (stdout if not specified or `-`)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+/// Arguments of `cviz capabilities`.
+#[derive(clap::Args, Debug)]
+struct CapabilitiesArgs {
+    /// The composition (`-` for stdin, or an http(s):// or oci:// URL)
+    #[arg(value_name = "FILE")]
+    file: PathBuf,
+
+    /// Output format
+    #[arg(short, long, default_value = "text", value_parser = report_format([ReportFormat::Text, ReportFormat::Csv, ReportFormat::Json]))]
+    format: ReportFormat,
+
+    /// Output file (stdout if not specified or `-`)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+/// Arguments of `cviz diff`.
+#[derive(clap::Args, Debug)]
+struct DiffArgs {
+    /// Diff format
+    #[arg(short, long, default_value = "json-patch", value_parser = report_format([ReportFormat::JsonPatch, ReportFormat::Text, ReportFormat::Json]))]
+    format: ReportFormat,
+
+    /// Output file (stdout if not specified or `-`)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// The earlier composition (`-` for stdin, or an http(s):// or oci:// URL)
+    #[arg(value_name = "OLD")]
+    old: PathBuf,
+
+    /// The later composition (`-` for stdin, or an http(s):// or oci:// URL)
+    #[arg(value_name = "NEW")]
+    new: PathBuf,
+}
+
+/// Arguments of `cviz validate`.
+#[derive(clap::Args, Debug)]
+struct ValidateArgs {
+    /// The composition (`-` for stdin, or an http(s):// or oci:// URL)
+    #[arg(value_name = "FILE")]
+    file: PathBuf,
+}
+
+/// Arguments of `cviz check`.
+#[derive(clap::Args, Debug)]
+struct CheckArgs {
+    /// The composition (`-` for stdin, or an http(s):// or oci:// URL)
+    #[arg(value_name = "FILE")]
+    file: PathBuf,
+
+    /// TOML file of rules such as `chain-order = ["*auth*", "*router*"]`
+    /// or `forbid-import = "wasi:sockets/*"`
+    #[arg(long, value_name = "FILE")]
+    rules: PathBuf,
+}
+
+/// Arguments of `cviz verify`.
+#[derive(clap::Args, Debug)]
+struct VerifyArgs {
+    /// The composition (`-` for stdin, or an http(s):// or oci:// URL)
+    #[arg(value_name = "FILE")]
+    file: PathBuf,
+
+    /// YAML file declaring the expected `nodes`, `edges` and `exports`
+    #[arg(long, value_name = "FILE")]
+    expect: PathBuf,
+}
+
+/// Arguments of `cviz path`.
+#[derive(clap::Args, Debug)]
+struct PathArgs {
+    /// The composition (`-` for stdin, or an http(s):// or oci:// URL)
+    #[arg(value_name = "FILE")]
+    file: PathBuf,
+
+    /// Instance the paths start from
+    #[arg(long, value_name = "NODE")]
+    from: String,
+
+    /// Instance or host interface the paths end at; the interface may
+    /// omit its version
+    #[arg(long, value_name = "NODE|INTERFACE")]
+    to: String,
+
+    /// Output format; mermaid draws the all-interfaces diagram with the
+    /// paths highlighted
+    #[arg(short, long, default_value = "text", value_parser = report_format([ReportFormat::Text, ReportFormat::Json, ReportFormat::Mermaid]))]
+    format: ReportFormat,
+
+    /// Diagram direction (mermaid only)
+    #[arg(short, long, env = "CVIZ_DIRECTION", default_value = "lr", value_parser = one_of(["lr", "td"], parse_direction))]
+    direction: Direction,
+
+    /// Output file (stdout if not specified or `-`)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+/// Arguments of `cviz inspect`.
+#[derive(clap::Args, Debug)]
+struct InspectArgs {
+    /// The composition (`-` for stdin, or an http(s):// or oci:// URL)
+    #[arg(value_name = "FILE")]
+    file: PathBuf,
+
+    /// Instance name, display label or numeric ID
+    #[arg(value_name = "NODE")]
+    node: String,
+
+    /// Output format
+    #[arg(short, long, default_value = "text", value_parser = report_format([ReportFormat::Text, ReportFormat::Json]))]
+    format: ReportFormat,
+
+    /// Output file (stdout if not specified or `-`)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+/// Arguments of `cviz why`.
+#[derive(clap::Args, Debug)]
+struct WhyArgs {
+    /// The composition (`-` for stdin, or an http(s):// or oci:// URL)
+    #[arg(value_name = "FILE")]
+    file: PathBuf,
+
+    /// Instance name, display label or numeric ID
+    #[arg(value_name = "NODE")]
+    node: String,
+
+    /// Output format
+    #[arg(short, long, default_value = "text", value_parser = report_format([ReportFormat::Text, ReportFormat::Json]))]
+    format: ReportFormat,
+
+    /// Output file (stdout if not specified or `-`)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+/// Arguments of `cviz audit`.
+#[derive(clap::Args, Debug)]
+struct AuditArgs {
+    /// The composition (`-` for stdin, or an http(s):// or oci:// URL)
+    #[arg(value_name = "FILE")]
+    file: PathBuf,
+
+    /// TOML file mapping instance names (wildcards allowed) to the
+    /// capability kinds or interfaces they may import
+    #[arg(long, value_name = "FILE")]
+    allow_list: Option<PathBuf>,
+
+    /// Output format
+    #[arg(short, long, default_value = "text", value_parser = report_format([ReportFormat::Text, ReportFormat::Json]))]
+    format: ReportFormat,
+
+    /// Output file (stdout if not specified or `-`)
     #[arg(short, long)]
     output: Option<PathBuf>,
 }
 
+/// Arguments of `cviz sbom`.
+#[derive(clap::Args, Debug)]
+struct SbomArgs {
+    /// The composition (`-` for stdin, or an http(s):// or oci:// URL)
+    #[arg(value_name = "FILE")]
+    file: PathBuf,
+
+    /// Document format
+    #[arg(short, long, default_value = "cyclonedx", value_parser = one_of(["cyclonedx", "spdx"], parse_sbom_format))]
+    format: SbomFormat,
+
+    /// Name of the composition in the document (default: the name the
+    /// component carries, or the file name)
+    #[arg(long)]
+    name: Option<String>,
+
+    /// Output file (stdout if not specified or `-`)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+/// Arguments of `cviz top`.
+#[derive(clap::Args, Debug)]
+struct TopArgs {
+    /// The composition (`-` for stdin, or an http(s):// or oci:// URL)
+    #[arg(value_name = "FILE")]
+    file: PathBuf,
+
+    /// Show only the N largest components
+    #[arg(short = 'n', long, value_name = "N")]
+    count: Option<usize>,
+
+    /// Output format
+    #[arg(short, long, default_value = "text", value_parser = report_format([ReportFormat::Text, ReportFormat::Json]))]
+    format: ReportFormat,
+
+    /// Output file (stdout if not specified or `-`)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+/// Arguments of `cviz annotate`.
+#[derive(clap::Args, Debug)]
+struct AnnotateArgs {
+    /// The component (`-` for stdin, or an http(s):// or oci:// URL)
+    #[arg(value_name = "FILE")]
+    file: PathBuf,
+
+    /// What to embed
+    #[arg(long, default_value = "both", value_parser = one_of(["json", "mermaid", "both"], parse_embed))]
+    embed: Embed,
+
+    /// Detail level of the mermaid diagram
+    #[arg(short = 'l', long, default_value = "all-interfaces", value_parser = one_of(["handler-chain", "chains", "all-interfaces", "full"], parse_detail))]
+    detail: DetailLevel,
+
+    /// Diagram direction
+    #[arg(short, long, default_value = "lr", value_parser = one_of(["lr", "td"], parse_direction))]
+    direction: Direction,
+
+    /// Print the embedded JSON graph or mermaid diagram instead of
+    /// embedding anything
+    #[arg(long, value_name = "WHAT", value_parser = one_of(["json", "mermaid"], parse_embed))]
+    extract: Option<Embed>,
+
+    /// Where to write the annotated binary (FILE itself if not
+    /// specified, `-` for stdout)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+/// Arguments of `cviz serve`.
+#[derive(clap::Args, Debug)]
+struct ServeArgs {
+    /// Component, graph or composition source to show
+    file: PathBuf,
+
+    /// Address to listen on, as HOST:PORT or :PORT (all interfaces)
+    #[arg(long, default_value = "127.0.0.1:7070", value_parser = parse_listen)]
+    listen: String,
+}
+
+/// Arguments of `cviz api`.
+#[derive(clap::Args, Debug)]
+struct ApiArgs {
+    /// Address to listen on, as HOST:PORT or :PORT (all interfaces)
+    #[arg(long, default_value = "127.0.0.1:7070", value_parser = parse_listen)]
+    listen: String,
+
+    /// Largest accepted request body, in bytes
+    #[arg(long, value_name = "BYTES", default_value_t = 64 * 1024 * 1024)]
+    max_body_size: usize,
+
+    /// Requests handled concurrently; a queue of the same size absorbs
+    /// bursts and further requests are rejected with 503
+    #[arg(long, value_name = "N", default_value_t = 8)]
+    max_concurrency: usize,
+}
+
+/// Accept Go-style `:PORT` as shorthand for listening on all interfaces.
+fn parse_listen(s: &str) -> Result<String, String> {
+    match s.strip_prefix(':') {
+        Some(port) => port
+            .parse::<u16>()
+            .map(|port| format!("0.0.0.0:{}", port))
+            .map_err(|_| format!("Invalid port: {}", port)),
+        None => Ok(s.to_string()),
+    }
+}
+
+/// What an input is
+#[derive(Debug, Clone, Copy)]
+enum InputFormat {
+    /// Guessed from the extension, or the content for stdin and URLs
+    Auto,
+    /// A component binary
+    Wasm,
+    /// A graph written with `--format json`
+    Json,
+    /// WAC composition source
+    Wac,
+    /// A wadm application manifest
+    Wadm,
+    /// A wasm-compose config
+    Compose,
+}
+
+fn parse_input_format(s: &str) -> Result<InputFormat, String> {
+    match s.to_lowercase().as_str() {
+        "auto" => Ok(InputFormat::Auto),
+        "wasm" | "component" => Ok(InputFormat::Wasm),
+        "json" => Ok(InputFormat::Json),
+        "wac" => Ok(InputFormat::Wac),
+        "wadm" => Ok(InputFormat::Wadm),
+        "compose" | "wasm-compose" => Ok(InputFormat::Compose),
+        _ => Err(format!(
+            "Unknown input format: {}. Use 'auto', 'wasm', 'json', 'wac', 'wadm' or 'compose'",
+            s
+        )),
+    }
+}
+
+/// How to print a report.  Each command accepts the subset of these it can
+/// render, see [`report_format`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ReportFormat {
+    #[value(alias = "table")]
+    Text,
+    #[value(alias = "md")]
+    Markdown,
+    Csv,
+    Json,
+    Mermaid,
+    #[value(alias = "jsonpatch")]
+    JsonPatch,
+}
+
+/// Parser for a `--format` that accepts only `formats`, listing them (but
+/// not their aliases, such as `table` for `text`) in `--help` and shell
+/// completions.
+fn report_format<const N: usize>(
+    formats: [ReportFormat; N],
+) -> impl TypedValueParser<Value = ReportFormat> {
+    PossibleValuesParser::new(formats.map(|f| f.to_possible_value().expect("no skipped formats")))
+        .map(|s| ReportFormat::from_str(&s, true).expect("accepted by the possible values"))
+}
+
+/// What `cviz annotate` embeds or extracts
+#[derive(Debug, Clone, Copy)]
+enum Embed {
+    Json,
+    Mermaid,
+    Both,
+}
+
+fn parse_embed(s: &str) -> Result<Embed, String> {
+    match s.to_lowercase().as_str() {
+        "json" => Ok(Embed::Json),
+        "mermaid" => Ok(Embed::Mermaid),
+        "both" => Ok(Embed::Both),
+        _ => Err(format!(
+            "Unknown annotation: {}. Use 'json', 'mermaid' or 'both'",
+            s
+        )),
+    }
+}
+
+/// Which document `cviz sbom` writes
+#[derive(Debug, Clone, Copy)]
+enum SbomFormat {
+    /// CycloneDX 1.5 JSON
+    CycloneDx,
+    /// SPDX 2.3 JSON
+    Spdx,
+}
+
+fn parse_sbom_format(s: &str) -> Result<SbomFormat, String> {
+    match s.to_lowercase().as_str() {
+        "cyclonedx" | "cdx" => Ok(SbomFormat::CycloneDx),
+        "spdx" => Ok(SbomFormat::Spdx),
+        _ => Err(format!(
+            "Unknown SBOM format: {}. Use 'cyclonedx' or 'spdx'",
+            s
+        )),
+    }
+}
+
+/// When to wrap labels in OSC 8 hyperlinks
+#[derive(Debug, Clone, Copy)]
+enum Hyperlinks {
+    /// Only on stdout, and only if the terminal is known to support them
+    Auto,
+    Always,
+    Never,
+}
+
+fn parse_hyperlinks(s: &str) -> Result<Hyperlinks, String> {
+    match s.to_lowercase().as_str() {
+        "auto" => Ok(Hyperlinks::Auto),
+        "always" => Ok(Hyperlinks::Always),
+        "never" => Ok(Hyperlinks::Never),
+        _ => Err(format!(
+            "Unknown hyperlinks mode: {}. Use 'auto', 'always' or 'never'",
+            s
+        )),
+    }
+}
+
+/// A `parse_*` function together with the values it accepts, so that
+/// `--help` and shell completions can list them.  Aliases the function
+/// also accepts, such as `cdx` for `cyclonedx`, are not listed.
+#[derive(Clone)]
+struct OneOf<T> {
+    values: Vec<&'static str>,
+    parse: fn(&str) -> Result<T, String>,
+}
+
+fn one_of<T>(
+    values: impl IntoIterator<Item = &'static str>,
+    parse: fn(&str) -> Result<T, String>,
+) -> OneOf<T> {
+    OneOf {
+        values: values.into_iter().collect(),
+        parse,
+    }
+}
+
+impl<T: Clone + Send + Sync + 'static> TypedValueParser for OneOf<T> {
+    type Value = T;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &std::ffi::OsStr,
+    ) -> Result<T, clap::Error> {
+        self.parse.parse_ref(cmd, arg, value)
+    }
+
+    fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
+        Some(Box::new(self.values.iter().map(PossibleValue::new)))
+    }
+}
+
+fn parse_category(s: &str) -> Result<Category, String> {
+    s.parse()
+}
+
 fn parse_format(s: &str) -> Result<OutputFormat, String> {
     s.parse()
 }
@@ -47,35 +929,1293 @@ fn parse_detail(s: &str) -> Result<DetailLevel, String> {
     s.parse()
 }
 
+/// Send log events to stderr at the level `-v`, `-vv` and `--quiet` ask
+/// for.  Targets are only shown when debugging.
+fn init_logging(args: &Args) {
+    let level = match (args.quiet, args.verbose) {
+        (true, _) => Level::ERROR,
+        (false, 0) => Level::INFO,
+        (false, 1) => Level::DEBUG,
+        (false, _) => Level::TRACE,
+    };
+    tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_writer(std::io::stderr)
+        .with_ansi(match args.color {
+            clap::ColorChoice::Auto => std::io::stderr().is_terminal(),
+            clap::ColorChoice::Always => true,
+            clap::ColorChoice::Never => false,
+        })
+        .with_target(args.verbose > 0)
+        .without_time()
+        .init();
+}
+
 fn main() -> Result<()> {
-    let args = Args::parse();
+    let matches = Args::command().get_matches();
+    let mut args = Args::from_arg_matches(&matches)?;
+    init_logging(&args);
+
+    let mut fetch = fetch::FetchOptions {
+        max_size: args.max_download_size,
+        ..fetch::FetchOptions::default()
+    };
+    fetch.progress &= !args.quiet;
+    let sources = Sources {
+        progress: fetch.progress && args.verbose == 0,
+        fetch,
+        compose_input: args.compose_input.clone(),
+        input_format: args.input_format,
+        parse: ParseOptions {
+            strict: args.strict || args.deny_warnings,
+            ..ParseOptions::default()
+        },
+    };
+
+    match args.command.take() {
+        None => cmd_graph(&args.graph, &args, &matches, &sources),
+        Some(Command::Graph(graph_args)) => cmd_graph(&graph_args, &args, &matches, &sources),
+        Some(Command::Stats(cmd)) => cmd_stats(&cmd, &args, &sources),
+        Some(Command::Health(cmd)) => cmd_health(&cmd, &args, &sources),
+        Some(Command::Reachable(cmd)) => cmd_reachable(&cmd, &args, &sources),
+        Some(Command::Capabilities(cmd)) => cmd_capabilities(&cmd, &args, &sources),
+        Some(Command::List { list }) => cmd_list(&list, &args, &sources),
+        Some(Command::Diff(cmd)) => cmd_diff(&cmd, &args, &sources),
+        Some(Command::Validate(cmd)) => cmd_validate(&cmd, &args, &matches, &sources),
+        Some(Command::Check(cmd)) => cmd_check(&cmd, &args, &sources),
+        Some(Command::Verify(cmd)) => cmd_verify(&cmd, &args, &sources),
+        Some(Command::Path(cmd)) => cmd_path(&cmd, &args, &sources),
+        Some(Command::Inspect(cmd)) => cmd_inspect(&cmd, &args, &sources),
+        Some(Command::Why(cmd)) => cmd_why(&cmd, &args, &sources),
+        Some(Command::Audit(cmd)) => cmd_audit(&cmd, &args, &sources),
+        Some(Command::Sbom(cmd)) => cmd_sbom(&cmd, &args, &sources),
+        Some(Command::Top(cmd)) => cmd_top(&cmd, &args, &sources),
+        Some(Command::Annotate(cmd)) => cmd_annotate(&cmd, &args, &sources),
+        Some(Command::Completions { shell }) => cmd_completions(shell),
+        Some(Command::Serve(cmd)) => cmd_serve(cmd, args, sources),
+        Some(Command::Api(cmd)) => cmd_api(&cmd),
+    }
+}
+
+/// `cviz graph`, and `cviz <FILE>...`: draw each input, one output per
+/// input, or all of them combined.
+fn cmd_graph(
+    graph_args: &GraphArgs,
+    args: &Args,
+    matches: &ArgMatches,
+    sources: &Sources,
+) -> Result<()> {
+    let inputs = match graph_args.files.as_slice() {
+        // `wkg get ... | cviz` reads the piped component
+        [] if !std::io::stdin().is_terminal() => vec![PathBuf::from(STDIN)],
+        [] => Args::command()
+            .error(
+                clap::error::ErrorKind::MissingRequiredArgument,
+                "the following required arguments were not provided:\n  <FILE>...",
+            )
+            .exit(),
+        files => batch_inputs(files, &graph_args.glob)?,
+    };
+
+    if graph_args.combine {
+        let mut sections = Vec::new();
+        for file in &inputs {
+            let to_stdout = is_stdout(graph_args.output.as_deref());
+            match render(file, to_stdout, graph_args, args, matches, sources) {
+                Ok(drawing) => sections.push(output::Section {
+                    title: file.display().to_string(),
+                    body: drawing.text,
+                    diagram: drawing.diagram,
+                }),
+                Err(err) => error!("{}: {:#}", file.display(), err),
+            }
+        }
+        let combined = output::combine_sections(graph_args.format, &sections);
+        write_output(graph_args.output.as_deref(), &combined)?;
+        if sections.len() < inputs.len() {
+            anyhow::bail!(
+                "{} of {} inputs failed",
+                inputs.len() - sections.len(),
+                inputs.len()
+            );
+        }
+        return Ok(());
+    }
+
+    let Some(dir) = &graph_args.output_dir else {
+        return match inputs.as_slice() {
+            [file] => draw(
+                file,
+                graph_args.output.as_deref(),
+                graph_args,
+                args,
+                matches,
+                sources,
+            ),
+            _ => anyhow::bail!(
+                "{} inputs need --output-dir to write one output per input, or --combine",
+                inputs.len()
+            ),
+        };
+    };
+    let mut outputs: Vec<PathBuf> = Vec::new();
+    for file in &inputs {
+        let output = dir.join(output_name(
+            &graph_args.output_name,
+            file,
+            graph_args.format,
+        ));
+        if outputs.contains(&output) {
+            anyhow::bail!(
+                "Several inputs would be written to {}; use a template that tells them apart",
+                output.display()
+            );
+        }
+        outputs.push(output);
+    }
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create output directory: {}", dir.display()))?;
+    let mut failed = 0;
+    for (file, output) in inputs.iter().zip(&outputs) {
+        if let Err(err) = draw(file, Some(output), graph_args, args, matches, sources) {
+            error!("{}: {:#}", file.display(), err);
+            failed += 1;
+        }
+    }
+    if failed > 0 {
+        anyhow::bail!("{} of {} inputs failed", failed, inputs.len());
+    }
+    Ok(())
+}
+
+fn cmd_stats(cmd: &StatsArgs, args: &Args, sources: &Sources) -> Result<()> {
+    let graph = load_reshaped(&cmd.file, args, sources)?;
+    let rendered = stats_report(&graph, cmd.format, cmd.health_weights.as_deref())?;
+    write_output(cmd.output.as_deref(), &rendered)
+}
+
+fn cmd_health(cmd: &HealthArgs, args: &Args, sources: &Sources) -> Result<()> {
+    let graph = load_reshaped(&cmd.file, args, sources)?;
+    let rendered = health_report(&graph, cmd.format, cmd.health_weights.as_deref())?;
+    write_output(cmd.output.as_deref(), &rendered)
+}
+
+fn cmd_reachable(cmd: &ReachableArgs, args: &Args, sources: &Sources) -> Result<()> {
+    let graph = load_reshaped(&cmd.file, args, sources)?;
+    let rendered = reachable_report(&graph, &cmd.export, cmd.format)?;
+    write_output(cmd.output.as_deref(), &rendered)
+}
+
+fn cmd_capabilities(cmd: &CapabilitiesArgs, args: &Args, sources: &Sources) -> Result<()> {
+    let graph = load_reshaped(&cmd.file, args, sources)?;
+    let rendered = capabilities_report(&graph, cmd.format)?;
+    write_output(cmd.output.as_deref(), &rendered)
+}
 
-    // Read the component file
-    let bytes = std::fs::read(&args.file)
-        .with_context(|| format!("Failed to read file: {}", args.file.display()))?;
+/// `cviz list`: print the list asked for.
+fn cmd_list(list: &ListCommand, args: &Args, sources: &Sources) -> Result<()> {
+    let (cmd, report): (_, fn(&CompositionGraph, ReportFormat) -> Result<String>) = match list {
+        ListCommand::Order(cmd) => (cmd, order_report),
+        ListCommand::Renames(cmd) => (cmd, renames_report),
+        ListCommand::Instances(cmd) => (cmd, instances_report),
+        ListCommand::Exports(cmd) => (cmd, exports_report),
+        ListCommand::Interfaces(cmd) => (cmd, interfaces_report),
+    };
+    let graph = load_reshaped(&cmd.file, args, sources)?;
+    write_output(cmd.output.as_deref(), &report(&graph, cmd.format)?)
+}
 
-    // Parse the component
-    let graph = cviz::parse::component::parse_component(&bytes)
-        .with_context(|| format!("Failed to parse component: {}", args.file.display()))?;
+fn cmd_diff(cmd: &DiffArgs, args: &Args, sources: &Sources) -> Result<()> {
+    if cmd.old == Path::new(STDIN) && cmd.new == Path::new(STDIN) {
+        anyhow::bail!("only one side of a diff can be read from stdin");
+    }
+    let old = load_reshaped(&cmd.old, args, sources)?;
+    let new = load_reshaped(&cmd.new, args, sources)?;
+    let rendered = match cmd.format {
+        ReportFormat::JsonPatch => output::json_patch::generate_json_patch(&old, &new, true)?,
+        ReportFormat::Json => serde_json::to_string_pretty(&diff_graphs(&old, &new))?,
+        _ => diff_graphs(&old, &new).to_text(),
+    };
+    write_output(cmd.output.as_deref(), &rendered)
+}
+
+fn cmd_validate(
+    cmd: &ValidateArgs,
+    args: &Args,
+    matches: &ArgMatches,
+    sources: &Sources,
+) -> Result<()> {
+    let graph = load_reshaped(&cmd.file, args, sources)?;
+    let diagnostics = diagnose(&graph, &lint_levels(args, matches)?);
+    for diag in &diagnostics {
+        println!("{}", diag);
+    }
+    fail_on_denied(&diagnostics)?;
+    match diagnostics.len() {
+        0 => println!("{}: ok", cmd.file.display()),
+        n => println!("{}: ok with {} warning(s)", cmd.file.display(), n),
+    }
+    Ok(())
+}
+
+fn cmd_check(cmd: &CheckArgs, args: &Args, sources: &Sources) -> Result<()> {
+    let graph = load_reshaped(&cmd.file, args, sources)?;
+    let text = std::fs::read_to_string(&cmd.rules)
+        .with_context(|| format!("Failed to read rules: {}", cmd.rules.display()))?;
+    let rule_set: RuleSet = toml::from_str(&text)
+        .with_context(|| format!("Failed to parse rules: {}", cmd.rules.display()))?;
+    let violations = rule_set.check(&graph);
+    for violation in &violations {
+        println!("{}", violation);
+    }
+    if !violations.is_empty() {
+        anyhow::bail!("aborting due to {} rule violation(s)", violations.len());
+    }
+    println!(
+        "{}: ok ({} rules)",
+        cmd.file.display(),
+        rule_set.rules.len()
+    );
+    Ok(())
+}
+
+fn cmd_verify(cmd: &VerifyArgs, args: &Args, sources: &Sources) -> Result<()> {
+    let graph = load_reshaped(&cmd.file, args, sources)?;
+    let text = std::fs::read_to_string(&cmd.expect)
+        .with_context(|| format!("Failed to read topology: {}", cmd.expect.display()))?;
+    let expected: Topology = serde_yaml::from_str(&text)
+        .with_context(|| format!("Failed to parse topology: {}", cmd.expect.display()))?;
+    let changes = expected.compare(&Topology::of(&graph));
+    for change in &changes {
+        println!("{}", change);
+    }
+    if !changes.is_empty() {
+        anyhow::bail!(
+            "{} differs from {} in {} place(s)",
+            cmd.file.display(),
+            cmd.expect.display(),
+            changes.len()
+        );
+    }
+    println!("{}: matches {}", cmd.file.display(), cmd.expect.display());
+    Ok(())
+}
+
+fn cmd_path(cmd: &PathArgs, args: &Args, sources: &Sources) -> Result<()> {
+    let graph = load_reshaped(&cmd.file, args, sources)?;
+    let known = || {
+        let labels: Vec<&str> = graph.nodes.values().map(|n| n.display_label()).collect();
+        labels.join(", ")
+    };
+    let start = graph
+        .find_node(&cmd.from)
+        .with_context(|| format!("No instance named '{}' (available: {})", cmd.from, known()))?;
+    let target = PathTarget::resolve(&graph, &cmd.to).with_context(|| {
+        format!(
+            "No instance or host interface named '{}' (instances: {}; host interfaces: {})",
+            cmd.to,
+            known(),
+            graph.host_interfaces().join(", ")
+        )
+    })?;
+    if target == PathTarget::Instance(start) {
+        anyhow::bail!("--from and --to name the same instance");
+    }
+    let paths = connection_paths(&graph, start, &target);
+    let rendered = match cmd.format {
+        ReportFormat::Json => serde_json::to_string_pretty(&paths)?,
+        ReportFormat::Mermaid => {
+            let opts = RenderOptions {
+                highlight: paths.paths.into_iter().flatten().collect(),
+                ..RenderOptions::default()
+            };
+            output::mermaid::generate_mermaid_with_options(
+                &graph,
+                DetailLevel::AllInterfaces,
+                cmd.direction,
+                &opts,
+            )
+        }
+        _ => paths.to_text(),
+    };
+    write_output(cmd.output.as_deref(), &rendered)
+}
+
+fn cmd_inspect(cmd: &InspectArgs, args: &Args, sources: &Sources) -> Result<()> {
+    let graph = load_reshaped(&cmd.file, args, sources)?;
+    let report = graph
+        .find_node(&cmd.node)
+        .and_then(|id| inspect(&graph, id))
+        .with_context(|| unknown_instance(&graph, &cmd.node))?;
+    let rendered = match cmd.format {
+        ReportFormat::Json => serde_json::to_string_pretty(&report)?,
+        _ => report.to_text(),
+    };
+    write_output(cmd.output.as_deref(), &rendered)
+}
+
+fn cmd_why(cmd: &WhyArgs, args: &Args, sources: &Sources) -> Result<()> {
+    let graph = load_reshaped(&cmd.file, args, sources)?;
+    let explanation = graph
+        .find_node(&cmd.node)
+        .and_then(|id| explain(&graph, id))
+        .with_context(|| unknown_instance(&graph, &cmd.node))?;
+    let rendered = match cmd.format {
+        ReportFormat::Json => serde_json::to_string_pretty(&explanation)?,
+        _ => explanation.to_text(),
+    };
+    write_output(cmd.output.as_deref(), &rendered)
+}
+
+fn cmd_audit(cmd: &AuditArgs, args: &Args, sources: &Sources) -> Result<()> {
+    let graph = load_reshaped(&cmd.file, args, sources)?;
+    let allow: Option<AllowList> = match &cmd.allow_list {
+        Some(path) => {
+            let text = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read allow-list: {}", path.display()))?;
+            Some(
+                toml::from_str(&text)
+                    .with_context(|| format!("Failed to parse allow-list: {}", path.display()))?,
+            )
+        }
+        None => None,
+    };
+    let report = audit(&graph, allow.as_ref());
+    let rendered = match cmd.format {
+        ReportFormat::Json => serde_json::to_string_pretty(&report)?,
+        _ => report.to_text(),
+    };
+    write_output(cmd.output.as_deref(), &rendered)?;
+    let violations = report.violations().len();
+    if violations > 0 {
+        anyhow::bail!("{} sensitive import(s) not in the allow-list", violations);
+    }
+    Ok(())
+}
+
+fn cmd_sbom(cmd: &SbomArgs, args: &Args, sources: &Sources) -> Result<()> {
+    let graph = load_reshaped(&cmd.file, args, sources)?;
+    let name = cmd
+        .name
+        .clone()
+        .or_else(|| graph.metadata.get(&None).and_then(|m| m.name.clone()))
+        .or_else(|| {
+            cmd.file
+                .file_stem()
+                .filter(|_| cmd.file.as_os_str() != STDIN)
+                .map(|s| s.to_string_lossy().into_owned())
+        })
+        .unwrap_or_else(|| "composition".to_string());
+    let options = SbomOptions {
+        name,
+        created: sbom_timestamp()?,
+    };
+    let bom = sbom(&graph);
+    let document = match cmd.format {
+        SbomFormat::CycloneDx => bom.to_cyclonedx(&options),
+        SbomFormat::Spdx => bom.to_spdx(&options),
+    };
+    write_output(
+        cmd.output.as_deref(),
+        &serde_json::to_string_pretty(&document)?,
+    )
+}
+
+fn cmd_top(cmd: &TopArgs, args: &Args, sources: &Sources) -> Result<()> {
+    let graph = load_reshaped(&cmd.file, args, sources)?;
+    let mut ranking = size_ranking(&graph);
+    if let Some(count) = cmd.count {
+        ranking.truncate(count);
+    }
+    let rendered = match cmd.format {
+        ReportFormat::Json => serde_json::to_string_pretty(&ranking)?,
+        _ => sizes_table(&ranking),
+    };
+    write_output(cmd.output.as_deref(), &rendered)
+}
+
+fn cmd_annotate(cmd: &AnnotateArgs, args: &Args, sources: &Sources) -> Result<()> {
+    let (bytes, source, remote) = read_input(&cmd.file, sources)?;
+    if let Some(what) = cmd.extract {
+        let annotations = read_annotations(&bytes)
+            .with_context(|| format!("Failed to read annotations: {}", source))?;
+        let (text, section) = match what {
+            Embed::Mermaid => (annotations.mermaid, "mermaid diagram"),
+            _ => (annotations.graph, "JSON graph"),
+        };
+        let text = text.with_context(|| {
+            format!(
+                "{} has no embedded {}; add one with `cviz annotate`",
+                source, section
+            )
+        })?;
+        return write_output(cmd.output.as_deref(), text.trim_end());
+    }
+
+    let target = match &cmd.output {
+        Some(path) => path.clone(),
+        None if remote || cmd.file.as_os_str() == STDIN => anyhow::bail!(
+            "{} cannot be annotated in place; give --output (`-` for stdout)",
+            source
+        ),
+        None => cmd.file.clone(),
+    };
+    let mut graph = parse_component_with_options(&bytes, &sources.parse)
+        .with_context(|| format!("Failed to parse component: {}", source))?;
+    reshape(&mut graph, args);
+    let annotations = Annotations {
+        graph: match cmd.embed {
+            Embed::Mermaid => None,
+            _ => Some(output::json::generate_json(&graph, false)?),
+        },
+        mermaid: match cmd.embed {
+            Embed::Json => None,
+            _ => Some(output::mermaid::generate_mermaid(
+                &graph,
+                cmd.detail,
+                cmd.direction,
+                false,
+            )),
+        },
+    };
+    let annotated = write_annotations(&bytes, &annotations)
+        .with_context(|| format!("Failed to annotate {}", source))?;
+    if is_stdout(Some(&target)) {
+        return write_stdout(&annotated);
+    }
+    std::fs::write(&target, annotated)
+        .with_context(|| format!("Failed to write output: {}", target.display()))?;
+    info!("Annotated component written to: {}", target.display());
+    Ok(())
+}
+
+fn cmd_completions(shell: clap_complete::Shell) -> Result<()> {
+    // Complete the name the binary was run as (`cviz-cli` when installed
+    // with cargo, `cviz` when renamed or aliased)
+    let bin = std::env::args_os()
+        .next()
+        .and_then(|arg0| {
+            Path::new(&arg0)
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+        })
+        .unwrap_or_else(|| "cviz".to_string());
+    clap_complete::generate(shell, &mut Args::command(), bin, &mut std::io::stdout());
+    Ok(())
+}
+
+fn cmd_serve(cmd: ServeArgs, args: Args, sources: Sources) -> Result<()> {
+    let listener = std::net::TcpListener::bind(&cmd.listen)
+        .with_context(|| format!("Failed to listen on {}", cmd.listen))?;
+    info!(
+        "serving {} on http://{}",
+        cmd.file.display(),
+        listener.local_addr()?
+    );
+    let title = cmd.file.display().to_string();
+    let load = move || load_reshaped(&cmd.file, &args, &sources);
+    server::serve_file(
+        listener,
+        server::ServerConfig {
+            max_body: 0,
+            workers: 4,
+        },
+        title,
+        load,
+    )
+}
+
+fn cmd_api(cmd: &ApiArgs) -> Result<()> {
+    let listener = std::net::TcpListener::bind(&cmd.listen)
+        .with_context(|| format!("Failed to listen on {}", cmd.listen))?;
+    info!("cviz api listening on http://{}", listener.local_addr()?);
+    server::serve(
+        listener,
+        server::ServerConfig {
+            max_body: cmd.max_body_size,
+            workers: cmd.max_concurrency,
+        },
+    )
+}
+
+/// The `cviz stats` report.
+fn stats_report(
+    graph: &CompositionGraph,
+    format: ReportFormat,
+    health_weights: Option<&Path>,
+) -> Result<String> {
+    let stats = graph_stats(graph, &read_health_weights(health_weights)?);
+    Ok(match format {
+        ReportFormat::Markdown => stats.to_markdown(),
+        ReportFormat::Json => serde_json::to_string_pretty(&stats)?,
+        _ => stats.to_text(),
+    })
+}
+
+/// The `cviz health` report.
+fn health_report(
+    graph: &CompositionGraph,
+    format: ReportFormat,
+    health_weights: Option<&Path>,
+) -> Result<String> {
+    let report = health_score(graph, &read_health_weights(health_weights)?);
+    Ok(match format {
+        ReportFormat::Markdown => report.to_markdown(),
+        ReportFormat::Json => serde_json::to_string_pretty(&report)?,
+        _ => report.to_text(),
+    })
+}
+
+/// The `cviz reachable` report.
+fn reachable_report(
+    graph: &CompositionGraph,
+    export: &str,
+    format: ReportFormat,
+) -> Result<String> {
+    let reach = reachable_from_export(graph, export).with_context(|| {
+        let known: Vec<&str> = graph
+            .component_exports
+            .iter()
+            .map(|e| e.name.as_str())
+            .collect();
+        format!(
+            "No single export matches '{}' (exported: {})",
+            export,
+            known.join(", ")
+        )
+    })?;
+    Ok(match format {
+        ReportFormat::Json => serde_json::to_string_pretty(&reach)?,
+        _ => reach.to_text(),
+    })
+}
+
+/// The `cviz capabilities` report.
+fn capabilities_report(graph: &CompositionGraph, format: ReportFormat) -> Result<String> {
+    let matrix = capability_matrix(graph);
+    Ok(match format {
+        ReportFormat::Csv => matrix.to_csv(),
+        ReportFormat::Json => serde_json::to_string_pretty(&matrix)?,
+        _ => matrix.to_table(),
+    })
+}
+
+/// The `cviz list order` report.
+fn order_report(graph: &CompositionGraph, format: ReportFormat) -> Result<String> {
+    let order = instantiation_order(graph);
+    Ok(match format {
+        ReportFormat::Json => serde_json::to_string_pretty(&order)?,
+        _ => order_text(&order),
+    })
+}
+
+/// The `cviz list renames` report.
+fn renames_report(graph: &CompositionGraph, format: ReportFormat) -> Result<String> {
+    let plugs = renamed_plugs(graph);
+    Ok(match format {
+        ReportFormat::Json => serde_json::to_string_pretty(&plugs)?,
+        _ => plugs_table(&plugs),
+    })
+}
+
+/// The `cviz list instances` report.
+fn instances_report(graph: &CompositionGraph, format: ReportFormat) -> Result<String> {
+    let instances = instance_summaries(graph);
+    Ok(match format {
+        ReportFormat::Json => serde_json::to_string_pretty(&instances)?,
+        _ => instances_table(&instances),
+    })
+}
+
+/// The `cviz list exports` report.
+fn exports_report(graph: &CompositionGraph, format: ReportFormat) -> Result<String> {
+    let exports = export_summaries(graph);
+    Ok(match format {
+        ReportFormat::Json => serde_json::to_string_pretty(&exports)?,
+        _ => exports_table(&exports),
+    })
+}
+
+/// The `cviz list interfaces` report.
+fn interfaces_report(graph: &CompositionGraph, format: ReportFormat) -> Result<String> {
+    let uses = interface_uses(graph);
+    Ok(match format {
+        ReportFormat::Json => serde_json::to_string_pretty(&uses)?,
+        _ => interfaces_table(&uses),
+    })
+}
+
+/// The report asked for with one of the graph command's deprecated report
+/// flags, produced by the code behind the subcommand that replaced it.
+fn deprecated_report(graph: &CompositionGraph, graph_args: &GraphArgs) -> Result<Option<String>> {
+    let weights = graph_args.health_weights.as_deref();
+    let (flag, replacement, report) = if let Some(format) = graph_args.health {
+        (
+            "--health",
+            "cviz health",
+            health_report(graph, format, weights)?,
+        )
+    } else if let Some(format) = graph_args.stats {
+        (
+            "--stats",
+            "cviz stats",
+            stats_report(graph, format, weights)?,
+        )
+    } else if let Some(format) = graph_args.order {
+        ("--order", "cviz list order", order_report(graph, format)?)
+    } else if let Some(export) = &graph_args.reachable {
+        let format = match graph_args.format {
+            OutputFormat::Ascii => ReportFormat::Text,
+            OutputFormat::Json | OutputFormat::JsonPretty => ReportFormat::Json,
+            OutputFormat::Mermaid => {
+                anyhow::bail!("--reachable supports the ascii and json formats")
+            }
+        };
+        (
+            "--reachable",
+            "cviz reachable",
+            reachable_report(graph, export, format)?,
+        )
+    } else if let Some(format) = graph_args.capabilities {
+        (
+            "--capabilities",
+            "cviz capabilities",
+            capabilities_report(graph, format)?,
+        )
+    } else if let Some(format) = graph_args.renames {
+        (
+            "--renames",
+            "cviz list renames",
+            renames_report(graph, format)?,
+        )
+    } else {
+        return Ok(None);
+    };
+    warn!("{} is deprecated; use `{}` instead", flag, replacement);
+    Ok(Some(report))
+}
+
+/// Draw the composition in `file` as `graph_args` asks, writing it to
+/// `output` (stdout if `None`).
+fn draw(
+    file: &Path,
+    output: Option<&Path>,
+    graph_args: &GraphArgs,
+    args: &Args,
+    matches: &ArgMatches,
+    sources: &Sources,
+) -> Result<()> {
+    let drawing = render(file, is_stdout(output), graph_args, args, matches, sources)?;
+    if drawing.diagram {
+        write_diagram(output, &drawing.text, file, graph_args)
+    } else {
+        write_output(output, &drawing.text)
+    }
+}
+
+/// What [`render`] produced for one input.
+struct Drawing {
+    text: String,
+    /// Whether `text` is a diagram, which `--open` can preview, rather than
+    /// a report asked for with a deprecated flag such as `--health`.
+    diagram: bool,
+}
+
+/// Render the composition in `file` as `graph_args` asks, for stdout if
+/// `to_stdout`.
+fn render(
+    file: &Path,
+    to_stdout: bool,
+    graph_args: &GraphArgs,
+    args: &Args,
+    matches: &ArgMatches,
+    sources: &Sources,
+) -> Result<Drawing> {
+    let file = match &graph_args.registry {
+        Some(registry) => {
+            let spec = file.to_string_lossy();
+            let source = fetch::resolve_package(registry, &spec)
+                .with_context(|| format!("Failed to resolve {} on {}", spec, registry))?;
+            match source {
+                fetch::PackageSource::Oci(reference) => {
+                    PathBuf::from(format!("{}{}", fetch::OCI_SCHEME, reference))
+                }
+                fetch::PackageSource::Warg {
+                    url,
+                    package,
+                    version,
+                } => fetch::pull_warg(&url, &package, version.as_deref(), &sources.fetch)
+                    .with_context(|| format!("Failed to pull {} from {}", spec, url))?,
+            }
+        }
+        None => file.to_path_buf(),
+    };
+    let mut graph = match load_graph(&file, sources) {
+        Ok(graph) => graph,
+        Err(err) => match err.downcast_ref::<CvizError>() {
+            Some(CvizError::NotAComponent(NotAComponent(module))) => {
+                info!(
+                    "{} is a core wasm module, not a component; showing its imports and exports",
+                    file.display()
+                );
+                let rendered = match graph_args.format {
+                    OutputFormat::Json => output::json::generate_module_json(module, false)?,
+                    OutputFormat::JsonPretty => output::json::generate_module_json(module, true)?,
+                    OutputFormat::Ascii => output::ascii::generate_module_ascii(module),
+                    OutputFormat::Mermaid => {
+                        output::mermaid::generate_module_mermaid(module, graph_args.direction)
+                    }
+                };
+                return Ok(Drawing {
+                    text: rendered,
+                    diagram: true,
+                });
+            }
+            _ => return Err(err),
+        },
+    };
+
+    reshape(&mut graph, args);
+
+    if let Some(path) = &graph_args.names {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read name mapping: {}", path.display()))?;
+        let names: NameMap = serde_json::from_str(&text)
+            .with_context(|| format!("Failed to parse name mapping: {}", path.display()))?;
+        let mut resolvers: Vec<Box<dyn NameResolver>> = vec![Box::new(names)];
+        resolvers.extend(naming::default_resolvers());
+        naming::resolve_labels(&mut graph, &resolvers);
+    }
+    if let Some(path) = &graph_args.labels {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read label overrides: {}", path.display()))?;
+        let labels: LabelOverrides = toml::from_str(&text)
+            .with_context(|| format!("Failed to parse label overrides: {}", path.display()))?;
+        labels.apply(&mut graph);
+    }
+
+    let levels = lint_levels(args, matches)?;
+    let diagnostics = diagnose(&graph, &levels);
+    for diag in &diagnostics {
+        match diag.level {
+            LintLevel::Deny => error!("[{}] {}", diag.category, diag.message),
+            _ => warn!("[{}] {}", diag.category, diag.message),
+        }
+    }
+    fail_on_denied(&diagnostics)?;
+
+    if let Some(name) = &graph_args.require_chain {
+        let chains = find_chain_interfaces(&graph);
+        if !chains.iter().any(|chain| chain.contains(name.as_str())) {
+            let exported = exported_interfaces(&graph);
+            let exported = if exported.is_empty() {
+                "none".to_string()
+            } else {
+                exported.join(", ")
+            };
+            if name.is_empty() {
+                anyhow::bail!("No handler chain found (exported: {})", exported);
+            }
+            anyhow::bail!(
+                "No handler chain exports an interface matching '{}' (exported: {})",
+                name,
+                exported
+            );
+        }
+    }
+
+    if let Some(report) = deprecated_report(&graph, graph_args)? {
+        return Ok(Drawing {
+            text: report,
+            diagram: false,
+        });
+    }
+
+    // OSC 8 hyperlinks only make sense when writing straight to a terminal
+    // that renders them; anywhere else they show up as escape garbage
+    let hyperlinks = match graph_args.hyperlinks {
+        Hyperlinks::Always => true,
+        Hyperlinks::Never => false,
+        Hyperlinks::Auto => {
+            to_stdout
+                && std::io::stdout().is_terminal()
+                && supports_hyperlinks(|var| std::env::var(var).ok())
+        }
+    };
+    let links = (graph_args.interface_url.is_some() || graph_args.node_url.is_some())
+        .then(|| LinkTemplates {
+            interface: graph_args.interface_url.clone(),
+            node: graph_args.node_url.clone(),
+        })
+        .filter(|_| hyperlinks);
+    let render_opts = RenderOptions {
+        show_types: graph_args.types,
+        links,
+        legend: graph_args.legend,
+        interface: graph_args.interface.clone(),
+        group_host: graph_args.group_host,
+        hide_host: graph_args.no_host,
+        show_synthetic: graph_args.show_synthetic,
+        group_namespaces: graph_args.group_namespaces,
+        labels: label_style(graph_args)?,
+        highlight: Vec::new(),
+    };
+    if let Some(interface) = &graph_args.interface {
+        if chain_interfaces_matching(&graph, Some(interface)).is_empty() {
+            anyhow::bail!(
+                "No exported interface matches '{}' (exported: {})",
+                interface,
+                exported_interfaces(&graph).join(", ")
+            );
+        }
+    }
+
+    let root = match &graph_args.root {
+        Some(name) => Some(
+            graph
+                .find_node(name)
+                .with_context(|| unknown_instance(&graph, name))?,
+        ),
+        None => None,
+    };
 
     // Generate the diagram based on format
-    let diagram = match args.format {
-        OutputFormat::Ascii => output::ascii::generate_ascii(&graph, args.detail, args.types),
-        OutputFormat::Mermaid => {
-            output::mermaid::generate_mermaid(&graph, args.detail, args.direction, args.types)
+    let diagram = match (graph_args.format, root) {
+        (OutputFormat::Ascii, Some(root)) => {
+            output::ascii::generate_dependency_ascii(&graph, root, &render_opts)
+        }
+        (OutputFormat::Mermaid, Some(root)) => {
+            output::mermaid::generate_dependency_mermaid_with_options(
+                &graph,
+                root,
+                graph_args.direction,
+                &render_opts,
+            )
         }
-        OutputFormat::Json => output::json::generate_json(&graph, false)?, // always generates the full graph
-        OutputFormat::JsonPretty => output::json::generate_json(&graph, true)?, // always generates the full graph
+        (OutputFormat::Ascii, None) => {
+            output::ascii::generate_ascii_with_options(&graph, graph_args.detail, &render_opts)
+        }
+        (OutputFormat::Mermaid, None) => output::mermaid::generate_mermaid_with_options(
+            &graph,
+            graph_args.detail,
+            graph_args.direction,
+            &render_opts,
+        ),
+        (OutputFormat::Json, _) => output::json::generate_json(&graph, false)?, // always generates the full graph
+        (OutputFormat::JsonPretty, _) => output::json::generate_json(&graph, true)?, // always generates the full graph
     };
 
-    // Output
-    if let Some(output_path) = args.output {
-        std::fs::write(&output_path, &diagram)
-            .with_context(|| format!("Failed to write output: {}", output_path.display()))?;
-        eprintln!("Diagram written to: {}", output_path.display());
+    Ok(Drawing {
+        text: diagram,
+        diagram: true,
+    })
+}
+
+/// Write a drawn diagram to `output` or stdout, or with `--open` preview it
+/// in the browser (still writing it to `output` if one was given).
+fn write_diagram(
+    output: Option<&Path>,
+    diagram: &str,
+    file: &Path,
+    graph_args: &GraphArgs,
+) -> Result<()> {
+    if !graph_args.open {
+        return write_output(output, diagram);
+    }
+    if !matches!(graph_args.format, OutputFormat::Mermaid) {
+        anyhow::bail!("--open supports the mermaid format only");
+    }
+    if output.is_some() {
+        write_output(output, diagram)?;
+    }
+
+    let stem = file
+        .file_stem()
+        .map_or_else(|| "stdin".into(), |n| n.to_string_lossy());
+    let page = std::env::temp_dir().join(format!("cviz-{}.html", stem));
+    let html = output::mermaid::generate_html_page(diagram, &file.to_string_lossy());
+    std::fs::write(&page, html)
+        .with_context(|| format!("Failed to write preview: {}", page.display()))?;
+    match open_in_browser(&page) {
+        Ok(()) => info!("Opened {}", page.display()),
+        Err(err) => {
+            warn!("Could not open {} in a browser: {:#}", page.display(), err);
+            println!("{}", output::mermaid::live_editor_url(diagram));
+        }
+    }
+    Ok(())
+}
+
+/// Open `path` with the platform's default handler.
+fn open_in_browser(path: &Path) -> Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        std::process::Command::new("open")
+    } else if cfg!(windows) {
+        let mut command = std::process::Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
     } else {
-        println!("{}", diagram);
+        std::process::Command::new("xdg-open")
+    };
+    let status = command
+        .arg(path)
+        .status()
+        .with_context(|| format!("Failed to run {:?}", command.get_program()))?;
+    if !status.success() {
+        anyhow::bail!("{:?} exited with {}", command.get_program(), status);
+    }
+    Ok(())
+}
+
+/// The inputs named on the command line, with each directory replaced by
+/// the files in it whose name matches `glob`, sorted by name.
+fn batch_inputs(files: &[PathBuf], glob: &str) -> Result<Vec<PathBuf>> {
+    let mut inputs = Vec::new();
+    for file in files {
+        if !file.is_dir() {
+            inputs.push(file.clone());
+            continue;
+        }
+        let entries = std::fs::read_dir(file)
+            .with_context(|| format!("Failed to read directory: {}", file.display()))?;
+        let mut found = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            let matched = path
+                .file_name()
+                .is_some_and(|name| glob_match(glob, &name.to_string_lossy()));
+            if matched && path.is_file() {
+                found.push(path);
+            }
+        }
+        if found.is_empty() {
+            anyhow::bail!("No files in {} match '{}'", file.display(), glob);
+        }
+        found.sort();
+        inputs.extend(found);
+    }
+    Ok(inputs)
+}
+
+/// The name `template` gives the output for `input`, with `{ext}` chosen
+/// by `--format`.
+fn output_name(template: &str, input: &Path, format: OutputFormat) -> String {
+    let ext = match format {
+        OutputFormat::Ascii => "txt",
+        OutputFormat::Mermaid => "mmd",
+        OutputFormat::Json | OutputFormat::JsonPretty => "json",
+    };
+    let name = input
+        .file_name()
+        .map_or_else(|| "stdin".into(), |n| n.to_string_lossy());
+    let stem = input
+        .file_stem()
+        .map_or_else(|| "stdin".into(), |n| n.to_string_lossy());
+    template
+        .replace("{stem}", &stem)
+        .replace("{name}", &name)
+        .replace("{ext}", ext)
+}
+
+/// The interface label style from `--label-style`, with the label flags
+/// layered on top.  Rewrites given on the command line are tried first.
+fn label_style(args: &GraphArgs) -> Result<LabelStyle> {
+    let mut style = match &args.label_style {
+        Some(path) => {
+            let text = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read label style: {}", path.display()))?;
+            toml::from_str(&text)
+                .with_context(|| format!("Failed to parse label style: {}", path.display()))?
+        }
+        None => LabelStyle::default(),
+    };
+    style.keep_namespace |= args.keep_namespace;
+    style.keep_package |= args.keep_package;
+    style.keep_version |= args.show_versions;
+    style
+        .rewrites
+        .splice(0..0, args.label_rewrite.iter().cloned());
+    Ok(style)
+}
+
+/// The FILE argument that reads from stdin.
+const STDIN: &str = "-";
+
+/// The `-o` argument that writes to stdout.
+const STDOUT: &str = "-";
+
+/// Where [`load_graph`] fetches remote inputs from and what it pairs with
+/// local ones.
+struct Sources {
+    fetch: fetch::FetchOptions,
+    /// Root component of a wasm-compose config.
+    compose_input: Option<PathBuf>,
+    /// What inputs are, if not guessed.
+    input_format: InputFormat,
+    /// How components are parsed.
+    parse: ParseOptions,
+    /// Show a spinner on stderr while parsing large components.
+    progress: bool,
+}
+
+/// Load a graph from a `.json` file written by `--format json`, from `.wac`
+/// composition source, a wadm manifest or a wasm-compose `.yml` config, or
+/// parse it from a `.wasm` component otherwise, unless `--input-format`
+/// says which it is.  `-` reads from stdin and an `http(s)://` URL downloads,
+/// telling a component from a JSON graph by the wasm magic number; `oci://`
+/// pulls a component from a registry.
+fn load_graph(path: &Path, sources: &Sources) -> Result<CompositionGraph> {
+    let (bytes, source, remote) = read_input(path, sources)?;
+
+    let format = match sources.input_format {
+        InputFormat::Auto if remote => {
+            if bytes.starts_with(b"\0asm") {
+                InputFormat::Wasm
+            } else {
+                InputFormat::Json
+            }
+        }
+        InputFormat::Auto => match path.extension().and_then(|ext| ext.to_str()) {
+            Some("wac") => InputFormat::Wac,
+            Some("yml" | "yaml") => {
+                if cviz::parse::wadm::is_manifest(&String::from_utf8_lossy(&bytes)) {
+                    InputFormat::Wadm
+                } else {
+                    InputFormat::Compose
+                }
+            }
+            Some("json") => InputFormat::Json,
+            _ => InputFormat::Wasm,
+        },
+        format => format,
+    };
+
+    if let InputFormat::Wasm = format {
+        let guessed = matches!(sources.input_format, InputFormat::Auto);
+        let _spinner = progress::Spinner::start(
+            format!(
+                "Parsing {} ({:.1} MiB)",
+                source,
+                bytes.len() as f64 / (1024.0 * 1024.0)
+            ),
+            sources.progress,
+        );
+        return parse_component_with_options(&bytes, &sources.parse).with_context(|| {
+            if guessed && !bytes.starts_with(b"\0asm") {
+                format!(
+                    "Failed to parse component: {} (use --input-format if it is not a component)",
+                    source
+                )
+            } else {
+                format!("Failed to parse component: {}", source)
+            }
+        });
+    }
+    let text = String::from_utf8(bytes).with_context(|| match sources.input_format {
+        InputFormat::Auto => format!("{} is neither a .wasm component nor a JSON graph", source),
+        _ => format!("{} is not UTF-8 text", source),
+    })?;
+    match format {
+        InputFormat::Json => cviz::parse::json::parse_json_str(&text)
+            .with_context(|| format!("Failed to parse JSON graph: {}", source)),
+        InputFormat::Wac => cviz::parse::wac::parse_wac_str(&text)
+            .with_context(|| format!("Failed to parse WAC source: {}", source)),
+        InputFormat::Wadm => cviz::parse::wadm::Manifest::from_yaml(&text)
+            .and_then(|manifest| manifest.to_graph())
+            .with_context(|| format!("Failed to parse wadm manifest: {}", source)),
+        InputFormat::Compose => {
+            let input = match &sources.compose_input {
+                Some(input) => Some(
+                    std::fs::read(input)
+                        .with_context(|| format!("Failed to read file: {}", input.display()))?,
+                ),
+                None => None,
+            };
+            let base_dir = if remote {
+                Path::new(".")
+            } else {
+                path.parent().unwrap_or(Path::new("."))
+            };
+            cviz::parse::compose::ComposeConfig::from_yaml(&text)
+                .and_then(|config| config.to_graph(base_dir, input.as_deref()))
+                .with_context(|| format!("Failed to parse wasm-compose config: {}", source))
+        }
+        InputFormat::Auto | InputFormat::Wasm => unreachable!("resolved above"),
     }
+}
+
+/// Load a graph with [`load_graph`] and [`reshape`] it as the global flags
+/// ask.
+fn load_reshaped(path: &Path, args: &Args, sources: &Sources) -> Result<CompositionGraph> {
+    let mut graph = load_graph(path, sources)?;
+    reshape(&mut graph, args);
+    Ok(graph)
+}
+
+/// The error context for an instance `name` that is not in `graph`.
+fn unknown_instance(graph: &CompositionGraph, name: &str) -> String {
+    let known: Vec<&str> = graph.nodes.values().map(|n| n.display_label()).collect();
+    format!(
+        "No instance named '{}' (available: {})",
+        name,
+        known.join(", ")
+    )
+}
+
+/// Read the bytes of an input, fetching URLs and registry references: the
+/// bytes, a name for them in messages and whether they came from the network.
+fn read_input(path: &Path, sources: &Sources) -> Result<(Vec<u8>, String, bool)> {
+    let fetch = &sources.fetch;
+    let (bytes, source, remote) =
+        if let Some(reference) = path.to_str().filter(|p| p.starts_with(fetch::OCI_SCHEME)) {
+            let bytes = fetch::OciReference::parse(reference)
+                .and_then(|r| fetch::pull(&r, fetch))
+                .with_context(|| format!("Failed to pull {}", reference))?;
+            (bytes, reference.to_string(), true)
+        } else if let Some(url) = path.to_str().filter(|p| fetch::is_url(p)) {
+            (fetch::download(url, fetch)?, url.to_string(), true)
+        } else if path == Path::new(STDIN) {
+            let mut bytes = Vec::new();
+            std::io::stdin()
+                .read_to_end(&mut bytes)
+                .context("Failed to read from stdin")?;
+            (bytes, "stdin".to_string(), true)
+        } else {
+            let bytes = std::fs::read(path)
+                .with_context(|| format!("Failed to read file: {}", path.display()))?;
+            (bytes, path.display().to_string(), false)
+        };
+    Ok((bytes, source, remote))
+}
 
+/// Resolve lint levels: the `--lints` file first, then `--allow`, `--warn`
+/// and `--deny` in the order they appear on the command line, so that a
+/// later flag overrides an earlier one (as with rustc's `-A`/`-W`/`-D`),
+/// and finally `--deny-warnings`.
+fn lint_levels(args: &Args, matches: &ArgMatches) -> Result<LintLevels> {
+    let mut levels = match &args.lints {
+        Some(path) => {
+            let text = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read lint levels: {}", path.display()))?;
+            serde_json::from_str(&text)
+                .with_context(|| format!("Failed to parse lint levels: {}", path.display()))?
+        }
+        None => LintLevels::default(),
+    };
+
+    let mut flags = Vec::new();
+    for (id, level, values) in [
+        ("allow", LintLevel::Allow, &args.allow),
+        ("warn", LintLevel::Warn, &args.warn),
+        ("deny", LintLevel::Deny, &args.deny),
+    ] {
+        if let Some(indices) = matches.indices_of(id) {
+            flags.extend(indices.zip(values).map(|(i, &c)| (i, c, level)));
+        }
+    }
+    flags.sort_by_key(|&(i, _, _)| i);
+    for (_, category, level) in flags {
+        levels.set(category, level);
+    }
+    if args.deny_warnings {
+        levels.deny_warnings();
+    }
+    Ok(levels)
+}
+
+/// The health score weights in the `--health-weights` file at `path`, or
+/// the defaults.
+fn read_health_weights(path: Option<&Path>) -> Result<HealthWeights> {
+    let Some(path) = path else {
+        return Ok(HealthWeights::default());
+    };
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read health weights: {}", path.display()))?;
+    serde_json::from_str(&text)
+        .with_context(|| format!("Failed to parse health weights: {}", path.display()))
+}
+
+/// Apply `--depth` and `--collapse-duplicates` to a loaded graph.
+fn reshape(graph: &mut CompositionGraph, args: &Args) {
+    if let Some(depth) = args.depth {
+        graph.collapse_nested(depth);
+    }
+    if args.collapse_duplicates {
+        graph.collapse_duplicates();
+    }
+    graph.filter_nodes(&args.only_node, &args.exclude_node);
+    graph.filter_interfaces(&args.only_interface, &args.exclude_interface);
+}
+
+/// Fail the run if any of `diagnostics` is denied.
+fn fail_on_denied(diagnostics: &[Diagnostic]) -> Result<()> {
+    let denied = diagnostics
+        .iter()
+        .filter(|d| d.level == LintLevel::Deny)
+        .count();
+    if denied > 0 {
+        anyhow::bail!("aborting due to {} denied diagnostic(s)", denied);
+    }
     Ok(())
 }
+
+/// Write `content` to `path`, or to stdout if no path was given or it is `-`.
+fn write_output(path: Option<&Path>, content: &str) -> Result<()> {
+    match path.filter(|_| !is_stdout(path)) {
+        Some(output_path) => {
+            std::fs::write(output_path, content)
+                .with_context(|| format!("Failed to write output: {}", output_path.display()))?;
+            info!("Output written to: {}", output_path.display());
+            Ok(())
+        }
+        None => write_stdout(format!("{}\n", content).as_bytes()),
+    }
+}
+
+/// Whether output to `path` goes to stdout: without `-o`, or with `-o -`.
+fn is_stdout(path: Option<&Path>) -> bool {
+    path.is_none_or(|p| p == Path::new(STDOUT))
+}
+
+/// Write `bytes` to stdout unchanged.  A reader that stops early, as `head`
+/// does, ends the output instead of failing the run.
+fn write_stdout(bytes: &[u8]) -> Result<()> {
+    let mut stdout = std::io::stdout().lock();
+    match stdout.write_all(bytes).and_then(|()| stdout.flush()) {
+        Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => Ok(()),
+        result => result.context("Failed to write to stdout"),
+    }
+}
+
+/// Creation time for `cviz sbom`: `SOURCE_DATE_EPOCH` when set, so builds
+/// can produce identical documents, or else now, as RFC 3339 UTC.
+fn sbom_timestamp() -> Result<String> {
+    let secs = match std::env::var("SOURCE_DATE_EPOCH") {
+        Ok(epoch) => epoch
+            .trim()
+            .parse::<u64>()
+            .with_context(|| format!("Invalid SOURCE_DATE_EPOCH: {}", epoch))?,
+        Err(_) => std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs(),
+    };
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let days = (secs / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let doe = days - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    Ok(format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs % 86400 / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    ))
+}
@@ -0,0 +1,63 @@
+//! A stderr spinner for steps that take long enough to look like a hang,
+//! such as parsing a multi-hundred-MB component.
+
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// How long a step runs before the spinner appears, so quick ones stay quiet.
+const DELAY: Duration = Duration::from_millis(500);
+const FRAMES: [char; 4] = ['|', '/', '-', '\\'];
+
+/// Draws `<frame> <label> <elapsed>` on stderr until dropped.
+pub struct Spinner {
+    stop: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Spinner {
+    /// Start a spinner for `label`; when `enabled` is false nothing is drawn.
+    pub fn start(label: String, enabled: bool) -> Self {
+        if !enabled {
+            return Spinner {
+                stop: None,
+                thread: None,
+            };
+        }
+        let (stop, stopped) = mpsc::channel::<()>();
+        let thread = std::thread::spawn(move || {
+            let started = Instant::now();
+            if stopped.recv_timeout(DELAY) != Err(RecvTimeoutError::Timeout) {
+                return;
+            }
+            for frame in FRAMES.iter().cycle() {
+                eprint!(
+                    "\r\x1b[2K{} {} {:.1}s",
+                    frame,
+                    label,
+                    started.elapsed().as_secs_f64()
+                );
+                if stopped.recv_timeout(Duration::from_millis(100))
+                    != Err(RecvTimeoutError::Timeout)
+                {
+                    break;
+                }
+            }
+            eprint!("\r\x1b[2K");
+        });
+        Spinner {
+            stop: Some(stop),
+            thread: Some(thread),
+        }
+    }
+}
+
+impl Drop for Spinner {
+    fn drop(&mut self) {
+        // Dropping the sender wakes the thread, which clears its line.
+        self.stop.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
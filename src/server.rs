@@ -0,0 +1,618 @@
+//! The HTTP servers behind `cviz api` and `cviz serve`.
+//!
+//! `cviz api` ([`serve`]) exposes parsing and rendering:
+//!
+//! - `POST /parse` — body is a `.wasm` component; responds with the JSON
+//!   graph and an `X-Cviz-Graph` header identifying it for `GET /render`.
+//! - `POST /render?format=mermaid` — body is a `.wasm` component; responds
+//!   with the rendered diagram.
+//! - `GET /render?graph=<id>&format=mermaid` — renders a graph returned by
+//!   an earlier `POST /parse`.
+//! - `GET /health` — liveness probe.
+//!
+//! `cviz serve` ([`serve_file`]) shows one composition while it is worked on:
+//!
+//! - `GET /` — an HTML page drawing the diagram, redrawn when it changes.
+//! - `GET /render` — the diagram, from the file as it is right now.
+//! - `GET /health` — liveness probe.
+//!
+//! `/render` accepts the same `format`, `detail` and `direction` values as
+//! the command line.  The server deliberately supports only what these
+//! endpoints need: one request per connection, `Content-Length` bodies and
+//! no TLS (put it behind a proxy for that).
+
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::mpsc::{sync_channel, Receiver, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use cviz::model::CompositionGraph;
+use cviz::output::{self, DetailLevel, Direction, OutputFormat, RenderOptions};
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
+/// Longest request line plus headers we accept.
+const MAX_HEAD_BYTES: usize = 16 * 1024;
+
+/// Parsed graphs kept for `GET /render`; the oldest is evicted first.
+const CACHED_GRAPHS: usize = 64;
+
+/// How long a client may take to send its request or read the response.
+const IO_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Limits applied to every connection.
+#[derive(Debug, Clone, Copy)]
+pub struct ServerConfig {
+    /// Largest request body, in bytes; larger requests get `413`.
+    pub max_body: usize,
+    /// Requests handled at once; further connections wait in a queue of the
+    /// same length, and get `503` once it is full.
+    pub workers: usize,
+}
+
+/// A function answering every request a server accepts.
+type Handler = dyn Fn(&Request) -> Response + Send + Sync;
+
+/// Serve the `cviz api` endpoints on `listener` until the process is stopped.
+pub fn serve(listener: TcpListener, config: ServerConfig) -> Result<()> {
+    let cache = GraphCache::default();
+    run(
+        listener,
+        config,
+        Arc::new(move |request: &Request| route(request, &cache)),
+    )
+}
+
+/// Serve a live view of one composition on `listener` until the process is
+/// stopped, calling `load` for a fresh graph on every request so edits to
+/// the input show up without a restart.
+pub fn serve_file(
+    listener: TcpListener,
+    config: ServerConfig,
+    title: String,
+    load: impl Fn() -> Result<CompositionGraph> + Send + Sync + 'static,
+) -> Result<()> {
+    let page = live_page(&title);
+    run(
+        listener,
+        config,
+        Arc::new(move |request: &Request| route_file(request, &page, &load)),
+    )
+}
+
+fn run(listener: TcpListener, config: ServerConfig, handler: Arc<Handler>) -> Result<()> {
+    let workers = config.workers.max(1);
+    let (tx, rx) = sync_channel::<TcpStream>(workers);
+    let rx = Arc::new(Mutex::new(rx));
+
+    for i in 0..workers {
+        let rx = Arc::clone(&rx);
+        let handler = Arc::clone(&handler);
+        thread::Builder::new()
+            .name(format!("cviz-http-{}", i))
+            .spawn(move || worker(&rx, &*handler, config))
+            .context("Failed to start worker thread")?;
+    }
+
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                warn!("accept failed: {}", e);
+                continue;
+            }
+        };
+        match tx.try_send(stream) {
+            Ok(()) => {}
+            Err(TrySendError::Full(mut stream)) => {
+                let _ = stream.set_write_timeout(Some(IO_TIMEOUT));
+                let _ = Response::error(503, "server busy, retry later").write_to(&mut stream);
+            }
+            Err(TrySendError::Disconnected(_)) => anyhow::bail!("all server workers exited"),
+        }
+    }
+    Ok(())
+}
+
+fn worker(rx: &Mutex<Receiver<TcpStream>>, handler: &Handler, config: ServerConfig) {
+    loop {
+        let stream = match rx.lock().expect("worker queue poisoned").recv() {
+            Ok(stream) => stream,
+            Err(_) => return,
+        };
+        if let Err(e) = handle_connection(stream, handler, config) {
+            warn!("{:#}", e);
+        }
+    }
+}
+
+fn handle_connection(mut stream: TcpStream, handler: &Handler, config: ServerConfig) -> Result<()> {
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+
+    let response = match read_request(&mut BufReader::new(&stream), config.max_body) {
+        Ok(request) => catch_unwind(AssertUnwindSafe(|| handler(&request)))
+            .unwrap_or_else(|_| Response::error(500, "internal error while handling request")),
+        Err(response) => response,
+    };
+    response
+        .write_to(&mut stream)
+        .context("Failed to write response")
+}
+
+/// A request as far as the API cares about it.
+#[derive(Debug)]
+struct Request {
+    method: String,
+    path: String,
+    query: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Request {
+    fn param(&self, key: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Read one request, or the error response to send instead.
+fn read_request(reader: &mut impl BufRead, max_body: usize) -> Result<Request, Response> {
+    let mut head = Vec::new();
+    loop {
+        let before = head.len();
+        let read = reader
+            .take((MAX_HEAD_BYTES + 1 - head.len()) as u64)
+            .read_until(b'\n', &mut head)
+            .map_err(|_| Response::error(408, "timed out reading request"))?;
+        if head.len() > MAX_HEAD_BYTES {
+            return Err(Response::error(431, "request headers too large"));
+        }
+        if read == 0 {
+            return Err(Response::error(400, "incomplete request"));
+        }
+        if head[before..] == *b"\r\n" || head[before..] == *b"\n" {
+            break;
+        }
+    }
+
+    let head = String::from_utf8(head).map_err(|_| Response::error(400, "invalid header"))?;
+    let mut lines = head.lines();
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let (method, target) = match (request_line.next(), request_line.next()) {
+        (Some(method), Some(target)) => (method.to_string(), target),
+        _ => return Err(Response::error(400, "malformed request line")),
+    };
+
+    let mut content_length = 0;
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("content-length") {
+            content_length = value
+                .parse()
+                .map_err(|_| Response::error(400, "invalid Content-Length"))?;
+        } else if name.eq_ignore_ascii_case("transfer-encoding") {
+            return Err(Response::error(411, "chunked bodies are not supported"));
+        }
+    }
+    if content_length > max_body {
+        return Err(Response::error(
+            413,
+            &format!("request body exceeds the {} byte limit", max_body),
+        ));
+    }
+
+    let mut body = vec![0; content_length];
+    reader
+        .read_exact(&mut body)
+        .map_err(|_| Response::error(400, "request body shorter than Content-Length"))?;
+
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    Ok(Request {
+        method,
+        path: path.to_string(),
+        query: query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (k, v) = pair.split_once('=').unwrap_or((pair, ""));
+                (percent_decode(k), percent_decode(v))
+            })
+            .collect(),
+        body,
+    })
+}
+
+fn route(request: &Request, cache: &GraphCache) -> Response {
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/health") => Response::new(200, "text/plain", "ok\n"),
+        ("POST", "/parse") => match parse_body(request, cache) {
+            Ok((id, graph)) => match output::json::generate_json(&graph, false) {
+                Ok(json) => {
+                    Response::new(200, "application/json", &json).header("X-Cviz-Graph", id)
+                }
+                Err(e) => Response::error(500, &e.to_string()),
+            },
+            Err(response) => response,
+        },
+        ("POST", "/render") => match parse_body(request, cache) {
+            Ok((_, graph)) => render(request, &graph),
+            Err(response) => response,
+        },
+        ("GET", "/render") => match request.param("graph") {
+            Some(id) => match cache.get(id) {
+                Some(graph) => render(request, &graph),
+                None => Response::error(404, &format!("unknown graph: {}", id)),
+            },
+            None => Response::error(400, "missing 'graph' parameter; POST the component instead"),
+        },
+        (_, "/health" | "/parse" | "/render") => Response::error(405, "method not allowed"),
+        _ => Response::error(404, "not found"),
+    }
+}
+
+fn route_file(
+    request: &Request,
+    page: &str,
+    load: &dyn Fn() -> Result<CompositionGraph>,
+) -> Response {
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/") => Response::new(200, "text/html; charset=utf-8", page),
+        ("GET", "/health") => Response::new(200, "text/plain", "ok\n"),
+        ("GET", "/render") => match load() {
+            Ok(graph) => render(request, &graph),
+            Err(e) => Response::error(422, &format!("{:#}", e)),
+        },
+        (_, "/" | "/health" | "/render") => Response::error(405, "method not allowed"),
+        _ => Response::error(404, "not found"),
+    }
+}
+
+/// The page `cviz serve` answers `GET /` with: it polls `/render` with its
+/// own query string and redraws the diagram, or shows the error, whenever
+/// the response changes.
+fn live_page(title: &str) -> String {
+    let title = title
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;");
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<style>
+body {{ font-family: sans-serif; margin: 1.5em; }}
+#error {{ color: #b00020; white-space: pre-wrap; font-family: monospace; }}
+</style>
+<script type="module">
+import mermaid from "https://cdn.jsdelivr.net/npm/mermaid@11/dist/mermaid.esm.min.mjs";
+mermaid.initialize({{ startOnLoad: false }});
+const error = document.getElementById("error");
+const diagram = document.getElementById("diagram");
+let last = null;
+async function refresh() {{
+  try {{
+    const response = await fetch("/render" + location.search);
+    const text = await response.text();
+    if (text !== last) {{
+      last = text;
+      if (response.ok) {{
+        diagram.innerHTML = (await mermaid.render("cviz-diagram", text)).svg;
+        error.textContent = "";
+      }} else {{
+        error.textContent = text;
+      }}
+    }}
+  }} catch (e) {{
+    last = null;
+    error.textContent = "cviz serve is not reachable: " + e;
+  }}
+  setTimeout(refresh, 1000);
+}}
+refresh();
+</script>
+</head>
+<body>
+<h1>{title}</h1>
+<div id="error"></div>
+<div id="diagram"></div>
+</body>
+</html>
+"#
+    )
+}
+
+/// Parse the request body as a component and remember the result.
+fn parse_body(
+    request: &Request,
+    cache: &GraphCache,
+) -> Result<(String, Arc<CompositionGraph>), Response> {
+    if request.body.is_empty() {
+        return Err(Response::error(
+            400,
+            "request body must be a .wasm component",
+        ));
+    }
+    let id = hex::encode(Sha256::digest(&request.body));
+    if let Some(graph) = cache.get(&id) {
+        return Ok((id, graph));
+    }
+    let graph = cviz::parse::component::parse_component(&request.body)
+        .map(Arc::new)
+        .map_err(|e| Response::error(422, &format!("failed to parse component: {:#}", e)))?;
+    cache.insert(id.clone(), Arc::clone(&graph));
+    Ok((id, graph))
+}
+
+fn render(request: &Request, graph: &CompositionGraph) -> Response {
+    fn param<T: std::str::FromStr<Err = String>>(
+        request: &Request,
+        key: &str,
+        default: &str,
+    ) -> Result<T, Response> {
+        request
+            .param(key)
+            .unwrap_or(default)
+            .parse()
+            .map_err(|e: String| Response::error(400, &e))
+    }
+    let options = (|| {
+        Ok::<_, Response>((
+            param::<OutputFormat>(request, "format", "mermaid")?,
+            param::<DetailLevel>(request, "detail", "handler-chain")?,
+            param::<Direction>(request, "direction", "lr")?,
+        ))
+    })();
+    let (format, detail, direction) = match options {
+        Ok(options) => options,
+        Err(response) => return response,
+    };
+
+    let opts = RenderOptions {
+        interface: request.param("interface").map(str::to_string),
+        ..RenderOptions::new(true)
+    };
+
+    let content_type = match format {
+        OutputFormat::Ascii => "text/plain; charset=utf-8",
+        OutputFormat::Mermaid => "text/vnd.mermaid; charset=utf-8",
+        OutputFormat::Json | OutputFormat::JsonPretty => "application/json",
+    };
+    match output::render(graph, format, detail, direction, &opts) {
+        Ok(body) => Response::new(200, content_type, &body),
+        Err(e) => Response::error(500, &e.to_string()),
+    }
+}
+
+/// Recently parsed graphs, keyed by the SHA-256 of the component bytes.
+#[derive(Default)]
+struct GraphCache {
+    graphs: Mutex<VecDeque<(String, Arc<CompositionGraph>)>>,
+}
+
+impl GraphCache {
+    fn get(&self, id: &str) -> Option<Arc<CompositionGraph>> {
+        let graphs = self.graphs.lock().expect("graph cache poisoned");
+        graphs
+            .iter()
+            .find(|(k, _)| k == id)
+            .map(|(_, g)| Arc::clone(g))
+    }
+
+    fn insert(&self, id: String, graph: Arc<CompositionGraph>) {
+        let mut graphs = self.graphs.lock().expect("graph cache poisoned");
+        if graphs.iter().any(|(k, _)| *k == id) {
+            return;
+        }
+        if graphs.len() == CACHED_GRAPHS {
+            graphs.pop_front();
+        }
+        graphs.push_back((id, graph));
+    }
+}
+
+#[derive(Debug)]
+struct Response {
+    status: u16,
+    content_type: &'static str,
+    headers: Vec<(&'static str, String)>,
+    body: String,
+}
+
+impl Response {
+    fn new(status: u16, content_type: &'static str, body: &str) -> Self {
+        Self {
+            status,
+            content_type,
+            headers: Vec::new(),
+            body: body.to_string(),
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self::new(
+            status,
+            "text/plain; charset=utf-8",
+            &format!("{}\n", message),
+        )
+    }
+
+    fn header(mut self, name: &'static str, value: String) -> Self {
+        self.headers.push((name, value));
+        self
+    }
+
+    fn write_to(&self, out: &mut impl Write) -> std::io::Result<()> {
+        let reason = match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            408 => "Request Timeout",
+            411 => "Length Required",
+            413 => "Payload Too Large",
+            422 => "Unprocessable Entity",
+            431 => "Request Header Fields Too Large",
+            503 => "Service Unavailable",
+            _ => "Internal Server Error",
+        };
+        write!(
+            out,
+            "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+            self.status,
+            reason,
+            self.content_type,
+            self.body.len()
+        )?;
+        for (name, value) in &self.headers {
+            write!(out, "{}: {}\r\n", name, value)?;
+        }
+        write!(out, "\r\n{}", self.body)?;
+        out.flush()
+    }
+}
+
+/// Decode `%XX` escapes and `+` in a query component.
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                match u8::from_str_radix(s.get(i + 1..i + 3).unwrap_or("-"), 16) {
+                    Ok(b) => {
+                        out.push(b);
+                        i += 2;
+                    }
+                    Err(_) => out.push(b'%'),
+                }
+            }
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &[u8] = include_bytes!("../tests/fixtures/composed-multiple.wasm");
+
+    fn request(raw: &[u8], max_body: usize) -> Result<Request, Response> {
+        read_request(&mut BufReader::new(raw), max_body)
+    }
+
+    fn post(path: &str, body: &[u8]) -> Request {
+        let mut raw = format!(
+            "POST {} HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+            path,
+            body.len()
+        )
+        .into_bytes();
+        raw.extend_from_slice(body);
+        request(&raw, usize::MAX).unwrap()
+    }
+
+    #[test]
+    fn test_read_request() {
+        let req = request(
+            b"POST /render?format=ascii&detail=all%2Dinterfaces HTTP/1.1\r\nHost: x\r\ncontent-length: 3\r\n\r\nabc",
+            1024,
+        )
+        .unwrap();
+        assert_eq!(req.method, "POST");
+        assert_eq!(req.path, "/render");
+        assert_eq!(req.param("format"), Some("ascii"));
+        assert_eq!(req.param("detail"), Some("all-interfaces"));
+        assert_eq!(req.body, b"abc");
+    }
+
+    #[test]
+    fn test_request_limits() {
+        let big = b"POST /parse HTTP/1.1\r\nContent-Length: 2048\r\n\r\n";
+        assert_eq!(request(big, 1024).unwrap_err().status, 413);
+
+        let chunked = b"POST /parse HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n";
+        assert_eq!(request(chunked, 1024).unwrap_err().status, 411);
+
+        let mut huge_head = b"GET /health HTTP/1.1\r\nX-Pad: ".to_vec();
+        huge_head.extend(std::iter::repeat_n(b'a', MAX_HEAD_BYTES));
+        huge_head.extend_from_slice(b"\r\n\r\n");
+        assert_eq!(request(&huge_head, 1024).unwrap_err().status, 431);
+    }
+
+    #[test]
+    fn test_parse_then_render_by_id() {
+        let cache = GraphCache::default();
+        let parsed = route(&post("/parse", FIXTURE), &cache);
+        assert_eq!(parsed.status, 200, "{}", parsed.body);
+        assert!(parsed.body.contains("\"nodes\""));
+        let (_, id) = &parsed.headers[0];
+
+        let get = request(
+            format!("GET /render?graph={}&format=mermaid HTTP/1.1\r\n\r\n", id).as_bytes(),
+            0,
+        )
+        .unwrap();
+        let rendered = route(&get, &cache);
+        assert_eq!(rendered.status, 200, "{}", rendered.body);
+        assert!(rendered.body.contains("graph LR\n"), "{}", rendered.body);
+
+        let direct = route(&post("/render?format=mermaid", FIXTURE), &cache);
+        assert_eq!(direct.body, rendered.body);
+    }
+
+    #[test]
+    fn test_error_responses() {
+        let cache = GraphCache::default();
+        assert_eq!(route(&post("/parse", b"not wasm"), &cache).status, 422);
+        assert_eq!(
+            route(&post("/render?format=svg", FIXTURE), &cache).status,
+            400
+        );
+        assert_eq!(route(&post("/health", b""), &cache).status, 405);
+        assert_eq!(route(&post("/nope", b""), &cache).status, 404);
+
+        let get = request(b"GET /render?graph=abc HTTP/1.1\r\n\r\n", 0).unwrap();
+        assert_eq!(route(&get, &cache).status, 404);
+    }
+
+    #[test]
+    fn test_serve_file_routes() {
+        let get = |target: &str| {
+            request(format!("GET {} HTTP/1.1\r\n\r\n", target).as_bytes(), 0).unwrap()
+        };
+        let load = || Ok(cviz::parse::component::parse_component(FIXTURE)?);
+        let page = live_page("a <b>");
+
+        let index = route_file(&get("/"), &page, &load);
+        assert_eq!(index.status, 200);
+        assert!(index.body.contains("<title>a &lt;b&gt;</title>"));
+
+        let rendered = route_file(&get("/render?direction=td"), &page, &load);
+        assert_eq!(rendered.status, 200, "{}", rendered.body);
+        assert!(rendered.body.contains("graph TD\n"), "{}", rendered.body);
+
+        let broken = || anyhow::bail!("bad magic number");
+        let failed = route_file(&get("/render"), &page, &broken);
+        assert_eq!(failed.status, 422);
+        assert_eq!(failed.body, "bad magic number\n");
+        assert_eq!(route_file(&post("/render", b""), &page, &load).status, 405);
+    }
+}